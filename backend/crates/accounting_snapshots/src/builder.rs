@@ -3,6 +3,7 @@
 //! This module provides the trait and implementation for building accounting
 //! snapshots from transactions using `pos_core_domain` business logic.
 
+use pos_core_domain::allocate_proportionally;
 use pos_core_models::{Transaction, TransactionStatus};
 use rust_decimal::Decimal;
 use tracing::{debug, warn};
@@ -65,21 +66,22 @@ impl SnapshotBuilder for DefaultSnapshotBuilder {
             "Building accounting snapshot"
         );
 
+        let line_totals: Vec<Decimal> = transaction
+            .items
+            .iter()
+            .map(|item| item.line_total.unwrap_or_else(|| item.calculate_line_total()))
+            .collect();
+
+        // Distribute tax proportionally based on line total, rounded to the
+        // cent with any remainder on the largest line so lines sum to the header
+        let line_taxes = allocate_proportionally(transaction.tax, &line_totals, 2);
+
         // Convert line items to snapshot lines
         let lines: Vec<SnapshotLine> = transaction
             .items
             .iter()
-            .map(|item| {
-                let line_total = item.line_total.unwrap_or_else(|| item.calculate_line_total());
-                
-                // Calculate tax amount for this line
-                // For now, we'll distribute tax proportionally based on line total
-                let tax_amount = if transaction.subtotal > Decimal::ZERO {
-                    (line_total / transaction.subtotal) * transaction.tax
-                } else {
-                    Decimal::ZERO
-                };
-
+            .zip(line_totals.iter().zip(line_taxes))
+            .map(|(item, (line_total, tax_amount))| {
                 SnapshotLine::new(
                     item.product_id.clone(),
                     // Use product_id as description for now
@@ -87,7 +89,7 @@ impl SnapshotBuilder for DefaultSnapshotBuilder {
                     item.product_id.clone(),
                     item.quantity,
                     item.unit_price,
-                    line_total,
+                    *line_total,
                     tax_amount,
                 )
             })
@@ -257,6 +259,28 @@ mod tests {
         assert!(diff < dec!(0.01), "Tax sum {} differs from transaction tax {} by {}", tax_sum, transaction.tax, diff);
     }
 
+    #[test]
+    fn test_snapshot_tax_distribution_rounds_to_cents() {
+        let mut transaction = Transaction::with_id(Uuid::new_v4());
+        for product in ["PROD-001", "PROD-002", "PROD-003"] {
+            transaction
+                .add_item(LineItem::new(product.to_string(), dec!(1), dec!(3.33)))
+                .unwrap();
+        }
+        transaction.subtotal = dec!(9.99);
+        transaction.tax = dec!(1.30);
+        transaction.discount_total = dec!(0.00);
+        transaction.total = dec!(11.29);
+        transaction.status = TransactionStatus::Finalized;
+        transaction.finalized_at = Some(Utc::now());
+
+        let snapshot = DefaultSnapshotBuilder::new().build_snapshot(&transaction).unwrap();
+
+        let tax_sum: Decimal = snapshot.lines.iter().map(|l| l.tax_amount).sum();
+        assert_eq!(tax_sum, dec!(1.30));
+        assert!(snapshot.lines.iter().all(|l| l.tax_amount.scale() <= 2));
+    }
+
     #[test]
     fn test_snapshot_payments_copied() {
         let transaction = create_test_transaction();
@@ -28,6 +28,10 @@ pub enum SnapshotError {
     #[error("Invalid transaction state: {0}")]
     InvalidTransactionState(String),
 
+    /// Snapshot falls inside a locked export batch's range
+    #[error("Accounting period is closed for {0}")]
+    PeriodClosed(String),
+
     /// Database error
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
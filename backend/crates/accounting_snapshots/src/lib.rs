@@ -17,7 +17,7 @@ pub mod repository;
 pub mod migration;
 
 // Re-export commonly used types
-pub use snapshot::{AccountingSnapshot, SnapshotLine, Payment};
+pub use snapshot::{AccountingSnapshot, SnapshotKind, SnapshotLine, SnapshotLineTax, Payment};
 pub use builder::SnapshotBuilder;
 pub use errors::{SnapshotError, SnapshotResult};
 pub use repository::SnapshotRepository;
@@ -210,7 +210,12 @@ impl MigrationJob {
             let snapshot_id = Uuid::parse_str(&snapshot_id_str)
                 .map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?;
             
-            // Delete snapshot lines
+            // Delete snapshot line taxes, then the lines
+            sqlx::query("DELETE FROM snapshot_line_taxes WHERE snapshot_line_id IN (SELECT id FROM snapshot_lines WHERE snapshot_id = ?)")
+                .bind(snapshot_id.to_string())
+                .execute(&self.pool)
+                .await?;
+
             sqlx::query("DELETE FROM snapshot_lines WHERE snapshot_id = ?")
                 .bind(snapshot_id.to_string())
                 .execute(&self.pool)
@@ -1,12 +1,15 @@
 //! Repository for persisting and retrieving accounting snapshots
 
 use chrono::{DateTime, Utc};
-use sqlx::Row;
+use sqlx::{Row, SqliteConnection};
 use uuid::Uuid;
 
 use pos_core_storage::DatabasePool;
 use crate::errors::{SnapshotError, SnapshotResult};
-use crate::snapshot::{AccountingSnapshot, Payment, SnapshotLine};
+use crate::snapshot::{AccountingSnapshot, Payment, SnapshotKind, SnapshotLine, SnapshotLineTax};
+
+/// Message of the trigger refusing snapshots in a locked export batch's range
+const PERIOD_CLOSED_MESSAGE: &str = "accounting period is closed";
 
 /// Repository for accounting snapshot database operations
 pub struct SnapshotRepository {
@@ -22,14 +25,36 @@ impl SnapshotRepository {
 
     /// Save a new accounting snapshot
     ///
+    /// The header, lines and payments are written in a single database
+    /// transaction.
+    ///
     /// # Errors
     ///
     /// Returns error if snapshot already exists or database operation fails
     pub async fn save(&self, snapshot: &AccountingSnapshot) -> SnapshotResult<()> {
+        let mut tx = self.pool.begin().await?;
+        Self::save_in_transaction(&mut tx, snapshot).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Save a new accounting snapshot on an existing connection
+    ///
+    /// Used when the snapshot must commit or roll back together with the
+    /// transaction it describes (e.g. checkout persisting the sale).
+    ///
+    /// # Errors
+    ///
+    /// Returns error if snapshot already exists, its period was closed by a
+    /// locked export batch, or database operation fails
+    pub async fn save_in_transaction(
+        conn: &mut SqliteConnection,
+        snapshot: &AccountingSnapshot,
+    ) -> SnapshotResult<()> {
         // Check if snapshot already exists
         let exists = sqlx::query("SELECT COUNT(*) as count FROM accounting_snapshots WHERE transaction_id = ?")
             .bind(snapshot.transaction_id.to_string())
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await?;
         
         let count: i64 = exists.try_get("count")?;
@@ -37,10 +62,18 @@ impl SnapshotRepository {
             return Err(SnapshotError::AlreadyExists(snapshot.transaction_id.to_string()));
         }
 
+        let (original_transaction_id, memo) = match &snapshot.kind {
+            SnapshotKind::Sale => (None, None),
+            SnapshotKind::CreditMemo { original_transaction_id, reason } => {
+                (Some(original_transaction_id.to_string()), reason.clone())
+            }
+        };
+
         // Insert snapshot
         sqlx::query(
-            "INSERT INTO accounting_snapshots (id, transaction_id, created_at, finalized_at, subtotal, tax, discount, total)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO accounting_snapshots (id, transaction_id, created_at, finalized_at, subtotal, tax, discount, total,
+             kind, original_transaction_id, memo)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(snapshot.id.to_string())
         .bind(snapshot.transaction_id.to_string())
@@ -50,16 +83,28 @@ impl SnapshotRepository {
         .bind(snapshot.tax.to_string())
         .bind(snapshot.discount.to_string())
         .bind(snapshot.total.to_string())
-        .execute(&self.pool)
-        .await?;
+        .bind(snapshot.kind.as_str())
+        .bind(original_transaction_id)
+        .bind(memo)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| match &e {
+            // Raised by the period-close trigger of locked export batches
+            sqlx::Error::Database(db) if db.message().contains(PERIOD_CLOSED_MESSAGE) => {
+                SnapshotError::PeriodClosed(snapshot.finalized_at.to_rfc3339())
+            }
+            _ => SnapshotError::Database(e),
+        })?;
 
-        // Insert lines
+        // Insert lines and their tax breakdown
         for line in &snapshot.lines {
+            let line_id = Uuid::new_v4().to_string();
             sqlx::query(
-                "INSERT INTO snapshot_lines (id, snapshot_id, product_id, description, quantity, unit_price, line_total, tax_amount)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+                "INSERT INTO snapshot_lines (id, snapshot_id, product_id, description, quantity, unit_price, line_total, tax_amount,
+                 category, unit_cost)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
             )
-            .bind(Uuid::new_v4().to_string())
+            .bind(&line_id)
             .bind(snapshot.id.to_string())
             .bind(&line.product_id)
             .bind(&line.description)
@@ -67,8 +112,26 @@ impl SnapshotRepository {
             .bind(line.unit_price.to_string())
             .bind(line.line_total.to_string())
             .bind(line.tax_amount.to_string())
-            .execute(&self.pool)
+            .bind(&line.category)
+            .bind(line.unit_cost.map(|cost| cost.to_string()))
+            .execute(&mut *conn)
             .await?;
+
+            for tax in &line.taxes {
+                sqlx::query(
+                    "INSERT INTO snapshot_line_taxes (id, snapshot_line_id, authority, label, rate, taxable_amount, tax_amount)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)"
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(&line_id)
+                .bind(&tax.authority)
+                .bind(&tax.label)
+                .bind(tax.rate.to_string())
+                .bind(tax.taxable_amount.to_string())
+                .bind(tax.tax_amount.to_string())
+                .execute(&mut *conn)
+                .await?;
+            }
         }
 
         // Insert payments
@@ -81,7 +144,7 @@ impl SnapshotRepository {
             .bind(snapshot.id.to_string())
             .bind(&payment.method)
             .bind(payment.amount.to_string())
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await?;
         }
 
@@ -144,13 +207,26 @@ impl SnapshotRepository {
         let discount_str: String = row.try_get("discount")?;
         let total_str: String = row.try_get("total")?;
 
+        let kind_str: String = row.try_get("kind")?;
+        let kind = match kind_str.as_str() {
+            "credit_memo" => {
+                let original_str: String = row.try_get("original_transaction_id")?;
+                SnapshotKind::CreditMemo {
+                    original_transaction_id: Uuid::parse_str(&original_str)
+                        .map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?,
+                    reason: row.try_get("memo")?,
+                }
+            }
+            _ => SnapshotKind::Sale,
+        };
+
         // Load lines
         let lines = self.load_lines(id).await?;
 
         // Load payments
         let payments = self.load_payments(id).await?;
 
-        Ok(AccountingSnapshot::with_id(
+        let mut snapshot = AccountingSnapshot::with_id(
             id,
             transaction_id,
             finalized_at,
@@ -160,23 +236,32 @@ impl SnapshotRepository {
             total_str.parse().map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?,
             payments,
             lines,
-        ))
+        );
+        snapshot.kind = kind;
+
+        Ok(snapshot)
     }
 
     async fn load_lines(&self, snapshot_id: Uuid) -> SnapshotResult<Vec<SnapshotLine>> {
-        let rows = sqlx::query("SELECT * FROM snapshot_lines WHERE snapshot_id = ?")
+        let rows = sqlx::query("SELECT * FROM snapshot_lines WHERE snapshot_id = ? ORDER BY rowid")
             .bind(snapshot_id.to_string())
             .fetch_all(&self.pool)
             .await?;
 
         let mut lines = Vec::new();
         for row in rows {
+            let line_id: String = row.try_get("id")?;
             let product_id: String = row.try_get("product_id")?;
             let description: String = row.try_get("description")?;
             let quantity_str: String = row.try_get("quantity")?;
             let unit_price_str: String = row.try_get("unit_price")?;
             let line_total_str: String = row.try_get("line_total")?;
             let tax_amount_str: String = row.try_get("tax_amount")?;
+            let category: Option<String> = row.try_get("category")?;
+            let unit_cost_str: Option<String> = row.try_get("unit_cost")?;
+            let unit_cost = unit_cost_str
+                .map(|cost| cost.parse().map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e)))))
+                .transpose()?;
 
             lines.push(SnapshotLine::new(
                 product_id,
@@ -185,12 +270,38 @@ impl SnapshotRepository {
                 unit_price_str.parse().map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?,
                 line_total_str.parse().map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?,
                 tax_amount_str.parse().map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?,
-            ));
+            )
+            .with_taxes(self.load_line_taxes(&line_id).await?)
+            .with_costing(category, unit_cost));
         }
 
         Ok(lines)
     }
 
+    async fn load_line_taxes(&self, snapshot_line_id: &str) -> SnapshotResult<Vec<SnapshotLineTax>> {
+        let rows = sqlx::query("SELECT * FROM snapshot_line_taxes WHERE snapshot_line_id = ? ORDER BY rowid")
+            .bind(snapshot_line_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut taxes = Vec::new();
+        for row in rows {
+            let rate_str: String = row.try_get("rate")?;
+            let taxable_amount_str: String = row.try_get("taxable_amount")?;
+            let tax_amount_str: String = row.try_get("tax_amount")?;
+
+            taxes.push(SnapshotLineTax {
+                authority: row.try_get("authority")?,
+                label: row.try_get("label")?,
+                rate: rate_str.parse().map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?,
+                taxable_amount: taxable_amount_str.parse().map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?,
+                tax_amount: tax_amount_str.parse().map_err(|e| SnapshotError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            });
+        }
+
+        Ok(taxes)
+    }
+
     async fn load_payments(&self, snapshot_id: Uuid) -> SnapshotResult<Vec<Payment>> {
         let rows = sqlx::query("SELECT * FROM snapshot_payments WHERE snapshot_id = ?")
             .bind(snapshot_id.to_string())
@@ -20,6 +20,21 @@ pub struct Payment {
     pub amount: Decimal,
 }
 
+/// Tax charged by one authority on a snapshot line
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotLineTax {
+    /// Tax authority / rate code (e.g. "GST", "PST", "STATE")
+    pub authority: String,
+    /// Display label of the rate
+    pub label: String,
+    /// Rate in percent
+    pub rate: Decimal,
+    /// Amount the rate was charged on
+    pub taxable_amount: Decimal,
+    /// Tax charged
+    pub tax_amount: Decimal,
+}
+
 /// Snapshot line item
 ///
 /// Represents a single line item in an accounting snapshot with all computed values.
@@ -37,6 +52,15 @@ pub struct SnapshotLine {
     pub line_total: Decimal,
     /// Tax amount for this line
     pub tax_amount: Decimal,
+    /// Per-authority breakdown of `tax_amount` (empty when not recorded)
+    #[serde(default)]
+    pub taxes: Vec<SnapshotLineTax>,
+    /// Product category at time of sale (drives the revenue account)
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Unit cost at time of sale, when the goods move inventory
+    #[serde(default)]
+    pub unit_cost: Option<Decimal>,
 }
 
 impl SnapshotLine {
@@ -57,6 +81,60 @@ impl SnapshotLine {
             unit_price,
             line_total,
             tax_amount,
+            taxes: Vec::new(),
+            category: None,
+            unit_cost: None,
+        }
+    }
+
+    /// Attach the per-authority tax breakdown
+    #[must_use]
+    pub fn with_taxes(mut self, taxes: Vec<SnapshotLineTax>) -> Self {
+        self.taxes = taxes;
+        self
+    }
+
+    /// Attach the product category and unit cost recorded at time of sale
+    #[must_use]
+    pub fn with_costing(mut self, category: Option<String>, unit_cost: Option<Decimal>) -> Self {
+        self.category = category;
+        self.unit_cost = unit_cost;
+        self
+    }
+
+    /// Cost of goods of the line (quantity × `unit_cost`, rounded to cents)
+    #[must_use]
+    pub fn cost_of_goods(&self) -> Option<Decimal> {
+        self.unit_cost.map(|cost| (cost * self.quantity).round_dp(2))
+    }
+}
+
+/// What an accounting snapshot records
+///
+/// Sales are exported as sales receipts / invoices; credit memos record a
+/// return against an earlier sale and carry all amounts as positive values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SnapshotKind {
+    /// A completed sale
+    #[default]
+    Sale,
+    /// A return (refund) against an earlier sale
+    CreditMemo {
+        /// Sale the goods were returned from
+        original_transaction_id: Uuid,
+        /// Reason given for the return
+        reason: Option<String>,
+    },
+}
+
+impl SnapshotKind {
+    /// Value stored in `accounting_snapshots.kind`
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sale => "sale",
+            Self::CreditMemo { .. } => "credit_memo",
         }
     }
 }
@@ -88,6 +166,9 @@ pub struct AccountingSnapshot {
     pub payments: Vec<Payment>,
     /// Line items with computed values
     pub lines: Vec<SnapshotLine>,
+    /// Sale or credit memo
+    #[serde(default)]
+    pub kind: SnapshotKind,
 }
 
 impl AccountingSnapshot {
@@ -114,6 +195,7 @@ impl AccountingSnapshot {
             total,
             payments,
             lines,
+            kind: SnapshotKind::Sale,
         }
     }
 
@@ -141,16 +223,41 @@ impl AccountingSnapshot {
             total,
             payments,
             lines,
+            kind: SnapshotKind::Sale,
         }
     }
 
+    /// Mark the snapshot as a credit memo for a return against `original_transaction_id`
+    #[must_use]
+    pub fn as_credit_memo(mut self, original_transaction_id: Uuid, reason: Option<String>) -> Self {
+        self.kind = SnapshotKind::CreditMemo {
+            original_transaction_id,
+            reason,
+        };
+        self
+    }
+
+    /// Whether the snapshot records a return rather than a sale
+    #[must_use]
+    pub const fn is_credit_memo(&self) -> bool {
+        matches!(self.kind, SnapshotKind::CreditMemo { .. })
+    }
+
     /// Verify that the snapshot's totals are internally consistent
     ///
     /// Checks that:
     /// - Line totals sum to subtotal
     /// - subtotal + tax - discount = total
+    /// - Each line's tax breakdown, when recorded, sums to its tax amount
     #[must_use] 
     pub fn verify_consistency(&self) -> bool {
+        if self.lines.iter().any(|line| {
+            !line.taxes.is_empty()
+                && line.taxes.iter().map(|tax| tax.tax_amount).sum::<Decimal>() != line.tax_amount
+        }) {
+            return false;
+        }
+
         // Sum line totals
         let line_total_sum: Decimal = self.lines.iter().map(|line| line.line_total).sum();
         
@@ -327,6 +434,86 @@ mod tests {
         assert!(!snapshot.verify_consistency());
     }
 
+    #[test]
+    fn test_snapshot_consistency_checks_line_tax_breakdown() {
+        let tax = |authority: &str, amount| SnapshotLineTax {
+            authority: authority.to_string(),
+            label: authority.to_string(),
+            rate: dec!(5),
+            taxable_amount: dec!(20.00),
+            tax_amount: amount,
+        };
+        let line = SnapshotLine::new(
+            "PROD-001".to_string(),
+            "Widget".to_string(),
+            dec!(2.0),
+            dec!(10.00),
+            dec!(20.00),
+            dec!(2.40),
+        );
+
+        let consistent = AccountingSnapshot::new(
+            Uuid::new_v4(),
+            Utc::now(),
+            dec!(20.00),
+            dec!(2.40),
+            dec!(0.00),
+            dec!(22.40),
+            vec![],
+            vec![line.clone().with_taxes(vec![tax("GST", dec!(1.00)), tax("PST", dec!(1.40))])],
+        );
+        assert!(consistent.verify_consistency());
+
+        let mut inconsistent = consistent;
+        inconsistent.lines = vec![line.with_taxes(vec![tax("GST", dec!(1.00))])];
+        assert!(!inconsistent.verify_consistency());
+    }
+
+    #[test]
+    fn test_credit_memo_kind() {
+        let original = Uuid::new_v4();
+        let snapshot = AccountingSnapshot::new(
+            Uuid::new_v4(),
+            Utc::now(),
+            dec!(20.00),
+            dec!(1.60),
+            dec!(0.00),
+            dec!(21.60),
+            vec![],
+            vec![],
+        );
+        assert!(!snapshot.is_credit_memo());
+        assert_eq!(snapshot.kind.as_str(), "sale");
+
+        let memo = snapshot.as_credit_memo(original, Some("Damaged".to_string()));
+        assert!(memo.is_credit_memo());
+        assert_eq!(memo.kind.as_str(), "credit_memo");
+        assert_eq!(
+            memo.kind,
+            SnapshotKind::CreditMemo {
+                original_transaction_id: original,
+                reason: Some("Damaged".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_line_cost_of_goods() {
+        let line = SnapshotLine::new(
+            "PROD-001".to_string(),
+            "Widget".to_string(),
+            dec!(3),
+            dec!(10.00),
+            dec!(30.00),
+            dec!(0.00),
+        );
+        assert_eq!(line.cost_of_goods(), None);
+
+        let line = line.with_costing(Some("Parts".to_string()), Some(dec!(4.335)));
+        assert_eq!(line.category.as_deref(), Some("Parts"));
+        assert_eq!(line.cost_of_goods(), Some(dec!(13.00)));
+    }
+
     #[test]
     fn test_total_paid() {
         let payments = vec![
@@ -26,7 +26,10 @@ async fn setup_test_db() -> SqlitePool {
             subtotal TEXT NOT NULL,
             tax TEXT NOT NULL,
             discount TEXT NOT NULL,
-            total TEXT NOT NULL
+            total TEXT NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'sale',
+            original_transaction_id TEXT,
+            memo TEXT
         )
         "#,
     )
@@ -46,6 +49,8 @@ async fn setup_test_db() -> SqlitePool {
             unit_price TEXT NOT NULL,
             line_total TEXT NOT NULL,
             tax_amount TEXT NOT NULL,
+            category TEXT,
+            unit_cost TEXT,
             FOREIGN KEY (snapshot_id) REFERENCES accounting_snapshots(id)
         )
         "#,
@@ -1,7 +1,7 @@
 // Property-based tests for accounting snapshots
 // Feature: split-build-system
 
-use accounting_snapshots::{AccountingSnapshot, SnapshotLine, Payment};
+use accounting_snapshots::{AccountingSnapshot, SnapshotKind, SnapshotLine, Payment};
 use chrono::Utc;
 use proptest::prelude::*;
 use rust_decimal::Decimal;
@@ -40,6 +40,9 @@ prop_compose! {
             unit_price,
             line_total,
             tax_amount,
+            taxes: Vec::new(),
+            category: None,
+            unit_cost: None,
         }
     }
 }
@@ -67,6 +70,7 @@ prop_compose! {
             total,
             payments,
             lines,
+            kind: SnapshotKind::Sale,
         }
     }
 }
@@ -131,6 +135,7 @@ proptest! {
                 amount: expected_total,
             }],
             lines: vec![],
+            kind: SnapshotKind::Sale,
         };
         
         // Verify total is consistent
@@ -157,6 +162,7 @@ proptest! {
             total,
             payments: payments.clone(),
             lines: vec![],
+            kind: SnapshotKind::Sale,
         };
         
         // Verify payments are preserved
@@ -24,6 +24,17 @@ pub enum ExportError {
     /// Missing required field
     #[error("Missing required field: {0}")]
     MissingField(String),
+
+    /// Journal postings whose debits and credits differ
+    #[error("Unbalanced journal entry for {reference}: debits {debits}, credits {credits}")]
+    Unbalanced {
+        /// Transaction or journal entry that does not balance
+        reference: String,
+        /// Total debits
+        debits: rust_decimal::Decimal,
+        /// Total credits
+        credits: rust_decimal::Decimal,
+    },
 }
 
 /// Export result type
@@ -1,6 +1,6 @@
 //! CSV exporter trait
 
-use accounting_snapshots::AccountingSnapshot;
+use accounting_snapshots::{AccountingSnapshot, SnapshotKind};
 use crate::errors::ExportResult;
 
 /// CSV exporter trait
@@ -14,3 +14,33 @@ pub trait CsvExporter {
     /// Export credit memos to CSV
     fn export_credit_memos(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String>;
 }
+
+/// Memo of a credit memo: the return reason and the sale it was returned from
+pub(crate) fn return_memo(snapshot: &AccountingSnapshot) -> String {
+    match &snapshot.kind {
+        SnapshotKind::CreditMemo { original_transaction_id, reason } => format!(
+            "Return of {original_transaction_id}{}",
+            reason.as_deref().map(|r| format!(" - {r}")).unwrap_or_default()
+        ),
+        SnapshotKind::Sale => "Return".to_string(),
+    }
+}
+
+/// Document number that fits in `max_len` characters
+///
+/// Accounting packages limit reference fields (11 characters in `QuickBooks`
+/// Desktop, 30 in Sage 50), so the transaction ID is written without hyphens
+/// and cut to length.
+pub(crate) fn document_number(snapshot: &AccountingSnapshot, max_len: usize) -> String {
+    let mut number = snapshot.transaction_id.simple().to_string();
+    number.truncate(max_len);
+    number
+}
+
+/// Format decimal with 2 decimal places, never as negative zero
+pub(crate) fn format_amount(value: rust_decimal::Decimal) -> String {
+    if value.is_zero() {
+        return "0.00".to_string();
+    }
+    format!("{:.2}", value.round_dp(2))
+}
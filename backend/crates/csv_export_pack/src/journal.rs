@@ -0,0 +1,508 @@
+//! Journal-entry (general ledger) exporter
+//!
+//! Instead of one document per sale, the bookkeeper posts one summarized
+//! journal entry per business day. Every snapshot of the day is broken down
+//! into postings through a [`ChartOfAccounts`]:
+//!
+//! - tenders debit their clearing account (unpaid balances debit receivables)
+//! - discounts debit the discount account
+//! - lines credit the revenue account of their product category
+//! - tax credits the payable account of each tax authority
+//! - lines with a unit cost debit cost of goods sold and credit inventory
+//!
+//! Credit memos post the same accounts with the opposite sign. Postings with
+//! the same account and memo are netted, so an entry has one line per
+//! account role. A batch is refused as a whole if any snapshot, or any day,
+//! does not balance.
+
+use std::collections::BTreeMap;
+use chrono::{DateTime, FixedOffset, NaiveDate, Offset, Utc};
+use csv::Writer;
+use accounting_snapshots::AccountingSnapshot;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::exporter::format_amount;
+use crate::errors::{ExportError, ExportResult};
+use crate::packaging::ON_ACCOUNT_METHOD;
+
+/// Accounts the journal entries post to
+///
+/// The per-category, per-authority and per-tender maps override the default
+/// account of their kind; anything not listed posts to the default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChartOfAccounts {
+    /// Revenue account of lines without a mapped category
+    pub revenue: String,
+    /// Revenue account by product category
+    pub revenue_by_category: BTreeMap<String, String>,
+    /// Account of invoice-level discounts
+    pub discounts: String,
+    /// Tax payable account of authorities without a mapping
+    pub tax_payable: String,
+    /// Tax payable account by tax authority (e.g. "GST", "PST")
+    pub tax_payable_by_authority: BTreeMap<String, String>,
+    /// Clearing account of tenders without a mapping
+    pub tender_clearing: String,
+    /// Clearing account by payment method (e.g. "cash", "card")
+    pub tender_clearing_by_method: BTreeMap<String, String>,
+    /// Account of sales charged on account and unpaid balances
+    pub accounts_receivable: String,
+    /// Cost of goods sold account
+    pub cost_of_goods_sold: String,
+    /// Inventory asset account
+    pub inventory: String,
+}
+
+impl ChartOfAccounts {
+    /// Revenue account of a product category
+    #[must_use]
+    pub fn revenue_account(&self, category: Option<&str>) -> &str {
+        category
+            .and_then(|category| self.revenue_by_category.get(category))
+            .unwrap_or(&self.revenue)
+    }
+
+    /// Tax payable account of a tax authority
+    #[must_use]
+    pub fn tax_account(&self, authority: Option<&str>) -> &str {
+        authority
+            .and_then(|authority| self.tax_payable_by_authority.get(authority))
+            .unwrap_or(&self.tax_payable)
+    }
+
+    /// Clearing account of a payment method
+    #[must_use]
+    pub fn tender_account(&self, method: &str) -> &str {
+        match self.tender_clearing_by_method.get(method) {
+            Some(account) => account,
+            None if method == ON_ACCOUNT_METHOD => &self.accounts_receivable,
+            None => &self.tender_clearing,
+        }
+    }
+}
+
+impl Default for ChartOfAccounts {
+    fn default() -> Self {
+        Self {
+            revenue: "Sales".to_string(),
+            revenue_by_category: BTreeMap::new(),
+            discounts: "Discounts Given".to_string(),
+            tax_payable: "Sales Tax Payable".to_string(),
+            tax_payable_by_authority: BTreeMap::new(),
+            tender_clearing: "Undeposited Funds".to_string(),
+            tender_clearing_by_method: BTreeMap::new(),
+            accounts_receivable: "Accounts Receivable".to_string(),
+            cost_of_goods_sold: "Cost of Goods Sold".to_string(),
+            inventory: "Inventory Asset".to_string(),
+        }
+    }
+}
+
+/// Chart of accounts and business day of a tenant's journal entries
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JournalSettings {
+    /// Accounts the entries post to
+    pub accounts: ChartOfAccounts,
+    /// Offset of the store's local time from UTC, in minutes; decides which
+    /// day a late-evening sale belongs to
+    pub utc_offset_minutes: i32,
+}
+
+/// One line of a journal entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalLine {
+    /// Account posted to
+    pub account: String,
+    /// Debit amount (zero for credit lines)
+    pub debit: Decimal,
+    /// Credit amount (zero for debit lines)
+    pub credit: Decimal,
+    /// What the line summarizes
+    pub memo: String,
+}
+
+/// Summarized journal entry of one business day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// Business day
+    pub date: NaiveDate,
+    /// Entry number (`JE-YYYYMMDD`)
+    pub number: String,
+    /// Number of snapshots summarized
+    pub transaction_count: usize,
+    /// Debit and credit lines
+    pub lines: Vec<JournalLine>,
+}
+
+impl JournalEntry {
+    /// Sum of the debit lines
+    #[must_use]
+    pub fn total_debits(&self) -> Decimal {
+        self.lines.iter().map(|line| line.debit).sum()
+    }
+
+    /// Sum of the credit lines
+    #[must_use]
+    pub fn total_credits(&self) -> Decimal {
+        self.lines.iter().map(|line| line.credit).sum()
+    }
+
+    /// Whether debits equal credits
+    #[must_use]
+    pub fn is_balanced(&self) -> bool {
+        self.total_debits() == self.total_credits()
+    }
+}
+
+/// Net postings keyed by (account, memo); positive amounts are debits
+type Postings = BTreeMap<(String, String), Decimal>;
+
+/// Journal-entry exporter
+pub struct JournalExporter {
+    settings: JournalSettings,
+}
+
+impl JournalExporter {
+    /// Create a journal exporter with the default accounts and UTC days
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_settings(JournalSettings::default())
+    }
+
+    /// Create a journal exporter with the given accounts and business day
+    #[must_use]
+    pub const fn with_settings(settings: JournalSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Business day a snapshot was finalized on
+    fn business_day(&self, finalized_at: &DateTime<Utc>) -> NaiveDate {
+        let offset = FixedOffset::east_opt(self.settings.utc_offset_minutes * 60)
+            .unwrap_or_else(|| Utc.fix());
+        finalized_at.with_timezone(&offset).date_naive()
+    }
+
+    /// Add the postings of one snapshot
+    fn post_snapshot(&self, postings: &mut Postings, snapshot: &AccountingSnapshot) {
+        let accounts = &self.settings.accounts;
+        let sign = if snapshot.is_credit_memo() { Decimal::NEGATIVE_ONE } else { Decimal::ONE };
+        let mut post = |account: &str, memo: String, amount: Decimal| {
+            *postings.entry((account.to_string(), memo)).or_default() += sign * amount;
+        };
+
+        for payment in &snapshot.payments {
+            post(accounts.tender_account(&payment.method), format!("Tender - {}", payment.method), payment.amount);
+        }
+        let unpaid = snapshot.total - snapshot.total_paid();
+        if unpaid > Decimal::ZERO {
+            post(&accounts.accounts_receivable, "Unpaid balance".to_string(), unpaid);
+        }
+
+        post(&accounts.discounts, "Discounts".to_string(), snapshot.discount);
+
+        let mut line_tax = Decimal::ZERO;
+        for line in &snapshot.lines {
+            let category = line.category.as_deref();
+            post(
+                accounts.revenue_account(category),
+                format!("Sales - {}", category.unwrap_or("Uncategorized")),
+                -line.line_total,
+            );
+
+            if line.taxes.is_empty() {
+                post(accounts.tax_account(None), "Sales tax".to_string(), -line.tax_amount);
+            }
+            for tax in &line.taxes {
+                post(
+                    accounts.tax_account(Some(&tax.authority)),
+                    format!("Sales tax - {}", tax.authority),
+                    -tax.tax_amount,
+                );
+            }
+            line_tax += line.tax_amount;
+
+            if let Some(cost) = line.cost_of_goods() {
+                post(&accounts.cost_of_goods_sold, "Cost of goods sold".to_string(), cost);
+                post(&accounts.inventory, "Inventory".to_string(), -cost);
+            }
+        }
+
+        // Tax rounded per invoice can differ from the sum of the lines by a cent
+        post(accounts.tax_account(None), "Sales tax".to_string(), line_tax - snapshot.tax);
+    }
+
+    /// Summarize snapshots into one journal entry per business day
+    ///
+    /// Entries are returned in date order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::Unbalanced` when the postings of a snapshot or
+    /// of a day do not balance; no entries are returned in that case.
+    pub fn daily_entries(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<Vec<JournalEntry>> {
+        let mut days: BTreeMap<NaiveDate, (usize, Postings)> = BTreeMap::new();
+
+        for snapshot in snapshots {
+            let mut postings = Postings::new();
+            self.post_snapshot(&mut postings, snapshot);
+
+            let (debits, credits) = split_totals(postings.values().copied());
+            if debits != credits {
+                return Err(ExportError::Unbalanced {
+                    reference: format!("transaction {}", snapshot.transaction_id),
+                    debits,
+                    credits,
+                });
+            }
+
+            let (count, day) = days.entry(self.business_day(&snapshot.finalized_at)).or_default();
+            *count += 1;
+            for (key, amount) in postings {
+                *day.entry(key).or_default() += amount;
+            }
+        }
+
+        let mut entries = Vec::with_capacity(days.len());
+        for (date, (transaction_count, postings)) in days {
+            let lines: Vec<JournalLine> = postings
+                .into_iter()
+                .map(|(key, amount)| (key, amount.round_dp(2)))
+                .filter(|(_, amount)| !amount.is_zero())
+                .map(|((account, memo), amount)| JournalLine {
+                    account,
+                    debit: amount.max(Decimal::ZERO),
+                    credit: (-amount).max(Decimal::ZERO),
+                    memo,
+                })
+                .collect();
+
+            let entry = JournalEntry {
+                date,
+                number: format!("JE-{}", date.format("%Y%m%d")),
+                transaction_count,
+                lines,
+            };
+            if !entry.is_balanced() {
+                return Err(ExportError::Unbalanced {
+                    debits: entry.total_debits(),
+                    credits: entry.total_credits(),
+                    reference: entry.number,
+                });
+            }
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Export daily journal entries to CSV
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::Unbalanced` when an entry does not balance, or
+    /// an error if the CSV cannot be written.
+    pub fn export(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        let entries = self.daily_entries(snapshots)?;
+        let mut wtr = Writer::from_writer(vec![]);
+
+        wtr.write_record(["JournalNo", "JournalDate", "Account", "Debit", "Credit", "Memo"])?;
+
+        for entry in &entries {
+            let date = entry.date.format("%Y-%m-%d").to_string();
+            for line in &entry.lines {
+                let (debit, credit) = if line.debit.is_zero() {
+                    (String::new(), format_amount(line.credit))
+                } else {
+                    (format_amount(line.debit), String::new())
+                };
+                wtr.write_record([&entry.number, &date, &line.account, &debit, &credit, &line.memo])?;
+            }
+        }
+
+        let data = wtr.into_inner()
+            .map_err(|e| ExportError::IoError(e.into_error()))?;
+
+        String::from_utf8(data)
+            .map_err(|e| ExportError::InvalidData(e.to_string()))
+    }
+}
+
+impl Default for JournalExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Total debits and credits of signed postings
+fn split_totals(amounts: impl Iterator<Item = Decimal>) -> (Decimal, Decimal) {
+    amounts.fold((Decimal::ZERO, Decimal::ZERO), |(debits, credits), amount| {
+        if amount > Decimal::ZERO {
+            (debits + amount, credits)
+        } else {
+            (debits, credits - amount)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accounting_snapshots::{Payment, SnapshotLine, SnapshotLineTax};
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn gst(amount: Decimal) -> SnapshotLineTax {
+        SnapshotLineTax {
+            authority: "GST".to_string(),
+            label: "GST 5%".to_string(),
+            rate: dec!(5),
+            taxable_amount: amount * dec!(20),
+            tax_amount: amount,
+        }
+    }
+
+    /// Sale of a part (cost 6.00) and a service, paid cash and card
+    fn create_test_snapshot(hour: u32) -> AccountingSnapshot {
+        AccountingSnapshot::new(
+            Uuid::new_v4(),
+            Utc.with_ymd_and_hms(2026, 3, 2, hour, 0, 0).unwrap(),
+            dec!(30.00),
+            dec!(1.40),
+            dec!(2.00),
+            dec!(29.40),
+            vec![
+                Payment { method: "cash".to_string(), amount: dec!(9.40) },
+                Payment { method: "card".to_string(), amount: dec!(20.00) },
+            ],
+            vec![
+                SnapshotLine::new(
+                    "PART-1".to_string(),
+                    "Part".to_string(),
+                    dec!(2),
+                    dec!(5.00),
+                    dec!(10.00),
+                    dec!(0.40),
+                )
+                .with_taxes(vec![gst(dec!(0.40))])
+                .with_costing(Some("Parts".to_string()), Some(dec!(3.00))),
+                SnapshotLine::new(
+                    "SVC-1".to_string(),
+                    "Labour".to_string(),
+                    dec!(1),
+                    dec!(20.00),
+                    dec!(20.00),
+                    dec!(1.00),
+                )
+                .with_taxes(vec![gst(dec!(1.00))])
+                .with_costing(Some("Service".to_string()), None),
+            ],
+        )
+    }
+
+    fn accounts() -> ChartOfAccounts {
+        ChartOfAccounts {
+            revenue_by_category: BTreeMap::from([("Service".to_string(), "4100 Labour".to_string())]),
+            tax_payable_by_authority: BTreeMap::from([("GST".to_string(), "2200 GST Payable".to_string())]),
+            tender_clearing_by_method: BTreeMap::from([("cash".to_string(), "1050 Cash Clearing".to_string())]),
+            ..ChartOfAccounts::default()
+        }
+    }
+
+    fn line<'a>(entry: &'a JournalEntry, memo: &str) -> &'a JournalLine {
+        entry.lines.iter().find(|line| line.memo == memo).unwrap()
+    }
+
+    #[test]
+    fn test_account_mapping_falls_back_to_defaults() {
+        let accounts = accounts();
+
+        assert_eq!(accounts.revenue_account(Some("Service")), "4100 Labour");
+        assert_eq!(accounts.revenue_account(Some("Parts")), "Sales");
+        assert_eq!(accounts.revenue_account(None), "Sales");
+        assert_eq!(accounts.tax_account(Some("PST")), "Sales Tax Payable");
+        assert_eq!(accounts.tender_account("cash"), "1050 Cash Clearing");
+        assert_eq!(accounts.tender_account("card"), "Undeposited Funds");
+        assert_eq!(accounts.tender_account(ON_ACCOUNT_METHOD), "Accounts Receivable");
+    }
+
+    #[test]
+    fn test_one_balanced_entry_per_day() {
+        let exporter = JournalExporter::with_settings(JournalSettings {
+            accounts: accounts(),
+            utc_offset_minutes: 0,
+        });
+        let snapshots = [
+            create_test_snapshot(10),
+            create_test_snapshot(15),
+            create_test_snapshot(16).as_credit_memo(Uuid::new_v4(), None),
+        ];
+
+        let entries = exporter.daily_entries(&snapshots).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        let entry = &entries[0];
+        assert_eq!(entry.number, "JE-20260302");
+        assert_eq!(entry.transaction_count, 3);
+        assert!(entry.is_balanced());
+        assert_eq!(entry.total_debits(), dec!(37.40));
+
+        // Two sales less one return nets to one sale
+        assert_eq!(line(entry, "Tender - cash").debit, dec!(9.40));
+        assert_eq!(line(entry, "Tender - cash").account, "1050 Cash Clearing");
+        assert_eq!(line(entry, "Sales - Service").credit, dec!(20.00));
+        assert_eq!(line(entry, "Sales - Service").account, "4100 Labour");
+        assert_eq!(line(entry, "Sales tax - GST").credit, dec!(1.40));
+        assert_eq!(line(entry, "Discounts").debit, dec!(2.00));
+        assert_eq!(line(entry, "Cost of goods sold").debit, dec!(6.00));
+        assert_eq!(line(entry, "Inventory").credit, dec!(6.00));
+    }
+
+    #[test]
+    fn test_business_day_follows_utc_offset() {
+        let exporter = JournalExporter::with_settings(JournalSettings {
+            accounts: ChartOfAccounts::default(),
+            utc_offset_minutes: -8 * 60,
+        });
+
+        // 04:00 UTC is the evening before in UTC-8
+        let entries = exporter
+            .daily_entries(&[create_test_snapshot(4), create_test_snapshot(10)])
+            .unwrap();
+        let dates: Vec<String> = entries.iter().map(|entry| entry.number.clone()).collect();
+        assert_eq!(dates, vec!["JE-20260301", "JE-20260302"]);
+    }
+
+    #[test]
+    fn test_unpaid_balance_posts_to_receivables() {
+        let mut snapshot = create_test_snapshot(10);
+        snapshot.payments.truncate(1);
+
+        let entries = JournalExporter::new().daily_entries(&[snapshot]).unwrap();
+        let unpaid = line(&entries[0], "Unpaid balance");
+        assert_eq!((unpaid.account.as_str(), unpaid.debit), ("Accounts Receivable", dec!(20.00)));
+    }
+
+    #[test]
+    fn test_unbalanced_batch_is_refused() {
+        let mut overpaid = create_test_snapshot(10);
+        overpaid.payments[1].amount = dec!(25.00);
+
+        let result = JournalExporter::new().export(&[create_test_snapshot(9), overpaid]);
+        assert!(matches!(
+            result,
+            Err(ExportError::Unbalanced { debits, credits, .. }) if debits == dec!(42.40) && credits == dec!(37.40)
+        ));
+    }
+
+    #[test]
+    fn test_export_writes_debit_and_credit_columns() {
+        let csv = JournalExporter::new().export(&[create_test_snapshot(10)]).unwrap();
+        let mut rows = csv.lines();
+
+        assert_eq!(rows.next(), Some("JournalNo,JournalDate,Account,Debit,Credit,Memo"));
+        assert!(rows.any(|row| row == "JE-20260302,2026-03-02,Inventory Asset,,6.00,Inventory"));
+    }
+}
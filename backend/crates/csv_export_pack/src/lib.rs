@@ -1,6 +1,8 @@
 //! CSV Export Pack
 //!
-//! This crate generates QuickBooks-compatible CSV files from accounting snapshots.
+//! This crate generates accounting import files from accounting snapshots:
+//! `QuickBooks` Online CSV, `QuickBooks` Desktop IIF, Xero and Sage 50 CSV,
+//! and summarized daily journal entries for the general ledger.
 //! It's feature-gated with `#[cfg(feature = "export")]` to enable optional compilation.
 
 #![deny(unsafe_code)]
@@ -8,6 +10,11 @@
 
 pub mod exporter;
 pub mod quickbooks;
+pub mod quickbooks_iif;
+pub mod xero;
+pub mod sage;
+pub mod journal;
+pub mod profile;
 pub mod generic;
 pub mod errors;
 pub mod packaging;
@@ -15,6 +22,11 @@ pub mod packaging;
 // Re-export commonly used types
 pub use exporter::CsvExporter;
 pub use quickbooks::QuickBooksExporter;
+pub use quickbooks_iif::{IifAccounts, QuickBooksIifExporter};
+pub use xero::{XeroExporter, XeroSettings};
+pub use sage::{SageExporter, SageSettings};
+pub use journal::{ChartOfAccounts, JournalEntry, JournalExporter, JournalLine, JournalSettings};
+pub use profile::{ExportFormat, ExportProfile};
 pub use generic::GenericExporter;
 pub use errors::{ExportError, ExportResult};
 pub use packaging::ZipPackager;
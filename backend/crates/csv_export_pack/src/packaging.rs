@@ -1,8 +1,17 @@
 //! ZIP packaging for multi-file exports
 
 use std::io::Write;
+use accounting_snapshots::AccountingSnapshot;
 use zip::write::{FileOptions, ZipWriter};
-use crate::errors::ExportResult;
+use crate::errors::{ExportError, ExportResult};
+use crate::journal::JournalExporter;
+use crate::profile::ExportProfile;
+
+/// Payment method of sales charged to the customer's account
+///
+/// Sales paid at the counter are exported as sales receipts; sales charged
+/// on account are exported as invoices.
+pub const ON_ACCOUNT_METHOD: &str = "on_account";
 
 /// ZIP packager for exports
 pub struct ZipPackager;
@@ -49,6 +58,67 @@ impl ZipPackager {
         Ok(zip_buffer)
     }
     
+    /// Export snapshots in every format of a tenant's profile and package them
+    ///
+    /// Each format gets its own folder (`xero/sales_receipts.csv`,
+    /// `quickbooks_desktop/credit_memos.iif`, ...). Document types without
+    /// snapshots are left out. When the profile has journal settings, the
+    /// daily journal entries are added as `journal/journal_entries.csv`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::InvalidData` when the profile exports nothing, or
+    /// the first error of an exporter.
+    pub fn package_profile(
+        &self,
+        profile: &ExportProfile,
+        snapshots: &[AccountingSnapshot],
+    ) -> ExportResult<Vec<u8>> {
+        if profile.formats.is_empty() && profile.journal.is_none() {
+            return Err(ExportError::InvalidData("No export formats configured".to_string()));
+        }
+
+        let (credit_memos, sales): (Vec<_>, Vec<_>) =
+            snapshots.iter().cloned().partition(AccountingSnapshot::is_credit_memo);
+        let (invoices, sales_receipts): (Vec<_>, Vec<_>) = sales.into_iter().partition(|snapshot| {
+            snapshot.payments.iter().any(|payment| payment.method == ON_ACCOUNT_METHOD)
+        });
+
+        let mut files = Vec::new();
+        for &format in &profile.formats {
+            let exporter = profile.exporter(format);
+            let extension = format.file_extension();
+
+            if !invoices.is_empty() {
+                files.push((
+                    format!("{format}/invoices.{extension}"),
+                    exporter.export_invoices(&invoices)?,
+                ));
+            }
+            if !sales_receipts.is_empty() {
+                files.push((
+                    format!("{format}/sales_receipts.{extension}"),
+                    exporter.export_sales_receipts(&sales_receipts)?,
+                ));
+            }
+            if !credit_memos.is_empty() {
+                files.push((
+                    format!("{format}/credit_memos.{extension}"),
+                    exporter.export_credit_memos(&credit_memos)?,
+                ));
+            }
+        }
+
+        if let Some(settings) = &profile.journal {
+            files.push((
+                "journal/journal_entries.csv".to_string(),
+                JournalExporter::with_settings(settings.clone()).export(snapshots)?,
+            ));
+        }
+
+        self.package_exports(files)
+    }
+
     fn create_manifest(files: &[(String, String)]) -> String {
         let mut manifest = String::from("Export Manifest\n");
         manifest.push_str("===============\n\n");
@@ -104,4 +174,63 @@ mod tests {
         let zip_data = result.unwrap();
         assert!(!zip_data.is_empty());
     }
+
+    #[test]
+    fn test_package_profile_bundles_configured_formats() {
+        use crate::profile::ExportFormat;
+        use accounting_snapshots::{Payment, SnapshotLine};
+        use chrono::Utc;
+        use rust_decimal_macros::dec;
+        use uuid::Uuid;
+
+        let sale = |method: &str| {
+            AccountingSnapshot::new(
+                Uuid::new_v4(),
+                Utc::now(),
+                dec!(10.00),
+                dec!(0.50),
+                dec!(0.00),
+                dec!(10.50),
+                vec![Payment { method: method.to_string(), amount: dec!(10.50) }],
+                vec![SnapshotLine::new(
+                    "PROD-001".to_string(),
+                    "Test Product".to_string(),
+                    dec!(1),
+                    dec!(10.00),
+                    dec!(10.00),
+                    dec!(0.50),
+                )],
+            )
+        };
+        let snapshots = vec![
+            sale("cash"),
+            sale(ON_ACCOUNT_METHOD),
+            sale("card").as_credit_memo(Uuid::new_v4(), None),
+        ];
+        let mut profile = ExportProfile::with_formats(vec![ExportFormat::QuickBooksDesktop, ExportFormat::Xero]);
+        profile.journal = Some(crate::journal::JournalSettings::default());
+
+        let zip_data = ZipPackager::new().package_profile(&profile, &snapshots).unwrap();
+        let archive = zip::ZipArchive::new(std::io::Cursor::new(zip_data)).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort_unstable();
+
+        assert_eq!(
+            names,
+            vec![
+                "IMPORT_ORDER.txt",
+                "MANIFEST.txt",
+                "journal/journal_entries.csv",
+                "quickbooks_desktop/credit_memos.iif",
+                "quickbooks_desktop/invoices.iif",
+                "quickbooks_desktop/sales_receipts.iif",
+                "xero/credit_memos.csv",
+                "xero/invoices.csv",
+                "xero/sales_receipts.csv",
+            ]
+        );
+
+        let empty = ExportProfile::with_formats(Vec::new());
+        assert!(ZipPackager::new().package_profile(&empty, &snapshots).is_err());
+    }
 }
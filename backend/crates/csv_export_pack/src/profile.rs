@@ -0,0 +1,182 @@
+//! Export formats and per-tenant export profiles
+//!
+//! A tenant picks the accounting packages it exports to, and the account
+//! names or codes each package posts to, in an [`ExportProfile`]. Tenants
+//! that post a daily journal entry instead also set a chart of accounts. The
+//! profile is stored as JSON and drives which files [`crate::ZipPackager`]
+//! bundles.
+
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::errors::{ExportError, ExportResult};
+use crate::exporter::CsvExporter;
+use crate::journal::JournalSettings;
+use crate::quickbooks::QuickBooksExporter;
+use crate::quickbooks_iif::{IifAccounts, QuickBooksIifExporter};
+use crate::sage::{SageExporter, SageSettings};
+use crate::xero::{XeroExporter, XeroSettings};
+
+/// Accounting package file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ExportFormat {
+    /// `QuickBooks` Online CSV import templates
+    #[serde(rename = "quickbooks_online")]
+    QuickBooksOnline,
+    /// `QuickBooks` Desktop IIF
+    #[serde(rename = "quickbooks_desktop")]
+    QuickBooksDesktop,
+    /// Xero invoice and credit note import templates
+    #[serde(rename = "xero")]
+    Xero,
+    /// Sage 50 audit trail transaction import
+    #[serde(rename = "sage")]
+    Sage,
+}
+
+impl ExportFormat {
+    /// Every supported format
+    pub const ALL: [Self; 4] = [Self::QuickBooksOnline, Self::QuickBooksDesktop, Self::Xero, Self::Sage];
+
+    /// Identifier used in settings and as the folder name in export bundles
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::QuickBooksOnline => "quickbooks_online",
+            Self::QuickBooksDesktop => "quickbooks_desktop",
+            Self::Xero => "xero",
+            Self::Sage => "sage",
+        }
+    }
+
+    /// File extension of the exported files
+    #[must_use]
+    pub const fn file_extension(self) -> &'static str {
+        match self {
+            Self::QuickBooksDesktop => "iif",
+            Self::QuickBooksOnline | Self::Xero | Self::Sage => "csv",
+        }
+    }
+
+    /// Parse a comma-separated list of formats, ignoring duplicates
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::InvalidData` for an unknown format.
+    pub fn parse_list(value: &str) -> ExportResult<Vec<Self>> {
+        let mut formats = Vec::new();
+        for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let format = name.parse()?;
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        Ok(formats)
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = ExportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ExportError::InvalidData(format!("Unknown export format: {s}")))
+    }
+}
+
+/// Export formats and account settings of one tenant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportProfile {
+    /// Formats bundled in every export, in this order
+    pub formats: Vec<ExportFormat>,
+    /// Account names of `QuickBooks` Desktop IIF files
+    pub quickbooks_desktop: IifAccounts,
+    /// Account codes of Xero files
+    pub xero: XeroSettings,
+    /// Nominal codes of Sage files
+    pub sage: SageSettings,
+    /// Chart of accounts of the daily journal entries, when exported
+    pub journal: Option<JournalSettings>,
+}
+
+impl ExportProfile {
+    /// Profile exporting the given formats with default account settings
+    #[must_use]
+    pub fn with_formats(formats: Vec<ExportFormat>) -> Self {
+        Self {
+            formats,
+            ..Self::default()
+        }
+    }
+
+    /// Exporter of one format, configured with this profile's settings
+    #[must_use]
+    pub fn exporter(&self, format: ExportFormat) -> Box<dyn CsvExporter> {
+        match format {
+            ExportFormat::QuickBooksOnline => Box::new(QuickBooksExporter::new()),
+            ExportFormat::QuickBooksDesktop => {
+                Box::new(QuickBooksIifExporter::with_accounts(self.quickbooks_desktop.clone()))
+            }
+            ExportFormat::Xero => Box::new(XeroExporter::with_settings(self.xero.clone())),
+            ExportFormat::Sage => Box::new(SageExporter::with_settings(self.sage.clone())),
+        }
+    }
+}
+
+impl Default for ExportProfile {
+    /// `QuickBooks` Online only, the format exported before profiles existed
+    fn default() -> Self {
+        Self {
+            formats: vec![ExportFormat::QuickBooksOnline],
+            quickbooks_desktop: IifAccounts::default(),
+            xero: XeroSettings::default(),
+            sage: SageSettings::default(),
+            journal: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        let formats = ExportFormat::parse_list(" xero, QuickBooks_Desktop,,xero ").unwrap();
+        assert_eq!(formats, vec![ExportFormat::Xero, ExportFormat::QuickBooksDesktop]);
+
+        assert!(ExportFormat::parse_list("").unwrap().is_empty());
+        assert!(matches!(ExportFormat::parse_list("xero,myob"), Err(ExportError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_profile_json_fills_in_defaults() {
+        let profile: ExportProfile =
+            serde_json::from_str(r#"{"formats":["sage"],"sage":{"bank_nominal":"1210"}}"#).unwrap();
+
+        assert_eq!(profile.formats, vec![ExportFormat::Sage]);
+        assert_eq!(profile.sage.bank_nominal, "1210");
+        assert_eq!(profile.sage.sales_nominal, "4000");
+        assert_eq!(profile.xero, XeroSettings::default());
+
+        let profile: ExportProfile = serde_json::from_str("{}").unwrap();
+        assert_eq!(profile, ExportProfile::default());
+
+        let profile: ExportProfile = serde_json::from_str(
+            r#"{"formats":[],"journal":{"accounts":{"revenue_by_category":{"Parts":"4010"}}}}"#,
+        )
+        .unwrap();
+        let journal = profile.journal.unwrap();
+        assert_eq!(journal.accounts.revenue_account(Some("Parts")), "4010");
+        assert_eq!(journal.accounts.inventory, "Inventory Asset");
+    }
+}
@@ -2,7 +2,7 @@
 
 use csv::Writer;
 use accounting_snapshots::AccountingSnapshot;
-use crate::exporter::CsvExporter;
+use crate::exporter::{return_memo, CsvExporter};
 use crate::errors::{ExportError, ExportResult};
 
 /// `QuickBooks` CSV exporter
@@ -151,6 +151,9 @@ impl CsvExporter for QuickBooksExporter {
         
         // Write data rows
         for snapshot in snapshots {
+            // Memo carries the return reason and the sale it was returned from
+            let memo = return_memo(snapshot);
+
             for line in &snapshot.lines {
                 wtr.write_record([
                     &snapshot.transaction_id.to_string(),
@@ -163,7 +166,7 @@ impl CsvExporter for QuickBooksExporter {
                     &Self::format_decimal(&line.line_total),
                     "TAX", // Tax code
                     &Self::format_decimal(&line.tax_amount),
-                    "Return - damaged item", // Memo
+                    &memo,
                 ])?;
             }
         }
@@ -242,4 +245,14 @@ mod tests {
             "*CreditMemoNo,*Customer,*CreditMemoDate,*Item(Product/Service),ItemDescription,ItemQuantity,ItemRate,ItemAmount,ItemTaxCode,ItemTaxAmount,Memo"
         );
     }
+    
+    #[test]
+    fn test_credit_memo_memo_names_original_sale_and_reason() {
+        let original = Uuid::new_v4();
+        let snapshot = create_test_snapshot().as_credit_memo(original, Some("Damaged".to_string()));
+        let csv = QuickBooksExporter::new().export_credit_memos(&[snapshot]).unwrap();
+        let row = csv.lines().nth(1).unwrap();
+        
+        assert!(row.ends_with(&format!("Return of {original} - Damaged")));
+    }
 }
@@ -0,0 +1,320 @@
+//! `QuickBooks` Desktop IIF exporter
+//!
+//! IIF (Intuit Interchange Format) is the tab-delimited file `QuickBooks`
+//! Desktop imports through File > Utilities > Import > IIF Files. Each
+//! transaction is written as:
+//!
+//! - a `TRNS` row posting the total to the deposit or receivable account
+//! - one `SPL` row per line, plus sales tax and discount splits, posting the
+//!   other side with the opposite sign
+//! - an `ENDTRNS` row
+//!
+//! `QuickBooks` rejects a transaction whose splits do not cancel out its
+//! `TRNS` row, so snapshots that don't add up are refused rather than written.
+
+use accounting_snapshots::AccountingSnapshot;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::exporter::{document_number, format_amount, return_memo, CsvExporter};
+use crate::errors::{ExportError, ExportResult};
+
+/// IIF column headers for cash sales, invoices and credit memos
+const IIF_HEADER: [&[&str]; 3] = [
+    &["!TRNS", "TRNSID", "TRNSTYPE", "DATE", "ACCNT", "NAME", "AMOUNT", "DOCNUM", "MEMO"],
+    &["!SPL", "SPLID", "TRNSTYPE", "DATE", "ACCNT", "NAME", "AMOUNT", "DOCNUM", "MEMO", "QNTY", "PRICE", "INVITEM"],
+    &["!ENDTRNS"],
+];
+
+/// `QuickBooks` Desktop limits reference numbers to 11 characters
+const DOCNUM_MAX_LEN: usize = 11;
+
+/// Chart of accounts names the IIF transactions post to
+///
+/// Names must match the company file exactly; `QuickBooks` creates any
+/// account it does not know as a new bank account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IifAccounts {
+    /// Customer name on every transaction
+    pub customer: String,
+    /// Account cash sales are deposited to
+    pub deposit: String,
+    /// Account invoices and credit memos post to
+    pub receivable: String,
+    /// Income account of sold lines
+    pub income: String,
+    /// Liability account of sales tax charged
+    pub sales_tax: String,
+    /// Account of invoice-level discounts
+    pub discount: String,
+}
+
+impl Default for IifAccounts {
+    fn default() -> Self {
+        Self {
+            customer: "Customer".to_string(),
+            deposit: "Undeposited Funds".to_string(),
+            receivable: "Accounts Receivable".to_string(),
+            income: "Sales".to_string(),
+            sales_tax: "Sales Tax Payable".to_string(),
+            discount: "Discounts Given".to_string(),
+        }
+    }
+}
+
+/// `QuickBooks` Desktop IIF exporter
+pub struct QuickBooksIifExporter {
+    accounts: IifAccounts,
+}
+
+impl QuickBooksIifExporter {
+    /// Create a new IIF exporter posting to the default account names
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_accounts(IifAccounts::default())
+    }
+
+    /// Create an IIF exporter posting to the given account names
+    #[must_use]
+    pub const fn with_accounts(accounts: IifAccounts) -> Self {
+        Self { accounts }
+    }
+
+    /// Format date for IIF (MM/DD/YYYY)
+    fn format_date(date: &chrono::DateTime<chrono::Utc>) -> String {
+        date.format("%m/%d/%Y").to_string()
+    }
+
+    /// Strip characters that would break the tab-delimited layout
+    fn clean(value: &str) -> String {
+        value.replace(['\t', '\r', '\n'], " ")
+    }
+
+    fn write_row(out: &mut String, fields: &[&str]) {
+        let fields: Vec<String> = fields.iter().map(|field| Self::clean(field)).collect();
+        out.push_str(&fields.join("\t"));
+        out.push_str("\r\n");
+    }
+
+    /// Write transactions of one type
+    ///
+    /// `sign` is the sign of the `TRNS` amount: positive for sales and
+    /// invoices (money in), negative for credit memos (money out).
+    fn export(
+        &self,
+        snapshots: &[AccountingSnapshot],
+        trns_type: &str,
+        account: &str,
+        sign: Decimal,
+    ) -> ExportResult<String> {
+        let mut out = String::new();
+        for header in IIF_HEADER {
+            Self::write_row(&mut out, header);
+        }
+
+        for snapshot in snapshots {
+            if !snapshot.verify_consistency() {
+                return Err(ExportError::InvalidData(format!(
+                    "Snapshot {} does not balance and cannot be exported",
+                    snapshot.transaction_id
+                )));
+            }
+
+            let date = Self::format_date(&snapshot.finalized_at);
+            let docnum = document_number(snapshot, DOCNUM_MAX_LEN);
+            let memo = if snapshot.is_credit_memo() {
+                return_memo(snapshot)
+            } else {
+                format!("POS transaction {}", snapshot.transaction_id)
+            };
+
+            // Total of the splits, which the TRNS row must cancel out exactly
+            let total: Decimal = snapshot.lines.iter().map(|line| line.line_total).sum::<Decimal>()
+                + snapshot.tax
+                - snapshot.discount;
+
+            Self::write_row(&mut out, &[
+                "TRNS",
+                "",
+                trns_type,
+                &date,
+                account,
+                &self.accounts.customer,
+                &format_amount(sign * total),
+                &docnum,
+                &memo,
+            ]);
+
+            for line in &snapshot.lines {
+                Self::write_row(&mut out, &[
+                    "SPL",
+                    "",
+                    trns_type,
+                    &date,
+                    &self.accounts.income,
+                    "",
+                    &format_amount(-sign * line.line_total),
+                    &docnum,
+                    &line.description,
+                    &format_amount(-sign * line.quantity),
+                    &format_amount(line.unit_price),
+                    &line.product_id,
+                ]);
+            }
+
+            if !snapshot.tax.is_zero() {
+                Self::write_row(&mut out, &[
+                    "SPL",
+                    "",
+                    trns_type,
+                    &date,
+                    &self.accounts.sales_tax,
+                    "",
+                    &format_amount(-sign * snapshot.tax),
+                    &docnum,
+                    "Sales tax",
+                    "",
+                    "",
+                    "",
+                ]);
+            }
+
+            if !snapshot.discount.is_zero() {
+                Self::write_row(&mut out, &[
+                    "SPL",
+                    "",
+                    trns_type,
+                    &date,
+                    &self.accounts.discount,
+                    "",
+                    &format_amount(sign * snapshot.discount),
+                    &docnum,
+                    "Discount",
+                    "",
+                    "",
+                    "",
+                ]);
+            }
+
+            Self::write_row(&mut out, &["ENDTRNS"]);
+        }
+
+        Ok(out)
+    }
+}
+
+impl Default for QuickBooksIifExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvExporter for QuickBooksIifExporter {
+    fn export_sales_receipts(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "CASH SALE", &self.accounts.deposit, Decimal::ONE)
+    }
+
+    fn export_invoices(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "INVOICE", &self.accounts.receivable, Decimal::ONE)
+    }
+
+    fn export_credit_memos(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "CREDIT MEMO", &self.accounts.receivable, Decimal::NEGATIVE_ONE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accounting_snapshots::{Payment, SnapshotLine};
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn create_test_snapshot(discount: Decimal) -> AccountingSnapshot {
+        AccountingSnapshot::new(
+            Uuid::new_v4(),
+            Utc::now(),
+            dec!(100.00),
+            dec!(8.00),
+            discount,
+            dec!(108.00) - discount,
+            vec![Payment {
+                method: "cash".to_string(),
+                amount: dec!(108.00) - discount,
+            }],
+            vec![
+                SnapshotLine::new(
+                    "PROD-001".to_string(),
+                    "Test\tProduct".to_string(),
+                    dec!(2.0),
+                    dec!(30.00),
+                    dec!(60.00),
+                    dec!(4.80),
+                ),
+                SnapshotLine::new(
+                    "PROD-002".to_string(),
+                    "Other Product".to_string(),
+                    dec!(1.0),
+                    dec!(40.00),
+                    dec!(40.00),
+                    dec!(3.20),
+                ),
+            ],
+        )
+    }
+
+    /// Sum of the AMOUNT column of every transaction, which must be zero
+    fn transaction_balances(iif: &str) -> Vec<Decimal> {
+        let mut balances = Vec::new();
+        let mut balance = Decimal::ZERO;
+        for row in iif.lines() {
+            let fields: Vec<&str> = row.split('\t').collect();
+            match fields[0] {
+                "TRNS" | "SPL" => balance += fields[6].parse::<Decimal>().unwrap(),
+                "ENDTRNS" => {
+                    balances.push(balance);
+                    balance = Decimal::ZERO;
+                }
+                _ => {}
+            }
+        }
+        balances
+    }
+
+    #[test]
+    fn test_transactions_balance() {
+        let exporter = QuickBooksIifExporter::new();
+        let snapshots = [create_test_snapshot(dec!(0)), create_test_snapshot(dec!(5.00))];
+
+        let receipts = exporter.export_sales_receipts(&snapshots).unwrap();
+        assert_eq!(transaction_balances(&receipts), vec![Decimal::ZERO, Decimal::ZERO]);
+
+        let memos = exporter
+            .export_credit_memos(&[create_test_snapshot(dec!(5.00)).as_credit_memo(Uuid::new_v4(), None)])
+            .unwrap();
+        assert_eq!(transaction_balances(&memos), vec![Decimal::ZERO]);
+        let trns: Vec<&str> = memos.lines().nth(3).unwrap().split('\t').collect();
+        assert_eq!((trns[2], trns[4], trns[6]), ("CREDIT MEMO", "Accounts Receivable", "-103.00"));
+    }
+
+    #[test]
+    fn test_fields_cannot_break_the_layout() {
+        let iif = QuickBooksIifExporter::new()
+            .export_invoices(&[create_test_snapshot(dec!(0))])
+            .unwrap();
+        let split = iif.lines().nth(4).unwrap();
+
+        assert_eq!(split.split('\t').count(), 12);
+        assert!(split.contains("Test Product"));
+    }
+
+    #[test]
+    fn test_unbalanced_snapshot_is_refused() {
+        let mut snapshot = create_test_snapshot(dec!(0));
+        snapshot.total = dec!(150.00);
+
+        let result = QuickBooksIifExporter::new().export_sales_receipts(&[snapshot]);
+        assert!(matches!(result, Err(ExportError::InvalidData(_))));
+    }
+}
@@ -0,0 +1,171 @@
+//! Sage 50 CSV exporter
+//!
+//! Writes the Sage 50 Accounts audit trail transaction import
+//! (File > Import > Audit Trail Transactions), one row per line:
+//!
+//! - sales receipts as bank receipts (`BR`) into the bank nominal
+//! - invoices as sales invoices (`SI`) on the customer account
+//! - credit memos as sales credits (`SC`) on the customer account
+//!
+//! Amounts are positive for every type; Sage applies the sign from the
+//! transaction type. Invoice-level discounts are written as a negative
+//! untaxed row.
+
+use csv::Writer;
+use accounting_snapshots::AccountingSnapshot;
+use serde::{Deserialize, Serialize};
+use crate::exporter::{document_number, format_amount, CsvExporter};
+use crate::errors::{ExportError, ExportResult};
+
+/// Sage 50 limits references to 30 characters
+const REFERENCE_MAX_LEN: usize = 30;
+
+/// Sage 50 limits details to 60 characters
+const DETAILS_MAX_LEN: usize = 60;
+
+/// Account references, nominal codes and tax codes the Sage import uses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SageSettings {
+    /// Customer account of invoices and credits
+    pub customer_account: String,
+    /// Bank nominal code sales receipts are paid into
+    pub bank_nominal: String,
+    /// Sales nominal code of sold lines
+    pub sales_nominal: String,
+    /// Nominal code of invoice-level discounts
+    pub discount_nominal: String,
+    /// Tax code of taxed lines
+    pub tax_code: String,
+    /// Tax code of untaxed lines and discounts
+    pub exempt_tax_code: String,
+}
+
+impl Default for SageSettings {
+    fn default() -> Self {
+        Self {
+            customer_account: "CASH".to_string(),
+            bank_nominal: "1200".to_string(),
+            sales_nominal: "4000".to_string(),
+            discount_nominal: "4009".to_string(),
+            tax_code: "T1".to_string(),
+            exempt_tax_code: "T9".to_string(),
+        }
+    }
+}
+
+/// Sage 50 CSV exporter
+pub struct SageExporter {
+    settings: SageSettings,
+}
+
+impl SageExporter {
+    /// Create a new Sage exporter with the default nominal codes
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_settings(SageSettings::default())
+    }
+
+    /// Create a Sage exporter with the given nominal codes
+    #[must_use]
+    pub const fn with_settings(settings: SageSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Format date for Sage (DD/MM/YYYY)
+    fn format_date(date: &chrono::DateTime<chrono::Utc>) -> String {
+        date.format("%d/%m/%Y").to_string()
+    }
+
+    fn details(value: &str) -> String {
+        value.chars().take(DETAILS_MAX_LEN).collect()
+    }
+
+    fn export(
+        &self,
+        snapshots: &[AccountingSnapshot],
+        transaction_type: &str,
+        account_reference: &str,
+    ) -> ExportResult<String> {
+        let mut wtr = Writer::from_writer(vec![]);
+
+        wtr.write_record([
+            "Type",
+            "Account Reference",
+            "Nominal A/C Ref",
+            "Department Code",
+            "Date",
+            "Reference",
+            "Details",
+            "Net Amount",
+            "Tax Code",
+            "Tax Amount",
+        ])?;
+
+        for snapshot in snapshots {
+            let date = Self::format_date(&snapshot.finalized_at);
+            let reference = document_number(snapshot, REFERENCE_MAX_LEN);
+
+            for line in &snapshot.lines {
+                let tax_code = if line.tax_amount.is_zero() {
+                    &self.settings.exempt_tax_code
+                } else {
+                    &self.settings.tax_code
+                };
+                wtr.write_record([
+                    transaction_type,
+                    account_reference,
+                    &self.settings.sales_nominal,
+                    "0",
+                    &date,
+                    &reference,
+                    &Self::details(&format!("{} x {}", format_amount(line.quantity), line.description)),
+                    &format_amount(line.line_total),
+                    tax_code,
+                    &format_amount(line.tax_amount),
+                ])?;
+            }
+
+            if !snapshot.discount.is_zero() {
+                wtr.write_record([
+                    transaction_type,
+                    account_reference,
+                    &self.settings.discount_nominal,
+                    "0",
+                    &date,
+                    &reference,
+                    "Discount",
+                    &format_amount(-snapshot.discount),
+                    &self.settings.exempt_tax_code,
+                    "0.00",
+                ])?;
+            }
+        }
+
+        let data = wtr.into_inner()
+            .map_err(|e| ExportError::IoError(e.into_error()))?;
+
+        String::from_utf8(data)
+            .map_err(|e| ExportError::InvalidData(e.to_string()))
+    }
+}
+
+impl Default for SageExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvExporter for SageExporter {
+    fn export_sales_receipts(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "BR", &self.settings.bank_nominal)
+    }
+
+    fn export_invoices(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "SI", &self.settings.customer_account)
+    }
+
+    fn export_credit_memos(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "SC", &self.settings.customer_account)
+    }
+}
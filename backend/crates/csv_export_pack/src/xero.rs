@@ -0,0 +1,195 @@
+//! Xero CSV exporter
+//!
+//! Writes the Xero sales invoice and credit note import templates
+//! (Business > Invoices > Import). Xero has no import for cash sales, so
+//! sales receipts are written as invoices referenced "POS sale" and are
+//! settled in Xero against the tender deposits.
+//!
+//! Invoice-level discounts are written as an extra untaxed line with a
+//! negative unit amount, since Xero's `Discount` column is a per-line
+//! percentage.
+
+use csv::Writer;
+use accounting_snapshots::AccountingSnapshot;
+use serde::{Deserialize, Serialize};
+use crate::exporter::{format_amount, return_memo, CsvExporter};
+use crate::errors::{ExportError, ExportResult};
+
+/// Account codes and tax rates the Xero import uses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct XeroSettings {
+    /// Contact on every invoice and credit note
+    pub contact_name: String,
+    /// Revenue account code of sold lines
+    pub sales_account: String,
+    /// Account code of invoice-level discounts
+    pub discount_account: String,
+    /// Tax rate name of taxed lines
+    pub tax_type: String,
+    /// Tax rate name of untaxed lines and discounts
+    pub exempt_tax_type: String,
+}
+
+impl Default for XeroSettings {
+    fn default() -> Self {
+        Self {
+            contact_name: "Customer".to_string(),
+            sales_account: "200".to_string(),
+            discount_account: "200".to_string(),
+            tax_type: "Tax on Sales".to_string(),
+            exempt_tax_type: "Tax Exempt".to_string(),
+        }
+    }
+}
+
+/// Xero CSV exporter
+pub struct XeroExporter {
+    settings: XeroSettings,
+}
+
+impl XeroExporter {
+    /// Create a new Xero exporter with the default account codes
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_settings(XeroSettings::default())
+    }
+
+    /// Create a Xero exporter with the given account codes
+    #[must_use]
+    pub const fn with_settings(settings: XeroSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Format date for Xero (DD/MM/YYYY)
+    fn format_date(date: &chrono::DateTime<chrono::Utc>) -> String {
+        date.format("%d/%m/%Y").to_string()
+    }
+
+    /// Write invoices, or credit notes when `credit_notes` is set
+    fn export(
+        &self,
+        snapshots: &[AccountingSnapshot],
+        reference: &str,
+        credit_notes: bool,
+    ) -> ExportResult<String> {
+        let mut wtr = Writer::from_writer(vec![]);
+
+        // Credit notes have no due date
+        if credit_notes {
+            wtr.write_record([
+                "*ContactName",
+                "*CreditNoteNumber",
+                "Reference",
+                "*CreditNoteDate",
+                "InventoryItemCode",
+                "*Description",
+                "*Quantity",
+                "*UnitAmount",
+                "*AccountCode",
+                "*TaxType",
+                "TaxAmount",
+            ])?;
+        } else {
+            wtr.write_record([
+                "*ContactName",
+                "*InvoiceNumber",
+                "Reference",
+                "*InvoiceDate",
+                "*DueDate",
+                "InventoryItemCode",
+                "*Description",
+                "*Quantity",
+                "*UnitAmount",
+                "*AccountCode",
+                "*TaxType",
+                "TaxAmount",
+            ])?;
+        }
+
+        for snapshot in snapshots {
+            let number = snapshot.transaction_id.to_string();
+            let date = Self::format_date(&snapshot.finalized_at);
+            let reference = if credit_notes {
+                return_memo(snapshot)
+            } else {
+                reference.to_string()
+            };
+
+            let mut rows: Vec<[String; 6]> = snapshot
+                .lines
+                .iter()
+                .map(|line| {
+                    let tax_type = if line.tax_amount.is_zero() {
+                        &self.settings.exempt_tax_type
+                    } else {
+                        &self.settings.tax_type
+                    };
+                    [
+                        line.product_id.clone(),
+                        line.description.clone(),
+                        format_amount(line.quantity),
+                        format_amount(line.unit_price),
+                        tax_type.clone(),
+                        format_amount(line.tax_amount),
+                    ]
+                })
+                .collect();
+
+            if !snapshot.discount.is_zero() {
+                rows.push([
+                    String::new(),
+                    "Discount".to_string(),
+                    "1.00".to_string(),
+                    format_amount(-snapshot.discount),
+                    self.settings.exempt_tax_type.clone(),
+                    "0.00".to_string(),
+                ]);
+            }
+
+            let last_line = snapshot.lines.len();
+            for (index, [item, description, quantity, unit_amount, tax_type, tax_amount]) in
+                rows.iter().enumerate()
+            {
+                let account = if index < last_line {
+                    &self.settings.sales_account
+                } else {
+                    &self.settings.discount_account
+                };
+
+                let mut record = vec![&self.settings.contact_name, &number, &reference, &date];
+                if !credit_notes {
+                    record.push(&date);
+                }
+                record.extend([item, description, quantity, unit_amount, account, tax_type, tax_amount]);
+                wtr.write_record(record)?;
+            }
+        }
+
+        let data = wtr.into_inner()
+            .map_err(|e| ExportError::IoError(e.into_error()))?;
+
+        String::from_utf8(data)
+            .map_err(|e| ExportError::InvalidData(e.to_string()))
+    }
+}
+
+impl Default for XeroExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvExporter for XeroExporter {
+    fn export_sales_receipts(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "POS sale", false)
+    }
+
+    fn export_invoices(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "POS invoice", false)
+    }
+
+    fn export_credit_memos(&self, snapshots: &[AccountingSnapshot]) -> ExportResult<String> {
+        self.export(snapshots, "", true)
+    }
+}
@@ -0,0 +1,169 @@
+// Golden-file tests for the accounting export formats
+//
+// Every exporter writes a fixed set of snapshots and the output is compared
+// byte for byte with the files in tests/golden. After an intended format
+// change, regenerate them with:
+//
+//     UPDATE_GOLDEN=1 cargo test -p csv_export_pack --test golden_formats
+
+use std::path::PathBuf;
+
+use accounting_snapshots::{AccountingSnapshot, Payment, SnapshotLine, SnapshotLineTax};
+use chrono::{TimeZone, Utc};
+use csv_export_pack::packaging::ON_ACCOUNT_METHOD;
+use csv_export_pack::{ExportFormat, ExportProfile, JournalExporter};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+/// Fixed UUID that differs from the others in its first characters, like a random one
+const fn uuid(n: u128) -> Uuid {
+    Uuid::from_u128((n << 96) | 0x0000_0000_5a1e_4000_8000_0000_0000_0000)
+}
+
+fn line(product_id: &str, description: &str, quantity: Decimal, unit_price: Decimal, tax: Decimal) -> SnapshotLine {
+    SnapshotLine::new(
+        product_id.to_string(),
+        description.to_string(),
+        quantity,
+        unit_price,
+        quantity * unit_price,
+        tax,
+    )
+}
+
+fn snapshot(
+    n: u128,
+    day: u32,
+    discount: Decimal,
+    payments: &[(&str, Decimal)],
+    lines: Vec<SnapshotLine>,
+) -> AccountingSnapshot {
+    let subtotal: Decimal = lines.iter().map(|line| line.line_total).sum();
+    let tax: Decimal = lines.iter().map(|line| line.tax_amount).sum();
+
+    AccountingSnapshot::with_id(
+        uuid(0x100 + n),
+        uuid(n),
+        Utc.with_ymd_and_hms(2026, 3, day, 17, 45, 0).unwrap(),
+        subtotal,
+        tax,
+        discount,
+        subtotal + tax - discount,
+        payments
+            .iter()
+            .map(|(method, amount)| Payment { method: (*method).to_string(), amount: *amount })
+            .collect(),
+        lines,
+    )
+}
+
+/// Cash and split-tender sales, one sale on account and one return
+fn fixture() -> (Vec<AccountingSnapshot>, Vec<AccountingSnapshot>, Vec<AccountingSnapshot>) {
+    let oil_filter = line("OF-1024", "Oil filter, \"premium\"", dec!(2), dec!(12.50), dec!(1.25)).with_taxes(vec![
+        SnapshotLineTax {
+            authority: "GST".to_string(),
+            label: "GST 5%".to_string(),
+            rate: dec!(5),
+            taxable_amount: dec!(25.00),
+            tax_amount: dec!(1.25),
+        },
+    ])
+    .with_costing(Some("Parts".to_string()), Some(dec!(6.10)));
+
+    let sales_receipts = vec![
+        snapshot(1, 2, dec!(0), &[("cash", dec!(26.25))], vec![oil_filter]),
+        snapshot(
+            2,
+            2,
+            dec!(5.00),
+            &[("card", dec!(50.00)), ("cash", dec!(19.80))],
+            vec![
+                line("BP-220", "Brake pads\tfront", dec!(1), dec!(64.00), dec!(7.68))
+                    .with_costing(Some("Parts".to_string()), Some(dec!(31.75))),
+                line("GIFT-WRAP", "Gift wrap", dec!(1), dec!(3.12), dec!(0)),
+            ],
+        ),
+    ];
+
+    let invoices = vec![snapshot(
+        3,
+        3,
+        dec!(0),
+        &[(ON_ACCOUNT_METHOD, dec!(336.00))],
+        vec![line("TIRE-205", "Tire 205/55R16", dec!(4), dec!(75.00), dec!(36.00))
+            .with_costing(Some("Tires".to_string()), Some(dec!(48.333)))],
+    )];
+
+    let credit_memos = vec![snapshot(
+        4,
+        4,
+        dec!(0),
+        &[("cash", dec!(14.00))],
+        vec![line("OF-1024", "Oil filter, \"premium\"", dec!(1), dec!(12.50), dec!(1.50))
+            .with_costing(Some("Parts".to_string()), Some(dec!(6.10)))],
+    )
+    .as_credit_memo(uuid(1), Some("Wrong part".to_string()))];
+
+    (sales_receipts, invoices, credit_memos)
+}
+
+fn assert_golden(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name].iter().collect();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).expect("Failed to write golden file");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Missing golden file {}: {e}", path.display()));
+    assert_eq!(actual, expected, "{name} differs from its golden file");
+}
+
+fn assert_format(format: ExportFormat) {
+    let exporter = ExportProfile::default().exporter(format);
+    let (sales_receipts, invoices, credit_memos) = fixture();
+    let extension = format.file_extension();
+
+    assert_golden(
+        &format!("{format}_sales_receipts.{extension}"),
+        &exporter.export_sales_receipts(&sales_receipts).unwrap(),
+    );
+    assert_golden(
+        &format!("{format}_invoices.{extension}"),
+        &exporter.export_invoices(&invoices).unwrap(),
+    );
+    assert_golden(
+        &format!("{format}_credit_memos.{extension}"),
+        &exporter.export_credit_memos(&credit_memos).unwrap(),
+    );
+}
+
+#[test]
+fn test_quickbooks_online_golden() {
+    assert_format(ExportFormat::QuickBooksOnline);
+}
+
+#[test]
+fn test_quickbooks_desktop_golden() {
+    assert_format(ExportFormat::QuickBooksDesktop);
+}
+
+#[test]
+fn test_xero_golden() {
+    assert_format(ExportFormat::Xero);
+}
+
+#[test]
+fn test_sage_golden() {
+    assert_format(ExportFormat::Sage);
+}
+
+#[test]
+fn test_journal_entries_golden() {
+    let (sales_receipts, invoices, credit_memos) = fixture();
+    let snapshots: Vec<AccountingSnapshot> = sales_receipts.into_iter().chain(invoices).chain(credit_memos).collect();
+
+    assert_golden("journal_entries.csv", &JournalExporter::new().export(&snapshots).unwrap());
+}
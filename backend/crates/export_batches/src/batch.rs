@@ -28,11 +28,23 @@ pub struct ExportBatch {
     /// Number of snapshots in the batch
     pub snapshot_count: i32,
     
-    /// Configuration hash (for idempotency)
+    /// Configuration hash of the date range and exporter settings
     pub config_hash: String,
     
     /// Error message (if status is Failed)
     pub error_message: Option<String>,
+    
+    /// Batch this batch re-exports
+    pub supersedes: Option<Uuid>,
+    
+    /// Batch that re-exported this batch (if status is Superseded)
+    pub superseded_by: Option<Uuid>,
+    
+    /// When the accountant confirmed the import (if status is Locked)
+    pub locked_at: Option<DateTime<Utc>>,
+    
+    /// User who locked the batch
+    pub locked_by: Option<Uuid>,
 }
 
 /// Batch status
@@ -47,6 +59,12 @@ pub enum BatchStatus {
     
     /// Batch export failed
     Failed,
+    
+    /// Batch was replaced by a re-export of the same range
+    Superseded,
+    
+    /// Import confirmed by the accountant; the batch's range is closed
+    Locked,
 }
 
 impl ExportBatch {
@@ -69,6 +87,10 @@ impl ExportBatch {
             snapshot_count,
             config_hash,
             error_message: None,
+            supersedes: None,
+            superseded_by: None,
+            locked_at: None,
+            locked_by: None,
         }
     }
     
@@ -95,6 +117,10 @@ impl ExportBatch {
             snapshot_count,
             config_hash,
             error_message,
+            supersedes: None,
+            superseded_by: None,
+            locked_at: None,
+            locked_by: None,
         }
     }
     
@@ -115,6 +141,62 @@ impl ExportBatch {
         self.status = BatchStatus::Pending;
         self.error_message = None;
     }
+    
+    /// Mark batch as replaced by `batch_id`
+    pub const fn mark_superseded(&mut self, batch_id: Uuid) {
+        self.status = BatchStatus::Superseded;
+        self.superseded_by = Some(batch_id);
+    }
+    
+    /// Lock batch after the accountant confirmed the import
+    pub fn lock(&mut self, locked_by: Uuid) {
+        self.status = BatchStatus::Locked;
+        self.locked_at = Some(Utc::now());
+        self.locked_by = Some(locked_by);
+    }
+}
+
+/// Difference between the snapshots of two batches
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchDiff {
+    /// Batch compared from
+    pub from_batch: Uuid,
+    
+    /// Batch compared to
+    pub to_batch: Uuid,
+    
+    /// Snapshots only in the `to` batch
+    pub added: Vec<Uuid>,
+    
+    /// Snapshots only in the `from` batch
+    pub removed: Vec<Uuid>,
+    
+    /// Number of snapshots in both batches
+    pub unchanged: usize,
+    
+    /// Whether the batches were exported with different settings
+    pub config_changed: bool,
+}
+
+impl BatchDiff {
+    /// Compare the snapshot IDs of two batches
+    #[must_use]
+    pub fn between(from: &ExportBatch, from_snapshots: &[Uuid], to: &ExportBatch, to_snapshots: &[Uuid]) -> Self {
+        Self {
+            from_batch: from.id,
+            to_batch: to.id,
+            added: to_snapshots.iter().filter(|id| !from_snapshots.contains(id)).copied().collect(),
+            removed: from_snapshots.iter().filter(|id| !to_snapshots.contains(id)).copied().collect(),
+            unchanged: to_snapshots.iter().filter(|id| from_snapshots.contains(id)).count(),
+            config_changed: from.config_hash != to.config_hash,
+        }
+    }
+    
+    /// Whether the two batches export exactly the same thing
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && !self.config_changed
+    }
 }
 
 impl BatchStatus {
@@ -125,6 +207,8 @@ impl BatchStatus {
             "pending" => Some(Self::Pending),
             "completed" => Some(Self::Completed),
             "failed" => Some(Self::Failed),
+            "superseded" => Some(Self::Superseded),
+            "locked" => Some(Self::Locked),
             _ => None,
         }
     }
@@ -136,6 +220,8 @@ impl BatchStatus {
             Self::Pending => "pending",
             Self::Completed => "completed",
             Self::Failed => "failed",
+            Self::Superseded => "superseded",
+            Self::Locked => "locked",
         }
     }
 }
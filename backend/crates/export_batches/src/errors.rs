@@ -24,6 +24,18 @@ pub enum BatchError {
     /// Configuration error
     #[error("Configuration error: {0}")]
     ConfigError(String),
+    
+    /// Batch is not in a state that allows the operation
+    #[error("Invalid batch state: {0}")]
+    InvalidState(String),
+    
+    /// Batch is locked and can no longer change
+    #[error("Batch is locked: {0}")]
+    Locked(String),
+    
+    /// Batches cover different date ranges
+    #[error("Batches cover different date ranges: {0}")]
+    RangeMismatch(String),
 }
 
 /// Batch result type
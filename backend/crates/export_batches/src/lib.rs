@@ -3,6 +3,10 @@
 //! This crate manages collections of accounting snapshots for export with
 //! idempotency guarantees. Batches track which snapshots have been exported
 //! to prevent duplicate exports.
+//!
+//! A completed batch can be superseded by a re-export of its range and
+//! compared with it; once the accountant confirms the import the batch is
+//! locked, which closes its period to new snapshots.
 
 #![deny(unsafe_code)]
 #![warn(clippy::pedantic, clippy::nursery, clippy::unwrap_used)]
@@ -13,7 +17,7 @@ pub mod errors;
 pub mod repository;
 
 // Re-export commonly used types
-pub use batch::{ExportBatch, BatchStatus};
+pub use batch::{ExportBatch, BatchStatus, BatchDiff};
 pub use manager::BatchManager;
 pub use errors::{BatchError, BatchResult};
 pub use repository::BatchRepository;
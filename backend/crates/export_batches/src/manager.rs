@@ -3,35 +3,64 @@
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::batch::ExportBatch;
+use crate::batch::{BatchDiff, ExportBatch};
 use crate::errors::BatchResult;
 
 /// Batch manager trait
 #[async_trait::async_trait]
 pub trait BatchManager {
     /// Create a new export batch for the given date range
-    /// 
+    ///
     /// This collects all snapshots within the date range that haven't been
-    /// included in completed batches.
+    /// included in completed or locked batches. `export_config` is the
+    /// serialized exporter settings the batch is exported with; it goes into
+    /// the batch's config hash.
     async fn create_batch(
         &self,
         created_by: Uuid,
         start_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
+        export_config: &str,
     ) -> BatchResult<ExportBatch>;
-    
+
     /// Get batch by ID
     async fn get_batch(&self, batch_id: Uuid) -> BatchResult<ExportBatch>;
-    
+
     /// Mark batch as completed
     async fn mark_completed(&self, batch_id: Uuid) -> BatchResult<()>;
-    
+
     /// Mark batch as failed
     async fn mark_failed(&self, batch_id: Uuid, error: String) -> BatchResult<()>;
-    
+
     /// Reset batch to pending (for retry)
     async fn reset_batch(&self, batch_id: Uuid) -> BatchResult<()>;
-    
+
     /// Get snapshot IDs for a batch
     async fn get_batch_snapshots(&self, batch_id: Uuid) -> BatchResult<Vec<Uuid>>;
+
+    /// Re-export the range of a completed batch
+    ///
+    /// Creates a pending batch with every snapshot of the range that is not in
+    /// a locked batch, and marks the completed batch as superseded by it.
+    async fn supersede_batch(
+        &self,
+        batch_id: Uuid,
+        created_by: Uuid,
+        export_config: &str,
+    ) -> BatchResult<ExportBatch>;
+
+    /// Compare the snapshots and settings of two batches of the same range
+    async fn diff_batches(&self, from_batch: Uuid, to_batch: Uuid) -> BatchResult<BatchDiff>;
+
+    /// Lock a completed batch once the accountant confirmed the import
+    ///
+    /// Locking closes the batch's range: no snapshot finalized inside it can
+    /// be written afterwards.
+    async fn lock_batch(&self, batch_id: Uuid, locked_by: Uuid) -> BatchResult<()>;
+
+    /// Whether a batch was exported with settings other than `export_config`
+    async fn config_changed(&self, batch_id: Uuid, export_config: &str) -> BatchResult<bool>;
+
+    /// Whether `at` falls inside the range of a locked batch
+    async fn is_period_locked(&self, at: DateTime<Utc>) -> BatchResult<bool>;
 }
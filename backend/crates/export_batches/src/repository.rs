@@ -1,12 +1,12 @@
 //! Repository for persisting and retrieving export batches
 
 use chrono::{DateTime, Utc};
-use sqlx::Row;
+use sqlx::{Row, SqliteConnection};
 use uuid::Uuid;
 use sha2::{Sha256, Digest};
 
 use pos_core_storage::DatabasePool;
-use crate::batch::{ExportBatch, BatchStatus};
+use crate::batch::{BatchDiff, ExportBatch, BatchStatus};
 use crate::errors::{BatchError, BatchResult};
 use crate::manager::BatchManager;
 
@@ -22,19 +22,23 @@ impl BatchRepository {
         Self { pool }
     }
     
-    /// Calculate configuration hash for idempotency
-    fn calculate_config_hash(start_date: &DateTime<Utc>, end_date: &DateTime<Utc>) -> String {
+    /// Calculate configuration hash of a date range and exporter settings
+    fn calculate_config_hash(start_date: &DateTime<Utc>, end_date: &DateTime<Utc>, export_config: &str) -> String {
         let mut hasher = Sha256::new();
         hasher.update(start_date.to_rfc3339().as_bytes());
         hasher.update(end_date.to_rfc3339().as_bytes());
+        hasher.update(export_config.as_bytes());
         format!("{:x}", hasher.finalize())
     }
     
-    /// Get snapshots that haven't been included in completed batches
+    /// Get snapshots that haven't been included in completed or locked batches
+    ///
+    /// Snapshots of `superseding` (the batch being re-exported) stay eligible.
     async fn get_eligible_snapshots(
         &self,
         start_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
+        superseding: Option<Uuid>,
     ) -> BatchResult<Vec<Uuid>> {
         let rows = sqlx::query(
             r"
@@ -45,13 +49,15 @@ impl BatchRepository {
                 SELECT bs.snapshot_id
                 FROM batch_snapshots bs
                 JOIN export_batches eb ON bs.batch_id = eb.id
-                WHERE eb.status = 'completed'
+                WHERE eb.status IN ('completed', 'locked')
+                AND eb.id != ?
             )
             ORDER BY s.finalized_at
             "
         )
         .bind(start_date.to_rfc3339())
         .bind(end_date.to_rfc3339())
+        .bind(superseding.map(|id| id.to_string()).unwrap_or_default())
         .fetch_all(&self.pool)
         .await?;
         
@@ -67,14 +73,14 @@ impl BatchRepository {
     }
     
     /// Save a new export batch
-    async fn save(&self, batch: &ExportBatch) -> BatchResult<()> {
+    async fn save(conn: &mut SqliteConnection, batch: &ExportBatch) -> BatchResult<()> {
         sqlx::query(
             r"
             INSERT INTO export_batches (
                 id, created_at, created_by, start_date, end_date,
-                status, snapshot_count, config_hash, error_message
+                status, snapshot_count, config_hash, error_message, supersedes
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "
         )
         .bind(batch.id.to_string())
@@ -86,14 +92,15 @@ impl BatchRepository {
         .bind(batch.snapshot_count)
         .bind(&batch.config_hash)
         .bind(&batch.error_message)
-        .execute(&self.pool)
+        .bind(batch.supersedes.map(|id| id.to_string()))
+        .execute(&mut *conn)
         .await?;
         
         Ok(())
     }
     
     /// Link snapshots to a batch
-    async fn link_snapshots(&self, batch_id: Uuid, snapshot_ids: &[Uuid]) -> BatchResult<()> {
+    async fn link_snapshots(conn: &mut SqliteConnection, batch_id: Uuid, snapshot_ids: &[Uuid]) -> BatchResult<()> {
         let now = Utc::now().to_rfc3339();
         
         for snapshot_id in snapshot_ids {
@@ -106,7 +113,7 @@ impl BatchRepository {
             .bind(batch_id.to_string())
             .bind(snapshot_id.to_string())
             .bind(&now)
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await?;
         }
         
@@ -124,6 +131,45 @@ impl BatchRepository {
         Self::row_to_batch(row)
     }
     
+    /// Save a batch and its snapshots in one database transaction, marking
+    /// the batch it supersedes (if any)
+    async fn save_with_snapshots(&self, batch: &ExportBatch, snapshot_ids: &[Uuid]) -> BatchResult<()> {
+        let mut tx = self.pool.begin().await?;
+        
+        Self::save(&mut tx, batch).await?;
+        Self::link_snapshots(&mut tx, batch.id, snapshot_ids).await?;
+        
+        if let Some(superseded) = batch.supersedes {
+            sqlx::query(
+                r"
+                UPDATE export_batches
+                SET status = 'superseded', superseded_by = ?
+                WHERE id = ? AND status = 'completed'
+                "
+            )
+            .bind(batch.id.to_string())
+            .bind(superseded.to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(())
+    }
+    
+    /// Reject changes to locked and superseded batches
+    fn ensure_mutable(batch: &ExportBatch) -> BatchResult<()> {
+        match batch.status {
+            BatchStatus::Locked => Err(BatchError::Locked(batch.id.to_string())),
+            BatchStatus::Superseded => Err(BatchError::InvalidState(format!(
+                "Batch {} was superseded by {}",
+                batch.id,
+                batch.superseded_by.map(|id| id.to_string()).unwrap_or_default()
+            ))),
+            BatchStatus::Pending | BatchStatus::Completed | BatchStatus::Failed => Ok(()),
+        }
+    }
+    
     /// Update batch status
     async fn update_status(&self, id: Uuid, status: BatchStatus, error_message: Option<String>) -> BatchResult<()> {
         sqlx::query(
@@ -174,7 +220,26 @@ impl BatchRepository {
         let config_hash: String = row.try_get("config_hash")?;
         let error_message: Option<String> = row.try_get("error_message")?;
         
-        Ok(ExportBatch::with_id(
+        let parse_uuid = |column: &str| -> BatchResult<Option<Uuid>> {
+            let value: Option<String> = row.try_get(column)?;
+            value
+                .map(|value| Uuid::parse_str(&value).map_err(|e| BatchError::Database(sqlx::Error::Decode(Box::new(e)))))
+                .transpose()
+        };
+        let supersedes = parse_uuid("supersedes")?;
+        let superseded_by = parse_uuid("superseded_by")?;
+        let locked_by = parse_uuid("locked_by")?;
+        
+        let locked_at_str: Option<String> = row.try_get("locked_at")?;
+        let locked_at = locked_at_str
+            .map(|value| {
+                DateTime::parse_from_rfc3339(&value)
+                    .map(|date| date.with_timezone(&Utc))
+                    .map_err(|e| BatchError::Database(sqlx::Error::Decode(Box::new(e))))
+            })
+            .transpose()?;
+        
+        let mut batch = ExportBatch::with_id(
             id,
             created_at,
             created_by,
@@ -184,7 +249,13 @@ impl BatchRepository {
             snapshot_count,
             config_hash,
             error_message,
-        ))
+        );
+        batch.supersedes = supersedes;
+        batch.superseded_by = superseded_by;
+        batch.locked_at = locked_at;
+        batch.locked_by = locked_by;
+        
+        Ok(batch)
     }
 }
 
@@ -195,6 +266,7 @@ impl BatchManager for BatchRepository {
         created_by: Uuid,
         start_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
+        export_config: &str,
     ) -> BatchResult<ExportBatch> {
         // Validate date range
         if start_date > end_date {
@@ -203,11 +275,11 @@ impl BatchManager for BatchRepository {
             ));
         }
         
-        // Get eligible snapshots (excluding those in completed batches)
-        let snapshot_ids = self.get_eligible_snapshots(start_date, end_date).await?;
+        // Get eligible snapshots (excluding those in completed or locked batches)
+        let snapshot_ids = self.get_eligible_snapshots(start_date, end_date, None).await?;
         
         // Calculate config hash for idempotency
-        let config_hash = Self::calculate_config_hash(&start_date, &end_date);
+        let config_hash = Self::calculate_config_hash(&start_date, &end_date, export_config);
         
         // Create batch
         let batch = ExportBatch::new(
@@ -218,11 +290,8 @@ impl BatchManager for BatchRepository {
             config_hash,
         );
         
-        // Save batch
-        self.save(&batch).await?;
-        
-        // Link snapshots to batch
-        self.link_snapshots(batch.id, &snapshot_ids).await?;
+        // Save batch and link its snapshots
+        self.save_with_snapshots(&batch, &snapshot_ids).await?;
         
         Ok(batch)
     }
@@ -237,15 +306,18 @@ impl BatchManager for BatchRepository {
         if batch.status == BatchStatus::Completed {
             return Err(BatchError::AlreadyCompleted(batch_id.to_string()));
         }
+        Self::ensure_mutable(&batch)?;
         
         self.update_status(batch_id, BatchStatus::Completed, None).await
     }
     
     async fn mark_failed(&self, batch_id: Uuid, error: String) -> BatchResult<()> {
+        Self::ensure_mutable(&self.find_by_id(batch_id).await?)?;
         self.update_status(batch_id, BatchStatus::Failed, Some(error)).await
     }
     
     async fn reset_batch(&self, batch_id: Uuid) -> BatchResult<()> {
+        Self::ensure_mutable(&self.find_by_id(batch_id).await?)?;
         self.update_status(batch_id, BatchStatus::Pending, None).await
     }
     
@@ -272,4 +344,105 @@ impl BatchManager for BatchRepository {
         
         Ok(snapshot_ids)
     }
+    
+    async fn supersede_batch(
+        &self,
+        batch_id: Uuid,
+        created_by: Uuid,
+        export_config: &str,
+    ) -> BatchResult<ExportBatch> {
+        let superseded = self.find_by_id(batch_id).await?;
+        Self::ensure_mutable(&superseded)?;
+        if superseded.status != BatchStatus::Completed {
+            return Err(BatchError::InvalidState(format!(
+                "Only completed batches can be superseded; batch {batch_id} is {}",
+                superseded.status.as_str()
+            )));
+        }
+        
+        let snapshot_ids = self
+            .get_eligible_snapshots(superseded.start_date, superseded.end_date, Some(batch_id))
+            .await?;
+        
+        let mut batch = ExportBatch::new(
+            created_by,
+            superseded.start_date,
+            superseded.end_date,
+            snapshot_ids.len() as i32,
+            Self::calculate_config_hash(&superseded.start_date, &superseded.end_date, export_config),
+        );
+        batch.supersedes = Some(batch_id);
+        
+        self.save_with_snapshots(&batch, &snapshot_ids).await?;
+        
+        Ok(batch)
+    }
+    
+    async fn diff_batches(&self, from_batch: Uuid, to_batch: Uuid) -> BatchResult<BatchDiff> {
+        let from = self.find_by_id(from_batch).await?;
+        let to = self.find_by_id(to_batch).await?;
+        
+        if from.start_date != to.start_date || from.end_date != to.end_date {
+            return Err(BatchError::RangeMismatch(format!(
+                "{} covers {} to {}, {} covers {} to {}",
+                from.id, from.start_date, from.end_date, to.id, to.start_date, to.end_date
+            )));
+        }
+        
+        let from_snapshots = self.get_batch_snapshots(from_batch).await?;
+        let to_snapshots = self.get_batch_snapshots(to_batch).await?;
+        
+        Ok(BatchDiff::between(&from, &from_snapshots, &to, &to_snapshots))
+    }
+    
+    async fn lock_batch(&self, batch_id: Uuid, locked_by: Uuid) -> BatchResult<()> {
+        let mut batch = self.find_by_id(batch_id).await?;
+        Self::ensure_mutable(&batch)?;
+        if batch.status != BatchStatus::Completed {
+            return Err(BatchError::InvalidState(format!(
+                "Only completed batches can be locked; batch {batch_id} is {}",
+                batch.status.as_str()
+            )));
+        }
+        
+        batch.lock(locked_by);
+        sqlx::query(
+            r"
+            UPDATE export_batches
+            SET status = ?, locked_at = ?, locked_by = ?
+            WHERE id = ?
+            "
+        )
+        .bind(batch.status.as_str())
+        .bind(batch.locked_at.map(|at| at.to_rfc3339()))
+        .bind(locked_by.to_string())
+        .bind(batch_id.to_string())
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn config_changed(&self, batch_id: Uuid, export_config: &str) -> BatchResult<bool> {
+        let batch = self.find_by_id(batch_id).await?;
+        
+        Ok(batch.config_hash != Self::calculate_config_hash(&batch.start_date, &batch.end_date, export_config))
+    }
+    
+    async fn is_period_locked(&self, at: DateTime<Utc>) -> BatchResult<bool> {
+        let row = sqlx::query(
+            r"
+            SELECT COUNT(*) as count
+            FROM export_batches
+            WHERE status = 'locked' AND start_date <= ? AND end_date >= ?
+            "
+        )
+        .bind(at.to_rfc3339())
+        .bind(at.to_rfc3339())
+        .fetch_one(&self.pool)
+        .await?;
+        
+        let count: i64 = row.try_get("count")?;
+        Ok(count > 0)
+    }
 }
@@ -0,0 +1,175 @@
+// Integration tests for the export batch lifecycle: create, complete,
+// supersede, diff and lock (period close)
+
+use accounting_snapshots::{AccountingSnapshot, SnapshotError, SnapshotRepository};
+use chrono::{DateTime, TimeZone, Utc};
+use export_batches::{BatchError, BatchManager, BatchRepository, BatchStatus};
+use rust_decimal_macros::dec;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+const EXPORT_CONFIG: &str = r#"{"formats":["quickbooks_online"]}"#;
+
+async fn setup_test_db() -> SqlitePool {
+    // One connection: every connection to sqlite::memory: is its own database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to create test pool");
+
+    for statement in [
+        "CREATE TABLE accounting_snapshots (
+            id TEXT PRIMARY KEY,
+            transaction_id TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL,
+            finalized_at TEXT NOT NULL,
+            subtotal TEXT NOT NULL,
+            tax TEXT NOT NULL,
+            discount TEXT NOT NULL,
+            total TEXT NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'sale',
+            original_transaction_id TEXT,
+            memo TEXT
+        )",
+        "CREATE TABLE snapshot_lines (
+            id TEXT PRIMARY KEY,
+            snapshot_id TEXT NOT NULL,
+            product_id TEXT NOT NULL,
+            description TEXT NOT NULL,
+            quantity TEXT NOT NULL,
+            unit_price TEXT NOT NULL,
+            line_total TEXT NOT NULL,
+            tax_amount TEXT NOT NULL,
+            category TEXT,
+            unit_cost TEXT
+        )",
+        "CREATE TABLE snapshot_payments (
+            id TEXT PRIMARY KEY,
+            snapshot_id TEXT NOT NULL,
+            method TEXT NOT NULL,
+            amount TEXT NOT NULL
+        )",
+        include_str!("../../../migrations/066_export_batch_lifecycle.sql"),
+    ] {
+        sqlx::query(statement)
+            .execute(&pool)
+            .await
+            .expect("Failed to create schema");
+    }
+
+    pool
+}
+
+fn day(day: u32, hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+}
+
+fn snapshot(finalized_at: DateTime<Utc>) -> AccountingSnapshot {
+    AccountingSnapshot::new(
+        Uuid::new_v4(),
+        finalized_at,
+        dec!(10.00),
+        dec!(0.50),
+        dec!(0.00),
+        dec!(10.50),
+        vec![],
+        vec![],
+    )
+}
+
+async fn save(pool: &SqlitePool, finalized_at: DateTime<Utc>) -> Result<Uuid, SnapshotError> {
+    let snapshot = snapshot(finalized_at);
+    SnapshotRepository::new(pool.clone()).save(&snapshot).await?;
+    Ok(snapshot.id)
+}
+
+#[tokio::test]
+async fn test_supersede_re_exports_range_and_diffs() {
+    let pool = setup_test_db().await;
+    let batches = BatchRepository::new(pool.clone());
+    let user = Uuid::new_v4();
+
+    save(&pool, day(2, 10)).await.unwrap();
+    let original = batches.create_batch(user, day(1, 0), day(31, 23), EXPORT_CONFIG).await.unwrap();
+    assert_eq!(original.snapshot_count, 1);
+
+    // Only completed batches can be superseded
+    assert!(matches!(
+        batches.supersede_batch(original.id, user, EXPORT_CONFIG).await,
+        Err(BatchError::InvalidState(_))
+    ));
+    batches.mark_completed(original.id).await.unwrap();
+
+    // A snapshot backdated into the exported range after the export
+    let late = save(&pool, day(3, 9)).await.unwrap();
+    let new_settings = r#"{"formats":["xero"]}"#;
+    assert!(batches.config_changed(original.id, new_settings).await.unwrap());
+    assert!(!batches.config_changed(original.id, EXPORT_CONFIG).await.unwrap());
+
+    // The re-export takes over the original's snapshots and adds the late one
+    let replacement = batches.supersede_batch(original.id, user, new_settings).await.unwrap();
+    assert_eq!(replacement.snapshot_count, 2);
+    assert_eq!(replacement.supersedes, Some(original.id));
+
+    let original = batches.get_batch(original.id).await.unwrap();
+    assert_eq!(original.status, BatchStatus::Superseded);
+    assert_eq!(original.superseded_by, Some(replacement.id));
+    assert!(matches!(batches.reset_batch(original.id).await, Err(BatchError::InvalidState(_))));
+
+    let diff = batches.diff_batches(original.id, replacement.id).await.unwrap();
+    assert_eq!(diff.added, vec![late]);
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.unchanged, 1);
+    assert!(diff.config_changed);
+    assert!(!diff.is_empty());
+
+    let other_range = batches.create_batch(user, day(1, 0), day(2, 0), EXPORT_CONFIG).await.unwrap();
+    assert!(matches!(
+        batches.diff_batches(original.id, other_range.id).await,
+        Err(BatchError::RangeMismatch(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_locked_batch_closes_its_period() {
+    let pool = setup_test_db().await;
+    let batches = BatchRepository::new(pool.clone());
+    let user = Uuid::new_v4();
+
+    save(&pool, day(2, 10)).await.unwrap();
+    let batch = batches.create_batch(user, day(1, 0), day(7, 23), EXPORT_CONFIG).await.unwrap();
+
+    // Locking waits for a completed export
+    assert!(matches!(batches.lock_batch(batch.id, user).await, Err(BatchError::InvalidState(_))));
+    batches.mark_completed(batch.id).await.unwrap();
+    batches.lock_batch(batch.id, user).await.unwrap();
+
+    let locked = batches.get_batch(batch.id).await.unwrap();
+    assert_eq!(locked.status, BatchStatus::Locked);
+    assert_eq!(locked.locked_by, Some(user));
+    assert!(locked.locked_at.is_some());
+
+    assert!(batches.is_period_locked(day(5, 12)).await.unwrap());
+    assert!(!batches.is_period_locked(day(8, 12)).await.unwrap());
+
+    // No snapshot may be written into the closed period; later ones may
+    assert!(matches!(save(&pool, day(5, 12)).await, Err(SnapshotError::PeriodClosed(_))));
+    save(&pool, day(8, 12)).await.unwrap();
+
+    // A locked batch can no longer change
+    assert!(matches!(batches.lock_batch(batch.id, user).await, Err(BatchError::Locked(_))));
+    assert!(matches!(
+        batches.supersede_batch(batch.id, user, EXPORT_CONFIG).await,
+        Err(BatchError::Locked(_))
+    ));
+    assert!(matches!(
+        batches.mark_failed(batch.id, "Import rejected".to_string()).await,
+        Err(BatchError::Locked(_))
+    ));
+
+    // Its snapshots are never exported again
+    let next = batches.create_batch(user, day(1, 0), day(31, 23), EXPORT_CONFIG).await.unwrap();
+    assert_eq!(next.snapshot_count, 1);
+}
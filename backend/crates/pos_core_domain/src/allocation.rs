@@ -0,0 +1,85 @@
+//! Proportional allocation of header amounts across lines
+//!
+//! Tax and cart-level discounts are calculated on the transaction header but
+//! must also be recorded per line. Naive proportional splitting leaves
+//! sub-cent fractions that no longer add up to the header once each line is
+//! rounded, so the remainder is assigned to the largest line.
+
+use rust_decimal::Decimal;
+
+/// Split `amount` across `weights` proportionally, rounding each share to
+/// `decimal_places`.
+///
+/// The shares always sum exactly to `amount` rounded to `decimal_places`;
+/// any rounding remainder is assigned to the share with the largest weight.
+/// When all weights are zero the whole amount goes to the first share.
+#[must_use]
+pub fn allocate_proportionally(amount: Decimal, weights: &[Decimal], decimal_places: u32) -> Vec<Decimal> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+
+    let target = amount.round_dp(decimal_places);
+    let total_weight: Decimal = weights.iter().copied().sum();
+
+    let mut shares: Vec<Decimal> = if total_weight.is_zero() {
+        vec![Decimal::ZERO; weights.len()]
+    } else {
+        weights
+            .iter()
+            .map(|weight| (target * weight / total_weight).round_dp(decimal_places))
+            .collect()
+    };
+
+    let allocated: Decimal = shares.iter().copied().sum();
+    let remainder = target - allocated;
+    if !remainder.is_zero() {
+        // First line wins ties so allocation is stable for equal weights
+        let mut largest = 0;
+        for (index, weight) in weights.iter().enumerate() {
+            if weight.abs() > weights[largest].abs() {
+                largest = index;
+            }
+        }
+        shares[largest] += remainder;
+    }
+
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_allocation_sums_to_amount() {
+        let shares = allocate_proportionally(dec!(1.00), &[dec!(1), dec!(1), dec!(1)], 2);
+        assert_eq!(shares.iter().copied().sum::<Decimal>(), dec!(1.00));
+        assert_eq!(shares, vec![dec!(0.34), dec!(0.33), dec!(0.33)]);
+    }
+
+    #[test]
+    fn test_allocation_remainder_goes_to_largest_weight() {
+        let shares = allocate_proportionally(dec!(0.10), &[dec!(1), dec!(2), dec!(4)], 2);
+        assert_eq!(shares.iter().copied().sum::<Decimal>(), dec!(0.10));
+        assert_eq!(shares, vec![dec!(0.01), dec!(0.03), dec!(0.06)]);
+    }
+
+    #[test]
+    fn test_allocation_proportional() {
+        let shares = allocate_proportionally(dec!(2.80), &[dec!(20.00), dec!(15.00)], 2);
+        assert_eq!(shares, vec![dec!(1.60), dec!(1.20)]);
+    }
+
+    #[test]
+    fn test_allocation_zero_weights() {
+        let shares = allocate_proportionally(dec!(5.00), &[dec!(0), dec!(0)], 2);
+        assert_eq!(shares, vec![dec!(5.00), dec!(0)]);
+    }
+
+    #[test]
+    fn test_allocation_empty_weights() {
+        assert!(allocate_proportionally(dec!(5.00), &[], 2).is_empty());
+    }
+}
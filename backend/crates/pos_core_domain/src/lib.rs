@@ -10,6 +10,7 @@
 #![deny(unsafe_code)]
 #![warn(clippy::pedantic, clippy::nursery, clippy::unwrap_used)]
 
+pub mod allocation;
 pub mod pricing;
 pub mod tax;
 pub mod discount;
@@ -18,11 +19,13 @@ pub mod transaction;
 // Re-export types from pos_core_models
 pub use pos_core_models::{
     DomainError, DomainResult, Transaction, TransactionStatus, Payment,
-    LineItem, PricingEngine, Discount, DiscountType, TaxRate,
+    LineItem, PricingEngine, Discount, DiscountType, TaxRate, PriceMode, TaxRounding,
+    TAX_CLASS_EXEMPT, TAX_CLASS_REDUCED, TAX_CLASS_STANDARD,
 };
 
 // Re-export domain logic traits
 pub use pricing::DefaultPricingEngine;
-pub use tax::TaxCalculator;
+pub use tax::{JurisdictionTaxCalculator, TaxBreakdown, TaxCalculator, TaxExemption, TaxJurisdiction};
 pub use discount::DiscountApplicator;
 pub use transaction::TransactionFinalizer;
+pub use allocation::allocate_proportionally;
@@ -1,7 +1,12 @@
 //! Tax calculation logic
 
 use rust_decimal::Decimal;
-use pos_core_models::{TaxRate, DomainError, DomainResult};
+use serde::{Deserialize, Serialize};
+use pos_core_models::{
+    DomainError, DomainResult, PriceMode, TaxRate, TaxRounding, TAX_CLASS_STANDARD,
+};
+
+use crate::allocation::allocate_proportionally;
 
 /// Trait for tax calculation
 pub trait TaxCalculator {
@@ -117,6 +122,376 @@ impl TaxCalculator for DefaultTaxCalculator {
     }
 }
 
+/// Tax rates that apply in one jurisdiction (typically one store)
+///
+/// Rates are applied in order, so compound rates must follow the rates they
+/// are charged on. Examples:
+/// - Canada, GST + PST: `GST 5%` and `PST 7%` restricted to standard goods
+/// - Canada, HST: a single `HST 13%` rate
+/// - US: `STATE 6.25%` and `COUNTY 1%`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxJurisdiction {
+    pub code: String,
+    pub name: String,
+    pub rates: Vec<TaxRate>,
+}
+
+impl TaxJurisdiction {
+    /// Create a jurisdiction from its rates
+    #[must_use]
+    pub const fn new(code: String, name: String, rates: Vec<TaxRate>) -> Self {
+        Self { code, name, rates }
+    }
+}
+
+/// Customer tax exemption backed by an exemption certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxExemption {
+    pub certificate_number: String,
+    /// Rate codes the customer is exempt from; empty means all of them
+    #[serde(default)]
+    pub rate_codes: Vec<String>,
+}
+
+impl TaxExemption {
+    /// Whether the exemption covers `rate_code`
+    #[must_use]
+    pub fn covers(&self, rate_code: &str) -> bool {
+        self.rate_codes.is_empty() || self.rate_codes.iter().any(|code| code == rate_code)
+    }
+}
+
+/// A line to be taxed, after all discounts
+#[derive(Debug, Clone)]
+pub struct TaxableLine {
+    pub product_id: String,
+    pub tax_class: String,
+    /// Net amount, or gross amount when prices include tax
+    pub amount: Decimal,
+}
+
+/// Tax charged by one authority on one line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineTax {
+    pub rate_code: String,
+    pub label: String,
+    pub rate_percent: Decimal,
+    pub taxable_amount: Decimal,
+    pub tax_amount: Decimal,
+}
+
+/// Taxes charged on one line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineTaxBreakdown {
+    pub product_id: String,
+    pub tax_class: String,
+    /// Line amount excluding tax; for tax-inclusive prices this is the
+    /// gross amount less the line's taxes
+    pub net_amount: Decimal,
+    pub taxes: Vec<LineTax>,
+}
+
+impl LineTaxBreakdown {
+    /// Total tax on the line
+    #[must_use]
+    pub fn total(&self) -> Decimal {
+        self.taxes.iter().map(|tax| tax.tax_amount).sum()
+    }
+}
+
+/// Per-line, per-authority tax for a whole transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaxBreakdown {
+    pub lines: Vec<LineTaxBreakdown>,
+    pub total_tax: Decimal,
+    /// Certificate of the exemption applied, if any
+    pub exemption_certificate: Option<String>,
+}
+
+impl TaxBreakdown {
+    /// Tax collected per authority, in the order the rates were applied
+    #[must_use]
+    pub fn authority_totals(&self) -> Vec<(String, Decimal)> {
+        let mut totals: Vec<(String, Decimal)> = Vec::new();
+        for tax in self.lines.iter().flat_map(|line| &line.taxes) {
+            match totals.iter_mut().find(|(code, _)| *code == tax.rate_code) {
+                Some((_, amount)) => *amount += tax.tax_amount,
+                None => totals.push((tax.rate_code.clone(), tax.tax_amount)),
+            }
+        }
+        totals
+    }
+}
+
+/// Tax calculator for jurisdictions with several authorities and tax classes
+///
+/// By default each rate is calculated on the sum of the lines it applies to
+/// and rounded once, then allocated back to those lines, so the per-authority
+/// totals match what a tax return would report and the lines sum exactly to
+/// the header. With [`TaxRounding::PerLine`] each line's tax is rounded on its
+/// own instead.
+///
+/// With [`PriceMode::TaxInclusive`] line amounts are gross: the net amount is
+/// back-calculated from the combined rate of the line's tax class and the
+/// taxes are taken out of the gross, which is never changed by rounding.
+#[derive(Debug, Clone)]
+pub struct JurisdictionTaxCalculator {
+    jurisdiction: TaxJurisdiction,
+    exemption: Option<TaxExemption>,
+    price_mode: PriceMode,
+    rounding: TaxRounding,
+    base: DefaultTaxCalculator,
+}
+
+impl JurisdictionTaxCalculator {
+    /// Create a calculator for a jurisdiction
+    #[must_use]
+    pub fn new(jurisdiction: TaxJurisdiction) -> Self {
+        Self {
+            jurisdiction,
+            exemption: None,
+            price_mode: PriceMode::default(),
+            rounding: TaxRounding::default(),
+            base: DefaultTaxCalculator::new(),
+        }
+    }
+
+    /// Apply a customer exemption to every calculation
+    #[must_use]
+    pub fn with_exemption(mut self, exemption: Option<TaxExemption>) -> Self {
+        self.exemption = exemption;
+        self
+    }
+
+    /// Set whether line amounts include tax
+    #[must_use]
+    pub const fn with_price_mode(mut self, price_mode: PriceMode) -> Self {
+        self.price_mode = price_mode;
+        self
+    }
+
+    /// Set where tax is rounded
+    #[must_use]
+    pub const fn with_rounding(mut self, rounding: TaxRounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Set the tolerance used when validating tax amounts
+    #[must_use]
+    pub const fn with_tolerance(mut self, tolerance: Decimal) -> Self {
+        self.base = DefaultTaxCalculator::with_tolerance(tolerance);
+        self
+    }
+
+    /// The jurisdiction this calculator applies
+    #[must_use]
+    pub const fn jurisdiction(&self) -> &TaxJurisdiction {
+        &self.jurisdiction
+    }
+
+    /// Whether line amounts include tax
+    #[must_use]
+    pub const fn price_mode(&self) -> PriceMode {
+        self.price_mode
+    }
+
+    /// Tolerance used when validating tax amounts
+    #[must_use]
+    pub const fn tolerance(&self) -> Decimal {
+        self.base.tolerance
+    }
+
+    fn is_exempt(&self, rate: &TaxRate) -> bool {
+        self.exemption.as_ref().is_some_and(|exemption| exemption.covers(&rate.rate_code))
+    }
+
+    /// Gross amount per unit of net amount for a tax class
+    ///
+    /// Additive rates each add their rate; a compound rate also applies to
+    /// the rates before it.
+    fn gross_factor(&self, tax_class: &str) -> Decimal {
+        self.jurisdiction
+            .rates
+            .iter()
+            .filter(|rate| !self.is_exempt(rate) && rate.applies_to(tax_class))
+            .fold(Decimal::ONE, |factor, rate| {
+                let rate_fraction = rate.rate_percent / Decimal::ONE_HUNDRED;
+                if rate.compound {
+                    factor + factor * rate_fraction
+                } else {
+                    factor + rate_fraction
+                }
+            })
+    }
+
+    /// Round the unrounded taxes of one rate to the cent
+    fn round_taxes(&self, exact: &[Decimal]) -> Vec<Decimal> {
+        match self.rounding {
+            TaxRounding::PerLine => exact.iter().map(|tax| tax.round_dp(2)).collect(),
+            TaxRounding::PerInvoice => {
+                allocate_proportionally(exact.iter().copied().sum(), exact, 2)
+            }
+        }
+    }
+
+    /// Calculate the per-line, per-authority tax for a set of lines
+    ///
+    /// # Errors
+    ///
+    /// Returns `DomainError::InvalidInput` if a line amount is negative.
+    pub fn calculate_breakdown(&self, lines: &[TaxableLine]) -> DomainResult<TaxBreakdown> {
+        if lines.iter().any(|line| line.amount < Decimal::ZERO) {
+            return Err(DomainError::InvalidInput(
+                "Taxable amount cannot be negative".to_string(),
+            ));
+        }
+
+        let inclusive = self.price_mode == PriceMode::TaxInclusive;
+        let nets: Vec<Decimal> = lines
+            .iter()
+            .map(|line| {
+                if inclusive {
+                    line.amount / self.gross_factor(&line.tax_class)
+                } else {
+                    line.amount
+                }
+            })
+            .collect();
+        // Unrounded taxes so far, used as the compound base for gross prices
+        let mut exact_taxes = vec![Decimal::ZERO; lines.len()];
+
+        let mut breakdown: Vec<LineTaxBreakdown> = lines
+            .iter()
+            .map(|line| LineTaxBreakdown {
+                product_id: line.product_id.clone(),
+                tax_class: line.tax_class.clone(),
+                net_amount: line.amount,
+                taxes: Vec::new(),
+            })
+            .collect();
+        let mut compound_flags: Vec<Vec<bool>> = vec![Vec::new(); lines.len()];
+
+        for rate in &self.jurisdiction.rates {
+            if self.is_exempt(rate) {
+                continue;
+            }
+
+            let applicable: Vec<usize> = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| rate.applies_to(&line.tax_class))
+                .map(|(index, _)| index)
+                .collect();
+            if applicable.is_empty() {
+                continue;
+            }
+
+            let bases: Vec<Decimal> = applicable
+                .iter()
+                .map(|&index| {
+                    let preceding = match (rate.compound, inclusive) {
+                        (false, _) => Decimal::ZERO,
+                        (true, false) => breakdown[index].total(),
+                        (true, true) => exact_taxes[index],
+                    };
+                    nets[index] + preceding
+                })
+                .collect();
+            let exact: Vec<Decimal> = bases
+                .iter()
+                .map(|base| base * rate.rate_percent / Decimal::ONE_HUNDRED)
+                .collect();
+            let rounded = self.round_taxes(&exact);
+
+            for (((&index, base), exact), tax) in applicable.iter().zip(bases).zip(exact).zip(rounded) {
+                exact_taxes[index] += exact;
+                compound_flags[index].push(rate.compound);
+                breakdown[index].taxes.push(LineTax {
+                    rate_code: rate.rate_code.clone(),
+                    label: rate.label.clone(),
+                    rate_percent: rate.rate_percent,
+                    taxable_amount: base,
+                    tax_amount: tax,
+                });
+            }
+        }
+
+        if inclusive {
+            // Net is whatever the rounded taxes leave of the gross, so that
+            // net + tax always equals the shelf price
+            for (line, compound) in breakdown.iter_mut().zip(&compound_flags) {
+                line.net_amount -= line.total();
+                let mut preceding = Decimal::ZERO;
+                for (tax, &compound) in line.taxes.iter_mut().zip(compound) {
+                    tax.taxable_amount = if compound {
+                        line.net_amount + preceding
+                    } else {
+                        line.net_amount
+                    };
+                    preceding += tax.tax_amount;
+                }
+            }
+        }
+
+        let total_tax = breakdown.iter().map(LineTaxBreakdown::total).sum();
+        Ok(TaxBreakdown {
+            lines: breakdown,
+            total_tax,
+            exemption_certificate: self
+                .exemption
+                .as_ref()
+                .map(|exemption| exemption.certificate_number.clone()),
+        })
+    }
+}
+
+impl TaxCalculator for JurisdictionTaxCalculator {
+    fn calculate_tax(&self, subtotal: Decimal, tax_rate: &TaxRate) -> DomainResult<Decimal> {
+        if self.is_exempt(tax_rate) {
+            return Ok(Decimal::ZERO);
+        }
+        self.base.calculate_tax(subtotal, tax_rate)
+    }
+
+    /// Tax `subtotal` as a single standard-class line
+    ///
+    /// `tax_rates` are applied after the jurisdiction's own rates.
+    fn calculate_multi_tax(
+        &self,
+        subtotal: Decimal,
+        tax_rates: &[TaxRate],
+    ) -> DomainResult<Decimal> {
+        let mut rates = self.jurisdiction.rates.clone();
+        rates.extend_from_slice(tax_rates);
+        let calculator = Self {
+            jurisdiction: TaxJurisdiction::new(
+                self.jurisdiction.code.clone(),
+                self.jurisdiction.name.clone(),
+                rates,
+            ),
+            ..self.clone()
+        };
+
+        let breakdown = calculator.calculate_breakdown(&[TaxableLine {
+            product_id: String::new(),
+            tax_class: TAX_CLASS_STANDARD.to_string(),
+            amount: subtotal,
+        }])?;
+        Ok(breakdown.total_tax)
+    }
+
+    fn validate_tax(
+        &self,
+        subtotal: Decimal,
+        tax_rate: &TaxRate,
+        expected_tax: Decimal,
+    ) -> DomainResult<bool> {
+        let calculated = self.calculate_tax(subtotal, tax_rate)?;
+        Ok((calculated - expected_tax).abs() <= self.base.tolerance)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,4 +583,241 @@ mod tests {
         let result = calculator.calculate_tax(dec!(-100.00), &rate);
         assert!(result.is_err());
     }
+
+    fn rate(code: &str, percent: Decimal) -> TaxRate {
+        TaxRate::new(code.to_string(), percent, code.to_string()).unwrap()
+    }
+
+    fn taxable(product_id: &str, tax_class: &str, amount: Decimal) -> TaxableLine {
+        TaxableLine {
+            product_id: product_id.to_string(),
+            tax_class: tax_class.to_string(),
+            amount,
+        }
+    }
+
+    fn gst_pst() -> TaxJurisdiction {
+        TaxJurisdiction::new(
+            "CA-BC".to_string(),
+            "British Columbia".to_string(),
+            vec![
+                rate("GST", dec!(5)),
+                rate("PST", dec!(7)).with_tax_classes(vec!["standard".to_string()]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_breakdown_gst_pst_by_tax_class() {
+        let calculator = JurisdictionTaxCalculator::new(gst_pst());
+        let breakdown = calculator
+            .calculate_breakdown(&[
+                taxable("P1", "standard", dec!(100.00)),
+                taxable("P2", "reduced", dec!(50.00)),
+                taxable("P3", "exempt", dec!(20.00)),
+            ])
+            .unwrap();
+
+        // GST on standard + reduced, PST on standard only, nothing on exempt
+        assert_eq!(breakdown.lines[0].total(), dec!(12.00));
+        assert_eq!(breakdown.lines[1].total(), dec!(2.50));
+        assert!(breakdown.lines[2].taxes.is_empty());
+        assert_eq!(breakdown.total_tax, dec!(14.50));
+        assert_eq!(
+            breakdown.authority_totals(),
+            vec![("GST".to_string(), dec!(7.50)), ("PST".to_string(), dec!(7.00))]
+        );
+    }
+
+    #[test]
+    fn test_breakdown_hst_single_authority() {
+        let calculator = JurisdictionTaxCalculator::new(TaxJurisdiction::new(
+            "CA-ON".to_string(),
+            "Ontario".to_string(),
+            vec![rate("HST", dec!(13))],
+        ));
+        let breakdown = calculator
+            .calculate_breakdown(&[taxable("P1", "standard", dec!(19.99))])
+            .unwrap();
+
+        assert_eq!(breakdown.total_tax, dec!(2.60));
+        assert_eq!(breakdown.lines[0].taxes[0].taxable_amount, dec!(19.99));
+    }
+
+    #[test]
+    fn test_breakdown_us_state_and_county_rounds_per_authority() {
+        let calculator = JurisdictionTaxCalculator::new(TaxJurisdiction::new(
+            "US-IL-COOK".to_string(),
+            "Cook County, IL".to_string(),
+            vec![rate("STATE", dec!(6.25)), rate("COUNTY", dec!(1.75))],
+        ));
+        let lines = [
+            taxable("P1", "standard", dec!(3.33)),
+            taxable("P2", "standard", dec!(3.33)),
+            taxable("P3", "standard", dec!(3.33)),
+        ];
+        let breakdown = calculator.calculate_breakdown(&lines).unwrap();
+
+        // 9.99 × 6.25% = 0.624375 → 0.62; 9.99 × 1.75% = 0.174825 → 0.17
+        assert_eq!(breakdown.authority_totals()[0].1, dec!(0.62));
+        assert_eq!(breakdown.authority_totals()[1].1, dec!(0.17));
+        let line_sum: Decimal = breakdown.lines.iter().map(LineTaxBreakdown::total).sum();
+        assert_eq!(line_sum, breakdown.total_tax);
+        assert_eq!(breakdown.total_tax, dec!(0.79));
+    }
+
+    #[test]
+    fn test_breakdown_compound_rate() {
+        let calculator = JurisdictionTaxCalculator::new(TaxJurisdiction::new(
+            "COMPOUND".to_string(),
+            "Compound".to_string(),
+            vec![rate("GST", dec!(5)), rate("QST", dec!(10)).compound()],
+        ));
+        let breakdown = calculator
+            .calculate_breakdown(&[taxable("P1", "standard", dec!(100.00))])
+            .unwrap();
+
+        assert_eq!(breakdown.lines[0].taxes[1].taxable_amount, dec!(105.00));
+        assert_eq!(breakdown.total_tax, dec!(15.50));
+    }
+
+    #[test]
+    fn test_breakdown_with_exemption() {
+        let full = JurisdictionTaxCalculator::new(gst_pst()).with_exemption(Some(TaxExemption {
+            certificate_number: "EX-1".to_string(),
+            rate_codes: Vec::new(),
+        }));
+        let breakdown = full
+            .calculate_breakdown(&[taxable("P1", "standard", dec!(100.00))])
+            .unwrap();
+        assert_eq!(breakdown.total_tax, Decimal::ZERO);
+        assert_eq!(breakdown.exemption_certificate.as_deref(), Some("EX-1"));
+
+        let pst_only = JurisdictionTaxCalculator::new(gst_pst()).with_exemption(Some(TaxExemption {
+            certificate_number: "PST-1".to_string(),
+            rate_codes: vec!["PST".to_string()],
+        }));
+        let breakdown = pst_only
+            .calculate_breakdown(&[taxable("P1", "standard", dec!(100.00))])
+            .unwrap();
+        assert_eq!(breakdown.total_tax, dec!(5.00));
+    }
+
+    #[test]
+    fn test_jurisdiction_calculator_multi_tax_uses_jurisdiction_rates() {
+        let calculator = JurisdictionTaxCalculator::new(gst_pst());
+        assert_eq!(calculator.calculate_multi_tax(dec!(100.00), &[]).unwrap(), dec!(12.00));
+    }
+
+    fn vat() -> TaxJurisdiction {
+        TaxJurisdiction::new(
+            "GB".to_string(),
+            "United Kingdom".to_string(),
+            vec![
+                rate("VAT", dec!(20)).with_tax_classes(vec!["standard".to_string()]),
+                rate("VAT5", dec!(5)).with_tax_classes(vec!["reduced".to_string()]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_inclusive_breakdown_back_calculates_net_by_tax_class() {
+        let calculator =
+            JurisdictionTaxCalculator::new(vat()).with_price_mode(PriceMode::TaxInclusive);
+        let breakdown = calculator
+            .calculate_breakdown(&[
+                taxable("P1", "standard", dec!(12.00)),
+                taxable("P2", "reduced", dec!(10.50)),
+                taxable("P3", "exempt", dec!(5.00)),
+            ])
+            .unwrap();
+
+        assert_eq!(breakdown.lines[0].total(), dec!(2.00));
+        assert_eq!(breakdown.lines[0].net_amount, dec!(10.00));
+        assert_eq!(breakdown.lines[0].taxes[0].taxable_amount, dec!(10.00));
+        assert_eq!(breakdown.lines[1].total(), dec!(0.50));
+        assert_eq!(breakdown.lines[1].net_amount, dec!(10.00));
+        assert_eq!(breakdown.lines[2].net_amount, dec!(5.00));
+        assert_eq!(breakdown.total_tax, dec!(2.50));
+    }
+
+    #[test]
+    fn test_inclusive_rounding_per_line_vs_per_invoice() {
+        let lines = [
+            taxable("P1", "standard", dec!(9.99)),
+            taxable("P2", "standard", dec!(9.99)),
+            taxable("P3", "standard", dec!(9.99)),
+        ];
+        let calculator =
+            JurisdictionTaxCalculator::new(vat()).with_price_mode(PriceMode::TaxInclusive);
+
+        // Each line contains 1.665 of VAT
+        let per_line = calculator
+            .clone()
+            .with_rounding(TaxRounding::PerLine)
+            .calculate_breakdown(&lines)
+            .unwrap();
+        assert_eq!(per_line.total_tax, dec!(4.98));
+        assert!(per_line.lines.iter().all(|line| line.total() == dec!(1.66)));
+
+        let per_invoice = calculator
+            .with_rounding(TaxRounding::PerInvoice)
+            .calculate_breakdown(&lines)
+            .unwrap();
+        assert_eq!(per_invoice.total_tax, dec!(5.00));
+
+        for breakdown in [per_line, per_invoice] {
+            assert!(breakdown
+                .lines
+                .iter()
+                .all(|line| line.net_amount + line.total() == dec!(9.99)));
+        }
+    }
+
+    #[test]
+    fn test_inclusive_compound_rate() {
+        let calculator = JurisdictionTaxCalculator::new(TaxJurisdiction::new(
+            "COMPOUND".to_string(),
+            "Compound".to_string(),
+            vec![rate("GST", dec!(5)), rate("QST", dec!(10)).compound()],
+        ))
+        .with_price_mode(PriceMode::TaxInclusive);
+        let breakdown = calculator
+            .calculate_breakdown(&[taxable("P1", "standard", dec!(115.50))])
+            .unwrap();
+
+        assert_eq!(breakdown.lines[0].net_amount, dec!(100.00));
+        assert_eq!(breakdown.lines[0].taxes[0].tax_amount, dec!(5.00));
+        assert_eq!(breakdown.lines[0].taxes[1].tax_amount, dec!(10.50));
+        assert_eq!(breakdown.lines[0].taxes[1].taxable_amount, dec!(105.00));
+    }
+
+    #[test]
+    fn test_exclusive_per_line_rounding() {
+        let calculator = JurisdictionTaxCalculator::new(TaxJurisdiction::new(
+            "US-IL-COOK".to_string(),
+            "Cook County, IL".to_string(),
+            vec![rate("STATE", dec!(6.25))],
+        ))
+        .with_rounding(TaxRounding::PerLine);
+        let breakdown = calculator
+            .calculate_breakdown(&[
+                taxable("P1", "standard", dec!(3.33)),
+                taxable("P2", "standard", dec!(3.33)),
+                taxable("P3", "standard", dec!(3.33)),
+            ])
+            .unwrap();
+
+        // 3.33 × 6.25% = 0.208125 → 0.21 on every line
+        assert_eq!(breakdown.total_tax, dec!(0.63));
+        assert_eq!(breakdown.lines[0].net_amount, dec!(3.33));
+    }
+
+    #[test]
+    fn test_breakdown_rejects_negative_amount() {
+        let calculator = JurisdictionTaxCalculator::new(gst_pst());
+        assert!(calculator
+            .calculate_breakdown(&[taxable("P1", "standard", dec!(-1.00))])
+            .is_err());
+    }
 }
@@ -19,5 +19,5 @@ pub mod errors;
 pub use transaction::{Transaction, TransactionStatus, Payment};
 pub use pricing::{LineItem, PricingEngine};
 pub use discount::{Discount, DiscountType};
-pub use tax::TaxRate;
+pub use tax::{PriceMode, TaxRate, TaxRounding, TAX_CLASS_EXEMPT, TAX_CLASS_REDUCED, TAX_CLASS_STANDARD};
 pub use errors::{DomainError, DomainResult};
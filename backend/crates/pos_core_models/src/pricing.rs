@@ -3,6 +3,7 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use crate::errors::DomainResult;
+use crate::tax::TAX_CLASS_STANDARD;
 
 /// Line item for pricing calculations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +12,9 @@ pub struct LineItem {
     pub quantity: Decimal,
     pub unit_price: Decimal,
     pub line_total: Option<Decimal>, // Pre-calculated if available
+    /// Tax class of the item; `None` is taxed as standard
+    #[serde(default)]
+    pub tax_class: Option<String>,
 }
 
 impl LineItem {
@@ -22,9 +26,23 @@ impl LineItem {
             quantity,
             unit_price,
             line_total: None,
+            tax_class: None,
         }
     }
 
+    /// Set the tax class of the item
+    #[must_use]
+    pub fn with_tax_class(mut self, tax_class: String) -> Self {
+        self.tax_class = Some(tax_class);
+        self
+    }
+
+    /// Tax class of the item, defaulting to standard
+    #[must_use]
+    pub fn tax_class(&self) -> &str {
+        self.tax_class.as_deref().unwrap_or(TAX_CLASS_STANDARD)
+    }
+
     /// Calculate the line total (quantity × `unit_price`)
     #[must_use] 
     pub fn calculate_line_total(&self) -> Decimal {
@@ -62,4 +80,13 @@ mod tests {
         let item = LineItem::new("PROD-001".to_string(), dec!(2.0), dec!(10.50));
         assert_eq!(item.calculate_line_total(), dec!(21.00));
     }
+
+    #[test]
+    fn test_line_item_tax_class_defaults_to_standard() {
+        let item = LineItem::new("PROD-001".to_string(), dec!(1), dec!(5.00));
+        assert_eq!(item.tax_class(), TAX_CLASS_STANDARD);
+
+        let item = item.with_tax_class("exempt".to_string());
+        assert_eq!(item.tax_class(), "exempt");
+    }
 }
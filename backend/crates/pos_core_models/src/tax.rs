@@ -4,12 +4,48 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use crate::errors::{DomainError, DomainResult};
 
+/// Tax class for goods taxed at the full rate
+pub const TAX_CLASS_STANDARD: &str = "standard";
+/// Tax class for goods taxed at a reduced rate (e.g. some food items)
+pub const TAX_CLASS_REDUCED: &str = "reduced";
+/// Tax class for goods that are never taxed
+pub const TAX_CLASS_EXEMPT: &str = "exempt";
+
+/// Whether shelf prices include tax
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceMode {
+    /// Tax is added on top of the price (North American sales tax)
+    #[default]
+    TaxExclusive,
+    /// Prices are gross and tax is back-calculated from them (VAT markets)
+    TaxInclusive,
+}
+
+/// Where tax amounts are rounded to the cent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxRounding {
+    /// Each line's tax is rounded on its own
+    PerLine,
+    /// Each rate is rounded once on the invoice total and allocated to lines
+    #[default]
+    PerInvoice,
+}
+
 /// Tax rate configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaxRate {
+    /// Rate code; identifies the taxing authority (e.g. "GST", "PST", "HST")
     pub rate_code: String,
     pub rate_percent: Decimal,
     pub label: String,
+    /// Tax classes this rate applies to; empty means every non-exempt class
+    #[serde(default)]
+    pub tax_classes: Vec<String>,
+    /// Whether the rate is charged on the amount plus the preceding taxes
+    #[serde(default)]
+    pub compound: bool,
 }
 
 impl TaxRate {
@@ -31,8 +67,35 @@ impl TaxRate {
             rate_code,
             rate_percent,
             label,
+            tax_classes: Vec::new(),
+            compound: false,
         })
     }
+
+    /// Restrict the rate to the given tax classes
+    #[must_use]
+    pub fn with_tax_classes(mut self, tax_classes: Vec<String>) -> Self {
+        self.tax_classes = tax_classes;
+        self
+    }
+
+    /// Charge the rate on the amount plus the taxes that precede it
+    #[must_use]
+    pub const fn compound(mut self) -> Self {
+        self.compound = true;
+        self
+    }
+
+    /// Whether the rate applies to items of `tax_class`
+    ///
+    /// Exempt items are never taxed.
+    #[must_use]
+    pub fn applies_to(&self, tax_class: &str) -> bool {
+        if tax_class == TAX_CLASS_EXEMPT {
+            return false;
+        }
+        self.tax_classes.is_empty() || self.tax_classes.iter().any(|class| class == tax_class)
+    }
 }
 
 #[cfg(test)]
@@ -59,4 +122,29 @@ mod tests {
         let result = TaxRate::new("TAX".to_string(), dec!(101.0), "Invalid".to_string());
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_price_mode_and_rounding_serialization() {
+        assert_eq!(serde_json::to_string(&PriceMode::TaxInclusive).unwrap(), "\"tax_inclusive\"");
+        assert_eq!(
+            serde_json::from_str::<TaxRounding>("\"per_line\"").unwrap(),
+            TaxRounding::PerLine
+        );
+        assert_eq!(PriceMode::default(), PriceMode::TaxExclusive);
+        assert_eq!(TaxRounding::default(), TaxRounding::PerInvoice);
+    }
+
+    #[test]
+    fn test_tax_rate_applies_to_classes() {
+        let all = TaxRate::new("GST".to_string(), dec!(5), "GST".to_string()).unwrap();
+        assert!(all.applies_to(TAX_CLASS_STANDARD));
+        assert!(all.applies_to(TAX_CLASS_REDUCED));
+        assert!(!all.applies_to(TAX_CLASS_EXEMPT));
+
+        let standard_only = TaxRate::new("PST".to_string(), dec!(7), "PST".to_string())
+            .unwrap()
+            .with_tax_classes(vec![TAX_CLASS_STANDARD.to_string()]);
+        assert!(standard_only.applies_to(TAX_CLASS_STANDARD));
+        assert!(!standard_only.applies_to(TAX_CLASS_REDUCED));
+    }
 }
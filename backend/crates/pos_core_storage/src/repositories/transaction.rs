@@ -350,6 +350,7 @@ impl TransactionRepository {
                 quantity,
                 unit_price,
                 line_total,
+                tax_class: None,
             });
         }
 
@@ -1,4 +1,4 @@
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 
@@ -12,7 +12,15 @@ pub struct Claims {
     pub station_id: Option<String>,  // Station ID (if assigned)
     pub exp: i64,              // Expiration time
     pub iat: i64,              // Issued at
-    pub permissions: Vec<String>,    // User permissions
+    /// Issued at, in milliseconds; `iat` is whole seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat_ms: Option<i64>,
+    /// Permissions resolved at sign-in; `None` if they never were
+    #[serde(default)]
+    pub permissions: Option<Vec<String>>,
+    /// pin or badge for a station sign-in; those sessions end when idle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_method: Option<String>,
 }
 
 #[derive(Debug)]
@@ -34,7 +42,19 @@ impl std::fmt::Display for JwtError {
 
 impl std::error::Error for JwtError {}
 
+impl Claims {
+    /// When the token was issued, to the millisecond where the token says
+    pub fn issued_at(&self) -> DateTime<Utc> {
+        self.iat_ms
+            .and_then(DateTime::from_timestamp_millis)
+            .or_else(|| DateTime::from_timestamp(self.iat, 0))
+            .unwrap_or(DateTime::UNIX_EPOCH)
+    }
+}
+
 /// Generate a JWT token for a user
+///
+/// The token carries no permissions, so the role's built-in permissions apply.
 pub fn generate_token(
     user_id: &str,
     username: &str,
@@ -44,6 +64,55 @@ pub fn generate_token(
     station_id: Option<String>,
     secret: &str,
     expiration_hours: i64,
+) -> Result<String, JwtError> {
+    generate(
+        user_id,
+        username,
+        role,
+        tenant_id,
+        store_id,
+        station_id,
+        None,
+        secret,
+        expiration_hours,
+    )
+}
+
+/// Generate a JWT token carrying the user's resolved permissions
+pub fn generate_token_with_permissions(
+    user_id: &str,
+    username: &str,
+    role: &str,
+    tenant_id: &str,
+    store_id: Option<String>,
+    station_id: Option<String>,
+    permissions: Vec<String>,
+    secret: &str,
+    expiration_hours: i64,
+) -> Result<String, JwtError> {
+    generate(
+        user_id,
+        username,
+        role,
+        tenant_id,
+        store_id,
+        station_id,
+        Some(permissions),
+        secret,
+        expiration_hours,
+    )
+}
+
+fn generate(
+    user_id: &str,
+    username: &str,
+    role: &str,
+    tenant_id: &str,
+    store_id: Option<String>,
+    station_id: Option<String>,
+    permissions: Option<Vec<String>>,
+    secret: &str,
+    expiration_hours: i64,
 ) -> Result<String, JwtError> {
     let now = Utc::now();
     let exp = now + Duration::hours(expiration_hours);
@@ -57,12 +126,19 @@ pub fn generate_token(
         station_id,
         exp: exp.timestamp(),
         iat: now.timestamp(),
-        permissions: vec![], // Will be populated by auth handler
+        iat_ms: Some(now.timestamp_millis()),
+        permissions,
+        login_method: None,
     };
 
+    encode_token(&claims, secret)
+}
+
+/// Sign a token for claims built by the caller
+pub fn encode_token(claims: &Claims, secret: &str) -> Result<String, JwtError> {
     encode(
         &Header::default(),
-        &claims,
+        claims,
         &EncodingKey::from_secret(secret.as_bytes()),
     )
     .map_err(|_| JwtError::EncodingError)
@@ -107,6 +183,19 @@ mod tests {
         assert_eq!(claims.station_id, None);
     }
 
+    #[test]
+    fn test_issued_at_to_the_millisecond() {
+        let token = generate_token("user-001", "testuser", "admin", crate::test_constants::TEST_TENANT_ID, None, None, TEST_SECRET, 8).unwrap();
+        let mut claims = validate_token(&token, TEST_SECRET).unwrap();
+        assert_eq!(claims.permissions, None);
+        assert_eq!(Some(claims.issued_at().timestamp_millis()), claims.iat_ms);
+        assert_eq!(claims.issued_at().timestamp(), claims.iat);
+
+        // Tokens issued before the claim existed
+        claims.iat_ms = None;
+        assert_eq!(claims.issued_at().timestamp_millis(), claims.iat * 1000);
+    }
+
     #[test]
     fn test_generate_token_with_context() {
         let token = generate_token(
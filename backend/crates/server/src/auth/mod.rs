@@ -8,5 +8,5 @@
 pub mod jwt;
 pub mod password;
 
-pub use jwt::{generate_token, validate_token};
+pub use jwt::{encode_token, generate_token, generate_token_with_permissions, validate_token};
 pub use password::{hash_password, verify_password};
@@ -7,7 +7,8 @@
  */
 
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::env;
 
@@ -20,6 +21,9 @@ use super::oauth::CloverTokens;
 // Client Configuration
 // ============================================================================
 
+/// Records per page when listing; Clover allows up to 1000
+const PAGE_SIZE: usize = 100;
+
 /// Clover API client
 #[derive(Clone)]
 pub struct CloverClient {
@@ -57,6 +61,83 @@ impl CloverClient {
         })
     }
     
+    /// Point the client at another API root (a proxy, or a mock in tests)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Make a GET request under the merchant and parse the JSON response
+    pub async fn get<T: DeserializeOwned>(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<T, ApiError> {
+        let url = format!(
+            "{}/merchants/{}/{}",
+            self.base_url,
+            self.merchant_id,
+            endpoint.trim_start_matches('/')
+        );
+
+        tracing::debug!("Clover GET: {}", url);
+
+        let response = self.http_client
+            .get(&url)
+            .query(query)
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .map_err(|e| ApiError::internal(format!("Clover API request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            tracing::error!("Clover API error ({}): {}", status, error_text);
+
+            return match status {
+                StatusCode::UNAUTHORIZED => Err(ApiError::unauthorized("Invalid Clover access token")),
+                StatusCode::FORBIDDEN => Err(ApiError::forbidden("Access denied to Clover resource")),
+                StatusCode::NOT_FOUND => Err(ApiError::not_found("Clover resource not found")),
+                StatusCode::TOO_MANY_REQUESTS => Err(ApiError::internal("Clover rate limit exceeded")),
+                _ => Err(ApiError::internal(format!(
+                    "Clover API error ({}): {}",
+                    status, error_text
+                ))),
+            };
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ApiError::internal(format!("Failed to parse Clover response: {}", e)))
+    }
+
+    /// Fetch every element of a list endpoint, a page at a time
+    pub async fn get_all<T: DeserializeOwned>(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<Vec<T>, ApiError> {
+        let mut all_elements = Vec::new();
+        let limit = PAGE_SIZE.to_string();
+        let mut offset = 0;
+
+        loop {
+            let offset_param = offset.to_string();
+            let mut page_query = query.to_vec();
+            page_query.push(("limit", limit.as_str()));
+            page_query.push(("offset", offset_param.as_str()));
+
+            let page: Elements<T> = self.get(endpoint, &page_query).await?;
+            let fetched = page.elements.len();
+            all_elements.extend(page.elements);
+
+            if fetched < PAGE_SIZE {
+                break;
+            }
+            offset += fetched;
+        }
+
+        Ok(all_elements)
+    }
+
     /// Get merchant summary
     /// 
     /// Requirements: 3.9
@@ -137,6 +218,25 @@ impl PlatformConnector for CloverClient {
 // Response Types
 // ============================================================================
 
+/// A list as Clover returns it, for pages and expanded relations alike
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Elements<T> {
+    #[serde(default = "Vec::new")]
+    pub elements: Vec<T>,
+}
+
+impl<T> Default for Elements<T> {
+    fn default() -> Self {
+        Self { elements: Vec::new() }
+    }
+}
+
+/// Reference to another Clover object by ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdRef {
+    pub id: String,
+}
+
 /// Clover merchant summary for display
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloverSummary {
@@ -0,0 +1,52 @@
+/**
+ * Clover Customers API
+ *
+ * Lists the customer directory with contact details.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::client::{CloverClient, Elements};
+use crate::models::ApiError;
+
+/// A customer profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloverCustomer {
+    pub id: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    #[serde(default)]
+    pub email_addresses: Elements<CloverEmailAddress>,
+    #[serde(default)]
+    pub phone_numbers: Elements<CloverPhoneNumber>,
+    /// Milliseconds since the epoch
+    pub customer_since: Option<i64>,
+    pub modified_time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloverEmailAddress {
+    pub email_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloverPhoneNumber {
+    pub phone_number: Option<String>,
+}
+
+impl CloverClient {
+    /// Fetch every customer; `modified_since` (epoch milliseconds) leaves
+    /// out customers not changed since
+    pub async fn get_all_customers(&self, modified_since: Option<i64>) -> Result<Vec<CloverCustomer>, ApiError> {
+        let filter = modified_since.map(|since| format!("modifiedTime>={}", since));
+        let mut query = vec![("expand", "emailAddresses,phoneNumbers")];
+        if let Some(filter) = &filter {
+            query.push(("filter", filter));
+        }
+
+        self.get_all("customers", &query).await
+    }
+}
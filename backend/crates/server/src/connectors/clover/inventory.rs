@@ -0,0 +1,73 @@
+/**
+ * Clover Inventory API
+ *
+ * Reads items, categories and item stock levels. Field names follow
+ * Clover's, so field mappings can refer to them as Clover documents them.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::client::{CloverClient, Elements, IdRef};
+use crate::models::ApiError;
+
+/// An inventory item; prices are in cents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloverItem {
+    pub id: String,
+    pub name: Option<String>,
+    pub alternate_name: Option<String>,
+    pub sku: Option<String>,
+    /// Product code, usually the UPC or EAN
+    pub code: Option<String>,
+    pub price: Option<i64>,
+    /// `FIXED`, `VARIABLE` or `PER_UNIT`
+    pub price_type: Option<String>,
+    pub cost: Option<i64>,
+    pub hidden: Option<bool>,
+    /// Milliseconds since the epoch
+    pub modified_time: Option<i64>,
+    #[serde(default)]
+    pub categories: Elements<CloverCategory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloverCategory {
+    pub id: String,
+    pub name: Option<String>,
+    pub sort_order: Option<i64>,
+}
+
+/// Units of an item on hand at the merchant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloverItemStock {
+    pub item: IdRef,
+    pub quantity: Option<f64>,
+    pub modified_time: Option<i64>,
+}
+
+impl CloverClient {
+    /// Fetch every item with its categories; `modified_since` (epoch
+    /// milliseconds) leaves out items not changed since
+    pub async fn get_all_items(&self, modified_since: Option<i64>) -> Result<Vec<CloverItem>, ApiError> {
+        let filter = modified_since.map(|since| format!("modifiedTime>={}", since));
+        let mut query = vec![("expand", "categories")];
+        if let Some(filter) = &filter {
+            query.push(("filter", filter));
+        }
+
+        self.get_all("items", &query).await
+    }
+
+    /// Fetch every category
+    pub async fn get_all_categories(&self) -> Result<Vec<CloverCategory>, ApiError> {
+        self.get_all("categories", &[]).await
+    }
+
+    /// Fetch the stock level of every tracked item
+    pub async fn get_all_item_stocks(&self) -> Result<Vec<CloverItemStock>, ApiError> {
+        self.get_all("item_stocks", &[]).await
+    }
+}
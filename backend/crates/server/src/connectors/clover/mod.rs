@@ -1,13 +1,17 @@
 /**
  * Clover Connector
  * 
- * Provides connectivity to Clover via OAuth authentication.
+ * Provides connectivity to Clover via OAuth authentication, and reads
+ * items, stock, customers and orders for syncing them into the POS.
  * 
  * Requirements: 3.2, 3.7, 6.6
  */
 
 pub mod client;
+pub mod customers;
+pub mod inventory;
 pub mod oauth;
+pub mod orders;
 
 pub use client::CloverClient;
 pub use oauth::{CloverOAuth, CloverTokens};
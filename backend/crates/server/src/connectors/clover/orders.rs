@@ -0,0 +1,74 @@
+/**
+ * Clover Orders API
+ *
+ * Reads paid orders with their line items, for bringing sales over.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::client::{CloverClient, Elements, IdRef};
+use crate::models::ApiError;
+
+/// Payment state of an order that has been paid in full
+pub const PAYMENT_STATE_PAID: &str = "PAID";
+
+/// An order; amounts are in cents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloverOrder {
+    pub id: String,
+    pub currency: Option<String>,
+    pub total: Option<i64>,
+    pub state: Option<String>,
+    pub payment_state: Option<String>,
+    pub note: Option<String>,
+    #[serde(default)]
+    pub customers: Elements<IdRef>,
+    #[serde(default)]
+    pub line_items: Elements<CloverLineItem>,
+    /// Milliseconds since the epoch
+    pub created_time: Option<i64>,
+    pub modified_time: Option<i64>,
+}
+
+/// One unit of an item sold, or a weighed quantity of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloverLineItem {
+    pub id: String,
+    pub name: Option<String>,
+    pub price: Option<i64>,
+    /// Thousandths of a unit, for items sold by weight or measure
+    pub unit_qty: Option<i64>,
+    /// The inventory item sold; absent for custom amounts
+    pub item: Option<IdRef>,
+    pub refunded: Option<bool>,
+}
+
+impl CloverLineItem {
+    /// Units sold: one, unless the line carries a measured quantity
+    pub fn quantity(&self) -> f64 {
+        self.unit_qty.map_or(1.0, |qty| qty as f64 / 1000.0)
+    }
+}
+
+impl CloverOrder {
+    /// Whether the order has been paid in full
+    pub fn is_paid(&self) -> bool {
+        self.payment_state.as_deref() == Some(PAYMENT_STATE_PAID)
+    }
+}
+
+impl CloverClient {
+    /// Fetch every order with its line items and customers;
+    /// `modified_since` (epoch milliseconds) leaves out older orders
+    pub async fn get_all_orders(&self, modified_since: Option<i64>) -> Result<Vec<CloverOrder>, ApiError> {
+        let filter = modified_since.map(|since| format!("modifiedTime>={}", since));
+        let mut query = vec![("expand", "lineItems,customers")];
+        if let Some(filter) = &filter {
+            query.push(("filter", filter));
+        }
+
+        self.get_all("orders", &query).await
+    }
+}
@@ -8,6 +8,7 @@
  * - Stripe (payments via Connect)
  * - Square (payments)
  * - Clover (payments)
+ * - Shopify (e-commerce)
  */
 
 pub mod common;
@@ -18,6 +19,7 @@ pub mod google_drive;
 pub mod stripe;
 pub mod square;
 pub mod clover;
+pub mod shopify;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
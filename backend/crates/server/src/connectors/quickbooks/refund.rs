@@ -2,12 +2,20 @@
  * QuickBooks Refund Operations
  * 
  * CRUD operations for QuickBooks refunds (CreditMemo for store credit, RefundReceipt for money-out)
+ * and conversion of POS returns (credit-memo accounting snapshots) into either document.
  * 
  * Requirements: 11.6
  */
 
+use std::collections::HashMap;
+
+use accounting_snapshots::{AccountingSnapshot, SnapshotKind};
+use pos_core_domain::allocate_proportionally;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use crate::models::ApiError;
+use crate::services::checkout_service::TENDER_STORE_CREDIT;
 use super::client::QuickBooksClient;
 
 /// QuickBooks CreditMemo (store credit refund)
@@ -177,7 +185,126 @@ pub struct RefundReceiptResponse {
     pub refund_receipt: QBRefundReceipt,
 }
 
+/// QuickBooks document for a POS return
+#[derive(Debug, Clone)]
+pub enum QBRefundDocument {
+    /// Refunded entirely to store credit
+    CreditMemo(QBCreditMemo),
+    /// Money handed back (cash, card, gift card)
+    RefundReceipt(QBRefundReceipt),
+}
+
+// ============================================================================
+// Snapshot Transformation
+// ============================================================================
+
+/// Transform a credit-memo accounting snapshot into the QuickBooks refund document
+/// 
+/// Returns refunded entirely to store credit become a CreditMemo; anything
+/// paid back in money becomes a RefundReceipt. `item_refs` maps POS product
+/// ids to QuickBooks item ids. Discounts are spread over the lines so the
+/// line amounts sum to the refunded amount before tax.
+pub fn transform_return(
+    snapshot: &AccountingSnapshot,
+    doc_number: &str,
+    customer_qb_id: &str,
+    item_refs: &HashMap<String, String>,
+) -> Result<QBRefundDocument, ApiError> {
+    let SnapshotKind::CreditMemo { original_transaction_id, reason } = &snapshot.kind else {
+        return Err(ApiError::validation_msg("Snapshot is not a credit memo"));
+    };
+    
+    let line_totals: Vec<Decimal> = snapshot.lines.iter().map(|line| line.line_total).collect();
+    let discounts = allocate_proportionally(snapshot.discount, &line_totals, 2);
+    
+    let mut lines = Vec::with_capacity(snapshot.lines.len());
+    for (index, (line, discount)) in snapshot.lines.iter().zip(discounts).enumerate() {
+        let item_id = item_refs.get(&line.product_id).ok_or_else(|| {
+            ApiError::validation_msg(format!("No QuickBooks item mapped for product {}", line.product_id))
+        })?;
+        let amount = line.line_total - discount;
+        
+        lines.push(Line {
+            id: None,
+            line_num: i32::try_from(index + 1).ok(),
+            description: Some(line.description.clone()),
+            amount: amount.to_f64().unwrap_or_default(),
+            detail_type: "SalesItemLineDetail".to_string(),
+            sales_item_line_detail: Some(SalesItemLineDetail {
+                item_ref: ItemRef {
+                    value: item_id.clone(),
+                    name: Some(line.description.clone()),
+                },
+                unit_price: (!line.quantity.is_zero())
+                    .then(|| (amount / line.quantity).round_dp(2).to_f64().unwrap_or_default()),
+                qty: line.quantity.to_f64(),
+            }),
+        });
+    }
+    
+    let customer_ref = CustomerRef {
+        value: customer_qb_id.to_string(),
+        name: None,
+    };
+    let txn_date = Some(snapshot.finalized_at.format("%Y-%m-%d").to_string());
+    let total_amt = snapshot.total.to_f64();
+    let customer_memo = Some(Memo {
+        value: match reason {
+            Some(reason) => format!("Return of {original_transaction_id} - {reason}"),
+            None => format!("Return of {original_transaction_id}"),
+        },
+    });
+    
+    let money_out = snapshot
+        .payments
+        .iter()
+        .find(|payment| payment.method != TENDER_STORE_CREDIT);
+    
+    Ok(match money_out {
+        None => QBRefundDocument::CreditMemo(QBCreditMemo {
+            id: None,
+            sync_token: None,
+            doc_number: Some(doc_number.to_string()),
+            txn_date,
+            customer_ref,
+            line: lines,
+            total_amt,
+            remaining_credit: total_amt,
+            customer_memo,
+            meta_data: None,
+        }),
+        Some(payment) => QBRefundDocument::RefundReceipt(QBRefundReceipt {
+            id: None,
+            sync_token: None,
+            doc_number: Some(doc_number.to_string()),
+            txn_date,
+            customer_ref,
+            line: lines,
+            total_amt,
+            payment_method_ref: Some(PaymentMethodRef {
+                value: payment.method.clone(),
+                name: Some(payment.method.clone()),
+            }),
+            deposit_to_account_ref: None,
+            customer_memo,
+            meta_data: None,
+        }),
+    })
+}
+
 impl QuickBooksClient {
+    /// Create the CreditMemo or RefundReceipt for a POS return
+    pub async fn create_refund_document(&self, document: &QBRefundDocument) -> Result<QBRefundDocument, ApiError> {
+        match document {
+            QBRefundDocument::CreditMemo(credit_memo) => {
+                self.create_credit_memo(credit_memo).await.map(QBRefundDocument::CreditMemo)
+            }
+            QBRefundDocument::RefundReceipt(refund_receipt) => {
+                self.create_refund_receipt(refund_receipt).await.map(QBRefundDocument::RefundReceipt)
+            }
+        }
+    }
+    
     /// Get credit memo by ID
     pub async fn get_credit_memo(&self, credit_memo_id: &str) -> Result<QBCreditMemo, ApiError> {
         let endpoint = format!("creditmemo/{}", credit_memo_id);
@@ -362,4 +489,58 @@ mod tests {
         assert!(json.contains("CustomerRef"));
         assert!(json.contains("PaymentMethodRef"));
     }
+    
+    fn return_snapshot(refund_method: &str) -> AccountingSnapshot {
+        use accounting_snapshots::{Payment, SnapshotLine};
+        use std::str::FromStr;
+        let dec = |value: &str| Decimal::from_str(value).unwrap();
+        
+        AccountingSnapshot::new(
+            uuid::Uuid::new_v4(),
+            chrono::Utc::now(),
+            dec("30.00"),
+            dec("2.70"),
+            dec("3.00"),
+            dec("29.70"),
+            vec![Payment { method: refund_method.to_string(), amount: dec("29.70") }],
+            vec![
+                SnapshotLine::new("p1".into(), "Widget".into(), dec("2"), dec("10.00"), dec("20.00"), dec("1.80")),
+                SnapshotLine::new("p2".into(), "Gadget".into(), dec("1"), dec("10.00"), dec("10.00"), dec("0.90")),
+            ],
+        )
+        .as_credit_memo(uuid::Uuid::new_v4(), Some("Damaged".to_string()))
+    }
+    
+    #[test]
+    fn test_transform_return_picks_document_by_refund_method() {
+        let item_refs: HashMap<String, String> =
+            [("p1".to_string(), "11".to_string()), ("p2".to_string(), "12".to_string())].into();
+        
+        let document = transform_return(&return_snapshot("store_credit"), "RTN-1", "7", &item_refs).unwrap();
+        let QBRefundDocument::CreditMemo(memo) = document else {
+            panic!("store credit refund should become a credit memo");
+        };
+        // 3.00 discount spread 2.00 / 1.00 over the lines
+        assert_eq!(memo.line[0].amount, 18.0);
+        assert_eq!(memo.line[1].amount, 9.0);
+        assert_eq!(memo.total_amt, Some(29.7));
+        assert_eq!(memo.line[0].sales_item_line_detail.as_ref().unwrap().item_ref.value, "11");
+        
+        let document = transform_return(&return_snapshot("cash"), "RTN-1", "7", &item_refs).unwrap();
+        let QBRefundDocument::RefundReceipt(receipt) = document else {
+            panic!("cash refund should become a refund receipt");
+        };
+        assert_eq!(receipt.payment_method_ref.unwrap().value, "cash");
+        assert!(receipt.customer_memo.unwrap().value.ends_with("Damaged"));
+    }
+    
+    #[test]
+    fn test_transform_return_requires_item_mapping_and_credit_memo() {
+        let item_refs: HashMap<String, String> = [("p1".to_string(), "11".to_string())].into();
+        assert!(transform_return(&return_snapshot("cash"), "RTN-1", "7", &item_refs).is_err());
+        
+        let mut sale = return_snapshot("cash");
+        sale.kind = SnapshotKind::Sale;
+        assert!(transform_return(&sale, "TXN-1", "7", &HashMap::new()).is_err());
+    }
 }
@@ -0,0 +1,371 @@
+/**
+ * Shopify Admin API Client
+ *
+ * HTTP client for the Shopify Admin REST and GraphQL APIs, authenticated
+ * with the access token from the app install.
+ */
+
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::connectors::{ConnectionStatus, PlatformConnector};
+use crate::models::errors::ApiError;
+
+use super::oauth::ShopifyTokens;
+
+// ============================================================================
+// Client Configuration
+// ============================================================================
+
+/// Admin API version every request is pinned to
+pub const API_VERSION: &str = "2024-01";
+
+/// Records per page when listing; Shopify allows up to 250
+const PAGE_SIZE: &str = "250";
+
+/// Shopify Admin API client
+#[derive(Clone)]
+pub struct ShopifyClient {
+    /// HTTP client
+    http_client: Client,
+    /// Admin API access token
+    access_token: String,
+    /// Shop domain, e.g. `example.myshopify.com`
+    shop_domain: String,
+    /// Base URL for the Admin API
+    base_url: String,
+}
+
+impl ShopifyClient {
+    /// Create new Shopify client from tokens
+    pub fn new(tokens: ShopifyTokens) -> Result<Self, ApiError> {
+        let http_client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| ApiError::internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        let base_url = format!("https://{}/admin/api/{}", tokens.shop_domain, API_VERSION);
+
+        Ok(Self {
+            http_client,
+            access_token: tokens.access_token,
+            shop_domain: tokens.shop_domain,
+            base_url,
+        })
+    }
+
+    /// Point the client at another API root (a proxy, or a mock in tests)
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Make a GET request and parse the JSON response
+    pub async fn get<T: DeserializeOwned>(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<T, ApiError> {
+        let url = self.url(endpoint);
+
+        tracing::debug!("Shopify GET: {}", url);
+
+        let (body, _) = self.send(self.http_client.get(&url).query(query)).await?;
+        Ok(body)
+    }
+
+    /// Make a PUT request and parse the JSON response
+    pub async fn put<B: Serialize, T: DeserializeOwned>(&self, endpoint: &str, body: &B) -> Result<T, ApiError> {
+        let url = self.url(endpoint);
+
+        tracing::debug!("Shopify PUT: {}", url);
+
+        let (body, _) = self.send(self.http_client.put(&url).json(body)).await?;
+        Ok(body)
+    }
+
+    /// Make a POST request and parse the JSON response
+    pub async fn post<B: Serialize, T: DeserializeOwned>(&self, endpoint: &str, body: &B) -> Result<T, ApiError> {
+        let url = self.url(endpoint);
+
+        tracing::debug!("Shopify POST: {}", url);
+
+        let (body, _) = self.send(self.http_client.post(&url).json(body)).await?;
+        Ok(body)
+    }
+
+    /// Fetch every record of a list endpoint, following the cursor in the
+    /// `Link` header; `key` is the field the records come in
+    pub async fn get_all<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        key: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>, ApiError> {
+        let url = self.url(endpoint);
+        let mut all_records = Vec::new();
+        let mut page_info: Option<String> = None;
+
+        loop {
+            // A cursor carries the original filters; Shopify rejects them alongside it
+            let mut page_query = vec![("limit", PAGE_SIZE)];
+            match &page_info {
+                Some(cursor) => page_query.push(("page_info", cursor.as_str())),
+                None => page_query.extend_from_slice(query),
+            }
+
+            tracing::debug!("Shopify GET: {}", url);
+
+            let (mut page, headers): (serde_json::Value, HeaderMap) =
+                self.send(self.http_client.get(&url).query(&page_query)).await?;
+            let records: Vec<T> = serde_json::from_value(page.get_mut(key).map(serde_json::Value::take).unwrap_or_default())
+                .map_err(|e| ApiError::internal(format!("Failed to parse Shopify {}: {}", key, e)))?;
+            all_records.extend(records);
+
+            page_info = headers
+                .get("link")
+                .and_then(|v| v.to_str().ok())
+                .and_then(next_page_info);
+            if page_info.is_none() {
+                break;
+            }
+        }
+
+        Ok(all_records)
+    }
+
+    /// Run a GraphQL Admin API query and return its `data`
+    pub async fn graphql<T: DeserializeOwned>(&self, query: &str, variables: serde_json::Value) -> Result<T, ApiError> {
+        let url = self.url("graphql.json");
+
+        tracing::debug!("Shopify GraphQL: {}", url);
+
+        let body = serde_json::json!({ "query": query, "variables": variables });
+        let (response, _): (GraphQlResponse<T>, HeaderMap) = self.send(self.http_client.post(&url).json(&body)).await?;
+
+        if let Some(error) = response.errors.as_ref().and_then(|e| e.first()) {
+            return Err(ApiError::internal(format!("Shopify GraphQL error: {}", error.message)));
+        }
+        response
+            .data
+            .ok_or_else(|| ApiError::internal("Shopify GraphQL response had no data"))
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}/{}", self.base_url, endpoint.trim_start_matches('/'))
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<(T, HeaderMap), ApiError> {
+        let response = request
+            .header("X-Shopify-Access-Token", &self.access_token)
+            .send()
+            .await
+            .map_err(|e| ApiError::internal(format!("Shopify API request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            tracing::error!("Shopify API error ({}): {}", status, error_text);
+
+            return match status {
+                StatusCode::UNAUTHORIZED => Err(ApiError::unauthorized("Invalid Shopify access token")),
+                StatusCode::FORBIDDEN => Err(ApiError::forbidden("Access denied to Shopify resource")),
+                StatusCode::NOT_FOUND => Err(ApiError::not_found("Shopify resource not found")),
+                StatusCode::TOO_MANY_REQUESTS => Err(ApiError::internal("Shopify rate limit exceeded")),
+                _ => Err(ApiError::internal(format!(
+                    "Shopify API error ({}): {}",
+                    status, error_text
+                ))),
+            };
+        }
+
+        let headers = response.headers().clone();
+        let body = response
+            .json()
+            .await
+            .map_err(|e| ApiError::internal(format!("Failed to parse Shopify response: {}", e)))?;
+        Ok((body, headers))
+    }
+
+    /// Get the shop's name and currency
+    pub async fn get_shop_summary(&self) -> Result<ShopifySummary, ApiError> {
+        let data: ShopQuery = self
+            .graphql("{ shop { name myshopifyDomain currencyCode } }", serde_json::json!({}))
+            .await?;
+
+        Ok(ShopifySummary {
+            shop_name: data.shop.name,
+            shop_domain: data.shop.myshopify_domain,
+            currency: data.shop.currency_code,
+        })
+    }
+
+    /// Get the shop domain
+    pub fn shop_domain(&self) -> &str {
+        &self.shop_domain
+    }
+}
+
+// ============================================================================
+// PlatformConnector Implementation
+// ============================================================================
+
+#[async_trait]
+impl PlatformConnector for ShopifyClient {
+    /// Test connection to Shopify
+    async fn test_connection(&self) -> Result<bool, ApiError> {
+        Ok(self.get_shop_summary().await.is_ok())
+    }
+
+    fn platform_name(&self) -> &str {
+        "shopify"
+    }
+
+    async fn get_status(&self) -> Result<ConnectionStatus, ApiError> {
+        let is_connected = self.test_connection().await.unwrap_or(false);
+
+        Ok(ConnectionStatus {
+            platform: "shopify".to_string(),
+            is_connected,
+            last_check: chrono::Utc::now().to_rfc3339(),
+            error_message: None,
+        })
+    }
+}
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+/// Shopify shop summary for display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopifySummary {
+    pub shop_name: String,
+    pub shop_domain: String,
+    pub currency: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQlError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShopQuery {
+    shop: Shop,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Shop {
+    name: String,
+    myshopify_domain: String,
+    currency_code: String,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// The `page_info` cursor of the `rel="next"` link, if there is one
+fn next_page_info(link_header: &str) -> Option<String> {
+    link_header
+        .split(',')
+        .find(|link| link.contains("rel=\"next\""))
+        .and_then(|link| {
+            let url = link.trim().trim_start_matches('<').split('>').next()?;
+            url::Url::parse(url)
+                .ok()?
+                .query_pairs()
+                .find(|(key, _)| key == "page_info")
+                .map(|(_, value)| value.into_owned())
+        })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer) -> ShopifyClient {
+        ShopifyClient::new(ShopifyTokens {
+            access_token: "shpat_test".to_string(),
+            shop_domain: "example.myshopify.com".to_string(),
+            scope: None,
+        })
+        .unwrap()
+        .with_base_url(server.uri())
+    }
+
+    #[test]
+    fn test_next_page_info() {
+        let link = "<https://example.myshopify.com/admin/api/2024-01/products.json?limit=250&page_info=abc>; rel=\"previous\", \
+                    <https://example.myshopify.com/admin/api/2024-01/products.json?limit=250&page_info=def>; rel=\"next\"";
+        assert_eq!(next_page_info(link).as_deref(), Some("def"));
+
+        let last = "<https://example.myshopify.com/admin/api/2024-01/products.json?limit=250&page_info=abc>; rel=\"previous\"";
+        assert_eq!(next_page_info(last), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_follows_link_header() {
+        let server = MockServer::start().await;
+        let next = format!("<{}/locations.json?limit=250&page_info=p2>; rel=\"next\"", server.uri());
+
+        Mock::given(method("GET"))
+            .and(path("/locations.json"))
+            .and(query_param("page_info", "p2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "locations": [{ "id": 2 }] })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/locations.json"))
+            .and(query_param("active", "true"))
+            .and(header("X-Shopify-Access-Token", "shpat_test"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Link", next.as_str())
+                    .set_body_json(serde_json::json!({ "locations": [{ "id": 1 }] })),
+            )
+            .mount(&server)
+            .await;
+
+        let ids: Vec<serde_json::Value> = client(&server)
+            .get_all("locations.json", "locations", &[("active", "true")])
+            .await
+            .unwrap();
+
+        assert_eq!(ids, vec![serde_json::json!({ "id": 1 }), serde_json::json!({ "id": 2 })]);
+    }
+
+    #[tokio::test]
+    async fn test_graphql_errors_are_returned() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/graphql.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "errors": [{ "message": "Access denied for shop field." }]
+            })))
+            .mount(&server)
+            .await;
+
+        let result = client(&server).get_shop_summary().await;
+
+        assert!(result.unwrap_err().to_string().contains("Access denied"));
+    }
+}
@@ -0,0 +1,85 @@
+/**
+ * Shopify Inventory API
+ *
+ * Reads locations and inventory levels, and sets the level of an item at
+ * a location.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::client::ShopifyClient;
+use crate::models::ApiError;
+
+/// Inventory items per levels request; Shopify allows up to 50
+const ITEMS_PER_REQUEST: usize = 50;
+
+/// A place the shop keeps stock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopifyLocation {
+    pub id: i64,
+    pub name: Option<String>,
+    pub active: Option<bool>,
+}
+
+/// Units of an inventory item available at a location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryLevel {
+    pub inventory_item_id: i64,
+    pub location_id: i64,
+    /// `None` when the item's stock is not tracked
+    pub available: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LevelEnvelope {
+    inventory_level: InventoryLevel,
+}
+
+impl ShopifyClient {
+    /// Fetch the shop's active locations
+    pub async fn get_locations(&self) -> Result<Vec<ShopifyLocation>, ApiError> {
+        let locations: Vec<ShopifyLocation> = self.get_all("locations.json", "locations", &[]).await?;
+        Ok(locations.into_iter().filter(|l| l.active != Some(false)).collect())
+    }
+
+    /// Fetch the levels of the given inventory items at a location
+    pub async fn get_inventory_levels(
+        &self,
+        location_id: i64,
+        inventory_item_ids: &[i64],
+    ) -> Result<Vec<InventoryLevel>, ApiError> {
+        let location = location_id.to_string();
+        let mut levels = Vec::new();
+
+        for chunk in inventory_item_ids.chunks(ITEMS_PER_REQUEST) {
+            let ids = chunk.iter().map(i64::to_string).collect::<Vec<_>>().join(",");
+            let page: Vec<InventoryLevel> = self
+                .get_all(
+                    "inventory_levels.json",
+                    "inventory_levels",
+                    &[("inventory_item_ids", ids.as_str()), ("location_ids", location.as_str())],
+                )
+                .await?;
+            levels.extend(page);
+        }
+
+        Ok(levels)
+    }
+
+    /// Set the units of an item available at a location
+    pub async fn set_inventory_level(
+        &self,
+        location_id: i64,
+        inventory_item_id: i64,
+        available: i64,
+    ) -> Result<InventoryLevel, ApiError> {
+        let body = serde_json::json!({
+            "location_id": location_id,
+            "inventory_item_id": inventory_item_id,
+            "available": available,
+        });
+        let response: LevelEnvelope = self.post("inventory_levels/set.json", &body).await?;
+        Ok(response.inventory_level)
+    }
+}
@@ -0,0 +1,18 @@
+/**
+ * Shopify Connector
+ * 
+ * Provides connectivity to Shopify through an app install (OAuth), and
+ * reads and writes products, variants and inventory levels and reads
+ * orders through the Admin API, for syncing a shop with the POS both ways.
+ * Webhooks are signed with the app secret.
+ */
+
+pub mod client;
+pub mod inventory;
+pub mod oauth;
+pub mod orders;
+pub mod products;
+pub mod webhooks;
+
+pub use client::ShopifyClient;
+pub use oauth::{ShopifyOAuth, ShopifyTokens};
@@ -0,0 +1,273 @@
+/**
+ * Shopify OAuth
+ *
+ * Handles the app install (authorization code) flow for Shopify.
+ * App credentials are read from environment variables.
+ */
+
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::env;
+
+use crate::models::errors::ApiError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Access scopes requested when none are configured
+const DEFAULT_SCOPES: &str = "read_products,write_products,read_inventory,write_inventory,read_locations,read_orders";
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Shopify OAuth configuration
+#[derive(Debug, Clone)]
+pub struct ShopifyOAuth {
+    /// App API key (client ID)
+    api_key: String,
+    /// App API secret; also signs callbacks and webhooks
+    api_secret: String,
+    /// OAuth redirect URI
+    redirect_uri: String,
+    /// Comma-separated access scopes
+    scopes: String,
+    /// HTTP client
+    http_client: Client,
+}
+
+impl ShopifyOAuth {
+    /// Create new Shopify OAuth handler from environment variables
+    ///
+    /// Required environment variables:
+    /// - SHOPIFY_API_KEY: App API key
+    /// - SHOPIFY_API_SECRET: App API secret
+    /// - SHOPIFY_REDIRECT_URI: OAuth callback URL
+    /// - SHOPIFY_SCOPES: access scopes (default: products, inventory,
+    ///   locations and orders)
+    pub fn from_env() -> Result<Self, ApiError> {
+        let api_key = env::var("SHOPIFY_API_KEY")
+            .map_err(|_| ApiError::configuration("SHOPIFY_API_KEY not configured"))?;
+
+        let api_secret = env::var("SHOPIFY_API_SECRET")
+            .map_err(|_| ApiError::configuration("SHOPIFY_API_SECRET not configured"))?;
+
+        let redirect_uri = env::var("SHOPIFY_REDIRECT_URI")
+            .map_err(|_| ApiError::configuration("SHOPIFY_REDIRECT_URI not configured"))?;
+
+        // Validate redirect URI is not localhost in production
+        if env::var("ENVIRONMENT").unwrap_or_default() == "production"
+            && redirect_uri.contains("localhost")
+        {
+            return Err(ApiError::configuration(
+                "SHOPIFY_REDIRECT_URI cannot use localhost in production"
+            ));
+        }
+
+        let scopes = env::var("SHOPIFY_SCOPES").unwrap_or_else(|_| DEFAULT_SCOPES.to_string());
+
+        let http_client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| ApiError::internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            api_key,
+            api_secret,
+            redirect_uri,
+            scopes,
+            http_client,
+        })
+    }
+
+    /// Generate the install URL for a shop
+    pub fn get_authorization_url(&self, shop_domain: &str, state: &str) -> String {
+        let params = [
+            ("client_id", self.api_key.as_str()),
+            ("scope", self.scopes.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("state", state),
+        ];
+
+        let query = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        format!("https://{}/admin/oauth/authorize?{}", shop_domain, query)
+    }
+
+    /// Check the `hmac` Shopify adds to the callback query
+    ///
+    /// The message is every other parameter, sorted by name and joined as
+    /// `name=value` with `&`; the signature is its hex HMAC-SHA256 under
+    /// the app secret.
+    pub fn verify_callback(&self, params: &[(String, String)]) -> bool {
+        verify_query_hmac(params, &self.api_secret)
+    }
+
+    /// Exchange authorization code for an offline access token
+    pub async fn exchange_code_for_tokens(&self, shop_domain: &str, code: &str) -> Result<ShopifyTokens, ApiError> {
+        let url = format!("https://{}/admin/oauth/access_token", shop_domain);
+
+        let body = serde_json::json!({
+            "client_id": self.api_key,
+            "client_secret": self.api_secret,
+            "code": code,
+        });
+
+        let response = self.http_client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ApiError::internal(format!("Shopify OAuth request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            return Err(ApiError::internal(format!(
+                "Shopify OAuth token exchange failed: {}",
+                error_body
+            )));
+        }
+
+        let tokens: ShopifyOAuthResponse = response
+            .json()
+            .await
+            .map_err(|e| ApiError::internal(format!("Failed to parse Shopify response: {}", e)))?;
+
+        Ok(ShopifyTokens {
+            access_token: tokens.access_token,
+            shop_domain: shop_domain.to_string(),
+            scope: tokens.scope,
+        })
+    }
+
+    /// Get the app secret, which webhooks are signed with
+    pub fn api_secret(&self) -> &str {
+        &self.api_secret
+    }
+
+    /// Get the configured redirect URI
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+}
+
+/// Validate a shop domain and bring it to `name.myshopify.com` form
+///
+/// Only myshopify.com hosts are accepted, so the install flow never sends
+/// the app's credentials anywhere else.
+pub fn normalize_shop_domain(shop: &str) -> Result<String, ApiError> {
+    let shop = shop
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/')
+        .to_lowercase();
+    let shop = if shop.contains('.') { shop } else { format!("{}.myshopify.com", shop) };
+
+    let name = shop.strip_suffix(".myshopify.com").unwrap_or_default();
+    let valid = !name.is_empty()
+        && !name.starts_with('-')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+        return Err(ApiError::bad_request(format!("Invalid Shopify shop domain: {}", shop)));
+    }
+
+    Ok(shop)
+}
+
+fn verify_query_hmac(params: &[(String, String)], secret: &str) -> bool {
+    let Some(signature) = params
+        .iter()
+        .find(|(key, _)| key == "hmac")
+        .and_then(|(_, value)| hex::decode(value).ok())
+    else {
+        return false;
+    };
+
+    let mut pairs: Vec<String> = params
+        .iter()
+        .filter(|(key, _)| key != "hmac" && key != "signature")
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    pairs.sort();
+
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(pairs.join("&").as_bytes());
+    mac.verify_slice(&signature).is_ok()
+}
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+/// Shopify OAuth token response
+#[derive(Debug, Deserialize)]
+struct ShopifyOAuthResponse {
+    access_token: String,
+    scope: Option<String>,
+}
+
+/// Shopify tokens for storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopifyTokens {
+    /// Offline Admin API access token
+    pub access_token: String,
+    /// Shop domain, e.g. `example.myshopify.com`
+    pub shop_domain: String,
+    /// Scopes the merchant granted
+    pub scope: Option<String>,
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(message: &str, secret: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(message.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn test_normalize_shop_domain() {
+        assert_eq!(normalize_shop_domain("Example-Shop").unwrap(), "example-shop.myshopify.com");
+        assert_eq!(
+            normalize_shop_domain("https://example.myshopify.com/").unwrap(),
+            "example.myshopify.com"
+        );
+        assert!(normalize_shop_domain("example.com").is_err());
+        assert!(normalize_shop_domain("evil.com/.myshopify.com").is_err());
+        assert!(normalize_shop_domain("").is_err());
+    }
+
+    #[test]
+    fn test_verify_callback_hmac() {
+        let secret = "hush";
+        let signature = sign("code=abc&shop=example.myshopify.com&state=s1&timestamp=1700000000", secret);
+        let params = |hmac: &str| {
+            vec![
+                ("shop".to_string(), "example.myshopify.com".to_string()),
+                ("code".to_string(), "abc".to_string()),
+                ("hmac".to_string(), hmac.to_string()),
+                ("timestamp".to_string(), "1700000000".to_string()),
+                ("state".to_string(), "s1".to_string()),
+            ]
+        };
+
+        assert!(verify_query_hmac(&params(&signature), secret));
+        assert!(!verify_query_hmac(&params(&signature), "other"));
+        assert!(!verify_query_hmac(&params("00ff"), secret));
+        assert!(!verify_query_hmac(&params(&signature)[..1], secret));
+    }
+}
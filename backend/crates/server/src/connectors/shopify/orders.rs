@@ -0,0 +1,61 @@
+/**
+ * Shopify Orders API
+ *
+ * Reads paid orders with their line items, for bringing online sales over.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::client::ShopifyClient;
+use crate::models::ApiError;
+
+/// An order; amounts are decimal strings in the shop's currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopifyOrder {
+    pub id: i64,
+    /// Order number as the shop shows it, e.g. `#1001`
+    pub name: Option<String>,
+    pub currency: Option<String>,
+    pub total_price: Option<String>,
+    pub subtotal_price: Option<String>,
+    pub total_tax: Option<String>,
+    pub total_discounts: Option<String>,
+    pub financial_status: Option<String>,
+    pub cancelled_at: Option<String>,
+    pub note: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub line_items: Vec<ShopifyLineItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopifyLineItem {
+    pub id: i64,
+    /// The variant sold; absent for custom items
+    pub variant_id: Option<i64>,
+    pub title: Option<String>,
+    pub quantity: i64,
+    pub price: Option<String>,
+}
+
+impl ShopifyOrder {
+    /// Whether the order has been paid and not cancelled
+    pub fn is_paid(&self) -> bool {
+        self.cancelled_at.is_none()
+            && matches!(self.financial_status.as_deref(), Some("paid") | Some("partially_refunded"))
+    }
+}
+
+impl ShopifyClient {
+    /// Fetch every paid order; `updated_since` (RFC 3339) leaves out
+    /// orders not changed since
+    pub async fn get_paid_orders(&self, updated_since: Option<&str>) -> Result<Vec<ShopifyOrder>, ApiError> {
+        let mut query = vec![("status", "any"), ("financial_status", "paid")];
+        if let Some(since) = updated_since {
+            query.push(("updated_at_min", since));
+        }
+
+        self.get_all("orders.json", "orders", &query).await
+    }
+}
@@ -0,0 +1,131 @@
+/**
+ * Shopify Products API
+ *
+ * Reads products with their variants, and writes variant prices, SKUs and
+ * barcodes and new products back.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::client::ShopifyClient;
+use crate::models::ApiError;
+
+/// Title Shopify gives the only variant of a product without options
+pub const DEFAULT_VARIANT_TITLE: &str = "Default Title";
+
+/// A product; each of its variants is one sellable item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopifyProduct {
+    pub id: i64,
+    pub title: String,
+    pub product_type: Option<String>,
+    pub vendor: Option<String>,
+    /// `active`, `draft` or `archived`
+    pub status: Option<String>,
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub variants: Vec<ShopifyVariant>,
+}
+
+/// A variant; prices are decimal strings in the shop's currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopifyVariant {
+    pub id: i64,
+    pub product_id: Option<i64>,
+    pub title: Option<String>,
+    pub sku: Option<String>,
+    pub barcode: Option<String>,
+    pub price: Option<String>,
+    /// Stock of the variant is tracked against this inventory item
+    pub inventory_item_id: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
+impl ShopifyProduct {
+    /// Whether the product has options, so its variants need their own names
+    pub fn has_options(&self) -> bool {
+        self.variants.len() > 1
+            || self.variants.iter().any(|v| {
+                v.title.as_deref().is_some_and(|t| t != DEFAULT_VARIANT_TITLE)
+            })
+    }
+}
+
+/// Fields written to a variant
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantUpdate {
+    pub sku: String,
+    pub price: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub barcode: Option<String>,
+}
+
+/// Fields written to a product
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductUpdate {
+    pub title: String,
+    pub product_type: String,
+}
+
+/// A product to create with a single variant
+#[derive(Debug, Clone, Serialize)]
+pub struct NewProduct {
+    pub title: String,
+    pub product_type: String,
+    pub variant: VariantUpdate,
+}
+
+#[derive(Deserialize)]
+struct ProductEnvelope {
+    product: ShopifyProduct,
+}
+
+#[derive(Deserialize)]
+struct VariantEnvelope {
+    variant: ShopifyVariant,
+}
+
+impl ShopifyClient {
+    /// Fetch every product with its variants; `updated_since` (RFC 3339)
+    /// leaves out products not changed since
+    pub async fn get_all_products(&self, updated_since: Option<&str>) -> Result<Vec<ShopifyProduct>, ApiError> {
+        let mut query = Vec::new();
+        if let Some(since) = updated_since {
+            query.push(("updated_at_min", since));
+        }
+
+        self.get_all("products.json", "products", &query).await
+    }
+
+    /// Write a variant's SKU, price and barcode
+    pub async fn update_variant(&self, variant_id: i64, update: &VariantUpdate) -> Result<ShopifyVariant, ApiError> {
+        let mut variant = serde_json::json!(update);
+        variant["id"] = serde_json::json!(variant_id);
+        let body = serde_json::json!({ "variant": variant });
+        let response: VariantEnvelope = self.put(&format!("variants/{}.json", variant_id), &body).await?;
+        Ok(response.variant)
+    }
+
+    /// Write a product's title and type
+    pub async fn update_product(&self, product_id: i64, update: &ProductUpdate) -> Result<ShopifyProduct, ApiError> {
+        let body = serde_json::json!({ "product": { "id": product_id, "title": update.title, "product_type": update.product_type } });
+        let response: ProductEnvelope = self.put(&format!("products/{}.json", product_id), &body).await?;
+        Ok(response.product)
+    }
+
+    /// Create an active product whose stock Shopify tracks
+    pub async fn create_product(&self, product: &NewProduct) -> Result<ShopifyProduct, ApiError> {
+        let mut variant = serde_json::json!(product.variant);
+        variant["inventory_management"] = serde_json::json!("shopify");
+        let body = serde_json::json!({
+            "product": {
+                "title": product.title,
+                "product_type": product.product_type,
+                "status": "active",
+                "variants": [variant],
+            }
+        });
+        let response: ProductEnvelope = self.post("products.json", &body).await?;
+        Ok(response.product)
+    }
+}
@@ -0,0 +1,82 @@
+/**
+ * Shopify Webhooks
+ *
+ * Validates webhook signatures and works out what a topic calls for.
+ */
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::models::ApiError;
+
+/// What a webhook topic calls for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookAction {
+    /// Pull this entity type from Shopify
+    Sync(&'static str),
+    /// The app was removed from the shop; its token no longer works
+    Uninstalled,
+    /// Nothing to do
+    Ignore,
+}
+
+/// Validate a Shopify webhook signature
+///
+/// `X-Shopify-Hmac-Sha256` is the base64 HMAC-SHA256 of the raw body under
+/// the app secret; it is compared in constant time.
+pub fn validate_signature(
+    body: &[u8],
+    signature: &str,
+    secret: &str,
+) -> Result<bool, ApiError> {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let Ok(signature) = general_purpose::STANDARD.decode(signature.trim()) else {
+        return Ok(false);
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| ApiError::internal(format!("Failed to create HMAC: {}", e)))?;
+    mac.update(body);
+
+    Ok(mac.verify_slice(&signature).is_ok())
+}
+
+/// Map a topic such as `products/update` to what it calls for
+pub fn topic_action(topic: &str) -> WebhookAction {
+    match topic {
+        "products/create" | "products/update" => WebhookAction::Sync("products"),
+        "inventory_levels/update" | "inventory_levels/connect" => WebhookAction::Sync("inventory"),
+        "orders/paid" | "orders/create" => WebhookAction::Sync("orders"),
+        "app/uninstalled" => WebhookAction::Uninstalled,
+        _ => WebhookAction::Ignore,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose};
+
+    #[test]
+    fn test_validate_signature() {
+        let body = br#"{"id":788032119674292922}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"app_secret").unwrap();
+        mac.update(body);
+        let signature = general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+
+        assert!(validate_signature(body, &signature, "app_secret").unwrap());
+        assert!(!validate_signature(body, &signature, "other_secret").unwrap());
+        assert!(!validate_signature(b"{}", &signature, "app_secret").unwrap());
+        assert!(!validate_signature(body, "not base64!", "app_secret").unwrap());
+    }
+
+    #[test]
+    fn test_topic_action() {
+        assert_eq!(topic_action("products/update"), WebhookAction::Sync("products"));
+        assert_eq!(topic_action("inventory_levels/update"), WebhookAction::Sync("inventory"));
+        assert_eq!(topic_action("orders/paid"), WebhookAction::Sync("orders"));
+        assert_eq!(topic_action("app/uninstalled"), WebhookAction::Uninstalled);
+        assert_eq!(topic_action("carts/update"), WebhookAction::Ignore);
+    }
+}
@@ -0,0 +1,142 @@
+/**
+ * Square Catalog API
+ *
+ * Lists catalog items with their variations, categories and modifier lists.
+ * Variations are what get sold and stocked; an item groups them under one
+ * name, category and set of modifier lists.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::client::{Money, SquareClient};
+use crate::models::ApiError;
+
+/// Catalog object types the importer reads
+pub const CATALOG_TYPES: &str = "ITEM,CATEGORY,MODIFIER_LIST";
+
+/// A catalog object; which `*_data` field is set depends on `object_type`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogObject {
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub id: String,
+    #[serde(default)]
+    pub is_deleted: bool,
+    pub updated_at: Option<String>,
+    pub item_data: Option<CatalogItem>,
+    pub item_variation_data: Option<CatalogItemVariation>,
+    pub category_data: Option<CatalogCategory>,
+    pub modifier_list_data: Option<CatalogModifierList>,
+    pub modifier_data: Option<CatalogModifier>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogItem {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Older single-category field, still set by many accounts
+    pub category_id: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<CatalogObjectReference>,
+    /// `ITEM_VARIATION` objects
+    #[serde(default)]
+    pub variations: Vec<CatalogObject>,
+    #[serde(default)]
+    pub modifier_list_info: Vec<CatalogItemModifierListInfo>,
+}
+
+impl CatalogItem {
+    /// The item's category, preferring the newer `categories` list
+    pub fn primary_category_id(&self) -> Option<&str> {
+        self.categories
+            .first()
+            .map(|c| c.id.as_str())
+            .or(self.category_id.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogObjectReference {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogItemModifierListInfo {
+    pub modifier_list_id: String,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogItemVariation {
+    pub item_id: Option<String>,
+    pub name: Option<String>,
+    pub sku: Option<String>,
+    pub upc: Option<String>,
+    /// `FIXED_PRICING` or `VARIABLE_PRICING` (price entered at sale)
+    pub pricing_type: Option<String>,
+    pub price_money: Option<Money>,
+    pub ordinal: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogCategory {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogModifierList {
+    pub name: Option<String>,
+    /// `MODIFIER` objects
+    #[serde(default)]
+    pub modifiers: Vec<CatalogObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogModifier {
+    pub name: Option<String>,
+    pub price_money: Option<Money>,
+    pub ordinal: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListCatalogResponse {
+    #[serde(default)]
+    objects: Vec<CatalogObject>,
+    cursor: Option<String>,
+}
+
+impl SquareClient {
+    /// Fetch one page of the catalog
+    pub async fn list_catalog(
+        &self,
+        types: &str,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<CatalogObject>, Option<String>), ApiError> {
+        let mut query = vec![("types", types)];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor));
+        }
+
+        let page: ListCatalogResponse = self.get("catalog/list", &query).await?;
+
+        Ok((page.objects, page.cursor))
+    }
+
+    /// Fetch every item, category and modifier list, following the cursor
+    pub async fn get_all_catalog_objects(&self) -> Result<Vec<CatalogObject>, ApiError> {
+        let mut all_objects = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let (objects, next) = self.list_catalog(CATALOG_TYPES, cursor.as_deref()).await?;
+            all_objects.extend(objects);
+
+            match next {
+                Some(next) if !next.is_empty() => cursor = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok(all_objects)
+    }
+}
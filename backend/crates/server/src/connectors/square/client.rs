@@ -7,7 +7,8 @@
  */
 
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use crate::connectors::{ConnectionStatus, PlatformConnector};
@@ -26,6 +27,9 @@ pub struct SquareCredentials {
     pub location_id: String,
 }
 
+/// Square API version sent with every request
+const SQUARE_VERSION: &str = "2024-01-18";
+
 /// Square API client
 #[derive(Clone)]
 pub struct SquareClient {
@@ -57,6 +61,66 @@ impl SquareClient {
         })
     }
     
+    /// Point the client at another API root, e.g. the sandbox or a mock server
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Make a GET request and parse the JSON response
+    pub async fn get<T: DeserializeOwned>(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<T, ApiError> {
+        let url = format!("{}/{}", self.base_url, endpoint.trim_start_matches('/'));
+
+        tracing::debug!("Square GET: {}", url);
+
+        self.send(self.http_client.get(&url).query(query)).await
+    }
+
+    /// Make a POST request and parse the JSON response
+    pub async fn post<B: Serialize, T: DeserializeOwned>(&self, endpoint: &str, body: &B) -> Result<T, ApiError> {
+        let url = format!("{}/{}", self.base_url, endpoint.trim_start_matches('/'));
+
+        tracing::debug!("Square POST: {}", url);
+
+        self.send(self.http_client.post(&url).json(body)).await
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ApiError> {
+        let response = request
+            .bearer_auth(&self.access_token)
+            .header("Square-Version", SQUARE_VERSION)
+            .send()
+            .await
+            .map_err(|e| ApiError::internal(format!("Square API request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            tracing::error!("Square API error ({}): {}", status, error_text);
+
+            return match status {
+                StatusCode::UNAUTHORIZED => Err(ApiError::unauthorized("Invalid Square access token")),
+                StatusCode::FORBIDDEN => Err(ApiError::forbidden("Access denied to Square resource")),
+                StatusCode::NOT_FOUND => Err(ApiError::not_found("Square resource not found")),
+                StatusCode::TOO_MANY_REQUESTS => Err(ApiError::internal("Square rate limit exceeded")),
+                _ => Err(ApiError::internal(format!(
+                    "Square API error ({}): {}",
+                    status, error_text
+                ))),
+            };
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ApiError::internal(format!("Failed to parse Square response: {}", e)))
+    }
+
     /// Get location summary
     /// 
     /// Requirements: 2.8
@@ -66,7 +130,7 @@ impl SquareClient {
         let response = self.http_client
             .get(&url)
             .bearer_auth(&self.access_token)
-            .header("Square-Version", "2024-01-18")
+            .header("Square-Version", SQUARE_VERSION)
             .send()
             .await
             .map_err(|e| ApiError::internal(format!("Square API request failed: {}", e)))?;
@@ -114,7 +178,7 @@ impl PlatformConnector for SquareClient {
         let response = self.http_client
             .get(&url)
             .bearer_auth(&self.access_token)
-            .header("Square-Version", "2024-01-18")
+            .header("Square-Version", SQUARE_VERSION)
             .send()
             .await
             .map_err(|e| ApiError::internal(format!("Square API request failed: {}", e)))?;
@@ -153,6 +217,29 @@ pub struct SquareSummary {
     pub capabilities: Vec<String>,
 }
 
+/// An amount of money in the currency's smallest unit (cents)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Money {
+    #[serde(default)]
+    pub amount: i64,
+    pub currency: Option<String>,
+}
+
+impl Money {
+    /// The amount in whole currency units
+    ///
+    /// Assumes a currency with two decimal places, like every currency
+    /// Square sells in apart from JPY.
+    pub fn as_major_units(&self) -> f64 {
+        self.amount as f64 / 100.0
+    }
+}
+
+/// Major-unit amount of an optional money field, zero when absent
+pub fn money_amount(money: &Option<Money>) -> f64 {
+    money.as_ref().map(Money::as_major_units).unwrap_or(0.0)
+}
+
 #[derive(Debug, Deserialize)]
 struct SquareLocationResponse {
     location: SquareLocation,
@@ -0,0 +1,85 @@
+/**
+ * Square Customers API
+ *
+ * Lists the customer directory.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::client::SquareClient;
+use crate::models::ApiError;
+
+/// A customer profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquareCustomer {
+    pub id: String,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub company_name: Option<String>,
+    pub email_address: Option<String>,
+    pub phone_number: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl SquareCustomer {
+    /// Full name, else company, else email; `None` when the profile has none
+    pub fn display_name(&self) -> Option<String> {
+        let full_name = [self.given_name.as_deref(), self.family_name.as_deref()]
+            .iter()
+            .filter_map(|&p| p.map(str::trim).filter(|p| !p.is_empty()))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if !full_name.is_empty() {
+            return Some(full_name);
+        }
+
+        [self.company_name.as_deref(), self.email_address.as_deref()]
+            .iter()
+            .filter_map(|&p| p.map(str::trim).filter(|p| !p.is_empty()))
+            .next()
+            .map(str::to_string)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ListCustomersResponse {
+    #[serde(default)]
+    customers: Vec<SquareCustomer>,
+    cursor: Option<String>,
+}
+
+impl SquareClient {
+    /// Fetch one page of customers
+    pub async fn list_customers(
+        &self,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<SquareCustomer>, Option<String>), ApiError> {
+        let mut query = vec![("limit", "100")];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor));
+        }
+
+        let page: ListCustomersResponse = self.get("customers", &query).await?;
+
+        Ok((page.customers, page.cursor))
+    }
+
+    /// Fetch every customer, following the cursor
+    pub async fn get_all_customers(&self) -> Result<Vec<SquareCustomer>, ApiError> {
+        let mut all_customers = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let (customers, next) = self.list_customers(cursor.as_deref()).await?;
+            all_customers.extend(customers);
+
+            match next {
+                Some(next) if !next.is_empty() => cursor = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok(all_customers)
+    }
+}
@@ -1,11 +1,16 @@
 /**
  * Square Connector
  * 
- * Provides connectivity to Square via API key authentication.
+ * Provides connectivity to Square via API key authentication, and reads
+ * the catalog, customers, stock counts and orders for importing a shop.
  * 
  * Requirements: 2.3, 2.7
  */
 
 pub mod client;
+pub mod catalog;
+pub mod customers;
+pub mod inventory;
+pub mod orders;
 
 pub use client::SquareClient;
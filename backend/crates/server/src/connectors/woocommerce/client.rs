@@ -46,6 +46,13 @@ impl WooCommerceClient {
         })
     }
 
+    /// Point the client at another API root (a proxy, or a mock in tests)
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
     /// Make a GET request to WooCommerce API
     pub async fn get(&self, endpoint: &str) -> Result<Response, ApiError> {
         let url = format!("{}/{}", self.base_url, endpoint.trim_start_matches('/'));
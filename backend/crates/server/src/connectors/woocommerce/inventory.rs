@@ -0,0 +1,154 @@
+/**
+ * WooCommerce Inventory API
+ *
+ * Looks up stock levels by SKU and writes them through the batch endpoints,
+ * up to 100 products or variations per request.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::client::WooCommerceClient;
+use crate::models::ApiError;
+
+/// Items per request; WooCommerce allows up to 100 for both lookups and batches
+const ITEMS_PER_REQUEST: usize = 100;
+
+/// A product's or variation's stock
+///
+/// Looking products up by SKU also finds variations, which carry their
+/// product's id as `parent_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockLevel {
+    pub id: i64,
+    /// Product of a variation; 0 for a product
+    #[serde(default)]
+    pub parent_id: i64,
+    #[serde(default)]
+    pub sku: String,
+    /// `true`, `false`, or `"parent"` for a variation whose product keeps its stock
+    #[serde(default)]
+    pub manage_stock: Value,
+    pub stock_quantity: Option<i64>,
+}
+
+impl StockLevel {
+    /// Whether WooCommerce keeps a stock quantity for this product or variation itself
+    pub fn tracks_stock(&self) -> bool {
+        self.manage_stock == Value::Bool(true)
+    }
+
+    /// The product whose variations endpoint this belongs to, if it is a variation
+    pub fn variation_of(&self) -> Option<i64> {
+        (self.parent_id != 0).then_some(self.parent_id)
+    }
+}
+
+/// A stock quantity to set
+#[derive(Debug, Clone, Serialize)]
+pub struct StockUpdate {
+    pub id: i64,
+    pub stock_quantity: i64,
+}
+
+/// The outcome of one update in a batch
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchItem {
+    pub id: i64,
+    pub stock_quantity: Option<i64>,
+    /// Set when this update was refused; the rest of the batch still applies
+    pub error: Option<BatchError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchError {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+struct BatchRequest<'a> {
+    update: &'a [StockUpdate],
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    #[serde(default)]
+    update: Vec<BatchItem>,
+}
+
+impl WooCommerceClient {
+    /// Fetch the stock of the products and variations with these SKUs
+    pub async fn get_stock_levels(&self, skus: &[String]) -> Result<Vec<StockLevel>, ApiError> {
+        let mut levels = Vec::new();
+
+        for chunk in skus.chunks(ITEMS_PER_REQUEST) {
+            let sku_list = chunk
+                .iter()
+                .map(|sku| urlencoding::encode(sku).into_owned())
+                .collect::<Vec<_>>()
+                .join(",");
+            let endpoint = format!("products?sku={}&per_page={}", sku_list, ITEMS_PER_REQUEST);
+
+            let response = self.get(&endpoint).await?;
+            let found: Vec<StockLevel> = response
+                .json()
+                .await
+                .map_err(|e| ApiError::internal(format!("Failed to parse stock levels: {}", e)))?;
+
+            levels.extend(found);
+        }
+
+        Ok(levels)
+    }
+
+    /// Set stock quantities with the batch endpoint
+    ///
+    /// Updates go to `products/batch`, or to the product's
+    /// `variations/batch` when `variation_of` is set.
+    pub async fn update_stock_batch(
+        &self,
+        variation_of: Option<i64>,
+        updates: &[StockUpdate],
+    ) -> Result<Vec<BatchItem>, ApiError> {
+        let endpoint = match variation_of {
+            Some(product_id) => format!("products/{}/variations/batch", product_id),
+            None => "products/batch".to_string(),
+        };
+
+        let mut results = Vec::new();
+
+        for chunk in updates.chunks(ITEMS_PER_REQUEST) {
+            let response = self.post(&endpoint, &BatchRequest { update: chunk }).await?;
+            let batch: BatchResponse = response
+                .json()
+                .await
+                .map_err(|e| ApiError::internal(format!("Failed to parse batch response: {}", e)))?;
+
+            results.extend(batch.update);
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stock_level_tracking() {
+        let levels: Vec<StockLevel> = serde_json::from_value(serde_json::json!([
+            {"id": 10, "parent_id": 0, "sku": "MUG", "manage_stock": true, "stock_quantity": 4},
+            {"id": 21, "parent_id": 20, "sku": "TEE-M", "manage_stock": "parent", "stock_quantity": null},
+            {"id": 30, "sku": "GIFT", "manage_stock": false, "stock_quantity": null}
+        ]))
+        .unwrap();
+
+        assert!(levels[0].tracks_stock());
+        assert_eq!(levels[0].variation_of(), None);
+        assert!(!levels[1].tracks_stock());
+        assert_eq!(levels[1].variation_of(), Some(20));
+        assert!(!levels[2].tracks_stock());
+    }
+}
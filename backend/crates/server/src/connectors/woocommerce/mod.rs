@@ -10,6 +10,7 @@
 pub mod client;
 pub mod orders;
 pub mod products;
+pub mod inventory;
 pub mod customers;
 pub mod webhooks;
 pub mod transformers;
@@ -1,8 +1,14 @@
 use sqlx::SqlitePool;
 use std::fs;
+use std::path::Path;
 
 /// Run database migrations
 pub async fn run_migrations(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    run_migrations_from(pool, Path::new("")).await
+}
+
+/// Run database migrations, reading the files relative to `root`
+pub(crate) async fn run_migrations_from(pool: &SqlitePool, root: &Path) -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("Running database migrations...");
 
     // Create migrations tracking table if it doesn't exist
@@ -62,7 +68,47 @@ pub async fn run_migrations(pool: &SqlitePool) -> Result<(), Box<dyn std::error:
         "migrations/041_settings_registry_keys.sql",
         "migrations/042_accounting_tables.sql",
         "migrations/043_review_cases_tables.sql",
+        "migrations/044_sales_transactions_table.sql",
+        "migrations/045_sales_unique_constraint.sql",
         "migrations/045_update_tenant_id_to_default.sql",
+        "migrations/046_email_logs.sql",
+        "migrations/046_ocr_jobs_table.sql",
+        "migrations/047_product_alternate_skus.sql",
+        "migrations/047_tax_rules.sql",
+        "migrations/048_cleanup_engine_tables.sql",
+        "migrations/049_cleanup_outcome_tracking.sql",
+        "migrations/050_sync_queue_resilience.sql",
+        "migrations/051_integrations_phase1.sql",
+        // 052_payments_phase2.sql is not applied: its webhook_events table
+        // clashes with the one 029 creates for sync webhooks
+        "migrations/053_add_last_login_to_users.sql",
+        "migrations/054_zones_table.sql",
+        "migrations/055_stock_adjustments.sql",
+        "migrations/056_bill_files_table.sql",
+        "migrations/057_stock_adjustments_location.sql",
+        "migrations/058_accounting_snapshots.sql",
+        "migrations/059_sales_payments.sql",
+        "migrations/060_tax_jurisdictions.sql",
+        "migrations/061_tax_inclusive_pricing.sql",
+        "migrations/062_sales_returns.sql",
+        "migrations/063_suspended_sales.sql",
+        "migrations/064_shifts.sql",
+        "migrations/065_snapshot_line_costing.sql",
+        "migrations/066_export_batch_lifecycle.sql",
+        "migrations/067_inventory_movements.sql",
+        "migrations/068_multi_location_inventory.sql",
+        "migrations/069_purchase_orders.sql",
+        "migrations/070_count_sessions.sql",
+        "migrations/071_inventory_costing.sql",
+        "migrations/072_vehicle_fitment_lookup.sql",
+        "migrations/073_roles_and_permissions.sql",
+        "migrations/074_manager_overrides.sql",
+        "migrations/075_station_pin_login.sql",
+        "migrations/076_two_factor_auth.sql",
+        "migrations/077_outbound_webhooks.sql",
+        "migrations/078_square_import.sql",
+        "migrations/079_clover_sync.sql",
+        "migrations/080_woocommerce_stock_push.sql",
     ];
 
     for migration_file in migrations {
@@ -82,7 +128,7 @@ pub async fn run_migrations(pool: &SqlitePool) -> Result<(), Box<dyn std::error:
 
         tracing::info!("Applying migration: {}", migration_file);
         
-        let sql = fs::read_to_string(migration_file)
+        let sql = fs::read_to_string(root.join(migration_file))
             .map_err(|e| format!("Failed to read migration file {}: {}", migration_file, e))?;
 
         // Parse SQL statements properly, handling parentheses
@@ -268,4 +314,46 @@ mod tests {
         assert!(table_names.contains(&"users".to_string()));
         assert!(table_names.contains(&"sessions".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_fresh_install_applies_every_migration() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+
+        run_migrations_from(&pool, &workspace).await.unwrap();
+
+        // 060 to 062 alter the tables 044 and 047 create
+        let columns: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info('sales_transactions')")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert!(columns.contains(&"tax_exempt_certificate".to_string()));
+        assert!(columns.contains(&"prices_include_tax".to_string()));
+        assert!(columns.contains(&"original_transaction_id".to_string()));
+
+        // 064 refers to sales_transactions too
+        let shifts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'shifts'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(shifts, 1);
+
+        // Email receipts log to 046's table; 057 adds locations to 055's adjustments
+        let email_logs: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'email_logs'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(email_logs, 1);
+        let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info('stock_adjustments')")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert!(columns.contains(&"location_id".to_string()));
+    }
 }
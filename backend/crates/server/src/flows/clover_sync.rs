@@ -0,0 +1,1530 @@
+/**
+ * Clover Sync Flow
+ *
+ * Sync flow: Clover → POS, run by the sync orchestrator one entity type
+ * at a time:
+ * - products: items, through the `clover-to-pos-product` field mapping,
+ *   in the first category they belong to
+ * - inventory: item stock levels set the on-hand of synced products at the
+ *   sync's store, through the inventory ledger
+ * - customers: through the `clover-to-pos-customer` field mapping
+ * - orders: paid orders become completed sales through the
+ *   `clover-to-pos-order` field mapping, once each; they do not move stock
+ *
+ * A tenant's active field mapping with one of those IDs replaces the
+ * default in crates/server/mappings. Every record applied is logged in
+ * integration_sync_operations with a hash of its mapped fields, and one
+ * that has not changed in Clover since is skipped. When a product or
+ * customer changed in Clover and was also edited in the POS since its last
+ * sync, a conflict is recorded and settled with the credential's strategy
+ * for the entity type (newest wins when none is set). Manual conflicts keep
+ * the POS record until someone resolves them.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::connectors::clover::client::CloverClient;
+use crate::connectors::clover::customers::CloverCustomer;
+use crate::connectors::clover::inventory::{CloverItem, CloverItemStock};
+use crate::connectors::clover::orders::CloverOrder;
+use crate::mappers::schema::FieldMap;
+use crate::mappers::{FieldMapping, MappingEngine, TransformationRegistry};
+use crate::services::id_mapper::IdMapper;
+use crate::services::inventory_ledger_service::{
+    self, MovementType, StockMovement, REASON_SYNC, SOURCE_CLOVER_SYNC,
+};
+use crate::services::sync_direction_control::{ConflictStrategy, SyncDirectionControl};
+
+/// Platform name on credentials, operations and conflicts
+pub const PLATFORM: &str = "clover";
+const TARGET_SYSTEM: &str = "easysale";
+
+const ENTITY_ITEM: &str = "item";
+const ENTITY_CUSTOMER: &str = "customer";
+const ENTITY_ORDER: &str = "order";
+
+/// Entity types as the orchestrator names them
+pub const PRODUCTS: &str = "products";
+pub const INVENTORY: &str = "inventory";
+pub const CUSTOMERS: &str = "customers";
+pub const ORDERS: &str = "orders";
+
+/// Recorded as the employee on sales brought over from Clover
+const SYNC_EMPLOYEE_ID: &str = "system";
+
+/// Errors kept on an entity's counts; the counts still cover every record
+const MAX_REPORTED_ERRORS: usize = 100;
+
+const DEFAULT_PRODUCT_MAPPING: &str = include_str!("../../mappings/clover-to-pos-product.json");
+const DEFAULT_CUSTOMER_MAPPING: &str = include_str!("../../mappings/clover-to-pos-customer.json");
+const DEFAULT_ORDER_MAPPING: &str = include_str!("../../mappings/clover-to-pos-order.json");
+
+// ============================================================================
+// Requests and Results
+// ============================================================================
+
+/// One entity sync for a tenant
+#[derive(Debug, Clone)]
+pub struct CloverSyncRequest {
+    pub tenant_id: String,
+    /// Store that new products, customers, stock levels and sales go to
+    pub store_id: String,
+    /// Orchestrator sync run, recorded on stock movements
+    pub sync_id: String,
+    /// Only records changed since (epoch milliseconds); `None` for all
+    pub modified_since: Option<i64>,
+    /// Count what would change without writing anything
+    pub dry_run: bool,
+}
+
+/// Outcome of syncing one entity type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CloverSyncCounts {
+    pub created: usize,
+    pub updated: usize,
+    /// Unchanged since the last sync, or with nothing to sync to
+    pub skipped: usize,
+    /// Changed on both sides and kept as in the POS
+    pub conflicts: usize,
+    pub failed: usize,
+    /// Clover ID and what went wrong, for the first failed records
+    pub errors: Vec<(String, String)>,
+}
+
+impl CloverSyncCounts {
+    /// Every record looked at
+    pub fn processed(&self) -> usize {
+        self.created + self.updated + self.skipped + self.conflicts + self.failed
+    }
+
+    fn tally(&mut self, clover_id: &str, result: Result<Applied, String>) {
+        match result {
+            Ok(Applied::Created) => self.created += 1,
+            Ok(Applied::Updated) => self.updated += 1,
+            Ok(Applied::Skipped) => self.skipped += 1,
+            Ok(Applied::ConflictKept) => self.conflicts += 1,
+            Err(e) => {
+                tracing::warn!("Clover sync of {}: {}", clover_id, e);
+                self.failed += 1;
+                if self.errors.len() < MAX_REPORTED_ERRORS {
+                    self.errors.push((clover_id.to_string(), e));
+                }
+            }
+        }
+    }
+}
+
+/// What happened to one record
+enum Applied {
+    Created,
+    Updated,
+    Skipped,
+    ConflictKept,
+}
+
+/// Which side a conflict went to
+enum Resolution {
+    Platform,
+    Pos,
+    Pending,
+}
+
+/// The last time a Clover record was applied
+#[derive(FromRow)]
+struct LastSync {
+    sync_hash: Option<String>,
+    completed_at: String,
+}
+
+/// A record changed on both sides
+struct ConflictCase<'a> {
+    entity_type: &'a str,
+    pos_id: &'a str,
+    clover_id: &'a str,
+    pos_version: String,
+    platform_version: String,
+    pos_updated_at: String,
+    platform_updated_at: String,
+}
+
+/// Layout of the mapping files in crates/server/mappings
+#[derive(Deserialize)]
+struct MappingFile {
+    mappings: Vec<FieldMap>,
+}
+
+// ============================================================================
+// Flow
+// ============================================================================
+
+/// Clover to POS sync flow
+pub struct CloverSyncFlow {
+    db: SqlitePool,
+    client: CloverClient,
+    /// The tenant's Clover `integration_credentials` row
+    credential_id: String,
+    id_mapper: IdMapper,
+    direction_control: Arc<SyncDirectionControl>,
+    engine: MappingEngine,
+}
+
+impl CloverSyncFlow {
+    pub fn new(
+        db: SqlitePool,
+        client: CloverClient,
+        credential_id: impl Into<String>,
+        direction_control: Arc<SyncDirectionControl>,
+    ) -> Self {
+        let id_mapper = IdMapper::new(db.clone());
+        Self {
+            db,
+            client,
+            credential_id: credential_id.into(),
+            id_mapper,
+            direction_control,
+            engine: MappingEngine::new(TransformationRegistry::new()),
+        }
+    }
+
+    /// Run the sync of one entity type
+    ///
+    /// A record that fails is counted and the sync carries on; failing to
+    /// read from Clover or to load the field mapping fails the whole type.
+    pub async fn sync(&self, entity_type: &str, request: &CloverSyncRequest) -> Result<CloverSyncCounts, String> {
+        match entity_type {
+            PRODUCTS => self.sync_products(request).await,
+            INVENTORY => self.sync_inventory(request).await,
+            CUSTOMERS => self.sync_customers(request).await,
+            ORDERS => self.sync_orders(request).await,
+            _ => Err(format!("Unsupported entity type: {}", entity_type)),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Products
+    // ------------------------------------------------------------------------
+
+    async fn sync_products(&self, request: &CloverSyncRequest) -> Result<CloverSyncCounts, String> {
+        let mapping = self.load_mapping(&request.tenant_id, "product", DEFAULT_PRODUCT_MAPPING).await?;
+        let category_names: HashMap<String, String> = self
+            .client
+            .get_all_categories()
+            .await
+            .map_err(|e| format!("Failed to fetch Clover categories: {}", e))?
+            .into_iter()
+            .filter_map(|c| non_empty(&c.name).map(|name| (c.id, name)))
+            .collect();
+        let items = self
+            .client
+            .get_all_items(request.modified_since)
+            .await
+            .map_err(|e| format!("Failed to fetch Clover items: {}", e))?;
+
+        let mut counts = CloverSyncCounts::default();
+        for mut item in items {
+            for category in &mut item.categories.elements {
+                if non_empty(&category.name).is_none() {
+                    category.name = category_names.get(&category.id).cloned();
+                }
+            }
+
+            let result = match self.map(&mapping, &item) {
+                Ok(mapped) => self.apply_product(request, &item, SyncedProduct::from_mapped(&mapped, &item.id)).await,
+                Err(e) => Err(e),
+            };
+            counts.tally(&item.id, result);
+        }
+
+        Ok(counts)
+    }
+
+    async fn apply_product(
+        &self,
+        request: &CloverSyncRequest,
+        item: &CloverItem,
+        product: SyncedProduct,
+    ) -> Result<Applied, String> {
+        let tenant_id = &request.tenant_id;
+        let platform_version = serde_json::to_string(&product).map_err(|e| e.to_string())?;
+        let hash = data_hash(&platform_version);
+
+        let mapped = self.mapped_id(tenant_id, ENTITY_ITEM, &item.id).await?;
+        let existing: Option<PosProduct> = sqlx::query_as(
+            "SELECT id, sku, name, category, unit_price, cost, barcode, updated_at
+             FROM products
+             WHERE tenant_id = ? AND (id = ? OR sku = ?)
+             ORDER BY id = ? DESC
+             LIMIT 1",
+        )
+        .bind(tenant_id)
+        .bind(&mapped)
+        .bind(&product.sku)
+        .bind(&mapped)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up product: {}", e))?;
+
+        let Some(pos) = existing else {
+            if request.dry_run {
+                return Ok(Applied::Created);
+            }
+
+            let product_id = Uuid::new_v4().to_string();
+            let now = Utc::now().to_rfc3339();
+            sqlx::query(
+                "INSERT INTO products (
+                    id, tenant_id, store_id, sku, name, category, unit_price, cost,
+                    quantity_on_hand, barcode, is_active, created_at, updated_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0, ?, 1, ?, ?)",
+            )
+            .bind(&product_id)
+            .bind(tenant_id)
+            .bind(&request.store_id)
+            .bind(&product.sku)
+            .bind(&product.name)
+            .bind(&product.category)
+            .bind(product.unit_price)
+            .bind(product.cost)
+            .bind(&product.barcode)
+            .bind(&now)
+            .bind(&now)
+            .execute(&self.db)
+            .await
+            .map_err(|e| format!("Failed to create product: {}", e))?;
+
+            self.record_applied(tenant_id, ENTITY_ITEM, "product", PRODUCTS, "create", &product_id, &item.id, &hash)
+                .await?;
+            return Ok(Applied::Created);
+        };
+
+        let last = self.last_sync(PRODUCTS, &item.id).await?;
+        if last.as_ref().and_then(|l| l.sync_hash.as_deref()) == Some(hash.as_str()) {
+            return Ok(Applied::Skipped);
+        }
+
+        let edited_in_pos = last.as_ref().is_some_and(|l| is_after(&pos.updated_at, &l.completed_at));
+        if edited_in_pos {
+            if request.dry_run {
+                return Ok(Applied::ConflictKept);
+            }
+
+            let case = ConflictCase {
+                entity_type: PRODUCTS,
+                pos_id: &pos.id,
+                clover_id: &item.id,
+                pos_version: serde_json::to_string(&pos.as_synced()).map_err(|e| e.to_string())?,
+                platform_version: platform_version.clone(),
+                pos_updated_at: normalize_timestamp(&pos.updated_at),
+                platform_updated_at: millis_to_rfc3339(item.modified_time),
+            };
+            match self.resolve_conflict(tenant_id, &case).await? {
+                Resolution::Platform => {}
+                Resolution::Pos => {
+                    self.record_applied(tenant_id, ENTITY_ITEM, "product", PRODUCTS, "skip", &pos.id, &item.id, &hash)
+                        .await?;
+                    return Ok(Applied::ConflictKept);
+                }
+                Resolution::Pending => return Ok(Applied::ConflictKept),
+            }
+        }
+
+        if request.dry_run {
+            return Ok(Applied::Updated);
+        }
+
+        sqlx::query(
+            "UPDATE products
+             SET sku = ?, name = ?, category = ?, unit_price = ?, cost = ?,
+                 barcode = COALESCE(?, barcode), is_active = 1,
+                 updated_at = ?, sync_version = sync_version + 1
+             WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(&product.sku)
+        .bind(&product.name)
+        .bind(&product.category)
+        .bind(product.unit_price)
+        .bind(product.cost)
+        .bind(&product.barcode)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&pos.id)
+        .bind(tenant_id)
+        .execute(&self.db)
+        .await
+        .map_err(|e| format!("Failed to update product: {}", e))?;
+
+        self.record_applied(tenant_id, ENTITY_ITEM, "product", PRODUCTS, "update", &pos.id, &item.id, &hash)
+            .await?;
+        Ok(Applied::Updated)
+    }
+
+    // ------------------------------------------------------------------------
+    // Inventory
+    // ------------------------------------------------------------------------
+
+    async fn sync_inventory(&self, request: &CloverSyncRequest) -> Result<CloverSyncCounts, String> {
+        let stocks = self
+            .client
+            .get_all_item_stocks()
+            .await
+            .map_err(|e| format!("Failed to fetch Clover item stocks: {}", e))?;
+
+        let mut counts = CloverSyncCounts::default();
+        for stock in &stocks {
+            let result = self.apply_stock(request, stock).await;
+            counts.tally(&stock.item.id, result);
+        }
+
+        Ok(counts)
+    }
+
+    /// Set a product's on-hand to Clover's level, when that level changed
+    /// since the last sync; POS sales in between are not undone otherwise
+    async fn apply_stock(&self, request: &CloverSyncRequest, stock: &CloverItemStock) -> Result<Applied, String> {
+        let tenant_id = &request.tenant_id;
+        let Some(quantity) = stock.quantity else {
+            return Ok(Applied::Skipped);
+        };
+        let Some(product_id) = self.mapped_id(tenant_id, ENTITY_ITEM, &stock.item.id).await? else {
+            return Ok(Applied::Skipped);
+        };
+
+        let hash = data_hash(&quantity.to_string());
+        let last = self.last_sync(INVENTORY, &stock.item.id).await?;
+        if last.as_ref().and_then(|l| l.sync_hash.as_deref()) == Some(hash.as_str()) {
+            return Ok(Applied::Skipped);
+        }
+        if request.dry_run {
+            return Ok(Applied::Updated);
+        }
+
+        let movement = StockMovement::new(tenant_id.as_str(), &product_id, MovementType::Adjustment, 0.0, REASON_SYNC)
+            .at_store(&request.store_id)
+            .with_source(SOURCE_CLOVER_SYNC, &request.sync_id)
+            .with_notes(Some("Clover stock level".to_string()));
+
+        let recorded = async {
+            let mut tx = self.db.begin().await?;
+            let recorded = inventory_ledger_service::set_on_hand(&mut tx, movement, quantity).await?;
+            tx.commit().await?;
+            Ok::<_, inventory_ledger_service::LedgerError>(recorded)
+        }
+        .await
+        .map_err(|e| e.to_string())?;
+
+        self.record_operation(tenant_id, INVENTORY, "update", &product_id, &stock.item.id, "success", &hash)
+            .await?;
+        Ok(if recorded.is_some() { Applied::Updated } else { Applied::Skipped })
+    }
+
+    // ------------------------------------------------------------------------
+    // Customers
+    // ------------------------------------------------------------------------
+
+    async fn sync_customers(&self, request: &CloverSyncRequest) -> Result<CloverSyncCounts, String> {
+        let mapping = self.load_mapping(&request.tenant_id, "customer", DEFAULT_CUSTOMER_MAPPING).await?;
+        let customers = self
+            .client
+            .get_all_customers(request.modified_since)
+            .await
+            .map_err(|e| format!("Failed to fetch Clover customers: {}", e))?;
+
+        let mut counts = CloverSyncCounts::default();
+        for customer in &customers {
+            let result = match self.map(&mapping, customer) {
+                Ok(mapped) => match SyncedCustomer::from_mapped(&mapped) {
+                    Some(synced) => self.apply_customer(request, customer, synced).await,
+                    None => Ok(Applied::Skipped),
+                },
+                Err(e) => Err(e),
+            };
+            counts.tally(&customer.id, result);
+        }
+
+        Ok(counts)
+    }
+
+    async fn apply_customer(
+        &self,
+        request: &CloverSyncRequest,
+        customer: &CloverCustomer,
+        synced: SyncedCustomer,
+    ) -> Result<Applied, String> {
+        let tenant_id = &request.tenant_id;
+        let platform_version = serde_json::to_string(&synced).map_err(|e| e.to_string())?;
+        let hash = data_hash(&platform_version);
+
+        let existing: Option<PosCustomer> = match self.mapped_id(tenant_id, ENTITY_CUSTOMER, &customer.id).await? {
+            Some(customer_id) => sqlx::query_as(
+                "SELECT id, name, email, phone, updated_at FROM customers WHERE id = ? AND tenant_id = ?",
+            )
+            .bind(customer_id)
+            .bind(tenant_id)
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| format!("Failed to look up customer: {}", e))?,
+            None => None,
+        };
+
+        let Some(pos) = existing else {
+            if request.dry_run {
+                return Ok(Applied::Created);
+            }
+
+            let customer_id = Uuid::new_v4().to_string();
+            let now = Utc::now().to_rfc3339();
+            let created_at = customer.customer_since.map_or_else(|| now.clone(), |ms| millis_to_rfc3339(Some(ms)));
+            sqlx::query(
+                "INSERT INTO customers (id, tenant_id, store_id, name, email, phone, created_at, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&customer_id)
+            .bind(tenant_id)
+            .bind(&request.store_id)
+            .bind(&synced.name)
+            .bind(&synced.email)
+            .bind(&synced.phone)
+            .bind(&created_at)
+            .bind(&now)
+            .execute(&self.db)
+            .await
+            .map_err(|e| format!("Failed to create customer: {}", e))?;
+
+            self.record_applied(tenant_id, ENTITY_CUSTOMER, "customer", CUSTOMERS, "create", &customer_id, &customer.id, &hash)
+                .await?;
+            return Ok(Applied::Created);
+        };
+
+        let last = self.last_sync(CUSTOMERS, &customer.id).await?;
+        if last.as_ref().and_then(|l| l.sync_hash.as_deref()) == Some(hash.as_str()) {
+            return Ok(Applied::Skipped);
+        }
+
+        let edited_in_pos = last.as_ref().is_some_and(|l| is_after(&pos.updated_at, &l.completed_at));
+        if edited_in_pos {
+            if request.dry_run {
+                return Ok(Applied::ConflictKept);
+            }
+
+            let case = ConflictCase {
+                entity_type: CUSTOMERS,
+                pos_id: &pos.id,
+                clover_id: &customer.id,
+                pos_version: serde_json::to_string(&pos.as_synced()).map_err(|e| e.to_string())?,
+                platform_version,
+                pos_updated_at: normalize_timestamp(&pos.updated_at),
+                platform_updated_at: millis_to_rfc3339(customer.modified_time),
+            };
+            match self.resolve_conflict(tenant_id, &case).await? {
+                Resolution::Platform => {}
+                Resolution::Pos => {
+                    self.record_applied(tenant_id, ENTITY_CUSTOMER, "customer", CUSTOMERS, "skip", &pos.id, &customer.id, &hash)
+                        .await?;
+                    return Ok(Applied::ConflictKept);
+                }
+                Resolution::Pending => return Ok(Applied::ConflictKept),
+            }
+        }
+
+        if request.dry_run {
+            return Ok(Applied::Updated);
+        }
+
+        sqlx::query(
+            "UPDATE customers
+             SET name = ?, email = COALESCE(?, email), phone = COALESCE(?, phone),
+                 updated_at = ?, sync_version = sync_version + 1
+             WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(&synced.name)
+        .bind(&synced.email)
+        .bind(&synced.phone)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&pos.id)
+        .bind(tenant_id)
+        .execute(&self.db)
+        .await
+        .map_err(|e| format!("Failed to update customer: {}", e))?;
+
+        self.record_applied(tenant_id, ENTITY_CUSTOMER, "customer", CUSTOMERS, "update", &pos.id, &customer.id, &hash)
+            .await?;
+        Ok(Applied::Updated)
+    }
+
+    // ------------------------------------------------------------------------
+    // Orders
+    // ------------------------------------------------------------------------
+
+    async fn sync_orders(&self, request: &CloverSyncRequest) -> Result<CloverSyncCounts, String> {
+        let mapping = self.load_mapping(&request.tenant_id, "order", DEFAULT_ORDER_MAPPING).await?;
+        let orders = self
+            .client
+            .get_all_orders(request.modified_since)
+            .await
+            .map_err(|e| format!("Failed to fetch Clover orders: {}", e))?;
+
+        let mut counts = CloverSyncCounts::default();
+        for order in &orders {
+            let result = async {
+                if !order.is_paid() || self.mapped_id(&request.tenant_id, ENTITY_ORDER, &order.id).await?.is_some() {
+                    return Ok(Applied::Skipped);
+                }
+                let mapped = self.map(&mapping, order)?;
+                if request.dry_run {
+                    return Ok(Applied::Created);
+                }
+                self.insert_order(request, order, &mapped).await?;
+                Ok::<_, String>(Applied::Created)
+            }
+            .await;
+            counts.tally(&order.id, result);
+        }
+
+        Ok(counts)
+    }
+
+    /// Record an order as a completed sale with the lines whose item was
+    /// synced; any other lines are named in the sale's notes
+    async fn insert_order(&self, request: &CloverSyncRequest, order: &CloverOrder, mapped: &Value) -> Result<(), String> {
+        let tenant_id = &request.tenant_id;
+        let customer_id = match order.customers.elements.first() {
+            Some(customer) => self.mapped_id(tenant_id, ENTITY_CUSTOMER, &customer.id).await?,
+            None => None,
+        };
+
+        let mut lines = Vec::new();
+        let mut left_out = Vec::new();
+        for line in order.line_items.elements.iter().filter(|l| l.refunded != Some(true)) {
+            let product_id = match &line.item {
+                Some(item) => self.mapped_id(tenant_id, ENTITY_ITEM, &item.id).await?,
+                None => None,
+            };
+            match product_id {
+                Some(product_id) => lines.push((product_id, line)),
+                None => left_out.push(line.name.clone().unwrap_or_else(|| "Custom amount".to_string())),
+            }
+        }
+
+        let mut notes = format!("Synced from Clover order {}", order.id);
+        if let Some(note) = mapped.get("notes").and_then(Value::as_str).filter(|n| !n.trim().is_empty()) {
+            notes.push_str(&format!(": {}", note.trim()));
+        }
+        if !left_out.is_empty() {
+            notes.push_str(&format!(". Lines not synced: {}", left_out.join(", ")));
+        }
+
+        // Clover's total includes tax and discounts; whatever is above the
+        // line prices is taken as tax and whatever is below as discount
+        let total = mapped.get("total_amount").and_then(Value::as_f64).unwrap_or(0.0);
+        let subtotal: f64 = lines
+            .iter()
+            .map(|(_, line)| cents(line.price) * line.quantity())
+            .sum();
+        let tax = (total - subtotal).max(0.0);
+        let discount = (subtotal - total).max(0.0);
+        let now = Utc::now().to_rfc3339();
+        let created_at = order.created_time.map_or_else(|| now.clone(), |ms| millis_to_rfc3339(Some(ms)));
+
+        let sale_id = Uuid::new_v4().to_string();
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            "INSERT INTO sales_transactions (
+                id, tenant_id, transaction_number, customer_id, employee_id, store_id,
+                total_amount, subtotal, tax_amount, discount_amount, items_count,
+                payment_method, payment_status, status, notes, transaction_type,
+                created_at, updated_at, completed_at
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NULL, 'completed', 'completed', ?, 'sale', ?, ?, ?)",
+        )
+        .bind(&sale_id)
+        .bind(tenant_id)
+        .bind(format!("CL-{}", order.id))
+        .bind(&customer_id)
+        .bind(SYNC_EMPLOYEE_ID)
+        .bind(&request.store_id)
+        .bind(total)
+        .bind(subtotal)
+        .bind(tax)
+        .bind(discount)
+        .bind(lines.len() as i64)
+        .bind(&notes)
+        .bind(&created_at)
+        .bind(&now)
+        .bind(&created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to create sale: {}", e))?;
+
+        for (product_id, line) in &lines {
+            let quantity = line.quantity();
+            let unit_price = cents(line.price);
+
+            sqlx::query(
+                "INSERT INTO sales_line_items (
+                    id, transaction_id, product_id, quantity, unit_price,
+                    subtotal, discount_amount, tax_amount, total, notes, created_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, 0, 0, ?, NULL, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&sale_id)
+            .bind(product_id)
+            .bind(quantity)
+            .bind(unit_price)
+            .bind(unit_price * quantity)
+            .bind(unit_price * quantity)
+            .bind(&created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to create sale line: {}", e))?;
+        }
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+
+        self.record_applied(tenant_id, ENTITY_ORDER, "sales_transaction", ORDERS, "create", &sale_id, &order.id, "")
+            .await
+    }
+
+    // ------------------------------------------------------------------------
+    // Mapping
+    // ------------------------------------------------------------------------
+
+    /// The tenant's active field mapping, or the default one
+    async fn load_mapping(&self, tenant_id: &str, entity: &str, default_json: &str) -> Result<FieldMapping, String> {
+        let mapping_id = format!("{}-to-pos-{}", PLATFORM, entity);
+        let custom: Option<String> = sqlx::query_scalar(
+            "SELECT mappings_json FROM field_mappings WHERE tenant_id = ? AND mapping_id = ? AND is_active = 1",
+        )
+        .bind(tenant_id)
+        .bind(&mapping_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to load field mapping {}: {}", mapping_id, e))?;
+
+        let field_maps = match custom {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Field mapping {} is invalid: {}", mapping_id, e))?,
+            None => serde_json::from_str::<MappingFile>(default_json)
+                .map_err(|e| format!("Default field mapping {} is invalid: {}", mapping_id, e))?
+                .mappings,
+        };
+
+        let mut mapping = FieldMapping::new(
+            tenant_id.to_string(),
+            mapping_id,
+            PLATFORM.to_string(),
+            "pos".to_string(),
+            entity.to_string(),
+        );
+        mapping.mappings = field_maps;
+        Ok(mapping)
+    }
+
+    fn map<T: Serialize>(&self, mapping: &FieldMapping, record: &T) -> Result<Value, String> {
+        let source = serde_json::to_value(record).map_err(|e| e.to_string())?;
+        self.engine.apply_mapping(mapping, &source)
+    }
+
+    // ------------------------------------------------------------------------
+    // Operations and Conflicts
+    // ------------------------------------------------------------------------
+
+    /// Record a conflict and settle it with the configured strategy
+    ///
+    /// A conflict already recorded for the same Clover data is not recorded
+    /// again: a pending one stays pending, and a resolved one is honoured.
+    async fn resolve_conflict(&self, tenant_id: &str, case: &ConflictCase<'_>) -> Result<Resolution, String> {
+        let previous: Option<(String, Option<String>, String)> = sqlx::query_as(
+            "SELECT status, resolved_version, platform_version FROM integration_sync_conflicts
+             WHERE credential_id = ? AND entity_type = ? AND entity_id = ?
+             ORDER BY created_at DESC
+             LIMIT 1",
+        )
+        .bind(&self.credential_id)
+        .bind(case.entity_type)
+        .bind(case.pos_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up conflicts: {}", e))?;
+
+        match previous {
+            Some((status, _, _)) if status == "pending" => return Ok(Resolution::Pending),
+            Some((status, resolved, platform_version))
+                if status == "resolved" && platform_version == case.platform_version =>
+            {
+                return Ok(match resolved.as_deref() {
+                    Some("platform") => Resolution::Platform,
+                    _ => Resolution::Pos,
+                });
+            }
+            _ => {}
+        }
+
+        let strategy = self
+            .direction_control
+            .get_sync_config(&self.credential_id)
+            .await?
+            .get_entity_config(case.entity_type)
+            .map(|c| c.conflict_strategy.clone())
+            .unwrap_or(ConflictStrategy::NewestWins);
+
+        let conflict_id = self
+            .direction_control
+            .create_conflict(
+                tenant_id,
+                &self.credential_id,
+                PLATFORM,
+                case.entity_type,
+                case.pos_id,
+                Some(case.clover_id),
+                &case.pos_version,
+                &case.platform_version,
+                &case.pos_updated_at,
+                &case.platform_updated_at,
+                strategy.clone(),
+            )
+            .await?;
+
+        if strategy == ConflictStrategy::Manual {
+            return Ok(Resolution::Pending);
+        }
+
+        let resolved = self
+            .direction_control
+            .apply_resolution_strategy(
+                &conflict_id,
+                strategy,
+                &case.pos_version,
+                &case.platform_version,
+                &case.pos_updated_at,
+                &case.platform_updated_at,
+            )
+            .await?;
+
+        Ok(if resolved == case.platform_version { Resolution::Platform } else { Resolution::Pos })
+    }
+
+    /// Link a Clover record to the POS record it was applied to, and log it
+    #[allow(clippy::too_many_arguments)]
+    async fn record_applied(
+        &self,
+        tenant_id: &str,
+        source_entity: &str,
+        target_entity: &str,
+        entity_type: &str,
+        operation: &str,
+        pos_id: &str,
+        clover_id: &str,
+        hash: &str,
+    ) -> Result<(), String> {
+        self.id_mapper
+            .store_mapping(tenant_id, PLATFORM, source_entity, clover_id, TARGET_SYSTEM, target_entity, pos_id)
+            .await?;
+        let status = if operation == "skip" { "skipped" } else { "success" };
+        self.record_operation(tenant_id, entity_type, operation, pos_id, clover_id, status, hash)
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn record_operation(
+        &self,
+        tenant_id: &str,
+        entity_type: &str,
+        operation: &str,
+        pos_id: &str,
+        clover_id: &str,
+        status: &str,
+        hash: &str,
+    ) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO integration_sync_operations (
+                id, tenant_id, credential_id, platform, operation_type, entity_type,
+                entity_id, platform_entity_id, status, direction, completed_at,
+                already_synced, sync_hash
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 'platform_to_pos', ?, 1, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(&self.credential_id)
+        .bind(PLATFORM)
+        .bind(operation)
+        .bind(entity_type)
+        .bind(pos_id)
+        .bind(clover_id)
+        .bind(status)
+        .bind(Utc::now().to_rfc3339())
+        .bind(Some(hash).filter(|h| !h.is_empty()))
+        .execute(&self.db)
+        .await
+        .map_err(|e| format!("Failed to record sync operation: {}", e))?;
+
+        Ok(())
+    }
+
+    /// When a Clover record was last applied, and what it looked like then
+    async fn last_sync(&self, entity_type: &str, clover_id: &str) -> Result<Option<LastSync>, String> {
+        sqlx::query_as(
+            "SELECT sync_hash, completed_at FROM integration_sync_operations
+             WHERE credential_id = ? AND entity_type = ? AND platform_entity_id = ?
+               AND direction = 'platform_to_pos' AND status IN ('success', 'skipped')
+               AND completed_at IS NOT NULL
+             ORDER BY completed_at DESC
+             LIMIT 1",
+        )
+        .bind(&self.credential_id)
+        .bind(entity_type)
+        .bind(clover_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up last sync: {}", e))
+    }
+
+    async fn mapped_id(&self, tenant_id: &str, entity: &str, clover_id: &str) -> Result<Option<String>, String> {
+        self.id_mapper
+            .get_mapping(tenant_id, PLATFORM, entity, clover_id, TARGET_SYSTEM)
+            .await
+    }
+}
+
+// ============================================================================
+// Records
+// ============================================================================
+
+/// An item's mapped fields as they land on the product
+#[derive(Debug, Serialize)]
+struct SyncedProduct {
+    sku: String,
+    name: String,
+    category: String,
+    unit_price: f64,
+    cost: f64,
+    barcode: Option<String>,
+}
+
+impl SyncedProduct {
+    fn from_mapped(mapped: &Value, item_id: &str) -> Self {
+        Self {
+            sku: mapped_str(mapped, "sku").unwrap_or_else(|| format!("CL-{}", item_id)),
+            name: mapped_str(mapped, "name").unwrap_or_else(|| "Unnamed item".to_string()),
+            category: mapped_str(mapped, "category").unwrap_or_else(|| "Uncategorized".to_string()),
+            unit_price: mapped.get("unit_price").and_then(Value::as_f64).unwrap_or(0.0),
+            cost: mapped.get("cost").and_then(Value::as_f64).unwrap_or(0.0),
+            barcode: mapped_str(mapped, "barcode"),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct PosProduct {
+    id: String,
+    sku: String,
+    name: String,
+    category: String,
+    unit_price: f64,
+    cost: f64,
+    barcode: Option<String>,
+    updated_at: String,
+}
+
+impl PosProduct {
+    fn as_synced(&self) -> SyncedProduct {
+        SyncedProduct {
+            sku: self.sku.clone(),
+            name: self.name.clone(),
+            category: self.category.clone(),
+            unit_price: self.unit_price,
+            cost: self.cost,
+            barcode: self.barcode.clone(),
+        }
+    }
+}
+
+/// A customer's mapped fields as they land on the customer
+#[derive(Debug, Serialize)]
+struct SyncedCustomer {
+    name: String,
+    email: Option<String>,
+    phone: Option<String>,
+}
+
+impl SyncedCustomer {
+    /// `None` when there is neither a name nor an email to go by
+    fn from_mapped(mapped: &Value) -> Option<Self> {
+        let email = mapped_str(mapped, "email");
+        let full_name = [mapped_str(mapped, "first_name"), mapped_str(mapped, "last_name")]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let name = Some(full_name).filter(|n| !n.is_empty()).or_else(|| email.clone())?;
+
+        Some(Self {
+            name,
+            email,
+            phone: mapped_str(mapped, "phone"),
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct PosCustomer {
+    id: String,
+    name: String,
+    email: Option<String>,
+    phone: Option<String>,
+    updated_at: String,
+}
+
+impl PosCustomer {
+    fn as_synced(&self) -> SyncedCustomer {
+        SyncedCustomer {
+            name: self.name.clone(),
+            email: self.email.clone(),
+            phone: self.phone.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+fn mapped_str(mapped: &Value, field: &str) -> Option<String> {
+    mapped
+        .get(field)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+fn cents(amount: Option<i64>) -> f64 {
+    amount.unwrap_or(0) as f64 / 100.0
+}
+
+fn data_hash(data: &str) -> String {
+    hex::encode(Sha256::digest(data.as_bytes()))
+}
+
+/// Clover epoch milliseconds as RFC 3339; now when absent
+fn millis_to_rfc3339(millis: Option<i64>) -> String {
+    millis
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .unwrap_or_else(Utc::now)
+        .to_rfc3339()
+}
+
+/// A POS timestamp, RFC 3339 or SQLite `datetime('now')`, as UTC
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|t| Utc.from_utc_datetime(&t))
+        })
+}
+
+/// A POS timestamp as RFC 3339, so it compares with Clover's
+fn normalize_timestamp(timestamp: &str) -> String {
+    parse_timestamp(timestamp).map_or_else(|| timestamp.to_string(), |t| t.to_rfc3339())
+}
+
+fn is_after(timestamp: &str, than: &str) -> bool {
+    match (parse_timestamp(timestamp), parse_timestamp(than)) {
+        (Some(a), Some(b)) => a > b,
+        _ => false,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::clover::CloverTokens;
+    use serde_json::json;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                sku TEXT NOT NULL UNIQUE,
+                name TEXT NOT NULL,
+                description TEXT,
+                category TEXT NOT NULL,
+                unit_price REAL NOT NULL,
+                cost REAL NOT NULL DEFAULT 0,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                barcode TEXT,
+                barcode_type TEXT,
+                is_active INTEGER NOT NULL DEFAULT 1,
+                sync_version INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+            "CREATE TABLE customers (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                email TEXT,
+                phone TEXT,
+                sync_version INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE TABLE sales_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_number TEXT NOT NULL,
+                customer_id TEXT,
+                employee_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                total_amount REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                items_count INTEGER NOT NULL,
+                payment_method TEXT,
+                payment_status TEXT NOT NULL,
+                status TEXT NOT NULL,
+                notes TEXT,
+                transaction_type TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                completed_at TEXT,
+                UNIQUE (tenant_id, transaction_number)
+            )",
+            "CREATE TABLE sales_line_items (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                quantity REAL NOT NULL,
+                unit_price REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                total REAL NOT NULL,
+                notes TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE settings (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            "INSERT INTO stores (id, name) VALUES ('s1', 'Main Street')",
+            include_str!("../../../../migrations/025_integration_credentials.sql"),
+            include_str!("../../../../migrations/026_field_mappings.sql"),
+            include_str!("../../../../migrations/028_sync_direction_control.sql"),
+            include_str!("../../../../migrations/029_sync_schedules.sql"),
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+            include_str!("../../../../migrations/077_outbound_webhooks.sql"),
+            include_str!("../../../../migrations/078_square_import.sql"),
+            include_str!("../../../../migrations/079_clover_sync.sql"),
+            "INSERT INTO integration_credentials (id, tenant_id, platform, credentials_encrypted)
+             VALUES ('cred-1', 't1', 'clover', 'encrypted')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    /// A latte in the Coffee category, a muffin without a SKU or category,
+    /// stock for both, one customer, and a paid and an open order
+    async fn mock_clover_merchant(latte_price: i64, latte_modified: i64) -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/merchants/M1/categories"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "elements": [{ "id": "CAT1", "name": "Coffee", "sortOrder": 1 }]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/merchants/M1/items"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "elements": [
+                    {
+                        "id": "ITEM1", "name": "Latte", "sku": "LAT-1", "code": "012345678905",
+                        "price": latte_price, "priceType": "FIXED", "cost": 120,
+                        "modifiedTime": latte_modified,
+                        "categories": { "elements": [{ "id": "CAT1" }] }
+                    },
+                    { "id": "ITEM2", "name": "Muffin", "price": 300, "modifiedTime": 1704067200000i64 }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/merchants/M1/item_stocks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "elements": [
+                    { "item": { "id": "ITEM1" }, "quantity": 12.0 },
+                    { "item": { "id": "ITEM2" }, "quantity": 4.0 },
+                    { "item": { "id": "UNKNOWN" }, "quantity": 1.0 }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/merchants/M1/customers"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "elements": [
+                    {
+                        "id": "CUST1", "firstName": "Ada", "lastName": "Lovelace",
+                        "emailAddresses": { "elements": [{ "emailAddress": "ada@example.com" }] },
+                        "phoneNumbers": { "elements": [{ "phoneNumber": "555-0100" }] },
+                        "customerSince": 1672531200000i64
+                    },
+                    { "id": "CUST2" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/merchants/M1/orders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "elements": [
+                    {
+                        "id": "ORDER1", "total": 1080, "paymentState": "PAID", "state": "locked",
+                        "customers": { "elements": [{ "id": "CUST1" }] },
+                        "lineItems": { "elements": [
+                            { "id": "L1", "name": "Latte", "price": 450, "item": { "id": "ITEM1" } },
+                            { "id": "L2", "name": "Latte", "price": 450, "item": { "id": "ITEM1" } },
+                            { "id": "L3", "name": "Gift wrap", "price": 0 }
+                        ] },
+                        "createdTime": 1704873600000i64
+                    },
+                    { "id": "ORDER2", "total": 300, "state": "open" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn flow(pool: &SqlitePool, server: &MockServer) -> CloverSyncFlow {
+        let client = CloverClient::new(CloverTokens {
+            access_token: "token".to_string(),
+            merchant_id: "M1".to_string(),
+        })
+        .unwrap()
+        .with_base_url(server.uri());
+
+        CloverSyncFlow::new(
+            pool.clone(),
+            client,
+            "cred-1",
+            Arc::new(SyncDirectionControl::new(pool.clone())),
+        )
+    }
+
+    fn request() -> CloverSyncRequest {
+        CloverSyncRequest {
+            tenant_id: "t1".to_string(),
+            store_id: "s1".to_string(),
+            sync_id: "sync-1".to_string(),
+            modified_since: None,
+            dry_run: false,
+        }
+    }
+
+    /// Mark the latte as edited in the POS after its last sync
+    async fn edit_latte_in_pos(pool: &SqlitePool) {
+        sqlx::query("UPDATE products SET name = 'House latte', updated_at = '2099-01-01T00:00:00+00:00' WHERE sku = 'LAT-1'")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn latte(pool: &SqlitePool) -> (String, f64, f64) {
+        sqlx::query_as("SELECT name, unit_price, quantity_on_hand FROM products WHERE sku = 'LAT-1'")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_first_sync_brings_over_products_stock_customers_and_orders() {
+        let pool = setup_test_db().await;
+        let server = mock_clover_merchant(450, 1704067200000).await;
+        let flow = flow(&pool, &server);
+
+        let products = flow.sync(PRODUCTS, &request()).await.unwrap();
+        assert_eq!(products, CloverSyncCounts { created: 2, ..CloverSyncCounts::default() });
+
+        let (name, category, price, cost, barcode): (String, String, f64, f64, Option<String>) =
+            sqlx::query_as("SELECT name, category, unit_price, cost, barcode FROM products WHERE sku = 'LAT-1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(name, "Latte");
+        assert_eq!(category, "Coffee");
+        assert_eq!(price, 4.5);
+        assert_eq!(cost, 1.2);
+        assert_eq!(barcode.as_deref(), Some("012345678905"));
+
+        let muffin_category: String = sqlx::query_scalar("SELECT category FROM products WHERE sku = 'CL-ITEM2'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(muffin_category, "Uncategorized");
+
+        let inventory = flow.sync(INVENTORY, &request()).await.unwrap();
+        assert_eq!(inventory, CloverSyncCounts { updated: 2, skipped: 1, ..CloverSyncCounts::default() });
+        assert_eq!(latte(&pool).await.2, 12.0);
+
+        let customers = flow.sync(CUSTOMERS, &request()).await.unwrap();
+        assert_eq!(customers, CloverSyncCounts { created: 1, skipped: 1, ..CloverSyncCounts::default() });
+        let (customer_name, email, phone): (String, Option<String>, Option<String>) =
+            sqlx::query_as("SELECT name, email, phone FROM customers")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(customer_name, "Ada Lovelace");
+        assert_eq!(email.as_deref(), Some("ada@example.com"));
+        assert_eq!(phone.as_deref(), Some("555-0100"));
+
+        let orders = flow.sync(ORDERS, &request()).await.unwrap();
+        assert_eq!(orders, CloverSyncCounts { created: 1, skipped: 1, ..CloverSyncCounts::default() });
+        let (number, total, subtotal, tax, items, customer_id, notes): (
+            String, f64, f64, f64, i64, Option<String>, String,
+        ) = sqlx::query_as(
+            "SELECT transaction_number, total_amount, subtotal, tax_amount, items_count, customer_id, notes
+             FROM sales_transactions",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(number, "CL-ORDER1");
+        assert_eq!(total, 10.8);
+        assert_eq!(subtotal, 9.0);
+        assert!((tax - 1.8).abs() < 1e-9);
+        assert_eq!(items, 2);
+        assert!(customer_id.is_some());
+        assert!(notes.contains("Gift wrap"));
+
+        // Sales brought over do not move stock
+        assert_eq!(latte(&pool).await.2, 12.0);
+
+        let logged: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM integration_sync_operations WHERE platform = 'clover' AND status = 'success'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(logged, 6);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_records_are_skipped_on_the_next_sync() {
+        let pool = setup_test_db().await;
+        let server = mock_clover_merchant(450, 1704067200000).await;
+        let flow = flow(&pool, &server);
+
+        for entity_type in [PRODUCTS, INVENTORY, CUSTOMERS, ORDERS] {
+            flow.sync(entity_type, &request()).await.unwrap();
+        }
+
+        assert_eq!(
+            flow.sync(PRODUCTS, &request()).await.unwrap(),
+            CloverSyncCounts { skipped: 2, ..CloverSyncCounts::default() }
+        );
+        assert_eq!(
+            flow.sync(INVENTORY, &request()).await.unwrap(),
+            CloverSyncCounts { skipped: 3, ..CloverSyncCounts::default() }
+        );
+        assert_eq!(
+            flow.sync(CUSTOMERS, &request()).await.unwrap(),
+            CloverSyncCounts { skipped: 2, ..CloverSyncCounts::default() }
+        );
+        assert_eq!(
+            flow.sync(ORDERS, &request()).await.unwrap(),
+            CloverSyncCounts { skipped: 2, ..CloverSyncCounts::default() }
+        );
+
+        let sales: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sales_transactions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(sales, 1);
+    }
+
+    #[tokio::test]
+    async fn test_change_in_clover_only_updates_product() {
+        let pool = setup_test_db().await;
+        let server = mock_clover_merchant(450, 1704067200000).await;
+        flow(&pool, &server).sync(PRODUCTS, &request()).await.unwrap();
+
+        let server = mock_clover_merchant(495, 1704153600000).await;
+        let counts = flow(&pool, &server).sync(PRODUCTS, &request()).await.unwrap();
+
+        assert_eq!(counts, CloverSyncCounts { updated: 1, skipped: 1, ..CloverSyncCounts::default() });
+        assert_eq!(latte(&pool).await.1, 4.95);
+    }
+
+    #[tokio::test]
+    async fn test_change_on_both_sides_is_recorded_as_conflict_and_newest_wins() {
+        let pool = setup_test_db().await;
+        let server = mock_clover_merchant(450, 1704067200000).await;
+        flow(&pool, &server).sync(PRODUCTS, &request()).await.unwrap();
+        edit_latte_in_pos(&pool).await;
+
+        // The POS edit is newer than Clover's change, so the POS keeps it
+        let server = mock_clover_merchant(495, 1704153600000).await;
+        let flow = flow(&pool, &server);
+        let counts = flow.sync(PRODUCTS, &request()).await.unwrap();
+
+        assert_eq!(counts, CloverSyncCounts { conflicts: 1, skipped: 1, ..CloverSyncCounts::default() });
+        let (name, price, _) = latte(&pool).await;
+        assert_eq!(name, "House latte");
+        assert_eq!(price, 4.5);
+
+        let (platform, entity_type, status, resolved_version, strategy): (String, String, String, Option<String>, String) =
+            sqlx::query_as(
+                "SELECT platform, entity_type, status, resolved_version, resolution_strategy
+                 FROM integration_sync_conflicts",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(platform, "clover");
+        assert_eq!(entity_type, PRODUCTS);
+        assert_eq!(status, "resolved");
+        assert_eq!(resolved_version.as_deref(), Some("pos"));
+        assert_eq!(strategy, "newest_wins");
+
+        // Settled: the same Clover data is not a conflict again
+        let counts = flow.sync(PRODUCTS, &request()).await.unwrap();
+        assert_eq!(counts, CloverSyncCounts { skipped: 2, ..CloverSyncCounts::default() });
+    }
+
+    #[tokio::test]
+    async fn test_manual_conflict_stays_pending_until_resolved() {
+        let pool = setup_test_db().await;
+        let direction_control = SyncDirectionControl::new(pool.clone());
+        let mut config = crate::services::sync_direction_control::SyncConfig::new();
+        config.add_entity(
+            PRODUCTS.to_string(),
+            crate::services::sync_direction_control::EntitySyncConfig {
+                source_of_truth: crate::services::sync_direction_control::SourceOfTruth::Platform,
+                conflict_strategy: ConflictStrategy::Manual,
+            },
+        );
+        direction_control.set_sync_config("cred-1", &config).await.unwrap();
+
+        let server = mock_clover_merchant(450, 1704067200000).await;
+        flow(&pool, &server).sync(PRODUCTS, &request()).await.unwrap();
+        edit_latte_in_pos(&pool).await;
+
+        let server = mock_clover_merchant(495, 1704153600000).await;
+        let flow = flow(&pool, &server);
+        flow.sync(PRODUCTS, &request()).await.unwrap();
+        let counts = flow.sync(PRODUCTS, &request()).await.unwrap();
+        assert_eq!(counts.conflicts, 1);
+
+        let pending = direction_control.get_pending_conflicts("t1").await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].platform_entity_id.as_deref(), Some("ITEM1"));
+        assert_eq!(latte(&pool).await.1, 4.5);
+
+        // Resolved in favour of Clover, the next sync applies it
+        direction_control
+            .resolve_conflict(&pending[0].id, "platform", &pending[0].platform_version, Some("u1"))
+            .await
+            .unwrap();
+        let counts = flow.sync(PRODUCTS, &request()).await.unwrap();
+
+        assert_eq!(counts.updated, 1);
+        let (name, price, _) = latte(&pool).await;
+        assert_eq!(name, "Latte");
+        assert_eq!(price, 4.95);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_field_mapping_replaces_default() {
+        let pool = setup_test_db().await;
+        let mappings = json!([
+            { "source_field": "alternateName", "target_field": "name", "required": true, "default_value": "No name" },
+            { "source_field": "sku", "target_field": "sku", "required": false },
+            { "source_field": "price", "target_field": "unit_price", "required": true, "transform": "centsToUnits" }
+        ]);
+        sqlx::query(
+            "INSERT INTO field_mappings (id, tenant_id, mapping_id, source_connector, target_connector, entity_type, mappings_json)
+             VALUES ('fm1', 't1', 'clover-to-pos-product', 'clover', 'pos', 'product', ?)",
+        )
+        .bind(mappings.to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let server = mock_clover_merchant(450, 1704067200000).await;
+        flow(&pool, &server).sync(PRODUCTS, &request()).await.unwrap();
+
+        let (name, category): (String, String) =
+            sqlx::query_as("SELECT name, category FROM products WHERE sku = 'LAT-1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(name, "No name");
+        assert_eq!(category, "Uncategorized");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_nothing() {
+        let pool = setup_test_db().await;
+        let server = mock_clover_merchant(450, 1704067200000).await;
+        let dry_run = CloverSyncRequest { dry_run: true, ..request() };
+
+        let counts = flow(&pool, &server).sync(PRODUCTS, &dry_run).await.unwrap();
+
+        assert_eq!(counts.created, 2);
+        let products: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM products")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(products, 0);
+    }
+}
@@ -5,10 +5,18 @@
  * - WooCommerce → QuickBooks
  * - WooCommerce → Supabase
  * - QuickBooks → Supabase
+ * - Square → EasySale (one-off import of a shop)
+ * - Clover → POS (products, stock, customers, orders)
+ * - Shopify ↔ POS (products, stock; orders from Shopify)
+ * - POS → WooCommerce (stock levels, as they change)
  * 
  * Requirements: 2.2, 2.6, 2.7
  */
 
+pub mod clover_sync;
+pub mod shopify_sync;
+pub mod square_import;
+pub mod woo_stock_push;
 pub mod woo_to_qbo;
 pub mod woo_to_supabase;
 
@@ -0,0 +1,1650 @@
+/**
+ * Shopify Sync Flow
+ *
+ * Sync flow: Shopify ↔ POS, run by the sync orchestrator one entity type
+ * and direction at a time. Each Shopify variant is one POS product.
+ * - products: pulled variants create or update POS products; pushed POS
+ *   products update their variant's SKU, price and barcode (and the title
+ *   and type of a product without options), or become new Shopify products
+ * - inventory: levels at the credential's location (the shop's first active
+ *   one when unset) against on-hand at the sync's store
+ * - orders: paid orders become completed sales, once each; pull only
+ *
+ * Directions follow the credential's sync direction control. One-way sync
+ * runs only from the entity type's source of truth (Shopify when none is
+ * set) and overwrites the other side. Two-way sync runs both ways: a record
+ * changed on both sides since its last sync is a conflict, settled with the
+ * entity type's strategy (newest wins when none is set), and stock changes
+ * on both sides are added together. Every record applied is logged in
+ * integration_sync_operations with a hash of its synced fields (the last
+ * level, for stock), which is how changes since are told apart.
+ *
+ * Sales brought over only move POS stock when the POS is the one-way source
+ * of truth for inventory; otherwise Shopify's levels already reflect them.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::connectors::shopify::client::ShopifyClient;
+use crate::connectors::shopify::orders::ShopifyOrder;
+use crate::connectors::shopify::products::{NewProduct, ProductUpdate, ShopifyProduct, ShopifyVariant, VariantUpdate};
+use crate::services::id_mapper::IdMapper;
+use crate::services::inventory_ledger_service::{
+    self, MovementType, StockMovement, REASON_SALE, REASON_SYNC, SOURCE_SALE, SOURCE_SHOPIFY_SYNC,
+};
+use crate::services::sync_direction_control::{
+    ConflictStrategy, SourceOfTruth, SyncDirection, SyncDirectionControl,
+};
+
+/// Platform name on credentials, operations and conflicts
+pub const PLATFORM: &str = "shopify";
+const TARGET_SYSTEM: &str = "easysale";
+
+const ENTITY_VARIANT: &str = "variant";
+const ENTITY_ORDER: &str = "order";
+
+/// Entity types as the orchestrator names them
+pub const PRODUCTS: &str = "products";
+pub const INVENTORY: &str = "inventory";
+pub const ORDERS: &str = "orders";
+
+/// Recorded as the employee on sales brought over from Shopify
+const SYNC_EMPLOYEE_ID: &str = "system";
+
+/// Errors kept on an entity's counts; the counts still cover every record
+const MAX_REPORTED_ERRORS: usize = 100;
+
+// ============================================================================
+// Requests and Results
+// ============================================================================
+
+/// Which way a sync runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Shopify → POS
+    Pull,
+    /// POS → Shopify
+    Push,
+}
+
+impl Direction {
+    /// As stored on integration_sync_operations
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Pull => "platform_to_pos",
+            Direction::Push => "pos_to_platform",
+        }
+    }
+}
+
+/// One entity sync for a tenant
+#[derive(Debug, Clone)]
+pub struct ShopifySyncRequest {
+    pub tenant_id: String,
+    /// Store that new products, stock levels and sales go to
+    pub store_id: String,
+    /// Orchestrator sync run, recorded on stock movements
+    pub sync_id: String,
+    /// Only records changed since (RFC 3339); `None` for all
+    pub updated_since: Option<String>,
+    /// Count what would change without writing anything
+    pub dry_run: bool,
+}
+
+/// Outcome of syncing one entity type in one direction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ShopifySyncCounts {
+    pub created: usize,
+    pub updated: usize,
+    /// Unchanged since the last sync, or with nothing to sync to
+    pub skipped: usize,
+    /// Changed on both sides and kept as the receiving side has it
+    pub conflicts: usize,
+    pub failed: usize,
+    /// Shopify ID (POS SKU, when pushing) and what went wrong, for the
+    /// first failed records
+    pub errors: Vec<(String, String)>,
+}
+
+impl ShopifySyncCounts {
+    /// Every record looked at
+    pub fn processed(&self) -> usize {
+        self.created + self.updated + self.skipped + self.conflicts + self.failed
+    }
+
+    fn tally(&mut self, id: &str, result: Result<Applied, String>) {
+        match result {
+            Ok(Applied::Created) => self.created += 1,
+            Ok(Applied::Updated) => self.updated += 1,
+            Ok(Applied::Skipped) => self.skipped += 1,
+            Ok(Applied::ConflictKept) => self.conflicts += 1,
+            Err(e) => {
+                tracing::warn!("Shopify sync of {}: {}", id, e);
+                self.failed += 1;
+                if self.errors.len() < MAX_REPORTED_ERRORS {
+                    self.errors.push((id.to_string(), e));
+                }
+            }
+        }
+    }
+}
+
+/// What happened to one record
+enum Applied {
+    Created,
+    Updated,
+    Skipped,
+    ConflictKept,
+}
+
+/// Which side a conflict went to
+enum Resolution {
+    Platform,
+    Pos,
+    Pending,
+}
+
+/// How an entity type is configured to sync
+struct EntityMode {
+    two_way: bool,
+    source_of_truth: SourceOfTruth,
+}
+
+impl EntityMode {
+    fn allows(&self, direction: Direction) -> bool {
+        self.two_way
+            || match direction {
+                Direction::Pull => self.source_of_truth == SourceOfTruth::Platform,
+                Direction::Push => self.source_of_truth == SourceOfTruth::Pos,
+            }
+    }
+}
+
+/// The last time a Shopify record was synced, either way
+#[derive(FromRow)]
+struct LastSync {
+    sync_hash: Option<String>,
+    response_data: Option<String>,
+    completed_at: String,
+}
+
+/// A record changed on both sides
+struct ConflictCase<'a> {
+    pos_id: &'a str,
+    shopify_id: &'a str,
+    pos_version: String,
+    platform_version: String,
+    pos_updated_at: String,
+    platform_updated_at: String,
+}
+
+// ============================================================================
+// Flow
+// ============================================================================
+
+/// Shopify to and from POS sync flow
+pub struct ShopifySyncFlow {
+    db: SqlitePool,
+    client: ShopifyClient,
+    /// The tenant's Shopify `integration_credentials` row
+    credential_id: String,
+    /// Location whose stock is synced; the shop's first active one when unset
+    location_id: Option<i64>,
+    id_mapper: IdMapper,
+    direction_control: Arc<SyncDirectionControl>,
+}
+
+impl ShopifySyncFlow {
+    pub fn new(
+        db: SqlitePool,
+        client: ShopifyClient,
+        credential_id: impl Into<String>,
+        location_id: Option<i64>,
+        direction_control: Arc<SyncDirectionControl>,
+    ) -> Self {
+        let id_mapper = IdMapper::new(db.clone());
+        Self {
+            db,
+            client,
+            credential_id: credential_id.into(),
+            location_id,
+            id_mapper,
+            direction_control,
+        }
+    }
+
+    /// Run the sync of one entity type in one direction
+    ///
+    /// A direction the credential's configuration does not allow syncs
+    /// nothing. A record that fails is counted and the sync carries on;
+    /// failing to read from Shopify fails the whole type.
+    pub async fn sync(
+        &self,
+        direction: Direction,
+        entity_type: &str,
+        request: &ShopifySyncRequest,
+    ) -> Result<ShopifySyncCounts, String> {
+        if ![PRODUCTS, INVENTORY, ORDERS].contains(&entity_type) {
+            return Err(format!("Unsupported entity type: {}", entity_type));
+        }
+        if entity_type == ORDERS && direction == Direction::Push {
+            return Err("Orders are only synced from Shopify".to_string());
+        }
+
+        let mode = self.entity_mode(entity_type).await?;
+        if !mode.allows(direction) {
+            tracing::info!(
+                "Shopify {} sync {} is not enabled for credential {}",
+                entity_type,
+                direction.as_str(),
+                self.credential_id
+            );
+            return Ok(ShopifySyncCounts::default());
+        }
+
+        match (entity_type, direction) {
+            (PRODUCTS, Direction::Pull) => self.pull_products(request, &mode).await,
+            (PRODUCTS, Direction::Push) => self.push_products(request, &mode).await,
+            (INVENTORY, _) => self.sync_inventory(direction, request, &mode).await,
+            _ => self.pull_orders(request).await,
+        }
+    }
+
+    async fn entity_mode(&self, entity_type: &str) -> Result<EntityMode, String> {
+        let two_way = self.direction_control.get_sync_direction(&self.credential_id).await? == SyncDirection::TwoWay;
+        let source_of_truth = self
+            .direction_control
+            .get_sync_config(&self.credential_id)
+            .await?
+            .get_entity_config(entity_type)
+            .map_or(SourceOfTruth::Platform, |c| c.source_of_truth.clone());
+
+        Ok(EntityMode { two_way, source_of_truth })
+    }
+
+    // ------------------------------------------------------------------------
+    // Products
+    // ------------------------------------------------------------------------
+
+    async fn pull_products(&self, request: &ShopifySyncRequest, mode: &EntityMode) -> Result<ShopifySyncCounts, String> {
+        let products = self
+            .client
+            .get_all_products(request.updated_since.as_deref())
+            .await
+            .map_err(|e| format!("Failed to fetch Shopify products: {}", e))?;
+
+        let mut counts = ShopifySyncCounts::default();
+        for product in &products {
+            for variant in &product.variants {
+                let result = self.pull_variant(request, mode, product, variant).await;
+                counts.tally(&variant.id.to_string(), result);
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn pull_variant(
+        &self,
+        request: &ShopifySyncRequest,
+        mode: &EntityMode,
+        product: &ShopifyProduct,
+        variant: &ShopifyVariant,
+    ) -> Result<Applied, String> {
+        let tenant_id = &request.tenant_id;
+        let shopify_id = variant.id.to_string();
+        let synced = SyncedProduct::from_shopify(product, variant);
+        let platform_version = serde_json::to_string(&synced).map_err(|e| e.to_string())?;
+        let hash = data_hash(&platform_version);
+
+        let mapped = self.mapped_id(tenant_id, ENTITY_VARIANT, &shopify_id).await?;
+        let existing: Option<PosProduct> = sqlx::query_as(
+            "SELECT id, sku, name, category, unit_price, barcode, updated_at
+             FROM products
+             WHERE tenant_id = ? AND (id = ? OR sku = ?)
+             ORDER BY id = ? DESC
+             LIMIT 1",
+        )
+        .bind(tenant_id)
+        .bind(&mapped)
+        .bind(&synced.sku)
+        .bind(&mapped)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up product: {}", e))?;
+
+        let Some(pos) = existing else {
+            if request.dry_run {
+                return Ok(Applied::Created);
+            }
+
+            let product_id = Uuid::new_v4().to_string();
+            let now = Utc::now().to_rfc3339();
+            sqlx::query(
+                "INSERT INTO products (
+                    id, tenant_id, store_id, sku, name, category, unit_price, cost,
+                    quantity_on_hand, barcode, is_active, created_at, updated_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, 0, 0, ?, 1, ?, ?)",
+            )
+            .bind(&product_id)
+            .bind(tenant_id)
+            .bind(&request.store_id)
+            .bind(&synced.sku)
+            .bind(&synced.name)
+            .bind(&synced.category)
+            .bind(synced.unit_price)
+            .bind(&synced.barcode)
+            .bind(&now)
+            .bind(&now)
+            .execute(&self.db)
+            .await
+            .map_err(|e| format!("Failed to create product: {}", e))?;
+
+            self.record_applied(tenant_id, Direction::Pull, ENTITY_VARIANT, "product", PRODUCTS, "create", &product_id, &shopify_id, &hash)
+                .await?;
+            return Ok(Applied::Created);
+        };
+
+        let last = self.last_sync(PRODUCTS, &shopify_id).await?;
+        let last_hash = last.as_ref().and_then(|l| l.sync_hash.as_deref());
+        if last_hash == Some(hash.as_str()) {
+            return Ok(Applied::Skipped);
+        }
+
+        let pos_version = serde_json::to_string(&pos.as_synced()).map_err(|e| e.to_string())?;
+        if pos_version == platform_version {
+            if !request.dry_run {
+                self.record_applied(tenant_id, Direction::Pull, ENTITY_VARIANT, "product", PRODUCTS, "skip", &pos.id, &shopify_id, &hash)
+                    .await?;
+            }
+            return Ok(Applied::Skipped);
+        }
+
+        let edited_in_pos = mode.two_way && last.as_ref().is_some_and(|l| is_after(&pos.updated_at, &l.completed_at));
+        if edited_in_pos {
+            if request.dry_run {
+                return Ok(Applied::ConflictKept);
+            }
+
+            let case = ConflictCase {
+                pos_id: &pos.id,
+                shopify_id: &shopify_id,
+                pos_version,
+                platform_version: platform_version.clone(),
+                pos_updated_at: normalize_timestamp(&pos.updated_at),
+                platform_updated_at: normalize_timestamp(&variant_updated_at(product, variant)),
+            };
+            match self.resolve_conflict(tenant_id, &case).await? {
+                Resolution::Platform => {}
+                Resolution::Pos => {
+                    self.record_applied(tenant_id, Direction::Pull, ENTITY_VARIANT, "product", PRODUCTS, "skip", &pos.id, &shopify_id, &hash)
+                        .await?;
+                    return Ok(Applied::ConflictKept);
+                }
+                Resolution::Pending => return Ok(Applied::ConflictKept),
+            }
+        }
+
+        if request.dry_run {
+            return Ok(Applied::Updated);
+        }
+
+        sqlx::query(
+            "UPDATE products
+             SET sku = ?, name = ?, category = ?, unit_price = ?,
+                 barcode = COALESCE(?, barcode), is_active = 1,
+                 updated_at = ?, sync_version = sync_version + 1
+             WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(&synced.sku)
+        .bind(&synced.name)
+        .bind(&synced.category)
+        .bind(synced.unit_price)
+        .bind(&synced.barcode)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&pos.id)
+        .bind(tenant_id)
+        .execute(&self.db)
+        .await
+        .map_err(|e| format!("Failed to update product: {}", e))?;
+
+        self.record_applied(tenant_id, Direction::Pull, ENTITY_VARIANT, "product", PRODUCTS, "update", &pos.id, &shopify_id, &hash)
+            .await?;
+        Ok(Applied::Updated)
+    }
+
+    async fn push_products(&self, request: &ShopifySyncRequest, mode: &EntityMode) -> Result<ShopifySyncCounts, String> {
+        let tenant_id = &request.tenant_id;
+        let shopify_products = self
+            .client
+            .get_all_products(None)
+            .await
+            .map_err(|e| format!("Failed to fetch Shopify products: {}", e))?;
+
+        let mut variants = HashMap::new();
+        let mut by_sku = HashMap::new();
+        for product in &shopify_products {
+            for variant in &product.variants {
+                variants.insert(variant.id, (product, variant));
+                if let Some(sku) = variant.sku.as_deref().filter(|s| !s.trim().is_empty()) {
+                    by_sku.insert(sku.trim().to_string(), variant.id);
+                }
+            }
+        }
+        let linked: HashMap<String, i64> = self
+            .linked_variants(tenant_id)
+            .await?
+            .into_iter()
+            .map(|(variant_id, pos_id)| (pos_id, variant_id))
+            .collect();
+
+        let pos_products: Vec<PosProduct> = sqlx::query_as(
+            "SELECT id, sku, name, category, unit_price, barcode, updated_at
+             FROM products
+             WHERE tenant_id = ? AND is_active = 1
+             ORDER BY sku",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| format!("Failed to load products: {}", e))?;
+
+        let mut counts = ShopifySyncCounts::default();
+        for pos in &pos_products {
+            if let Some(since) = &request.updated_since {
+                if !is_after(&pos.updated_at, since) {
+                    continue;
+                }
+            }
+
+            let found = linked
+                .get(&pos.id)
+                .or_else(|| by_sku.get(&pos.sku))
+                .and_then(|variant_id| variants.get(variant_id))
+                .copied();
+            let result = self.push_product(request, mode, pos, found).await;
+            counts.tally(&pos.sku, result);
+        }
+
+        Ok(counts)
+    }
+
+    async fn push_product(
+        &self,
+        request: &ShopifySyncRequest,
+        mode: &EntityMode,
+        pos: &PosProduct,
+        found: Option<(&ShopifyProduct, &ShopifyVariant)>,
+    ) -> Result<Applied, String> {
+        let tenant_id = &request.tenant_id;
+
+        let Some((product, variant)) = found else {
+            if request.dry_run {
+                return Ok(Applied::Created);
+            }
+
+            let created = self
+                .client
+                .create_product(&NewProduct {
+                    title: pos.name.clone(),
+                    product_type: pos.category.clone(),
+                    variant: pos.variant_update(),
+                })
+                .await
+                .map_err(|e| format!("Failed to create Shopify product: {}", e))?;
+            let variant = created
+                .variants
+                .first()
+                .ok_or_else(|| "Shopify created the product without a variant".to_string())?;
+            let hash = data_hash(&serde_json::to_string(&SyncedProduct::from_shopify(&created, variant)).map_err(|e| e.to_string())?);
+
+            self.record_applied(tenant_id, Direction::Push, ENTITY_VARIANT, "product", PRODUCTS, "create", &pos.id, &variant.id.to_string(), &hash)
+                .await?;
+            return Ok(Applied::Created);
+        };
+
+        let shopify_id = variant.id.to_string();
+        let remote = SyncedProduct::from_shopify(product, variant);
+        let expected = pos.as_pushed(product, &remote);
+        let platform_version = serde_json::to_string(&remote).map_err(|e| e.to_string())?;
+        let pos_version = serde_json::to_string(&expected).map_err(|e| e.to_string())?;
+        let hash = data_hash(&pos_version);
+
+        let last = self.last_sync(PRODUCTS, &shopify_id).await?;
+        if pos_version == platform_version {
+            if !request.dry_run && last.as_ref().and_then(|l| l.sync_hash.as_deref()) != Some(hash.as_str()) {
+                self.record_applied(tenant_id, Direction::Push, ENTITY_VARIANT, "product", PRODUCTS, "skip", &pos.id, &shopify_id, &hash)
+                    .await?;
+            }
+            return Ok(Applied::Skipped);
+        }
+
+        let shopify_updated_at = variant_updated_at(product, variant);
+        if let Some(last) = last.as_ref().filter(|_| mode.two_way) {
+            if is_after(&shopify_updated_at, &last.completed_at) {
+                // Changed in Shopify only: the pull brings it over
+                if !is_after(&pos.updated_at, &last.completed_at) {
+                    return Ok(Applied::Skipped);
+                }
+                if request.dry_run {
+                    return Ok(Applied::ConflictKept);
+                }
+
+                let case = ConflictCase {
+                    pos_id: &pos.id,
+                    shopify_id: &shopify_id,
+                    pos_version,
+                    platform_version,
+                    pos_updated_at: normalize_timestamp(&pos.updated_at),
+                    platform_updated_at: normalize_timestamp(&shopify_updated_at),
+                };
+                match self.resolve_conflict(tenant_id, &case).await? {
+                    Resolution::Pos => {}
+                    Resolution::Platform | Resolution::Pending => return Ok(Applied::ConflictKept),
+                }
+            }
+        }
+
+        if request.dry_run {
+            return Ok(Applied::Updated);
+        }
+
+        self.client
+            .update_variant(variant.id, &pos.variant_update())
+            .await
+            .map_err(|e| format!("Failed to update Shopify variant: {}", e))?;
+        if !product.has_options() && (remote.name != expected.name || remote.category != expected.category) {
+            self.client
+                .update_product(
+                    product.id,
+                    &ProductUpdate {
+                        title: expected.name.clone(),
+                        product_type: expected.category.clone(),
+                    },
+                )
+                .await
+                .map_err(|e| format!("Failed to update Shopify product: {}", e))?;
+        }
+
+        self.record_applied(tenant_id, Direction::Push, ENTITY_VARIANT, "product", PRODUCTS, "update", &pos.id, &shopify_id, &hash)
+            .await?;
+        Ok(Applied::Updated)
+    }
+
+    // ------------------------------------------------------------------------
+    // Inventory
+    // ------------------------------------------------------------------------
+
+    async fn sync_inventory(
+        &self,
+        direction: Direction,
+        request: &ShopifySyncRequest,
+        mode: &EntityMode,
+    ) -> Result<ShopifySyncCounts, String> {
+        let location_id = match self.location_id {
+            Some(location_id) => location_id,
+            None => self
+                .client
+                .get_locations()
+                .await
+                .map_err(|e| format!("Failed to fetch Shopify locations: {}", e))?
+                .first()
+                .map(|l| l.id)
+                .ok_or_else(|| "The Shopify shop has no active location".to_string())?,
+        };
+
+        let products = self
+            .client
+            .get_all_products(None)
+            .await
+            .map_err(|e| format!("Failed to fetch Shopify products: {}", e))?;
+        let linked = self.linked_variants(&request.tenant_id).await?;
+
+        let item_ids: Vec<i64> = products
+            .iter()
+            .flat_map(|p| &p.variants)
+            .filter(|v| linked.contains_key(&v.id))
+            .filter_map(|v| v.inventory_item_id)
+            .collect();
+        let levels: HashMap<i64, i64> = self
+            .client
+            .get_inventory_levels(location_id, &item_ids)
+            .await
+            .map_err(|e| format!("Failed to fetch Shopify inventory levels: {}", e))?
+            .into_iter()
+            .filter_map(|level| level.available.map(|available| (level.inventory_item_id, available)))
+            .collect();
+
+        let mut counts = ShopifySyncCounts::default();
+        for variant in products.iter().flat_map(|p| &p.variants) {
+            let tracked = linked
+                .get(&variant.id)
+                .zip(variant.inventory_item_id)
+                .and_then(|(pos_id, item_id)| levels.get(&item_id).map(|level| (pos_id, item_id, *level)));
+            let result = match tracked {
+                Some((pos_id, item_id, level)) => {
+                    self.apply_level(direction, request, mode, location_id, pos_id, variant.id, item_id, level)
+                        .await
+                }
+                None => Ok(Applied::Skipped),
+            };
+            counts.tally(&variant.id.to_string(), result);
+        }
+
+        Ok(counts)
+    }
+
+    /// Bring a variant's Shopify level and its product's on-hand at the
+    /// store together
+    ///
+    /// One-way, the source of truth's level is written to the other side.
+    /// Two-way, each side's change since the last synced level is added to
+    /// it and the total written to both.
+    #[allow(clippy::too_many_arguments)]
+    async fn apply_level(
+        &self,
+        direction: Direction,
+        request: &ShopifySyncRequest,
+        mode: &EntityMode,
+        location_id: i64,
+        pos_id: &str,
+        variant_id: i64,
+        inventory_item_id: i64,
+        shopify_level: i64,
+    ) -> Result<Applied, String> {
+        let tenant_id = &request.tenant_id;
+        let shopify_id = variant_id.to_string();
+        let pos_level = self.pos_level(tenant_id, pos_id, &request.store_id).await?.floor() as i64;
+        let last_level = self
+            .last_sync(INVENTORY, &shopify_id)
+            .await?
+            .and_then(|l| l.response_data)
+            .and_then(|level| level.parse::<i64>().ok());
+
+        let level = match (mode.two_way, last_level, direction) {
+            (true, Some(base), _) => shopify_level + pos_level - base,
+            (_, _, Direction::Pull) => shopify_level,
+            (_, _, Direction::Push) => pos_level,
+        };
+        let write_pos = pos_level != level && (mode.two_way || direction == Direction::Pull);
+        let write_shopify = shopify_level != level && (mode.two_way || direction == Direction::Push);
+
+        if !write_pos && !write_shopify {
+            if !request.dry_run && last_level != Some(level) {
+                self.record_operation(tenant_id, INVENTORY, direction, "skip", pos_id, &shopify_id, "skipped", "", Some(level))
+                    .await?;
+            }
+            return Ok(Applied::Skipped);
+        }
+        if request.dry_run {
+            return Ok(Applied::Updated);
+        }
+
+        if write_pos {
+            let movement = StockMovement::new(tenant_id.as_str(), pos_id, MovementType::Adjustment, 0.0, REASON_SYNC)
+                .at_store(&request.store_id)
+                .with_source(SOURCE_SHOPIFY_SYNC, &request.sync_id)
+                .with_notes(Some("Shopify stock level".to_string()));
+
+            async {
+                let mut tx = self.db.begin().await?;
+                inventory_ledger_service::set_on_hand(&mut tx, movement, level as f64).await?;
+                tx.commit().await?;
+                Ok::<_, inventory_ledger_service::LedgerError>(())
+            }
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        if write_shopify {
+            self.client
+                .set_inventory_level(location_id, inventory_item_id, level)
+                .await
+                .map_err(|e| format!("Failed to set Shopify inventory level: {}", e))?;
+        }
+
+        self.record_operation(tenant_id, INVENTORY, direction, "update", pos_id, &shopify_id, "success", "", Some(level))
+            .await?;
+        Ok(Applied::Updated)
+    }
+
+    /// A product's on-hand at a store
+    async fn pos_level(&self, tenant_id: &str, product_id: &str, store_id: &str) -> Result<f64, String> {
+        let level: Option<f64> = sqlx::query_scalar(
+            "SELECT quantity_on_hand FROM product_locations
+             WHERE tenant_id = ? AND product_id = ? AND store_id = ?",
+        )
+        .bind(tenant_id)
+        .bind(product_id)
+        .bind(store_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up stock: {}", e))?;
+
+        Ok(level.unwrap_or(0.0))
+    }
+
+    // ------------------------------------------------------------------------
+    // Orders
+    // ------------------------------------------------------------------------
+
+    async fn pull_orders(&self, request: &ShopifySyncRequest) -> Result<ShopifySyncCounts, String> {
+        let orders = self
+            .client
+            .get_paid_orders(request.updated_since.as_deref())
+            .await
+            .map_err(|e| format!("Failed to fetch Shopify orders: {}", e))?;
+        let inventory = self.entity_mode(INVENTORY).await?;
+        let move_stock = !inventory.two_way && inventory.source_of_truth == SourceOfTruth::Pos;
+
+        let mut counts = ShopifySyncCounts::default();
+        for order in &orders {
+            let shopify_id = order.id.to_string();
+            let result = async {
+                if !order.is_paid() || self.mapped_id(&request.tenant_id, ENTITY_ORDER, &shopify_id).await?.is_some() {
+                    return Ok(Applied::Skipped);
+                }
+                if request.dry_run {
+                    return Ok(Applied::Created);
+                }
+                self.insert_order(request, order, move_stock).await?;
+                Ok::<_, String>(Applied::Created)
+            }
+            .await;
+            counts.tally(&shopify_id, result);
+        }
+
+        Ok(counts)
+    }
+
+    /// Record an order as a completed sale with the lines whose variant was
+    /// synced; any other lines are named in the sale's notes
+    async fn insert_order(&self, request: &ShopifySyncRequest, order: &ShopifyOrder, move_stock: bool) -> Result<(), String> {
+        let tenant_id = &request.tenant_id;
+
+        let mut lines = Vec::new();
+        let mut left_out = Vec::new();
+        for line in &order.line_items {
+            let product_id = match line.variant_id {
+                Some(variant_id) => self.mapped_id(tenant_id, ENTITY_VARIANT, &variant_id.to_string()).await?,
+                None => None,
+            };
+            match product_id {
+                Some(product_id) => lines.push((product_id, line)),
+                None => left_out.push(line.title.clone().unwrap_or_else(|| "Custom item".to_string())),
+            }
+        }
+
+        let number = order
+            .name
+            .as_deref()
+            .map(|n| n.trim_start_matches('#').to_string())
+            .unwrap_or_else(|| order.id.to_string());
+        let mut notes = format!("Synced from Shopify order #{}", number);
+        if let Some(note) = order.note.as_deref().filter(|n| !n.trim().is_empty()) {
+            notes.push_str(&format!(": {}", note.trim()));
+        }
+        if !left_out.is_empty() {
+            notes.push_str(&format!(". Lines not synced: {}", left_out.join(", ")));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let created_at = order.created_at.as_deref().map_or_else(|| now.clone(), normalize_timestamp);
+
+        let sale_id = Uuid::new_v4().to_string();
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            "INSERT INTO sales_transactions (
+                id, tenant_id, transaction_number, customer_id, employee_id, store_id,
+                total_amount, subtotal, tax_amount, discount_amount, items_count,
+                payment_method, payment_status, status, notes, transaction_type,
+                created_at, updated_at, completed_at
+             ) VALUES (?, ?, ?, NULL, ?, ?, ?, ?, ?, ?, ?, NULL, 'completed', 'completed', ?, 'sale', ?, ?, ?)",
+        )
+        .bind(&sale_id)
+        .bind(tenant_id)
+        .bind(format!("SH-{}", number))
+        .bind(SYNC_EMPLOYEE_ID)
+        .bind(&request.store_id)
+        .bind(amount(&order.total_price))
+        .bind(amount(&order.subtotal_price))
+        .bind(amount(&order.total_tax))
+        .bind(amount(&order.total_discounts))
+        .bind(lines.len() as i64)
+        .bind(&notes)
+        .bind(&created_at)
+        .bind(&now)
+        .bind(&created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to create sale: {}", e))?;
+
+        for (product_id, line) in &lines {
+            let quantity = line.quantity as f64;
+            let unit_price = amount(&line.price);
+
+            sqlx::query(
+                "INSERT INTO sales_line_items (
+                    id, transaction_id, product_id, quantity, unit_price,
+                    subtotal, discount_amount, tax_amount, total, notes, created_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, 0, 0, ?, NULL, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&sale_id)
+            .bind(product_id)
+            .bind(quantity)
+            .bind(unit_price)
+            .bind(unit_price * quantity)
+            .bind(unit_price * quantity)
+            .bind(&created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to create sale line: {}", e))?;
+
+            if move_stock && line.quantity > 0 {
+                let movement = StockMovement::new(tenant_id.as_str(), product_id, MovementType::Sale, -quantity, REASON_SALE)
+                    .at_store(&request.store_id)
+                    .with_source(SOURCE_SALE, &sale_id);
+                inventory_ledger_service::record_movement(&mut tx, &movement)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+
+        self.record_applied(tenant_id, Direction::Pull, ENTITY_ORDER, "sales_transaction", ORDERS, "create", &sale_id, &order.id.to_string(), "")
+            .await
+    }
+
+    // ------------------------------------------------------------------------
+    // Operations and Conflicts
+    // ------------------------------------------------------------------------
+
+    /// Record a product conflict and settle it with the configured strategy
+    ///
+    /// A conflict already recorded for the same Shopify data is not recorded
+    /// again: a pending one stays pending, and a resolved one is honoured.
+    async fn resolve_conflict(&self, tenant_id: &str, case: &ConflictCase<'_>) -> Result<Resolution, String> {
+        let previous: Option<(String, Option<String>, String)> = sqlx::query_as(
+            "SELECT status, resolved_version, platform_version FROM integration_sync_conflicts
+             WHERE credential_id = ? AND entity_type = ? AND entity_id = ?
+             ORDER BY created_at DESC
+             LIMIT 1",
+        )
+        .bind(&self.credential_id)
+        .bind(PRODUCTS)
+        .bind(case.pos_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up conflicts: {}", e))?;
+
+        match previous {
+            Some((status, _, _)) if status == "pending" => return Ok(Resolution::Pending),
+            Some((status, resolved, platform_version))
+                if status == "resolved" && platform_version == case.platform_version =>
+            {
+                return Ok(match resolved.as_deref() {
+                    Some("platform") => Resolution::Platform,
+                    _ => Resolution::Pos,
+                });
+            }
+            _ => {}
+        }
+
+        let strategy = self
+            .direction_control
+            .get_sync_config(&self.credential_id)
+            .await?
+            .get_entity_config(PRODUCTS)
+            .map(|c| c.conflict_strategy.clone())
+            .unwrap_or(ConflictStrategy::NewestWins);
+
+        let conflict_id = self
+            .direction_control
+            .create_conflict(
+                tenant_id,
+                &self.credential_id,
+                PLATFORM,
+                PRODUCTS,
+                case.pos_id,
+                Some(case.shopify_id),
+                &case.pos_version,
+                &case.platform_version,
+                &case.pos_updated_at,
+                &case.platform_updated_at,
+                strategy.clone(),
+            )
+            .await?;
+
+        if strategy == ConflictStrategy::Manual {
+            return Ok(Resolution::Pending);
+        }
+
+        let resolved = self
+            .direction_control
+            .apply_resolution_strategy(
+                &conflict_id,
+                strategy,
+                &case.pos_version,
+                &case.platform_version,
+                &case.pos_updated_at,
+                &case.platform_updated_at,
+            )
+            .await?;
+
+        Ok(if resolved == case.platform_version { Resolution::Platform } else { Resolution::Pos })
+    }
+
+    /// Link a Shopify record to the POS record it was synced with, and log it
+    #[allow(clippy::too_many_arguments)]
+    async fn record_applied(
+        &self,
+        tenant_id: &str,
+        direction: Direction,
+        source_entity: &str,
+        target_entity: &str,
+        entity_type: &str,
+        operation: &str,
+        pos_id: &str,
+        shopify_id: &str,
+        hash: &str,
+    ) -> Result<(), String> {
+        self.id_mapper
+            .store_mapping(tenant_id, PLATFORM, source_entity, shopify_id, TARGET_SYSTEM, target_entity, pos_id)
+            .await?;
+        let status = if operation == "skip" { "skipped" } else { "success" };
+        self.record_operation(tenant_id, entity_type, direction, operation, pos_id, shopify_id, status, hash, None)
+            .await
+    }
+
+    /// Log an operation; `level` is the stock level both sides were left at
+    #[allow(clippy::too_many_arguments)]
+    async fn record_operation(
+        &self,
+        tenant_id: &str,
+        entity_type: &str,
+        direction: Direction,
+        operation: &str,
+        pos_id: &str,
+        shopify_id: &str,
+        status: &str,
+        hash: &str,
+        level: Option<i64>,
+    ) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO integration_sync_operations (
+                id, tenant_id, credential_id, platform, operation_type, entity_type,
+                entity_id, platform_entity_id, status, direction, completed_at,
+                already_synced, sync_hash, response_data
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(&self.credential_id)
+        .bind(PLATFORM)
+        .bind(operation)
+        .bind(entity_type)
+        .bind(pos_id)
+        .bind(shopify_id)
+        .bind(status)
+        .bind(direction.as_str())
+        .bind(Utc::now().to_rfc3339())
+        .bind(Some(hash).filter(|h| !h.is_empty()))
+        .bind(level.map(|l| l.to_string()))
+        .execute(&self.db)
+        .await
+        .map_err(|e| format!("Failed to record sync operation: {}", e))?;
+
+        Ok(())
+    }
+
+    /// When a Shopify record was last synced either way, and what it looked
+    /// like then
+    async fn last_sync(&self, entity_type: &str, shopify_id: &str) -> Result<Option<LastSync>, String> {
+        sqlx::query_as(
+            "SELECT sync_hash, response_data, completed_at FROM integration_sync_operations
+             WHERE credential_id = ? AND entity_type = ? AND platform_entity_id = ?
+               AND status IN ('success', 'skipped')
+               AND completed_at IS NOT NULL
+             ORDER BY completed_at DESC
+             LIMIT 1",
+        )
+        .bind(&self.credential_id)
+        .bind(entity_type)
+        .bind(shopify_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up last sync: {}", e))
+    }
+
+    async fn mapped_id(&self, tenant_id: &str, entity: &str, shopify_id: &str) -> Result<Option<String>, String> {
+        self.id_mapper
+            .get_mapping(tenant_id, PLATFORM, entity, shopify_id, TARGET_SYSTEM)
+            .await
+    }
+
+    /// Every synced variant, with the POS product it is linked to
+    async fn linked_variants(&self, tenant_id: &str) -> Result<HashMap<i64, String>, String> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT source_id, target_id FROM id_mappings
+             WHERE tenant_id = ? AND source_system = ? AND source_entity = ? AND target_system = ?",
+        )
+        .bind(tenant_id)
+        .bind(PLATFORM)
+        .bind(ENTITY_VARIANT)
+        .bind(TARGET_SYSTEM)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| format!("Failed to load variant links: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(variant_id, pos_id)| variant_id.parse().ok().map(|id| (id, pos_id)))
+            .collect())
+    }
+}
+
+// ============================================================================
+// Records
+// ============================================================================
+
+/// A variant's fields as they land on the product
+#[derive(Debug, Serialize)]
+struct SyncedProduct {
+    sku: String,
+    name: String,
+    category: String,
+    unit_price: f64,
+    barcode: Option<String>,
+}
+
+impl SyncedProduct {
+    fn from_shopify(product: &ShopifyProduct, variant: &ShopifyVariant) -> Self {
+        let name = match non_empty(&variant.title) {
+            Some(title) if product.has_options() => format!("{} - {}", product.title.trim(), title),
+            _ => product.title.trim().to_string(),
+        };
+
+        Self {
+            sku: non_empty(&variant.sku).unwrap_or_else(|| format!("SH-{}", variant.id)),
+            name,
+            category: non_empty(&product.product_type).unwrap_or_else(|| "Uncategorized".to_string()),
+            unit_price: amount(&variant.price),
+            barcode: non_empty(&variant.barcode),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct PosProduct {
+    id: String,
+    sku: String,
+    name: String,
+    category: String,
+    unit_price: f64,
+    barcode: Option<String>,
+    updated_at: String,
+}
+
+impl PosProduct {
+    fn as_synced(&self) -> SyncedProduct {
+        SyncedProduct {
+            sku: self.sku.clone(),
+            name: self.name.clone(),
+            category: self.category.clone(),
+            unit_price: self.unit_price,
+            barcode: self.barcode.clone(),
+        }
+    }
+
+    /// How a variant looks once this product is pushed to it; the name and
+    /// type of a product with options are left as they are
+    fn as_pushed(&self, product: &ShopifyProduct, remote: &SyncedProduct) -> SyncedProduct {
+        let (name, category) = if product.has_options() {
+            (remote.name.clone(), remote.category.clone())
+        } else {
+            (self.name.clone(), self.category.clone())
+        };
+
+        SyncedProduct {
+            sku: self.sku.clone(),
+            name,
+            category,
+            unit_price: round_cents(self.unit_price),
+            barcode: self.barcode.clone().or_else(|| remote.barcode.clone()),
+        }
+    }
+
+    fn variant_update(&self) -> VariantUpdate {
+        VariantUpdate {
+            sku: self.sku.clone(),
+            price: format!("{:.2}", self.unit_price),
+            barcode: self.barcode.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// A Shopify decimal string; zero when absent
+fn amount(value: &Option<String>) -> f64 {
+    value.as_deref().and_then(|v| v.trim().parse().ok()).unwrap_or(0.0)
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn data_hash(data: &str) -> String {
+    hex::encode(Sha256::digest(data.as_bytes()))
+}
+
+/// The later of a variant's and its product's last change
+fn variant_updated_at(product: &ShopifyProduct, variant: &ShopifyVariant) -> String {
+    match (&product.updated_at, &variant.updated_at) {
+        (Some(p), Some(v)) if is_after(p, v) => p.clone(),
+        (_, Some(v)) => v.clone(),
+        (Some(p), None) => p.clone(),
+        (None, None) => Utc::now().to_rfc3339(),
+    }
+}
+
+/// A timestamp, RFC 3339 or SQLite `datetime('now')`, as UTC
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|t| Utc.from_utc_datetime(&t))
+        })
+}
+
+/// A timestamp as UTC RFC 3339, so POS and Shopify ones compare
+fn normalize_timestamp(timestamp: &str) -> String {
+    parse_timestamp(timestamp).map_or_else(|| timestamp.to_string(), |t| t.to_rfc3339())
+}
+
+fn is_after(timestamp: &str, than: &str) -> bool {
+    match (parse_timestamp(timestamp), parse_timestamp(than)) {
+        (Some(a), Some(b)) => a > b,
+        _ => false,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::shopify::ShopifyTokens;
+    use crate::services::sync_direction_control::{EntitySyncConfig, SyncConfig};
+    use serde_json::json;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                sku TEXT NOT NULL UNIQUE,
+                name TEXT NOT NULL,
+                description TEXT,
+                category TEXT NOT NULL,
+                unit_price REAL NOT NULL,
+                cost REAL NOT NULL DEFAULT 0,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                barcode TEXT,
+                barcode_type TEXT,
+                is_active INTEGER NOT NULL DEFAULT 1,
+                sync_version INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+            "CREATE TABLE sales_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_number TEXT NOT NULL,
+                customer_id TEXT,
+                employee_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                total_amount REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                items_count INTEGER NOT NULL,
+                payment_method TEXT,
+                payment_status TEXT NOT NULL,
+                status TEXT NOT NULL,
+                notes TEXT,
+                transaction_type TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                completed_at TEXT,
+                UNIQUE (tenant_id, transaction_number)
+            )",
+            "CREATE TABLE sales_line_items (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                quantity REAL NOT NULL,
+                unit_price REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                total REAL NOT NULL,
+                notes TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE settings (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            "INSERT INTO stores (id, name) VALUES ('s1', 'Main Street')",
+            include_str!("../../../../migrations/025_integration_credentials.sql"),
+            include_str!("../../../../migrations/026_field_mappings.sql"),
+            include_str!("../../../../migrations/028_sync_direction_control.sql"),
+            include_str!("../../../../migrations/029_sync_schedules.sql"),
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+            include_str!("../../../../migrations/077_outbound_webhooks.sql"),
+            include_str!("../../../../migrations/078_square_import.sql"),
+            include_str!("../../../../migrations/079_clover_sync.sql"),
+            "INSERT INTO integration_credentials (id, tenant_id, platform, credentials_encrypted)
+             VALUES ('cred-1', 't1', 'shopify', 'encrypted')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    /// A latte without options, a T-shirt in two sizes (the large one
+    /// without a SKU or tracked stock), one location, and a paid and a
+    /// pending order
+    async fn mock_shop(latte_price: &str, latte_updated_at: &str, latte_level: i64) -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/products.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "products": [
+                    {
+                        "id": 1, "title": "Latte", "product_type": "Coffee", "updated_at": latte_updated_at,
+                        "variants": [{
+                            "id": 11, "product_id": 1, "title": "Default Title", "sku": "LAT-1",
+                            "barcode": "012345678905", "price": latte_price, "inventory_item_id": 111,
+                            "updated_at": latte_updated_at
+                        }]
+                    },
+                    {
+                        "id": 2, "title": "T-Shirt", "product_type": "Apparel", "updated_at": "2024-01-01T00:00:00-05:00",
+                        "variants": [
+                            { "id": 21, "product_id": 2, "title": "Small", "sku": "TS-S", "price": "20.00", "inventory_item_id": 211 },
+                            { "id": 22, "product_id": 2, "title": "Large", "sku": "", "price": "22.00", "inventory_item_id": 212 }
+                        ]
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/locations.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "locations": [{ "id": 1, "name": "Warehouse", "active": true }]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/inventory_levels.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "inventory_levels": [
+                    { "inventory_item_id": 111, "location_id": 1, "available": latte_level },
+                    { "inventory_item_id": 211, "location_id": 1, "available": 3 },
+                    { "inventory_item_id": 212, "location_id": 1, "available": null }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/orders.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "orders": [
+                    {
+                        "id": 1001001, "name": "#1001", "total_price": "10.80", "subtotal_price": "9.00",
+                        "total_tax": "1.80", "total_discounts": "0.00", "financial_status": "paid",
+                        "created_at": "2024-01-10T08:00:00-05:00",
+                        "line_items": [
+                            { "id": 1, "variant_id": 11, "title": "Latte", "quantity": 2, "price": "4.50" },
+                            { "id": 2, "variant_id": null, "title": "Gift wrap", "quantity": 1, "price": "0.00" }
+                        ]
+                    },
+                    { "id": 1002002, "name": "#1002", "total_price": "20.00", "financial_status": "pending" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    async fn default_shop() -> MockServer {
+        mock_shop("4.50", "2024-01-01T00:00:00-05:00", 12).await
+    }
+
+    fn flow(pool: &SqlitePool, server: &MockServer) -> ShopifySyncFlow {
+        let client = ShopifyClient::new(ShopifyTokens {
+            access_token: "shpat_test".to_string(),
+            shop_domain: "example.myshopify.com".to_string(),
+            scope: None,
+        })
+        .unwrap()
+        .with_base_url(server.uri());
+
+        ShopifySyncFlow::new(
+            pool.clone(),
+            client,
+            "cred-1",
+            None,
+            Arc::new(SyncDirectionControl::new(pool.clone())),
+        )
+    }
+
+    fn request() -> ShopifySyncRequest {
+        ShopifySyncRequest {
+            tenant_id: "t1".to_string(),
+            store_id: "s1".to_string(),
+            sync_id: "sync-1".to_string(),
+            updated_since: None,
+            dry_run: false,
+        }
+    }
+
+    async fn two_way(pool: &SqlitePool) {
+        SyncDirectionControl::new(pool.clone())
+            .set_sync_direction("cred-1", SyncDirection::TwoWay)
+            .await
+            .unwrap();
+    }
+
+    async fn latte(pool: &SqlitePool) -> (String, f64, f64) {
+        sqlx::query_as("SELECT name, unit_price, quantity_on_hand FROM products WHERE sku = 'LAT-1'")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pull_brings_over_variants_stock_and_orders() {
+        let pool = setup_test_db().await;
+        let server = default_shop().await;
+        let flow = flow(&pool, &server);
+
+        let products = flow.sync(Direction::Pull, PRODUCTS, &request()).await.unwrap();
+        assert_eq!(products, ShopifySyncCounts { created: 3, ..ShopifySyncCounts::default() });
+
+        let (category, barcode): (String, Option<String>) =
+            sqlx::query_as("SELECT category, barcode FROM products WHERE sku = 'LAT-1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(category, "Coffee");
+        assert_eq!(barcode.as_deref(), Some("012345678905"));
+        let (name, price, _) = latte(&pool).await;
+        assert_eq!(name, "Latte");
+        assert_eq!(price, 4.5);
+
+        let large: String = sqlx::query_scalar("SELECT name FROM products WHERE sku = 'SH-22'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(large, "T-Shirt - Large");
+
+        let inventory = flow.sync(Direction::Pull, INVENTORY, &request()).await.unwrap();
+        assert_eq!(inventory, ShopifySyncCounts { updated: 2, skipped: 1, ..ShopifySyncCounts::default() });
+        assert_eq!(latte(&pool).await.2, 12.0);
+
+        let orders = flow.sync(Direction::Pull, ORDERS, &request()).await.unwrap();
+        assert_eq!(orders, ShopifySyncCounts { created: 1, skipped: 1, ..ShopifySyncCounts::default() });
+        let (number, total, subtotal, tax, items, notes): (String, f64, f64, f64, i64, String) = sqlx::query_as(
+            "SELECT transaction_number, total_amount, subtotal, tax_amount, items_count, notes FROM sales_transactions",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(number, "SH-1001");
+        assert_eq!(total, 10.8);
+        assert_eq!(subtotal, 9.0);
+        assert_eq!(tax, 1.8);
+        assert_eq!(items, 1);
+        assert!(notes.contains("Gift wrap"));
+
+        // Shopify's levels already count its sales
+        assert_eq!(latte(&pool).await.2, 12.0);
+
+        // One-way from Shopify by default: nothing is pushed
+        let pushed = flow.sync(Direction::Push, PRODUCTS, &request()).await.unwrap();
+        assert_eq!(pushed, ShopifySyncCounts::default());
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_records_are_skipped_on_the_next_pull() {
+        let pool = setup_test_db().await;
+        let server = default_shop().await;
+        let flow = flow(&pool, &server);
+
+        for entity_type in [PRODUCTS, INVENTORY, ORDERS] {
+            flow.sync(Direction::Pull, entity_type, &request()).await.unwrap();
+        }
+
+        for entity_type in [PRODUCTS, INVENTORY] {
+            assert_eq!(
+                flow.sync(Direction::Pull, entity_type, &request()).await.unwrap(),
+                ShopifySyncCounts { skipped: 3, ..ShopifySyncCounts::default() }
+            );
+        }
+        assert_eq!(
+            flow.sync(Direction::Pull, ORDERS, &request()).await.unwrap(),
+            ShopifySyncCounts { skipped: 2, ..ShopifySyncCounts::default() }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_two_way_push_updates_changed_products_and_creates_new_ones() {
+        let pool = setup_test_db().await;
+        two_way(&pool).await;
+        let server = default_shop().await;
+        let flow = flow(&pool, &server);
+        flow.sync(Direction::Pull, PRODUCTS, &request()).await.unwrap();
+
+        sqlx::query("UPDATE products SET unit_price = 4.95, updated_at = '2099-01-01T00:00:00+00:00' WHERE sku = 'LAT-1'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO products (id, tenant_id, store_id, sku, name, category, unit_price)
+             VALUES ('p-scone', 't1', 's1', 'SCN-1', 'Scone', 'Bakery', 3.25)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        Mock::given(method("PUT"))
+            .and(path("/variants/11.json"))
+            .and(body_partial_json(json!({ "variant": { "id": 11, "sku": "LAT-1", "price": "4.95" } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "variant": { "id": 11, "product_id": 1, "sku": "LAT-1", "price": "4.95" }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/products.json"))
+            .and(body_partial_json(json!({ "product": { "title": "Scone", "product_type": "Bakery" } })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "product": {
+                    "id": 3, "title": "Scone", "product_type": "Bakery",
+                    "variants": [{ "id": 31, "product_id": 3, "title": "Default Title", "sku": "SCN-1", "price": "3.25" }]
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let counts = flow.sync(Direction::Push, PRODUCTS, &request()).await.unwrap();
+
+        assert_eq!(counts, ShopifySyncCounts { created: 1, updated: 1, skipped: 2, ..ShopifySyncCounts::default() });
+        let linked = flow.mapped_id("t1", ENTITY_VARIANT, "31").await.unwrap();
+        assert_eq!(linked.as_deref(), Some("p-scone"));
+    }
+
+    #[tokio::test]
+    async fn test_two_way_conflict_newest_wins_and_pos_edit_is_pushed() {
+        let pool = setup_test_db().await;
+        two_way(&pool).await;
+        let server = default_shop().await;
+        flow(&pool, &server).sync(Direction::Pull, PRODUCTS, &request()).await.unwrap();
+        sqlx::query("UPDATE products SET name = 'House latte', updated_at = '2099-01-01T00:00:00+00:00' WHERE sku = 'LAT-1'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Shopify's price change is older than the POS edit, so the POS keeps it
+        let server = mock_shop("4.95", "2024-01-02T00:00:00-05:00", 12).await;
+        let flow = flow(&pool, &server);
+        let counts = flow.sync(Direction::Pull, PRODUCTS, &request()).await.unwrap();
+
+        assert_eq!(counts, ShopifySyncCounts { conflicts: 1, skipped: 2, ..ShopifySyncCounts::default() });
+        let (name, price, _) = latte(&pool).await;
+        assert_eq!(name, "House latte");
+        assert_eq!(price, 4.5);
+
+        let (platform, status, resolved_version): (String, String, Option<String>) =
+            sqlx::query_as("SELECT platform, status, resolved_version FROM integration_sync_conflicts")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(platform, "shopify");
+        assert_eq!(status, "resolved");
+        assert_eq!(resolved_version.as_deref(), Some("pos"));
+
+        // The push then sends the POS version over
+        Mock::given(method("PUT"))
+            .and(path("/variants/11.json"))
+            .and(body_partial_json(json!({ "variant": { "price": "4.50" } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "variant": { "id": 11 } })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/products/1.json"))
+            .and(body_partial_json(json!({ "product": { "title": "House latte" } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "product": { "id": 1, "title": "House latte" } })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let counts = flow.sync(Direction::Push, PRODUCTS, &request()).await.unwrap();
+        assert_eq!(counts.updated, 1);
+    }
+
+    #[tokio::test]
+    async fn test_two_way_stock_changes_on_both_sides_are_added_together() {
+        let pool = setup_test_db().await;
+        two_way(&pool).await;
+        let server = default_shop().await;
+        let first = flow(&pool, &server);
+        first.sync(Direction::Pull, PRODUCTS, &request()).await.unwrap();
+        first.sync(Direction::Pull, INVENTORY, &request()).await.unwrap();
+        assert_eq!(latte(&pool).await.2, 12.0);
+
+        // Two sold in store, one online
+        let (product_id,): (String,) = sqlx::query_as("SELECT id FROM products WHERE sku = 'LAT-1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let sale = StockMovement::new("t1", &product_id, MovementType::Sale, -2.0, REASON_SALE).at_store("s1");
+        let mut conn = pool.acquire().await.unwrap();
+        inventory_ledger_service::record_movement(&mut conn, &sale).await.unwrap();
+        drop(conn);
+
+        let server = mock_shop("4.50", "2024-01-01T00:00:00-05:00", 11).await;
+        Mock::given(method("POST"))
+            .and(path("/inventory_levels/set.json"))
+            .and(body_partial_json(json!({ "location_id": 1, "inventory_item_id": 111, "available": 9 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "inventory_level": { "inventory_item_id": 111, "location_id": 1, "available": 9 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let counts = flow(&pool, &server).sync(Direction::Push, INVENTORY, &request()).await.unwrap();
+
+        assert_eq!(counts, ShopifySyncCounts { updated: 1, skipped: 2, ..ShopifySyncCounts::default() });
+        assert_eq!(latte(&pool).await.2, 9.0);
+    }
+
+    #[tokio::test]
+    async fn test_orders_move_stock_when_pos_owns_inventory() {
+        let pool = setup_test_db().await;
+        let mut config = SyncConfig::new();
+        config.add_entity(
+            INVENTORY.to_string(),
+            EntitySyncConfig {
+                source_of_truth: SourceOfTruth::Pos,
+                conflict_strategy: ConflictStrategy::NewestWins,
+            },
+        );
+        SyncDirectionControl::new(pool.clone()).set_sync_config("cred-1", &config).await.unwrap();
+
+        let server = default_shop().await;
+        let flow = flow(&pool, &server);
+        flow.sync(Direction::Pull, PRODUCTS, &request()).await.unwrap();
+
+        let pulled = flow.sync(Direction::Pull, INVENTORY, &request()).await.unwrap();
+        assert_eq!(pulled, ShopifySyncCounts::default());
+
+        flow.sync(Direction::Pull, ORDERS, &request()).await.unwrap();
+        assert_eq!(latte(&pool).await.2, -2.0);
+        assert!(flow.sync(Direction::Push, ORDERS, &request()).await.is_err());
+    }
+}
@@ -0,0 +1,1321 @@
+/**
+ * Square Import Flow
+ *
+ * Import flow: Square shop → EasySale
+ *
+ * Steps:
+ * 1. Catalog: each item variation becomes a product named after its item,
+ *    in the item's category, with the item's modifier lists as its modifiers
+ * 2. Customers
+ * 3. Stock: in-stock counts at each mapped Square location set the product's
+ *    on-hand at the matching store, through the inventory ledger
+ * 4. Sales history: completed orders become completed sales
+ *
+ * Every record goes through `IdMapper`, so re-running an import updates the
+ * products and customers brought over before and skips orders already
+ * imported. A product that was not imported but has the same SKU is taken
+ * over rather than duplicated. Imported orders do not move stock; step 3
+ * sets the current levels.
+ */
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::connectors::square::catalog::{CatalogItemVariation, CatalogObject};
+use crate::connectors::square::client::{money_amount, SquareClient};
+use crate::connectors::square::customers::SquareCustomer;
+use crate::connectors::square::inventory::STATE_IN_STOCK;
+use crate::connectors::square::orders::SquareOrder;
+use crate::services::checkout_service::PAYMENT_METHOD_SPLIT;
+use crate::services::id_mapper::IdMapper;
+use crate::services::inventory_ledger_service::{
+    self, MovementType, StockMovement, REASON_IMPORT, SOURCE_SQUARE_IMPORT,
+};
+
+const SOURCE_SYSTEM: &str = "square";
+const TARGET_SYSTEM: &str = "easysale";
+
+const ENTITY_ITEM_VARIATION: &str = "item_variation";
+const ENTITY_CUSTOMER: &str = "customer";
+const ENTITY_ORDER: &str = "order";
+
+/// Category of items that have none in Square
+const DEFAULT_CATEGORY: &str = "Uncategorized";
+
+/// Errors kept on a run's summary; the counts still cover every record
+const MAX_REPORTED_ERRORS: usize = 100;
+
+// ============================================================================
+// Options and Results
+// ============================================================================
+
+/// What to import, and where it goes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquareImportOptions {
+    /// Store that imported products and customers belong to
+    pub store_id: String,
+    /// Square location ID → store ID, for stock counts and orders. When
+    /// empty, the connected location goes to `store_id`. Other locations
+    /// are left out.
+    #[serde(default)]
+    pub locations: HashMap<String, String>,
+    #[serde(default = "default_true")]
+    pub catalog: bool,
+    #[serde(default = "default_true")]
+    pub customers: bool,
+    #[serde(default = "default_true")]
+    pub inventory: bool,
+    #[serde(default = "default_true")]
+    pub orders: bool,
+    /// Only import orders closed at or after this time (RFC 3339)
+    pub orders_since: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Outcome for one kind of record
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportCounts {
+    pub created: usize,
+    pub updated: usize,
+    /// Unchanged, already imported, or with nowhere to go
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Outcome of an import run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SquareImportSummary {
+    pub products: ImportCounts,
+    pub customers: ImportCounts,
+    /// Stock levels set, per product and store
+    pub stock_levels: ImportCounts,
+    pub orders: ImportCounts,
+    /// What went wrong with failed records, first ones only
+    pub errors: Vec<String>,
+}
+
+impl SquareImportSummary {
+    fn record_error(&mut self, message: String) {
+        tracing::warn!("Square import: {}", message);
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(message);
+        }
+    }
+}
+
+/// Whether a record was inserted or an existing one brought up to date
+enum Upserted {
+    Created(String),
+    Updated(String),
+}
+
+/// A modifier option, flattened from its modifier list
+struct ModifierOption {
+    group_name: String,
+    name: String,
+    price: f64,
+}
+
+// ============================================================================
+// Flow
+// ============================================================================
+
+/// Square to EasySale import flow
+pub struct SquareImportFlow {
+    db: SqlitePool,
+    client: SquareClient,
+    id_mapper: IdMapper,
+}
+
+impl SquareImportFlow {
+    pub fn new(db: SqlitePool, client: SquareClient) -> Self {
+        let id_mapper = IdMapper::new(db.clone());
+        Self { db, client, id_mapper }
+    }
+
+    /// Run the steps the options ask for, in order
+    ///
+    /// A record that fails is counted and the import carries on; failing to
+    /// read from Square ends the run. `user_id` is recorded as the employee
+    /// on imported sales and on the stock movements.
+    pub async fn run(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        run_id: &str,
+        options: &SquareImportOptions,
+    ) -> Result<SquareImportSummary, String> {
+        let mut summary = SquareImportSummary::default();
+        let location_stores = if options.locations.is_empty() {
+            HashMap::from([(self.client.location_id().to_string(), options.store_id.clone())])
+        } else {
+            options.locations.clone()
+        };
+        let mut location_ids: Vec<String> = location_stores.keys().cloned().collect();
+        location_ids.sort();
+
+        if options.catalog {
+            self.import_catalog(tenant_id, options, &mut summary).await?;
+        }
+        if options.customers {
+            self.import_customers(tenant_id, options, &mut summary).await?;
+        }
+        if options.inventory {
+            self.import_stock(tenant_id, user_id, run_id, &location_ids, &location_stores, &mut summary)
+                .await?;
+        }
+        if options.orders {
+            self.import_orders(tenant_id, user_id, options, &location_ids, &location_stores, &mut summary)
+                .await?;
+        }
+
+        Ok(summary)
+    }
+
+    // ------------------------------------------------------------------------
+    // Catalog
+    // ------------------------------------------------------------------------
+
+    async fn import_catalog(
+        &self,
+        tenant_id: &str,
+        options: &SquareImportOptions,
+        summary: &mut SquareImportSummary,
+    ) -> Result<(), String> {
+        let objects = self
+            .client
+            .get_all_catalog_objects()
+            .await
+            .map_err(|e| format!("Failed to fetch Square catalog: {}", e))?;
+
+        let mut categories: HashMap<String, String> = HashMap::new();
+        let mut modifier_lists: HashMap<String, Vec<ModifierOption>> = HashMap::new();
+        let mut items: Vec<&CatalogObject> = Vec::new();
+
+        for object in objects.iter().filter(|o| !o.is_deleted) {
+            match object.object_type.as_str() {
+                "CATEGORY" => {
+                    if let Some(name) = object.category_data.as_ref().and_then(|c| non_empty(&c.name)) {
+                        categories.insert(object.id.clone(), name);
+                    }
+                }
+                "MODIFIER_LIST" => {
+                    if let Some(list) = &object.modifier_list_data {
+                        let group_name = non_empty(&list.name).unwrap_or_else(|| "Options".to_string());
+                        let mut modifiers: Vec<&CatalogObject> =
+                            list.modifiers.iter().filter(|m| !m.is_deleted).collect();
+                        modifiers.sort_by_key(|m| m.modifier_data.as_ref().and_then(|d| d.ordinal));
+
+                        let options = modifiers
+                            .into_iter()
+                            .filter_map(|m| m.modifier_data.as_ref())
+                            .filter_map(|m| {
+                                non_empty(&m.name).map(|name| ModifierOption {
+                                    group_name: group_name.clone(),
+                                    name,
+                                    price: money_amount(&m.price_money),
+                                })
+                            })
+                            .collect();
+                        modifier_lists.insert(object.id.clone(), options);
+                    }
+                }
+                "ITEM" => items.push(object),
+                _ => {}
+            }
+        }
+
+        for item in items {
+            let Some(data) = &item.item_data else { continue };
+            let item_name = non_empty(&data.name).unwrap_or_else(|| "Unnamed item".to_string());
+            let category = data
+                .primary_category_id()
+                .and_then(|id| categories.get(id))
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_CATEGORY.to_string());
+            let modifiers: Vec<&ModifierOption> = data
+                .modifier_list_info
+                .iter()
+                .filter(|info| info.enabled != Some(false))
+                .filter_map(|info| modifier_lists.get(&info.modifier_list_id))
+                .flatten()
+                .collect();
+
+            let variations: Vec<(&str, &CatalogItemVariation)> = data
+                .variations
+                .iter()
+                .filter(|v| !v.is_deleted)
+                .filter_map(|v| v.item_variation_data.as_ref().map(|d| (v.id.as_str(), d)))
+                .collect();
+            let has_variants = variations.len() > 1;
+
+            for (variation_id, variation) in variations {
+                let product = ImportedProduct {
+                    sku: non_empty(&variation.sku).unwrap_or_else(|| format!("SQ-{}", variation_id)),
+                    name: match non_empty(&variation.name) {
+                        Some(variant) if has_variants => format!("{} - {}", item_name, variant),
+                        _ => item_name.clone(),
+                    },
+                    description: non_empty(&data.description),
+                    category: category.clone(),
+                    unit_price: money_amount(&variation.price_money),
+                    barcode: non_empty(&variation.upc),
+                };
+
+                match self.upsert_product(tenant_id, &options.store_id, variation_id, &product, &modifiers).await {
+                    Ok(Upserted::Created(_)) => summary.products.created += 1,
+                    Ok(Upserted::Updated(_)) => summary.products.updated += 1,
+                    Err(e) => {
+                        summary.products.failed += 1;
+                        summary.record_error(format!("Item variation {} ({}): {}", variation_id, product.sku, e));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn upsert_product(
+        &self,
+        tenant_id: &str,
+        store_id: &str,
+        variation_id: &str,
+        product: &ImportedProduct,
+        modifiers: &[&ModifierOption],
+    ) -> Result<Upserted, String> {
+        let mapped = self.mapped_id(tenant_id, ENTITY_ITEM_VARIATION, variation_id).await?;
+        let existing: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM products
+             WHERE tenant_id = ? AND (id = ? OR sku = ?)
+             ORDER BY id = ? DESC
+             LIMIT 1",
+        )
+        .bind(tenant_id)
+        .bind(&mapped)
+        .bind(&product.sku)
+        .bind(&mapped)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up product: {}", e))?;
+
+        let now = Utc::now().to_rfc3339();
+        let barcode_type = product.barcode.as_deref().map(barcode_type);
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        let upserted = match existing {
+            Some(product_id) => {
+                sqlx::query(
+                    "UPDATE products
+                     SET sku = ?, name = ?, description = ?, category = ?, unit_price = ?,
+                         barcode = COALESCE(?, barcode), barcode_type = COALESCE(?, barcode_type),
+                         is_active = 1, updated_at = ?, sync_version = sync_version + 1
+                     WHERE id = ? AND tenant_id = ?",
+                )
+                .bind(&product.sku)
+                .bind(&product.name)
+                .bind(&product.description)
+                .bind(&product.category)
+                .bind(product.unit_price)
+                .bind(&product.barcode)
+                .bind(barcode_type)
+                .bind(&now)
+                .bind(&product_id)
+                .bind(tenant_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to update product: {}", e))?;
+                Upserted::Updated(product_id)
+            }
+            None => {
+                let product_id = Uuid::new_v4().to_string();
+                sqlx::query(
+                    "INSERT INTO products (
+                        id, tenant_id, store_id, sku, name, description, category,
+                        unit_price, cost, quantity_on_hand, barcode, barcode_type,
+                        is_active, created_at, updated_at
+                     ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0, 0, ?, ?, 1, ?, ?)",
+                )
+                .bind(&product_id)
+                .bind(tenant_id)
+                .bind(store_id)
+                .bind(&product.sku)
+                .bind(&product.name)
+                .bind(&product.description)
+                .bind(&product.category)
+                .bind(product.unit_price)
+                .bind(&product.barcode)
+                .bind(barcode_type)
+                .bind(&now)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to create product: {}", e))?;
+                Upserted::Created(product_id)
+            }
+        };
+
+        let (Upserted::Created(product_id) | Upserted::Updated(product_id)) = &upserted;
+
+        sqlx::query("DELETE FROM product_modifiers WHERE product_id = ? AND tenant_id = ?")
+            .bind(product_id)
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to replace modifiers: {}", e))?;
+        for (sort_order, modifier) in modifiers.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO product_modifiers (
+                    id, tenant_id, product_id, group_name, name, price, sort_order, created_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(product_id)
+            .bind(&modifier.group_name)
+            .bind(&modifier.name)
+            .bind(modifier.price)
+            .bind(sort_order as i64)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to replace modifiers: {}", e))?;
+        }
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+
+        self.id_mapper
+            .store_mapping(tenant_id, SOURCE_SYSTEM, ENTITY_ITEM_VARIATION, variation_id, TARGET_SYSTEM, "product", product_id)
+            .await?;
+
+        Ok(upserted)
+    }
+
+    // ------------------------------------------------------------------------
+    // Customers
+    // ------------------------------------------------------------------------
+
+    async fn import_customers(
+        &self,
+        tenant_id: &str,
+        options: &SquareImportOptions,
+        summary: &mut SquareImportSummary,
+    ) -> Result<(), String> {
+        let customers = self
+            .client
+            .get_all_customers()
+            .await
+            .map_err(|e| format!("Failed to fetch Square customers: {}", e))?;
+
+        for customer in &customers {
+            let Some(name) = customer.display_name() else {
+                summary.customers.skipped += 1;
+                continue;
+            };
+
+            match self.upsert_customer(tenant_id, &options.store_id, customer, &name).await {
+                Ok(Upserted::Created(_)) => summary.customers.created += 1,
+                Ok(Upserted::Updated(_)) => summary.customers.updated += 1,
+                Err(e) => {
+                    summary.customers.failed += 1;
+                    summary.record_error(format!("Customer {}: {}", customer.id, e));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn upsert_customer(
+        &self,
+        tenant_id: &str,
+        store_id: &str,
+        customer: &SquareCustomer,
+        name: &str,
+    ) -> Result<Upserted, String> {
+        let mapped = self.mapped_id(tenant_id, ENTITY_CUSTOMER, &customer.id).await?;
+        let email = non_empty(&customer.email_address);
+        let phone = non_empty(&customer.phone_number);
+        let existing: Option<String> = match mapped {
+            Some(customer_id) => sqlx::query_scalar("SELECT id FROM customers WHERE id = ? AND tenant_id = ?")
+                .bind(customer_id)
+                .bind(tenant_id)
+                .fetch_optional(&self.db)
+                .await
+                .map_err(|e| format!("Failed to look up customer: {}", e))?,
+            None => None,
+        };
+
+        let now = Utc::now().to_rfc3339();
+        let upserted = match existing {
+            Some(customer_id) => {
+                sqlx::query(
+                    "UPDATE customers
+                     SET name = ?, email = COALESCE(?, email), phone = COALESCE(?, phone),
+                         updated_at = ?, sync_version = sync_version + 1
+                     WHERE id = ? AND tenant_id = ?",
+                )
+                .bind(name)
+                .bind(&email)
+                .bind(&phone)
+                .bind(&now)
+                .bind(&customer_id)
+                .bind(tenant_id)
+                .execute(&self.db)
+                .await
+                .map_err(|e| format!("Failed to update customer: {}", e))?;
+                Upserted::Updated(customer_id)
+            }
+            None => {
+                let customer_id = Uuid::new_v4().to_string();
+                let created_at = customer.created_at.as_deref().and_then(to_rfc3339).unwrap_or_else(|| now.clone());
+                sqlx::query(
+                    "INSERT INTO customers (id, tenant_id, store_id, name, email, phone, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&customer_id)
+                .bind(tenant_id)
+                .bind(store_id)
+                .bind(name)
+                .bind(&email)
+                .bind(&phone)
+                .bind(&created_at)
+                .bind(&now)
+                .execute(&self.db)
+                .await
+                .map_err(|e| format!("Failed to create customer: {}", e))?;
+                Upserted::Created(customer_id)
+            }
+        };
+
+        let (Upserted::Created(customer_id) | Upserted::Updated(customer_id)) = &upserted;
+        self.id_mapper
+            .store_mapping(tenant_id, SOURCE_SYSTEM, ENTITY_CUSTOMER, &customer.id, TARGET_SYSTEM, "customer", customer_id)
+            .await?;
+
+        Ok(upserted)
+    }
+
+    // ------------------------------------------------------------------------
+    // Stock
+    // ------------------------------------------------------------------------
+
+    async fn import_stock(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        run_id: &str,
+        location_ids: &[String],
+        location_stores: &HashMap<String, String>,
+        summary: &mut SquareImportSummary,
+    ) -> Result<(), String> {
+        let counts = self
+            .client
+            .get_all_inventory_counts(location_ids)
+            .await
+            .map_err(|e| format!("Failed to fetch Square inventory counts: {}", e))?;
+
+        for count in counts.iter().filter(|c| c.state == STATE_IN_STOCK) {
+            let Some(store_id) = location_stores.get(&count.location_id) else {
+                summary.stock_levels.skipped += 1;
+                continue;
+            };
+            let Some(product_id) = self.mapped_id(tenant_id, ENTITY_ITEM_VARIATION, &count.catalog_object_id).await? else {
+                summary.stock_levels.skipped += 1;
+                continue;
+            };
+
+            let movement = StockMovement::new(tenant_id, &product_id, MovementType::Adjustment, 0.0, REASON_IMPORT)
+                .at_store(store_id)
+                .by_user(user_id)
+                .with_source(SOURCE_SQUARE_IMPORT, run_id)
+                .with_notes(Some("Square stock count".to_string()));
+
+            let result = async {
+                let mut tx = self.db.begin().await?;
+                let recorded = inventory_ledger_service::set_on_hand(&mut tx, movement, count.quantity()).await?;
+                tx.commit().await?;
+                Ok::<_, inventory_ledger_service::LedgerError>(recorded)
+            }
+            .await;
+
+            match result {
+                Ok(Some(_)) => summary.stock_levels.updated += 1,
+                Ok(None) => summary.stock_levels.skipped += 1,
+                Err(e) => {
+                    summary.stock_levels.failed += 1;
+                    summary.record_error(format!(
+                        "Stock of {} at {}: {}",
+                        count.catalog_object_id, count.location_id, e
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Orders
+    // ------------------------------------------------------------------------
+
+    async fn import_orders(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        options: &SquareImportOptions,
+        location_ids: &[String],
+        location_stores: &HashMap<String, String>,
+        summary: &mut SquareImportSummary,
+    ) -> Result<(), String> {
+        let orders = self
+            .client
+            .get_all_completed_orders(location_ids, options.orders_since.as_deref())
+            .await
+            .map_err(|e| format!("Failed to fetch Square orders: {}", e))?;
+
+        for order in &orders {
+            let Some(store_id) = location_stores.get(&order.location_id) else {
+                summary.orders.skipped += 1;
+                continue;
+            };
+            if self.mapped_id(tenant_id, ENTITY_ORDER, &order.id).await?.is_some() {
+                summary.orders.skipped += 1;
+                continue;
+            }
+
+            match self.insert_order(tenant_id, user_id, store_id, order).await {
+                Ok(()) => summary.orders.created += 1,
+                Err(e) => {
+                    summary.orders.failed += 1;
+                    summary.record_error(format!("Order {}: {}", order.id, e));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record an order as a completed sale with the lines whose variation
+    /// was imported; any other lines are named in the sale's notes
+    async fn insert_order(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        store_id: &str,
+        order: &SquareOrder,
+    ) -> Result<(), String> {
+        let customer_id = match &order.customer_id {
+            Some(id) => self.mapped_id(tenant_id, ENTITY_CUSTOMER, id).await?,
+            None => None,
+        };
+
+        let mut lines = Vec::new();
+        let mut left_out = Vec::new();
+        for line in &order.line_items {
+            let product_id = match &line.catalog_object_id {
+                Some(id) => self.mapped_id(tenant_id, ENTITY_ITEM_VARIATION, id).await?,
+                None => None,
+            };
+            match product_id {
+                Some(product_id) => lines.push((product_id, line)),
+                None => left_out.push(format!(
+                    "{} x{}",
+                    line.name.as_deref().unwrap_or("Custom amount"),
+                    line.quantity.trim()
+                )),
+            }
+        }
+
+        let mut notes = format!("Imported from Square order {}", order.id);
+        if !left_out.is_empty() {
+            notes.push_str(&format!(". Lines not imported: {}", left_out.join(", ")));
+        }
+
+        let total = money_amount(&order.total_money);
+        let tax = money_amount(&order.total_tax_money);
+        let discount = money_amount(&order.total_discount_money);
+        let payment_method = match order.tenders.as_slice() {
+            [] => None,
+            [single] => Some(tender_method(&single.tender_type).to_string()),
+            _ => Some(PAYMENT_METHOD_SPLIT.to_string()),
+        };
+        let now = Utc::now().to_rfc3339();
+        let created_at = order.created_at.as_deref().and_then(to_rfc3339).unwrap_or_else(|| now.clone());
+        let completed_at = order.closed_at.as_deref().and_then(to_rfc3339).unwrap_or_else(|| created_at.clone());
+
+        let sale_id = Uuid::new_v4().to_string();
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            "INSERT INTO sales_transactions (
+                id, tenant_id, transaction_number, customer_id, employee_id, store_id,
+                total_amount, subtotal, tax_amount, discount_amount, items_count,
+                payment_method, payment_status, status, notes, transaction_type,
+                created_at, updated_at, completed_at
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'completed', 'completed', ?, 'sale', ?, ?, ?)",
+        )
+        .bind(&sale_id)
+        .bind(tenant_id)
+        .bind(format!("SQ-{}", order.id))
+        .bind(&customer_id)
+        .bind(user_id)
+        .bind(store_id)
+        .bind(total)
+        .bind(total - tax + discount)
+        .bind(tax)
+        .bind(discount)
+        .bind(lines.len() as i64)
+        .bind(&payment_method)
+        .bind(&notes)
+        .bind(&created_at)
+        .bind(&now)
+        .bind(&completed_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to create sale: {}", e))?;
+
+        for (product_id, line) in &lines {
+            let quantity = line.quantity();
+            let unit_price = money_amount(&line.base_price_money);
+            let subtotal = line
+                .gross_sales_money
+                .as_ref()
+                .map_or(unit_price * quantity, |m| m.as_major_units());
+
+            sqlx::query(
+                "INSERT INTO sales_line_items (
+                    id, transaction_id, product_id, quantity, unit_price,
+                    subtotal, discount_amount, tax_amount, total, notes, created_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&sale_id)
+            .bind(product_id)
+            .bind(quantity)
+            .bind(unit_price)
+            .bind(subtotal)
+            .bind(money_amount(&line.total_discount_money))
+            .bind(money_amount(&line.total_tax_money))
+            .bind(money_amount(&line.total_money))
+            .bind(&line.note)
+            .bind(&created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to create sale line: {}", e))?;
+        }
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+
+        self.id_mapper
+            .store_mapping(tenant_id, SOURCE_SYSTEM, ENTITY_ORDER, &order.id, TARGET_SYSTEM, "sales_transaction", &sale_id)
+            .await
+    }
+
+    async fn mapped_id(&self, tenant_id: &str, entity: &str, source_id: &str) -> Result<Option<String>, String> {
+        self.id_mapper
+            .get_mapping(tenant_id, SOURCE_SYSTEM, entity, source_id, TARGET_SYSTEM)
+            .await
+    }
+}
+
+/// A variation's fields as they land on the product
+struct ImportedProduct {
+    sku: String,
+    name: String,
+    description: Option<String>,
+    category: String,
+    unit_price: f64,
+    barcode: Option<String>,
+}
+
+// ============================================================================
+// Import Runs
+// ============================================================================
+
+/// A recorded import run
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SquareImportRun {
+    pub id: String,
+    pub status: String,
+    /// JSON `SquareImportOptions`
+    pub options: String,
+    /// JSON `SquareImportSummary`, once the run has ended
+    pub summary: Option<String>,
+    pub error: Option<String>,
+    pub started_by: Option<String>,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// Record the start of a run and return its ID
+pub async fn start_run(
+    db: &SqlitePool,
+    tenant_id: &str,
+    user_id: &str,
+    options: &SquareImportOptions,
+) -> Result<String, sqlx::Error> {
+    let id = Uuid::new_v4().to_string();
+    let options = serde_json::to_string(options).unwrap_or_else(|_| "{}".to_string());
+
+    sqlx::query(
+        "INSERT INTO square_import_runs (id, tenant_id, status, options, started_by, started_at)
+         VALUES (?, ?, 'running', ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(tenant_id)
+    .bind(&options)
+    .bind(user_id)
+    .bind(Utc::now().to_rfc3339())
+    .execute(db)
+    .await?;
+
+    Ok(id)
+}
+
+/// Record how a run ended
+pub async fn finish_run(
+    db: &SqlitePool,
+    run_id: &str,
+    result: &Result<SquareImportSummary, String>,
+) -> Result<(), sqlx::Error> {
+    let (status, summary, error) = match result {
+        Ok(summary) => ("completed", serde_json::to_string(summary).ok(), None),
+        Err(e) => ("failed", None, Some(e.as_str())),
+    };
+
+    sqlx::query(
+        "UPDATE square_import_runs SET status = ?, summary = ?, error = ?, completed_at = ? WHERE id = ?",
+    )
+    .bind(status)
+    .bind(summary)
+    .bind(error)
+    .bind(Utc::now().to_rfc3339())
+    .bind(run_id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// A tenant's most recent runs, newest first
+pub async fn list_runs(db: &SqlitePool, tenant_id: &str, limit: i64) -> Result<Vec<SquareImportRun>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, status, options, summary, error, started_by, started_at, completed_at
+         FROM square_import_runs
+         WHERE tenant_id = ?
+         ORDER BY started_at DESC
+         LIMIT ?",
+    )
+    .bind(tenant_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+}
+
+pub async fn get_run(db: &SqlitePool, tenant_id: &str, run_id: &str) -> Result<Option<SquareImportRun>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, status, options, summary, error, started_by, started_at, completed_at
+         FROM square_import_runs
+         WHERE id = ? AND tenant_id = ?",
+    )
+    .bind(run_id)
+    .bind(tenant_id)
+    .fetch_optional(db)
+    .await
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// Square timestamps re-written the way EasySale stores them
+fn to_rfc3339(timestamp: &str) -> Option<String> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Utc).to_rfc3339())
+}
+
+fn barcode_type(barcode: &str) -> &'static str {
+    match barcode.len() {
+        12 => "UPC-A",
+        13 => "EAN-13",
+        _ => "CUSTOM",
+    }
+}
+
+/// `sales_transactions.payment_method` for a Square tender type
+fn tender_method(tender_type: &str) -> &'static str {
+    match tender_type {
+        "CARD" => "card",
+        "CASH" => "cash",
+        "SQUARE_GIFT_CARD" => "gift_card",
+        "WALLET" => "wallet",
+        _ => "other",
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::square::client::SquareCredentials;
+    use serde_json::json;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                sku TEXT NOT NULL UNIQUE,
+                name TEXT NOT NULL,
+                description TEXT,
+                category TEXT NOT NULL,
+                unit_price REAL NOT NULL,
+                cost REAL NOT NULL DEFAULT 0,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                barcode TEXT,
+                barcode_type TEXT,
+                is_active INTEGER NOT NULL DEFAULT 1,
+                sync_version INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+            "CREATE TABLE customers (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                email TEXT,
+                phone TEXT,
+                sync_version INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE TABLE sales_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_number TEXT NOT NULL,
+                customer_id TEXT,
+                employee_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                total_amount REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                items_count INTEGER NOT NULL,
+                payment_method TEXT,
+                payment_status TEXT NOT NULL,
+                status TEXT NOT NULL,
+                notes TEXT,
+                transaction_type TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                completed_at TEXT,
+                UNIQUE (tenant_id, transaction_number)
+            )",
+            "CREATE TABLE sales_line_items (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                quantity REAL NOT NULL,
+                unit_price REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                total REAL NOT NULL,
+                notes TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE settings (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            "INSERT INTO stores (id, name) VALUES ('s1', 'Main Street'), ('s2', 'Airport')",
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+            include_str!("../../../../migrations/077_outbound_webhooks.sql"),
+            include_str!("../../../../migrations/078_square_import.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    /// A shop with a two-size latte (milk options), a muffin without a SKU,
+    /// one customer, stock at two locations and one order
+    async fn mock_square_shop() -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/catalog/list"))
+            .and(query_param_is_missing("cursor"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "objects": [
+                    { "type": "CATEGORY", "id": "CAT1", "category_data": { "name": "Coffee" } },
+                    {
+                        "type": "MODIFIER_LIST", "id": "ML1",
+                        "modifier_list_data": {
+                            "name": "Milk",
+                            "modifiers": [
+                                { "type": "MODIFIER", "id": "M2", "modifier_data": {
+                                    "name": "Oat", "ordinal": 2,
+                                    "price_money": { "amount": 75, "currency": "USD" } } },
+                                { "type": "MODIFIER", "id": "M1", "modifier_data": {
+                                    "name": "Whole", "ordinal": 1 } }
+                            ]
+                        }
+                    },
+                    {
+                        "type": "ITEM", "id": "ITEM1",
+                        "item_data": {
+                            "name": "Latte",
+                            "categories": [{ "id": "CAT1" }],
+                            "modifier_list_info": [{ "modifier_list_id": "ML1", "enabled": true }],
+                            "variations": [
+                                { "type": "ITEM_VARIATION", "id": "VAR1", "item_variation_data": {
+                                    "item_id": "ITEM1", "name": "Small", "sku": "LAT-S",
+                                    "upc": "012345678905",
+                                    "price_money": { "amount": 450, "currency": "USD" } } },
+                                { "type": "ITEM_VARIATION", "id": "VAR2", "item_variation_data": {
+                                    "item_id": "ITEM1", "name": "Large", "sku": "LAT-L",
+                                    "price_money": { "amount": 525, "currency": "USD" } } }
+                            ]
+                        }
+                    }
+                ],
+                "cursor": "page2"
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/catalog/list"))
+            .and(query_param("cursor", "page2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "objects": [{
+                    "type": "ITEM", "id": "ITEM2",
+                    "item_data": {
+                        "name": "Muffin",
+                        "variations": [
+                            { "type": "ITEM_VARIATION", "id": "VAR3", "item_variation_data": {
+                                "item_id": "ITEM2", "name": "Regular",
+                                "price_money": { "amount": 300, "currency": "USD" } } }
+                        ]
+                    }
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/customers"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "customers": [
+                    { "id": "CUST1", "given_name": "Ada", "family_name": "Lovelace",
+                      "email_address": "ada@example.com", "created_at": "2023-05-01T10:00:00Z" },
+                    { "id": "CUST2" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/inventory/counts/batch-retrieve"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "counts": [
+                    { "catalog_object_id": "VAR1", "state": "IN_STOCK", "location_id": "LOC1", "quantity": "12" },
+                    { "catalog_object_id": "VAR1", "state": "IN_STOCK", "location_id": "LOC2", "quantity": "5" },
+                    { "catalog_object_id": "VAR3", "state": "IN_STOCK", "location_id": "LOC1", "quantity": "7.5" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/orders/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "orders": [{
+                    "id": "ORDER1",
+                    "location_id": "LOC1",
+                    "customer_id": "CUST1",
+                    "state": "COMPLETED",
+                    "line_items": [
+                        { "uid": "L1", "name": "Latte", "quantity": "2", "catalog_object_id": "VAR1",
+                          "base_price_money": { "amount": 450 }, "gross_sales_money": { "amount": 900 },
+                          "total_tax_money": { "amount": 72 }, "total_money": { "amount": 972 } },
+                        { "uid": "L2", "name": "Tip jar", "quantity": "1",
+                          "base_price_money": { "amount": 100 }, "total_money": { "amount": 100 } }
+                    ],
+                    "total_money": { "amount": 1072 },
+                    "total_tax_money": { "amount": 72 },
+                    "total_discount_money": { "amount": 0 },
+                    "tenders": [{ "type": "CARD", "amount_money": { "amount": 1072 } }],
+                    "created_at": "2024-01-10T09:00:00Z",
+                    "closed_at": "2024-01-10T09:01:00Z"
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn flow(pool: &SqlitePool, server: &MockServer) -> SquareImportFlow {
+        let client = SquareClient::new(SquareCredentials {
+            access_token: "token".to_string(),
+            location_id: "LOC1".to_string(),
+        })
+        .unwrap()
+        .with_base_url(server.uri());
+
+        SquareImportFlow::new(pool.clone(), client)
+    }
+
+    fn options() -> SquareImportOptions {
+        SquareImportOptions {
+            store_id: "s1".to_string(),
+            locations: HashMap::from([
+                ("LOC1".to_string(), "s1".to_string()),
+                ("LOC2".to_string(), "s2".to_string()),
+            ]),
+            catalog: true,
+            customers: true,
+            inventory: true,
+            orders: true,
+            orders_since: None,
+        }
+    }
+
+    async fn product_by_sku(pool: &SqlitePool, sku: &str) -> (String, String, String, f64, Option<String>) {
+        sqlx::query_as("SELECT id, name, category, unit_price, barcode_type FROM products WHERE sku = ?")
+            .bind(sku)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn count(pool: &SqlitePool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_brings_over_catalog_customers_stock_and_orders() {
+        let pool = setup_test_db().await;
+        let server = mock_square_shop().await;
+
+        let summary = flow(&pool, &server).run("t1", "u1", "run-1", &options()).await.unwrap();
+
+        assert_eq!(summary.products, ImportCounts { created: 3, ..ImportCounts::default() });
+        assert_eq!(summary.customers, ImportCounts { created: 1, skipped: 1, ..ImportCounts::default() });
+        assert_eq!(summary.stock_levels, ImportCounts { updated: 3, ..ImportCounts::default() });
+        assert_eq!(summary.orders, ImportCounts { created: 1, ..ImportCounts::default() });
+        assert!(summary.errors.is_empty());
+
+        // Variations are named after their item; a lone variation is not
+        let (small_id, name, category, price, barcode_type) = product_by_sku(&pool, "LAT-S").await;
+        assert_eq!(name, "Latte - Small");
+        assert_eq!(category, "Coffee");
+        assert_eq!(price, 4.5);
+        assert_eq!(barcode_type.as_deref(), Some("UPC-A"));
+        let (muffin_id, name, category, price, _) = product_by_sku(&pool, "SQ-VAR3").await;
+        assert_eq!(name, "Muffin");
+        assert_eq!(category, DEFAULT_CATEGORY);
+        assert_eq!(price, 3.0);
+
+        let modifiers: Vec<(String, String, f64)> = sqlx::query_as(
+            "SELECT group_name, name, price FROM product_modifiers WHERE product_id = ? ORDER BY sort_order",
+        )
+        .bind(&small_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            modifiers,
+            vec![
+                ("Milk".to_string(), "Whole".to_string(), 0.0),
+                ("Milk".to_string(), "Oat".to_string(), 0.75),
+            ]
+        );
+
+        // Stock lands at the store mapped to each location
+        let small_stock: Vec<(String, f64)> = sqlx::query_as(
+            "SELECT store_id, quantity_on_hand FROM product_locations WHERE product_id = ? ORDER BY store_id",
+        )
+        .bind(&small_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(small_stock, vec![("s1".to_string(), 12.0), ("s2".to_string(), 5.0)]);
+        let muffin_total: f64 = sqlx::query_scalar("SELECT quantity_on_hand FROM products WHERE id = ?")
+            .bind(&muffin_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(muffin_total, 7.5);
+
+        // The order keeps Square's totals; the custom amount is only noted
+        let (number, customer_id, store_id, total, subtotal, payment_method, items_count, notes, completed_at): (
+            String,
+            Option<String>,
+            String,
+            f64,
+            f64,
+            Option<String>,
+            i64,
+            String,
+            String,
+        ) = sqlx::query_as(
+            "SELECT transaction_number, customer_id, store_id, total_amount, subtotal,
+                    payment_method, items_count, notes, completed_at
+             FROM sales_transactions",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(number, "SQ-ORDER1");
+        assert!(customer_id.is_some());
+        assert_eq!(store_id, "s1");
+        assert_eq!(total, 10.72);
+        assert_eq!(subtotal, 10.0);
+        assert_eq!(payment_method.as_deref(), Some("card"));
+        assert_eq!(items_count, 1);
+        assert!(notes.contains("Tip jar x1"));
+        assert!(completed_at.starts_with("2024-01-10T09:01:00"));
+
+        let (product_id, quantity, line_total): (String, f64, f64) =
+            sqlx::query_as("SELECT product_id, quantity, total FROM sales_line_items")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(product_id, small_id);
+        assert_eq!(quantity, 2.0);
+        assert_eq!(line_total, 9.72);
+
+        // Historical orders leave stock alone
+        let small_total: f64 = sqlx::query_scalar("SELECT quantity_on_hand FROM products WHERE id = ?")
+            .bind(&small_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(small_total, 17.0);
+    }
+
+    #[tokio::test]
+    async fn test_rerun_updates_instead_of_duplicating() {
+        let pool = setup_test_db().await;
+        let server = mock_square_shop().await;
+        let flow = flow(&pool, &server);
+
+        flow.run("t1", "u1", "run-1", &options()).await.unwrap();
+        let summary = flow.run("t1", "u1", "run-2", &options()).await.unwrap();
+
+        assert_eq!(summary.products, ImportCounts { updated: 3, ..ImportCounts::default() });
+        assert_eq!(summary.customers, ImportCounts { updated: 1, skipped: 1, ..ImportCounts::default() });
+        assert_eq!(summary.stock_levels, ImportCounts { skipped: 3, ..ImportCounts::default() });
+        assert_eq!(summary.orders, ImportCounts { skipped: 1, ..ImportCounts::default() });
+
+        assert_eq!(count(&pool, "products").await, 3);
+        assert_eq!(count(&pool, "product_modifiers").await, 4);
+        assert_eq!(count(&pool, "customers").await, 1);
+        assert_eq!(count(&pool, "sales_transactions").await, 1);
+        assert_eq!(count(&pool, "sales_line_items").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_existing_product_with_same_sku_is_taken_over() {
+        let pool = setup_test_db().await;
+        sqlx::query(
+            "INSERT INTO products (id, tenant_id, store_id, sku, name, category, unit_price, cost)
+             VALUES ('p-existing', 't1', 's1', 'LAT-L', 'Large latte', 'Drinks', 5.0, 1.2)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let server = mock_square_shop().await;
+
+        let options = SquareImportOptions {
+            customers: false,
+            inventory: false,
+            orders: false,
+            ..options()
+        };
+        let summary = flow(&pool, &server).run("t1", "u1", "run-1", &options).await.unwrap();
+
+        assert_eq!(summary.products, ImportCounts { created: 2, updated: 1, ..ImportCounts::default() });
+        let (id, name, category, price, _) = product_by_sku(&pool, "LAT-L").await;
+        assert_eq!(id, "p-existing");
+        assert_eq!(name, "Latte - Large");
+        assert_eq!(category, "Coffee");
+        assert_eq!(price, 5.25);
+
+        // The product's cost is not Square's to set
+        let cost: f64 = sqlx::query_scalar("SELECT cost FROM products WHERE id = 'p-existing'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(cost, 1.2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetch_ends_run_and_is_recorded() {
+        let pool = setup_test_db().await;
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/catalog/list"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "errors": [{ "category": "AUTHENTICATION_ERROR", "code": "UNAUTHORIZED" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let run_id = start_run(&pool, "t1", "u1", &options()).await.unwrap();
+        let result = flow(&pool, &server).run("t1", "u1", &run_id, &options()).await;
+        assert!(result.as_ref().unwrap_err().contains("Square catalog"));
+        finish_run(&pool, &run_id, &result).await.unwrap();
+
+        let run = get_run(&pool, "t1", &run_id).await.unwrap().unwrap();
+        assert_eq!(run.status, "failed");
+        assert!(run.error.is_some());
+        assert!(run.completed_at.is_some());
+        assert!(get_run(&pool, "t2", &run_id).await.unwrap().is_none());
+        assert_eq!(list_runs(&pool, "t1", 10).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,685 @@
+/**
+ * WooCommerce Stock Push Flow
+ *
+ * Push flow: POS → WooCommerce stock levels, for the pushes that stock
+ * movements queue in sync_queue (see migration 080). The sync queue
+ * processor hands over the products whose pushes are due; products are
+ * matched to WooCommerce products and variations by SKU, and every level is
+ * written with the batch endpoints.
+ *
+ * The web store keeps selling while the register does, so a push never
+ * overwrites WooCommerce's level with the POS's. It adds the POS's change
+ * since the last push to WooCommerce's current level instead, which keeps
+ * web sales made in between. The POS level a push was made from is logged
+ * in integration_sync_operations and is what the next change is measured
+ * from; a product's first push sets WooCommerce to the POS level.
+ *
+ * When both sides sold the same units (the web store sold the last one
+ * while the register did too) the level would go below zero: WooCommerce is
+ * left at zero and the oversell is recorded as a manual conflict.
+ *
+ * Pushes run unless the connection is one-way with WooCommerce set as the
+ * source of truth for inventory.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::connectors::woocommerce::client::WooCommerceClient;
+use crate::connectors::woocommerce::inventory::{StockLevel, StockUpdate};
+use crate::services::sync_direction_control::{
+    ConflictStrategy, SourceOfTruth, SyncDirection, SyncDirectionControl,
+};
+
+/// Platform name on credentials, operations and conflicts
+pub const PLATFORM: &str = "woocommerce";
+
+/// Entity type of stock levels on operations, conflicts and the sync config
+pub const INVENTORY: &str = "inventory";
+
+/// Direction recorded on integration_sync_operations
+const DIRECTION: &str = "pos_to_platform";
+
+/// What happened to one product's push
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// WooCommerce was set to this level
+    Pushed(i64),
+    /// WooCommerce already had the level
+    Unchanged,
+    /// Both sides sold these units; WooCommerce was left at zero and a
+    /// conflict recorded
+    Oversold(i64),
+    /// Nothing to push to, or pushes are off; not worth retrying
+    Skipped(String),
+    /// Refused by WooCommerce; worth retrying
+    Failed(String),
+}
+
+/// A product's stock as the POS has it
+#[derive(sqlx::FromRow)]
+struct PosStock {
+    id: String,
+    sku: String,
+    quantity_on_hand: f64,
+}
+
+/// A level to write for one product
+struct PlannedPush {
+    product_id: String,
+    woo_id: i64,
+    pos_level: i64,
+    quantity: i64,
+    oversold: Option<Oversell>,
+}
+
+/// The figures an oversell conflict is recorded with
+struct Oversell {
+    units: i64,
+    last_pushed: i64,
+    woo_level: i64,
+}
+
+// ============================================================================
+// Flow
+// ============================================================================
+
+/// POS to WooCommerce stock push flow
+pub struct WooStockPushFlow {
+    db: SqlitePool,
+    client: WooCommerceClient,
+    /// The tenant's WooCommerce `integration_credentials` row
+    credential_id: String,
+    direction_control: Arc<SyncDirectionControl>,
+}
+
+impl WooStockPushFlow {
+    pub fn new(
+        db: SqlitePool,
+        client: WooCommerceClient,
+        credential_id: impl Into<String>,
+        direction_control: Arc<SyncDirectionControl>,
+    ) -> Self {
+        Self {
+            db,
+            client,
+            credential_id: credential_id.into(),
+            direction_control,
+        }
+    }
+
+    /// Push the stock of these products, returning what happened to each
+    ///
+    /// Failing to read the direction settings, the POS levels or
+    /// WooCommerce's levels fails the whole push; a batch WooCommerce
+    /// refuses fails only its own products.
+    pub async fn push(&self, tenant_id: &str, product_ids: &[String]) -> Result<HashMap<String, PushOutcome>, String> {
+        let mut outcomes = HashMap::new();
+
+        if !self.pushes_enabled().await? {
+            for product_id in product_ids {
+                outcomes.insert(
+                    product_id.clone(),
+                    PushOutcome::Skipped("WooCommerce is the source of truth for inventory".to_string()),
+                );
+            }
+            return Ok(outcomes);
+        }
+
+        let mut products = Vec::new();
+        for product_id in product_ids {
+            match self.pos_stock(tenant_id, product_id).await? {
+                Some(product) if !product.sku.is_empty() => products.push(product),
+                _ => {
+                    outcomes.insert(product_id.clone(), PushOutcome::Skipped("Product has no SKU".to_string()));
+                }
+            }
+        }
+        if products.is_empty() {
+            return Ok(outcomes);
+        }
+
+        let skus: Vec<String> = products.iter().map(|p| p.sku.clone()).collect();
+        let mut levels: HashMap<String, StockLevel> = HashMap::new();
+        for level in self
+            .client
+            .get_stock_levels(&skus)
+            .await
+            .map_err(|e| format!("Failed to fetch WooCommerce stock levels: {}", e))?
+        {
+            levels.entry(level.sku.clone()).or_insert(level);
+        }
+
+        // Levels to write, by the product whose variations they are
+        let mut batches: HashMap<Option<i64>, Vec<PlannedPush>> = HashMap::new();
+
+        for product in products {
+            let Some(level) = levels.get(&product.sku) else {
+                outcomes.insert(
+                    product.id,
+                    PushOutcome::Skipped(format!("No WooCommerce product has SKU {}", product.sku)),
+                );
+                continue;
+            };
+            if !level.tracks_stock() {
+                outcomes.insert(
+                    product.id,
+                    PushOutcome::Skipped(format!("WooCommerce does not manage stock for SKU {}", product.sku)),
+                );
+                continue;
+            }
+
+            let pos_level = product.quantity_on_hand.floor() as i64;
+            let woo_level = level.stock_quantity.unwrap_or(0);
+            let last_pushed = self.last_pushed(&product.id).await?;
+
+            let wanted = match last_pushed {
+                Some(base) => woo_level + pos_level - base,
+                None => pos_level,
+            };
+            let oversold = match last_pushed {
+                Some(base) if wanted < 0 && woo_level < base => Some(Oversell { units: -wanted, last_pushed: base, woo_level }),
+                _ => None,
+            };
+            let planned = PlannedPush {
+                product_id: product.id,
+                woo_id: level.id,
+                pos_level,
+                quantity: wanted.max(0),
+                oversold,
+            };
+
+            if planned.quantity == woo_level {
+                if last_pushed != Some(pos_level) {
+                    self.record_operation(tenant_id, &planned, "skip", "skipped").await?;
+                }
+                let outcome = self.settle(tenant_id, &planned, PushOutcome::Unchanged).await?;
+                outcomes.insert(planned.product_id, outcome);
+                continue;
+            }
+
+            batches.entry(level.variation_of()).or_default().push(planned);
+        }
+
+        for (variation_of, planned) in batches {
+            let updates: Vec<StockUpdate> = planned
+                .iter()
+                .map(|p| StockUpdate { id: p.woo_id, stock_quantity: p.quantity })
+                .collect();
+
+            let results = match self.client.update_stock_batch(variation_of, &updates).await {
+                Ok(results) => results,
+                Err(e) => {
+                    let error = format!("Failed to update WooCommerce stock: {}", e);
+                    for p in planned {
+                        outcomes.insert(p.product_id, PushOutcome::Failed(error.clone()));
+                    }
+                    continue;
+                }
+            };
+            let results: HashMap<i64, _> = results.into_iter().map(|item| (item.id, item)).collect();
+
+            for p in planned {
+                let outcome = match results.get(&p.woo_id) {
+                    Some(item) => match &item.error {
+                        Some(error) => PushOutcome::Failed(format!("WooCommerce refused the update: {}", error.message)),
+                        None => {
+                            self.record_operation(tenant_id, &p, "update", "success").await?;
+                            self.settle(tenant_id, &p, PushOutcome::Pushed(p.quantity)).await?
+                        }
+                    },
+                    None => PushOutcome::Failed("Missing from the WooCommerce batch response".to_string()),
+                };
+                outcomes.insert(p.product_id, outcome);
+            }
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Whether the credential lets stock flow from the POS to WooCommerce
+    async fn pushes_enabled(&self) -> Result<bool, String> {
+        let two_way = self.direction_control.get_sync_direction(&self.credential_id).await? == SyncDirection::TwoWay;
+        let platform_owns_stock = self
+            .direction_control
+            .get_sync_config(&self.credential_id)
+            .await?
+            .get_entity_config(INVENTORY)
+            .is_some_and(|c| c.source_of_truth == SourceOfTruth::Platform);
+
+        Ok(two_way || !platform_owns_stock)
+    }
+
+    /// The outcome of a push WooCommerce took, or had no need of; an
+    /// oversell is recorded as a conflict
+    async fn settle(&self, tenant_id: &str, push: &PlannedPush, outcome: PushOutcome) -> Result<PushOutcome, String> {
+        let Some(oversell) = &push.oversold else {
+            return Ok(outcome);
+        };
+
+        tracing::warn!(
+            "Product {} oversold by {} between the POS and WooCommerce",
+            push.product_id,
+            oversell.units
+        );
+
+        let now = Utc::now().to_rfc3339();
+        self.direction_control
+            .create_conflict(
+                tenant_id,
+                &self.credential_id,
+                PLATFORM,
+                INVENTORY,
+                &push.product_id,
+                Some(&push.woo_id.to_string()),
+                &json!({ "quantity_on_hand": push.pos_level, "last_pushed": oversell.last_pushed }).to_string(),
+                &json!({ "stock_quantity": oversell.woo_level, "oversold": oversell.units }).to_string(),
+                &now,
+                &now,
+                ConflictStrategy::Manual,
+            )
+            .await?;
+
+        Ok(PushOutcome::Oversold(oversell.units))
+    }
+
+    /// A product's SKU and on-hand over all stores
+    async fn pos_stock(&self, tenant_id: &str, product_id: &str) -> Result<Option<PosStock>, String> {
+        sqlx::query_as(
+            "SELECT id, COALESCE(sku, '') AS sku, quantity_on_hand FROM products
+             WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(product_id)
+        .bind(tenant_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up stock: {}", e))
+    }
+
+    /// The POS level the product's last push was made from
+    async fn last_pushed(&self, product_id: &str) -> Result<Option<i64>, String> {
+        let level: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT response_data FROM integration_sync_operations
+             WHERE credential_id = ? AND entity_type = ? AND entity_id = ? AND direction = ?
+               AND status IN ('success', 'skipped')
+               AND completed_at IS NOT NULL
+             ORDER BY completed_at DESC
+             LIMIT 1",
+        )
+        .bind(&self.credential_id)
+        .bind(INVENTORY)
+        .bind(product_id)
+        .bind(DIRECTION)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to look up last push: {}", e))?;
+
+        Ok(level.flatten().and_then(|level| level.parse().ok()))
+    }
+
+    /// Log a push, with the POS level it was made from
+    async fn record_operation(&self, tenant_id: &str, push: &PlannedPush, operation: &str, status: &str) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO integration_sync_operations (
+                id, tenant_id, credential_id, platform, operation_type, entity_type,
+                entity_id, platform_entity_id, status, direction, completed_at,
+                already_synced, response_data
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(&self.credential_id)
+        .bind(PLATFORM)
+        .bind(operation)
+        .bind(INVENTORY)
+        .bind(&push.product_id)
+        .bind(push.woo_id.to_string())
+        .bind(status)
+        .bind(DIRECTION)
+        .bind(Utc::now().to_rfc3339())
+        .bind(push.pos_level.to_string())
+        .execute(&self.db)
+        .await
+        .map_err(|e| format!("Failed to record sync operation: {}", e))?;
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::credential_service::WooCommerceCredentials;
+    use crate::services::inventory_ledger_service::{
+        self, MovementType, StockMovement, REASON_SALE, REASON_SYNC, REASON_TRANSFER_OUT,
+    };
+    use crate::services::sync_direction_control::{EntitySyncConfig, SyncConfig};
+    use crate::services::sync_queue_processor::{BackoffPolicy, StockPushCounts, SyncQueueProcessor};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                sku TEXT NOT NULL UNIQUE,
+                name TEXT NOT NULL,
+                category TEXT NOT NULL DEFAULT 'General',
+                unit_price REAL NOT NULL DEFAULT 0,
+                cost REAL NOT NULL DEFAULT 0,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+            "CREATE TABLE sync_queue (
+                id TEXT PRIMARY KEY,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                sync_status TEXT NOT NULL DEFAULT 'pending',
+                retry_count INTEGER NOT NULL DEFAULT 0,
+                last_retry_at TEXT,
+                error_message TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                store_id TEXT NOT NULL,
+                tenant_id VARCHAR(255) NOT NULL DEFAULT 'default',
+                idempotency_key TEXT,
+                priority INTEGER NOT NULL DEFAULT 99
+            )",
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE settings (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            "INSERT INTO stores (id, name) VALUES ('s1', 'Main Street'), ('s2', 'Harbour')",
+            include_str!("../../../../migrations/025_integration_credentials.sql"),
+            include_str!("../../../../migrations/026_field_mappings.sql"),
+            include_str!("../../../../migrations/028_sync_direction_control.sql"),
+            include_str!("../../../../migrations/029_sync_schedules.sql"),
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+            include_str!("../../../../migrations/077_outbound_webhooks.sql"),
+            include_str!("../../../../migrations/078_square_import.sql"),
+            include_str!("../../../../migrations/079_clover_sync.sql"),
+            include_str!("../../../../migrations/080_woocommerce_stock_push.sql"),
+            "INSERT INTO integration_credentials (id, tenant_id, platform, credentials_encrypted)
+             VALUES ('cred-1', 't1', 'woocommerce', 'encrypted')",
+            "INSERT INTO products (id, tenant_id, store_id, sku, name) VALUES
+                ('p-mug', 't1', 's1', 'MUG', 'Mug'),
+                ('p-tee', 't1', 's1', 'TEE-M', 'T-Shirt (M)'),
+                ('p-bag', 't1', 's1', '', 'Paper bag'),
+                ('p-other', 't2', 's1', 'OTHER', 'Other tenant''s mug')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    async fn stock(pool: &SqlitePool, tenant_id: &str, product_id: &str, level: f64) {
+        let movement = StockMovement::new(tenant_id, product_id, MovementType::Adjustment, 0.0, REASON_SYNC).at_store("s1");
+        let mut conn = pool.acquire().await.unwrap();
+        inventory_ledger_service::set_on_hand(&mut conn, movement, level).await.unwrap();
+    }
+
+    async fn sell(pool: &SqlitePool, product_id: &str, quantity: f64) {
+        let movement = StockMovement::new("t1", product_id, MovementType::Sale, -quantity, REASON_SALE).at_store("s1");
+        let mut conn = pool.acquire().await.unwrap();
+        inventory_ledger_service::record_movement(&mut conn, &movement).await.unwrap();
+    }
+
+    /// Let every queued push's debounce run out
+    async fn make_due(pool: &SqlitePool) {
+        sqlx::query("UPDATE sync_queue SET not_before = datetime('now', '-1 second') WHERE sync_status = 'pending'")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn pending_pushes(pool: &SqlitePool) -> Vec<(String, i64)> {
+        sqlx::query_as(
+            "SELECT entity_id, json_extract(payload, '$.changes') FROM sync_queue
+             WHERE entity_type = 'inventory' AND operation = 'push' AND sync_status = 'pending'
+             ORDER BY entity_id",
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    /// The mug as a product and the medium T-shirt as a variation of product 20
+    async fn mock_store(mug_level: i64, tee_level: i64) -> MockServer {
+        let server = MockServer::start().await;
+        mount_levels(&server, mug_level, tee_level).await;
+        server
+    }
+
+    async fn mount_levels(server: &MockServer, mug_level: i64, tee_level: i64) {
+        Mock::given(method("GET"))
+            .and(path("/products"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "id": 10, "parent_id": 0, "sku": "MUG", "manage_stock": true, "stock_quantity": mug_level },
+                { "id": 21, "parent_id": 20, "sku": "TEE-M", "manage_stock": true, "stock_quantity": tee_level }
+            ])))
+            .mount(server)
+            .await;
+    }
+
+    fn flow(pool: &SqlitePool, server: &MockServer) -> WooStockPushFlow {
+        let client = WooCommerceClient::new(WooCommerceCredentials {
+            consumer_key: "ck_test".to_string(),
+            consumer_secret: "cs_test".to_string(),
+            store_url: "https://shop.example.com".to_string(),
+        })
+        .unwrap()
+        .with_base_url(server.uri());
+
+        WooStockPushFlow::new(pool.clone(), client, "cred-1", Arc::new(SyncDirectionControl::new(pool.clone())))
+    }
+
+    /// Push the opening levels, which WooCommerce already has
+    async fn first_push(pool: &SqlitePool, server: &MockServer) {
+        make_due(pool).await;
+        let counts = SyncQueueProcessor::new(pool.clone()).push_stock("t1", &flow(pool, server)).await.unwrap();
+        assert_eq!(counts.unchanged, 2);
+        assert!(pending_pushes(pool).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_movements_queue_one_debounced_push_per_product() {
+        let pool = setup_test_db().await;
+        for product_id in ["p-mug", "p-tee", "p-bag"] {
+            stock(&pool, "t1", product_id, 5.0).await;
+        }
+        stock(&pool, "t2", "p-other", 5.0).await;
+
+        sell(&pool, "p-mug", 1.0).await;
+        sell(&pool, "p-mug", 2.0).await;
+        let transfer = StockMovement::new("t1", "p-tee", MovementType::Transfer, -1.0, REASON_TRANSFER_OUT).at_store("s1");
+        let mut conn = pool.acquire().await.unwrap();
+        inventory_ledger_service::record_movement(&mut conn, &transfer).await.unwrap();
+        drop(conn);
+
+        // Sales coalesce into the opening push; the transfer, the product
+        // without a SKU and the tenant without WooCommerce queue nothing
+        assert_eq!(
+            pending_pushes(&pool).await,
+            vec![("p-mug".to_string(), 3), ("p-tee".to_string(), 1)]
+        );
+
+        let processor = SyncQueueProcessor::new(pool.clone());
+        assert!(processor.fetch_due_stock_pushes("t1", 100).await.unwrap().is_empty());
+
+        make_due(&pool).await;
+        let due = processor.fetch_due_stock_pushes("t1", 100).await.unwrap();
+        assert_eq!(due.len(), 2);
+        assert!(due.iter().all(|item| item.store_id == "s1"));
+    }
+
+    #[tokio::test]
+    async fn test_pos_changes_are_added_to_woocommerce_levels_in_batches() {
+        let pool = setup_test_db().await;
+        stock(&pool, "t1", "p-mug", 5.0).await;
+        stock(&pool, "t1", "p-tee", 3.0).await;
+        let server = mock_store(5, 3).await;
+        first_push(&pool, &server).await;
+
+        // The web store sells a mug while the register sells two, and a T-shirt
+        server.reset().await;
+        mount_levels(&server, 4, 3).await;
+        Mock::given(method("POST"))
+            .and(path("/products/batch"))
+            .and(body_partial_json(serde_json::json!({ "update": [{ "id": 10, "stock_quantity": 2 }] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "update": [{ "id": 10, "stock_quantity": 2 }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/products/20/variations/batch"))
+            .and(body_partial_json(serde_json::json!({ "update": [{ "id": 21, "stock_quantity": 2 }] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "update": [{ "id": 21, "stock_quantity": 2 }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        sell(&pool, "p-mug", 2.0).await;
+        sell(&pool, "p-tee", 1.0).await;
+        make_due(&pool).await;
+
+        let counts = SyncQueueProcessor::new(pool.clone()).push_stock("t1", &flow(&pool, &server)).await.unwrap();
+        assert_eq!(counts, StockPushCounts { pushed: 2, ..Default::default() });
+        assert!(pending_pushes(&pool).await.is_empty());
+
+        // The next change is measured from the level this push was made from
+        let flow = flow(&pool, &server);
+        assert_eq!(flow.last_pushed("p-mug").await.unwrap(), Some(3));
+        assert_eq!(flow.last_pushed("p-tee").await.unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_last_unit_sold_on_both_sides_is_an_oversell_conflict() {
+        let pool = setup_test_db().await;
+        stock(&pool, "t1", "p-mug", 1.0).await;
+        stock(&pool, "t1", "p-tee", 3.0).await;
+        let server = mock_store(1, 3).await;
+        first_push(&pool, &server).await;
+
+        // The web store sells the last mug while the register does too
+        server.reset().await;
+        mount_levels(&server, 0, 3).await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "update": [] })))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        sell(&pool, "p-mug", 1.0).await;
+        make_due(&pool).await;
+
+        let counts = SyncQueueProcessor::new(pool.clone()).push_stock("t1", &flow(&pool, &server)).await.unwrap();
+        assert_eq!(counts, StockPushCounts { oversold: 1, ..Default::default() });
+
+        let conflict: (String, String, String, String) = sqlx::query_as(
+            "SELECT entity_id, platform_entity_id, resolution_strategy, status FROM integration_sync_conflicts
+             WHERE credential_id = 'cred-1' AND entity_type = 'inventory'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(conflict, ("p-mug".into(), "10".into(), "manual".into(), "pending".into()));
+    }
+
+    #[tokio::test]
+    async fn test_refused_update_is_retried_after_backoff() {
+        let pool = setup_test_db().await;
+        stock(&pool, "t1", "p-mug", 5.0).await;
+        stock(&pool, "t1", "p-tee", 3.0).await;
+        let server = mock_store(5, 3).await;
+        first_push(&pool, &server).await;
+
+        Mock::given(method("POST"))
+            .and(path("/products/batch"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "update": [{
+                    "id": 10,
+                    "error": { "code": "woocommerce_rest_product_invalid_id", "message": "Invalid ID.", "data": { "status": 400 } }
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        sell(&pool, "p-mug", 1.0).await;
+        make_due(&pool).await;
+
+        let processor = SyncQueueProcessor::with_backoff_policy(
+            pool.clone(),
+            BackoffPolicy { base_delay_ms: 60_000, jitter_factor: 0.0, ..Default::default() },
+        );
+        let counts = processor.push_stock("t1", &flow(&pool, &server)).await.unwrap();
+        assert_eq!(counts, StockPushCounts { failed: 1, ..Default::default() });
+
+        let (status, retry_count, error): (String, i32, String) = sqlx::query_as(
+            "SELECT sync_status, retry_count, error_message FROM sync_queue
+             WHERE entity_id = 'p-mug' AND operation = 'push' ORDER BY rowid DESC LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((status.as_str(), retry_count), ("pending", 1));
+        assert!(error.contains("Invalid ID."));
+        assert!(processor.fetch_due_stock_pushes("t1", 100).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_nothing_is_pushed_when_woocommerce_owns_stock() {
+        let pool = setup_test_db().await;
+        let mut config = SyncConfig::new();
+        config.add_entity(
+            INVENTORY.to_string(),
+            EntitySyncConfig { source_of_truth: SourceOfTruth::Platform, conflict_strategy: ConflictStrategy::NewestWins },
+        );
+        SyncDirectionControl::new(pool.clone()).set_sync_config("cred-1", &config).await.unwrap();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        stock(&pool, "t1", "p-mug", 5.0).await;
+        make_due(&pool).await;
+
+        let counts = SyncQueueProcessor::new(pool.clone()).push_stock("t1", &flow(&pool, &server)).await.unwrap();
+        assert_eq!(counts, StockPushCounts { skipped: 1, ..Default::default() });
+        assert!(pending_pushes(&pool).await.is_empty());
+    }
+}
@@ -3,10 +3,18 @@ use chrono::{Duration, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use crate::auth::{generate_token, verify_password};
+use crate::auth::jwt::Claims;
+use crate::auth::{encode_token, generate_token_with_permissions, verify_password};
 use crate::config::Config;
 use crate::middleware::{get_current_tenant_id, generate_csrf_token, create_csrf_cookie, clear_csrf_cookie};
-use crate::models::{LoginRequest, LoginResponse, User, UserResponse};
+use crate::models::{
+    LoginEnrollRequest, LoginRequest, LoginResponse, LoginVerifyRequest, StationLoginRequest, StationLoginResponse,
+    TwoFactorChallengeResponse, User, UserResponse,
+};
+use crate::services::credential_service::CredentialService;
+use crate::services::station_login_service::{self, StationLoginError};
+use crate::services::two_factor_service::TwoFactorError;
+use crate::services::{role_service, StationLoginService, TwoFactorService};
 
 /// Cookie name for auth token
 const AUTH_COOKIE_NAME: &str = "auth_token";
@@ -109,6 +117,9 @@ fn check_rate_limit(ip: &str) -> Result<(), AuthError> {
 
 /// POST /auth/login
 /// Authenticate a user and return a JWT token
+///
+/// Users with a second factor, or whose role requires one, get a login
+/// challenge instead; the token is issued by `POST /auth/login/verify`.
 #[post("/auth/login")]
 pub async fn login(
     pool: web::Data<SqlitePool>,
@@ -205,14 +216,133 @@ pub async fn login(
         }));
     }
 
+    // Second factor, when the user has one or their role requires one
+    let two_factor = match two_factor_service(pool.get_ref()) {
+        Ok(service) => service,
+        Err(response) => return response,
+    };
+    match two_factor.start_login(&user.tenant_id, &user.id).await {
+        Ok(Some(challenge)) => {
+            tracing::info!("Password accepted for user {}; second factor required", user.username);
+            return HttpResponse::Ok().json(TwoFactorChallengeResponse {
+                two_factor_required: true,
+                setup_required: challenge.setup_required,
+                challenge_token: challenge.token,
+                expires_at: challenge.expires_at,
+            });
+        }
+        Ok(None) => {}
+        Err(e) => return two_factor_error_response(e),
+    }
+
+    complete_sign_in(pool.get_ref(), &config, user, None).await
+}
+
+/// POST /auth/login/enroll
+/// Start the enrollment a login challenge requires: the secret and the
+/// provisioning URI to show as a QR code
+#[post("/auth/login/enroll")]
+pub async fn login_enroll(
+    pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    req: web::Json<LoginEnrollRequest>,
+) -> impl Responder {
+    let two_factor = match two_factor_service(pool.get_ref()) {
+        Ok(service) => service,
+        Err(response) => return response,
+    };
+
+    match two_factor
+        .challenge_enrollment(&get_current_tenant_id(), &req.challenge_token, &config.store_name)
+        .await
+    {
+        Ok(enrollment) => HttpResponse::Ok().json(enrollment),
+        Err(e) => two_factor_error_response(e),
+    }
+}
+
+/// POST /auth/login/verify
+/// Complete a login challenge with an authenticator or recovery code and
+/// return a JWT token
+#[post("/auth/login/verify")]
+pub async fn login_verify(
+    pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    req: web::Json<LoginVerifyRequest>,
+) -> impl Responder {
+    let two_factor = match two_factor_service(pool.get_ref()) {
+        Ok(service) => service,
+        Err(response) => return response,
+    };
+
+    let completed = match two_factor
+        .complete_login(&get_current_tenant_id(), &req.challenge_token, &req.code)
+        .await
+    {
+        Ok(completed) => completed,
+        Err(e) => return two_factor_error_response(e),
+    };
+
+    let user_result = sqlx::query_as::<_, User>(
+        "SELECT id, tenant_id, username, email, password_hash, display_name, role, first_name, last_name, 
+         store_id, station_policy, station_id, is_active, created_at, updated_at 
+         FROM users 
+         WHERE id = ? AND is_active = 1 AND tenant_id = ?"
+    )
+    .bind(&completed.user_id)
+    .bind(get_current_tenant_id())
+    .fetch_optional(pool.get_ref())
+    .await;
+
+    match user_result {
+        Ok(Some(user)) => complete_sign_in(pool.get_ref(), &config, user, completed.recovery_codes).await,
+        Ok(None) => HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Invalid username or password"
+        })),
+        Err(e) => {
+            tracing::error!("Database error during login: {:?}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            }))
+        }
+    }
+}
+
+/// Issue the token and session for a user who has passed every sign-in check
+async fn complete_sign_in(
+    pool: &SqlitePool,
+    config: &Config,
+    user: User,
+    recovery_codes: Option<Vec<String>>,
+) -> HttpResponse {
+    // Resolve permissions from the tenant's roles for the user's store
+    let permissions = match role_service::resolve_permissions(
+        pool,
+        &user.tenant_id,
+        &user.id,
+        &user.role,
+        user.store_id.as_deref(),
+    )
+    .await
+    {
+        Ok(permissions) => permissions,
+        Err(e) => {
+            tracing::error!("Failed to resolve permissions for user {}: {:?}", user.id, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            }));
+        }
+    };
+
     // Generate JWT token with user context
-    let token = match generate_token(
+    let token = match generate_token_with_permissions(
         &user.id,
         &user.username,
         &user.role,
         &user.tenant_id,
         user.store_id.clone(),
         user.station_id.clone(),
+        permissions.clone(),
         &config.jwt_secret,
         config.jwt_expiration_hours as i64,
     ) {
@@ -238,7 +368,7 @@ pub async fn login(
     .bind(&user.id)
     .bind(&token)
     .bind(&expires_at_str)
-    .execute(pool.get_ref())
+    .execute(pool)
     .await;
 
     if let Err(e) = session_result {
@@ -254,7 +384,7 @@ pub async fn login(
         .bind(&now)
         .bind(&user.id)
         .bind(get_current_tenant_id())
-        .execute(pool.get_ref())
+        .execute(pool)
         .await
     {
         tracing::warn!("Failed to update last_login_at for user {}: {:?}", user.id, e);
@@ -263,34 +393,157 @@ pub async fn login(
 
     tracing::info!("Login successful for user: {} ({})", user.username, user.id);
 
+    let (cookie, csrf_cookie) = session_cookies(&token, config.jwt_expiration_hours as i64);
+
+    // Return response with user info (token is in cookie, not body for security)
+    // We still include token in response for backward compatibility during migration
+    let mut user = UserResponse::from(user);
+    user.permissions = permissions;
+    let response = LoginResponse {
+        token: token.clone(),
+        user,
+        expires_at,
+        recovery_codes,
+    };
+
+    HttpResponse::Ok()
+        .cookie(cookie)
+        .cookie(csrf_cookie)
+        .json(response)
+}
+
+fn two_factor_service(pool: &SqlitePool) -> Result<TwoFactorService, HttpResponse> {
+    match CredentialService::new(pool.clone()) {
+        Ok(credentials) => Ok(TwoFactorService::new(pool.clone(), credentials)),
+        Err(e) => {
+            tracing::error!("Failed to create credential service: {:?}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Two-factor authentication is unavailable"
+            })))
+        }
+    }
+}
+
+fn two_factor_error_response(e: TwoFactorError) -> HttpResponse {
+    let (mut response, message) = match e {
+        TwoFactorError::Validation(msg) => (HttpResponse::BadRequest(), msg),
+        TwoFactorError::NotFound(msg) => (HttpResponse::NotFound(), msg),
+        TwoFactorError::Forbidden(msg) => (HttpResponse::Forbidden(), msg),
+        TwoFactorError::Conflict(msg) => (HttpResponse::Conflict(), msg),
+        TwoFactorError::InvalidCode | TwoFactorError::ChallengeExpired => (HttpResponse::Unauthorized(), e.to_string()),
+        TwoFactorError::TooManyAttempts => (HttpResponse::TooManyRequests(), e.to_string()),
+        TwoFactorError::Encryption(_) | TwoFactorError::Database(_) => {
+            tracing::error!("Two-factor error during login: {:?}", e);
+            (HttpResponse::InternalServerError(), "Failed to verify the second factor".to_string())
+        }
+    };
+    response.json(serde_json::json!({
+        "error": message
+    }))
+}
+
+/// POST /auth/station-login
+/// Quick sign-in at a register by PIN or badge, bound to the station
+///
+/// Failed attempts count against the station and lock its PIN pad; the
+/// session ends after the store's idle timeout without a request.
+#[post("/auth/station-login")]
+pub async fn station_login(
+    pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    req: web::Json<StationLoginRequest>,
+) -> impl Responder {
+    let service = StationLoginService::new(pool.get_ref().clone(), config.jwt_secret.clone());
+
+    let sign_in = match service.authenticate(&get_current_tenant_id(), &req).await {
+        Ok(sign_in) => sign_in,
+        Err(e) => {
+            let (mut response, message) = match e {
+                StationLoginError::Validation(msg) => (HttpResponse::BadRequest(), msg),
+                StationLoginError::NotFound(msg) => (HttpResponse::NotFound(), msg),
+                StationLoginError::Forbidden(msg) => (HttpResponse::Forbidden(), msg),
+                StationLoginError::InvalidCredentials => (HttpResponse::Unauthorized(), e.to_string()),
+                StationLoginError::LockedOut(_) => (HttpResponse::TooManyRequests(), e.to_string()),
+                StationLoginError::Database(err) => {
+                    tracing::error!("Database error during station login: {:?}", err);
+                    (HttpResponse::InternalServerError(), "Database error".to_string())
+                }
+            };
+            return response.json(serde_json::json!({
+                "error": message
+            }));
+        }
+    };
+
+    let now = Utc::now();
+    let expires_at = now + Duration::hours(config.jwt_expiration_hours as i64);
+    let claims = Claims {
+        sub: sign_in.user.id.clone(),
+        username: sign_in.user.username.clone(),
+        role: sign_in.user.role.clone(),
+        tenant_id: sign_in.user.tenant_id.clone(),
+        store_id: Some(sign_in.store_id.clone()),
+        station_id: Some(sign_in.station_id.clone()),
+        exp: expires_at.timestamp(),
+        iat: now.timestamp(),
+        iat_ms: Some(now.timestamp_millis()),
+        permissions: Some(sign_in.permissions.clone()),
+        login_method: Some(sign_in.method.to_string()),
+    };
+    let token = match encode_token(&claims, &config.jwt_secret) {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::error!("Token generation error: {:?}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to generate token"
+            }));
+        }
+    };
+
+    if let Err(e) = service.start_session(&sign_in, &token, &expires_at).await {
+        tracing::error!("Failed to create session: {:?}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to create session"
+        }));
+    }
+
+    let (cookie, csrf_cookie) = session_cookies(&token, config.jwt_expiration_hours as i64);
+
+    let mut user = UserResponse::from(sign_in.user);
+    user.store_id = Some(sign_in.store_id);
+    user.station_id = Some(sign_in.station_id.clone());
+    user.permissions = sign_in.permissions;
+    HttpResponse::Ok()
+        .cookie(cookie)
+        .cookie(csrf_cookie)
+        .json(StationLoginResponse {
+            token,
+            user,
+            expires_at,
+            station_id: sign_in.station_id,
+            idle_timeout_minutes: sign_in.idle_timeout_minutes,
+        })
+}
+
+/// Auth cookie (httpOnly) and CSRF cookie for a new session
+fn session_cookies(token: &str, expiration_hours: i64) -> (Cookie<'static>, Cookie<'static>) {
     // Determine if we're in production (use Secure flag)
     let is_production = cfg!(not(debug_assertions)) || std::env::var("ENVIRONMENT").unwrap_or_default() == "production";
     
     // Build httpOnly cookie for secure token storage
-    let cookie = Cookie::build(AUTH_COOKIE_NAME, token.clone())
+    let cookie = Cookie::build(AUTH_COOKIE_NAME, token.to_string())
         .path("/")
         .http_only(true)  // Prevents JavaScript access - XSS protection
         .secure(is_production)  // Only send over HTTPS in production
         .same_site(SameSite::Lax)  // Lax allows cookie on top-level navigations, Strict was too restrictive
-        .max_age(actix_web::cookie::time::Duration::hours(config.jwt_expiration_hours as i64))
+        .max_age(actix_web::cookie::time::Duration::hours(expiration_hours))
         .finish();
 
     // Generate CSRF token and create cookie (readable by JavaScript for double-submit pattern)
     let csrf_token = generate_csrf_token();
     let csrf_cookie = create_csrf_cookie(&csrf_token, is_production);
 
-    // Return response with user info (token is in cookie, not body for security)
-    // We still include token in response for backward compatibility during migration
-    let response = LoginResponse {
-        token: token.clone(),
-        user: UserResponse::from(user),
-        expires_at,
-    };
-
-    HttpResponse::Ok()
-        .cookie(cookie)
-        .cookie(csrf_cookie)
-        .json(response)
+    (cookie, csrf_cookie)
 }
 
 /// POST /auth/logout
@@ -405,6 +658,38 @@ pub async fn get_current_user(
         }
     };
 
+    // Reject tokens issued before the user's permissions changed
+    match role_service::token_is_current(pool.get_ref(), &claims.tenant_id, &claims.sub, claims.issued_at()).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Invalid or expired token"
+            }));
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            }));
+        }
+    }
+
+    // Station sign-ins end when idle or signed out
+    if claims.login_method.is_some() {
+        match station_login_service::session_is_active(pool.get_ref(), &token).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::Unauthorized().json(serde_json::json!({
+                    "error": "Invalid or expired token"
+                }));
+            }
+            Err(_) => {
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Database error"
+                }));
+            }
+        }
+    }
+
     // Fetch user from database
     let user_result = sqlx::query_as::<_, User>(
         "SELECT id, tenant_id, username, email, password_hash, display_name, role, first_name, last_name, 
@@ -418,7 +703,14 @@ pub async fn get_current_user(
     .await;
 
     match user_result {
-        Ok(Some(user)) => HttpResponse::Ok().json(UserResponse::from(user)),
+        Ok(Some(user)) => {
+            // The permissions the token grants, resolved at sign-in
+            let mut user = UserResponse::from(user);
+            if let Some(permissions) = claims.permissions {
+                user.permissions = permissions;
+            }
+            HttpResponse::Ok().json(user)
+        }
         Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
             "error": "User not found"
         })),
@@ -433,6 +725,8 @@ mod tests {
     use super::*;
     use actix_web::{test, App};
     use crate::test_utils::create_test_db;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::path::Path;
 
     #[actix_web::test]
     async fn test_login_requires_store_for_pos_roles() {
@@ -665,6 +959,91 @@ mod tests {
         assert!(body["token"].is_string());
         assert_eq!(body["user"]["username"], "admin1");
     }
+
+    #[actix_web::test]
+    async fn test_login_requires_second_factor_when_role_does() {
+        // Roles and second factors live in the workspace migrations
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        crate::db::migrations::run_migrations_from(&pool, &workspace).await.unwrap();
+        // Login resolves the tenant from the environment, which other tests set
+        let tenant_id = get_current_tenant_id();
+        let config = Config {
+            jwt_secret: "test-secret".to_string(),
+            jwt_expiration_hours: 8,
+            ..Default::default()
+        };
+
+        let password_hash = bcrypt::hash("password123", bcrypt::DEFAULT_COST).unwrap();
+        sqlx::query(
+            "INSERT INTO users (id, tenant_id, username, email, password_hash, role, store_id, station_policy, is_active, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, NULL, ?, ?, datetime('now'), datetime('now'))"
+        )
+        .bind("user-5")
+        .bind(&tenant_id)
+        .bind("admin2")
+        .bind("admin2@test.com")
+        .bind(&password_hash)
+        .bind("admin")
+        .bind("none")
+        .bind(1)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        role_service::ensure_system_roles(&mut conn, &tenant_id)
+            .await
+            .unwrap();
+        drop(conn);
+        sqlx::query("UPDATE roles SET requires_two_factor = 1 WHERE tenant_id = ? AND name = 'admin'")
+            .bind(&tenant_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::new(config.clone()))
+                .service(login)
+                .service(login_verify),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/auth/login")
+            .set_json(serde_json::json!({
+                "username": "admin2",
+                "password": "password123"
+            }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+        assert!(resp.response().cookies().next().is_none());
+
+        // No token until the second factor is set up and verified
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert!(body["token"].is_null());
+        assert_eq!(body["two_factor_required"], true);
+        assert_eq!(body["setup_required"], true);
+
+        let req = test::TestRequest::post()
+            .uri("/auth/login/verify")
+            .set_json(serde_json::json!({
+                "challenge_token": body["challenge_token"],
+                "code": "123456"
+            }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+    }
 }
 
 
@@ -211,28 +211,6 @@ pub async fn calculate_commission(
     Ok(commissions)
 }
 
-/// Reverse commission for a returned transaction
-pub async fn reverse_commission(
-    pool: &SqlitePool,
-    transaction_id: &str,
-) -> Result<(), sqlx::Error> {
-    tracing::info!("Reversing commissions for transaction: {}", transaction_id);
-
-    // Mark existing commissions as reversed
-    sqlx::query(
-        "UPDATE commissions 
-         SET is_reversed = 1 
-         WHERE transaction_id = ? AND is_reversed = 0 AND tenant_id = ?",
-    )
-    .bind(transaction_id)
-    .bind(&get_current_tenant_id())
-    .execute(pool)
-    .await?;
-
-    tracing::info!("Commissions reversed for transaction: {}", transaction_id);
-    Ok(())
-}
-
 /// GET /api/commissions/employee/:id
 /// Get commissions for an employee
 #[get("/api/commissions/employee/{id}")]
@@ -0,0 +1,227 @@
+/**
+ * Stock Count Handlers
+ *
+ * Cycle counts and full physical inventories:
+ * - Start a count of a store, a category or a bin
+ * - Scan or key in counts from any number of devices
+ * - Finish the count, recount the lines flagged for it and finish again
+ * - Review the valuation impact, then approve to post the variances
+ *
+ * Counting requires the adjust_inventory permission; approving a count
+ * requires approve_inventory_counts.
+ */
+
+use actix_web::{get, post, web, HttpResponse};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::count_service::{CountEntry, CountScope, CountSessionRequest, CountStatus};
+use crate::services::CountService;
+
+// ============================================================================
+// Request Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct StartCountRequest {
+    /// Defaults to the signed-in user's store
+    pub store_id: Option<String>,
+    pub scope: CountScope,
+    /// The category or bin to count
+    pub scope_value: Option<String>,
+    pub recount_threshold_percent: Option<f64>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListCountsQuery {
+    pub store_id: Option<String>,
+    pub status: Option<CountStatus>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Start a count session
+///
+/// POST /api/inventory/counts
+#[post("/api/inventory/counts")]
+pub async fn start_count(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<StartCountRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+    let body = body.into_inner();
+
+    let store_id = body
+        .store_id
+        .or_else(|| context.store_id.clone())
+        .ok_or_else(|| ApiError::bad_request("store_id is required"))?;
+
+    let session = CountService::new(pool.get_ref().clone())
+        .start(CountSessionRequest {
+            tenant_id: context.tenant_id.clone(),
+            store_id,
+            scope: body.scope,
+            scope_value: body.scope_value,
+            recount_threshold_percent: body.recount_threshold_percent,
+            created_by: context.user_id.clone(),
+            notes: body.notes,
+        })
+        .await?;
+
+    Ok(HttpResponse::Created().json(session))
+}
+
+/// Count sessions, newest first
+///
+/// GET /api/inventory/counts
+#[get("/api/inventory/counts")]
+pub async fn list_counts(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<ListCountsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let sessions = CountService::new(pool.get_ref().clone())
+        .list(&context.tenant_id, query.store_id.as_deref(), query.status)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "total": sessions.len(),
+        "counts": sessions,
+    })))
+}
+
+/// GET /api/inventory/counts/{id}
+#[get("/api/inventory/counts/{id}")]
+pub async fn get_count(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let session = CountService::new(pool.get_ref().clone())
+        .get(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(session))
+}
+
+/// Add a scan or keyed quantity; the device defaults to the user's station
+///
+/// POST /api/inventory/counts/{id}/entries
+#[post("/api/inventory/counts/{id}/entries")]
+pub async fn record_entry(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<CountEntry>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+    let mut entry = body.into_inner();
+    if entry.device_id.is_none() {
+        entry.device_id = context.station_id.clone();
+    }
+
+    let line = CountService::new(pool.get_ref().clone())
+        .record_entry(&context.tenant_id, &path.into_inner(), &context.user_id, &entry)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(line))
+}
+
+/// Finish the count or the recount
+///
+/// POST /api/inventory/counts/{id}/finish
+#[post("/api/inventory/counts/{id}/finish")]
+pub async fn finish_count(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+
+    let session = CountService::new(pool.get_ref().clone())
+        .finish(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(session))
+}
+
+/// Valuation impact of the variances
+///
+/// GET /api/inventory/counts/{id}/report
+#[get("/api/inventory/counts/{id}/report")]
+pub async fn get_count_report(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let report = CountService::new(pool.get_ref().clone())
+        .report(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Approve a finished count and post its variances
+///
+/// POST /api/inventory/counts/{id}/approve
+#[post("/api/inventory/counts/{id}/approve")]
+pub async fn approve_count(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "approve_inventory_counts")?;
+
+    let report = CountService::new(pool.get_ref().clone())
+        .approve(&context.tenant_id, &path.into_inner(), &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Cancel a count that has not been posted
+///
+/// POST /api/inventory/counts/{id}/cancel
+#[post("/api/inventory/counts/{id}/cancel")]
+pub async fn cancel_count(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+
+    let session = CountService::new(pool.get_ref().clone())
+        .cancel(&context.tenant_id, &path.into_inner(), &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(session))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_permission(context: &UserContext, permission: &str) -> Result<(), ApiError> {
+    if context.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(format!("This requires the {permission} permission")))
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(start_count)
+       .service(list_counts)
+       .service(get_count)
+       .service(record_entry)
+       .service(finish_count)
+       .service(get_count_report)
+       .service(approve_count)
+       .service(cancel_count);
+}
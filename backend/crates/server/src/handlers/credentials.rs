@@ -495,6 +495,12 @@ pub async fn get_credentials(
                     "merchant_id": creds.merchant_id,
                     "has_credentials": true
                 }),
+                PlatformCredentials::Shopify(creds) => serde_json::json!({
+                    "platform": "shopify",
+                    "shop_domain": creds.shop_domain,
+                    "location_id": creds.location_id,
+                    "has_credentials": true
+                }),
                 PlatformCredentials::Stripe(creds) => serde_json::json!({
                     "platform": "stripe",
                     "account_id_masked": mask_stripe_account_id(&creds.stripe_user_id),
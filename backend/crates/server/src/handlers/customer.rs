@@ -7,6 +7,7 @@ use crate::middleware::get_current_tenant_id;
 use crate::models::{
     CreateCustomerRequest, Customer, CustomerResponse, CustomerWithStats, PricingTier, UpdateCustomerRequest,
 };
+use crate::services::outbound_webhook_service::{self, WebhookEventType};
 
 /// POST /api/customers
 /// Create a new customer
@@ -25,21 +26,46 @@ pub async fn create_customer(
         .unwrap_or(&PricingTier::Retail)
         .as_str();
 
-    let result = sqlx::query(
-        "INSERT INTO customers (id, tenant_id, name, email, phone, pricing_tier, loyalty_points, 
-         store_credit, credit_balance, created_at, updated_at, sync_version, store_id)
-         VALUES (?, ?, ?, ?, ?, ?, 0, 0.0, 0.0, ?, ?, 0, ?)",
-    )
-    .bind(&customer_id)
-    .bind(get_current_tenant_id())
-    .bind(&req.name)
-    .bind(&req.email)
-    .bind(&req.phone)
-    .bind(pricing_tier)
-    .bind(&now)
-    .bind(&now)
-    .bind(&req.store_id)
-    .execute(pool.get_ref())
+    let tenant_id = get_current_tenant_id();
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO customers (id, tenant_id, name, email, phone, pricing_tier, loyalty_points, 
+             store_credit, credit_balance, created_at, updated_at, sync_version, store_id)
+             VALUES (?, ?, ?, ?, ?, ?, 0, 0.0, 0.0, ?, ?, 0, ?)",
+        )
+        .bind(&customer_id)
+        .bind(&tenant_id)
+        .bind(&req.name)
+        .bind(&req.email)
+        .bind(&req.phone)
+        .bind(pricing_tier)
+        .bind(&now)
+        .bind(&now)
+        .bind(&req.store_id)
+        .execute(&mut *tx)
+        .await?;
+
+        outbound_webhook_service::publish(
+            &mut tx,
+            &tenant_id,
+            WebhookEventType::CustomerCreated,
+            serde_json::json!({
+                "customer_id": customer_id,
+                "name": req.name,
+                "email": req.email,
+                "phone": req.phone,
+                "pricing_tier": pricing_tier,
+                "store_id": req.store_id,
+                "created_at": now,
+            }),
+        )
+        .await?;
+
+        tx.commit().await
+    }
     .await;
 
     match result {
@@ -14,8 +14,9 @@ use crate::utils::csv_validation::{
     clean_keywords, clean_barcode_type, clean_pricing_tier, clean_payment_terms,
     clean_tax_class, validate_email, validate_sku, validate_barcode,
     validate_barcode_type, validate_price_cost_relationship,
-    validate_non_negative,
+    validate_non_negative, validate_tax_class,
 };
+use crate::services::tax_service::TaxService;
 
 /// Escape a CSV field value to prevent injection and handle special characters
 fn escape_csv_field(value: &str) -> String {
@@ -832,6 +833,12 @@ async fn import_products(
             create_req.category = default_category.clone();
         }
         
+        let tax_class = dynamic_attrs
+            .get("tax_class")
+            .and_then(|v| v.as_str())
+            .map(clean_tax_class)
+            .filter(|c| !c.is_empty());
+        
         // Merge dynamic attributes
         if !dynamic_attrs.is_empty() {
             let existing_attrs = create_req.attributes.unwrap_or(serde_json::json!({}));
@@ -852,6 +859,15 @@ async fn import_products(
                 let product_id = product.id.clone();
                 sku_to_id.insert(sku, product_id.clone());
                 
+                if let Some(tax_class) = tax_class {
+                    if let Err(e) = TaxService::new(pool.get_ref().clone())
+                        .set_product_tax_class(tenant_id, &product_id, &tax_class)
+                        .await
+                    {
+                        tracing::warn!("Row {}: tax_class '{}' not applied: {}", row_num, tax_class, e);
+                    }
+                }
+                
                 // Insert alternate SKUs
                 for alt_sku in alternate_skus {
                     let _ = insert_alternate_sku(
@@ -1021,8 +1037,13 @@ fn parse_product_row(
         }
     }
     
-    // Tax class normalization (validated but stored in attributes)
-    let _tax_class = get_field("tax_class").map(|s| clean_tax_class(&s));
+    // Tax class normalization (validated here, applied to products.tax_class on import)
+    let tax_class = get_field("tax_class").map(|s| clean_tax_class(&s));
+    if let Some(ref tc) = tax_class {
+        if !validate_tax_class(tc) {
+            tracing::warn!("Row {}: tax_class '{}' is not a known class (standard, reduced, clothing, grocery, exempt)", row_num, tc);
+        }
+    }
     
     // Collect ALL dynamic attributes (any column starting with attr_)
     let mut dynamic_attrs = serde_json::Map::new();
@@ -1104,7 +1125,7 @@ fn parse_product_row(
     }
     
     // Add tax_class and notes to attributes
-    if let Some(tax_class) = get_field("tax_class") {
+    if let Some(tax_class) = tax_class.filter(|tc| !tc.is_empty()) {
         dynamic_attrs.insert("tax_class".to_string(), serde_json::Value::String(tax_class));
     }
     if let Some(notes) = get_field("notes") {
@@ -1307,19 +1328,24 @@ async fn import_customers(
             });
         
         // Tax exempt with normalization
-        let _tax_exempt: Option<bool> = get_field_value("tax_exempt").and_then(|t| clean_boolean(&t));
+        let tax_exempt = get_field_value("tax_exempt").and_then(|t| clean_boolean(&t)).unwrap_or(false);
+        let tax_exempt_certificate: Option<String> = get_field_value("tax_exempt_certificate");
+        if tax_exempt && tax_exempt_certificate.is_none() {
+            tracing::warn!("Row {}: tax_exempt customer has no tax_exempt_certificate", row_num);
+        }
         
         let store_id: String = get_field_value("store_id").unwrap_or_else(|| "default-store".to_string());
         let _notes: Option<String> = get_field_value("notes");
         let now = chrono::Utc::now().to_rfc3339();
         
         let result = sqlx::query(
-            r"INSERT INTO customers (id, name, email, phone, address, city, state, zip, pricing_tier, store_id, tenant_id, created_at, updated_at)
-              VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            r"INSERT INTO customers (id, name, email, phone, address, city, state, zip, pricing_tier, tax_exempt, tax_exempt_certificate, store_id, tenant_id, created_at, updated_at)
+              VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
               ON CONFLICT(id) DO UPDATE SET 
                 name = excluded.name, email = excluded.email, phone = excluded.phone,
                 address = excluded.address, city = excluded.city, state = excluded.state, zip = excluded.zip,
-                pricing_tier = excluded.pricing_tier, updated_at = excluded.updated_at"
+                pricing_tier = excluded.pricing_tier, tax_exempt = excluded.tax_exempt,
+                tax_exempt_certificate = excluded.tax_exempt_certificate, updated_at = excluded.updated_at"
         )
         .bind(&id)
         .bind(&name)
@@ -1330,6 +1356,8 @@ async fn import_customers(
         .bind(&state)
         .bind(&zip)
         .bind(&pricing_tier)
+        .bind(tax_exempt)
+        .bind(&tax_exempt_certificate)
         .bind(&store_id)
         .bind(tenant_id)
         .bind(&now)
@@ -15,6 +15,9 @@ use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 
 use crate::models::errors::ApiError;
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_IMPORT, SOURCE_DATA_BATCH,
+};
 
 // ============================================================================
 // Request/Response Types
@@ -379,7 +382,10 @@ async fn seed_demo_products(
         .execute(pool)
         .await;
         
-        if result.is_ok() {
+        if let Ok(done) = result {
+            if done.rows_affected() > 0 {
+                import_stock_level(pool, tenant_id, batch_id, &sku, 100.0).await;
+            }
             created += 1;
         }
     }
@@ -387,6 +393,29 @@ async fn seed_demo_products(
     Ok(created)
 }
 
+/// Record the stock of a seeded or imported product in the inventory ledger
+///
+/// The product row already carries the quantity; the ledger gets the
+/// difference to it. Failures are logged, like failed rows are skipped.
+async fn import_stock_level(pool: &SqlitePool, tenant_id: &str, batch_id: &str, sku: &str, quantity: f64) {
+    let result: Result<_, LedgerError> = async {
+        let mut conn = pool.acquire().await?;
+        let product_id: String = sqlx::query_scalar("SELECT id FROM products WHERE tenant_id = ? AND sku = ?")
+            .bind(tenant_id)
+            .bind(sku)
+            .fetch_one(&mut *conn)
+            .await?;
+        let movement = StockMovement::new(tenant_id, product_id, MovementType::Adjustment, 0.0, REASON_IMPORT)
+            .with_source(SOURCE_DATA_BATCH, batch_id);
+        inventory_ledger_service::set_on_hand(&mut conn, movement, quantity).await
+    }
+    .await;
+    
+    if let Err(e) = result {
+        tracing::warn!("Could not record imported stock for SKU {}: {}", sku, e);
+    }
+}
+
 async fn seed_demo_customers(
     pool: &SqlitePool,
     tenant_id: &str,
@@ -467,6 +496,7 @@ async fn import_products_csv(
             .await;
             
             if result.is_ok() {
+                import_stock_level(pool, tenant_id, batch_id, sku, f64::from(quantity)).await;
                 imported += 1;
             }
         }
@@ -0,0 +1,203 @@
+/**
+ * Vehicle Fitment Handlers
+ *
+ * The parts counter's vehicle lookup:
+ * - Find the products that fit a year, make, model and engine
+ * - Offer the makes, models and engines on file for the pickers
+ * - List, add and remove a product's fitment, or import it in bulk from
+ *   ACES-style XML or CSV
+ * - Suggest the scheduled maintenance a vehicle is due for, and record the
+ *   services a work order carried out
+ *
+ * Changing fitment requires the access_warehouse permission.
+ */
+
+use actix_web::{delete, get, post, web, HttpResponse};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::models::errors::ApiError;
+use crate::models::fitment::{CreateFitmentRequest, VehicleQuery};
+use crate::models::UserContext;
+use crate::services::fitment_service::{FitmentImportFormat, VehicleOptionsQuery};
+use crate::services::FitmentService;
+
+// ============================================================================
+// Request Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ImportFitmentRequest {
+    pub format: FitmentImportFormat,
+    /// The XML or CSV document
+    pub data: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DueMaintenanceQuery {
+    /// Defaults to the latest reading on the vehicle's work orders
+    pub odometer: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordServicesRequest {
+    /// maintenance_schedules service types carried out
+    pub service_types: Vec<String>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Products that fit a vehicle
+///
+/// GET /api/fitment/lookup?year=&make=&model=&engine=&trim=
+#[get("/api/fitment/lookup")]
+pub async fn lookup_fitment(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<VehicleQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let products = FitmentService::new(pool.get_ref().clone())
+        .lookup(&context.tenant_id, &query)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "total": products.len(),
+        "products": products,
+    })))
+}
+
+/// Makes for a year, models for a make, or engines for a model
+///
+/// GET /api/fitment/options?year=&make=&model=
+#[get("/api/fitment/options")]
+pub async fn get_vehicle_options(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<VehicleOptionsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let options = FitmentService::new(pool.get_ref().clone())
+        .vehicle_options(&context.tenant_id, &query)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "options": options })))
+}
+
+/// GET /api/products/{id}/fitment
+#[get("/api/products/{id}/fitment")]
+pub async fn get_product_fitment(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let fitment = FitmentService::new(pool.get_ref().clone())
+        .for_product(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(fitment))
+}
+
+/// POST /api/products/{id}/fitment
+#[post("/api/products/{id}/fitment")]
+pub async fn add_product_fitment(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<CreateFitmentRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "access_warehouse")?;
+
+    let fitment = FitmentService::new(pool.get_ref().clone())
+        .add(&context.tenant_id, &path.into_inner(), &body)
+        .await?;
+
+    Ok(HttpResponse::Created().json(fitment))
+}
+
+/// DELETE /api/fitment/{id}
+#[delete("/api/fitment/{id}")]
+pub async fn delete_fitment(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "access_warehouse")?;
+
+    FitmentService::new(pool.get_ref().clone())
+        .delete(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Import fitment from ACES-style XML or CSV, matching parts by SKU
+///
+/// POST /api/fitment/import
+#[post("/api/fitment/import")]
+pub async fn import_fitment(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<ImportFitmentRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "access_warehouse")?;
+
+    let result = FitmentService::new(pool.get_ref().clone())
+        .import(&context.tenant_id, body.format, &body.data)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Scheduled maintenance the vehicle is due for
+///
+/// GET /api/vehicles/{id}/due-maintenance?odometer=
+#[get("/api/vehicles/{id}/due-maintenance")]
+pub async fn get_due_maintenance(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    query: web::Query<DueMaintenanceQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let due = FitmentService::new(pool.get_ref().clone())
+        .due_maintenance(&context.tenant_id, &path.into_inner(), query.odometer)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(due))
+}
+
+/// Record the scheduled services a work order carried out
+///
+/// POST /api/work-orders/{id}/services
+#[post("/api/work-orders/{id}/services")]
+pub async fn record_work_order_services(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<RecordServicesRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let recorded = FitmentService::new(pool.get_ref().clone())
+        .record_services(&context.tenant_id, &path.into_inner(), &body.service_types, &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Created().json(serde_json::json!({ "recorded": recorded })))
+}
+
+fn require_permission(context: &UserContext, permission: &str) -> Result<(), ApiError> {
+    if context.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(format!("This requires the {permission} permission")))
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(lookup_fitment)
+       .service(get_vehicle_options)
+       .service(get_product_fitment)
+       .service(add_product_fitment)
+       .service(delete_fitment)
+       .service(import_fitment)
+       .service(get_due_maintenance)
+       .service(record_work_order_services);
+}
@@ -3,6 +3,8 @@ use chrono::Utc;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use crate::services::stored_value_service::{self, StoredValueError};
+
 use crate::models::{
     GiftCard, GiftCardStatus, GiftCardTransactionType,
     IssueGiftCardRequest, RedeemGiftCardRequest, ReloadGiftCardRequest,
@@ -186,116 +188,54 @@ pub async fn redeem_gift_card(
         }
     };
 
-    // Fetch gift card
-    let card = match sqlx::query_as::<_, GiftCard>(
-        "SELECT id, card_number, initial_balance, current_balance, status, issued_date, 
-         expiry_date, customer_id 
-         FROM gift_cards 
-         WHERE card_number = ?",
+    // Debit the card (validates status, expiry and balance)
+    let debit = match stored_value_service::debit_gift_card(
+        &mut tx,
+        None,
+        &card_number,
+        req.amount,
+        req.reference_id.as_deref(),
     )
-    .bind(&card_number)
-    .fetch_one(&mut *tx)
     .await
     {
-        Ok(card) => card,
-        Err(sqlx::Error::RowNotFound) => {
+        Ok(debit) => debit,
+        Err(StoredValueError::GiftCardNotFound(_)) => {
             let _ = tx.rollback().await;
             return HttpResponse::NotFound().json(serde_json::json!({
                 "error": "Gift card not found"
             }));
         }
+        Err(StoredValueError::GiftCardInactive(status)) => {
+            let _ = tx.rollback().await;
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Gift card is {}", status),
+                "status": status
+            }));
+        }
+        Err(StoredValueError::GiftCardExpired) => {
+            let _ = tx.rollback().await;
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Gift card has expired"
+            }));
+        }
+        Err(StoredValueError::InsufficientBalance { available, requested }) => {
+            let _ = tx.rollback().await;
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Insufficient balance",
+                "current_balance": available,
+                "requested_amount": requested
+            }));
+        }
         Err(e) => {
-            tracing::error!("Failed to fetch gift card: {:?}", e);
+            tracing::error!("Failed to redeem gift card: {:?}", e);
             let _ = tx.rollback().await;
             return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch gift card"
+                "error": "Failed to redeem gift card"
             }));
         }
     };
-
-    // Check card status
-    if card.status != GiftCardStatus::Active.as_str() {
-        let _ = tx.rollback().await;
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": format!("Gift card is {}", card.status),
-            "status": card.status
-        }));
-    }
-
-    // Check expiry
-    if let Some(expiry_date) = &card.expiry_date {
-        if let Ok(expiry) = chrono::DateTime::parse_from_rfc3339(expiry_date) {
-            if Utc::now() > expiry {
-                let _ = tx.rollback().await;
-                return HttpResponse::BadRequest().json(serde_json::json!({
-                    "error": "Gift card has expired",
-                    "expiry_date": expiry_date
-                }));
-            }
-        }
-    }
-
-    // Check balance
-    if req.amount > card.current_balance {
-        let _ = tx.rollback().await;
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Insufficient balance",
-            "current_balance": card.current_balance,
-            "requested_amount": req.amount
-        }));
-    }
-
-    let new_balance = card.current_balance - req.amount;
-    let new_status = if new_balance <= 0.01 {
-        GiftCardStatus::Depleted.as_str()
-    } else {
-        GiftCardStatus::Active.as_str()
-    };
-
-    // Update gift card balance
-    let result = sqlx::query(
-        "UPDATE gift_cards 
-         SET current_balance = ?, status = ? 
-         WHERE id = ?",
-    )
-    .bind(new_balance)
-    .bind(new_status)
-    .bind(&card.id)
-    .execute(&mut *tx)
-    .await;
-
-    if let Err(e) = result {
-        tracing::error!("Failed to update gift card: {:?}", e);
-        let _ = tx.rollback().await;
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Failed to update gift card"
-        }));
-    }
-
-    // Record transaction
-    let transaction_id = Uuid::new_v4().to_string();
-    let now = Utc::now().to_rfc3339();
-    let result = sqlx::query(
-        "INSERT INTO gift_card_transactions (id, gift_card_id, transaction_type, amount, 
-         reference_id, created_at)
-         VALUES (?, ?, ?, ?, ?, ?)",
-    )
-    .bind(&transaction_id)
-    .bind(&card.id)
-    .bind(GiftCardTransactionType::Redeemed.as_str())
-    .bind(req.amount)
-    .bind(&req.reference_id)
-    .bind(&now)
-    .execute(&mut *tx)
-    .await;
-
-    if let Err(e) = result {
-        tracing::error!("Failed to record gift card transaction: {:?}", e);
-        let _ = tx.rollback().await;
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Failed to record gift card transaction"
-        }));
-    }
+    let new_balance = debit.new_balance;
+    let new_status = debit.status;
 
     // Commit transaction
     if let Err(e) = tx.commit().await {
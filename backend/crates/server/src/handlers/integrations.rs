@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use url::Url;
 
-use crate::models::ApiError;
+use crate::flows::square_import::{self, SquareImportFlow, SquareImportOptions};
+use crate::models::{ApiError, UserContext};
 use crate::services::{
     CredentialService,
     credential_service::{
@@ -13,6 +14,7 @@ use crate::services::{
         SupabaseCredentials,
         SquareCredentials,
         CloverCredentials,
+        ShopifyCredentials,
         StripeConnectCredentials,
     },
 };
@@ -24,6 +26,7 @@ use crate::connectors::{
     stripe::{StripeOAuth, StripeClient},
     square::SquareClient,
     clover::{CloverOAuth, CloverClient, CloverTokens},
+    shopify::{oauth::normalize_shop_domain, ShopifyOAuth, ShopifyClient, ShopifyTokens},
 };
 
 // ============================================================================
@@ -950,6 +953,105 @@ pub async fn get_square_logs(
     get_integration_logs(pool.get_ref(), &tenant_id, "square").await
 }
 
+/// Start importing the catalog, customers, stock and sales history from
+/// Square; the import runs in the background
+///
+/// POST /api/integrations/square/import
+pub async fn start_square_import(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+    context: web::ReqData<UserContext>,
+    payload: web::Json<SquareImportOptions>,
+) -> Result<HttpResponse, ApiError> {
+    let options = payload.into_inner();
+    if options.store_id.trim().is_empty() {
+        return Err(ApiError::validation_msg("store_id is required"));
+    }
+
+    let credential_service = CredentialService::new(pool.get_ref().clone())?;
+    let creds = credential_service.get_credentials(&tenant_id, "square").await?
+        .ok_or_else(|| ApiError::not_found("Square credentials not found"))?;
+
+    let square_creds = match creds {
+        PlatformCredentials::Square(c) => c,
+        _ => return Err(ApiError::internal("Invalid credential type")),
+    };
+
+    let client = SquareClient::new(crate::connectors::square::client::SquareCredentials {
+        access_token: square_creds.access_token,
+        location_id: square_creds.location_id,
+    })?;
+
+    let run_id = square_import::start_run(pool.get_ref(), &tenant_id, &context.user_id, &options)
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to start Square import: {}", e)))?;
+
+    log_integration_event(pool.get_ref(), &tenant_id, "square", "info", "import_started", "Square import started").await;
+
+    let pool = pool.get_ref().clone();
+    let tenant_id = tenant_id.into_inner();
+    let user_id = context.user_id.clone();
+    let background_run_id = run_id.clone();
+    tokio::spawn(async move {
+        let flow = SquareImportFlow::new(pool.clone(), client);
+        let result = flow.run(&tenant_id, &user_id, &background_run_id, &options).await;
+
+        if let Err(e) = square_import::finish_run(&pool, &background_run_id, &result).await {
+            tracing::error!("Failed to record end of Square import {}: {}", background_run_id, e);
+        }
+
+        match &result {
+            Ok(summary) => {
+                let message = format!(
+                    "Square import completed: {} products, {} customers, {} stock levels, {} orders imported; {} failed",
+                    summary.products.created + summary.products.updated,
+                    summary.customers.created + summary.customers.updated,
+                    summary.stock_levels.updated,
+                    summary.orders.created,
+                    summary.products.failed + summary.customers.failed
+                        + summary.stock_levels.failed + summary.orders.failed,
+                );
+                let level = if summary.errors.is_empty() { "info" } else { "warning" };
+                log_integration_event(&pool, &tenant_id, "square", level, "import_completed", &message).await;
+            }
+            Err(e) => {
+                log_integration_event(&pool, &tenant_id, "square", "error", "import_failed", e).await;
+            }
+        }
+    });
+
+    Ok(HttpResponse::Accepted().json(serde_json::json!({
+        "run_id": run_id,
+        "status": "running"
+    })))
+}
+
+/// GET /api/integrations/square/imports
+pub async fn list_square_imports(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+) -> Result<HttpResponse, ApiError> {
+    let runs = square_import::list_runs(pool.get_ref(), &tenant_id, 20)
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to list Square imports: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(runs))
+}
+
+/// GET /api/integrations/square/imports/{id}
+pub async fn get_square_import(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let run = square_import::get_run(pool.get_ref(), &tenant_id, &path.into_inner())
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to get Square import: {}", e)))?
+        .ok_or_else(|| ApiError::not_found("Square import not found"))?;
+
+    Ok(HttpResponse::Ok().json(run))
+}
+
 // ============================================================================
 // Clover Endpoints (OAuth)
 // ============================================================================
@@ -1169,6 +1271,299 @@ pub async fn get_clover_logs(
     get_integration_logs(pool.get_ref(), &tenant_id, "clover").await
 }
 
+// ============================================================================
+// Shopify Endpoints (OAuth app install)
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ShopifyAuthUrlRequest {
+    /// Shop to install on, e.g. `example` or `example.myshopify.com`
+    pub shop: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShopifyAuthUrlResponse {
+    pub auth_url: String,
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShopifyLocationRequest {
+    /// Location whose stock is synced; `None` for the shop's first active one
+    pub location_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShopifySummaryResponse {
+    pub shop_name: String,
+    pub shop_domain: String,
+    pub currency: String,
+    /// Location whose stock is synced, when one was chosen
+    pub location_id: Option<i64>,
+    pub locations: Vec<crate::connectors::shopify::inventory::ShopifyLocation>,
+}
+
+/// POST /api/integrations/shopify/auth-url
+pub async fn get_shopify_auth_url(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+    body: web::Json<ShopifyAuthUrlRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let oauth = ShopifyOAuth::from_env()?;
+    let shop = normalize_shop_domain(&body.shop)?;
+    
+    let state = uuid::Uuid::new_v4().to_string();
+    let state_id = uuid::Uuid::new_v4().to_string();
+    let expires_at = chrono::Utc::now() + chrono::Duration::minutes(5);
+    
+    sqlx::query(
+        "INSERT INTO oauth_states (id, tenant_id, platform, state, expires_at, created_at) 
+         VALUES (?, ?, ?, ?, ?, datetime('now'))"
+    )
+    .bind(&state_id)
+    .bind(tenant_id.to_string())
+    .bind("shopify")
+    .bind(&state)
+    .bind(expires_at.to_rfc3339())
+    .execute(pool.get_ref())
+    .await
+    .map_err(|e| ApiError::internal(format!("Failed to store OAuth state: {}", e)))?;
+    
+    let auth_url = oauth.get_authorization_url(&shop, &state);
+    
+    Ok(HttpResponse::Ok().json(ShopifyAuthUrlResponse {
+        auth_url,
+        state,
+    }))
+}
+
+/// GET /api/integrations/shopify/callback
+///
+/// Shopify signs the whole query, so every parameter is kept for the check.
+pub async fn shopify_oauth_callback(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+    query: web::Query<Vec<(String, String)>>,
+) -> Result<HttpResponse, ApiError> {
+    let oauth = ShopifyOAuth::from_env()?;
+    if !oauth.verify_callback(&query) {
+        return Err(ApiError::unauthorized("Invalid Shopify callback signature"));
+    }
+    
+    let param = |name: &str| {
+        query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| ApiError::bad_request(format!("Missing {} parameter", name)))
+    };
+    let state = param("state")?;
+    let code = param("code")?;
+    let shop = normalize_shop_domain(&param("shop")?)?;
+    
+    // Validate state
+    let state_result = sqlx::query_as::<_, (String, String)>(
+        "SELECT id, expires_at FROM oauth_states 
+         WHERE state = ? AND tenant_id = ? AND platform = 'shopify'"
+    )
+    .bind(&state)
+    .bind(tenant_id.to_string())
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(|e| ApiError::internal(format!("Failed to validate OAuth state: {}", e)))?;
+    
+    let (state_id, expires_at) = match state_result {
+        Some((id, exp)) => (id, exp),
+        None => {
+            return Err(ApiError::unauthorized("Invalid OAuth state"));
+        }
+    };
+    
+    let expires_at_dt = chrono::DateTime::parse_from_rfc3339(&expires_at)
+        .map_err(|e| ApiError::internal(format!("Failed to parse expiry: {}", e)))?;
+    
+    if chrono::Utc::now() > expires_at_dt {
+        let _ = sqlx::query("DELETE FROM oauth_states WHERE id = ?")
+            .bind(&state_id)
+            .execute(pool.get_ref())
+            .await;
+        return Err(ApiError::unauthorized("OAuth state expired"));
+    }
+    
+    sqlx::query("DELETE FROM oauth_states WHERE id = ?")
+        .bind(&state_id)
+        .execute(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to delete OAuth state: {}", e)))?;
+    
+    // Exchange code for an offline token
+    let tokens = oauth.exchange_code_for_tokens(&shop, &code).await?;
+    
+    // Store credentials
+    let credential_service = CredentialService::new(pool.get_ref().clone())?;
+    let credentials = PlatformCredentials::Shopify(ShopifyCredentials {
+        shop_domain: tokens.shop_domain.clone(),
+        access_token: tokens.access_token.clone(),
+        location_id: None,
+    });
+    credential_service.store_credentials(&tenant_id, credentials).await?;
+    
+    let _ = update_connection_status(pool.get_ref(), &tenant_id, "shopify", true, None).await;
+    log_integration_event(
+        pool.get_ref(),
+        &tenant_id,
+        "shopify",
+        "info",
+        "connected",
+        &format!("Shopify shop {} connected", tokens.shop_domain),
+    ).await;
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "message": "Shopify connected successfully",
+        "shop_domain": tokens.shop_domain,
+    })))
+}
+
+/// GET /api/integrations/shopify/status
+pub async fn get_shopify_status(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+) -> Result<HttpResponse, ApiError> {
+    let status = get_connection_status(pool.get_ref(), &tenant_id, "shopify").await?;
+    Ok(HttpResponse::Ok().json(status))
+}
+
+/// GET /api/integrations/shopify/summary
+pub async fn get_shopify_summary(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+) -> Result<HttpResponse, ApiError> {
+    let shopify_creds = load_shopify_credentials(pool.get_ref(), &tenant_id).await?;
+    let location_id = shopify_creds.location_id;
+    
+    let client = ShopifyClient::new(ShopifyTokens {
+        access_token: shopify_creds.access_token,
+        shop_domain: shopify_creds.shop_domain,
+        scope: None,
+    })?;
+    let summary = client.get_shop_summary().await?;
+    let locations = client.get_locations().await?;
+    
+    Ok(HttpResponse::Ok().json(ShopifySummaryResponse {
+        shop_name: summary.shop_name,
+        shop_domain: summary.shop_domain,
+        currency: summary.currency,
+        location_id,
+        locations,
+    }))
+}
+
+/// POST /api/integrations/shopify/test
+pub async fn test_shopify_connection(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+) -> Result<HttpResponse, ApiError> {
+    let shopify_creds = load_shopify_credentials(pool.get_ref(), &tenant_id).await?;
+    
+    let client = ShopifyClient::new(ShopifyTokens {
+        access_token: shopify_creds.access_token,
+        shop_domain: shopify_creds.shop_domain,
+        scope: None,
+    })?;
+    
+    match client.test_connection().await {
+        Ok(true) => {
+            let _ = update_connection_status(pool.get_ref(), &tenant_id, "shopify", true, None).await;
+            Ok(HttpResponse::Ok().json(TestConnectionResponse {
+                success: true,
+                message: "Shopify connection successful".to_string(),
+                details: None,
+            }))
+        }
+        Ok(false) | Err(_) => {
+            let error_msg = "Connection test failed";
+            let _ = update_connection_status(pool.get_ref(), &tenant_id, "shopify", false, Some(error_msg)).await;
+            Ok(HttpResponse::Ok().json(TestConnectionResponse {
+                success: false,
+                message: error_msg.to_string(),
+                details: None,
+            }))
+        }
+    }
+}
+
+/// PUT /api/integrations/shopify/location
+/// Choose the Shopify location whose stock is synced
+pub async fn set_shopify_location(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+    body: web::Json<ShopifyLocationRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let mut shopify_creds = load_shopify_credentials(pool.get_ref(), &tenant_id).await?;
+    
+    if let Some(location_id) = body.location_id {
+        let client = ShopifyClient::new(ShopifyTokens {
+            access_token: shopify_creds.access_token.clone(),
+            shop_domain: shopify_creds.shop_domain.clone(),
+            scope: None,
+        })?;
+        if !client.get_locations().await?.iter().any(|l| l.id == location_id) {
+            return Err(ApiError::bad_request(format!(
+                "Location {} is not an active location of the shop",
+                location_id
+            )));
+        }
+    }
+    
+    shopify_creds.location_id = body.location_id;
+    let credential_service = CredentialService::new(pool.get_ref().clone())?;
+    credential_service
+        .store_credentials(&tenant_id, PlatformCredentials::Shopify(shopify_creds))
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "location_id": body.location_id,
+    })))
+}
+
+/// DELETE /api/integrations/shopify/disconnect
+pub async fn disconnect_shopify(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+) -> Result<HttpResponse, ApiError> {
+    let credential_service = CredentialService::new(pool.get_ref().clone())?;
+    credential_service.delete_credentials(&tenant_id, "shopify").await?;
+    
+    log_integration_event(pool.get_ref(), &tenant_id, "shopify", "info", "disconnected", "Shopify disconnected").await;
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "message": "Shopify disconnected successfully"
+    })))
+}
+
+/// GET /api/integrations/shopify/logs
+pub async fn get_shopify_logs(
+    pool: web::Data<SqlitePool>,
+    tenant_id: web::ReqData<String>,
+) -> Result<HttpResponse, ApiError> {
+    get_integration_logs(pool.get_ref(), &tenant_id, "shopify").await
+}
+
+async fn load_shopify_credentials(pool: &SqlitePool, tenant_id: &str) -> Result<ShopifyCredentials, ApiError> {
+    let credential_service = CredentialService::new(pool.clone())?;
+    
+    let creds = credential_service.get_credentials(tenant_id, "shopify").await?
+        .ok_or_else(|| ApiError::not_found("Shopify credentials not found"))?;
+    
+    match creds {
+        PlatformCredentials::Shopify(c) => Ok(c),
+        _ => Err(ApiError::internal("Invalid credential type")),
+    }
+}
+
 // ============================================================================
 // General Endpoints
 // ============================================================================
@@ -1179,7 +1574,7 @@ pub async fn get_all_connections(
     pool: web::Data<SqlitePool>,
     tenant_id: web::ReqData<String>,
 ) -> Result<HttpResponse, ApiError> {
-    let platforms = vec!["woocommerce", "quickbooks", "supabase", "stripe", "square", "clover"];
+    let platforms = vec!["woocommerce", "quickbooks", "supabase", "stripe", "square", "clover", "shopify"];
     let mut connections = Vec::new();
     
     for platform in platforms {
@@ -1373,6 +1768,9 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .route("/square/test", web::post().to(test_square_connection))
             .route("/square/disconnect", web::delete().to(disconnect_square))
             .route("/square/logs", web::get().to(get_square_logs))
+            .route("/square/import", web::post().to(start_square_import))
+            .route("/square/imports", web::get().to(list_square_imports))
+            .route("/square/imports/{id}", web::get().to(get_square_import))
             // Clover routes (OAuth)
             .route("/clover/auth-url", web::post().to(get_clover_auth_url))
             .route("/clover/callback", web::get().to(clover_oauth_callback))
@@ -1381,6 +1779,15 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .route("/clover/test", web::post().to(test_clover_connection))
             .route("/clover/disconnect", web::delete().to(disconnect_clover))
             .route("/clover/logs", web::get().to(get_clover_logs))
+            // Shopify routes
+            .route("/shopify/auth-url", web::post().to(get_shopify_auth_url))
+            .route("/shopify/callback", web::get().to(shopify_oauth_callback))
+            .route("/shopify/status", web::get().to(get_shopify_status))
+            .route("/shopify/summary", web::get().to(get_shopify_summary))
+            .route("/shopify/test", web::post().to(test_shopify_connection))
+            .route("/shopify/location", web::put().to(set_shopify_location))
+            .route("/shopify/disconnect", web::delete().to(disconnect_shopify))
+            .route("/shopify/logs", web::get().to(get_shopify_logs))
             // General routes
             .route("/connections", web::get().to(get_all_connections))
     );
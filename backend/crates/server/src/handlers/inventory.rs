@@ -1,8 +1,17 @@
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{get, post, put, web, HttpResponse, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::costing_service::CostingMethod;
+use crate::services::inventory_ledger_service::{
+    LocationStock, MovementFilter, MovementType, SELECT_LOCATION_STOCK,
+};
+use crate::services::{CostingService, InventoryLedgerService};
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct InventoryItem {
     pub id: String,
     pub name: String,
@@ -11,38 +20,328 @@ pub struct InventoryItem {
     pub stock: f64,
     pub min_stock: f64,
     pub location: String,
-    pub last_received: String,
+    /// Time of the last receipt in the inventory ledger
+    pub last_received: Option<String>,
     pub status: String,
+    /// Stock at each store, with what is in transit to it
+    #[sqlx(skip)]
+    pub locations: Vec<LocationStock>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MovementsQuery {
+    pub product_id: Option<String>,
+    pub store_id: Option<String>,
+    pub movement_type: Option<MovementType>,
+    /// Sale, return, vendor bill, ... the movements came from
+    pub source_id: Option<String>,
+    /// RFC 3339 bounds, inclusive
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnHandQuery {
+    /// RFC 3339; defaults to now
+    pub as_of: Option<String>,
+    /// One store's stock; defaults to all stores
+    pub store_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValuationQuery {
+    /// RFC 3339; defaults to now
+    pub as_of: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CostLayersQuery {
+    /// Include layers that have been used up
+    #[serde(default)]
+    pub all: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CostingMethodRequest {
+    pub costing_method: CostingMethod,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateLocationRequest {
+    /// Bin, aisle or shelf within the store
+    pub bin_location: Option<String>,
+    pub reorder_point: Option<f64>,
 }
 
 pub async fn get_inventory_items(
     pool: web::Data<SqlitePool>,
 ) -> Result<HttpResponse> {
-    let items = sqlx::query_as!(
-        InventoryItem,
+    let mut items = sqlx::query_as::<_, InventoryItem>(
         r#"
-        SELECT 
-            id as "id!",
-            name as "name!",
-            sku as "sku!",
-            category as "category!",
-            quantity_on_hand as "stock!",
-            COALESCE(reorder_point, 0.0) as "min_stock!",
-            'Main Warehouse' as "location!",
-            datetime('now') as "last_received!",
-            CASE 
-                WHEN quantity_on_hand > COALESCE(reorder_point, 0.0) * 2 THEN 'in-stock'
-                WHEN quantity_on_hand > COALESCE(reorder_point, 0.0) THEN 'low-stock'
+        SELECT
+            p.id,
+            p.name,
+            p.sku,
+            p.category,
+            p.quantity_on_hand as stock,
+            COALESCE(p.reorder_point, 0.0) as min_stock,
+            COALESCE(s.name, p.store_id) as location,
+            (
+                SELECT MAX(m.created_at) FROM inventory_movements m
+                WHERE m.product_id = p.id AND m.movement_type = 'receipt'
+            ) as last_received,
+            CASE
+                WHEN p.quantity_on_hand > COALESCE(p.reorder_point, 0.0) * 2 THEN 'in-stock'
+                WHEN p.quantity_on_hand > COALESCE(p.reorder_point, 0.0) THEN 'low-stock'
                 ELSE 'out-of-stock'
-            END as "status!"
-        FROM products
-        WHERE is_active = 1
-        ORDER BY name
+            END as status
+        FROM products p
+        LEFT JOIN stores s ON s.id = p.store_id
+        WHERE p.is_active = 1
+        ORDER BY p.name
         "#
     )
     .fetch_all(pool.get_ref())
     .await
     .unwrap_or_default();
 
+    let locations = sqlx::query_as::<_, LocationStock>(&format!(
+        "{SELECT_LOCATION_STOCK} ORDER BY l.product_id, l.store_id"
+    ))
+    .fetch_all(pool.get_ref())
+    .await
+    .unwrap_or_default();
+
+    for location in locations {
+        if let Some(item) = items.iter_mut().find(|item| item.id == location.product_id) {
+            item.locations.push(location);
+        }
+    }
+
     Ok(HttpResponse::Ok().json(items))
 }
+
+/// Stock movements from the inventory ledger, newest first
+///
+/// GET /api/inventory/movements
+#[get("/api/inventory/movements")]
+pub async fn list_movements(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<MovementsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let filter = MovementFilter {
+        product_id: query.product_id,
+        store_id: query.store_id,
+        movement_type: query.movement_type,
+        source_id: query.source_id,
+        from: query.from.as_deref().map(|from| timestamp(from, "from")).transpose()?,
+        to: query.to.as_deref().map(|to| timestamp(to, "to")).transpose()?,
+        limit: query.limit.unwrap_or(100).clamp(1, 1000),
+    };
+
+    let movements = InventoryLedgerService::new(pool.get_ref().clone())
+        .movements(&context.tenant_id, &filter)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "movements": movements,
+        "total": movements.len(),
+    })))
+}
+
+/// A product's stock at a point in time, summed from the ledger
+///
+/// GET /api/inventory/products/{id}/on-hand
+#[get("/api/inventory/products/{id}/on-hand")]
+pub async fn get_on_hand(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    query: web::Query<OnHandQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let product_id = path.into_inner();
+    let as_of = match query.as_of.as_deref() {
+        Some(as_of) => timestamp(as_of, "as_of")?,
+        None => Utc::now().to_rfc3339(),
+    };
+
+    let quantity = InventoryLedgerService::new(pool.get_ref().clone())
+        .on_hand_as_of(&context.tenant_id, &product_id, query.store_id.as_deref(), &as_of)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "product_id": product_id,
+        "store_id": query.store_id,
+        "as_of": as_of,
+        "quantity_on_hand": quantity,
+    })))
+}
+
+/// A product's stock at each store
+///
+/// GET /api/inventory/products/{id}/locations
+#[get("/api/inventory/products/{id}/locations")]
+pub async fn get_locations(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let product_id = path.into_inner();
+
+    let locations = InventoryLedgerService::new(pool.get_ref().clone())
+        .locations(&context.tenant_id, &product_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "product_id": product_id,
+        "locations": locations,
+    })))
+}
+
+/// Set where a product sits in a store and the store's reorder point
+///
+/// PUT /api/inventory/products/{id}/locations/{store_id}
+#[put("/api/inventory/products/{id}/locations/{store_id}")]
+pub async fn update_location(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<(String, String)>,
+    body: web::Json<UpdateLocationRequest>,
+) -> Result<HttpResponse, ApiError> {
+    if !context.has_permission("adjust_inventory") {
+        return Err(ApiError::forbidden("Changing stock locations requires the adjust_inventory permission"));
+    }
+    let (product_id, store_id) = path.into_inner();
+
+    let location = InventoryLedgerService::new(pool.get_ref().clone())
+        .set_bin_location(
+            &context.tenant_id,
+            &product_id,
+            &store_id,
+            body.bin_location.as_deref(),
+            body.reorder_point,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(location))
+}
+
+/// Recompute cached stock levels from the ledger
+///
+/// POST /api/inventory/rebuild
+#[post("/api/inventory/rebuild")]
+pub async fn rebuild_on_hand(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+) -> Result<HttpResponse, ApiError> {
+    if !context.has_permission("adjust_inventory") {
+        return Err(ApiError::forbidden("Rebuilding stock levels requires the adjust_inventory permission"));
+    }
+
+    let corrections = InventoryLedgerService::new(pool.get_ref().clone())
+        .rebuild_on_hand(&context.tenant_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "corrected": corrections.len(),
+        "corrections": corrections,
+    })))
+}
+
+/// Stock value per product at a point in time, from the cost entries
+///
+/// GET /api/inventory/valuation
+#[get("/api/inventory/valuation")]
+pub async fn get_valuation(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<ValuationQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let as_of = match query.as_of.as_deref() {
+        Some(as_of) => timestamp(as_of, "as_of")?,
+        None => Utc::now().to_rfc3339(),
+    };
+
+    let report = CostingService::new(pool.get_ref().clone())
+        .valuation(&context.tenant_id, &as_of)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// A product's cost layers, oldest first
+///
+/// GET /api/inventory/products/{id}/cost-layers
+#[get("/api/inventory/products/{id}/cost-layers")]
+pub async fn get_cost_layers(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    query: web::Query<CostLayersQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let product_id = path.into_inner();
+
+    let layers = CostingService::new(pool.get_ref().clone())
+        .layers(&context.tenant_id, &product_id, query.all)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "product_id": product_id,
+        "layers": layers,
+    })))
+}
+
+/// GET /api/inventory/costing-method
+#[get("/api/inventory/costing-method")]
+pub async fn get_costing_method(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+) -> Result<HttpResponse, ApiError> {
+    let method = CostingService::new(pool.get_ref().clone())
+        .method(&context.tenant_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "costing_method": method })))
+}
+
+/// Switch between FIFO and weighted average; applies to movements from now on
+///
+/// PUT /api/inventory/costing-method
+#[put("/api/inventory/costing-method")]
+pub async fn update_costing_method(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<CostingMethodRequest>,
+) -> Result<HttpResponse, ApiError> {
+    if !context.has_permission("manage_settings") {
+        return Err(ApiError::forbidden("Changing the costing method requires the manage_settings permission"));
+    }
+
+    let method = CostingService::new(pool.get_ref().clone())
+        .set_method(&context.tenant_id, body.costing_method)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "costing_method": method })))
+}
+
+/// Normalize an RFC 3339 timestamp to UTC, as stored in the ledger
+fn timestamp(value: &str, field: &str) -> Result<String, ApiError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|at| at.with_timezone(&Utc).to_rfc3339())
+        .map_err(|_| ApiError::bad_request(format!("{field} must be an RFC 3339 timestamp")))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_movements)
+       .service(get_on_hand)
+       .service(get_locations)
+       .service(update_location)
+       .service(rebuild_on_hand)
+       .service(get_valuation)
+       .service(get_cost_layers)
+       .service(get_costing_method)
+       .service(update_costing_method);
+}
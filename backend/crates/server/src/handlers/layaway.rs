@@ -8,6 +8,7 @@ use crate::models::{
     CreateLayawayPaymentRequest, CreateLayawayRequest, Layaway, LayawayItem, LayawayPayment,
     LayawayResponse, LayawayStatus,
 };
+use crate::services::outbound_webhook_service::{self, WebhookEventType};
 
 /// POST /api/layaways
 /// Create a new layaway
@@ -523,23 +524,8 @@ pub async fn check_overdue_layaways(pool: web::Data<SqlitePool>) -> impl Respond
 
     let now = Utc::now().to_rfc3339();
 
-    // Update overdue layaways
-    let result = sqlx::query(
-        "UPDATE layaways 
-         SET status = ?, updated_at = ?, sync_version = sync_version + 1
-         WHERE status = ? AND due_date IS NOT NULL AND due_date < ? AND tenant_id = ?",
-    )
-    .bind(LayawayStatus::Overdue.as_str())
-    .bind(&now)
-    .bind(LayawayStatus::Active.as_str())
-    .bind(&now)
-    .bind(&get_current_tenant_id())
-    .execute(pool.get_ref())
-    .await;
-
-    match result {
-        Ok(rows) => {
-            let count = rows.rows_affected();
+    match flag_overdue_layaways(pool.get_ref(), &get_current_tenant_id(), &now).await {
+        Ok(count) => {
             tracing::info!("Flagged {} layaways as overdue", count);
             HttpResponse::Ok().json(serde_json::json!({
                 "message": format!("Flagged {} layaways as overdue", count),
@@ -555,6 +541,49 @@ pub async fn check_overdue_layaways(pool: web::Data<SqlitePool>) -> impl Respond
     }
 }
 
+/// Flag active layaways past their due date as overdue, publishing a
+/// `layaway.overdue` webhook event for each
+async fn flag_overdue_layaways(pool: &SqlitePool, tenant_id: &str, now: &str) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let flagged = sqlx::query_as::<_, Layaway>(
+        "UPDATE layaways 
+         SET status = ?, updated_at = ?, sync_version = sync_version + 1
+         WHERE status = ? AND due_date IS NOT NULL AND due_date < ? AND tenant_id = ?
+         RETURNING id, tenant_id, customer_id, status, total_amount, deposit_amount, balance_due, 
+         due_date, created_at, updated_at, completed_at, sync_version, store_id",
+    )
+    .bind(LayawayStatus::Overdue.as_str())
+    .bind(now)
+    .bind(LayawayStatus::Active.as_str())
+    .bind(now)
+    .bind(tenant_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for layaway in &flagged {
+        outbound_webhook_service::publish(
+            &mut tx,
+            tenant_id,
+            WebhookEventType::LayawayOverdue,
+            serde_json::json!({
+                "layaway_id": layaway.id,
+                "customer_id": layaway.customer_id,
+                "store_id": layaway.store_id,
+                "total_amount": layaway.total_amount,
+                "deposit_amount": layaway.deposit_amount,
+                "balance_due": layaway.balance_due,
+                "due_date": layaway.due_date,
+            }),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(flagged.len())
+}
+
 /// GET /api/layaways/overdue
 /// Get all overdue layaways
 #[get("/api/layaways/overdue")]
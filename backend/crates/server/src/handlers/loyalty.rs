@@ -3,7 +3,9 @@ use chrono::Utc;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use crate::middleware::get_current_tenant_id;
 use crate::models::{LoyaltyTransaction, LoyaltyTransactionType, PriceLevel, RedeemPointsRequest};
+use crate::services::stored_value_service::{self, StoredValueError};
 
 /// GET /api/customers/:id/loyalty
 /// Get loyalty balance for a customer
@@ -546,40 +548,6 @@ pub async fn redeem_store_credit(
         reference_id
     );
 
-    // Get current store credit balance
-    let result = sqlx::query_as::<_, (f64,)>(
-        "SELECT store_credit FROM customers WHERE id = ?"
-    )
-    .bind(&customer_id)
-    .fetch_one(pool.get_ref())
-    .await;
-
-    let current_balance = match result {
-        Ok((balance,)) => balance,
-        Err(sqlx::Error::RowNotFound) => {
-            return HttpResponse::NotFound().json(serde_json::json!({
-                "error": "Customer not found"
-            }));
-        }
-        Err(e) => {
-            tracing::error!("Failed to fetch customer: {:?}", e);
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch customer"
-            }));
-        }
-    };
-
-    // Check if customer has enough store credit
-    if current_balance < amount {
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Insufficient store credit",
-            "current_balance": current_balance,
-            "requested_amount": amount
-        }));
-    }
-
-    let now = Utc::now().to_rfc3339();
-
     // Start transaction
     let mut tx = match pool.begin().await {
         Ok(tx) => tx,
@@ -591,50 +559,40 @@ pub async fn redeem_store_credit(
         }
     };
 
-    // Update customer store credit balance
-    let result = sqlx::query(
-        "UPDATE customers 
-         SET store_credit = store_credit - ?, updated_at = ?, sync_version = sync_version + 1 
-         WHERE id = ?",
-    )
-    .bind(amount)
-    .bind(&now)
-    .bind(&customer_id)
-    .execute(&mut *tx)
-    .await;
-
-    if let Err(e) = result {
-        tracing::error!("Failed to update store credit: {:?}", e);
-        let _ = tx.rollback().await;
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Failed to update store credit"
-        }));
-    }
-
-    // Record loyalty transaction for audit trail
-    let transaction_id = Uuid::new_v4().to_string();
-    let result = sqlx::query(
-        "INSERT INTO loyalty_transactions (id, customer_id, transaction_type, points, amount, 
-         reference_id, created_at, employee_id)
-         VALUES (?, ?, ?, NULL, ?, ?, ?, ?)",
+    // Debit store credit (checks the customer has enough balance)
+    let debit = match stored_value_service::debit_store_credit(
+        &mut tx,
+        &get_current_tenant_id(),
+        &customer_id,
+        amount,
+        reference_id,
+        employee_id,
     )
-    .bind(&transaction_id)
-    .bind(&customer_id)
-    .bind("StoreCreditRedeemed")
-    .bind(-amount) // Negative for redemption
-    .bind(reference_id)
-    .bind(&now)
-    .bind(employee_id)
-    .execute(&mut *tx)
-    .await;
-
-    if let Err(e) = result {
-        tracing::error!("Failed to record store credit redemption: {:?}", e);
-        let _ = tx.rollback().await;
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Failed to record transaction"
-        }));
-    }
+    .await
+    {
+        Ok(debit) => debit,
+        Err(StoredValueError::CustomerNotFound(_)) => {
+            let _ = tx.rollback().await;
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Customer not found"
+            }));
+        }
+        Err(StoredValueError::InsufficientBalance { available, requested }) => {
+            let _ = tx.rollback().await;
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Insufficient store credit",
+                "current_balance": available,
+                "requested_amount": requested
+            }));
+        }
+        Err(e) => {
+            tracing::error!("Failed to redeem store credit: {:?}", e);
+            let _ = tx.rollback().await;
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update store credit"
+            }));
+        }
+    };
 
     // Commit transaction
     if let Err(e) = tx.commit().await {
@@ -648,13 +606,13 @@ pub async fn redeem_store_credit(
         "Redeemed ${:.2} store credit for customer {}. New balance: ${:.2}",
         amount,
         customer_id,
-        current_balance - amount
+        debit.new_balance
     );
 
     HttpResponse::Ok().json(serde_json::json!({
         "message": "Store credit redeemed successfully",
         "amount_redeemed": amount,
-        "new_balance": current_balance - amount
+        "new_balance": debit.new_balance
     }))
 }
 
@@ -22,6 +22,7 @@ pub mod commission;
 pub mod company;
 pub mod config;
 pub mod conflicts;
+pub mod counts;
 pub mod credit;
 pub mod customer;
 pub mod customers;
@@ -37,6 +38,7 @@ pub mod payments;
 pub mod feature_flags;
 pub mod fresh_install;
 pub mod files;
+pub mod fitment;
 pub mod gift_card;
 pub mod google_drive_oauth;
 pub mod health;
@@ -46,10 +48,13 @@ pub mod inventory;
 pub mod layaway;
 pub mod loyalty;
 pub mod mappings;
+pub mod outbound_webhooks;
+pub mod overrides;
 pub mod product;
 pub mod product_advanced;
 pub mod products;
 pub mod promotion;
+pub mod purchase_orders;
 pub mod quickbooks;
 pub mod quickbooks_crud;
 pub mod quickbooks_transform;
@@ -59,6 +64,7 @@ pub mod quickbooks_vendor;
 pub mod quickbooks_bill;
 pub mod quickbooks_refund;
 pub mod reporting;
+pub mod roles;
 pub mod sales;
 pub mod search_operations;
 pub mod session_management;
@@ -66,12 +72,17 @@ pub mod settings;
 pub mod settings_handlers;
 pub mod settings_crud;
 pub mod setup;
+pub mod shifts;
 pub mod stats;
 pub mod stores;
+pub mod suspended_sales;
 pub mod sync;
 pub mod sync_config;
 pub mod sync_operations;
 pub mod sync_history;
+pub mod tax;
+pub mod transfers;
+pub mod two_factor;
 pub mod units;
 pub mod unit_conversion;
 pub mod user_handlers;
@@ -0,0 +1,208 @@
+/**
+ * Outbound Webhook Handlers
+ *
+ * Subscriptions of other systems to EasySale events:
+ * - The event types that can be subscribed to
+ * - Subscriptions: create (returns the signing secret), edit, pause,
+ *   delete and rotate the secret
+ * - A subscription's delivery log, one delivery with its attempts, and
+ *   sending a delivery again
+ *
+ * Inbound webhooks from other platforms are in `handlers::webhooks`.
+ */
+
+use actix_web::{delete, get, post, put, web, HttpResponse};
+use sqlx::SqlitePool;
+
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::credential_service::CredentialService;
+use crate::services::outbound_webhook_service::{
+    CreateSubscriptionRequest, DeliveryQuery, UpdateSubscriptionRequest,
+};
+use crate::services::OutboundWebhookService;
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /api/admin/webhooks/events
+#[get("/api/admin/webhooks/events")]
+pub async fn list_webhook_event_types(context: web::ReqData<UserContext>) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    Ok(HttpResponse::Ok().json(OutboundWebhookService::event_types()))
+}
+
+/// GET /api/admin/webhooks
+#[get("/api/admin/webhooks")]
+pub async fn list_webhooks(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    let subscriptions = service(&pool)?.list_subscriptions(&context.tenant_id).await?;
+
+    Ok(HttpResponse::Ok().json(subscriptions))
+}
+
+/// Register an endpoint; the signing secret is only in this response
+///
+/// POST /api/admin/webhooks
+#[post("/api/admin/webhooks")]
+pub async fn create_webhook(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<CreateSubscriptionRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    let created = service(&pool)?
+        .create_subscription(&context.tenant_id, &context.user_id, body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created().json(created))
+}
+
+/// GET /api/admin/webhooks/{id}
+#[get("/api/admin/webhooks/{id}")]
+pub async fn get_webhook(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    let subscription = service(&pool)?
+        .get_subscription(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(subscription))
+}
+
+/// PUT /api/admin/webhooks/{id}
+#[put("/api/admin/webhooks/{id}")]
+pub async fn update_webhook(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<UpdateSubscriptionRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    let subscription = service(&pool)?
+        .update_subscription(&context.tenant_id, &path.into_inner(), body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(subscription))
+}
+
+/// DELETE /api/admin/webhooks/{id}
+#[delete("/api/admin/webhooks/{id}")]
+pub async fn delete_webhook(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    service(&pool)?
+        .delete_subscription(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/admin/webhooks/{id}/rotate-secret
+#[post("/api/admin/webhooks/{id}/rotate-secret")]
+pub async fn rotate_webhook_secret(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    let rotated = service(&pool)?
+        .rotate_secret(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(rotated))
+}
+
+/// GET /api/admin/webhooks/{id}/deliveries
+#[get("/api/admin/webhooks/{id}/deliveries")]
+pub async fn list_webhook_deliveries(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    query: web::Query<DeliveryQuery>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    let deliveries = service(&pool)?
+        .list_deliveries(&context.tenant_id, &path.into_inner(), &query)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(deliveries))
+}
+
+/// GET /api/admin/webhook-deliveries/{id}
+#[get("/api/admin/webhook-deliveries/{id}")]
+pub async fn get_webhook_delivery(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    let delivery = service(&pool)?
+        .get_delivery(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(delivery))
+}
+
+/// Send a delivery again now; the response has the new attempt
+///
+/// POST /api/admin/webhook-deliveries/{id}/redeliver
+#[post("/api/admin/webhook-deliveries/{id}/redeliver")]
+pub async fn redeliver_webhook(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_settings")?;
+
+    let delivery = service(&pool)?
+        .redeliver(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(delivery))
+}
+
+fn service(pool: &SqlitePool) -> Result<OutboundWebhookService, ApiError> {
+    Ok(OutboundWebhookService::new(pool.clone(), CredentialService::new(pool.clone())?))
+}
+
+fn require_permission(context: &UserContext, permission: &str) -> Result<(), ApiError> {
+    if context.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(format!("This requires the {permission} permission")))
+    }
+}
+
+/// `/events` is registered before `/{id}` so it is not taken for an id
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_webhook_event_types)
+       .service(list_webhooks)
+       .service(create_webhook)
+       .service(get_webhook)
+       .service(update_webhook)
+       .service(delete_webhook)
+       .service(rotate_webhook_secret)
+       .service(list_webhook_deliveries)
+       .service(get_webhook_delivery)
+       .service(redeliver_webhook);
+}
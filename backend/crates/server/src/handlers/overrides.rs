@@ -0,0 +1,106 @@
+/**
+ * Override Handlers
+ *
+ * Manager approval at the register:
+ * - A manager enters their PIN or scans their badge on the cashier's station
+ *   to approve one price override, discount, no-receipt return or void
+ * - The approval token goes with the sale, return or void it was given for
+ * - Users set their own PIN and badge; admins set anyone's
+ */
+
+use actix_web::{post, put, web, HttpRequest, HttpResponse};
+use sqlx::SqlitePool;
+
+use crate::config::Config;
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::override_service::{ApproveRequest, SetCredentialsRequest};
+use crate::services::OverrideService;
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Approve an action for the signed-in user at their station
+///
+/// POST /api/overrides/approve
+#[post("/api/overrides/approve")]
+pub async fn approve_override(
+    pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    context: web::ReqData<UserContext>,
+    req: HttpRequest,
+    body: web::Json<ApproveRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "access_sell")?;
+    let context = register_context(&context, &req);
+
+    let approval = OverrideService::new(pool.get_ref().clone(), config.jwt_secret.clone())
+        .approve(&context, &body)
+        .await?;
+
+    Ok(HttpResponse::Created().json(approval))
+}
+
+/// Set the signed-in user's PIN or badge
+///
+/// PUT /api/overrides/credentials
+#[put("/api/overrides/credentials")]
+pub async fn set_own_credentials(
+    pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<SetCredentialsRequest>,
+) -> Result<HttpResponse, ApiError> {
+    OverrideService::new(pool.get_ref().clone(), config.jwt_secret.clone())
+        .set_credentials(&context.tenant_id, &context.user_id, &body)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// PUT /api/admin/users/{id}/credentials
+#[put("/api/admin/users/{id}/credentials")]
+pub async fn set_user_credentials(
+    pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<SetCredentialsRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    OverrideService::new(pool.get_ref().clone(), config.jwt_secret.clone())
+        .set_credentials(&context.tenant_id, &path.into_inner(), &body)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// The signed-in user at the register they are using: their token's
+/// station, else the X-Station-ID header, as the sales endpoints see it
+fn register_context(context: &UserContext, req: &HttpRequest) -> UserContext {
+    let mut context = context.clone();
+    if context.station_id.is_none() {
+        context.station_id = req
+            .headers()
+            .get("X-Station-ID")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+    }
+    context
+}
+
+fn require_permission(context: &UserContext, permission: &str) -> Result<(), ApiError> {
+    if context.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(format!("This requires the {permission} permission")))
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(approve_override)
+       .service(set_own_credentials)
+       .service(set_user_credentials);
+}
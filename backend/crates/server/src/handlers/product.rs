@@ -7,7 +7,10 @@ use crate::middleware::get_current_tenant_id;
 use crate::models::{
     BulkOperationRequest, CreateProductRequest, ProductSearchRequest, UpdateProductRequest,
 };
-use crate::services::{BarcodeService, ProductService, ProductLookupService, SearchService, VariantService};
+use crate::services::{BarcodeService, FitmentService, ProductService, ProductLookupService, SearchService, VariantService};
+use crate::services::inventory_ledger_service::{
+    self, MovementType, StockMovement, SOURCE_STOCK_ADJUSTMENT,
+};
 
 /// Helper function to extract user_id from request context (Task 19.1)
 fn get_user_id_from_context(req: &HttpRequest) -> Result<String, HttpResponse> {
@@ -74,7 +77,14 @@ pub async fn get_product(
     let service = ProductService::new(pool.get_ref().clone(), config_loader.get_ref().clone());
 
     match service.get_product(&product_id, &tenant_id).await {
-        Ok(product) => HttpResponse::Ok().json(product),
+        Ok(mut product) => {
+            // Vehicles the part fits, for the parts counter
+            match FitmentService::new(pool.get_ref().clone()).for_product(&tenant_id, &product_id).await {
+                Ok(fitment) => product.fitment = fitment,
+                Err(e) => tracing::warn!("Failed to load fitment for product {}: {}", product_id, e),
+            }
+            HttpResponse::Ok().json(product)
+        }
         Err(errors) => {
             if errors.iter().any(|e| e.message.contains("not found")) {
                 HttpResponse::NotFound().json(serde_json::json!({
@@ -784,55 +794,70 @@ pub async fn adjust_stock(
         }
     };
 
-    // Record the adjustment in audit log
     let adjustment_id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
     
     let store_id = body.store_id.clone().unwrap_or_else(|| "default".to_string());
     
-    let audit_result = sqlx::query(
-        r#"
-        INSERT INTO stock_adjustments (
-            id, product_id, tenant_id, user_id,
-            adjustment_type, quantity_before, quantity_after, quantity_change,
-            reason, notes, store_id, location_id, created_at
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-        "#
-    )
-    .bind(&adjustment_id)
-    .bind(&product_id)
-    .bind(&tenant_id)
-    .bind(&user_id)
-    .bind(&body.adjustment_type)
-    .bind(old_quantity)
-    .bind(new_quantity)
-    .bind(new_quantity - old_quantity)
-    .bind(&body.reason)
-    .bind(&body.notes)
-    .bind(&store_id)
-    .bind(&body.location_id)
-    .bind(&now)
-    .execute(pool_ref)
-    .await;
+    let result: Result<(), String> = async {
+        let mut tx = pool_ref.begin().await.map_err(|e| e.to_string())?;
+
+        // Record the adjustment in audit log
+        sqlx::query(
+            r#"
+            INSERT INTO stock_adjustments (
+                id, product_id, tenant_id, user_id,
+                adjustment_type, quantity_before, quantity_after, quantity_change,
+                reason, notes, store_id, location_id, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&adjustment_id)
+        .bind(&product_id)
+        .bind(&tenant_id)
+        .bind(&user_id)
+        .bind(&body.adjustment_type)
+        .bind(old_quantity)
+        .bind(new_quantity)
+        .bind(new_quantity - old_quantity)
+        .bind(&body.reason)
+        .bind(&body.notes)
+        .bind(&store_id)
+        .bind(&body.location_id)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
 
-    // If audit table doesn't exist, log warning but continue
-    if let Err(e) = audit_result {
-        tracing::warn!("Could not record stock adjustment audit: {}. Table may not exist.", e);
-    }
+        // Move the stock through the inventory ledger; "set" is a count
+        if new_quantity != old_quantity {
+            let movement_type = if body.adjustment_type == "set" {
+                MovementType::Count
+            } else {
+                MovementType::Adjustment
+            };
+            let movement = StockMovement::new(
+                &tenant_id,
+                &product_id,
+                movement_type,
+                f64::from(new_quantity - old_quantity),
+                &body.reason,
+            )
+            .with_source(SOURCE_STOCK_ADJUSTMENT, &adjustment_id)
+            .by_user(&user_id)
+            .at_store(&store_id)
+            .with_notes(body.notes.clone());
+            inventory_ledger_service::record_movement(&mut tx, &movement)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
 
-    // Update the product quantity
-    let update_result = sqlx::query(
-        "UPDATE products SET quantity_on_hand = ?, updated_at = ? WHERE id = ? AND tenant_id = ?"
-    )
-    .bind(new_quantity)
-    .bind(&now)
-    .bind(&product_id)
-    .bind(&tenant_id)
-    .execute(pool_ref)
+        tx.commit().await.map_err(|e| e.to_string())
+    }
     .await;
 
-    match update_result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+    match result {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
             "success": true,
             "adjustment_id": adjustment_id,
             "product_id": product_id,
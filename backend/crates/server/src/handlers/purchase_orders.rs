@@ -0,0 +1,317 @@
+/**
+ * Purchase Order Handlers
+ *
+ * Order stock from vendors:
+ * - Create a purchase order per vendor, or raise drafts from the products
+ *   at or below their reorder point
+ * - Submit it, receive goods against its lines in one or more receipts,
+ *   cancel it before anything arrives or close it short
+ * - Match a vendor bill against its PO and receipts; variances go to the
+ *   review queue and hold the bill back from posting until approved
+ *
+ * Changing a purchase order requires the adjust_inventory permission;
+ * receiving goods requires receive_stock.
+ */
+
+use actix_web::{get, post, web, HttpResponse};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::purchase_order_service::{
+    PurchaseOrderLineRequest, PurchaseOrderRequest, PurchaseOrderStatus, ReceiptLine,
+};
+use crate::services::PurchaseOrderService;
+
+// ============================================================================
+// Request Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePurchaseOrderRequest {
+    pub vendor_id: String,
+    /// Defaults to the signed-in user's store
+    pub store_id: Option<String>,
+    pub expected_date: Option<String>,
+    pub notes: Option<String>,
+    pub lines: Vec<PurchaseOrderLineRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReceivePurchaseOrderRequest {
+    pub lines: Vec<ReceiptLine>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListPurchaseOrdersQuery {
+    pub vendor_id: Option<String>,
+    pub store_id: Option<String>,
+    pub status: Option<PurchaseOrderStatus>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SuggestionsQuery {
+    /// Defaults to the signed-in user's store
+    pub store_id: Option<String>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Create a draft purchase order
+///
+/// POST /api/purchase-orders
+#[post("/api/purchase-orders")]
+pub async fn create_purchase_order(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<CreatePurchaseOrderRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+    let body = body.into_inner();
+    let store_id = store_or_default(&context, body.store_id)?;
+
+    let order = PurchaseOrderService::new(pool.get_ref().clone())
+        .create(PurchaseOrderRequest {
+            tenant_id: context.tenant_id.clone(),
+            vendor_id: body.vendor_id,
+            store_id,
+            created_by: context.user_id.clone(),
+            expected_date: body.expected_date,
+            notes: body.notes,
+            lines: body.lines,
+        })
+        .await?;
+
+    Ok(HttpResponse::Created().json(order))
+}
+
+/// Purchase orders, newest first
+///
+/// GET /api/purchase-orders
+#[get("/api/purchase-orders")]
+pub async fn list_purchase_orders(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<ListPurchaseOrdersQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let orders = PurchaseOrderService::new(pool.get_ref().clone())
+        .list(
+            &context.tenant_id,
+            query.vendor_id.as_deref(),
+            query.store_id.as_deref(),
+            query.status,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "total": orders.len(),
+        "purchase_orders": orders,
+    })))
+}
+
+/// Products at or below their reorder point, by vendor
+///
+/// GET /api/purchase-orders/suggestions
+#[get("/api/purchase-orders/suggestions")]
+pub async fn get_suggestions(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<SuggestionsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let store_id = store_or_default(&context, query.into_inner().store_id)?;
+
+    let suggestions = PurchaseOrderService::new(pool.get_ref().clone())
+        .suggestions(&context.tenant_id, &store_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "store_id": store_id,
+        "suggestions": suggestions,
+    })))
+}
+
+/// Raise draft purchase orders from the reorder suggestions
+///
+/// POST /api/purchase-orders/suggestions
+#[post("/api/purchase-orders/suggestions")]
+pub async fn create_suggested_purchase_orders(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: Option<web::Json<SuggestionsQuery>>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+    let body = body.map(web::Json::into_inner).unwrap_or_default();
+    let store_id = store_or_default(&context, body.store_id)?;
+
+    let orders = PurchaseOrderService::new(pool.get_ref().clone())
+        .create_suggested(&context.tenant_id, &store_id, &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Created().json(serde_json::json!({
+        "total": orders.len(),
+        "purchase_orders": orders,
+    })))
+}
+
+/// Match a vendor bill against its purchase order and receipts
+///
+/// POST /api/purchase-orders/bills/{bill_id}/match
+#[post("/api/purchase-orders/bills/{bill_id}/match")]
+pub async fn match_bill(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let bill_id = path.into_inner();
+
+    let matched = PurchaseOrderService::new(pool.get_ref().clone())
+        .match_bill(&context.tenant_id, &bill_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Vendor bill {bill_id} has no purchase order")))?;
+
+    Ok(HttpResponse::Ok().json(matched))
+}
+
+/// GET /api/purchase-orders/{id}
+#[get("/api/purchase-orders/{id}")]
+pub async fn get_purchase_order(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let order = PurchaseOrderService::new(pool.get_ref().clone())
+        .get(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+/// Send a draft to the vendor
+///
+/// POST /api/purchase-orders/{id}/submit
+#[post("/api/purchase-orders/{id}/submit")]
+pub async fn submit_purchase_order(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+
+    let order = PurchaseOrderService::new(pool.get_ref().clone())
+        .submit(&context.tenant_id, &path.into_inner(), &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+/// Receive goods against purchase order lines
+///
+/// POST /api/purchase-orders/{id}/receive
+#[post("/api/purchase-orders/{id}/receive")]
+pub async fn receive_purchase_order(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<ReceivePurchaseOrderRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "receive_stock")?;
+    let body = body.into_inner();
+
+    let order = PurchaseOrderService::new(pool.get_ref().clone())
+        .receive(&context.tenant_id, &path.into_inner(), &context.user_id, &body.lines, body.notes)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+/// Cancel a purchase order nothing has been received on
+///
+/// POST /api/purchase-orders/{id}/cancel
+#[post("/api/purchase-orders/{id}/cancel")]
+pub async fn cancel_purchase_order(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+
+    let order = PurchaseOrderService::new(pool.get_ref().clone())
+        .cancel(&context.tenant_id, &path.into_inner(), &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+/// Close a partially received purchase order short
+///
+/// POST /api/purchase-orders/{id}/close
+#[post("/api/purchase-orders/{id}/close")]
+pub async fn close_purchase_order(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+
+    let order = PurchaseOrderService::new(pool.get_ref().clone())
+        .close(&context.tenant_id, &path.into_inner(), &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+/// Tie a vendor bill to a purchase order and match it
+///
+/// POST /api/purchase-orders/{id}/bills/{bill_id}
+#[post("/api/purchase-orders/{id}/bills/{bill_id}")]
+pub async fn link_bill(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "adjust_inventory")?;
+    let (id, bill_id) = path.into_inner();
+
+    let matched = PurchaseOrderService::new(pool.get_ref().clone())
+        .link_bill(&context.tenant_id, &id, &bill_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(matched))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_permission(context: &UserContext, permission: &str) -> Result<(), ApiError> {
+    if context.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(format!("This requires the {permission} permission")))
+    }
+}
+
+fn store_or_default(context: &UserContext, store_id: Option<String>) -> Result<String, ApiError> {
+    store_id
+        .or_else(|| context.store_id.clone())
+        .ok_or_else(|| ApiError::bad_request("store_id is required"))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // Fixed paths before /api/purchase-orders/{id}
+    cfg.service(create_purchase_order)
+       .service(list_purchase_orders)
+       .service(get_suggestions)
+       .service(create_suggested_purchase_orders)
+       .service(match_bill)
+       .service(get_purchase_order)
+       .service(submit_purchase_order)
+       .service(receive_purchase_order)
+       .service(cancel_purchase_order)
+       .service(close_purchase_order)
+       .service(link_bill);
+}
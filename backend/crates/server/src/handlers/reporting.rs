@@ -464,7 +464,10 @@ pub async fn get_sales_by_category(
             p.category,
             COUNT(DISTINCT st.id) as transaction_count,
             SUM(sli.quantity) as items_sold,
-            SUM(sli.subtotal) as total_revenue
+            SUM(sli.subtotal) as total_revenue,
+            COALESCE(SUM(sli.subtotal - sli.discount_amount), 0.0) as net_revenue,
+            COALESCE(SUM(sli.cost), 0.0) as cost_of_goods,
+            COALESCE(SUM(sli.profit), 0.0) as gross_profit
         FROM sales_transactions st
         JOIN sales_line_items sli ON st.id = sli.transaction_id
         JOIN products p ON sli.product_id = p.id
@@ -490,11 +493,16 @@ pub async fn get_sales_by_category(
             let categories: Vec<serde_json::Value> = rows
                 .iter()
                 .map(|row| {
+                    let net_revenue = row.try_get::<f64, _>("net_revenue").unwrap_or(0.0);
+                    let gross_profit = row.try_get::<f64, _>("gross_profit").unwrap_or(0.0);
                     serde_json::json!({
                         "category": row.try_get::<String, _>("category").unwrap_or_default(),
                         "transaction_count": row.try_get::<i64, _>("transaction_count").unwrap_or(0),
                         "items_sold": row.try_get::<i64, _>("items_sold").unwrap_or(0),
                         "total_revenue": row.try_get::<f64, _>("total_revenue").unwrap_or(0.0),
+                        "cost_of_goods": row.try_get::<f64, _>("cost_of_goods").unwrap_or(0.0),
+                        "gross_profit": gross_profit,
+                        "margin_percent": margin_percent(gross_profit, net_revenue),
                     })
                 })
                 .collect();
@@ -510,6 +518,122 @@ pub async fn get_sales_by_category(
     }
 }
 
+/// GET /api/reports/margins
+/// Gross margin per product, at the cost of goods recorded on each sale line
+#[get("/api/reports/margins")]
+pub async fn get_margin_report(
+    pool: web::Data<SqlitePool>,
+    query: web::Query<SalesReportParams>,
+) -> impl Responder {
+    tracing::info!("Generating margin report");
+
+    // Validate date inputs
+    let date_range = match ValidatedDateRange::new(
+        query.start_date.as_deref(),
+        query.end_date.as_deref(),
+    ) {
+        Ok(range) => range,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid date format or range"
+            }));
+        }
+    };
+
+    let tenant_id = get_current_tenant_id();
+
+    // Returns carry negative amounts and cost, so they net out
+    let mut query_builder = QueryBuilder::new(
+        "SELECT
+            p.id as product_id,
+            p.sku,
+            p.name,
+            p.category,
+            COALESCE(SUM(sli.quantity), 0.0) as quantity_sold,
+            COALESCE(SUM(sli.subtotal - sli.discount_amount), 0.0) as net_revenue,
+            COALESCE(SUM(sli.cost), 0.0) as cost_of_goods,
+            COALESCE(SUM(sli.profit), 0.0) as gross_profit
+        FROM sales_transactions st
+        JOIN sales_line_items sli ON st.id = sli.transaction_id
+        JOIN products p ON sli.product_id = p.id
+        WHERE st.status = 'completed' AND st.tenant_id = "
+    );
+    query_builder.push_bind(tenant_id);
+
+    if let Some(start_date) = &date_range.start_date {
+        query_builder.push(" AND st.created_at >= ");
+        query_builder.push_bind(start_date);
+    }
+    if let Some(end_date) = &date_range.end_date {
+        query_builder.push(" AND st.created_at <= ");
+        query_builder.push_bind(end_date);
+    }
+    if let Some(category) = &query.category {
+        query_builder.push(" AND p.category = ");
+        query_builder.push_bind(category);
+    }
+
+    query_builder.push(" GROUP BY p.id, p.sku, p.name, p.category ORDER BY gross_profit DESC");
+
+    let result = query_builder.build().fetch_all(pool.get_ref()).await;
+
+    match result {
+        Ok(rows) => {
+            let mut total_revenue = 0.0;
+            let mut total_cost = 0.0;
+            let mut total_profit = 0.0;
+            let products: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|row| {
+                    let net_revenue = row.try_get::<f64, _>("net_revenue").unwrap_or(0.0);
+                    let cost_of_goods = row.try_get::<f64, _>("cost_of_goods").unwrap_or(0.0);
+                    let gross_profit = row.try_get::<f64, _>("gross_profit").unwrap_or(0.0);
+                    total_revenue += net_revenue;
+                    total_cost += cost_of_goods;
+                    total_profit += gross_profit;
+                    serde_json::json!({
+                        "product_id": row.try_get::<String, _>("product_id").unwrap_or_default(),
+                        "sku": row.try_get::<String, _>("sku").unwrap_or_default(),
+                        "name": row.try_get::<String, _>("name").unwrap_or_default(),
+                        "category": row.try_get::<Option<String>, _>("category").unwrap_or_default(),
+                        "quantity_sold": row.try_get::<f64, _>("quantity_sold").unwrap_or(0.0),
+                        "net_revenue": net_revenue,
+                        "cost_of_goods": cost_of_goods,
+                        "gross_profit": gross_profit,
+                        "margin_percent": margin_percent(gross_profit, net_revenue),
+                    })
+                })
+                .collect();
+
+            HttpResponse::Ok().json(serde_json::json!({
+                "products": products,
+                "totals": {
+                    "net_revenue": total_revenue,
+                    "cost_of_goods": total_cost,
+                    "gross_profit": total_profit,
+                    "margin_percent": margin_percent(total_profit, total_revenue),
+                },
+                "status": "success"
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to generate margin report: {:?}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to generate margin report"
+            }))
+        }
+    }
+}
+
+/// Gross profit as a percentage of net revenue
+fn margin_percent(gross_profit: f64, net_revenue: f64) -> f64 {
+    if net_revenue == 0.0 {
+        0.0
+    } else {
+        (gross_profit / net_revenue) * 100.0
+    }
+}
+
 /// GET /api/reports/sales/by-tier
 /// Sales breakdown by pricing tier
 #[get("/api/reports/sales/by-tier")]
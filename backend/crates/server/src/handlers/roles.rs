@@ -0,0 +1,189 @@
+/**
+ * Role Handlers
+ *
+ * Admin API for the tenant's roles:
+ * - The permission catalog roles are built from
+ * - Create custom roles and change the permissions of any role but admin
+ * - Require a second factor of a role's holders, admin included
+ * - Assign roles to users at every store or at one store
+ *
+ * Changes take effect at the holders' next sign-in; their current tokens
+ * stop working. Everything here requires the manage_users permission.
+ */
+
+use actix_web::{delete, get, post, put, web, HttpResponse};
+use sqlx::SqlitePool;
+
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::role_service::{AssignRoleRequest, CreateRoleRequest, UpdateRoleRequest};
+use crate::services::RoleService;
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /api/admin/permissions
+#[get("/api/admin/permissions")]
+pub async fn list_permissions(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    let permissions = RoleService::new(pool.get_ref().clone()).permissions().await?;
+
+    Ok(HttpResponse::Ok().json(permissions))
+}
+
+/// GET /api/admin/roles
+#[get("/api/admin/roles")]
+pub async fn list_roles(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    let roles = RoleService::new(pool.get_ref().clone())
+        .list(&context.tenant_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+/// POST /api/admin/roles
+#[post("/api/admin/roles")]
+pub async fn create_role(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<CreateRoleRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    let role = RoleService::new(pool.get_ref().clone())
+        .create(&context.tenant_id, &body)
+        .await?;
+
+    Ok(HttpResponse::Created().json(role))
+}
+
+/// GET /api/admin/roles/{id}
+#[get("/api/admin/roles/{id}")]
+pub async fn get_role(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    let role = RoleService::new(pool.get_ref().clone())
+        .get(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(role))
+}
+
+/// PUT /api/admin/roles/{id}
+#[put("/api/admin/roles/{id}")]
+pub async fn update_role(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<UpdateRoleRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    let role = RoleService::new(pool.get_ref().clone())
+        .update(&context.tenant_id, &path.into_inner(), &body)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(role))
+}
+
+/// DELETE /api/admin/roles/{id}
+#[delete("/api/admin/roles/{id}")]
+pub async fn delete_role(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    RoleService::new(pool.get_ref().clone())
+        .delete(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Roles assigned to a user on top of their primary role
+///
+/// GET /api/admin/users/{id}/roles
+#[get("/api/admin/users/{id}/roles")]
+pub async fn list_user_roles(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    let assignments = RoleService::new(pool.get_ref().clone())
+        .assignments(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(assignments))
+}
+
+/// POST /api/admin/users/{id}/roles
+#[post("/api/admin/users/{id}/roles")]
+pub async fn assign_user_role(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<AssignRoleRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    let assignment = RoleService::new(pool.get_ref().clone())
+        .assign(&context.tenant_id, &path.into_inner(), &body, &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Created().json(assignment))
+}
+
+/// DELETE /api/admin/users/{id}/roles/{assignment_id}
+#[delete("/api/admin/users/{id}/roles/{assignment_id}")]
+pub async fn unassign_user_role(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+    let (user_id, assignment_id) = path.into_inner();
+
+    RoleService::new(pool.get_ref().clone())
+        .unassign(&context.tenant_id, &user_id, &assignment_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn require_permission(context: &UserContext, permission: &str) -> Result<(), ApiError> {
+    if context.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(format!("This requires the {permission} permission")))
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_permissions)
+       .service(list_roles)
+       .service(create_role)
+       .service(get_role)
+       .service(update_role)
+       .service(delete_role)
+       .service(list_user_roles)
+       .service(assign_user_role)
+       .service(unassign_user_role);
+}
@@ -1,17 +1,40 @@
 /**
  * Sales Handlers
  * 
- * Handles POS sales transactions - creating, completing, and voiding sales.
- * This is the core checkout flow for the point of sale.
+ * Handles POS sales transactions - creating, completing, voiding and
+ * returning sales. This is the core checkout flow for the point of sale. Sale
+ * totals are computed by pos_core_domain via `CheckoutService`; returns
+ * against an earlier sale go through `ReturnService`.
+ *
+ * Price overrides, discounts over the store's threshold, returns without
+ * the receipt and voids by a user without the permission need a manager's
+ * approval at the register (`OverrideService`); the approval tokens come in
+ * `override_tokens`.
  */
 
-use actix_web::{web, HttpRequest, HttpResponse, post, get};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, post, get};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 use chrono::Utc;
 
+use crate::config::Config;
 use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::{CheckoutService, OverrideService, ReturnService};
+use crate::services::inventory_ledger_service::{
+    self, MovementType, StockMovement, REASON_SALE_VOID, SOURCE_SALE,
+};
+use crate::services::checkout_service::{
+    self, decimal_from_f64, money_to_f64, CheckoutError, CheckoutLine, CheckoutRequest, Tender,
+};
+use crate::services::outbound_webhook_service::{self, WebhookEventType};
+use crate::services::override_service::{RequiredOverride, SaleLine};
+use crate::services::return_service::{
+    OriginalSale, RefundDestination, ReturnDisposition, ReturnLine, ReturnRequest,
+    TRANSACTION_TYPE_SALE,
+};
+use crate::services::stored_value_service::StoredValueError;
 
 // ============================================================================
 // Request/Response Types
@@ -25,13 +48,73 @@ pub struct SaleLineItem {
     pub discount_amount: Option<f64>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SaleTender {
+    /// cash, card, gift_card, store_credit, ...
+    pub method: String,
+    /// Amount tendered; omit to pay the remaining balance
+    pub amount: Option<f64>,
+    /// Gift card number, card authorization code, etc.
+    pub reference: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateSaleRequest {
     pub customer_id: Option<String>,
     pub items: Vec<SaleLineItem>,
-    pub payment_method: String,
+    /// Single payment method for the full amount; ignored when `tenders` is given
+    pub payment_method: Option<String>,
+    pub tenders: Option<Vec<SaleTender>>,
     pub discount_amount: Option<f64>,
     pub notes: Option<String>,
+    /// Parked cart this sale checks out (see `suspended_sales`)
+    pub suspended_sale_id: Option<String>,
+    /// Manager approvals for price overrides and the discount
+    #[serde(default)]
+    pub override_tokens: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReturnItemRequest {
+    /// Line of the original sale being returned
+    pub line_item_id: String,
+    pub quantity: f64,
+    /// restock (default) or damaged
+    pub disposition: Option<ReturnDisposition>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReturnRequest {
+    pub items: Vec<ReturnItemRequest>,
+    /// original_tenders (default) or store_credit
+    #[serde(default)]
+    pub refund_to: RefundDestination,
+    /// Customer to credit when the original sale had none
+    pub customer_id: Option<String>,
+    pub reason: Option<String>,
+    /// The customer has no receipt; the sale was found another way
+    #[serde(default)]
+    pub no_receipt: bool,
+    /// Manager approval for a return without the receipt
+    #[serde(default)]
+    pub override_tokens: Vec<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SaleTenderResponse {
+    pub method: String,
+    pub tendered_amount: f64,
+    pub amount: f64,
+    pub change_amount: f64,
+    pub reference: Option<String>,
+}
+
+/// Tax collected by one authority on a sale
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SaleTaxResponse {
+    pub authority: String,
+    pub label: String,
+    pub tax_amount: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -47,6 +130,23 @@ pub struct SaleResponse {
     pub payment_method: String,
     pub status: String,
     pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_due: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenders: Option<Vec<SaleTenderResponse>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taxes: Option<Vec<SaleTaxResponse>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_exempt_certificate: Option<String>,
+    /// Whether the line prices included tax (the subtotal is then net of it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prices_include_tax: Option<bool>,
+    /// sale or return
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<String>,
+    /// Sale a return was made against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_transaction_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -65,6 +165,7 @@ pub struct SaleListResponse {
 #[post("/api/sales")]
 pub async fn create_sale(
     pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
     req: HttpRequest,
     body: web::Json<CreateSaleRequest>,
 ) -> Result<HttpResponse, ApiError> {
@@ -77,116 +178,129 @@ pub async fn create_sale(
         return Err(ApiError::bad_request("Sale must have at least one item"));
     }
     
-    // Calculate totals
-    let mut subtotal = 0.0;
-    let mut items_count = 0;
-    
+    let mut lines = Vec::with_capacity(body.items.len());
     for item in &body.items {
         if item.quantity <= 0.0 {
             return Err(ApiError::bad_request("Item quantity must be positive"));
         }
-        subtotal += item.unit_price * item.quantity - item.discount_amount.unwrap_or(0.0);
-        items_count += 1;
+        lines.push(CheckoutLine {
+            product_id: item.product_id.clone(),
+            quantity: decimal_from_f64(item.quantity, "quantity")?,
+            unit_price: decimal_from_f64(item.unit_price, "unit_price")?,
+            discount_amount: decimal_from_f64(item.discount_amount.unwrap_or(0.0), "discount_amount")?,
+        });
     }
     
-    let discount_amount = body.discount_amount.unwrap_or(0.0);
-    subtotal -= discount_amount;
-    
-    // Get tax rate from localization settings or default to 13%
-    let tax_rate = get_tenant_tax_rate(&pool, &tenant_id).await;
-    let tax_amount = subtotal * tax_rate;
-    let total_amount = subtotal + tax_amount;
-    
-    // Generate transaction number
-    let transaction_number = generate_transaction_number(&pool, &tenant_id).await?;
+    let tenders = match &body.tenders {
+        Some(tenders) => tenders
+            .iter()
+            .map(|t| {
+                Ok(Tender {
+                    method: t.method.clone(),
+                    amount: t.amount.map(|a| decimal_from_f64(a, "tender amount")).transpose()?,
+                    reference: t.reference.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, CheckoutError>>()?,
+        None => {
+            let method = body
+                .payment_method
+                .clone()
+                .ok_or_else(|| ApiError::bad_request("Either tenders or payment_method is required"))?;
+            vec![Tender { method, amount: None, reference: None }]
+        }
+    };
     
-    // Create sale record
-    let sale_id = Uuid::new_v4().to_string();
-    let now = Utc::now().to_rfc3339();
+    let cart_discount = body.discount_amount.unwrap_or(0.0);
+    let sale_lines: Vec<SaleLine> = body
+        .items
+        .iter()
+        .map(|item| SaleLine {
+            product_id: item.product_id.clone(),
+            quantity: item.quantity,
+            unit_price: item.unit_price,
+            discount: item.discount_amount.unwrap_or(0.0),
+        })
+        .collect();
+    let overrides = OverrideService::new(pool.get_ref().clone(), config.jwt_secret.clone());
+    let required = overrides
+        .sale_overrides(&tenant_id, Some(&store_id), body.customer_id.as_deref(), &sale_lines, cart_discount)
+        .await?;
+    let approvals = overrides
+        .authorize(&register_context(&req)?, &required, &body.override_tokens)
+        .await?;
     
-    sqlx::query(
-        r#"
-        INSERT INTO sales_transactions (
-            id, tenant_id, transaction_number, customer_id, employee_id, store_id,
-            total_amount, subtotal, tax_amount, discount_amount, items_count,
-            payment_method, payment_status, status, notes, created_at, updated_at, completed_at
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'completed', 'completed', ?, ?, ?, ?)
-        "#
-    )
-    .bind(&sale_id)
-    .bind(&tenant_id)
-    .bind(&transaction_number)
-    .bind(&body.customer_id)
-    .bind(&employee_id)
-    .bind(&store_id)
-    .bind(total_amount)
-    .bind(subtotal)
-    .bind(tax_amount)
-    .bind(discount_amount)
-    .bind(items_count)
-    .bind(&body.payment_method)
-    .bind(&body.notes)
-    .bind(&now)
-    .bind(&now)
-    .bind(&now)
-    .execute(pool.get_ref())
-    .await
-    .map_err(|e| ApiError::internal(format!("Failed to create sale: {}", e)))?;
+    // Price, finalize and persist through pos_core_domain
+    let service = CheckoutService::new(pool.get_ref().clone());
+    let sale = service
+        .complete_sale(CheckoutRequest {
+            tenant_id,
+            store_id,
+            station_id: extract_station_id(&req),
+            employee_id,
+            customer_id: body.customer_id.clone(),
+            lines,
+            cart_discount: decimal_from_f64(cart_discount, "discount_amount")?,
+            tenders,
+            notes: body.notes.clone(),
+            suspended_sale_id: body.suspended_sale_id.clone(),
+        })
+        .await;
+    let sale = match sale {
+        Ok(sale) => sale,
+        Err(e) => {
+            release_approvals(&overrides, &approvals).await;
+            return Err(e.into());
+        }
+    };
     
-    // Create line items
-    for item in &body.items {
-        let line_id = Uuid::new_v4().to_string();
-        let item_subtotal = item.unit_price * item.quantity;
-        let item_discount = item.discount_amount.unwrap_or(0.0);
-        let item_tax = (item_subtotal - item_discount) * tax_rate;
-        let item_total = item_subtotal - item_discount + item_tax;
-        
-        sqlx::query(
-            r#"
-            INSERT INTO sales_line_items (
-                id, transaction_id, product_id, quantity, unit_price,
-                subtotal, discount_amount, tax_amount, total, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#
-        )
-        .bind(&line_id)
-        .bind(&sale_id)
-        .bind(&item.product_id)
-        .bind(item.quantity)
-        .bind(item.unit_price)
-        .bind(item_subtotal)
-        .bind(item_discount)
-        .bind(item_tax)
-        .bind(item_total)
-        .bind(&now)
-        .execute(pool.get_ref())
-        .await
-        .map_err(|e| ApiError::internal(format!("Failed to create line item: {}", e)))?;
-        
-        // Update inventory (decrease stock)
-        sqlx::query(
-            "UPDATE products SET quantity_on_hand = quantity_on_hand - ? WHERE id = ? AND tenant_id = ?"
-        )
-        .bind(item.quantity)
-        .bind(&item.product_id)
-        .bind(&tenant_id)
-        .execute(pool.get_ref())
-        .await
-        .ok(); // Don't fail if product doesn't exist
-    }
+    let taxes = sale
+        .tax_breakdown
+        .authority_totals()
+        .into_iter()
+        .map(|(authority, amount)| SaleTaxResponse {
+            label: sale
+                .tax_breakdown
+                .lines
+                .iter()
+                .flat_map(|line| &line.taxes)
+                .find(|tax| tax.rate_code == authority)
+                .map_or_else(|| authority.clone(), |tax| tax.label.clone()),
+            authority,
+            tax_amount: money_to_f64(amount),
+        })
+        .collect();
     
     Ok(HttpResponse::Created().json(SaleResponse {
-        id: sale_id,
-        transaction_number,
+        id: sale.sale_id,
+        transaction_number: sale.transaction_number,
         customer_id: body.customer_id.clone(),
-        subtotal,
-        tax_amount,
-        discount_amount,
-        total_amount,
-        items_count,
-        payment_method: body.payment_method.clone(),
+        subtotal: money_to_f64(sale.subtotal),
+        tax_amount: money_to_f64(sale.tax),
+        discount_amount: money_to_f64(sale.discount),
+        total_amount: money_to_f64(sale.total),
+        items_count: sale.items_count,
+        payment_method: sale.payment_method,
         status: "completed".to_string(),
-        created_at: now,
+        created_at: sale.created_at,
+        change_due: Some(money_to_f64(sale.change_due)),
+        tenders: Some(
+            sale.tenders
+                .into_iter()
+                .map(|t| SaleTenderResponse {
+                    method: t.method,
+                    tendered_amount: money_to_f64(t.tendered),
+                    amount: money_to_f64(t.applied),
+                    change_amount: money_to_f64(t.change),
+                    reference: t.reference,
+                })
+                .collect(),
+        ),
+        taxes: Some(taxes),
+        tax_exempt_certificate: sale.tax_breakdown.exemption_certificate,
+        prices_include_tax: Some(sale.prices_include_tax),
+        transaction_type: Some(TRANSACTION_TYPE_SALE.to_string()),
+        original_transaction_id: None,
     }))
 }
 
@@ -205,7 +319,8 @@ pub async fn get_sale(
     let sale = sqlx::query_as::<_, SaleRecord>(
         r#"
         SELECT id, transaction_number, customer_id, subtotal, tax_amount,
-               discount_amount, total_amount, items_count, payment_method, status, created_at
+               discount_amount, total_amount, items_count, payment_method, status, created_at,
+               tax_exempt_certificate, prices_include_tax, transaction_type, original_transaction_id
         FROM sales_transactions
         WHERE id = ? AND tenant_id = ?
         "#
@@ -216,6 +331,35 @@ pub async fn get_sale(
     .await
     .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
     
+    let tenders = sqlx::query_as::<_, SaleTenderResponse>(
+        r#"
+        SELECT method, tendered_amount, amount, change_amount, reference
+        FROM sales_payments
+        WHERE transaction_id = ? AND tenant_id = ?
+        ORDER BY created_at
+        "#
+    )
+    .bind(&sale_id)
+    .bind(&tenant_id)
+    .fetch_all(pool.get_ref())
+    .await
+    .unwrap_or_default();
+    
+    let taxes = sqlx::query_as::<_, SaleTaxResponse>(
+        r#"
+        SELECT authority, MIN(label) AS label, ROUND(SUM(tax_amount), 2) AS tax_amount
+        FROM sales_line_taxes
+        WHERE transaction_id = ? AND tenant_id = ?
+        GROUP BY authority
+        ORDER BY MIN(rowid)
+        "#
+    )
+    .bind(&sale_id)
+    .bind(&tenant_id)
+    .fetch_all(pool.get_ref())
+    .await
+    .unwrap_or_default();
+    
     match sale {
         Some(s) => Ok(HttpResponse::Ok().json(SaleResponse {
             id: s.id,
@@ -229,6 +373,13 @@ pub async fn get_sale(
             payment_method: s.payment_method.unwrap_or_default(),
             status: s.status,
             created_at: s.created_at,
+            change_due: (!tenders.is_empty()).then(|| tenders.iter().map(|t| t.change_amount).sum()),
+            tenders: (!tenders.is_empty()).then_some(tenders),
+            taxes: (!taxes.is_empty()).then_some(taxes),
+            tax_exempt_certificate: s.tax_exempt_certificate,
+            prices_include_tax: Some(s.prices_include_tax),
+            transaction_type: s.transaction_type,
+            original_transaction_id: s.original_transaction_id,
         })),
         None => Err(ApiError::not_found("Sale not found")),
     }
@@ -250,7 +401,8 @@ pub async fn list_sales(
     let sales = sqlx::query_as::<_, SaleRecord>(
         r#"
         SELECT id, transaction_number, customer_id, subtotal, tax_amount,
-               discount_amount, total_amount, items_count, payment_method, status, created_at
+               discount_amount, total_amount, items_count, payment_method, status, created_at,
+               transaction_type, original_transaction_id
         FROM sales_transactions
         WHERE tenant_id = ?
         ORDER BY created_at DESC
@@ -284,6 +436,13 @@ pub async fn list_sales(
         payment_method: s.payment_method.unwrap_or_default(),
         status: s.status,
         created_at: s.created_at,
+        change_due: None,
+        tenders: None,
+        taxes: None,
+        tax_exempt_certificate: None,
+        prices_include_tax: None,
+        transaction_type: s.transaction_type,
+        original_transaction_id: s.original_transaction_id,
     }).collect();
     
     Ok(HttpResponse::Ok().json(SaleListResponse {
@@ -298,6 +457,7 @@ pub async fn list_sales(
 #[post("/api/sales/{id}/void")]
 pub async fn void_sale(
     pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
     req: HttpRequest,
     path: web::Path<String>,
     body: web::Json<VoidSaleRequest>,
@@ -305,9 +465,7 @@ pub async fn void_sale(
     let tenant_id = extract_tenant_id(&req)?;
     let user_id = extract_user_id(&req)?;
     let sale_id = path.into_inner();
-    let now = Utc::now().to_rfc3339();
     
-    // Check sale exists and is not already voided
     let sale = sqlx::query_as::<_, SaleRecord>(
         "SELECT * FROM sales_transactions WHERE id = ? AND tenant_id = ?"
     )
@@ -319,52 +477,247 @@ pub async fn void_sale(
     
     let sale = sale.ok_or_else(|| ApiError::not_found("Sale not found"))?;
     
-    if sale.status == "voided" {
+    if sale.transaction_type.as_deref().is_some_and(|t| t != TRANSACTION_TYPE_SALE) {
+        return Err(ApiError::bad_request("Returns cannot be voided"));
+    }
+    
+    let overrides = OverrideService::new(pool.get_ref().clone(), config.jwt_secret.clone());
+    let approvals = overrides
+        .authorize(&register_context(&req)?, &[RequiredOverride::void_sale(&sale_id)], &body.override_tokens)
+        .await?;
+    
+    if let Err(e) = void_recorded_sale(pool.get_ref(), &tenant_id, &user_id, &sale, &body.reason).await {
+        release_approvals(&overrides, &approvals).await;
+        return Err(e);
+    }
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "message": "Sale voided successfully"
+    })))
+}
+
+/// Void the sale, put its goods back and credit back its stored value
+/// tenders in one transaction
+///
+/// The sale is checked inside the transaction and only a completed sale is
+/// voided, so two voids of the same sale at once cannot both restock it and
+/// credit its tenders.
+async fn void_recorded_sale(
+    pool: &SqlitePool,
+    tenant_id: &str,
+    user_id: &str,
+    sale: &SaleRecord,
+    reason: &str,
+) -> Result<(), ApiError> {
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await
+        .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+    
+    let status: String = sqlx::query_scalar(
+        "SELECT status FROM sales_transactions WHERE id = ? AND tenant_id = ?"
+    )
+    .bind(&sale.id)
+    .bind(tenant_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+    
+    if status == "voided" {
         return Err(ApiError::bad_request("Sale is already voided"));
     }
     
-    // Void the sale
-    sqlx::query(
+    // Returned goods and refunds would be counted twice
+    let returns: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sales_transactions WHERE original_transaction_id = ? AND tenant_id = ?"
+    )
+    .bind(&sale.id)
+    .bind(tenant_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
+    
+    if returns > 0 {
+        return Err(ApiError::conflict("Sale has returns recorded against it and cannot be voided"));
+    }
+    
+    let voided = sqlx::query(
         r#"
         UPDATE sales_transactions 
         SET status = 'voided', voided_at = ?, voided_by = ?, void_reason = ?, updated_at = ?
-        WHERE id = ? AND tenant_id = ?
+        WHERE id = ? AND tenant_id = ? AND status = 'completed'
         "#
     )
     .bind(&now)
-    .bind(&user_id)
-    .bind(&body.reason)
+    .bind(user_id)
+    .bind(reason)
     .bind(&now)
-    .bind(&sale_id)
-    .bind(&tenant_id)
-    .execute(pool.get_ref())
+    .bind(&sale.id)
+    .bind(tenant_id)
+    .execute(&mut *tx)
     .await
     .map_err(|e| ApiError::internal(format!("Failed to void sale: {}", e)))?;
     
-    // Restore inventory
+    if voided.rows_affected() == 0 {
+        return Err(ApiError::conflict("Sale was voided or changed while this void was in progress"));
+    }
+    
+    // Put the goods back on the shelf through the inventory ledger, at the
+    // cost they were sold at
     let line_items = sqlx::query_as::<_, LineItemRecord>(
-        "SELECT product_id, quantity FROM sales_line_items WHERE transaction_id = ?"
+        "SELECT product_id, quantity, cost FROM sales_line_items WHERE transaction_id = ?"
     )
-    .bind(&sale_id)
-    .fetch_all(pool.get_ref())
+    .bind(&sale.id)
+    .fetch_all(&mut *tx)
     .await
-    .unwrap_or_default();
+    .map_err(|e| ApiError::internal(format!("Database error: {}", e)))?;
     
     for item in line_items {
-        sqlx::query(
-            "UPDATE products SET quantity_on_hand = quantity_on_hand + ? WHERE id = ? AND tenant_id = ?"
+        let mut restocked = StockMovement::new(
+            tenant_id,
+            &item.product_id,
+            MovementType::Sale,
+            item.quantity,
+            REASON_SALE_VOID,
         )
-        .bind(item.quantity)
-        .bind(&item.product_id)
-        .bind(&tenant_id)
-        .execute(pool.get_ref())
+        .with_source(SOURCE_SALE, &sale.id)
+        .by_user(user_id)
+        .with_unit_cost(item.cost.filter(|_| item.quantity > 0.0).map(|cost| cost / item.quantity));
+        restocked.store_id = sale.store_id.clone();
+        inventory_ledger_service::record_movement(&mut tx, &restocked).await?;
+    }
+    
+    // Gift cards and store credit get back what the sale took from them
+    checkout_service::credit_back_tenders(&mut tx, tenant_id, &sale.id, sale.customer_id.as_deref(), user_id)
         .await
-        .ok();
+        .map_err(|e| match e {
+            CheckoutError::Tender(StoredValueError::Database(_)) | CheckoutError::Database(_) => {
+                ApiError::internal(format!("Failed to void sale: {}", e))
+            }
+            e => ApiError::conflict(format!("Sale cannot be voided: {}", e)),
+        })?;
+    
+    outbound_webhook_service::publish(
+        &mut tx,
+        tenant_id,
+        WebhookEventType::SaleVoided,
+        serde_json::json!({
+            "sale_id": sale.id,
+            "transaction_number": sale.transaction_number,
+            "store_id": sale.store_id,
+            "customer_id": sale.customer_id,
+            "total": sale.total_amount,
+            "reason": reason,
+            "voided_by": user_id,
+            "voided_at": now,
+        }),
+    )
+    .await
+    .map_err(|e| ApiError::internal(format!("Failed to void sale: {}", e)))?;
+    
+    tx.commit().await
+        .map_err(|e| ApiError::internal(format!("Failed to void sale: {}", e)))
+}
+
+/// Look up a sale for return by transaction number (receipt barcode)
+/// 
+/// GET /api/sales/lookup/{number}
+#[get("/api/sales/lookup/{number}")]
+pub async fn lookup_sale_for_return(
+    pool: web::Data<SqlitePool>,
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let number = path.into_inner();
+    
+    let sale = ReturnService::new(pool.get_ref().clone())
+        .find_by_number(&tenant_id, &number)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(returnable_sale_json(&sale)))
+}
+
+/// Return items from a sale
+/// 
+/// POST /api/sales/{id}/returns
+#[post("/api/sales/{id}/returns")]
+pub async fn create_return(
+    pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<CreateReturnRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let employee_id = extract_user_id(&req)?;
+    let store_id = extract_store_id(&req)?;
+    let body = body.into_inner();
+    
+    if body.items.is_empty() {
+        return Err(ApiError::bad_request("Return must have at least one item"));
     }
     
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "success": true,
-        "message": "Sale voided successfully"
+    let lines = body
+        .items
+        .iter()
+        .map(|item| {
+            Ok(ReturnLine {
+                line_item_id: item.line_item_id.clone(),
+                quantity: decimal_from_f64(item.quantity, "quantity")?,
+                disposition: item.disposition.unwrap_or_default(),
+            })
+        })
+        .collect::<Result<Vec<_>, CheckoutError>>()?;
+    
+    let original_sale_id = path.into_inner();
+    let required = if body.no_receipt {
+        vec![RequiredOverride::no_receipt_return(&original_sale_id)]
+    } else {
+        Vec::new()
+    };
+    let overrides = OverrideService::new(pool.get_ref().clone(), config.jwt_secret.clone());
+    let approvals = overrides
+        .authorize(&register_context(&req)?, &required, &body.override_tokens)
+        .await?;
+    
+    let completed = ReturnService::new(pool.get_ref().clone())
+        .process_return(ReturnRequest {
+            tenant_id,
+            store_id,
+            station_id: extract_station_id(&req),
+            employee_id,
+            original_sale_id,
+            lines,
+            refund_to: body.refund_to,
+            customer_id: body.customer_id,
+            reason: body.reason,
+        })
+        .await;
+    let completed = match completed {
+        Ok(completed) => completed,
+        Err(e) => {
+            release_approvals(&overrides, &approvals).await;
+            return Err(e.into());
+        }
+    };
+    
+    Ok(HttpResponse::Created().json(serde_json::json!({
+        "id": completed.return_id,
+        "transaction_number": completed.transaction_number,
+        "transaction_type": "return",
+        "original_transaction_id": completed.original_sale_id,
+        "subtotal": money_to_f64(completed.subtotal),
+        "discount_amount": money_to_f64(completed.discount),
+        "tax_amount": money_to_f64(completed.tax),
+        "total_amount": money_to_f64(completed.total),
+        "refunds": completed.refunds.iter().map(|refund| serde_json::json!({
+            "method": refund.method,
+            "reference": refund.reference,
+            "amount": money_to_f64(refund.amount),
+        })).collect::<Vec<_>>(),
+        "fully_returned": completed.fully_returned,
+        "created_at": completed.created_at,
     })))
 }
 
@@ -386,7 +739,8 @@ pub async fn get_customer_transactions(
     let sales = sqlx::query_as::<_, SaleRecord>(
         r#"
         SELECT id, transaction_number, customer_id, subtotal, tax_amount,
-               discount_amount, total_amount, items_count, payment_method, status, created_at
+               discount_amount, total_amount, items_count, payment_method, status, created_at,
+               transaction_type, original_transaction_id
         FROM sales_transactions
         WHERE tenant_id = ? AND customer_id = ?
         ORDER BY created_at DESC
@@ -439,6 +793,13 @@ pub async fn get_customer_transactions(
         payment_method: s.payment_method.unwrap_or_default(),
         status: s.status,
         created_at: s.created_at,
+        change_due: None,
+        tenders: None,
+        taxes: None,
+        tax_exempt_certificate: None,
+        prices_include_tax: None,
+        transaction_type: s.transaction_type,
+        original_transaction_id: s.original_transaction_id,
     }).collect();
     
     Ok(HttpResponse::Ok().json(serde_json::json!({
@@ -469,7 +830,8 @@ pub async fn email_receipt(
     let sale = sqlx::query_as::<_, SaleRecord>(
         r#"
         SELECT id, transaction_number, customer_id, subtotal, tax_amount,
-               discount_amount, total_amount, items_count, payment_method, status, created_at
+               discount_amount, total_amount, items_count, payment_method, status, created_at,
+               transaction_type, original_transaction_id
         FROM sales_transactions
         WHERE id = ? AND tenant_id = ?
         "#
@@ -535,6 +897,41 @@ pub async fn email_receipt(
 // Helper Functions
 // ============================================================================
 
+fn quantity_to_f64(quantity: rust_decimal::Decimal) -> f64 {
+    rust_decimal::prelude::ToPrimitive::to_f64(&quantity).unwrap_or_default()
+}
+
+/// Sale with the quantities and tender amounts still available for return
+fn returnable_sale_json(sale: &OriginalSale) -> serde_json::Value {
+    serde_json::json!({
+        "id": sale.id,
+        "transaction_number": sale.transaction_number,
+        "customer_id": sale.customer_id,
+        "status": sale.status,
+        "created_at": sale.created_at,
+        "total_amount": money_to_f64(sale.total),
+        "prices_include_tax": sale.prices_include_tax,
+        "fully_returned": sale.fully_returned(),
+        "lines": sale.lines.iter().map(|line| serde_json::json!({
+            "line_item_id": line.line_item_id,
+            "product_id": line.product_id,
+            "description": line.description,
+            "unit_price": money_to_f64(line.unit_price),
+            "quantity": quantity_to_f64(line.sold.quantity),
+            "returned_quantity": quantity_to_f64(line.returned.quantity),
+            "returnable_quantity": quantity_to_f64(line.returnable_quantity()),
+            "total": money_to_f64(line.sold.total),
+            "returned_total": money_to_f64(line.returned.total),
+        })).collect::<Vec<_>>(),
+        "tenders": sale.tenders.iter().map(|tender| serde_json::json!({
+            "method": tender.method,
+            "reference": tender.reference,
+            "amount": money_to_f64(tender.paid),
+            "refunded_amount": money_to_f64(tender.refunded),
+        })).collect::<Vec<_>>(),
+    })
+}
+
 fn extract_tenant_id(req: &HttpRequest) -> Result<String, ApiError> {
     req.headers()
         .get("X-Tenant-ID")
@@ -559,19 +956,44 @@ fn extract_store_id(req: &HttpRequest) -> Result<String, ApiError> {
         .unwrap_or_else(|| "default".to_string()))
 }
 
-async fn generate_transaction_number(pool: &SqlitePool, tenant_id: &str) -> Result<String, ApiError> {
-    let today = Utc::now().format("%Y%m%d").to_string();
-    
-    // Get count of today's transactions
-    let count: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM sales_transactions WHERE tenant_id = ? AND DATE(created_at) = DATE('now')"
-    )
-    .bind(tenant_id)
-    .fetch_one(pool)
-    .await
-    .unwrap_or(0);
-    
-    Ok(format!("TXN-{}-{:04}", today, count + 1))
+/// Register of the signed-in user, or the X-Station-ID header
+fn extract_station_id(req: &HttpRequest) -> Option<String> {
+    req.extensions()
+        .get::<UserContext>()
+        .and_then(|context| context.station_id.clone())
+        .or_else(|| {
+            req.headers()
+                .get("X-Station-ID")
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string())
+        })
+}
+
+/// The signed-in user at their register, for manager approvals; a request
+/// without a token is the X-User-ID user with no permissions
+fn register_context(req: &HttpRequest) -> Result<UserContext, ApiError> {
+    let signed_in = req.extensions().get::<UserContext>().cloned();
+    let mut context = match signed_in {
+        Some(context) => context,
+        None => UserContext {
+            user_id: extract_user_id(req)?,
+            username: String::new(),
+            role: String::new(),
+            tenant_id: extract_tenant_id(req)?,
+            store_id: Some(extract_store_id(req)?),
+            station_id: None,
+            permissions: Vec::new(),
+        },
+    };
+    context.station_id = extract_station_id(req);
+    Ok(context)
+}
+
+/// Approvals used by a sale or return that then failed can be used again
+async fn release_approvals(overrides: &OverrideService, approvals: &[String]) {
+    if let Err(e) = overrides.release(approvals).await {
+        tracing::warn!(error = %e, "Failed to release override approvals");
+    }
 }
 
 // ============================================================================
@@ -587,6 +1009,9 @@ pub struct ListSalesQuery {
 #[derive(Debug, Deserialize)]
 pub struct VoidSaleRequest {
     pub reason: String,
+    /// Manager approval when the user may not void sales
+    #[serde(default)]
+    pub override_tokens: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -607,12 +1032,24 @@ struct SaleRecord {
     payment_method: Option<String>,
     status: String,
     created_at: String,
+    #[sqlx(default)]
+    tax_exempt_certificate: Option<String>,
+    #[sqlx(default)]
+    prices_include_tax: bool,
+    #[sqlx(default)]
+    transaction_type: Option<String>,
+    #[sqlx(default)]
+    original_transaction_id: Option<String>,
+    #[sqlx(default)]
+    store_id: Option<String>,
 }
 
 #[derive(Debug, sqlx::FromRow)]
 struct LineItemRecord {
     product_id: String,
     quantity: f64,
+    /// Cost of goods charged when the line was sold
+    cost: Option<f64>,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -631,44 +1068,6 @@ struct ReceiptLineItem {
     product_name: Option<String>,
 }
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
-
-/// Get the tax rate for a tenant from localization settings or tax_rules table
-async fn get_tenant_tax_rate(pool: &SqlitePool, tenant_id: &str) -> f64 {
-    // First try to get default tax rule
-    let tax_rule: Option<(f64,)> = sqlx::query_as(
-        "SELECT rate FROM tax_rules WHERE tenant_id = ? AND is_default = 1 LIMIT 1"
-    )
-    .bind(tenant_id)
-    .fetch_optional(pool)
-    .await
-    .ok()
-    .flatten();
-    
-    if let Some((rate,)) = tax_rule {
-        return rate / 100.0; // Convert percentage to decimal
-    }
-    
-    // Fall back to localization settings
-    let localization: Option<(f64,)> = sqlx::query_as(
-        "SELECT tax_rate FROM localization_settings WHERE tenant_id = ?"
-    )
-    .bind(tenant_id)
-    .fetch_optional(pool)
-    .await
-    .ok()
-    .flatten();
-    
-    if let Some((rate,)) = localization {
-        return rate / 100.0; // Convert percentage to decimal
-    }
-    
-    // Default to 13% if no settings found
-    0.13
-}
-
 // ============================================================================
 // Route Configuration
 // ============================================================================
@@ -678,6 +1077,85 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
        .service(get_sale)
        .service(list_sales)
        .service(void_sale)
+       .service(lookup_sale_for_return)
+       .service(create_return)
        .service(get_customer_transactions)
        .service(email_receipt);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::path::Path;
+
+    #[actix_web::test]
+    async fn test_void_restores_gift_card_balance() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        crate::db::migrations::run_migrations_from(&pool, &workspace).await.unwrap();
+
+        // A 25.00 sale paid 20.00 from a gift card and 5.00 in cash
+        for statement in [
+            "INSERT INTO users (id, tenant_id, username, email, password_hash, role)
+             VALUES ('manager1', 't1', 'mia', 'mia@example.com', 'x', 'manager')",
+            "INSERT INTO gift_cards (id, tenant_id, card_number, initial_balance, current_balance, status)
+             VALUES ('gc1', 't1', '4000', 20.0, 0.0, 'Depleted')",
+            "INSERT INTO sales_transactions (id, tenant_id, store_id, employee_id, transaction_number, subtotal, tax_amount,
+             discount_amount, total_amount, items_count, payment_method, status, created_at, updated_at)
+             VALUES ('sale1', 't1', 's1', 'manager1', 'TXN-1', 25.0, 0.0, 0.0, 25.0, 0, 'split', 'completed',
+             '2026-01-01T00:00:00+00:00', '2026-01-01T00:00:00+00:00')",
+            "INSERT INTO sales_payments (id, tenant_id, transaction_id, method, tendered_amount, amount, change_amount,
+             reference, created_at)
+             VALUES ('pay1', 't1', 'sale1', 'gift_card', 20.0, 20.0, 0, '4000', '2026-01-01T00:00:00+00:00'),
+                    ('pay2', 't1', 'sale1', 'cash', 5.0, 5.0, 0, NULL, '2026-01-01T00:00:00+00:00')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::new(Config::default()))
+                .service(void_sale),
+        )
+        .await;
+        let void = || {
+            let req = test::TestRequest::post()
+                .uri("/api/sales/sale1/void")
+                .insert_header(("X-Tenant-ID", "t1"))
+                .insert_header(("X-User-ID", "manager1"))
+                .insert_header(("X-Store-ID", "s1"))
+                .set_json(serde_json::json!({ "reason": "Rang up twice" }))
+                .to_request();
+            req.extensions_mut().insert(UserContext {
+                user_id: "manager1".to_string(),
+                username: "mia".to_string(),
+                role: "manager".to_string(),
+                tenant_id: "t1".to_string(),
+                store_id: Some("s1".to_string()),
+                station_id: None,
+                permissions: vec!["void_sale".to_string()],
+            });
+            req
+        };
+        let resp = test::call_service(&app, void()).await;
+        assert!(resp.status().is_success(), "void failed: {:?}", test::read_body(resp).await);
+
+        // A second void credits nothing
+        let resp = test::call_service(&app, void()).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+        let (balance, status): (f64, String) =
+            sqlx::query_as("SELECT current_balance, status FROM gift_cards WHERE id = 'gc1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((balance, status.as_str()), (20.0, "Active"));
+    }
+}
@@ -333,8 +333,8 @@ pub async fn get_tax_rules(
 ) -> ApiResult<HttpResponse> {
     // First try to get from tax_rules table
     let rules: Vec<TaxRule> = sqlx::query_as::<_, TaxRule>(
-        "SELECT id, name, rate, category, is_default, store_id
-         FROM tax_rules WHERE tenant_id = ? ORDER BY is_default DESC, name ASC"
+        "SELECT id, name, rate, category, is_default, store_id, authority, tax_classes, is_compound
+         FROM tax_rules WHERE tenant_id = ? ORDER BY is_default DESC, store_id ASC, sort_order ASC, name ASC"
     )
     .bind(&context.tenant_id)
     .fetch_all(pool.get_ref())
@@ -381,6 +381,9 @@ struct TaxRule {
     category: Option<String>,
     is_default: bool,
     store_id: String,
+    authority: Option<String>,
+    tax_classes: Option<String>,
+    is_compound: bool,
 }
 
 /// PUT /api/users/me/password
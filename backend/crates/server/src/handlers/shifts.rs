@@ -0,0 +1,272 @@
+/**
+ * Shift Handlers
+ *
+ * Cash drawer shifts per station:
+ * - Open a shift with a float, close it with a blind count by denomination
+ * - Pay-ins and pay-outs during the shift
+ * - X-report (mid-shift) and Z-report (closing) grouped by tender
+ *
+ * The over/short of every close is recorded in the audit log.
+ */
+
+use actix_web::{get, post, web, HttpResponse};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::checkout_service::decimal_from_f64;
+use crate::services::shift_service::{CashMovementType, DenominationCount, OpenShift};
+use crate::services::ShiftService;
+
+// ============================================================================
+// Request Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct DenominationCountRequest {
+    /// Face value of the note or coin, e.g. 20.0 or 0.25
+    pub denomination: f64,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenShiftRequest {
+    /// Defaults to the register of the signed-in user
+    pub station_id: Option<String>,
+    pub opening_float: f64,
+    /// Optional count of the float by denomination
+    #[serde(default)]
+    pub counts: Vec<DenominationCountRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CashMovementRequest {
+    pub movement_type: CashMovementType,
+    pub amount: f64,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloseShiftRequest {
+    /// Blind count of the drawer
+    pub counts: Vec<DenominationCountRequest>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListShiftsQuery {
+    pub store_id: Option<String>,
+    /// open or closed
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CurrentShiftQuery {
+    pub station_id: Option<String>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Open a shift on a station
+///
+/// POST /api/shifts
+#[post("/api/shifts")]
+pub async fn open_shift(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<OpenShiftRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
+    let station_id = station_id(&context, body.station_id)?;
+
+    let shift = ShiftService::new(pool.get_ref().clone())
+        .open(OpenShift {
+            tenant_id: context.tenant_id.clone(),
+            station_id,
+            employee_id: context.user_id.clone(),
+            opening_float: decimal_from_f64(body.opening_float, "opening_float")?,
+            counts: denomination_counts(&body.counts)?,
+        })
+        .await?;
+
+    Ok(HttpResponse::Created().json(shift))
+}
+
+/// GET /api/shifts
+#[get("/api/shifts")]
+pub async fn list_shifts(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<ListShiftsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+
+    let shifts = ShiftService::new(pool.get_ref().clone())
+        .list(&context.tenant_id, query.store_id.as_deref(), query.status.as_deref(), limit)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "shifts": shifts,
+        "total": shifts.len(),
+    })))
+}
+
+/// Open shift of a station
+///
+/// GET /api/shifts/current
+#[get("/api/shifts/current")]
+pub async fn get_current_shift(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<CurrentShiftQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let station_id = station_id(&context, query.into_inner().station_id)?;
+
+    let shift = ShiftService::new(pool.get_ref().clone())
+        .current(&context.tenant_id, &station_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("No open shift on station {station_id}")))?;
+
+    Ok(HttpResponse::Ok().json(shift))
+}
+
+/// GET /api/shifts/{id}
+#[get("/api/shifts/{id}")]
+pub async fn get_shift(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let shift_id = path.into_inner();
+    let service = ShiftService::new(pool.get_ref().clone());
+
+    let shift = service.get(&context.tenant_id, &shift_id).await?;
+    let movements = service.cash_movements(&context.tenant_id, &shift_id).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "shift": shift,
+        "cash_movements": movements,
+    })))
+}
+
+/// Pay cash in or out of the drawer
+///
+/// POST /api/shifts/{id}/cash-movements
+#[post("/api/shifts/{id}/cash-movements")]
+pub async fn record_cash_movement(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<CashMovementRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let movement = ShiftService::new(pool.get_ref().clone())
+        .record_cash_movement(
+            &context.tenant_id,
+            &path.into_inner(),
+            body.movement_type,
+            decimal_from_f64(body.amount, "amount")?,
+            &body.reason,
+            &context.user_id,
+        )
+        .await?;
+
+    Ok(HttpResponse::Created().json(movement))
+}
+
+/// Running totals of an open shift
+///
+/// GET /api/shifts/{id}/x-report
+#[get("/api/shifts/{id}/x-report")]
+pub async fn get_x_report(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let report = ShiftService::new(pool.get_ref().clone())
+        .x_report(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Close a shift with a blind count and produce its Z-report
+///
+/// POST /api/shifts/{id}/close
+#[post("/api/shifts/{id}/close")]
+pub async fn close_shift(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<CloseShiftRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let counts = denomination_counts(&body.counts)?;
+
+    let report = ShiftService::new(pool.get_ref().clone())
+        .close(
+            &context.tenant_id,
+            &path.into_inner(),
+            &context.user_id,
+            &counts,
+            body.notes.as_deref(),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Z-report of a closed shift
+///
+/// GET /api/shifts/{id}/z-report
+#[get("/api/shifts/{id}/z-report")]
+pub async fn get_z_report(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let report = ShiftService::new(pool.get_ref().clone())
+        .z_report(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn station_id(context: &UserContext, requested: Option<String>) -> Result<String, ApiError> {
+    requested
+        .or_else(|| context.station_id.clone())
+        .ok_or_else(|| ApiError::bad_request("station_id is required"))
+}
+
+fn denomination_counts(counts: &[DenominationCountRequest]) -> Result<Vec<DenominationCount>, ApiError> {
+    counts
+        .iter()
+        .map(|count| {
+            Ok(DenominationCount {
+                denomination: decimal_from_f64(count.denomination, "denomination")?.round_dp(2),
+                quantity: count.quantity,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Route Configuration
+// ============================================================================
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(open_shift)
+       .service(list_shifts)
+       .service(get_current_shift)
+       .service(get_shift)
+       .service(record_cash_movement)
+       .service(get_x_report)
+       .service(close_shift)
+       .service(get_z_report);
+}
@@ -0,0 +1,236 @@
+/**
+ * Suspended Sale Handlers
+ *
+ * Park an in-progress sale and serve the next customer:
+ * - Suspend the cart at one register, list parked carts per store or station
+ * - Resume a parked cart on any register of the store
+ * - Park a resumed cart again, or cancel it
+ *
+ * A resumed cart is checked out through `POST /api/sales` with its
+ * `suspended_sale_id`. Parked carts expire after
+ * `sales.suspended_sale_ttl_minutes` (8 hours by default).
+ *
+ * These routes must be registered before `GET /api/sales/{id}`.
+ */
+
+use actix_web::{delete, get, post, put, web, HttpResponse};
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::handlers::sales::SaleLineItem;
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::checkout_service::{decimal_from_f64, CheckoutLine};
+use crate::services::suspended_sale_service::SuspendRequest;
+use crate::services::SuspendedSaleService;
+
+// ============================================================================
+// Request Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct SuspendSaleRequest {
+    pub items: Vec<SaleLineItem>,
+    pub discount_amount: Option<f64>,
+    pub customer_id: Option<String>,
+    /// Short name shown in the parked list
+    pub label: Option<String>,
+    pub notes: Option<String>,
+    /// Defaults to the store and register of the signed-in user
+    pub store_id: Option<String>,
+    pub station_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListSuspendedSalesQuery {
+    pub store_id: Option<String>,
+    pub station_id: Option<String>,
+    /// Also list carts currently open at a register
+    #[serde(default)]
+    pub include_open: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResumeSaleRequest {
+    /// Register taking the cart; defaults to the signed-in user's register
+    pub station_id: Option<String>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Park the current cart
+///
+/// POST /api/sales/suspended
+#[post("/api/sales/suspended")]
+pub async fn suspend_sale(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<SuspendSaleRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let request = suspend_request(&context, body.into_inner())?;
+
+    let sale = SuspendedSaleService::new(pool.get_ref().clone())
+        .suspend(request)
+        .await?;
+
+    Ok(HttpResponse::Created().json(sale))
+}
+
+/// Parked carts of a store, oldest first
+///
+/// GET /api/sales/suspended
+#[get("/api/sales/suspended")]
+pub async fn list_suspended_sales(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<ListSuspendedSalesQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let store_id = store_id(&context, query.store_id.as_ref());
+
+    let sales = SuspendedSaleService::new(pool.get_ref().clone())
+        .list(&context.tenant_id, &store_id, query.station_id.as_deref(), query.include_open)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "store_id": store_id,
+        "total": sales.len(),
+        "suspended_sales": sales,
+    })))
+}
+
+/// Void parked carts past their expiry
+///
+/// POST /api/sales/suspended/expire
+#[post("/api/sales/suspended/expire")]
+pub async fn expire_suspended_sales(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+) -> Result<HttpResponse, ApiError> {
+    let expired = SuspendedSaleService::new(pool.get_ref().clone())
+        .expire_stale(&context.tenant_id, Utc::now())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "expired": expired })))
+}
+
+/// GET /api/sales/suspended/{id}
+#[get("/api/sales/suspended/{id}")]
+pub async fn get_suspended_sale(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let sale = SuspendedSaleService::new(pool.get_ref().clone())
+        .get(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(sale))
+}
+
+/// Take a parked cart to this register
+///
+/// POST /api/sales/suspended/{id}/resume
+#[post("/api/sales/suspended/{id}/resume")]
+pub async fn resume_suspended_sale(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: Option<web::Json<ResumeSaleRequest>>,
+) -> Result<HttpResponse, ApiError> {
+    let station_id = body
+        .and_then(|body| body.into_inner().station_id)
+        .or_else(|| context.station_id.clone());
+
+    let sale = SuspendedSaleService::new(pool.get_ref().clone())
+        .resume(&context.tenant_id, &path.into_inner(), station_id.as_deref(), &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(sale))
+}
+
+/// Park a resumed cart again with its current contents
+///
+/// PUT /api/sales/suspended/{id}
+#[put("/api/sales/suspended/{id}")]
+pub async fn update_suspended_sale(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<SuspendSaleRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let request = suspend_request(&context, body.into_inner())?;
+
+    let sale = SuspendedSaleService::new(pool.get_ref().clone())
+        .suspend_again(&path.into_inner(), request)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(sale))
+}
+
+/// Cancel a suspended cart
+///
+/// DELETE /api/sales/suspended/{id}
+#[delete("/api/sales/suspended/{id}")]
+pub async fn cancel_suspended_sale(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let sale = SuspendedSaleService::new(pool.get_ref().clone())
+        .cancel(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(sale))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn store_id(context: &UserContext, requested: Option<&String>) -> String {
+    requested
+        .or(context.store_id.as_ref())
+        .cloned()
+        .unwrap_or_else(|| "default".to_string())
+}
+
+fn suspend_request(context: &UserContext, body: SuspendSaleRequest) -> Result<SuspendRequest, ApiError> {
+    let mut lines = Vec::with_capacity(body.items.len());
+    for item in &body.items {
+        lines.push(CheckoutLine {
+            product_id: item.product_id.clone(),
+            quantity: decimal_from_f64(item.quantity, "quantity")?,
+            unit_price: decimal_from_f64(item.unit_price, "unit_price")?,
+            discount_amount: decimal_from_f64(item.discount_amount.unwrap_or(0.0), "discount_amount")?,
+        });
+    }
+
+    Ok(SuspendRequest {
+        tenant_id: context.tenant_id.clone(),
+        store_id: store_id(context, body.store_id.as_ref()),
+        station_id: body.station_id.or_else(|| context.station_id.clone()),
+        employee_id: context.user_id.clone(),
+        customer_id: body.customer_id,
+        label: body.label,
+        notes: body.notes,
+        lines,
+        cart_discount: decimal_from_f64(body.discount_amount.unwrap_or(0.0), "discount_amount")?,
+    })
+}
+
+// ============================================================================
+// Route Configuration
+// ============================================================================
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(suspend_sale)
+       .service(list_suspended_sales)
+       .service(expire_suspended_sales)
+       .service(get_suspended_sale)
+       .service(resume_suspended_sale)
+       .service(update_suspended_sale)
+       .service(cancel_suspended_sale);
+}
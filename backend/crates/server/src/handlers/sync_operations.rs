@@ -290,6 +290,180 @@ pub async fn sync_woocommerce_customers(
     }
 }
 
+/// POST /api/sync/clover/run
+/// Trigger a Clover sync into the POS, of every entity type or those asked for
+#[post("/api/sync/clover/run")]
+pub async fn sync_clover(
+    _pool: web::Data<SqlitePool>,
+    orchestrator: web::Data<Arc<SyncOrchestrator>>,
+    user_ctx: web::ReqData<UserContext>,
+    req: web::Json<CloverSyncRequest>,
+) -> impl Responder {
+    use crate::flows::clover_sync::{CUSTOMERS, INVENTORY, ORDERS, PRODUCTS};
+
+    // Products come first so stock levels and order lines can find them
+    let valid_entities = [PRODUCTS, INVENTORY, CUSTOMERS, ORDERS];
+    let entity_types = req
+        .entity_types
+        .clone()
+        .unwrap_or_else(|| valid_entities.iter().map(|e| e.to_string()).collect());
+    if let Some(invalid) = entity_types.iter().find(|e| !valid_entities.contains(&e.as_str())) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Invalid entity type: {}. Valid types: {:?}", invalid, valid_entities)
+        }));
+    }
+    tracing::info!("Triggering Clover sync of {:?}", entity_types);
+
+    let tenant_id = user_ctx.tenant_id.clone();
+    let mut filters = std::collections::HashMap::new();
+    if let Some(store_id) = &req.store_id {
+        filters.insert("store_id".to_string(), store_id.clone());
+    }
+
+    let options = crate::services::sync_orchestrator::SyncOptions {
+        mode: if req.full_sync {
+            crate::services::sync_orchestrator::SyncMode::Full
+        } else {
+            crate::services::sync_orchestrator::SyncMode::Incremental
+        },
+        dry_run: req.dry_run,
+        entity_types: Some(entity_types),
+        date_range: req.date_range.as_ref().map(|dr| crate::services::sync_orchestrator::DateRange {
+            start: dr.start.clone(),
+            end: dr.end.clone(),
+        }),
+        filters,
+    };
+
+    match orchestrator.start_sync(&tenant_id, "clover-to-pos", options).await {
+        Ok(result) => HttpResponse::Ok().json(serde_json::json!({
+            "sync_id": result.sync_id,
+            "status": format!("{:?}", result.status),
+            "records_processed": result.records_processed,
+            "records_created": result.records_created,
+            "records_updated": result.records_updated,
+            "records_failed": result.records_failed,
+            "duration_ms": result.duration_ms,
+            "errors": result.errors.iter().map(|e| serde_json::json!({
+                "entity_type": e.entity_type,
+                "entity_id": e.entity_id,
+                "error": e.error_message
+            })).collect::<Vec<_>>()
+        })),
+        Err(e) => {
+            tracing::error!("Clover sync failed: {:?}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Sync failed: {}", e)
+            }))
+        }
+    }
+}
+
+/// POST /api/sync/shopify/run
+/// Trigger a Shopify sync: a pull into the POS, a push out of it, or both,
+/// of every entity type or those asked for
+///
+/// Each direction only syncs what the credential's sync direction control
+/// allows. Orders are only pulled.
+#[post("/api/sync/shopify/run")]
+pub async fn sync_shopify(
+    _pool: web::Data<SqlitePool>,
+    orchestrator: web::Data<Arc<SyncOrchestrator>>,
+    user_ctx: web::ReqData<UserContext>,
+    req: web::Json<ShopifySyncRequest>,
+) -> impl Responder {
+    use crate::flows::shopify_sync::{INVENTORY, ORDERS, PRODUCTS};
+
+    // Products come first so stock levels and order lines can find them
+    let valid_entities = [PRODUCTS, INVENTORY, ORDERS];
+    let entity_types = req
+        .entity_types
+        .clone()
+        .unwrap_or_else(|| valid_entities.iter().map(|e| e.to_string()).collect());
+    if let Some(invalid) = entity_types.iter().find(|e| !valid_entities.contains(&e.as_str())) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Invalid entity type: {}. Valid types: {:?}", invalid, valid_entities)
+        }));
+    }
+
+    let direction = req.direction.as_deref().unwrap_or("both");
+    let connectors: &[&str] = match direction {
+        "pull" => &["shopify-to-pos"],
+        "push" => &["pos-to-shopify"],
+        "both" => &["shopify-to-pos", "pos-to-shopify"],
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Invalid direction: {}. Valid directions: pull, push, both", direction)
+            }));
+        }
+    };
+    tracing::info!("Triggering Shopify sync ({}) of {:?}", direction, entity_types);
+
+    let tenant_id = user_ctx.tenant_id.clone();
+    let mut filters = std::collections::HashMap::new();
+    if let Some(store_id) = &req.store_id {
+        filters.insert("store_id".to_string(), store_id.clone());
+    }
+
+    let mut results = serde_json::Map::new();
+    for connector_id in connectors {
+        let push = connector_id.starts_with("pos-");
+        let entities: Vec<String> = entity_types
+            .iter()
+            .filter(|e| !(push && e.as_str() == ORDERS))
+            .cloned()
+            .collect();
+        if entities.is_empty() {
+            continue;
+        }
+
+        let options = crate::services::sync_orchestrator::SyncOptions {
+            mode: if req.full_sync {
+                crate::services::sync_orchestrator::SyncMode::Full
+            } else {
+                crate::services::sync_orchestrator::SyncMode::Incremental
+            },
+            dry_run: req.dry_run,
+            entity_types: Some(entities),
+            date_range: req.date_range.as_ref().map(|dr| crate::services::sync_orchestrator::DateRange {
+                start: dr.start.clone(),
+                end: dr.end.clone(),
+            }),
+            filters: filters.clone(),
+        };
+
+        let result = match orchestrator.start_sync(&tenant_id, connector_id, options).await {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::error!("Shopify sync {} failed: {:?}", connector_id, e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": format!("Sync failed: {}", e)
+                }));
+            }
+        };
+
+        results.insert(
+            if push { "push" } else { "pull" }.to_string(),
+            serde_json::json!({
+                "sync_id": result.sync_id,
+                "status": format!("{:?}", result.status),
+                "records_processed": result.records_processed,
+                "records_created": result.records_created,
+                "records_updated": result.records_updated,
+                "records_failed": result.records_failed,
+                "duration_ms": result.duration_ms,
+                "errors": result.errors.iter().map(|e| serde_json::json!({
+                    "entity_type": e.entity_type,
+                    "entity_id": e.entity_id,
+                    "error": e.error_message
+                })).collect::<Vec<_>>()
+            }),
+        );
+    }
+
+    HttpResponse::Ok().json(serde_json::Value::Object(results))
+}
+
 /// GET /api/sync/status
 /// List recent sync runs with status
 #[get("/api/sync/status")]
@@ -997,6 +1171,35 @@ pub struct WooCommerceSyncRequest {
     pub date_range: Option<DateRange>,
 }
 
+#[derive(Deserialize)]
+pub struct CloverSyncRequest {
+    /// Defaults to products, inventory, customers and orders, in that order
+    pub entity_types: Option<Vec<String>>,
+    #[serde(default)]
+    pub full_sync: bool,
+    #[serde(default)]
+    pub dry_run: bool,
+    pub date_range: Option<DateRange>,
+    /// Store that new records go to; the tenant's first store when absent
+    pub store_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ShopifySyncRequest {
+    /// Defaults to products, inventory and orders, in that order
+    pub entity_types: Option<Vec<String>>,
+    /// `pull`, `push` or `both` (the default), pull first
+    pub direction: Option<String>,
+    #[serde(default)]
+    pub full_sync: bool,
+    #[serde(default)]
+    pub dry_run: bool,
+    pub date_range: Option<DateRange>,
+    /// Store whose stock and sales are synced; the tenant's first store
+    /// when absent
+    pub store_id: Option<String>,
+}
+
 #[derive(Deserialize, Serialize, Default)]
 pub struct SyncFilters {
     pub status: Option<Vec<String>>,
@@ -0,0 +1,192 @@
+/**
+ * Tax Configuration Handlers
+ *
+ * Manages jurisdiction-aware sales tax:
+ * - Store tax rules (one rule per authority, e.g. GST + PST or state + county)
+ * - Store tax policy (tax-inclusive prices, per-line or per-invoice rounding)
+ * - Product tax classes (standard, reduced, clothing, grocery, exempt)
+ * - Customer tax exemptions with certificate numbers
+ *
+ * All endpoints require the manage_settings permission.
+ */
+
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::models::{ApiResult, UserContext};
+use crate::services::tax_service::{StoreTaxPolicy, StoreTaxRuleInput, TaxService};
+use pos_core_domain::TaxExemption;
+
+// ============================================================================
+// Request Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ReplaceStoreTaxRulesRequest {
+    /// Rules in the order they are applied; compound rules must follow the
+    /// rules they are charged on
+    pub rules: Vec<StoreTaxRuleInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetTaxClassRequest {
+    pub tax_class: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetTaxExemptionRequest {
+    /// Exemption certificate number; `null` removes the exemption
+    pub certificate_number: Option<String>,
+    /// Authorities the certificate covers; empty means all of them
+    #[serde(default)]
+    pub authorities: Vec<String>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /api/tax/stores/{store_id}/rules
+/// Tax rules of a store, in the order they are applied
+pub async fn get_store_tax_rules(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> ApiResult<HttpResponse> {
+    let store_id = path.into_inner();
+    let service = TaxService::new(pool.get_ref().clone());
+
+    let rules = service.store_rules(&context.tenant_id, &store_id).await?;
+    let jurisdiction = service.jurisdiction_for_store(&context.tenant_id, &store_id).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "store_id": store_id,
+        "rules": rules,
+        "effective_rates": jurisdiction.rates,
+    })))
+}
+
+/// PUT /api/tax/stores/{store_id}/rules
+/// Replace the tax rules of a store; an empty list reverts to the tenant default
+pub async fn replace_store_tax_rules(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<ReplaceStoreTaxRulesRequest>,
+) -> ApiResult<HttpResponse> {
+    let store_id = path.into_inner();
+
+    let rules = TaxService::new(pool.get_ref().clone())
+        .replace_store_rules(&context.tenant_id, &store_id, &body.rules)
+        .await?;
+
+    tracing::info!(
+        tenant_id = %context.tenant_id,
+        store_id = %store_id,
+        rules = rules.len(),
+        "Store tax rules replaced"
+    );
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "store_id": store_id,
+        "rules": rules,
+    })))
+}
+
+/// GET /api/tax/stores/{store_id}/policy
+/// Whether a store's prices include tax and how tax is rounded
+pub async fn get_store_tax_policy(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> ApiResult<HttpResponse> {
+    let store_id = path.into_inner();
+    let service = TaxService::new(pool.get_ref().clone());
+
+    service.require_store(&context.tenant_id, &store_id).await?;
+    let policy = service.policy_for_store(&context.tenant_id, &store_id).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "store_id": store_id,
+        "policy": policy,
+    })))
+}
+
+/// PUT /api/tax/stores/{store_id}/policy
+/// Set whether a store's prices include tax and how tax is rounded
+pub async fn set_store_tax_policy(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<StoreTaxPolicy>,
+) -> ApiResult<HttpResponse> {
+    let store_id = path.into_inner();
+    let policy = body.into_inner();
+
+    TaxService::new(pool.get_ref().clone())
+        .set_store_policy(&context.tenant_id, &store_id, policy)
+        .await?;
+
+    tracing::info!(
+        tenant_id = %context.tenant_id,
+        store_id = %store_id,
+        price_mode = ?policy.price_mode,
+        rounding = ?policy.rounding,
+        "Store tax policy updated"
+    );
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "store_id": store_id,
+        "policy": policy,
+    })))
+}
+
+/// PUT /api/products/{id}/tax-class
+/// Set the tax class of a product
+pub async fn set_product_tax_class(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<SetTaxClassRequest>,
+) -> ApiResult<HttpResponse> {
+    let product_id = path.into_inner();
+    let tax_class = body.tax_class.trim().to_lowercase();
+
+    TaxService::new(pool.get_ref().clone())
+        .set_product_tax_class(&context.tenant_id, &product_id, &tax_class)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "product_id": product_id,
+        "tax_class": tax_class,
+    })))
+}
+
+/// PUT /api/customers/{id}/tax-exemption
+/// Record or remove a customer's tax exemption certificate
+pub async fn set_customer_tax_exemption(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: web::Json<SetTaxExemptionRequest>,
+) -> ApiResult<HttpResponse> {
+    let customer_id = path.into_inner();
+    let body = body.into_inner();
+
+    let exemption = body.certificate_number.map(|certificate_number| TaxExemption {
+        certificate_number,
+        rate_codes: body.authorities,
+    });
+
+    TaxService::new(pool.get_ref().clone())
+        .set_customer_exemption(&context.tenant_id, &customer_id, exemption.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "customer_id": customer_id,
+        "tax_exempt": exemption.is_some(),
+        "certificate_number": exemption.as_ref().map(|e| &e.certificate_number),
+        "authorities": exemption.map(|e| e.rate_codes).unwrap_or_default(),
+    })))
+}
@@ -0,0 +1,194 @@
+/**
+ * Transfer Order Handlers
+ *
+ * Move stock between stores:
+ * - Request products from another store
+ * - Ship a request (stock leaves the sending store and is in transit)
+ * - Receive a shipment, noting short or damaged lines as discrepancies
+ * - Cancel a request before it ships
+ *
+ * Changing a transfer requires the adjust_inventory permission.
+ */
+
+use actix_web::{get, post, web, HttpResponse};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::transfer_service::{LineQuantity, TransferLineRequest, TransferRequest, TransferStatus};
+use crate::services::TransferService;
+
+// ============================================================================
+// Request Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTransferRequest {
+    pub from_store_id: String,
+    /// Defaults to the signed-in user's store
+    pub to_store_id: Option<String>,
+    pub notes: Option<String>,
+    pub lines: Vec<TransferLineRequest>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TransferLinesRequest {
+    /// Lines not listed ship as requested, or arrive as shipped
+    #[serde(default)]
+    pub lines: Vec<LineQuantity>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTransfersQuery {
+    /// Transfers from or to this store
+    pub store_id: Option<String>,
+    pub status: Option<TransferStatus>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Request products from another store
+///
+/// POST /api/transfers
+#[post("/api/transfers")]
+pub async fn create_transfer(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<CreateTransferRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_adjust_inventory(&context)?;
+    let body = body.into_inner();
+
+    let to_store_id = body
+        .to_store_id
+        .or_else(|| context.store_id.clone())
+        .ok_or_else(|| ApiError::bad_request("to_store_id is required"))?;
+
+    let order = TransferService::new(pool.get_ref().clone())
+        .request(TransferRequest {
+            tenant_id: context.tenant_id.clone(),
+            from_store_id: body.from_store_id,
+            to_store_id,
+            requested_by: context.user_id.clone(),
+            notes: body.notes,
+            lines: body.lines,
+        })
+        .await?;
+
+    Ok(HttpResponse::Created().json(order))
+}
+
+/// Transfer orders, newest first
+///
+/// GET /api/transfers
+#[get("/api/transfers")]
+pub async fn list_transfers(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    query: web::Query<ListTransfersQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let orders = TransferService::new(pool.get_ref().clone())
+        .list(&context.tenant_id, query.store_id.as_deref(), query.status)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "total": orders.len(),
+        "transfers": orders,
+    })))
+}
+
+/// GET /api/transfers/{id}
+#[get("/api/transfers/{id}")]
+pub async fn get_transfer(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let order = TransferService::new(pool.get_ref().clone())
+        .get(&context.tenant_id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+/// Ship a requested transfer
+///
+/// POST /api/transfers/{id}/ship
+#[post("/api/transfers/{id}/ship")]
+pub async fn ship_transfer(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: Option<web::Json<TransferLinesRequest>>,
+) -> Result<HttpResponse, ApiError> {
+    require_adjust_inventory(&context)?;
+    let body = body.map(web::Json::into_inner).unwrap_or_default();
+
+    let order = TransferService::new(pool.get_ref().clone())
+        .ship(&context.tenant_id, &path.into_inner(), &context.user_id, &body.lines)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+/// Receive a transfer in transit
+///
+/// POST /api/transfers/{id}/receive
+#[post("/api/transfers/{id}/receive")]
+pub async fn receive_transfer(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+    body: Option<web::Json<TransferLinesRequest>>,
+) -> Result<HttpResponse, ApiError> {
+    require_adjust_inventory(&context)?;
+    let body = body.map(web::Json::into_inner).unwrap_or_default();
+
+    let order = TransferService::new(pool.get_ref().clone())
+        .receive(&context.tenant_id, &path.into_inner(), &context.user_id, &body.lines)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+/// Cancel a transfer that has not shipped
+///
+/// POST /api/transfers/{id}/cancel
+#[post("/api/transfers/{id}/cancel")]
+pub async fn cancel_transfer(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_adjust_inventory(&context)?;
+
+    let order = TransferService::new(pool.get_ref().clone())
+        .cancel(&context.tenant_id, &path.into_inner(), &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn require_adjust_inventory(context: &UserContext) -> Result<(), ApiError> {
+    if context.has_permission("adjust_inventory") {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("Transfers require the adjust_inventory permission"))
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_transfer)
+       .service(list_transfers)
+       .service(get_transfer)
+       .service(ship_transfer)
+       .service(receive_transfer)
+       .service(cancel_transfer);
+}
@@ -0,0 +1,132 @@
+/**
+ * Two-Factor Handlers
+ *
+ * The signed-in user's second factor:
+ * - Enroll with an authenticator app and confirm with a code from it
+ * - Regenerate recovery codes or turn the second factor off, with a code
+ * - Admins reset the second factor of a user who has lost their device
+ *
+ * Sign-in with the second factor is in `handlers::auth`.
+ */
+
+use actix_web::{delete, get, post, web, HttpResponse};
+use sqlx::SqlitePool;
+
+use crate::config::Config;
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::credential_service::CredentialService;
+use crate::services::two_factor_service::CodeRequest;
+use crate::services::TwoFactorService;
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /api/auth/2fa
+#[get("/api/auth/2fa")]
+pub async fn get_two_factor_status(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+) -> Result<HttpResponse, ApiError> {
+    let status = service(&pool)?
+        .status(&context.tenant_id, &context.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(status))
+}
+
+/// Start enrolling: the secret and the provisioning URI to show as a QR code
+///
+/// POST /api/auth/2fa/enroll
+#[post("/api/auth/2fa/enroll")]
+pub async fn begin_two_factor_enrollment(
+    pool: web::Data<SqlitePool>,
+    config: web::Data<Config>,
+    context: web::ReqData<UserContext>,
+) -> Result<HttpResponse, ApiError> {
+    let enrollment = service(&pool)?
+        .begin_enrollment(&context.tenant_id, &context.user_id, &config.store_name)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(enrollment))
+}
+
+/// Confirm enrollment with a code from the app; returns the recovery codes
+///
+/// POST /api/auth/2fa/confirm
+#[post("/api/auth/2fa/confirm")]
+pub async fn confirm_two_factor_enrollment(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<CodeRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let recovery_codes = service(&pool)?
+        .confirm_enrollment(&context.tenant_id, &context.user_id, &body.code)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(recovery_codes))
+}
+
+/// POST /api/auth/2fa/recovery-codes
+#[post("/api/auth/2fa/recovery-codes")]
+pub async fn regenerate_recovery_codes(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<CodeRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let recovery_codes = service(&pool)?
+        .regenerate_recovery_codes(&context.tenant_id, &context.user_id, &body.code)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(recovery_codes))
+}
+
+/// DELETE /api/auth/2fa
+#[delete("/api/auth/2fa")]
+pub async fn disable_two_factor(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    body: web::Json<CodeRequest>,
+) -> Result<HttpResponse, ApiError> {
+    service(&pool)?
+        .disable(&context.tenant_id, &context.user_id, &body.code)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/admin/users/{id}/2fa/reset
+#[post("/api/admin/users/{id}/2fa/reset")]
+pub async fn reset_user_two_factor(
+    pool: web::Data<SqlitePool>,
+    context: web::ReqData<UserContext>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    require_permission(&context, "manage_users")?;
+
+    service(&pool)?.reset(&context, &path.into_inner()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn service(pool: &SqlitePool) -> Result<TwoFactorService, ApiError> {
+    Ok(TwoFactorService::new(pool.clone(), CredentialService::new(pool.clone())?))
+}
+
+fn require_permission(context: &UserContext, permission: &str) -> Result<(), ApiError> {
+    if context.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden(format!("This requires the {permission} permission")))
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_two_factor_status)
+       .service(begin_two_factor_enrollment)
+       .service(confirm_two_factor_enrollment)
+       .service(regenerate_recovery_codes)
+       .service(disable_two_factor)
+       .service(reset_user_two_factor);
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use chrono::Utc;
 use crate::services::audit_logger::AuditLogger;
+use crate::services::role_service::{self, RoleService};
 use crate::models::errors::ApiError;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -78,10 +79,9 @@ fn validate_create_user(req: &CreateUserRequest) -> Result<(), String> {
         return Err("Password must be at least 8 characters".to_string());
     }
 
-    // Role validation
-    let valid_roles = vec!["admin", "manager", "cashier", "inventory"];
-    if !valid_roles.contains(&req.role.as_str()) {
-        return Err(format!("Invalid role. Must be one of: {}", valid_roles.join(", ")));
+    // Role validation; whether the tenant has the role is checked against its roles
+    if req.role.trim().is_empty() {
+        return Err("Role is required".to_string());
     }
 
     // Store requirement for POS roles
@@ -105,6 +105,19 @@ fn validate_create_user(req: &CreateUserRequest) -> Result<(), String> {
     Ok(())
 }
 
+/// Bad request unless the tenant has the role
+async fn check_role_exists(pool: &SqlitePool, tenant_id: &str, role: &str) -> Option<HttpResponse> {
+    match RoleService::new(pool.clone()).role_exists(tenant_id, role).await {
+        Ok(true) => None,
+        Ok(false) => Some(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Unknown role: {}", role)
+        }))),
+        Err(e) => Some(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to check role: {}", e)
+        }))),
+    }
+}
+
 /// Create a new user
 pub async fn create_user(
     pool: web::Data<SqlitePool>,
@@ -118,6 +131,9 @@ pub async fn create_user(
             "error": e
         })));
     }
+    if let Some(response) = check_role_exists(pool.get_ref(), &context.tenant_id, &req.role).await {
+        return Ok(response);
+    }
 
     // Hash password (in production, use bcrypt)
     let password_hash = crate::services::PasswordService::hash_password(&req.password)
@@ -327,6 +343,12 @@ pub async fn update_user(
 ) -> Result<HttpResponse> {
     let user_id = user_id.into_inner();
 
+    if let Some(role) = &req.role {
+        if let Some(response) = check_role_exists(pool.get_ref(), &context.tenant_id, role).await {
+            return Ok(response);
+        }
+    }
+
     // Fetch current user data for audit log
     let current_user = sqlx::query!(
         r#"
@@ -411,6 +433,17 @@ pub async fn update_user(
 
     match result {
         Ok(_) => {
+            // A new role, store or status changes the user's permissions; they sign in again
+            if req.role.is_some() || req.store_id.is_some() || req.is_active.is_some() {
+                let invalidated = match pool.acquire().await {
+                    Ok(mut conn) => role_service::invalidate_user_tokens(&mut conn, &context.tenant_id, &user_id).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = invalidated {
+                    tracing::error!("Failed to invalidate tokens for user {}: {:?}", user_id, e);
+                }
+            }
+
             // Log audit event
             let before_data = serde_json::json!({
                 "username": current_user.username,
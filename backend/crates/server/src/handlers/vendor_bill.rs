@@ -1,5 +1,8 @@
 use crate::models::vendor::{VendorBill, VendorBillLine, VendorSkuAlias};
 use crate::services::{BillIngestService, ReceivingService, UnitConversionService};
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_OPENING_BALANCE, SOURCE_VENDOR_BILL,
+};
 use crate::services::receiving_service::CostPolicy;
 use crate::services::matching_engine::{MatchingEngine, MatchSuggestionsRequest};
 use actix_multipart::Multipart;
@@ -618,6 +621,28 @@ pub async fn create_product_from_line(
         })));
     }
 
+    // Record the starting stock in the inventory ledger
+    let opening = StockMovement::new(
+        &tenant_id,
+        &product_id,
+        MovementType::Adjustment,
+        0.0,
+        REASON_OPENING_BALANCE,
+    )
+    .with_source(SOURCE_VENDOR_BILL, &bill.id)
+    .by_user(&user_id)
+    .at_store(&bill.store_id);
+    let recorded: Result<_, LedgerError> = async {
+        let mut conn = pool.acquire().await?;
+        inventory_ledger_service::set_on_hand(&mut conn, opening, quantity).await
+    }
+    .await;
+    if let Err(e) = recorded {
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to record stock movement: {}", e)
+        })));
+    }
+
     // Update the line item with the matched SKU
     let _ = sqlx::query(
         r#"
@@ -12,8 +12,11 @@ use sqlx::SqlitePool;
 use std::sync::Arc;
 
 use crate::models::ApiError;
-use crate::services::TenantResolver;
+use crate::services::{CredentialService, SyncOrchestrator, TenantResolver};
+use crate::services::sync_orchestrator::{DateRange, SyncMode, SyncOptions};
 use crate::connectors::woocommerce::webhooks::{validate_signature, parse_webhook_event, WebhookEvent, WebhookResource, WebhookEventType};
+use crate::connectors::shopify::oauth::normalize_shop_domain;
+use crate::connectors::shopify::webhooks::{self as shopify_webhooks, WebhookAction};
 use crate::connectors::quickbooks::webhooks::{
     validate_qb_signature, parse_qb_webhook, QBWebhookPayload, QBSyncOperation,
 };
@@ -719,6 +722,184 @@ pub async fn update_webhook_config(
     })))
 }
 
+// ============================================================================
+// Shopify Webhook Handler
+// ============================================================================
+
+/// How far back the sync a Shopify webhook starts looks for changes
+const SHOPIFY_WEBHOOK_SYNC_WINDOW_MINUTES: i64 = 15;
+
+/// Handle incoming Shopify webhook
+///
+/// The body is signed with the app secret. The tenant is the one whose
+/// Shopify credentials are for the sending shop. Product, inventory and
+/// order topics start an incremental pull of that entity type in the
+/// background; `app/uninstalled` deactivates the credentials. Shopify
+/// retries anything but a 2xx, so deliveries for shops no longer connected
+/// and repeat deliveries are acknowledged and dropped.
+pub async fn handle_shopify_webhook(
+    req: HttpRequest,
+    body: web::Bytes,
+    pool: web::Data<SqlitePool>,
+    orchestrator: web::Data<Arc<SyncOrchestrator>>,
+) -> Result<HttpResponse, ApiError> {
+    let signature = header_str(&req, "X-Shopify-Hmac-Sha256")
+        .ok_or_else(|| ApiError::unauthorized("Missing webhook signature"))?;
+
+    // Shopify signs webhooks with the app secret - REQUIRED for security
+    let app_secret = std::env::var("SHOPIFY_API_SECRET")
+        .map_err(|_| ApiError::internal("SHOPIFY_API_SECRET environment variable not configured"))?;
+
+    if !shopify_webhooks::validate_signature(&body, signature, &app_secret)? {
+        tracing::warn!("Invalid Shopify webhook signature received");
+        return Err(ApiError::unauthorized("Invalid webhook signature"));
+    }
+
+    let topic = header_str(&req, "X-Shopify-Topic")
+        .ok_or_else(|| ApiError::bad_request("Missing X-Shopify-Topic header"))?
+        .to_string();
+    let shop = normalize_shop_domain(
+        header_str(&req, "X-Shopify-Shop-Domain").ok_or_else(|| ApiError::bad_request("Missing X-Shopify-Shop-Domain header"))?,
+    )?;
+    let event_id = header_str(&req, "X-Shopify-Webhook-Id")
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let tenant_id: Option<String> = sqlx::query_scalar(
+        "SELECT tenant_id FROM integration_credentials WHERE platform = 'shopify' AND store_url = ? AND is_active = 1",
+    )
+    .bind(&shop)
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(|e| ApiError::internal(format!("Failed to resolve tenant: {}", e)))?;
+
+    let Some(tenant_id) = tenant_id else {
+        tracing::info!("Shopify webhook {} from {}, which is not connected, ignored", topic, shop);
+        return Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "ignored",
+            "message": "Shop is not connected"
+        })));
+    };
+
+    tracing::info!(
+        "Received Shopify webhook: {} (ID: {}) for tenant: {}",
+        topic,
+        event_id,
+        tenant_id
+    );
+
+    let payload: serde_json::Value = serde_json::from_slice(&body).unwrap_or(serde_json::json!({}));
+    let entity_id = payload
+        .get("id")
+        .or_else(|| payload.get("inventory_item_id"))
+        .map(|id| id.to_string());
+    let action = shopify_webhooks::topic_action(&topic);
+    let (entity_type, status) = match &action {
+        WebhookAction::Sync(entity_type) => (Some(*entity_type), "processing"),
+        WebhookAction::Uninstalled => (None, "processing"),
+        WebhookAction::Ignore => (None, "ignored"),
+    };
+
+    // Record receipt; a delivery already recorded is a retry
+    let row_id = uuid::Uuid::new_v4().to_string();
+    let inserted = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO integration_webhook_events (
+            id, tenant_id, platform, event_type, event_id, entity_type, entity_id,
+            payload, signature, signature_verified, status
+        ) VALUES (?, ?, 'shopify', ?, ?, ?, ?, ?, ?, 1, ?)
+        "#
+    )
+    .bind(&row_id)
+    .bind(&tenant_id)
+    .bind(&topic)
+    .bind(&event_id)
+    .bind(entity_type)
+    .bind(&entity_id)
+    .bind(String::from_utf8_lossy(&body).to_string())
+    .bind(signature)
+    .bind(status)
+    .execute(pool.get_ref())
+    .await
+    .map_err(|e| ApiError::internal(format!("Failed to record webhook event: {}", e)))?;
+
+    if inserted.rows_affected() == 0 {
+        tracing::info!("Duplicate Shopify webhook event {} ignored", event_id);
+        return Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "duplicate",
+            "message": "Event already processed"
+        })));
+    }
+
+    match action {
+        WebhookAction::Sync(entity_type) => {
+            let now = chrono::Utc::now();
+            let options = SyncOptions {
+                mode: SyncMode::Incremental,
+                dry_run: false,
+                entity_types: Some(vec![entity_type.to_string()]),
+                date_range: Some(DateRange {
+                    start: (now - chrono::Duration::minutes(SHOPIFY_WEBHOOK_SYNC_WINDOW_MINUTES)).to_rfc3339(),
+                    end: now.to_rfc3339(),
+                }),
+                filters: std::collections::HashMap::new(),
+            };
+            let orchestrator = orchestrator.get_ref().clone();
+            let pool = pool.get_ref().clone();
+
+            // Shopify expects an answer within seconds; the sync runs after it
+            tokio::spawn(async move {
+                let outcome = orchestrator.start_sync(&tenant_id, "shopify-to-pos", options).await;
+                if let Err(e) = &outcome {
+                    tracing::warn!("Sync for Shopify webhook {} failed: {}", row_id, e);
+                }
+                let _ = mark_shopify_webhook_event(&pool, &row_id, outcome.err()).await;
+            });
+        }
+        WebhookAction::Uninstalled => {
+            // The shop's token stopped working with the uninstall
+            let credential_service = CredentialService::new(pool.get_ref().clone())?;
+            credential_service.delete_credentials(&tenant_id, "shopify").await?;
+            tracing::info!("Shopify app uninstalled from {} (tenant: {})", shop, tenant_id);
+            mark_shopify_webhook_event(pool.get_ref(), &row_id, None).await?;
+        }
+        WebhookAction::Ignore => {}
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "success",
+        "message": "Webhook received"
+    })))
+}
+
+fn header_str<'a>(req: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    req.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Record how handling a Shopify webhook event ended
+async fn mark_shopify_webhook_event(
+    pool: &SqlitePool,
+    id: &str,
+    error: Option<String>,
+) -> Result<(), ApiError> {
+    sqlx::query(
+        r#"
+        UPDATE integration_webhook_events
+        SET status = ?, error_message = ?, processed_at = ?
+        WHERE id = ?
+        "#
+    )
+    .bind(if error.is_some() { "failed" } else { "processed" })
+    .bind(&error)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(|e| ApiError::internal(format!("Failed to update webhook event: {}", e)))?;
+
+    Ok(())
+}
+
 // ============================================================================
 // Route Configuration
 // ============================================================================
@@ -729,6 +910,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .route("/woocommerce", web::post().to(handle_woocommerce_webhook))
             .route("/quickbooks", web::post().to(handle_quickbooks_webhook))
             .route("/quickbooks/cloudevents", web::post().to(handle_quickbooks_cloudevents))
+            .route("/shopify", web::post().to(handle_shopify_webhook))
             .route("/config", web::get().to(get_webhook_config))
             .route("/config", web::put().to(update_webhook_config))
     );
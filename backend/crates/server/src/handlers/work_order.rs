@@ -8,6 +8,7 @@ use crate::models::{
     CreateWorkOrderLineRequest, CreateWorkOrderRequest, UpdateWorkOrderRequest, WorkOrder,
     WorkOrderLine, WorkOrderLineType, WorkOrderResponse, WorkOrderStatus,
 };
+use crate::services::FitmentService;
 
 /// Generate unique work order number
 fn generate_work_order_number() -> String {
@@ -32,8 +33,8 @@ pub async fn create_work_order(
     let result = sqlx::query(
         "INSERT INTO work_orders (id, tenant_id, work_order_number, customer_id, vehicle_id, status, 
          description, estimated_total, labor_total, parts_total, created_at, updated_at, 
-         assigned_technician_id, is_warranty, sync_version, store_id)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0.0, 0.0, ?, ?, ?, ?, 0, ?)",
+         assigned_technician_id, is_warranty, sync_version, store_id, odometer)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0.0, 0.0, ?, ?, ?, ?, 0, ?, ?)",
     )
     .bind(&work_order_id)
     .bind(&get_current_tenant_id())
@@ -48,6 +49,7 @@ pub async fn create_work_order(
     .bind(&req.assigned_technician_id)
     .bind(is_warranty_int)
     .bind(&req.store_id)
+    .bind(req.odometer)
     .execute(pool.get_ref())
     .await;
 
@@ -55,7 +57,19 @@ pub async fn create_work_order(
         Ok(_) => {
             tracing::info!("Work order created successfully: {}", work_order_number);
             match get_work_order_with_lines(pool.get_ref(), &work_order_id).await {
-                Ok(work_order) => HttpResponse::Created().json(work_order),
+                Ok(mut work_order) => {
+                    // Suggest the scheduled maintenance the vehicle is due for
+                    if let Some(vehicle_id) = &req.vehicle_id {
+                        match FitmentService::new(pool.get_ref().clone())
+                            .due_maintenance(&get_current_tenant_id(), vehicle_id, req.odometer)
+                            .await
+                        {
+                            Ok(due) => work_order.due_maintenance = due,
+                            Err(e) => tracing::warn!("Failed to check due maintenance for {}: {}", vehicle_id, e),
+                        }
+                    }
+                    HttpResponse::Created().json(work_order)
+                }
                 Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
                     "error": "Work order created but failed to fetch"
                 })),
@@ -351,7 +365,7 @@ pub async fn list_work_orders(
 
     let mut sql = "SELECT id, tenant_id, work_order_number, customer_id, vehicle_id, status, description, 
                    estimated_total, actual_total, labor_total, parts_total, created_at, updated_at, 
-                   completed_at, invoiced_at, assigned_technician_id, is_warranty, sync_version, store_id 
+                   completed_at, invoiced_at, assigned_technician_id, is_warranty, sync_version, store_id, odometer 
                    FROM work_orders WHERE tenant_id = ?".to_string();
 
     let mut bindings: Vec<String> = vec![get_current_tenant_id()];
@@ -400,7 +414,7 @@ async fn get_work_order_by_id(pool: &SqlitePool, id: &str) -> Result<WorkOrder,
     sqlx::query_as::<_, WorkOrder>(
         "SELECT id, tenant_id, work_order_number, customer_id, vehicle_id, status, description, 
          estimated_total, actual_total, labor_total, parts_total, created_at, updated_at, 
-         completed_at, invoiced_at, assigned_technician_id, is_warranty, sync_version, store_id 
+         completed_at, invoiced_at, assigned_technician_id, is_warranty, sync_version, store_id, odometer 
          FROM work_orders 
          WHERE id = ? AND tenant_id = ?",
     )
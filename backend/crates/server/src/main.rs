@@ -172,6 +172,23 @@ async fn main() -> std::io::Result<()> {
         tracing::info!("Sync scheduler started");
     }
 
+    // Start outbound webhook delivery worker
+    match services::CredentialService::new(pool.clone()) {
+        Ok(credentials) => {
+            services::OutboundWebhookService::new(pool.clone(), credentials).start();
+        }
+        Err(e) => tracing::warn!("Failed to start webhook delivery worker: {}", e),
+    }
+
+    // Start WooCommerce stock push worker
+    match services::CredentialService::new(pool.clone()) {
+        Ok(credentials) => {
+            services::sync_queue_processor::SyncQueueProcessor::new(pool.clone())
+                .start_stock_push_worker(credentials);
+        }
+        Err(e) => tracing::warn!("Failed to start stock push worker: {}", e),
+    }
+
     // Initialize tenant resolver
     let tenant_resolver = std::sync::Arc::new(services::TenantResolver::new(pool.clone()));
     tracing::info!("Tenant resolver initialized");
@@ -271,6 +288,9 @@ async fn main() -> std::io::Result<()> {
             .service(handlers::config::get_capabilities)
             // Authentication endpoints
             .service(handlers::auth::login)
+            .service(handlers::auth::login_enroll)
+            .service(handlers::auth::login_verify)
+            .service(handlers::auth::station_login)
             .service(handlers::auth::logout)
             // Note: get_current_user is registered BEFORE ContextExtractor to handle unauthenticated requests properly
             // Stats endpoints (dashboard data)
@@ -486,6 +506,7 @@ async fn main() -> std::io::Result<()> {
             .service(handlers::reporting::get_sales_by_category)
             .service(handlers::reporting::get_sales_by_employee)
             .service(handlers::reporting::get_sales_by_tier)
+            .service(handlers::reporting::get_margin_report)
             .service(handlers::reporting::get_customer_report)
             .service(handlers::reporting::get_employee_report)
             .service(handlers::reporting::get_layaway_report)
@@ -569,6 +590,8 @@ async fn main() -> std::io::Result<()> {
             .service(handlers::sync_operations::sync_woocommerce_orders)
             .service(handlers::sync_operations::sync_woocommerce_products)
             .service(handlers::sync_operations::sync_woocommerce_customers)
+            .service(handlers::sync_operations::sync_clover)
+            .service(handlers::sync_operations::sync_shopify)
             .service(handlers::sync_operations::list_sync_status)
             .service(handlers::sync_operations::get_sync_status)
             .service(handlers::sync_operations::retry_failed_records)
@@ -666,6 +689,29 @@ async fn main() -> std::io::Result<()> {
                 // Wrap all settings routes with permission check
                 cfg.default_service(web::to(|| async { HttpResponse::Forbidden().finish() }));
             })
+            // Tax configuration endpoints (protected with manage_settings permission)
+            .service(
+                web::resource("/api/tax/stores/{store_id}/rules")
+                    .route(web::get().to(handlers::tax::get_store_tax_rules))
+                    .route(web::put().to(handlers::tax::replace_store_tax_rules))
+                    .wrap(require_permission("manage_settings"))
+            )
+            .service(
+                web::resource("/api/tax/stores/{store_id}/policy")
+                    .route(web::get().to(handlers::tax::get_store_tax_policy))
+                    .route(web::put().to(handlers::tax::set_store_tax_policy))
+                    .wrap(require_permission("manage_settings"))
+            )
+            .service(
+                web::resource("/api/products/{id}/tax-class")
+                    .route(web::put().to(handlers::tax::set_product_tax_class))
+                    .wrap(require_permission("manage_settings"))
+            )
+            .service(
+                web::resource("/api/customers/{id}/tax-exemption")
+                    .route(web::put().to(handlers::tax::set_customer_tax_exemption))
+                    .wrap(require_permission("manage_settings"))
+            )
             // Feature flags endpoints (protected with manage_settings permission)
             .service(
                 web::resource("/api/feature-flags")
@@ -888,6 +934,28 @@ async fn main() -> std::io::Result<()> {
             .configure(handlers::file_operations::configure)
             // Receiving operations (inventory receiving)
             .configure(handlers::receiving_operations::configure)
+            // Inventory movement ledger (history, stock as of a date, cache rebuild) and stock per store
+            .configure(handlers::inventory::configure)
+            // Cycle counts and physical inventories
+            .configure(handlers::counts::configure)
+            // Transfer orders between stores
+            .configure(handlers::transfers::configure)
+            // Purchase orders, reorder suggestions and three-way bill match
+            .configure(handlers::purchase_orders::configure)
+            // Vehicle fitment lookup and import, due maintenance on vehicles
+            .configure(handlers::fitment::configure)
+            // Roles, permissions and per-store role assignments
+            .configure(handlers::roles::configure)
+            // Manager approvals (PIN/badge) for price overrides, discounts, returns and voids
+            .configure(handlers::overrides::configure)
+            // Two-factor enrollment, recovery codes and admin reset
+            .configure(handlers::two_factor::configure)
+            // Outbound webhook subscriptions, delivery log and redelivery
+            .configure(handlers::outbound_webhooks::configure)
+            // Shifts and cash drawer reconciliation (X/Z reports)
+            .configure(handlers::shifts::configure)
+            // Suspended sales (parked carts); before sales so /api/sales/{id} does not match them
+            .configure(handlers::suspended_sales::configure)
             // Sales operations (POS checkout)
             .configure(handlers::sales::configure)
             // Tenant operations (multi-tenant context and configuration)
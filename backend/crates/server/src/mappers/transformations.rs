@@ -7,6 +7,8 @@
  * - split: Split strings
  * - lookup: Resolve IDs via mapping table
  * - String operations: uppercase, lowercase, trim, replace
+ * - centsToUnits: Minor currency units (e.g. Clover prices) to decimal
+ * - first: First element of an array path
  * 
  * Requirements: 3.4
  */
@@ -45,6 +47,8 @@ impl TransformationRegistry {
             "uppercase" => TransformationFunctions::uppercase(&value),
             "lowercase" => TransformationFunctions::lowercase(&value),
             "trim" => TransformationFunctions::trim(&value),
+            "centsToUnits" => TransformationFunctions::cents_to_units(&value),
+            "first" => TransformationFunctions::first(&value),
             _ => {
                 // For transformations that need parameters, parse the name
                 if transform_name.starts_with("dateFormat(") {
@@ -250,6 +254,25 @@ impl TransformationFunctions {
         Ok(Value::String(string.replace(from, to)))
     }
 
+    /// Convert an amount in cents to a decimal amount; null stays null
+    pub fn cents_to_units(value: &Value) -> Result<Value, String> {
+        if value.is_null() {
+            return Ok(Value::Null);
+        }
+        let cents = value.as_f64()
+            .ok_or_else(|| "Value is not a number".to_string())?;
+        Ok(serde_json::json!(cents / 100.0))
+    }
+
+    /// Take the first element of an array; null or an empty array is null
+    pub fn first(value: &Value) -> Result<Value, String> {
+        match value {
+            Value::Null => Ok(Value::Null),
+            Value::Array(items) => Ok(items.first().cloned().unwrap_or(Value::Null)),
+            _ => Err("Value is not an array".to_string()),
+        }
+    }
+
     /// Lookup QuickBooks Customer ID by email
     pub fn lookup_qbo_customer(
         email: &Value,
@@ -394,6 +417,20 @@ mod tests {
         assert_eq!(result.unwrap(), json!("hello world"));
     }
 
+    #[test]
+    fn test_cents_to_units() {
+        assert_eq!(TransformationFunctions::cents_to_units(&json!(1299)).unwrap(), json!(12.99));
+        assert_eq!(TransformationFunctions::cents_to_units(&Value::Null).unwrap(), Value::Null);
+        assert!(TransformationFunctions::cents_to_units(&json!("12.99")).is_err());
+    }
+
+    #[test]
+    fn test_first() {
+        assert_eq!(TransformationFunctions::first(&json!(["Coffee", "Hot"])).unwrap(), json!("Coffee"));
+        assert_eq!(TransformationFunctions::first(&json!([])).unwrap(), Value::Null);
+        assert_eq!(TransformationFunctions::first(&Value::Null).unwrap(), Value::Null);
+    }
+
     #[test]
     fn test_replace() {
         let value = json!("hello world");
@@ -2,19 +2,26 @@ use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
     Error, HttpMessage,
 };
+use sqlx::SqlitePool;
 use std::future::{ready, Ready};
 use std::pin::Pin;
+use std::rc::Rc;
 
 use crate::auth::jwt::{validate_token, JwtError};
 use crate::config::Config;
 use crate::models::UserContext;
+use crate::services::{role_service, station_login_service};
 
 /// Middleware to extract and validate user context from JWT token
+///
+/// When the database pool is available, tokens issued before the user's
+/// roles or permissions last changed are treated like expired ones, and so
+/// are station PIN/badge sign-ins whose session has ended or gone idle.
 pub struct ContextExtractor;
 
 impl<S, B> Transform<S, ServiceRequest> for ContextExtractor
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -25,17 +32,17 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(ContextExtractorMiddleware { service }))
+        ready(Ok(ContextExtractorMiddleware { service: Rc::new(service) }))
     }
 }
 
 pub struct ContextExtractorMiddleware<S> {
-    service: S,
+    service: Rc<S>,
 }
 
 impl<S, B> Service<ServiceRequest> for ContextExtractorMiddleware<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -110,24 +117,53 @@ where
             }
         };
 
-        // Create UserContext from claims
-        let context = UserContext::from_claims(claims);
+        let pool = req.app_data::<actix_web::web::Data<SqlitePool>>().cloned();
+        let service = Rc::clone(&self.service);
 
-        // Validate context (e.g., check store assignment for POS roles)
-        if let Err(e) = context.validate() {
-            tracing::warn!("Context validation failed: {}", e);
-            return Box::pin(async move {
-                Err(actix_web::error::ErrorForbidden(e))
-            });
-        }
+        Box::pin(async move {
+            // Tokens issued before a role, assignment or user change carry stale
+            // permissions; continue without context so the user signs in again
+            if let Some(pool) = pool {
+                match role_service::token_is_current(pool.get_ref(), &claims.tenant_id, &claims.sub, claims.issued_at()).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        tracing::debug!("Token issued before the user's permissions changed, continuing without context");
+                        return service.call(req).await;
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to check token against user permissions: {:?}", e);
+                        return service.call(req).await;
+                    }
+                }
+
+                if claims.login_method.is_some() {
+                    match station_login_service::session_is_active(pool.get_ref(), &token).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            tracing::debug!("Station session ended or idle, continuing without context");
+                            return service.call(req).await;
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to check station session: {:?}", e);
+                            return service.call(req).await;
+                        }
+                    }
+                }
+            }
 
-        // Inject context into request extensions
-        req.extensions_mut().insert(context);
+            // Create UserContext from claims
+            let context = UserContext::from_claims(claims);
 
-        let fut = self.service.call(req);
-        Box::pin(async move {
-            let res = fut.await?;
-            Ok(res)
+            // Validate context (e.g., check store assignment for POS roles)
+            if let Err(e) = context.validate() {
+                tracing::warn!("Context validation failed: {}", e);
+                return Err(actix_web::error::ErrorForbidden(e));
+            }
+
+            // Inject context into request extensions
+            req.extensions_mut().insert(context);
+
+            service.call(req).await
         })
     }
 }
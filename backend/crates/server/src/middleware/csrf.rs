@@ -144,12 +144,13 @@ where
         let path = req.path();
         
         // Public endpoints that don't need CSRF protection:
-        // - Login (no session yet)
+        // - Login and its second-factor steps (no session yet)
         // - Webhooks (authenticated by signature)
         // - Health checks
         // - Fresh install endpoints
         let exempt_paths = [
             "/auth/login",
+            "/auth/station-login",
             "/api/auth/login",
             "/health",
             "/api/webhooks/",
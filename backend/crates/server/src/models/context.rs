@@ -17,8 +17,14 @@ pub struct UserContext {
 
 impl UserContext {
     /// Create UserContext from JWT claims
+    ///
+    /// Tokens carry the permissions resolved from the tenant's roles at
+    /// sign-in, even when that is none; only a token that never resolved
+    /// them gets the role's built-in permissions.
     pub fn from_claims(claims: Claims) -> Self {
-        let permissions = get_permissions_for_role(&claims.role);
+        let permissions = claims
+            .permissions
+            .unwrap_or_else(|| get_permissions_for_role(&claims.role));
         
         Self {
             user_id: claims.sub,
@@ -80,7 +86,9 @@ mod tests {
             station_id,
             exp: 9999999999, // Far future
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         }
     }
     
@@ -171,7 +179,28 @@ mod tests {
         assert!(context.has_permission("manage_settings"));
         assert!(!context.has_permission("nonexistent_permission"));
     }
-    
+
+    #[test]
+    fn test_permissions_from_token_replace_role_defaults() {
+        let mut claims = create_test_claims(
+            "user-2",
+            "lead1",
+            "shift_lead",
+            crate::test_constants::TEST_TENANT_ID,
+            Some("store-1".to_string()),
+            None,
+        );
+        claims.permissions = Some(vec!["access_sell".to_string(), "apply_discount".to_string()]);
+        let context = UserContext::from_claims(claims.clone());
+
+        assert!(context.has_permission("apply_discount"));
+        assert!(!context.has_permission("process_return"));
+
+        // Resolved to none is not the same as never resolved
+        claims.permissions = Some(vec![]);
+        assert!(UserContext::from_claims(claims).permissions.is_empty());
+    }
+
     #[test]
     fn test_validate_admin_without_store() {
         let claims = create_test_claims("user-1", "admin", "admin", crate::test_constants::TEST_TENANT_ID, None, None);
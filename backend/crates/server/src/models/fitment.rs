@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A range of vehicles a product fits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct VehicleFitment {
+    pub id: String,
+    pub product_id: String,
+    pub make: String,
+    pub model: String,
+    pub year_start: i64,
+    pub year_end: i64,
+    /// `None` fits every engine
+    pub engine: Option<String>,
+    pub trim: Option<String>,
+    pub notes: Option<String>,
+    /// manual, csv or aces
+    pub source: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateFitmentRequest {
+    pub make: String,
+    pub model: String,
+    pub year_start: i64,
+    /// Defaults to `year_start`
+    pub year_end: Option<i64>,
+    pub engine: Option<String>,
+    pub trim: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// A vehicle to find parts for
+#[derive(Debug, Clone, Deserialize)]
+pub struct VehicleQuery {
+    pub year: i64,
+    pub make: String,
+    pub model: String,
+    pub engine: Option<String>,
+    pub trim: Option<String>,
+}
+
+/// A product that fits the vehicle looked up
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct CompatibleProduct {
+    pub product_id: String,
+    pub sku: String,
+    pub name: String,
+    pub category: String,
+    pub unit_price: f64,
+    pub quantity_on_hand: f64,
+    pub fitment_id: String,
+    pub engine: Option<String>,
+    pub trim: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct MaintenanceSchedule {
+    pub id: String,
+    pub make: String,
+    pub model: Option<String>,
+    pub year_start: Option<i64>,
+    pub year_end: Option<i64>,
+    pub service_type: String,
+    pub description: String,
+    pub mileage_interval: Option<i64>,
+    pub time_interval_months: Option<i64>,
+    /// high, medium or low
+    pub priority: String,
+}
+
+/// Why a scheduled service is due
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DueReason {
+    /// Never done on this vehicle
+    NotRecorded,
+    /// Mileage interval passed since it was last done
+    Mileage,
+    /// Time interval passed since it was last done
+    Time,
+}
+
+/// A scheduled service due on a vehicle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DueMaintenance {
+    pub schedule_id: String,
+    pub service_type: String,
+    pub description: String,
+    pub priority: String,
+    pub reason: DueReason,
+    pub last_performed_at: Option<String>,
+    pub last_odometer: Option<i64>,
+    /// Distance driven since it was last done, when both readings are known
+    pub distance_since: Option<i64>,
+}
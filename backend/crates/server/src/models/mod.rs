@@ -10,6 +10,7 @@ pub mod credit;
 pub mod customer;
 pub mod errors;
 pub mod external_entities;
+pub mod fitment;
 pub mod gift_card;
 pub mod layaway;
 pub mod lexicon;
@@ -51,6 +52,10 @@ pub use external_entities::{
     OrderStatus, PaymentStatus, ProductType, DiscountType,
     Address, LineItem, TaxLine, ShippingLine, Discount,
 };
+pub use fitment::{
+    CompatibleProduct, CreateFitmentRequest, DueMaintenance, DueReason, MaintenanceSchedule,
+    VehicleFitment, VehicleQuery,
+};
 pub use gift_card::{GiftCard, GiftCardStatus, GiftCardTransactionType, IssueGiftCardRequest, RedeemGiftCardRequest, ReloadGiftCardRequest};
 pub use settings::{
     LocalizationSettings, NetworkSettings, PerformanceSettings, UserPreferences,
@@ -83,7 +88,8 @@ pub use station::{CreateStationRequest, Station, UpdateStationRequest};
 pub use store::{CreateStoreRequest, Store, UpdateStoreRequest};
 pub use sync::{AuditLog as SyncAuditLog, ConflictResolution, CreateAuditLog, CreateSyncQueueItem, SyncConflict, SyncQueueItem, SyncState, SyncStats};
 pub use user::{
-    LoginRequest, LoginResponse, User, UserResponse,
+    LoginEnrollRequest, LoginRequest, LoginResponse, LoginVerifyRequest, StationLoginRequest,
+    StationLoginResponse, TwoFactorChallengeResponse, User, UserResponse,
 };
 #[allow(unused_imports)]
 pub use validation::{FlagSeverity, FixAction, HardRule, SoftRule, SuggestedFix, ToleranceConfig, ValidationEngine, ValidationFlag, ValidationResult};
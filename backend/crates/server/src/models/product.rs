@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use sqlx::FromRow;
 
+use super::fitment::VehicleFitment;
+
 /// Product model with dynamic attributes support
 /// Supports configuration-driven product catalogs for any business type
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -155,6 +157,9 @@ pub struct ProductResponse {
     pub updated_at: String,
     pub profit_margin: f64,
     pub profit_amount: f64,
+    /// Vehicles the product fits; filled in on the product detail
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fitment: Vec<VehicleFitment>,
 }
 
 impl From<Product> for ProductResponse {
@@ -188,6 +193,7 @@ impl From<Product> for ProductResponse {
             updated_at: product.updated_at,
             profit_margin,
             profit_amount,
+            fitment: Vec::new(),
         }
     }
 }
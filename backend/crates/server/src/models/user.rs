@@ -49,6 +49,51 @@ pub struct LoginResponse {
     pub token: String,
     pub user: UserResponse,
     pub expires_at: DateTime<Utc>,
+    /// Shown once, when the sign-in completed a two-factor enrollment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+/// The password was accepted; the token is issued once the second factor
+/// is verified with `POST /auth/login/verify`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorChallengeResponse {
+    pub two_factor_required: bool,
+    /// The user's role requires a second factor they have not set up; they
+    /// enroll with `POST /auth/login/enroll` before verifying
+    pub setup_required: bool,
+    pub challenge_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginEnrollRequest {
+    pub challenge_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginVerifyRequest {
+    pub challenge_token: String,
+    /// A code from the authenticator app, or a recovery code
+    pub code: String,
+}
+
+/// Quick sign-in at a register by PIN or badge scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StationLoginRequest {
+    pub station_id: String,
+    pub pin: Option<String>,
+    pub badge: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StationLoginResponse {
+    pub token: String,
+    pub user: UserResponse,
+    pub expires_at: DateTime<Utc>,
+    pub station_id: String,
+    /// The session ends after this long without a request
+    pub idle_timeout_minutes: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,7 +132,11 @@ impl From<User> for UserResponse {
     }
 }
 
-/// Get permissions for a given role
+/// Roles every tenant has; their permissions start out as
+/// [`get_permissions_for_role`] and can then be changed in the database
+pub const SYSTEM_ROLES: [&str; 6] = ["admin", "manager", "cashier", "specialist", "inventory_clerk", "technician"];
+
+/// Get the built-in permissions for a given role
 pub fn get_permissions_for_role(role: &str) -> Vec<String> {
     match role {
         "admin" => vec![
@@ -97,8 +146,11 @@ pub fn get_permissions_for_role(role: &str) -> Vec<String> {
             "apply_discount".to_string(),
             "override_price".to_string(),
             "process_return".to_string(),
+            "return_without_receipt".to_string(),
+            "void_sale".to_string(),
             "receive_stock".to_string(),
             "adjust_inventory".to_string(),
+            "approve_inventory_counts".to_string(),
             "manage_users".to_string(),
             "manage_settings".to_string(),
             "view_audit_logs".to_string(),
@@ -109,8 +161,11 @@ pub fn get_permissions_for_role(role: &str) -> Vec<String> {
             "apply_discount".to_string(),
             "override_price".to_string(),
             "process_return".to_string(),
+            "return_without_receipt".to_string(),
+            "void_sale".to_string(),
             "receive_stock".to_string(),
             "adjust_inventory".to_string(),
+            "approve_inventory_counts".to_string(),
             "view_audit_logs".to_string(),
         ],
         "cashier" => vec!["access_sell".to_string(), "process_return".to_string()],
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use super::fitment::DueMaintenance;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum WorkOrderStatus {
     Created,
@@ -87,6 +89,9 @@ pub struct WorkOrder {
     pub is_warranty: bool,
     pub sync_version: i64,
     pub store_id: String,
+    /// Odometer reading when the vehicle came in
+    #[sqlx(default)]
+    pub odometer: Option<i64>,
 }
 
 impl WorkOrder {
@@ -133,6 +138,9 @@ pub struct CreateWorkOrderRequest {
     pub assigned_technician_id: Option<String>,
     pub is_warranty: bool,
     pub store_id: String,
+    /// Odometer reading when the vehicle came in
+    #[serde(default)]
+    pub odometer: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -171,7 +179,12 @@ pub struct WorkOrderResponse {
     pub invoiced_at: Option<String>,
     pub assigned_technician_id: Option<String>,
     pub is_warranty: bool,
+    pub odometer: Option<i64>,
     pub lines: Vec<WorkOrderLine>,
+    /// Scheduled services the vehicle is due for, suggested when the work
+    /// order is opened
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub due_maintenance: Vec<DueMaintenance>,
 }
 
 impl From<WorkOrder> for WorkOrderResponse {
@@ -193,7 +206,9 @@ impl From<WorkOrder> for WorkOrderResponse {
             invoiced_at: work_order.invoiced_at,
             assigned_technician_id: work_order.assigned_technician_id,
             is_warranty: work_order.is_warranty,
+            odometer: work_order.odometer,
             lines: Vec::new(),
+            due_maintenance: Vec::new(),
         }
     }
 }
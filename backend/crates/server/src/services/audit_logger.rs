@@ -1,8 +1,16 @@
 use chrono::Utc;
 use serde_json::Value;
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
+const INSERT_ENTRY: &str = r#"
+    INSERT INTO audit_log (
+        id, entity_type, entity_id, operation, user_id, employee_id,
+        changes, ip_address, user_agent, is_offline, created_at, store_id
+    )
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
 /// Audit logger service
 /// Logs all operations for compliance and debugging
 pub struct AuditLogger {
@@ -32,15 +40,7 @@ impl AuditLogger {
         let now = Utc::now().to_rfc3339();
         let changes_str = changes.map(|c| serde_json::to_string(&c).ok()).flatten();
 
-        sqlx::query(
-            r#"
-            INSERT INTO audit_log (
-                id, entity_type, entity_id, operation, user_id, employee_id,
-                changes, ip_address, user_agent, is_offline, created_at, store_id
-            )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#
-        )
+        sqlx::query(INSERT_ENTRY)
         .bind(&id)
         .bind(entity_type)
         .bind(entity_id)
@@ -202,6 +202,47 @@ impl AuditLogger {
         .await
     }
 
+    /// Log the over/short of a cash drawer count at shift close
+    ///
+    /// Written on the caller's connection so the entry commits together
+    /// with the close.
+    pub async fn log_cash_variance(
+        conn: &mut SqliteConnection,
+        shift_id: &str,
+        station_id: &str,
+        expected: &str,
+        counted: &str,
+        variance: &str,
+        employee_id: &str,
+        store_id: &str,
+    ) -> Result<String, sqlx::Error> {
+        let id = Uuid::new_v4().to_string();
+        let changes = serde_json::json!({
+            "station_id": station_id,
+            "expected_cash": expected,
+            "counted_cash": counted,
+            "variance": variance
+        });
+
+        sqlx::query(INSERT_ENTRY)
+            .bind(&id)
+            .bind("shift")
+            .bind(shift_id)
+            .bind("cash_variance")
+            .bind(employee_id)
+            .bind(employee_id)
+            .bind(changes.to_string())
+            .bind(None::<String>)
+            .bind(None::<String>)
+            .bind(false)
+            .bind(Utc::now().to_rfc3339())
+            .bind(store_id)
+            .execute(conn)
+            .await?;
+
+        Ok(id)
+    }
+
     /// Get audit trail for an entity
     pub async fn get_audit_trail(
         &self,
@@ -0,0 +1,1734 @@
+/**
+ * Checkout Service
+ *
+ * Completes POS sales using the `pos_core_domain` engines. The cart is built as
+ * a core `Transaction`, finalized with the default pricing/discount
+ * implementations using exact decimal arithmetic, taxed line by line for the
+ * store's jurisdiction (product tax classes, customer exemptions), and then
+ * persisted in a single database transaction together with the stock
+ * decrement, the per-line tax breakdown and the immutable accounting snapshot.
+ *
+ * A sale may be paid with several tenders. Cash may exceed the amount due
+ * (change is given back from cash); gift cards and store credit are debited
+ * on the same database transaction so a declined tender rolls back the sale.
+ * Voiding a sale credits those tenders back (`credit_back_tenders`).
+ *
+ * Stores whose shelf prices include tax (VAT markets) have the tax
+ * back-calculated from the gross line amounts; the sale is then recorded net
+ * of tax (line subtotals exclude the included tax) so that
+ * subtotal - discount + tax = total holds in both modes.
+ *
+ * Each line's cost of goods is what the inventory ledger charged for the
+ * units sold (FIFO layers or moving average cost, per the tenant's costing
+ * method). It is stored on the sale line with the line's profit and carried
+ * on the snapshot line as its unit cost.
+ *
+ * A sale resumed from a suspended cart finalizes that cart in the same
+ * database transaction, so a parked cart can be checked out only once.
+ *
+ * The `sale.completed` webhook event is published in the same transaction.
+ *
+ * The `sales_transactions` / `sales_line_items` tables store money as REAL, so
+ * amounts are rounded to the cent before being written. Per-line tax and
+ * cart-level discount are allocated so that the lines always sum exactly to
+ * the header and to the snapshot.
+ */
+
+use accounting_snapshots::builder::DefaultSnapshotBuilder;
+use accounting_snapshots::{
+    AccountingSnapshot, SnapshotBuilder, SnapshotError, SnapshotLineTax, SnapshotRepository,
+};
+use chrono::Utc;
+use pos_core_domain::discount::DefaultDiscountApplicator;
+use pos_core_domain::tax::{DefaultTaxCalculator, TaxableLine};
+use pos_core_domain::transaction::DefaultTransactionFinalizer;
+use pos_core_domain::{
+    allocate_proportionally, DefaultPricingEngine, Discount, DiscountType, DomainError,
+    JurisdictionTaxCalculator, LineItem, Payment, PriceMode, TaxBreakdown, Transaction,
+    TransactionFinalizer,
+};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde_json::json;
+use sqlx::{Sqlite, SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_SALE, SOURCE_SALE,
+};
+use crate::services::outbound_webhook_service::{self, WebhookEventType};
+use crate::services::stored_value_service::{self, StoredValueError};
+use crate::services::suspended_sale_service;
+use crate::services::tax_service::{self, TaxError, TaxService};
+
+/// Tender methods with special handling; any other method (e.g. "card",
+/// "check", "on_account") is recorded as-is.
+pub const TENDER_CASH: &str = "cash";
+pub const TENDER_GIFT_CARD: &str = "gift_card";
+pub const TENDER_STORE_CREDIT: &str = "store_credit";
+
+/// `sales_transactions.payment_method` value for sales paid with several tenders
+pub const PAYMENT_METHOD_SPLIT: &str = "split";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum CheckoutError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Product not found: {0}")]
+    ProductNotFound(String),
+
+    #[error("Suspended sale unavailable: {0}")]
+    SuspendedSaleUnavailable(String),
+
+    #[error("Pricing error: {0}")]
+    Domain(#[from] DomainError),
+
+    #[error("Tender declined: {0}")]
+    Tender(#[from] StoredValueError),
+
+    #[error("Tax configuration error: {0}")]
+    Tax(#[from] TaxError),
+
+    #[error("Accounting snapshot error: {0}")]
+    Snapshot(#[from] SnapshotError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<CheckoutError> for ApiError {
+    fn from(err: CheckoutError) -> Self {
+        match err {
+            CheckoutError::Validation(msg) => Self::bad_request(msg),
+            CheckoutError::Domain(e) => Self::bad_request(e.to_string()),
+            CheckoutError::Tender(StoredValueError::Database(e)) => {
+                Self::internal(format!("Failed to apply tender: {e}"))
+            }
+            CheckoutError::Tender(e) => Self::bad_request(format!("Tender declined: {e}")),
+            CheckoutError::Tax(e) => e.into(),
+            CheckoutError::ProductNotFound(id) => Self::not_found(format!("Product not found: {id}")),
+            CheckoutError::SuspendedSaleUnavailable(id) => Self::conflict(format!(
+                "Suspended sale {id} is no longer open; it was checked out, cancelled or expired"
+            )),
+            CheckoutError::Snapshot(e @ SnapshotError::PeriodClosed(_)) => Self::conflict(e.to_string()),
+            CheckoutError::Snapshot(e) => {
+                Self::internal(format!("Failed to record accounting snapshot: {e}"))
+            }
+            CheckoutError::Database(e) => Self::internal(format!("Failed to create sale: {e}")),
+        }
+    }
+}
+
+impl From<LedgerError> for CheckoutError {
+    fn from(err: LedgerError) -> Self {
+        match err {
+            LedgerError::ProductNotFound(id) => Self::ProductNotFound(id),
+            LedgerError::Validation(msg) => Self::Validation(msg),
+            LedgerError::Database(e) => Self::Database(e),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A cart line as submitted by the register
+#[derive(Debug, Clone)]
+pub struct CheckoutLine {
+    pub product_id: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    /// Line-level discount amount (currency, not percent)
+    pub discount_amount: Decimal,
+}
+
+/// A payment offered by the customer
+#[derive(Debug, Clone)]
+pub struct Tender {
+    pub method: String,
+    /// Amount handed over; cash may exceed the amount due. `None` pays
+    /// whatever the other tenders leave outstanding.
+    pub amount: Option<Decimal>,
+    /// Gift card number, card authorization code, etc.
+    pub reference: Option<String>,
+}
+
+/// A tender after change has been worked out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettledTender {
+    pub method: String,
+    pub reference: Option<String>,
+    pub tendered: Decimal,
+    /// Portion of the tender applied to the sale
+    pub applied: Decimal,
+    /// Change given back from this tender (cash only)
+    pub change: Decimal,
+}
+
+/// Everything needed to complete a sale
+#[derive(Debug, Clone)]
+pub struct CheckoutRequest {
+    pub tenant_id: String,
+    pub store_id: String,
+    /// Register ringing up the sale
+    pub station_id: Option<String>,
+    pub employee_id: String,
+    pub customer_id: Option<String>,
+    pub lines: Vec<CheckoutLine>,
+    /// Cart-level discount amount applied after line discounts
+    pub cart_discount: Decimal,
+    pub tenders: Vec<Tender>,
+    pub notes: Option<String>,
+    /// Suspended cart being checked out; finalized together with the sale
+    pub suspended_sale_id: Option<String>,
+}
+
+/// Per-line amounts as persisted to `sales_line_items`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PricedLine {
+    /// quantity × unit price, less the included tax when prices include tax
+    pub subtotal: Decimal,
+    /// Own line discount plus its share of the cart discount
+    pub discount: Decimal,
+    /// Tax charged on the line (sum of its per-authority taxes)
+    pub tax: Decimal,
+    /// subtotal - discount + tax
+    pub total: Decimal,
+}
+
+/// Result of a completed checkout
+#[derive(Debug, Clone)]
+pub struct CompletedSale {
+    pub sale_id: String,
+    pub transaction_number: String,
+    pub snapshot_id: Uuid,
+    pub subtotal: Decimal,
+    pub discount: Decimal,
+    pub tax: Decimal,
+    pub total: Decimal,
+    pub change_due: Decimal,
+    /// Per-line, per-authority tax
+    pub tax_breakdown: TaxBreakdown,
+    /// Whether the shelf prices included tax
+    pub prices_include_tax: bool,
+    /// Single tender method, or `split`
+    pub payment_method: String,
+    pub tenders: Vec<SettledTender>,
+    pub items_count: i32,
+    pub created_at: String,
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct CheckoutService {
+    pool: SqlitePool,
+}
+
+impl CheckoutService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Finalize and persist a sale
+    ///
+    /// The sale header, its lines, the stock decrement and the accounting
+    /// snapshot are committed together; any failure rolls all of them back.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cart is invalid, a product is unknown, the
+    /// tenders do not cover the total, a tender is declined, or the database
+    /// write fails.
+    pub async fn complete_sale(&self, request: CheckoutRequest) -> Result<CompletedSale, CheckoutError> {
+        if request.lines.is_empty() {
+            return Err(CheckoutError::Validation("Sale must have at least one item".to_string()));
+        }
+
+        let tax_service = TaxService::new(self.pool.clone());
+        let jurisdiction = tax_service
+            .jurisdiction_for_store(&request.tenant_id, &request.store_id)
+            .await?;
+        let tax_policy = tax_service.policy_for_store(&request.tenant_id, &request.store_id).await?;
+        let sale_uuid = Uuid::new_v4();
+
+        let mut tx = self.pool.begin().await?;
+
+        let mut descriptions = Vec::with_capacity(request.lines.len());
+        let mut tax_classes = Vec::with_capacity(request.lines.len());
+        let mut costing = Vec::with_capacity(request.lines.len());
+        for line in &request.lines {
+            let product: Option<(String, String, String, f64)> = sqlx::query_as(
+                "SELECT name, tax_class, category, cost FROM products WHERE id = ? AND tenant_id = ?",
+            )
+            .bind(&line.product_id)
+            .bind(&request.tenant_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let (name, tax_class, category, cost) =
+                product.ok_or_else(|| CheckoutError::ProductNotFound(line.product_id.clone()))?;
+            descriptions.push(name);
+            tax_classes.push(tax_class);
+            costing.push((category, decimal_from_f64(cost, "cost")?));
+        }
+
+        let exemption = match &request.customer_id {
+            Some(customer_id) => tax_service::customer_exemption(&mut tx, &request.tenant_id, customer_id).await?,
+            None => None,
+        };
+        let calculator = JurisdictionTaxCalculator::new(jurisdiction)
+            .with_exemption(exemption)
+            .with_price_mode(tax_policy.price_mode)
+            .with_rounding(tax_policy.rounding);
+        let prices_include_tax = tax_policy.price_mode == PriceMode::TaxInclusive;
+
+        let mut transaction = build_transaction(
+            sale_uuid,
+            &request.lines,
+            &tax_classes,
+            request.cart_discount,
+        )?;
+        finalize_transaction(&mut transaction)?;
+        let (priced_lines, tax_breakdown) = apply_tax(&mut transaction, &request.lines, &calculator)?;
+        let tenders = settle_tenders(transaction.total, &request.tenders)?;
+        apply_tenders(&mut transaction, &tenders);
+        let payment_method = summarize_payment_method(&tenders);
+
+        let mut snapshot = build_snapshot(&transaction, &priced_lines, &tax_breakdown, descriptions, costing)?;
+
+        let sale_id = sale_uuid.to_string();
+        let transaction_number = next_transaction_number(&mut tx, &request.tenant_id, "TXN").await?;
+        let now = Utc::now().to_rfc3339();
+        let items_count = i32::try_from(request.lines.len())
+            .map_err(|_| CheckoutError::Validation("Too many items in sale".to_string()))?;
+
+        sqlx::query(
+            r"
+            INSERT INTO sales_transactions (
+                id, tenant_id, transaction_number, customer_id, employee_id, store_id, station_id,
+                total_amount, subtotal, tax_amount, discount_amount, items_count,
+                payment_method, payment_status, status, notes, tax_exempt_certificate,
+                prices_include_tax, created_at, updated_at, completed_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'completed', 'completed', ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&sale_id)
+        .bind(&request.tenant_id)
+        .bind(&transaction_number)
+        .bind(&request.customer_id)
+        .bind(&request.employee_id)
+        .bind(&request.store_id)
+        .bind(&request.station_id)
+        .bind(money_to_f64(transaction.total))
+        .bind(money_to_f64(transaction.subtotal))
+        .bind(money_to_f64(transaction.tax))
+        .bind(money_to_f64(transaction.discount_total))
+        .bind(items_count)
+        .bind(&payment_method)
+        .bind(&request.notes)
+        .bind(&tax_breakdown.exemption_certificate)
+        .bind(prices_include_tax)
+        .bind(&now)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        for (index, ((line, priced), line_tax)) in
+            request.lines.iter().zip(&priced_lines).zip(&tax_breakdown.lines).enumerate()
+        {
+            let quantity = line.quantity.to_f64().unwrap_or_default();
+            let line_item_id = Uuid::new_v4().to_string();
+
+            let sold = StockMovement::new(
+                &request.tenant_id,
+                &line.product_id,
+                MovementType::Sale,
+                -quantity,
+                REASON_SALE,
+            )
+            .with_source(SOURCE_SALE, &sale_id)
+            .by_user(&request.employee_id)
+            .at_store(&request.store_id);
+            let sold = inventory_ledger_service::record_movement(&mut tx, &sold).await?;
+
+            // Cost of goods as the ledger charged it
+            let unit_cost = decimal_from_f64(sold.unit_cost.unwrap_or_default(), "cost")?.round_dp(4);
+            let cost = (line.quantity * unit_cost).round_dp(2);
+            if let Some(snapshot_line) = snapshot.lines.get_mut(index) {
+                snapshot_line.unit_cost = Some(unit_cost);
+            }
+
+            sqlx::query(
+                r"
+                INSERT INTO sales_line_items (
+                    id, transaction_id, product_id, quantity, unit_price,
+                    subtotal, discount_amount, tax_amount, total, tax_class,
+                    cost, profit, created_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ",
+            )
+            .bind(&line_item_id)
+            .bind(&sale_id)
+            .bind(&line.product_id)
+            .bind(quantity)
+            .bind(money_to_f64(line.unit_price))
+            .bind(money_to_f64(priced.subtotal))
+            .bind(money_to_f64(priced.discount))
+            .bind(money_to_f64(priced.tax))
+            .bind(money_to_f64(priced.total))
+            .bind(&line_tax.tax_class)
+            .bind(money_to_f64(cost))
+            .bind(money_to_f64(priced.subtotal - priced.discount - cost))
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+
+            for tax in &line_tax.taxes {
+                sqlx::query(
+                    r"
+                    INSERT INTO sales_line_taxes (
+                        id, tenant_id, transaction_id, line_item_id, authority, label,
+                        rate, taxable_amount, tax_amount, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ",
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(&request.tenant_id)
+                .bind(&sale_id)
+                .bind(&line_item_id)
+                .bind(&tax.rate_code)
+                .bind(&tax.label)
+                .bind(tax.rate_percent.to_f64().unwrap_or_default())
+                .bind(money_to_f64(tax.taxable_amount))
+                .bind(money_to_f64(tax.tax_amount))
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        for tender in &tenders {
+            Self::record_tender(&mut tx, &request, &sale_id, tender).await?;
+        }
+
+        if let Some(suspended_sale_id) = &request.suspended_sale_id {
+            let finalized = suspended_sale_service::finalize_suspended_sale(
+                &mut tx,
+                &request.tenant_id,
+                suspended_sale_id,
+                &sale_id,
+            )
+            .await?;
+            if !finalized {
+                return Err(CheckoutError::SuspendedSaleUnavailable(suspended_sale_id.clone()));
+            }
+        }
+
+        SnapshotRepository::save_in_transaction(&mut tx, &snapshot).await?;
+
+        let items: Vec<_> = request
+            .lines
+            .iter()
+            .zip(&priced_lines)
+            .map(|(line, priced)| {
+                json!({
+                    "product_id": line.product_id,
+                    "quantity": line.quantity.to_f64().unwrap_or_default(),
+                    "unit_price": money_to_f64(line.unit_price),
+                    "total": money_to_f64(priced.total),
+                })
+            })
+            .collect();
+        outbound_webhook_service::publish(
+            &mut tx,
+            &request.tenant_id,
+            WebhookEventType::SaleCompleted,
+            json!({
+                "sale_id": sale_id,
+                "transaction_number": transaction_number,
+                "store_id": request.store_id,
+                "station_id": request.station_id,
+                "employee_id": request.employee_id,
+                "customer_id": request.customer_id,
+                "subtotal": money_to_f64(transaction.subtotal),
+                "discount": money_to_f64(transaction.discount_total),
+                "tax": money_to_f64(transaction.tax),
+                "total": money_to_f64(transaction.total),
+                "payment_method": payment_method,
+                "items": items,
+                "completed_at": now,
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            sale_id = %sale_id,
+            transaction_number = %transaction_number,
+            total = %transaction.total,
+            "Sale completed"
+        );
+
+        Ok(CompletedSale {
+            sale_id,
+            transaction_number,
+            snapshot_id: snapshot.id,
+            subtotal: transaction.subtotal,
+            discount: transaction.discount_total,
+            tax: transaction.tax,
+            total: transaction.total,
+            change_due: tenders.iter().map(|t| t.change).sum(),
+            tax_breakdown,
+            prices_include_tax,
+            payment_method,
+            tenders,
+            items_count,
+            created_at: now,
+        })
+    }
+
+    /// Debit stored value for a tender and record it against the sale
+    async fn record_tender(
+        tx: &mut sqlx::Transaction<'_, Sqlite>,
+        request: &CheckoutRequest,
+        sale_id: &str,
+        tender: &SettledTender,
+    ) -> Result<(), CheckoutError> {
+        let applied = money_to_f64(tender.applied);
+
+        match tender.method.as_str() {
+            // Nothing to debit when other tenders already covered the sale
+            _ if tender.applied.is_zero() => {}
+            TENDER_GIFT_CARD => {
+                let card_number = tender.reference.as_deref().ok_or_else(|| {
+                    CheckoutError::Validation("Gift card tender requires a card number".to_string())
+                })?;
+                stored_value_service::debit_gift_card(
+                    tx,
+                    Some(&request.tenant_id),
+                    card_number,
+                    applied,
+                    Some(sale_id),
+                )
+                .await?;
+            }
+            TENDER_STORE_CREDIT => {
+                let customer_id = request.customer_id.as_deref().ok_or_else(|| {
+                    CheckoutError::Validation("Store credit tender requires a customer".to_string())
+                })?;
+                stored_value_service::debit_store_credit(
+                    tx,
+                    &request.tenant_id,
+                    customer_id,
+                    applied,
+                    sale_id,
+                    &request.employee_id,
+                )
+                .await?;
+            }
+            _ => {}
+        }
+
+        sqlx::query(
+            "INSERT INTO sales_payments (
+                id, tenant_id, transaction_id, method, tendered_amount, amount, change_amount, reference, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&request.tenant_id)
+        .bind(sale_id)
+        .bind(&tender.method)
+        .bind(money_to_f64(tender.tendered))
+        .bind(applied)
+        .bind(money_to_f64(tender.change))
+        .bind(&tender.reference)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Voids
+// ============================================================================
+
+/// Credit back the gift card and store credit tenders a sale was paid with
+///
+/// Reads the sale's `sales_payments` on the caller's transaction so that the
+/// credits commit or roll back with the void; other tenders are handed back
+/// at the register.
+///
+/// # Errors
+///
+/// Returns an error if a card can no longer be credited (cancelled or
+/// expired), a store credit tender has no customer, or a database operation
+/// fails.
+pub async fn credit_back_tenders(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    sale_id: &str,
+    customer_id: Option<&str>,
+    employee_id: &str,
+) -> Result<(), CheckoutError> {
+    let payments = sqlx::query_as::<_, (String, f64, Option<String>)>(
+        "SELECT method, amount, reference FROM sales_payments
+         WHERE transaction_id = ? AND tenant_id = ? AND method IN (?, ?) AND amount > 0",
+    )
+    .bind(sale_id)
+    .bind(tenant_id)
+    .bind(TENDER_GIFT_CARD)
+    .bind(TENDER_STORE_CREDIT)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (method, amount, reference) in payments {
+        if method == TENDER_GIFT_CARD {
+            let card_number = reference.ok_or_else(|| {
+                CheckoutError::Validation("Gift card tender has no card number to credit".to_string())
+            })?;
+            stored_value_service::credit_gift_card(conn, tenant_id, &card_number, amount, sale_id).await?;
+        } else {
+            let customer_id = customer_id.ok_or_else(|| {
+                CheckoutError::Validation("Store credit tender has no customer to credit".to_string())
+            })?;
+            stored_value_service::credit_store_credit(conn, tenant_id, customer_id, amount, sale_id, employee_id).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Domain mapping
+// ============================================================================
+
+/// Convert a client-supplied amount into a decimal, rejecting NaN/infinite values
+///
+/// # Errors
+///
+/// Returns `CheckoutError::Validation` if the value cannot be represented.
+pub fn decimal_from_f64(value: f64, field: &str) -> Result<Decimal, CheckoutError> {
+    Decimal::from_f64(value)
+        .ok_or_else(|| CheckoutError::Validation(format!("Invalid {field}: {value}")))
+}
+
+/// Round a decimal amount to the cent for storage in REAL columns
+#[must_use]
+pub fn money_to_f64(value: Decimal) -> f64 {
+    value.round_dp(2).to_f64().unwrap_or_default()
+}
+
+/// Build a draft core transaction from cart lines
+///
+/// `tax_classes` holds the product tax class of each line; lines without one
+/// are taxed as standard. Line discounts become fixed discounts and the cart
+/// discount a fixed-cart discount, applied in that order by the discount
+/// applicator. Tax is applied after finalization by [`apply_tax`].
+///
+/// # Errors
+///
+/// Returns an error if a line has a non-positive quantity, a negative price
+/// or a discount larger than the line.
+pub fn build_transaction(
+    id: Uuid,
+    lines: &[CheckoutLine],
+    tax_classes: &[String],
+    cart_discount: Decimal,
+) -> Result<Transaction, CheckoutError> {
+    let mut transaction = Transaction::with_id(id);
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.quantity <= Decimal::ZERO {
+            return Err(CheckoutError::Validation("Item quantity must be positive".to_string()));
+        }
+        if line.unit_price < Decimal::ZERO {
+            return Err(CheckoutError::Validation("Item price cannot be negative".to_string()));
+        }
+
+        let line_total = (line.quantity * line.unit_price).round_dp(2);
+        if line.discount_amount > line_total {
+            return Err(CheckoutError::Validation(format!(
+                "Discount exceeds line total for product {}",
+                line.product_id
+            )));
+        }
+
+        let mut item = LineItem::new(line.product_id.clone(), line.quantity, line.unit_price);
+        item.line_total = Some(line_total);
+        item.tax_class = tax_classes.get(index).cloned();
+        transaction.add_item(item)?;
+
+        if line.discount_amount > Decimal::ZERO {
+            transaction.add_discount(Discount::new(
+                format!("LINE-{}", line.product_id),
+                DiscountType::Fixed,
+                line.discount_amount.round_dp(2),
+            )?)?;
+        }
+    }
+
+    if cart_discount > Decimal::ZERO {
+        transaction.add_discount(Discount::new(
+            "CART".to_string(),
+            DiscountType::FixedCart,
+            cart_discount.round_dp(2),
+        )?)?;
+    }
+
+    Ok(transaction)
+}
+
+/// Finalize a transaction with the default engines
+///
+/// The transaction carries no tax rates, so this settles the subtotal and
+/// discounts only; tax follows in [`apply_tax`].
+///
+/// # Errors
+///
+/// Returns `CheckoutError::Domain` if the domain engines reject the cart,
+/// e.g. when discounts exceed the subtotal.
+pub fn finalize_transaction(transaction: &mut Transaction) -> Result<(), CheckoutError> {
+    let finalizer = DefaultTransactionFinalizer::new(
+        DefaultPricingEngine::new(),
+        DefaultTaxCalculator::new(),
+        DefaultDiscountApplicator::new(),
+    );
+    finalizer.finalize_transaction(transaction)?;
+
+    Ok(())
+}
+
+/// Work out how much of each tender is applied to `total` and the change due
+///
+/// Tenders must cover the total. Only cash may be over-tendered; change is
+/// taken from the last cash tender first. At most one tender may omit its
+/// amount, in which case it pays the outstanding balance.
+///
+/// # Errors
+///
+/// Returns `CheckoutError::Validation` if there are no tenders, a tender is
+/// not positive, the tenders fall short of the total, or a non-cash tender
+/// would produce change.
+pub fn settle_tenders(total: Decimal, tenders: &[Tender]) -> Result<Vec<SettledTender>, CheckoutError> {
+    if tenders.is_empty() {
+        return Err(CheckoutError::Validation("At least one tender is required".to_string()));
+    }
+    if tenders.iter().filter(|t| t.amount.is_none()).count() > 1 {
+        return Err(CheckoutError::Validation(
+            "Only one tender may omit its amount".to_string(),
+        ));
+    }
+    if let Some(tender) = tenders.iter().find(|t| t.amount.is_some_and(|a| a <= Decimal::ZERO)) {
+        return Err(CheckoutError::Validation(format!(
+            "Tender amount must be positive ({})",
+            tender.method
+        )));
+    }
+
+    let fixed: Decimal = tenders.iter().filter_map(|t| t.amount).map(|a| a.round_dp(2)).sum();
+    let outstanding = (total - fixed).max(Decimal::ZERO);
+    let amounts: Vec<Decimal> = tenders
+        .iter()
+        .map(|t| t.amount.map_or(outstanding, |a| a.round_dp(2)))
+        .collect();
+
+    let tendered: Decimal = amounts.iter().copied().sum();
+    if tendered < total {
+        return Err(CheckoutError::Validation(format!(
+            "Tendered amount {tendered} is less than the sale total {total}"
+        )));
+    }
+
+    let cash: Decimal = tenders
+        .iter()
+        .zip(&amounts)
+        .filter(|(t, _)| t.method == TENDER_CASH)
+        .map(|(_, amount)| *amount)
+        .sum();
+    let mut change_remaining = tendered - total;
+    if change_remaining > cash {
+        return Err(CheckoutError::Validation(
+            "Only cash tenders can exceed the amount due".to_string(),
+        ));
+    }
+
+    let mut settled: Vec<SettledTender> = tenders
+        .iter()
+        .zip(amounts)
+        .map(|(t, amount)| SettledTender {
+            method: t.method.clone(),
+            reference: t.reference.clone(),
+            tendered: amount,
+            applied: amount,
+            change: Decimal::ZERO,
+        })
+        .collect();
+
+    for tender in settled.iter_mut().rev().filter(|t| t.method == TENDER_CASH) {
+        if change_remaining.is_zero() {
+            break;
+        }
+        let change = change_remaining.min(tender.tendered);
+        tender.change = change;
+        tender.applied = tender.tendered - change;
+        change_remaining -= change;
+    }
+
+    Ok(settled)
+}
+
+/// Record the applied tenders as the transaction's payments
+pub fn apply_tenders(transaction: &mut Transaction, tenders: &[SettledTender]) {
+    transaction.payments = tenders
+        .iter()
+        .filter(|t| t.applied > Decimal::ZERO)
+        .map(|t| Payment {
+            method: t.method.clone(),
+            amount: t.applied,
+        })
+        .collect();
+}
+
+/// Payment method stored on the sale header
+#[must_use]
+pub fn summarize_payment_method(tenders: &[SettledTender]) -> String {
+    match tenders {
+        [single] => single.method.clone(),
+        _ => PAYMENT_METHOD_SPLIT.to_string(),
+    }
+}
+
+/// Tax a finalized transaction line by line and break it down into per-line amounts
+///
+/// The cart discount is allocated by each line's amount after its own
+/// discount. Each line is then taxed on its discounted amount and tax class by
+/// the jurisdiction calculator, and the transaction tax and total are set from
+/// the resulting breakdown, so every column sums exactly to the header.
+///
+/// When the calculator treats prices as tax-inclusive, the discounted amounts
+/// are gross: the included tax is taken out of each line's subtotal (and the
+/// transaction subtotal), leaving the total equal to the gross amount paid.
+///
+/// # Errors
+///
+/// Returns `CheckoutError::Domain` if the calculator rejects a line.
+pub fn apply_tax(
+    transaction: &mut Transaction,
+    lines: &[CheckoutLine],
+    calculator: &JurisdictionTaxCalculator,
+) -> Result<(Vec<PricedLine>, TaxBreakdown), CheckoutError> {
+    let subtotals: Vec<Decimal> = transaction
+        .items
+        .iter()
+        .map(|item| item.line_total.unwrap_or_else(|| item.calculate_line_total()))
+        .collect();
+    let own_discounts: Vec<Decimal> = lines.iter().map(|l| l.discount_amount.round_dp(2)).collect();
+
+    let after_own: Vec<Decimal> = subtotals
+        .iter()
+        .zip(&own_discounts)
+        .map(|(subtotal, discount)| subtotal - discount)
+        .collect();
+    let cart_discount = transaction.discount_total - own_discounts.iter().copied().sum::<Decimal>();
+    let cart_shares = allocate_proportionally(cart_discount, &after_own, 2);
+
+    let discounts: Vec<Decimal> = own_discounts
+        .iter()
+        .zip(&cart_shares)
+        .map(|(own, share)| own + share)
+        .collect();
+    let taxable: Vec<TaxableLine> = transaction
+        .items
+        .iter()
+        .zip(subtotals.iter().zip(&discounts))
+        .map(|(item, (subtotal, discount))| TaxableLine {
+            product_id: item.product_id.clone(),
+            tax_class: item.tax_class().to_string(),
+            amount: subtotal - discount,
+        })
+        .collect();
+
+    let breakdown = calculator.calculate_breakdown(&taxable)?;
+    let subtotals: Vec<Decimal> = if calculator.price_mode() == PriceMode::TaxInclusive {
+        let net: Vec<Decimal> = subtotals
+            .iter()
+            .zip(&breakdown.lines)
+            .map(|(subtotal, line_tax)| subtotal - line_tax.total())
+            .collect();
+        for (item, subtotal) in transaction.items.iter_mut().zip(&net) {
+            item.line_total = Some(*subtotal);
+        }
+        transaction.subtotal = net.iter().copied().sum();
+        net
+    } else {
+        subtotals
+    };
+    transaction.tax = breakdown.total_tax;
+    transaction.total = transaction.subtotal - transaction.discount_total + breakdown.total_tax;
+
+    let priced = subtotals
+        .into_iter()
+        .zip(discounts)
+        .zip(&breakdown.lines)
+        .map(|((subtotal, discount), line_tax)| PricedLine {
+            subtotal,
+            discount,
+            tax: line_tax.total(),
+            total: subtotal - discount + line_tax.total(),
+        })
+        .collect();
+
+    Ok((priced, breakdown))
+}
+
+/// Build the accounting snapshot with product descriptions, the category and
+/// unit cost of each product, and the same per-line tax breakdown that is
+/// written to `sales_line_items`
+fn build_snapshot(
+    transaction: &Transaction,
+    priced_lines: &[PricedLine],
+    tax_breakdown: &TaxBreakdown,
+    descriptions: Vec<String>,
+    costing: Vec<(String, Decimal)>,
+) -> Result<AccountingSnapshot, CheckoutError> {
+    let mut snapshot = DefaultSnapshotBuilder::new().build_snapshot(transaction)?;
+
+    for ((((line, priced), line_tax), description), (category, unit_cost)) in snapshot
+        .lines
+        .iter_mut()
+        .zip(priced_lines)
+        .zip(&tax_breakdown.lines)
+        .zip(descriptions)
+        .zip(costing)
+    {
+        line.description = description;
+        line.category = Some(category);
+        line.unit_cost = Some(unit_cost);
+        line.tax_amount = priced.tax;
+        line.taxes = line_tax
+            .taxes
+            .iter()
+            .map(|tax| SnapshotLineTax {
+                authority: tax.rate_code.clone(),
+                label: tax.label.clone(),
+                rate: tax.rate_percent,
+                taxable_amount: tax.taxable_amount,
+                tax_amount: tax.tax_amount,
+            })
+            .collect();
+    }
+
+    if !snapshot.verify_consistency() {
+        return Err(SnapshotError::InconsistentData(format!(
+            "Snapshot for transaction {} failed consistency verification",
+            transaction.id
+        ))
+        .into());
+    }
+
+    Ok(snapshot)
+}
+
+/// Generate the next PREFIX-YYYYMMDD-NNNN number for a tenant (TXN for
+/// sales, RTN for returns; the sequence is shared so numbers stay unique)
+pub(crate) async fn next_transaction_number(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    tenant_id: &str,
+    prefix: &str,
+) -> Result<String, CheckoutError> {
+    let today = Utc::now().format("%Y%m%d").to_string();
+
+    // Get count of today's transactions
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sales_transactions WHERE tenant_id = ? AND DATE(created_at) = DATE('now')",
+    )
+    .bind(tenant_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(format!("{}-{}-{:04}", prefix, today, count + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pos_core_domain::{TaxJurisdiction, TaxRate};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn line(product_id: &str, quantity: &str, unit_price: &str, discount: &str) -> CheckoutLine {
+        CheckoutLine {
+            product_id: product_id.to_string(),
+            quantity: dec(quantity),
+            unit_price: dec(unit_price),
+            discount_amount: dec(discount),
+        }
+    }
+
+    fn single_rate(tax_rate: &str) -> JurisdictionTaxCalculator {
+        JurisdictionTaxCalculator::new(TaxJurisdiction::new(
+            "test".to_string(),
+            "Test".to_string(),
+            vec![TaxRate::new("TAX".to_string(), dec(tax_rate), "Sales Tax".to_string()).unwrap()],
+        ))
+    }
+
+    fn priced(
+        lines: &[CheckoutLine],
+        tax_classes: &[String],
+        cart_discount: &str,
+        calculator: &JurisdictionTaxCalculator,
+    ) -> (Transaction, Vec<PricedLine>, TaxBreakdown) {
+        let mut transaction =
+            build_transaction(Uuid::new_v4(), lines, tax_classes, dec(cart_discount)).unwrap();
+        finalize_transaction(&mut transaction).unwrap();
+        let (priced, breakdown) = apply_tax(&mut transaction, lines, calculator).unwrap();
+        (transaction, priced, breakdown)
+    }
+
+    fn finalized(lines: &[CheckoutLine], cart_discount: &str, tax_rate: &str) -> Transaction {
+        priced(lines, &[], cart_discount, &single_rate(tax_rate)).0
+    }
+
+    fn tender(method: &str, amount: &str, reference: Option<&str>) -> Tender {
+        Tender {
+            method: method.to_string(),
+            amount: Some(dec(amount)),
+            reference: reference.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_finalize_uses_decimal_math() {
+        let lines = vec![line("p1", "3", "0.10", "0"), line("p2", "1", "19.99", "0")];
+        let transaction = finalized(&lines, "0", "13");
+
+        assert_eq!(transaction.subtotal, dec("20.29"));
+        assert_eq!(transaction.tax, dec("2.64"));
+        assert_eq!(transaction.total, dec("22.93"));
+    }
+
+    #[test]
+    fn test_settle_split_tenders_with_cash_change() {
+        let tenders = vec![
+            tender("card", "10.00", Some("AUTH1")),
+            tender(TENDER_CASH, "20.00", None),
+        ];
+        let settled = settle_tenders(dec("22.93"), &tenders).unwrap();
+
+        assert_eq!(settled[0].applied, dec("10.00"));
+        assert_eq!(settled[1].applied, dec("12.93"));
+        assert_eq!(settled[1].change, dec("7.07"));
+        assert_eq!(summarize_payment_method(&settled), PAYMENT_METHOD_SPLIT);
+
+        let mut transaction = finalized(&[line("p1", "1", "20.29", "0")], "0", "13");
+        let settled = settle_tenders(transaction.total, &tenders).unwrap();
+        apply_tenders(&mut transaction, &settled);
+        assert!(transaction.is_paid_in_full());
+    }
+
+    #[test]
+    fn test_settle_tenders_rejects_short_and_non_cash_overpayment() {
+        assert!(matches!(
+            settle_tenders(dec("20.00"), &[tender(TENDER_CASH, "19.99", None)]),
+            Err(CheckoutError::Validation(_))
+        ));
+        assert!(matches!(
+            settle_tenders(dec("20.00"), &[tender("card", "25.00", None)]),
+            Err(CheckoutError::Validation(_))
+        ));
+        assert!(matches!(
+            settle_tenders(dec("20.00"), &[]),
+            Err(CheckoutError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_settle_open_tender_pays_balance() {
+        let open = Tender {
+            method: "card".to_string(),
+            amount: None,
+            reference: None,
+        };
+        let settled = settle_tenders(dec("20.00"), &[tender(TENDER_GIFT_CARD, "5.00", Some("4000")), open]).unwrap();
+
+        assert_eq!(settled[1].applied, dec("15.00"));
+        assert_eq!(summarize_payment_method(&settled), PAYMENT_METHOD_SPLIT);
+    }
+
+    #[test]
+    fn test_priced_lines_sum_to_header() {
+        let lines = vec![
+            line("p1", "1", "3.33", "0.50"),
+            line("p2", "2", "4.99", "0"),
+            line("p3", "1.5", "2.25", "0"),
+        ];
+        let (transaction, priced, _) = priced(&lines, &[], "1.00", &single_rate("13"));
+
+        let subtotal: Decimal = priced.iter().map(|l| l.subtotal).sum();
+        let discount: Decimal = priced.iter().map(|l| l.discount).sum();
+        let tax: Decimal = priced.iter().map(|l| l.tax).sum();
+        let total: Decimal = priced.iter().map(|l| l.total).sum();
+
+        assert_eq!(subtotal, transaction.subtotal);
+        assert_eq!(discount, transaction.discount_total);
+        assert_eq!(tax, transaction.tax);
+        assert_eq!(total, transaction.total);
+        assert!(priced.iter().all(|l| l.tax.scale() <= 2 && l.discount.scale() <= 2));
+    }
+
+    #[test]
+    fn test_snapshot_matches_priced_lines() {
+        let lines = vec![line("p1", "1", "10.00", "1.00"), line("p2", "1", "5.00", "0")];
+        let (transaction, priced, breakdown) = priced(&lines, &[], "0", &single_rate("5"));
+
+        let costing = vec![("Parts".into(), dec("4.20")), ("Accessories".into(), dec("2.00"))];
+        let snapshot =
+            build_snapshot(&transaction, &priced, &breakdown, vec!["Widget".into(), "Gadget".into()], costing)
+                .unwrap();
+
+        assert_eq!(snapshot.total, transaction.total);
+        assert_eq!(snapshot.lines[0].description, "Widget");
+        assert_eq!(snapshot.lines[0].category.as_deref(), Some("Parts"));
+        assert_eq!(snapshot.lines[1].unit_cost, Some(dec("2.00")));
+        assert_eq!(snapshot.lines[0].tax_amount, priced[0].tax);
+        assert_eq!(snapshot.lines[0].taxes[0].authority, "TAX");
+        assert!(snapshot.verify_consistency());
+    }
+
+    #[test]
+    fn test_tax_classes_and_line_discounts_drive_line_tax() {
+        // BC: GST on everything taxable, PST on standard goods only
+        let calculator = JurisdictionTaxCalculator::new(TaxJurisdiction::new(
+            "CA-BC".to_string(),
+            "British Columbia".to_string(),
+            vec![
+                TaxRate::new("GST".to_string(), dec("5"), "GST".to_string()).unwrap(),
+                TaxRate::new("PST".to_string(), dec("7"), "PST".to_string())
+                    .unwrap()
+                    .with_tax_classes(vec!["standard".to_string()]),
+            ],
+        ));
+        let lines = vec![
+            line("p1", "1", "100.00", "10.00"),
+            line("p2", "2", "5.00", "0"),
+            line("p3", "1", "20.00", "0"),
+        ];
+        let classes = vec!["standard".to_string(), "grocery".to_string(), "exempt".to_string()];
+        let (transaction, priced, breakdown) = priced(&lines, &classes, "0", &calculator);
+
+        // p1: 90.00 × 12%, p2: 10.00 × 5%, p3: untaxed
+        assert_eq!(priced[0].tax, dec("10.80"));
+        assert_eq!(priced[1].tax, dec("0.50"));
+        assert_eq!(priced[2].tax, Decimal::ZERO);
+        assert_eq!(transaction.tax, dec("11.30"));
+        assert_eq!(transaction.total, dec("131.30"));
+        assert_eq!(
+            breakdown.authority_totals(),
+            vec![("GST".to_string(), dec("5.00")), ("PST".to_string(), dec("6.30"))]
+        );
+    }
+
+    #[test]
+    fn test_tax_inclusive_prices_are_recorded_net_of_tax() {
+        let calculator = single_rate("20").with_price_mode(PriceMode::TaxInclusive);
+        let lines = vec![line("p1", "1", "12.00", "0"), line("p2", "3", "3.33", "0")];
+        let (transaction, priced, breakdown) = priced(&lines, &[], "2.00", &calculator);
+
+        // The customer pays the shelf prices less the cart discount
+        assert_eq!(transaction.total, dec("19.99"));
+        assert_eq!(transaction.tax, dec("3.33"));
+        assert_eq!(transaction.subtotal, dec("18.66"));
+        assert_eq!(transaction.subtotal - transaction.discount_total + transaction.tax, transaction.total);
+
+        let total: Decimal = priced.iter().map(|l| l.total).sum();
+        assert_eq!(total, transaction.total);
+        assert_eq!(priced[0].subtotal + priced[0].tax - priced[0].discount, priced[0].total);
+
+        let costing = vec![("Parts".into(), dec("4.20")), ("Parts".into(), dec("1.00"))];
+        let snapshot =
+            build_snapshot(&transaction, &priced, &breakdown, vec!["Widget".into(), "Gadget".into()], costing)
+                .unwrap();
+        assert_eq!(snapshot.total, dec("19.99"));
+        assert!(snapshot.verify_consistency());
+    }
+
+    #[test]
+    fn test_invalid_lines_rejected() {
+        let zero_qty = vec![line("p1", "0", "1.00", "0")];
+        assert!(matches!(
+            build_transaction(Uuid::new_v4(), &zero_qty, &[], Decimal::ZERO),
+            Err(CheckoutError::Validation(_))
+        ));
+
+        let over_discount = vec![line("p1", "1", "1.00", "2.00")];
+        assert!(matches!(
+            build_transaction(Uuid::new_v4(), &over_discount, &[], Decimal::ZERO),
+            Err(CheckoutError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_cart_discount_exceeding_subtotal_fails_finalization() {
+        let lines = vec![line("p1", "1", "5.00", "0")];
+        let mut transaction = build_transaction(Uuid::new_v4(), &lines, &[], dec("10.00")).unwrap();
+        assert!(matches!(
+            finalize_transaction(&mut transaction),
+            Err(CheckoutError::Domain(_))
+        ));
+    }
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                store_id TEXT,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                tax_class TEXT NOT NULL DEFAULT 'standard',
+                category TEXT NOT NULL DEFAULT 'General',
+                cost REAL NOT NULL DEFAULT 0,
+                reorder_point REAL
+            )",
+            "CREATE TABLE tax_rules (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL DEFAULT 'default',
+                name TEXT NOT NULL,
+                rate REAL NOT NULL,
+                is_default INTEGER NOT NULL DEFAULT 0,
+                authority TEXT,
+                tax_classes TEXT,
+                is_compound INTEGER NOT NULL DEFAULT 0,
+                sort_order INTEGER NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE sales_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_number TEXT NOT NULL,
+                customer_id TEXT,
+                employee_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                station_id TEXT,
+                total_amount REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                items_count INTEGER NOT NULL,
+                payment_method TEXT,
+                payment_status TEXT NOT NULL,
+                status TEXT NOT NULL,
+                notes TEXT,
+                tax_exempt_certificate TEXT,
+                prices_include_tax INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                completed_at TEXT
+            )",
+            "CREATE TABLE settings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT,
+                data_type TEXT NOT NULL DEFAULT 'string',
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(key, scope, scope_id)
+            )",
+            "CREATE TABLE sales_line_items (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                quantity REAL NOT NULL,
+                unit_price REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                total REAL NOT NULL,
+                tax_class TEXT,
+                cost REAL,
+                profit REAL,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE sales_line_taxes (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_id TEXT NOT NULL,
+                line_item_id TEXT NOT NULL,
+                authority TEXT NOT NULL,
+                label TEXT NOT NULL,
+                rate REAL NOT NULL,
+                taxable_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE accounting_snapshots (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL,
+                finalized_at TEXT NOT NULL,
+                subtotal TEXT NOT NULL,
+                tax TEXT NOT NULL,
+                discount TEXT NOT NULL,
+                total TEXT NOT NULL,
+                kind TEXT NOT NULL DEFAULT 'sale',
+                original_transaction_id TEXT,
+                memo TEXT
+            )",
+            "CREATE TABLE snapshot_lines (
+                id TEXT PRIMARY KEY,
+                snapshot_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                description TEXT NOT NULL,
+                quantity TEXT NOT NULL,
+                unit_price TEXT NOT NULL,
+                line_total TEXT NOT NULL,
+                tax_amount TEXT NOT NULL,
+                category TEXT,
+                unit_cost TEXT
+            )",
+            "CREATE TABLE snapshot_line_taxes (
+                id TEXT PRIMARY KEY,
+                snapshot_line_id TEXT NOT NULL,
+                authority TEXT NOT NULL,
+                label TEXT NOT NULL,
+                rate TEXT NOT NULL,
+                taxable_amount TEXT NOT NULL,
+                tax_amount TEXT NOT NULL
+            )",
+            "CREATE TABLE snapshot_payments (
+                id TEXT PRIMARY KEY,
+                snapshot_id TEXT NOT NULL,
+                method TEXT NOT NULL,
+                amount TEXT NOT NULL
+            )",
+            "CREATE TABLE sales_payments (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_id TEXT NOT NULL,
+                method TEXT NOT NULL,
+                tendered_amount REAL NOT NULL,
+                amount REAL NOT NULL,
+                change_amount REAL NOT NULL DEFAULT 0.0,
+                reference TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE gift_cards (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                card_number TEXT NOT NULL UNIQUE,
+                current_balance REAL NOT NULL,
+                status TEXT NOT NULL,
+                expiry_date TEXT
+            )",
+            "CREATE TABLE gift_card_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                gift_card_id TEXT NOT NULL,
+                transaction_type TEXT NOT NULL,
+                amount REAL NOT NULL,
+                reference_id TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE customers (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL DEFAULT 'default',
+                store_credit REAL NOT NULL DEFAULT 0.0,
+                tax_exempt INTEGER NOT NULL DEFAULT 0,
+                tax_exempt_certificate TEXT,
+                tax_exempt_authorities TEXT,
+                updated_at TEXT,
+                sync_version INTEGER NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE loyalty_transactions (
+                id TEXT PRIMARY KEY,
+                customer_id TEXT NOT NULL,
+                transaction_type TEXT NOT NULL,
+                points INTEGER NOT NULL,
+                amount REAL,
+                reference_id TEXT,
+                created_at TEXT NOT NULL,
+                employee_id TEXT NOT NULL
+            )",
+            "CREATE TABLE inventory_movements (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                store_id TEXT,
+                movement_type TEXT NOT NULL,
+                quantity REAL NOT NULL,
+                quantity_after REAL NOT NULL,
+                reason_code TEXT NOT NULL,
+                source_type TEXT,
+                source_id TEXT,
+                user_id TEXT,
+                notes TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE product_locations (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                quantity_on_hand REAL NOT NULL DEFAULT 0.0,
+                bin_location TEXT,
+                reorder_point REAL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                UNIQUE (product_id, store_id)
+            )",
+            "INSERT INTO products (id, tenant_id, name, store_id, quantity_on_hand, cost) VALUES ('p1', 't1', 'Widget', 's1', 10, 4.0)",
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+            include_str!("../../../../migrations/077_outbound_webhooks.sql"),
+            "INSERT INTO product_locations (id, tenant_id, product_id, store_id, quantity_on_hand, created_at, updated_at)
+             VALUES ('l1', 't1', 'p1', 's1', 10, '2026-01-01T00:00:00+00:00', '2026-01-01T00:00:00+00:00')",
+            "INSERT INTO gift_cards (id, tenant_id, card_number, current_balance, status)
+             VALUES ('gc1', 't1', '4000', 10.0, 'Active')",
+            "INSERT INTO customers (id, tenant_id, store_credit) VALUES ('c1', 't1', 5.0)",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    fn request(lines: Vec<CheckoutLine>) -> CheckoutRequest {
+        CheckoutRequest {
+            tenant_id: "t1".to_string(),
+            store_id: "s1".to_string(),
+            station_id: None,
+            employee_id: "u1".to_string(),
+            customer_id: None,
+            lines,
+            cart_discount: Decimal::ZERO,
+            tenders: vec![tender(TENDER_CASH, "100.00", None)],
+            notes: None,
+            suspended_sale_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_sale_persists_sale_stock_and_snapshot() {
+        let pool = setup_test_db().await;
+        let service = CheckoutService::new(pool.clone());
+
+        let sale = service
+            .complete_sale(request(vec![line("p1", "2", "9.99", "0")]))
+            .await
+            .unwrap();
+
+        // No tax rules: falls back to 13%
+        assert_eq!(sale.subtotal, dec("19.98"));
+        assert_eq!(sale.tax, dec("2.60"));
+        assert_eq!(sale.total, dec("22.58"));
+        assert_eq!(sale.change_due, dec("77.42"));
+        assert_eq!(sale.payment_method, TENDER_CASH);
+
+        let on_hand: f64 = sqlx::query_scalar("SELECT quantity_on_hand FROM products WHERE id = 'p1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(on_hand, 8.0);
+
+        let movement: (String, f64, f64, String) = sqlx::query_as(
+            "SELECT movement_type, quantity, quantity_after, source_id FROM inventory_movements WHERE product_id = 'p1'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(movement, ("sale".to_string(), -2.0, 8.0, sale.sale_id.clone()));
+
+        // The selling store's stock goes down
+        let at_store: f64 = sqlx::query_scalar(
+            "SELECT quantity_on_hand FROM product_locations WHERE product_id = 'p1' AND store_id = 's1'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(at_store, 8.0);
+
+        let snapshot = SnapshotRepository::new(pool.clone())
+            .find_by_transaction_id(Uuid::parse_str(&sale.sale_id).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(snapshot.total, sale.total);
+        assert_eq!(snapshot.lines[0].description, "Widget");
+        assert_eq!(snapshot.lines[0].category.as_deref(), Some("General"));
+        assert_eq!(snapshot.lines[0].unit_cost, Some(dec("4.00")));
+
+        // Cost of goods from the opening cost layer
+        let (cost, profit): (f64, f64) =
+            sqlx::query_as("SELECT cost, profit FROM sales_line_items WHERE transaction_id = ?")
+                .bind(&sale.sale_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((cost, profit), (8.0, 11.98));
+
+        let header_total: f64 = sqlx::query_scalar("SELECT total_amount FROM sales_transactions WHERE id = ?")
+            .bind(&sale.sale_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(header_total, money_to_f64(snapshot.total));
+        assert_eq!(snapshot.lines[0].taxes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_complete_sale_records_jurisdiction_breakdown_and_exemption() {
+        let pool = setup_test_db().await;
+        for statement in [
+            "INSERT INTO tax_rules (id, tenant_id, store_id, name, rate, authority, sort_order)
+             VALUES ('r1', 't1', 's1', 'GST', 5.0, 'GST', 0)",
+            "INSERT INTO tax_rules (id, tenant_id, store_id, name, rate, authority, tax_classes, sort_order)
+             VALUES ('r2', 't1', 's1', 'PST', 7.0, 'PST', 'standard', 1)",
+            "INSERT INTO customers (id, tenant_id, tax_exempt, tax_exempt_certificate, tax_exempt_authorities)
+             VALUES ('c2', 't1', 1, 'PST-EX-9', 'PST')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        let service = CheckoutService::new(pool.clone());
+
+        let sale = service
+            .complete_sale(request(vec![line("p1", "1", "10.00", "0")]))
+            .await
+            .unwrap();
+        assert_eq!(sale.tax, dec("1.20"));
+        let taxes: Vec<(String, f64)> = sqlx::query_as(
+            "SELECT authority, tax_amount FROM sales_line_taxes WHERE transaction_id = ? ORDER BY authority",
+        )
+        .bind(&sale.sale_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(taxes, vec![("GST".to_string(), 0.5), ("PST".to_string(), 0.7)]);
+
+        // PST-exempt customer pays GST only and the certificate is kept on the sale
+        let mut req = request(vec![line("p1", "1", "10.00", "0")]);
+        req.customer_id = Some("c2".to_string());
+        let sale = service.complete_sale(req).await.unwrap();
+        assert_eq!(sale.tax, dec("0.50"));
+
+        let certificate: Option<String> =
+            sqlx::query_scalar("SELECT tax_exempt_certificate FROM sales_transactions WHERE id = ?")
+                .bind(&sale.sale_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(certificate.as_deref(), Some("PST-EX-9"));
+    }
+
+    #[tokio::test]
+    async fn test_complete_sale_rolls_back_on_unknown_product() {
+        let pool = setup_test_db().await;
+        let service = CheckoutService::new(pool.clone());
+
+        let result = service
+            .complete_sale(request(vec![line("p1", "1", "5.00", "0"), line("missing", "1", "5.00", "0")]))
+            .await;
+        assert!(matches!(result, Err(CheckoutError::ProductNotFound(_))));
+
+        let sales: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sales_transactions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let snapshots: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM accounting_snapshots")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let on_hand: f64 = sqlx::query_scalar("SELECT quantity_on_hand FROM products WHERE id = 'p1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(sales, 0);
+        assert_eq!(snapshots, 0);
+        assert_eq!(on_hand, 10.0);
+    }
+
+    #[tokio::test]
+    async fn test_complete_sale_debits_split_tenders() {
+        let pool = setup_test_db().await;
+        let service = CheckoutService::new(pool.clone());
+
+        let mut req = request(vec![line("p1", "2", "9.99", "0")]);
+        req.customer_id = Some("c1".to_string());
+        req.tenders = vec![
+            tender(TENDER_GIFT_CARD, "10.00", Some("4000")),
+            tender(TENDER_STORE_CREDIT, "5.00", None),
+            tender(TENDER_CASH, "10.00", None),
+        ];
+
+        let sale = service.complete_sale(req).await.unwrap();
+        assert_eq!(sale.payment_method, PAYMENT_METHOD_SPLIT);
+        assert_eq!(sale.change_due, dec("2.42"));
+
+        let card_balance: f64 = sqlx::query_scalar("SELECT current_balance FROM gift_cards WHERE id = 'gc1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let store_credit: f64 = sqlx::query_scalar("SELECT store_credit FROM customers WHERE id = 'c1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let payments: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sales_payments WHERE transaction_id = ?")
+            .bind(&sale.sale_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(card_balance, 0.0);
+        assert_eq!(store_credit, 0.0);
+        assert_eq!(payments, 3);
+
+        let snapshot = SnapshotRepository::new(pool.clone())
+            .find_by_transaction_id(Uuid::parse_str(&sale.sale_id).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(snapshot.total_paid(), snapshot.total);
+    }
+
+    #[tokio::test]
+    async fn test_credit_back_tenders_restores_stored_value() {
+        let pool = setup_test_db().await;
+        let service = CheckoutService::new(pool.clone());
+
+        let mut req = request(vec![line("p1", "2", "9.99", "0")]);
+        req.customer_id = Some("c1".to_string());
+        req.tenders = vec![
+            tender(TENDER_GIFT_CARD, "10.00", Some("4000")),
+            tender(TENDER_STORE_CREDIT, "5.00", None),
+            tender(TENDER_CASH, "10.00", None),
+        ];
+        let sale = service.complete_sale(req).await.unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        credit_back_tenders(&mut tx, "t1", &sale.sale_id, Some("c1"), "u1").await.unwrap();
+        tx.commit().await.unwrap();
+
+        let (card_balance, card_status): (f64, String) =
+            sqlx::query_as("SELECT current_balance, status FROM gift_cards WHERE id = 'gc1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let store_credit: f64 = sqlx::query_scalar("SELECT store_credit FROM customers WHERE id = 'c1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!((card_balance, card_status.as_str()), (10.0, "Active"));
+        assert_eq!(store_credit, 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_complete_sale_rolls_back_when_tender_declined() {
+        let pool = setup_test_db().await;
+        let service = CheckoutService::new(pool.clone());
+
+        // Store credit covers only 5.00 of the 7.00 requested
+        let mut req = request(vec![line("p1", "2", "9.99", "0")]);
+        req.customer_id = Some("c1".to_string());
+        req.tenders = vec![
+            tender(TENDER_GIFT_CARD, "10.00", Some("4000")),
+            tender(TENDER_STORE_CREDIT, "7.00", None),
+            tender(TENDER_CASH, "10.00", None),
+        ];
+
+        let result = service.complete_sale(req).await;
+        assert!(matches!(
+            result,
+            Err(CheckoutError::Tender(StoredValueError::InsufficientBalance { .. }))
+        ));
+
+        let card_balance: f64 = sqlx::query_scalar("SELECT current_balance FROM gift_cards WHERE id = 'gc1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let sales: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sales_transactions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let on_hand: f64 = sqlx::query_scalar("SELECT quantity_on_hand FROM products WHERE id = 'p1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(card_balance, 10.0);
+        assert_eq!(sales, 0);
+        assert_eq!(on_hand, 10.0);
+    }
+
+    #[tokio::test]
+    async fn test_complete_sale_finalizes_suspended_sale_once() {
+        let pool = setup_test_db().await;
+        sqlx::query(
+            "CREATE TABLE suspended_sales (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                parked INTEGER NOT NULL,
+                expires_at TEXT NOT NULL,
+                sale_id TEXT,
+                finalized_at TEXT,
+                updated_at TEXT
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO suspended_sales (id, tenant_id, status, parked, expires_at)
+             VALUES ('held-1', 't1', 'draft', 0, '2000-01-01T00:00:00+00:00')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let service = CheckoutService::new(pool.clone());
+
+        let mut req = request(vec![line("p1", "1", "9.99", "0")]);
+        req.suspended_sale_id = Some("held-1".to_string());
+        let sale = service.complete_sale(req.clone()).await.unwrap();
+
+        let (status, sale_id): (String, Option<String>) =
+            sqlx::query_as("SELECT status, sale_id FROM suspended_sales WHERE id = 'held-1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(status, "finalized");
+        assert_eq!(sale_id, Some(sale.sale_id));
+
+        // Checking the same cart out again rolls the second sale back
+        assert!(matches!(
+            service.complete_sale(req).await,
+            Err(CheckoutError::SuspendedSaleUnavailable(_))
+        ));
+        let sales: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sales_transactions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(sales, 1);
+    }
+}
@@ -0,0 +1,90 @@
+/**
+ * Commission Service
+ *
+ * Reverses and claws back commissions when goods are returned. Both run on a
+ * caller-supplied connection so the commission change commits or rolls back
+ * together with the return.
+ */
+
+use chrono::Utc;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+/// Reverse commission for a returned transaction
+///
+/// Marks every commission earned on the sale as reversed. Runs on the
+/// caller's connection so the reversal commits together with the return.
+pub async fn reverse_commission(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    transaction_id: &str,
+) -> Result<u64, sqlx::Error> {
+    tracing::info!("Reversing commissions for transaction: {}", transaction_id);
+
+    // Mark existing commissions as reversed
+    let result = sqlx::query(
+        "UPDATE commissions 
+         SET is_reversed = 1 
+         WHERE transaction_id = ? AND is_reversed = 0 AND tenant_id = ?",
+    )
+    .bind(transaction_id)
+    .bind(tenant_id)
+    .execute(&mut *conn)
+    .await?;
+
+    tracing::info!("Commissions reversed for transaction: {}", transaction_id);
+    Ok(result.rows_affected())
+}
+
+/// Claw back part of the commission earned on a sale after a partial return
+///
+/// Records a negative commission against the return for `fraction` of every
+/// commission still in force on the original sale, so commission totals net
+/// out the returned goods.
+pub async fn claw_back_commission(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    transaction_id: &str,
+    return_id: &str,
+    fraction: f64,
+) -> Result<u64, sqlx::Error> {
+    let earned: Vec<(String, String, f64, f64, f64)> = sqlx::query_as(
+        "SELECT employee_id, rule_id, sale_amount, profit_amount, commission_amount
+         FROM commissions
+         WHERE transaction_id = ? AND is_reversed = 0 AND tenant_id = ?",
+    )
+    .bind(transaction_id)
+    .bind(tenant_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let now = Utc::now().to_rfc3339();
+    let round = |amount: f64| (amount * fraction * 100.0).round() / 100.0;
+
+    for (employee_id, rule_id, sale_amount, profit_amount, commission_amount) in &earned {
+        sqlx::query(
+            "INSERT INTO commissions (id, tenant_id, employee_id, transaction_id, rule_id,
+             sale_amount, profit_amount, commission_amount, created_at, is_reversed)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 0)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(employee_id)
+        .bind(return_id)
+        .bind(rule_id)
+        .bind(-round(*sale_amount))
+        .bind(-round(*profit_amount))
+        .bind(-round(*commission_amount))
+        .bind(&now)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    tracing::info!(
+        "Commissions clawed back for transaction {} by return {} ({:.4})",
+        transaction_id,
+        return_id,
+        fraction
+    );
+    Ok(earned.len() as u64)
+}
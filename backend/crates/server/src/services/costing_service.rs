@@ -0,0 +1,574 @@
+/**
+ * Costing Service
+ *
+ * Values stock at what it cost.
+ *
+ * The inventory ledger costs every movement it records (transfers excepted,
+ * as they move no value):
+ * - Units coming in open a cost layer at the movement's unit cost, or at the
+ *   product's current cost when the movement has none
+ * - Units going out are costed by the tenant's method: FIFO takes the oldest
+ *   layers first, weighted average charges the moving average cost; both
+ *   draw the layers down oldest first
+ * - Each costed movement writes a cost entry of signed quantity and value,
+ *   so stock value as of any date is a sum of entries
+ *
+ * Costing method per tenant: setting `inventory.costing_method`, `fifo` or
+ * `weighted_average` (default).
+ */
+
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+
+/// Setting holding the tenant's costing method
+pub const SETTING_COSTING_METHOD: &str = "inventory.costing_method";
+
+/// Quantities closer than this are the same
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum CostingError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Product not found: {0}")]
+    ProductNotFound(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<CostingError> for ApiError {
+    fn from(err: CostingError) -> Self {
+        match err {
+            CostingError::Validation(msg) => Self::bad_request(msg),
+            CostingError::ProductNotFound(id) => Self::not_found(format!("Product not found: {id}")),
+            CostingError::Database(e) => Self::internal(format!("Failed to access inventory costs: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostingMethod {
+    /// First in, first out
+    Fifo,
+    /// Moving weighted average cost
+    #[default]
+    WeightedAverage,
+}
+
+impl CostingMethod {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Fifo => "fifo",
+            Self::WeightedAverage => "weighted_average",
+        }
+    }
+
+    /// Parse a setting value; unknown values are `None`
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "fifo" => Some(Self::Fifo),
+            "weighted_average" | "average" => Some(Self::WeightedAverage),
+            _ => None,
+        }
+    }
+}
+
+/// Units received at one cost
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct CostLayer {
+    pub id: String,
+    pub product_id: String,
+    pub movement_id: Option<String>,
+    pub quantity: f64,
+    pub remaining_quantity: f64,
+    pub unit_cost: f64,
+    pub created_at: String,
+}
+
+/// A product's stock and its value
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct ProductValuation {
+    pub product_id: String,
+    pub sku: String,
+    pub name: String,
+    pub category: Option<String>,
+    pub quantity: f64,
+    pub value: f64,
+    #[sqlx(skip)]
+    pub unit_cost: f64,
+}
+
+/// Stock value of a tenant as of a date
+#[derive(Debug, Clone, Serialize)]
+pub struct ValuationReport {
+    pub as_of: String,
+    pub costing_method: CostingMethod,
+    pub products: Vec<ProductValuation>,
+    pub total_quantity: f64,
+    pub total_value: f64,
+}
+
+// ============================================================================
+// Costing movements
+// ============================================================================
+
+/// Cost a movement the ledger has just recorded and return its unit cost
+///
+/// `unit_cost` is what incoming units cost; `None` takes the product's
+/// current cost. It is ignored for units going out, which are costed by the
+/// tenant's method.
+///
+/// # Errors
+///
+/// Returns an error if the database write fails.
+pub async fn cost_movement(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    product_id: &str,
+    movement_id: &str,
+    quantity: f64,
+    unit_cost: Option<f64>,
+    created_at: &str,
+) -> Result<f64, sqlx::Error> {
+    let method = costing_method(&mut *conn, tenant_id).await?;
+
+    let unit_cost = if quantity > 0.0 {
+        let unit_cost = match unit_cost {
+            Some(cost) => cost,
+            None => current_unit_cost(&mut *conn, tenant_id, product_id, method).await?,
+        };
+
+        sqlx::query(
+            r"
+            INSERT INTO inventory_cost_layers (
+                id, tenant_id, product_id, movement_id, quantity, remaining_quantity, unit_cost, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(product_id)
+        .bind(movement_id)
+        .bind(quantity)
+        .bind(quantity)
+        .bind(unit_cost)
+        .bind(created_at)
+        .execute(&mut *conn)
+        .await?;
+
+        unit_cost
+    } else {
+        // The average must be taken before the layers are drawn down
+        let average = current_unit_cost(&mut *conn, tenant_id, product_id, CostingMethod::WeightedAverage).await?;
+        let (drawn, drawn_cost) = draw_down_layers(&mut *conn, tenant_id, product_id, -quantity).await?;
+
+        match method {
+            CostingMethod::WeightedAverage => average,
+            CostingMethod::Fifo => {
+                // Units beyond the layers (stock gone negative) at the latest cost
+                let beyond = -quantity - drawn;
+                let beyond_cost = if beyond > QUANTITY_EPSILON {
+                    beyond * current_unit_cost(&mut *conn, tenant_id, product_id, CostingMethod::Fifo).await?
+                } else {
+                    0.0
+                };
+                (drawn_cost + beyond_cost) / -quantity
+            }
+        }
+    };
+
+    sqlx::query(
+        r"
+        INSERT INTO inventory_cost_entries (
+            id, tenant_id, product_id, movement_id, quantity, unit_cost, value, costing_method, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(tenant_id)
+    .bind(product_id)
+    .bind(movement_id)
+    .bind(quantity)
+    .bind(unit_cost)
+    .bind(quantity * unit_cost)
+    .bind(method.as_str())
+    .bind(created_at)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(unit_cost)
+}
+
+/// The tenant's costing method
+///
+/// # Errors
+///
+/// Returns an error if the database read fails.
+pub async fn costing_method(conn: &mut SqliteConnection, tenant_id: &str) -> Result<CostingMethod, sqlx::Error> {
+    let value: Option<String> = sqlx::query_scalar(
+        "SELECT value FROM settings
+         WHERE key = ? AND ((scope = 'tenant' AND scope_id = ?) OR scope = 'global')
+         ORDER BY CASE scope WHEN 'tenant' THEN 1 ELSE 2 END
+         LIMIT 1",
+    )
+    .bind(SETTING_COSTING_METHOD)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(value.as_deref().and_then(CostingMethod::parse).unwrap_or_default())
+}
+
+/// What a unit of the product costs now: the moving average under weighted
+/// average, the latest layer under FIFO, else `products.cost`
+async fn current_unit_cost(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    product_id: &str,
+    method: CostingMethod,
+) -> Result<f64, sqlx::Error> {
+    let cost: Option<f64> = match method {
+        CostingMethod::WeightedAverage => {
+            let (quantity, value): (f64, f64) = sqlx::query_as(
+                "SELECT COALESCE(SUM(quantity), 0.0), COALESCE(SUM(value), 0.0)
+                 FROM inventory_cost_entries WHERE tenant_id = ? AND product_id = ?",
+            )
+            .bind(tenant_id)
+            .bind(product_id)
+            .fetch_one(&mut *conn)
+            .await?;
+            (quantity > QUANTITY_EPSILON && value >= 0.0).then(|| value / quantity)
+        }
+        CostingMethod::Fifo => {
+            sqlx::query_scalar(
+                "SELECT unit_cost FROM inventory_cost_layers
+                 WHERE tenant_id = ? AND product_id = ?
+                 ORDER BY created_at DESC, rowid DESC LIMIT 1",
+            )
+            .bind(tenant_id)
+            .bind(product_id)
+            .fetch_optional(&mut *conn)
+            .await?
+        }
+    };
+
+    match cost {
+        Some(cost) => Ok(cost),
+        None => {
+            let cost: Option<f64> = sqlx::query_scalar("SELECT cost FROM products WHERE id = ? AND tenant_id = ?")
+                .bind(product_id)
+                .bind(tenant_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+            Ok(cost.unwrap_or(0.0))
+        }
+    }
+}
+
+/// Take units from the oldest open layers; returns the units found and
+/// their cost
+async fn draw_down_layers(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    product_id: &str,
+    quantity: f64,
+) -> Result<(f64, f64), sqlx::Error> {
+    let layers: Vec<(String, f64, f64)> = sqlx::query_as(
+        "SELECT id, remaining_quantity, unit_cost FROM inventory_cost_layers
+         WHERE tenant_id = ? AND product_id = ? AND remaining_quantity > 0
+         ORDER BY created_at ASC, rowid ASC",
+    )
+    .bind(tenant_id)
+    .bind(product_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut left = quantity;
+    let mut cost = 0.0;
+    for (id, remaining, unit_cost) in layers {
+        if left < QUANTITY_EPSILON {
+            break;
+        }
+        let taken = remaining.min(left);
+        sqlx::query("UPDATE inventory_cost_layers SET remaining_quantity = remaining_quantity - ? WHERE id = ?")
+            .bind(taken)
+            .bind(&id)
+            .execute(&mut *conn)
+            .await?;
+        left -= taken;
+        cost += taken * unit_cost;
+    }
+
+    Ok((quantity - left, cost))
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct CostingService {
+    pool: SqlitePool,
+}
+
+impl CostingService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// The tenant's costing method
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn method(&self, tenant_id: &str) -> Result<CostingMethod, CostingError> {
+        let mut conn = self.pool.acquire().await?;
+        Ok(costing_method(&mut conn, tenant_id).await?)
+    }
+
+    /// Change the tenant's costing method; movements from now on are costed
+    /// by it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database write fails.
+    pub async fn set_method(&self, tenant_id: &str, method: CostingMethod) -> Result<CostingMethod, CostingError> {
+        sqlx::query(
+            "INSERT INTO settings (key, value, scope, scope_id, data_type)
+             VALUES (?, ?, 'tenant', ?, 'string')
+             ON CONFLICT(key, scope, scope_id) DO UPDATE SET
+                value = excluded.value, updated_at = datetime('now')",
+        )
+        .bind(SETTING_COSTING_METHOD)
+        .bind(method.as_str())
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!(tenant_id = %tenant_id, costing_method = method.as_str(), "Costing method changed");
+
+        Ok(method)
+    }
+
+    /// Stock value per product as of an RFC 3339 timestamp
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn valuation(&self, tenant_id: &str, as_of: &str) -> Result<ValuationReport, CostingError> {
+        let method = self.method(tenant_id).await?;
+
+        let mut products = sqlx::query_as::<_, ProductValuation>(
+            r"
+            SELECT p.id AS product_id, p.sku, p.name, p.category,
+                   SUM(e.quantity) AS quantity, SUM(e.value) AS value
+            FROM inventory_cost_entries e
+            JOIN products p ON p.id = e.product_id
+            WHERE e.tenant_id = ? AND e.created_at <= ?
+            GROUP BY p.id, p.sku, p.name, p.category
+            HAVING ABS(SUM(e.quantity)) > 1e-9 OR ABS(SUM(e.value)) > 0.005
+            ORDER BY p.sku
+            ",
+        )
+        .bind(tenant_id)
+        .bind(as_of)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for product in &mut products {
+            product.value = round_money(product.value);
+            product.unit_cost = if product.quantity.abs() > QUANTITY_EPSILON {
+                round_money(product.value / product.quantity)
+            } else {
+                0.0
+            };
+        }
+
+        Ok(ValuationReport {
+            as_of: as_of.to_string(),
+            costing_method: method,
+            total_quantity: products.iter().map(|p| p.quantity).sum(),
+            total_value: round_money(products.iter().map(|p| p.value).sum()),
+            products,
+        })
+    }
+
+    /// A product's cost layers, oldest first; only those with units left
+    /// unless `all`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the product does not exist or the database read
+    /// fails.
+    pub async fn layers(&self, tenant_id: &str, product_id: &str, all: bool) -> Result<Vec<CostLayer>, CostingError> {
+        let exists: Option<String> = sqlx::query_scalar("SELECT id FROM products WHERE id = ? AND tenant_id = ?")
+            .bind(product_id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if exists.is_none() {
+            return Err(CostingError::ProductNotFound(product_id.to_string()));
+        }
+
+        let layers = sqlx::query_as::<_, CostLayer>(
+            "SELECT id, product_id, movement_id, quantity, remaining_quantity, unit_cost, created_at
+             FROM inventory_cost_layers
+             WHERE tenant_id = ? AND product_id = ? AND (? OR remaining_quantity > 0)
+             ORDER BY created_at ASC, rowid ASC",
+        )
+        .bind(tenant_id)
+        .bind(product_id)
+        .bind(all)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(layers)
+    }
+}
+
+fn round_money(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::inventory_ledger_service::{self, MovementType, StockMovement};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                sku TEXT NOT NULL,
+                name TEXT NOT NULL,
+                category TEXT,
+                store_id TEXT,
+                cost REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                quantity_on_hand REAL NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE settings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT,
+                data_type TEXT NOT NULL DEFAULT 'string',
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(key, scope, scope_id)
+            )",
+            "INSERT INTO stores (id, name) VALUES ('s1', 'Main Street')",
+            "INSERT INTO products (id, tenant_id, sku, name, category, store_id, cost, quantity_on_hand) VALUES
+                ('p1', 't1', 'PAD-1', 'Brake pads', 'Brakes', 's1', 5.0, 10)",
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    async fn move_stock(pool: &SqlitePool, movement_type: MovementType, quantity: f64, unit_cost: Option<f64>) -> f64 {
+        let movement = StockMovement::new("t1", "p1", movement_type, quantity, "test").with_unit_cost(unit_cost);
+        let mut conn = pool.acquire().await.unwrap();
+        inventory_ledger_service::record_movement(&mut conn, &movement)
+            .await
+            .unwrap()
+            .unit_cost
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_weighted_average_charges_moving_average() {
+        let pool = setup_test_db().await;
+        let service = CostingService::new(pool.clone());
+        assert_eq!(service.method("t1").await.unwrap(), CostingMethod::WeightedAverage);
+
+        // 10 @ 5.00 on hand, 10 more @ 7.00 received
+        assert_eq!(move_stock(&pool, MovementType::Receipt, 10.0, Some(7.0)).await, 7.0);
+        assert!((move_stock(&pool, MovementType::Sale, -5.0, None).await - 6.0).abs() < 1e-9);
+
+        // Incoming units without a cost come in at the average
+        assert!((move_stock(&pool, MovementType::Adjustment, 1.0, None).await - 6.0).abs() < 1e-9);
+
+        let report = service.valuation("t1", &chrono::Utc::now().to_rfc3339()).await.unwrap();
+        assert_eq!(report.products.len(), 1);
+        assert_eq!(report.products[0].quantity, 16.0);
+        assert_eq!(report.total_value, 96.0);
+        assert_eq!(report.products[0].unit_cost, 6.0);
+
+        // The layers are drawn down oldest first all the same
+        let open = service.layers("t1", "p1", false).await.unwrap();
+        assert_eq!(
+            open.iter().map(|layer| (layer.remaining_quantity, layer.unit_cost)).collect::<Vec<_>>(),
+            vec![(5.0, 5.0), (10.0, 7.0), (1.0, 6.0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fifo_consumes_oldest_layers_and_values_as_of_date() {
+        let pool = setup_test_db().await;
+        let service = CostingService::new(pool.clone());
+        service.set_method("t1", CostingMethod::Fifo).await.unwrap();
+        assert_eq!(service.method("t1").await.unwrap(), CostingMethod::Fifo);
+
+        move_stock(&pool, MovementType::Receipt, 10.0, Some(7.0)).await;
+        let before_sale = chrono::Utc::now().to_rfc3339();
+
+        // 10 @ 5.00 then 2 @ 7.00
+        let unit_cost = move_stock(&pool, MovementType::Sale, -12.0, None).await;
+        assert!((unit_cost - 64.0 / 12.0).abs() < 1e-9);
+
+        let open = service.layers("t1", "p1", false).await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!((open[0].remaining_quantity, open[0].unit_cost), (8.0, 7.0));
+
+        // Selling past the layers charges the latest cost
+        let unit_cost = move_stock(&pool, MovementType::Sale, -10.0, None).await;
+        assert!((unit_cost - 7.0).abs() < 1e-9);
+
+        let then = service.valuation("t1", &before_sale).await.unwrap();
+        assert_eq!((then.total_quantity, then.total_value), (20.0, 120.0));
+        let now = service.valuation("t1", &chrono::Utc::now().to_rfc3339()).await.unwrap();
+        assert_eq!((now.total_quantity, now.total_value), (-2.0, -14.0));
+
+        // Transfers move no value
+        move_stock_transfer(&pool).await;
+        assert_eq!(service.layers("t1", "p1", true).await.unwrap().len(), 2);
+    }
+
+    async fn move_stock_transfer(pool: &SqlitePool) {
+        let movement = StockMovement::new("t1", "p1", MovementType::Transfer, 1.0, "test");
+        let mut conn = pool.acquire().await.unwrap();
+        let recorded = inventory_ledger_service::record_movement(&mut conn, &movement).await.unwrap();
+        assert_eq!(recorded.unit_cost, None);
+    }
+}
@@ -0,0 +1,1061 @@
+/**
+ * Count Service
+ *
+ * Cycle counts and full physical inventories of a store.
+ *
+ * Lifecycle of a count session:
+ * - start: every product in scope (the whole store, a category or a bin) gets
+ *   a line with its expected quantity and unit cost frozen
+ * - count: counters scan barcodes or key in SKUs from any number of devices;
+ *   entries add up on the line, and a negative entry takes back a mis-scan
+ * - finish: products not counted are counted as zero; lines whose variance
+ *   is above the session's threshold must be counted again, after which the
+ *   session waits for approval
+ * - approve: a manager posts each variance to the inventory ledger
+ *
+ * Variances are posted as changes rather than new levels, so sales and
+ * receipts made while the count ran are kept. The valuation report prices
+ * each variance at the cost frozen when the session started.
+ */
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_CYCLE_COUNT, SOURCE_COUNT_SESSION,
+};
+
+/// Setting holding the variance, in percent, that calls for a recount
+pub const SETTING_RECOUNT_THRESHOLD_PERCENT: &str = "inventory.count_recount_threshold_percent";
+
+/// Variances over 5% of the expected quantity are recounted unless configured otherwise
+pub const DEFAULT_RECOUNT_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// Quantities closer than this are the same
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum CountError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Count session not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid count session state: {0}")]
+    InvalidState(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<LedgerError> for CountError {
+    fn from(err: LedgerError) -> Self {
+        match err {
+            LedgerError::Database(e) => Self::Database(e),
+            other => Self::Validation(other.to_string()),
+        }
+    }
+}
+
+impl From<CountError> for ApiError {
+    fn from(err: CountError) -> Self {
+        match err {
+            CountError::Validation(msg) => Self::bad_request(msg),
+            CountError::NotFound(id) => Self::not_found(format!("Count session not found: {id}")),
+            CountError::InvalidState(msg) => Self::conflict(msg),
+            CountError::Database(e) => Self::internal(format!("Failed to access count sessions: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountStatus {
+    Counting,
+    Recount,
+    PendingApproval,
+    Posted,
+    Cancelled,
+}
+
+impl CountStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Counting => "counting",
+            Self::Recount => "recount",
+            Self::PendingApproval => "pending_approval",
+            Self::Posted => "posted",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// What a session counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountScope {
+    /// Every product stocked at the store
+    Full,
+    /// Products of one category
+    Category,
+    /// Products in one bin
+    Bin,
+}
+
+impl CountScope {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Category => "category",
+            Self::Bin => "bin",
+        }
+    }
+}
+
+/// A count session to start
+#[derive(Debug, Clone)]
+pub struct CountSessionRequest {
+    pub tenant_id: String,
+    pub store_id: String,
+    pub scope: CountScope,
+    /// The category or bin; required unless the scope is full
+    pub scope_value: Option<String>,
+    /// Defaults to the store's setting
+    pub recount_threshold_percent: Option<f64>,
+    pub created_by: String,
+    pub notes: Option<String>,
+}
+
+/// A scan or keyed quantity
+#[derive(Debug, Clone, Deserialize)]
+pub struct CountEntry {
+    /// Barcode, SKU or alternate SKU
+    pub code: String,
+    /// Defaults to one; negative to take back a mis-scan
+    pub quantity: Option<f64>,
+    pub device_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct CountLine {
+    pub id: String,
+    pub product_id: String,
+    pub sku: Option<String>,
+    pub product_name: Option<String>,
+    pub bin_location: Option<String>,
+    pub expected_quantity: f64,
+    pub unit_cost: f64,
+    pub counted_quantity: Option<f64>,
+    pub recount_required: bool,
+    pub recount_quantity: Option<f64>,
+}
+
+impl CountLine {
+    /// The quantity that stands: the recount if there was one, else the count
+    #[must_use]
+    pub fn final_quantity(&self) -> f64 {
+        match (self.recount_required, self.recount_quantity) {
+            (true, Some(recounted)) => recounted,
+            _ => self.counted_quantity.unwrap_or(0.0),
+        }
+    }
+
+    /// Counted less expected
+    #[must_use]
+    pub fn variance(&self) -> f64 {
+        self.final_quantity() - self.expected_quantity
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CountSession {
+    pub id: String,
+    pub session_number: String,
+    pub store_id: String,
+    /// full, category or bin
+    pub scope: String,
+    pub scope_value: Option<String>,
+    /// counting, recount, pending_approval, posted or cancelled
+    pub status: String,
+    pub recount_threshold_percent: f64,
+    pub notes: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub submitted_at: Option<String>,
+    pub approved_by: Option<String>,
+    pub approved_at: Option<String>,
+    #[sqlx(skip)]
+    pub lines: Vec<CountLine>,
+}
+
+/// A product whose count differs from what was expected
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VarianceLine {
+    pub product_id: String,
+    pub sku: Option<String>,
+    pub product_name: Option<String>,
+    pub expected_quantity: f64,
+    pub counted_quantity: f64,
+    pub variance: f64,
+    pub unit_cost: f64,
+    pub value_change: f64,
+}
+
+/// Valuation impact of a count session
+#[derive(Debug, Clone, Serialize)]
+pub struct ValuationReport {
+    pub session_id: String,
+    pub session_number: String,
+    pub status: String,
+    pub lines_in_scope: usize,
+    pub lines_counted: usize,
+    pub variances: Vec<VarianceLine>,
+    /// Value of stock missing, as a negative amount
+    pub shrinkage_value: f64,
+    /// Value of stock found
+    pub overage_value: f64,
+    pub net_value_change: f64,
+}
+
+impl ValuationReport {
+    fn for_session(session: &CountSession) -> Self {
+        let variances: Vec<VarianceLine> = session
+            .lines
+            .iter()
+            .filter(|line| line.variance().abs() > QUANTITY_EPSILON)
+            .map(|line| VarianceLine {
+                product_id: line.product_id.clone(),
+                sku: line.sku.clone(),
+                product_name: line.product_name.clone(),
+                expected_quantity: line.expected_quantity,
+                counted_quantity: line.final_quantity(),
+                variance: line.variance(),
+                unit_cost: line.unit_cost,
+                value_change: round_money(line.variance() * line.unit_cost),
+            })
+            .collect();
+
+        let shrinkage_value: f64 = variances.iter().map(|v| v.value_change).filter(|v| *v < 0.0).sum();
+        let overage_value: f64 = variances.iter().map(|v| v.value_change).filter(|v| *v > 0.0).sum();
+
+        Self {
+            session_id: session.id.clone(),
+            session_number: session.session_number.clone(),
+            status: session.status.clone(),
+            lines_in_scope: session.lines.len(),
+            lines_counted: session.lines.iter().filter(|line| line.counted_quantity.is_some()).count(),
+            variances,
+            shrinkage_value: round_money(shrinkage_value),
+            overage_value: round_money(overage_value),
+            net_value_change: round_money(shrinkage_value + overage_value),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ExpectedStock {
+    product_id: String,
+    bin_location: Option<String>,
+    expected_quantity: f64,
+    unit_cost: f64,
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+const SELECT_COUNT_SESSION: &str = "SELECT id, session_number, store_id, scope, scope_value, status,
+        recount_threshold_percent, notes, created_by, created_at, submitted_at, approved_by, approved_at
+     FROM count_sessions";
+
+/// Sessions still open; a product is in at most one of them per store
+const OPEN_STATUSES: &str = "('counting', 'recount', 'pending_approval')";
+
+pub struct CountService {
+    pool: SqlitePool,
+}
+
+impl CountService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Start a count, freezing the expected quantities
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store is unknown, a category or bin scope has
+    /// no value, nothing is in scope, a product in scope is already being
+    /// counted, or the database write fails.
+    pub async fn start(&self, request: CountSessionRequest) -> Result<CountSession, CountError> {
+        let scope_value = request.scope_value.as_deref().map(str::trim).filter(|v| !v.is_empty());
+        if request.scope != CountScope::Full && scope_value.is_none() {
+            return Err(CountError::Validation(format!(
+                "A {} count needs the {} to count",
+                request.scope.as_str(),
+                request.scope.as_str()
+            )));
+        }
+        if request.recount_threshold_percent.is_some_and(|percent| percent < 0.0) {
+            return Err(CountError::Validation("Recount threshold cannot be negative".to_string()));
+        }
+
+        let store: Option<String> = sqlx::query_scalar("SELECT id FROM stores WHERE id = ?")
+            .bind(&request.store_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if store.is_none() {
+            return Err(CountError::Validation(format!("Store not found: {}", request.store_id)));
+        }
+
+        let threshold = match request.recount_threshold_percent {
+            Some(percent) => percent,
+            None => self.recount_threshold_percent(&request.store_id).await?,
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        let expected = sqlx::query_as::<_, ExpectedStock>(
+            r"
+            SELECT
+                p.id AS product_id,
+                l.bin_location,
+                COALESCE(l.quantity_on_hand, CASE WHEN p.store_id = ? THEN p.quantity_on_hand ELSE 0.0 END)
+                    AS expected_quantity,
+                p.cost AS unit_cost
+            FROM products p
+            LEFT JOIN product_locations l ON l.product_id = p.id AND l.store_id = ?
+            WHERE p.tenant_id = ? AND p.is_active = 1
+              AND (l.id IS NOT NULL OR p.store_id = ?)
+              AND (? <> 'category' OR p.category = ?)
+              AND (? <> 'bin' OR l.bin_location = ?)
+            ORDER BY l.bin_location, p.sku
+            ",
+        )
+        .bind(&request.store_id)
+        .bind(&request.store_id)
+        .bind(&request.tenant_id)
+        .bind(&request.store_id)
+        .bind(request.scope.as_str())
+        .bind(scope_value)
+        .bind(request.scope.as_str())
+        .bind(scope_value)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if expected.is_empty() {
+            return Err(CountError::Validation("Nothing to count in this scope".to_string()));
+        }
+
+        let product_ids = serde_json::to_string(
+            &expected.iter().map(|stock| stock.product_id.as_str()).collect::<Vec<_>>(),
+        )
+        .unwrap_or_else(|_| "[]".to_string());
+        let overlapping: Option<String> = sqlx::query_scalar(&format!(
+            "SELECT s.session_number FROM count_sessions s
+             JOIN count_session_lines l ON l.session_id = s.id
+             WHERE s.tenant_id = ? AND s.store_id = ? AND s.status IN {OPEN_STATUSES}
+               AND l.product_id IN (SELECT value FROM json_each(?))
+             LIMIT 1"
+        ))
+        .bind(&request.tenant_id)
+        .bind(&request.store_id)
+        .bind(&product_ids)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(session_number) = overlapping {
+            return Err(CountError::InvalidState(format!(
+                "Products in this scope are already being counted in {session_number}"
+            )));
+        }
+
+        let today = Utc::now().format("%Y%m%d").to_string();
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM count_sessions WHERE tenant_id = ? AND DATE(created_at) = DATE('now')",
+        )
+        .bind(&request.tenant_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let session_number = format!("CNT-{}-{:04}", today, count + 1);
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r"
+            INSERT INTO count_sessions (
+                id, tenant_id, session_number, store_id, scope, scope_value, status,
+                recount_threshold_percent, notes, created_by, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&id)
+        .bind(&request.tenant_id)
+        .bind(&session_number)
+        .bind(&request.store_id)
+        .bind(request.scope.as_str())
+        .bind(scope_value)
+        .bind(CountStatus::Counting.as_str())
+        .bind(threshold)
+        .bind(&request.notes)
+        .bind(&request.created_by)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        for stock in &expected {
+            sqlx::query(
+                r"
+                INSERT INTO count_session_lines (
+                    id, session_id, product_id, bin_location, expected_quantity, unit_cost
+                ) VALUES (?, ?, ?, ?, ?, ?)
+                ",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&id)
+            .bind(&stock.product_id)
+            .bind(&stock.bin_location)
+            .bind(stock.expected_quantity)
+            .bind(stock.unit_cost)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        tracing::info!(
+            count_session_id = %id,
+            session_number = %session_number,
+            store_id = %request.store_id,
+            scope = request.scope.as_str(),
+            lines = expected.len(),
+            "Count session started"
+        );
+
+        self.get(&request.tenant_id, &id).await
+    }
+
+    /// Add a scan or keyed quantity to the count
+    ///
+    /// During a recount only the lines flagged for it can be counted, and
+    /// their recount starts from zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not being counted, the code is not
+    /// a product in the session, the line does not need a recount, or the
+    /// total would drop below zero.
+    pub async fn record_entry(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        user_id: &str,
+        entry: &CountEntry,
+    ) -> Result<CountLine, CountError> {
+        let quantity = entry.quantity.unwrap_or(1.0);
+        if quantity.abs() < QUANTITY_EPSILON {
+            return Err(CountError::Validation("Counted quantity cannot be zero".to_string()));
+        }
+        let code = entry.code.trim();
+        if code.is_empty() {
+            return Err(CountError::Validation("Scan a barcode or enter a SKU".to_string()));
+        }
+
+        let session = self.session(tenant_id, id).await?;
+        let round = match session.status.as_str() {
+            "counting" => 1,
+            "recount" => 2,
+            other => {
+                return Err(CountError::InvalidState(format!(
+                    "Count session {} is {other} and no longer takes counts",
+                    session.session_number
+                )))
+            }
+        };
+
+        let line: Option<(String, bool)> = sqlx::query_as(
+            "SELECT l.id, l.recount_required FROM count_session_lines l
+             JOIN products p ON p.id = l.product_id
+             WHERE l.session_id = ?
+               AND (p.barcode = ? OR p.sku = ? OR EXISTS (
+                   SELECT 1 FROM product_alternate_skus a
+                   WHERE a.product_id = p.id AND a.alternate_sku = ? AND a.tenant_id = p.tenant_id
+               ))
+             ORDER BY CASE WHEN p.barcode = ? THEN 0 WHEN p.sku = ? THEN 1 ELSE 2 END
+             LIMIT 1",
+        )
+        .bind(id)
+        .bind(code)
+        .bind(code)
+        .bind(code)
+        .bind(code)
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((line_id, recount_required)) = line else {
+            return Err(CountError::Validation(format!(
+                "{code} is not a product in count session {}",
+                session.session_number
+            )));
+        };
+        if round == 2 && !recount_required {
+            return Err(CountError::InvalidState(format!("{code} does not need a recount")));
+        }
+
+        let column = if round == 1 { "counted_quantity" } else { "recount_quantity" };
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query(&format!(
+            "UPDATE count_session_lines SET {column} = COALESCE({column}, 0.0) + ?
+             WHERE id = ? AND COALESCE({column}, 0.0) + ? >= 0"
+        ))
+        .bind(quantity)
+        .bind(&line_id)
+        .bind(quantity)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(CountError::Validation(format!("Count of {code} cannot go below zero")));
+        }
+
+        sqlx::query(
+            r"
+            INSERT INTO count_entries (
+                id, session_id, line_id, code, quantity, round, device_id, counted_by, counted_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(id)
+        .bind(&line_id)
+        .bind(code)
+        .bind(quantity)
+        .bind(round)
+        .bind(&entry.device_id)
+        .bind(user_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let lines = self.lines(id, Some(&line_id)).await?;
+        lines.into_iter().next().ok_or_else(|| CountError::NotFound(id.to_string()))
+    }
+
+    /// Finish the count or the recount
+    ///
+    /// Finishing the count flags the lines to recount; the session goes to
+    /// recount if there are any and waits for approval otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not being counted or flagged lines
+    /// have not been recounted.
+    pub async fn finish(&self, tenant_id: &str, id: &str) -> Result<CountSession, CountError> {
+        let session = self.get(tenant_id, id).await?;
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        let status = match session.status.as_str() {
+            "counting" => {
+                let mut recounts = 0;
+                for line in &session.lines {
+                    if needs_recount(line, session.recount_threshold_percent) {
+                        sqlx::query("UPDATE count_session_lines SET recount_required = 1 WHERE id = ?")
+                            .bind(&line.id)
+                            .execute(&mut *tx)
+                            .await?;
+                        recounts += 1;
+                    }
+                }
+                if recounts > 0 {
+                    CountStatus::Recount
+                } else {
+                    CountStatus::PendingApproval
+                }
+            }
+            "recount" => {
+                let missing = session
+                    .lines
+                    .iter()
+                    .filter(|line| line.recount_required && line.recount_quantity.is_none())
+                    .count();
+                if missing > 0 {
+                    return Err(CountError::Validation(format!(
+                        "{missing} products still need a recount"
+                    )));
+                }
+                CountStatus::PendingApproval
+            }
+            other => {
+                return Err(CountError::InvalidState(format!(
+                    "Count session {} is {other} and cannot be finished",
+                    session.session_number
+                )))
+            }
+        };
+
+        let submitted_at = (status == CountStatus::PendingApproval).then_some(now.as_str());
+        sqlx::query(
+            "UPDATE count_sessions SET status = ?, submitted_at = ?, updated_at = ?
+             WHERE id = ? AND status = ?",
+        )
+        .bind(status.as_str())
+        .bind(submitted_at)
+        .bind(&now)
+        .bind(id)
+        .bind(&session.status)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            count_session_id = %id,
+            session_number = %session.session_number,
+            status = status.as_str(),
+            "Count session finished"
+        );
+
+        self.get(tenant_id, id).await
+    }
+
+    /// Approve a finished count and post its variances to the ledger
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not waiting for approval or the
+    /// database write fails.
+    pub async fn approve(&self, tenant_id: &str, id: &str, user_id: &str) -> Result<ValuationReport, CountError> {
+        let session = self.get(tenant_id, id).await?;
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        let approved = sqlx::query(
+            "UPDATE count_sessions SET status = ?, approved_by = ?, approved_at = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ? AND status = ?",
+        )
+        .bind(CountStatus::Posted.as_str())
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(CountStatus::PendingApproval.as_str())
+        .execute(&mut *tx)
+        .await?;
+        if approved.rows_affected() == 0 {
+            return Err(CountError::InvalidState(format!(
+                "Count session {} is {} and cannot be approved",
+                session.session_number, session.status
+            )));
+        }
+
+        for line in &session.lines {
+            let variance = line.variance();
+            if variance.abs() < QUANTITY_EPSILON {
+                continue;
+            }
+
+            let movement = StockMovement::new(
+                tenant_id,
+                &line.product_id,
+                MovementType::Count,
+                variance,
+                REASON_CYCLE_COUNT,
+            )
+            .with_source(SOURCE_COUNT_SESSION, id)
+            .by_user(user_id)
+            .at_store(&session.store_id);
+            inventory_ledger_service::record_movement(&mut tx, &movement).await?;
+        }
+
+        tx.commit().await?;
+
+        let session = self.get(tenant_id, id).await?;
+        let report = ValuationReport::for_session(&session);
+
+        tracing::info!(
+            count_session_id = %id,
+            session_number = %session.session_number,
+            approved_by = %user_id,
+            variances = report.variances.len(),
+            net_value_change = report.net_value_change,
+            "Count session approved and posted"
+        );
+
+        Ok(report)
+    }
+
+    /// Drop a session that has not been posted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is unknown, posted or cancelled.
+    pub async fn cancel(&self, tenant_id: &str, id: &str, user_id: &str) -> Result<CountSession, CountError> {
+        let now = Utc::now().to_rfc3339();
+
+        let cancelled = sqlx::query(&format!(
+            "UPDATE count_sessions SET status = ?, cancelled_by = ?, cancelled_at = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ? AND status IN {OPEN_STATUSES}"
+        ))
+        .bind(CountStatus::Cancelled.as_str())
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        let session = self.get(tenant_id, id).await?;
+        if cancelled.rows_affected() == 0 {
+            return Err(CountError::InvalidState(format!(
+                "Count session {} is {} and cannot be cancelled",
+                session.session_number, session.status
+            )));
+        }
+
+        tracing::info!(count_session_id = %id, session_number = %session.session_number, "Count session cancelled");
+
+        Ok(session)
+    }
+
+    /// Valuation impact of the variances found so far
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotFound` if the tenant has no such session.
+    pub async fn report(&self, tenant_id: &str, id: &str) -> Result<ValuationReport, CountError> {
+        let session = self.get(tenant_id, id).await?;
+        Ok(ValuationReport::for_session(&session))
+    }
+
+    /// Count sessions, newest first, without their lines
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn list(
+        &self,
+        tenant_id: &str,
+        store_id: Option<&str>,
+        status: Option<CountStatus>,
+    ) -> Result<Vec<CountSession>, CountError> {
+        let status = status.map(CountStatus::as_str);
+
+        let sessions = sqlx::query_as::<_, CountSession>(&format!(
+            "{SELECT_COUNT_SESSION}
+             WHERE tenant_id = ?
+               AND (? IS NULL OR store_id = ?)
+               AND (? IS NULL OR status = ?)
+             ORDER BY created_at DESC"
+        ))
+        .bind(tenant_id)
+        .bind(store_id)
+        .bind(store_id)
+        .bind(status)
+        .bind(status)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// A count session with its lines
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotFound` if the tenant has no such session.
+    pub async fn get(&self, tenant_id: &str, id: &str) -> Result<CountSession, CountError> {
+        let mut session = self.session(tenant_id, id).await?;
+        session.lines = self.lines(id, None).await?;
+        Ok(session)
+    }
+
+    async fn session(&self, tenant_id: &str, id: &str) -> Result<CountSession, CountError> {
+        sqlx::query_as::<_, CountSession>(&format!("{SELECT_COUNT_SESSION} WHERE id = ? AND tenant_id = ?"))
+            .bind(id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| CountError::NotFound(id.to_string()))
+    }
+
+    async fn lines(&self, id: &str, line_id: Option<&str>) -> Result<Vec<CountLine>, CountError> {
+        let lines = sqlx::query_as::<_, CountLine>(
+            "SELECT l.id, l.product_id, p.sku, p.name AS product_name, l.bin_location,
+                    l.expected_quantity, l.unit_cost, l.counted_quantity, l.recount_required,
+                    l.recount_quantity
+             FROM count_session_lines l
+             LEFT JOIN products p ON p.id = l.product_id
+             WHERE l.session_id = ? AND (? IS NULL OR l.id = ?)
+             ORDER BY l.bin_location, p.sku",
+        )
+        .bind(id)
+        .bind(line_id)
+        .bind(line_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lines)
+    }
+
+    async fn recount_threshold_percent(&self, store_id: &str) -> Result<f64, CountError> {
+        let value: Option<String> = sqlx::query_scalar(
+            "SELECT value FROM settings
+             WHERE key = ? AND ((scope = 'store' AND scope_id = ?) OR scope = 'global')
+             ORDER BY CASE scope WHEN 'store' THEN 1 ELSE 2 END
+             LIMIT 1",
+        )
+        .bind(SETTING_RECOUNT_THRESHOLD_PERCENT)
+        .bind(store_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(value
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|percent| *percent >= 0.0)
+            .unwrap_or(DEFAULT_RECOUNT_THRESHOLD_PERCENT))
+    }
+}
+
+/// Whether a line's variance is above the threshold; any variance on a
+/// product expected to be out of stock is
+fn needs_recount(line: &CountLine, threshold_percent: f64) -> bool {
+    let variance = line.variance().abs();
+    if variance < QUANTITY_EPSILON {
+        return false;
+    }
+    if line.expected_quantity.abs() < QUANTITY_EPSILON {
+        return true;
+    }
+    variance > line.expected_quantity.abs() * threshold_percent / 100.0
+}
+
+fn round_money(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                sku TEXT NOT NULL,
+                name TEXT NOT NULL,
+                category TEXT NOT NULL,
+                barcode TEXT,
+                store_id TEXT,
+                cost REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                is_active INTEGER NOT NULL DEFAULT 1
+            )",
+            "CREATE TABLE product_alternate_skus (
+                id TEXT PRIMARY KEY,
+                product_id TEXT NOT NULL,
+                alternate_sku TEXT NOT NULL,
+                tenant_id TEXT NOT NULL
+            )",
+            "CREATE TABLE settings (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            "INSERT INTO stores (id, name) VALUES ('s1', 'Main Street')",
+            "INSERT INTO products (id, tenant_id, sku, name, category, barcode, store_id, cost, quantity_on_hand) VALUES
+                ('p1', 't1', 'PAD-1', 'Brake pads', 'Brakes', '0001', 's1', 20.0, 100),
+                ('p2', 't1', 'ROT-2', 'Rotor', 'Brakes', '0002', 's1', 50.0, 10),
+                ('p3', 't1', 'OIL-5', 'Oil 5L', 'Fluids', '0003', 's1', 30.0, 4)",
+            "INSERT INTO product_alternate_skus (id, product_id, alternate_sku, tenant_id) VALUES
+                ('a1', 'p2', 'MFR-ROTOR', 't1')",
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/070_count_sessions.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    fn request(scope: CountScope, scope_value: Option<&str>) -> CountSessionRequest {
+        CountSessionRequest {
+            tenant_id: "t1".to_string(),
+            store_id: "s1".to_string(),
+            scope,
+            scope_value: scope_value.map(str::to_string),
+            recount_threshold_percent: None,
+            created_by: "u1".to_string(),
+            notes: None,
+        }
+    }
+
+    fn scan(code: &str, quantity: f64, device: &str) -> CountEntry {
+        CountEntry {
+            code: code.to_string(),
+            quantity: Some(quantity),
+            device_id: Some(device.to_string()),
+        }
+    }
+
+    async fn on_hand(pool: &SqlitePool, product_id: &str) -> f64 {
+        sqlx::query_scalar("SELECT quantity_on_hand FROM product_locations WHERE product_id = ? AND store_id = 's1'")
+            .bind(product_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_count_recount_and_post_variances() {
+        let pool = setup_test_db().await;
+        let service = CountService::new(pool.clone());
+
+        let session = service.start(request(CountScope::Full, None)).await.unwrap();
+        assert_eq!(session.status, "counting");
+        assert!(session.session_number.starts_with("CNT-"));
+        assert_eq!(session.recount_threshold_percent, DEFAULT_RECOUNT_THRESHOLD_PERCENT);
+        assert_eq!(session.lines.len(), 3);
+
+        // Two devices count the pads; a mis-scan is taken back
+        service.record_entry("t1", &session.id, "u1", &scan("0001", 60.0, "hh-1")).await.unwrap();
+        service.record_entry("t1", &session.id, "u2", &scan("PAD-1", 38.0, "hh-2")).await.unwrap();
+        service.record_entry("t1", &session.id, "u2", &scan("0002", 1.0, "hh-2")).await.unwrap();
+        let line = service.record_entry("t1", &session.id, "u2", &scan("0002", -1.0, "hh-2")).await.unwrap();
+        assert_eq!(line.counted_quantity, Some(0.0));
+        service.record_entry("t1", &session.id, "u1", &scan("MFR-ROTOR", 7.0, "hh-1")).await.unwrap();
+        assert!(matches!(
+            service.record_entry("t1", &session.id, "u1", &scan("0003", -1.0, "hh-1")).await,
+            Err(CountError::Validation(_))
+        ));
+        assert!(matches!(
+            service.record_entry("t1", &session.id, "u1", &scan("NOPE", 1.0, "hh-1")).await,
+            Err(CountError::Validation(_))
+        ));
+
+        // Pads are 2% out, within the threshold; rotors 30% out and oil,
+        // never counted, 100% out go to a recount
+        let session = service.finish("t1", &session.id).await.unwrap();
+        assert_eq!(session.status, "recount");
+        let flagged: Vec<_> = session.lines.iter().filter(|l| l.recount_required).map(|l| l.product_id.as_str()).collect();
+        assert_eq!(flagged, vec!["p3", "p2"]);
+
+        assert!(matches!(
+            service.record_entry("t1", &session.id, "u1", &scan("0001", 1.0, "hh-1")).await,
+            Err(CountError::InvalidState(_))
+        ));
+        service.record_entry("t1", &session.id, "u3", &scan("0002", 8.0, "hh-1")).await.unwrap();
+        assert!(matches!(service.finish("t1", &session.id).await, Err(CountError::Validation(_))));
+        service.record_entry("t1", &session.id, "u3", &scan("0003", 4.0, "hh-1")).await.unwrap();
+
+        let session = service.finish("t1", &session.id).await.unwrap();
+        assert_eq!(session.status, "pending_approval");
+        assert!(session.submitted_at.is_some());
+
+        // A sale while the count waited is kept
+        let sale = StockMovement::new("t1", "p1", MovementType::Sale, -5.0, "sale").at_store("s1");
+        let mut conn = pool.acquire().await.unwrap();
+        inventory_ledger_service::record_movement(&mut conn, &sale).await.unwrap();
+        drop(conn);
+
+        let report = service.approve("t1", &session.id, "m1").await.unwrap();
+        assert_eq!(report.status, "posted");
+        assert_eq!(report.lines_in_scope, 3);
+        assert_eq!(report.variances.len(), 2);
+        assert_eq!(report.shrinkage_value, -140.0);
+        assert_eq!(report.overage_value, 0.0);
+        assert_eq!(report.net_value_change, -140.0);
+
+        assert_eq!(on_hand(&pool, "p1").await, 93.0);
+        assert_eq!(on_hand(&pool, "p2").await, 8.0);
+        assert_eq!(on_hand(&pool, "p3").await, 4.0);
+
+        let movements: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM inventory_movements WHERE source_type = 'count_session' AND source_id = ?",
+        )
+        .bind(&session.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(movements, 2);
+
+        assert!(matches!(service.approve("t1", &session.id, "m1").await, Err(CountError::InvalidState(_))));
+        assert!(matches!(service.cancel("t1", &session.id, "m1").await, Err(CountError::InvalidState(_))));
+    }
+
+    #[tokio::test]
+    async fn test_scoped_sessions_do_not_overlap() {
+        let pool = setup_test_db().await;
+        let service = CountService::new(pool.clone());
+
+        assert!(matches!(
+            service.start(request(CountScope::Category, None)).await,
+            Err(CountError::Validation(_))
+        ));
+        assert!(matches!(
+            service.start(request(CountScope::Category, Some("Tyres"))).await,
+            Err(CountError::Validation(_))
+        ));
+
+        let brakes = service.start(request(CountScope::Category, Some("Brakes"))).await.unwrap();
+        assert_eq!(brakes.lines.len(), 2);
+        assert!(matches!(
+            service.start(request(CountScope::Full, None)).await,
+            Err(CountError::InvalidState(_))
+        ));
+
+        sqlx::query("UPDATE product_locations SET bin_location = 'F-01' WHERE product_id = 'p3'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut bin = request(CountScope::Bin, Some("F-01"));
+        bin.recount_threshold_percent = Some(50.0);
+        let bin = service.start(bin).await.unwrap();
+        assert_eq!(bin.lines.len(), 1);
+        assert_eq!(bin.lines[0].bin_location.as_deref(), Some("F-01"));
+
+        // Within 50%, nothing to recount
+        service.record_entry("t1", &bin.id, "u1", &scan("OIL-5", 3.0, "hh-1")).await.unwrap();
+        let bin = service.finish("t1", &bin.id).await.unwrap();
+        assert_eq!(bin.status, "pending_approval");
+        assert_eq!(service.report("t1", &bin.id).await.unwrap().shrinkage_value, -30.0);
+
+        service.cancel("t1", &brakes.id, "u1").await.unwrap();
+        assert_eq!(service.list("t1", Some("s1"), Some(CountStatus::Cancelled)).await.unwrap().len(), 1);
+        assert_eq!(on_hand(&pool, "p1").await, 100.0);
+    }
+}
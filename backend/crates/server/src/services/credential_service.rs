@@ -69,6 +69,16 @@ pub struct CloverCredentials {
     pub merchant_id: String,
 }
 
+/// Shopify credentials (app install token)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopifyCredentials {
+    pub shop_domain: String,
+    pub access_token: String,
+    /// Location whose stock is synced; the shop's first active one when unset
+    #[serde(default)]
+    pub location_id: Option<i64>,
+}
+
 /// Stripe Connect credentials (OAuth tokens)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StripeConnectCredentials {
@@ -88,6 +98,7 @@ pub enum PlatformCredentials {
     Square(SquareCredentials),
     Clover(CloverCredentials),
     Stripe(StripeConnectCredentials),
+    Shopify(ShopifyCredentials),
 }
 
 /// Integration credential record
@@ -241,6 +252,9 @@ impl CredentialService {
             PlatformCredentials::Stripe(_) => {
                 ("stripe", None, None, None)
             }
+            PlatformCredentials::Shopify(creds) => {
+                ("shopify", None, Some(creds.shop_domain.clone()), None)
+            }
         };
 
         // Insert into database
@@ -255,6 +269,7 @@ impl CredentialService {
                 realm_id = excluded.realm_id,
                 store_url = excluded.store_url,
                 project_url = excluded.project_url,
+                is_active = 1,
                 updated_at = CURRENT_TIMESTAMP
             "#,
         )
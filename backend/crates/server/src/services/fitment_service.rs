@@ -0,0 +1,1065 @@
+/**
+ * Fitment Service
+ *
+ * Which parts fit which vehicles, and what service a vehicle is due for.
+ *
+ * - Lookup: products whose fitment covers a year, make and model, narrowed
+ *   by engine and trim when given; fitment without an engine or trim fits
+ *   them all. Makes, models and engines are offered level by level for the
+ *   counter's year/make/model/engine pickers.
+ * - Import: fitment in bulk from ACES-style XML (`<App>` elements with
+ *   `<Part>`, `<Make>`, `<Model>`, `<Years from to>`, `<EngineBase>`,
+ *   `<SubModel>` and `<Note>`) or CSV (sku, make, model, year_start,
+ *   year_end, engine, trim, notes). Parts are matched by SKU; rows already
+ *   on file are skipped and bad rows are reported without stopping the rest.
+ * - Maintenance: the `maintenance_schedules` for a vehicle's make, model and
+ *   year, checked against the services recorded on it and the odometer.
+ */
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::models::fitment::{
+    CompatibleProduct, CreateFitmentRequest, DueMaintenance, DueReason, MaintenanceSchedule, VehicleFitment,
+    VehicleQuery,
+};
+
+/// Oldest and newest model years accepted
+const MIN_YEAR: i64 = 1900;
+const MAX_YEAR: i64 = 2100;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum FitmentError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<FitmentError> for ApiError {
+    fn from(err: FitmentError) -> Self {
+        match err {
+            FitmentError::Validation(msg) => Self::bad_request(msg),
+            FitmentError::NotFound(what) => Self::not_found(what),
+            FitmentError::Database(e) => Self::internal(format!("Failed to access vehicle fitment: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitmentImportFormat {
+    Csv,
+    /// ACES-style XML
+    Aces,
+}
+
+impl FitmentImportFormat {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Aces => "aces",
+        }
+    }
+}
+
+/// A fitment row read from an import file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FitmentImportRow {
+    /// Line of the CSV or ordinal of the `<App>`
+    pub row: usize,
+    pub sku: String,
+    pub make: String,
+    pub model: String,
+    pub year_start: i64,
+    pub year_end: i64,
+    pub engine: Option<String>,
+    pub trim: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FitmentImportError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FitmentImportResult {
+    pub format: FitmentImportFormat,
+    pub imported: usize,
+    /// Rows already on file
+    pub duplicates: usize,
+    pub errors: Vec<FitmentImportError>,
+}
+
+/// Next level of the vehicle picker: makes, then models, then engines
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct VehicleOptionsQuery {
+    pub year: Option<i64>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+}
+
+const SELECT_FITMENT: &str = "SELECT id, product_id, make, model, year_start, year_end, engine, trim, notes,
+        source, created_at
+     FROM vehicle_fitment";
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct FitmentService {
+    pool: SqlitePool,
+}
+
+impl FitmentService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Active products that fit a vehicle, by category and name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the vehicle is incomplete or the database read
+    /// fails.
+    pub async fn lookup(&self, tenant_id: &str, vehicle: &VehicleQuery) -> Result<Vec<CompatibleProduct>, FitmentError> {
+        validate_vehicle(&vehicle.make, &vehicle.model, vehicle.year, vehicle.year)?;
+        let engine = non_empty(vehicle.engine.as_deref());
+        let trim = non_empty(vehicle.trim.as_deref());
+
+        let matches = sqlx::query_as::<_, CompatibleProduct>(
+            r"
+            SELECT p.id AS product_id, p.sku, p.name, p.category, p.unit_price, p.quantity_on_hand,
+                   f.id AS fitment_id, f.engine, f.trim, f.notes
+            FROM vehicle_fitment f
+            JOIN products p ON p.id = f.product_id AND p.tenant_id = f.tenant_id
+            WHERE f.tenant_id = ?
+              AND f.make = ? COLLATE NOCASE
+              AND f.model = ? COLLATE NOCASE
+              AND ? BETWEEN f.year_start AND f.year_end
+              AND (f.engine IS NULL OR ? IS NULL OR f.engine = ? COLLATE NOCASE)
+              AND (f.trim IS NULL OR ? IS NULL OR f.trim = ? COLLATE NOCASE)
+              AND p.is_active = 1
+            ORDER BY p.category, p.name, f.engine IS NULL, f.trim IS NULL
+            ",
+        )
+        .bind(tenant_id)
+        .bind(vehicle.make.trim())
+        .bind(vehicle.model.trim())
+        .bind(vehicle.year)
+        .bind(engine)
+        .bind(engine)
+        .bind(trim)
+        .bind(trim)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // A product listed for the exact engine and for all engines shows once
+        let mut seen = HashSet::new();
+        Ok(matches
+            .into_iter()
+            .filter(|product| seen.insert(product.product_id.clone()))
+            .collect())
+    }
+
+    /// Makes for a year, models for a make, or engines for a model
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn vehicle_options(
+        &self,
+        tenant_id: &str,
+        query: &VehicleOptionsQuery,
+    ) -> Result<Vec<String>, FitmentError> {
+        let make = non_empty(query.make.as_deref());
+        let model = non_empty(query.model.as_deref());
+        let column = match (make, model) {
+            (None, _) => "make",
+            (Some(_), None) => "model",
+            (Some(_), Some(_)) => "engine",
+        };
+
+        let options: Vec<String> = sqlx::query_scalar(&format!(
+            "SELECT DISTINCT {column} FROM vehicle_fitment
+             WHERE tenant_id = ? AND {column} IS NOT NULL
+               AND (? IS NULL OR ? BETWEEN year_start AND year_end)
+               AND (? IS NULL OR make = ? COLLATE NOCASE)
+               AND (? IS NULL OR model = ? COLLATE NOCASE)
+             ORDER BY {column} COLLATE NOCASE"
+        ))
+        .bind(tenant_id)
+        .bind(query.year)
+        .bind(query.year)
+        .bind(make)
+        .bind(make)
+        .bind(model)
+        .bind(model)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(options)
+    }
+
+    /// The vehicles a product fits
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn for_product(&self, tenant_id: &str, product_id: &str) -> Result<Vec<VehicleFitment>, FitmentError> {
+        let fitment = sqlx::query_as::<_, VehicleFitment>(&format!(
+            "{SELECT_FITMENT} WHERE tenant_id = ? AND product_id = ?
+             ORDER BY make, model, year_start, engine"
+        ))
+        .bind(tenant_id)
+        .bind(product_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(fitment)
+    }
+
+    /// Add a vehicle range to a product
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the product does not exist, the range is invalid
+    /// or the database write fails.
+    pub async fn add(
+        &self,
+        tenant_id: &str,
+        product_id: &str,
+        request: &CreateFitmentRequest,
+    ) -> Result<VehicleFitment, FitmentError> {
+        let year_end = request.year_end.unwrap_or(request.year_start);
+        validate_vehicle(&request.make, &request.model, request.year_start, year_end)?;
+
+        let exists: Option<String> = sqlx::query_scalar("SELECT id FROM products WHERE id = ? AND tenant_id = ?")
+            .bind(product_id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if exists.is_none() {
+            return Err(FitmentError::NotFound(format!("Product not found: {product_id}")));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            r"
+            INSERT INTO vehicle_fitment (
+                id, tenant_id, product_id, make, model, year_start, year_end, engine, trim, notes,
+                source, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'manual', ?)
+            ",
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(product_id)
+        .bind(request.make.trim())
+        .bind(request.model.trim())
+        .bind(request.year_start)
+        .bind(year_end)
+        .bind(non_empty(request.engine.as_deref()))
+        .bind(non_empty(request.trim.as_deref()))
+        .bind(non_empty(request.notes.as_deref()))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        self.get(tenant_id, &id).await
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the fitment does not exist or the database write
+    /// fails.
+    pub async fn delete(&self, tenant_id: &str, id: &str) -> Result<(), FitmentError> {
+        let deleted = sqlx::query("DELETE FROM vehicle_fitment WHERE id = ? AND tenant_id = ?")
+            .bind(id)
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        if deleted == 0 {
+            return Err(FitmentError::NotFound(format!("Fitment not found: {id}")));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, tenant_id: &str, id: &str) -> Result<VehicleFitment, FitmentError> {
+        sqlx::query_as::<_, VehicleFitment>(&format!("{SELECT_FITMENT} WHERE id = ? AND tenant_id = ?"))
+            .bind(id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| FitmentError::NotFound(format!("Fitment not found: {id}")))
+    }
+
+    /// Import fitment from an ACES-style XML or CSV file
+    ///
+    /// Rows are imported in one transaction; rows that cannot be read or
+    /// whose SKU is unknown are reported and left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read at all or the database
+    /// write fails.
+    pub async fn import(
+        &self,
+        tenant_id: &str,
+        format: FitmentImportFormat,
+        data: &str,
+    ) -> Result<FitmentImportResult, FitmentError> {
+        let (rows, mut errors) = match format {
+            FitmentImportFormat::Csv => parse_csv(data)?,
+            FitmentImportFormat::Aces => parse_aces(data),
+        };
+
+        let mut imported = 0;
+        let mut duplicates = 0;
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        for row in rows {
+            let product_id: Option<String> =
+                sqlx::query_scalar("SELECT id FROM products WHERE sku = ? AND tenant_id = ?")
+                    .bind(&row.sku)
+                    .bind(tenant_id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            let Some(product_id) = product_id else {
+                errors.push(FitmentImportError { row: row.row, message: format!("Unknown SKU {}", row.sku) });
+                continue;
+            };
+
+            let existing: Option<String> = sqlx::query_scalar(
+                r"
+                SELECT id FROM vehicle_fitment
+                WHERE tenant_id = ? AND product_id = ?
+                  AND make = ? COLLATE NOCASE AND model = ? COLLATE NOCASE
+                  AND year_start = ? AND year_end = ?
+                  AND COALESCE(engine, '') = COALESCE(?, '') COLLATE NOCASE
+                  AND COALESCE(trim, '') = COALESCE(?, '') COLLATE NOCASE
+                ",
+            )
+            .bind(tenant_id)
+            .bind(&product_id)
+            .bind(&row.make)
+            .bind(&row.model)
+            .bind(row.year_start)
+            .bind(row.year_end)
+            .bind(&row.engine)
+            .bind(&row.trim)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if existing.is_some() {
+                duplicates += 1;
+                continue;
+            }
+
+            sqlx::query(
+                r"
+                INSERT INTO vehicle_fitment (
+                    id, tenant_id, product_id, make, model, year_start, year_end, engine, trim, notes,
+                    source, created_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(&product_id)
+            .bind(&row.make)
+            .bind(&row.model)
+            .bind(row.year_start)
+            .bind(row.year_end)
+            .bind(&row.engine)
+            .bind(&row.trim)
+            .bind(&row.notes)
+            .bind(format.as_str())
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            imported += 1;
+        }
+
+        tx.commit().await?;
+        errors.sort_by_key(|error| error.row);
+
+        tracing::info!(
+            tenant_id = %tenant_id,
+            format = format.as_str(),
+            imported,
+            duplicates,
+            errors = errors.len(),
+            "Fitment imported"
+        );
+
+        Ok(FitmentImportResult { format, imported, duplicates, errors })
+    }
+
+    /// Scheduled services due on a vehicle, most important first
+    ///
+    /// The odometer defaults to the latest reading on the vehicle's work
+    /// orders.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the vehicle does not exist or the database read
+    /// fails.
+    pub async fn due_maintenance(
+        &self,
+        tenant_id: &str,
+        vehicle_id: &str,
+        odometer: Option<i64>,
+    ) -> Result<Vec<DueMaintenance>, FitmentError> {
+        let vehicle: Option<(String, String, i64)> =
+            sqlx::query_as("SELECT make, model, year FROM vehicles WHERE id = ? AND tenant_id = ?")
+                .bind(vehicle_id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some((make, model, year)) = vehicle else {
+            return Err(FitmentError::NotFound(format!("Vehicle not found: {vehicle_id}")));
+        };
+
+        let odometer = match odometer {
+            Some(odometer) => Some(odometer),
+            None => sqlx::query_scalar(
+                "SELECT odometer FROM work_orders
+                 WHERE vehicle_id = ? AND tenant_id = ? AND odometer IS NOT NULL
+                 ORDER BY created_at DESC LIMIT 1",
+            )
+            .bind(vehicle_id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?,
+        };
+
+        let schedules = sqlx::query_as::<_, MaintenanceSchedule>(
+            r"
+            SELECT id, make, model, year_start, year_end, service_type, description,
+                   mileage_interval, time_interval_months, priority
+            FROM maintenance_schedules
+            WHERE tenant_id = ?
+              AND make = ? COLLATE NOCASE
+              AND (model IS NULL OR model = ? COLLATE NOCASE)
+              AND (year_start IS NULL OR year_start <= ?)
+              AND (year_end IS NULL OR year_end >= ?)
+            ORDER BY CASE priority WHEN 'high' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END, service_type
+            ",
+        )
+        .bind(tenant_id)
+        .bind(&make)
+        .bind(&model)
+        .bind(year)
+        .bind(year)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let now = Utc::now();
+        let mut due = Vec::new();
+        for schedule in schedules {
+            let last: Option<(String, Option<i64>)> = sqlx::query_as(
+                "SELECT performed_at, odometer FROM vehicle_service_history
+                 WHERE tenant_id = ? AND vehicle_id = ? AND service_type = ? COLLATE NOCASE
+                 ORDER BY performed_at DESC LIMIT 1",
+            )
+            .bind(tenant_id)
+            .bind(vehicle_id)
+            .bind(&schedule.service_type)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            let (last_performed_at, last_odometer) = match last {
+                Some((performed_at, odometer)) => (Some(performed_at), odometer),
+                None => (None, None),
+            };
+            if let Some(reason) = due_reason(&schedule, last_performed_at.as_deref(), last_odometer, odometer, now) {
+                due.push(DueMaintenance {
+                    schedule_id: schedule.id,
+                    service_type: schedule.service_type,
+                    description: schedule.description,
+                    priority: schedule.priority,
+                    reason,
+                    last_performed_at,
+                    last_odometer,
+                    distance_since: odometer.zip(last_odometer).map(|(now, then)| now - then),
+                });
+            }
+        }
+
+        Ok(due)
+    }
+
+    /// Record scheduled services carried out on a work order's vehicle, at
+    /// the work order's odometer reading
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the work order does not exist or has no vehicle,
+    /// no service is given, or the database write fails.
+    pub async fn record_services(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+        service_types: &[String],
+        user_id: &str,
+    ) -> Result<usize, FitmentError> {
+        let service_types: Vec<&str> = service_types
+            .iter()
+            .map(|service| service.trim())
+            .filter(|service| !service.is_empty())
+            .collect();
+        if service_types.is_empty() {
+            return Err(FitmentError::Validation("At least one service is required".to_string()));
+        }
+
+        let work_order: Option<(Option<String>, Option<i64>)> =
+            sqlx::query_as("SELECT vehicle_id, odometer FROM work_orders WHERE id = ? AND tenant_id = ?")
+                .bind(work_order_id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some((vehicle_id, odometer)) = work_order else {
+            return Err(FitmentError::NotFound(format!("Work order not found: {work_order_id}")));
+        };
+        let Some(vehicle_id) = vehicle_id else {
+            return Err(FitmentError::Validation("Work order has no vehicle".to_string()));
+        };
+
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        for service_type in &service_types {
+            sqlx::query(
+                r"
+                INSERT INTO vehicle_service_history (
+                    id, tenant_id, vehicle_id, service_type, work_order_id, odometer, performed_at, performed_by
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                ",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(&vehicle_id)
+            .bind(service_type)
+            .bind(work_order_id)
+            .bind(odometer)
+            .bind(&now)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(service_types.len())
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+fn validate_vehicle(make: &str, model: &str, year_start: i64, year_end: i64) -> Result<(), FitmentError> {
+    if make.trim().is_empty() || model.trim().is_empty() {
+        return Err(FitmentError::Validation("Make and model are required".to_string()));
+    }
+    if !(MIN_YEAR..=MAX_YEAR).contains(&year_start) || !(MIN_YEAR..=MAX_YEAR).contains(&year_end) {
+        return Err(FitmentError::Validation(format!("Years must be between {MIN_YEAR} and {MAX_YEAR}")));
+    }
+    if year_start > year_end {
+        return Err(FitmentError::Validation("The first year cannot be after the last".to_string()));
+    }
+    Ok(())
+}
+
+/// Why a schedule is due, or `None` when it is not
+fn due_reason(
+    schedule: &MaintenanceSchedule,
+    last_performed_at: Option<&str>,
+    last_odometer: Option<i64>,
+    odometer: Option<i64>,
+    now: DateTime<Utc>,
+) -> Option<DueReason> {
+    let Some(last_performed_at) = last_performed_at else {
+        return Some(DueReason::NotRecorded);
+    };
+
+    if let (Some(interval), Some(odometer), Some(last_odometer)) = (schedule.mileage_interval, odometer, last_odometer) {
+        if odometer - last_odometer >= interval {
+            return Some(DueReason::Mileage);
+        }
+    }
+
+    if let (Some(interval), Ok(performed_at)) =
+        (schedule.time_interval_months, DateTime::parse_from_rfc3339(last_performed_at))
+    {
+        let performed_at = performed_at.with_timezone(&Utc);
+        let mut months = i64::from(now.year() - performed_at.year()) * 12
+            + i64::from(now.month()) - i64::from(performed_at.month());
+        if now.day() < performed_at.day() {
+            months -= 1;
+        }
+        if months >= interval {
+            return Some(DueReason::Time);
+        }
+    }
+
+    None
+}
+
+/// Read fitment rows from CSV with a header row
+fn parse_csv(data: &str) -> Result<(Vec<FitmentImportRow>, Vec<FitmentImportError>), FitmentError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| FitmentError::Validation(format!("Failed to read CSV headers: {e}")))?
+        .iter()
+        .map(|header| header.trim_end_matches('*').to_lowercase())
+        .collect();
+    let column = |names: &[&str]| headers.iter().position(|header| names.contains(&header.as_str()));
+    let sku = column(&["sku", "part", "part_number"]);
+    let make = column(&["make"]);
+    let model = column(&["model"]);
+    let year_start = column(&["year_start", "year_from", "year"]);
+    let year_end = column(&["year_end", "year_to"]);
+    let engine = column(&["engine"]);
+    let trim = column(&["trim", "submodel"]);
+    let notes = column(&["notes", "note"]);
+
+    if sku.is_none() || make.is_none() || model.is_none() || year_start.is_none() {
+        return Err(FitmentError::Validation(
+            "CSV needs sku, make, model and year_start (or year) columns".to_string(),
+        ));
+    }
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(FitmentImportError { row, message: format!("Failed to read row: {e}") });
+                continue;
+            }
+        };
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| record.get(column))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        match build_row(
+            row,
+            field(sku),
+            field(make),
+            field(model),
+            field(year_start),
+            field(year_end),
+        ) {
+            Ok(mut fitment) => {
+                fitment.engine = field(engine);
+                fitment.trim = field(trim);
+                fitment.notes = field(notes);
+                rows.push(fitment);
+            }
+            Err(message) => errors.push(FitmentImportError { row, message }),
+        }
+    }
+
+    Ok((rows, errors))
+}
+
+/// Read fitment rows from the `<App>` elements of an ACES-style document
+fn parse_aces(data: &str) -> (Vec<FitmentImportRow>, Vec<FitmentImportError>) {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut rest = data;
+    let mut row = 0;
+    while let Some(start) = find_element(rest, "App") {
+        row += 1;
+        let app = &rest[start..];
+        let Some(end) = app.find("</App>") else {
+            errors.push(FitmentImportError { row, message: "App element is not closed".to_string() });
+            break;
+        };
+        let app_xml = &app[..end];
+        rest = &app[end + "</App>".len()..];
+
+        let years = element_attributes(app_xml, "Years");
+        let year_start = years
+            .as_deref()
+            .and_then(|attributes| attribute(attributes, "from"))
+            .or_else(|| element_text(app_xml, "Year"));
+        let year_end = years.as_deref().and_then(|attributes| attribute(attributes, "to"));
+
+        match build_row(
+            row,
+            element_text(app_xml, "Part"),
+            element_text(app_xml, "Make"),
+            element_text(app_xml, "Model"),
+            year_start,
+            year_end,
+        ) {
+            Ok(mut fitment) => {
+                fitment.engine = element_text(app_xml, "EngineBase").or_else(|| element_text(app_xml, "Engine"));
+                fitment.trim = element_text(app_xml, "SubModel");
+                let notes = all_element_text(app_xml, "Note");
+                fitment.notes = (!notes.is_empty()).then(|| notes.join("; "));
+                rows.push(fitment);
+            }
+            Err(message) => errors.push(FitmentImportError { row, message }),
+        }
+    }
+
+    (rows, errors)
+}
+
+fn build_row(
+    row: usize,
+    sku: Option<String>,
+    make: Option<String>,
+    model: Option<String>,
+    year_start: Option<String>,
+    year_end: Option<String>,
+) -> Result<FitmentImportRow, String> {
+    let sku = sku.ok_or("Missing SKU")?;
+    let make = make.ok_or("Missing make")?;
+    let model = model.ok_or("Missing model")?;
+    let year_start: i64 = year_start
+        .ok_or("Missing year")?
+        .parse()
+        .map_err(|_| "Year is not a number".to_string())?;
+    let year_end: i64 = match year_end {
+        Some(year_end) => year_end.parse().map_err(|_| "Last year is not a number".to_string())?,
+        None => year_start,
+    };
+    validate_vehicle(&make, &model, year_start, year_end).map_err(|e| match e {
+        FitmentError::Validation(message) => message,
+        other => other.to_string(),
+    })?;
+
+    Ok(FitmentImportRow { row, sku, make, model, year_start, year_end, ..FitmentImportRow::default() })
+}
+
+/// Offset of the next `<name` start tag (not one that merely starts with it)
+fn find_element(xml: &str, name: &str) -> Option<usize> {
+    let open = format!("<{name}");
+    let mut offset = 0;
+    while let Some(found) = xml[offset..].find(&open) {
+        let at = offset + found;
+        match xml[at + open.len()..].chars().next() {
+            Some(c) if c == '>' || c == '/' || c.is_whitespace() => return Some(at),
+            _ => offset = at + open.len(),
+        }
+    }
+    None
+}
+
+/// Attributes of the first `<name ...>` tag
+fn element_attributes(xml: &str, name: &str) -> Option<String> {
+    let start = find_element(xml, name)? + name.len() + 1;
+    let end = xml[start..].find('>')? + start;
+    Some(xml[start..end].trim_end_matches('/').to_string())
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let key = format!("{name}=");
+    let at = attributes
+        .match_indices(&key)
+        .find(|(at, _)| *at == 0 || attributes[..*at].ends_with(char::is_whitespace))?
+        .0
+        + key.len();
+    let quote = attributes[at..].chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &attributes[at + 1..];
+    let end = value.find(quote)?;
+    non_empty(Some(&value[..end])).map(unescape)
+}
+
+fn element_text(xml: &str, name: &str) -> Option<String> {
+    all_element_text(xml, name).into_iter().next()
+}
+
+/// Text of every `<name>...</name>` element
+fn all_element_text(xml: &str, name: &str) -> Vec<String> {
+    let close = format!("</{name}>");
+    let mut texts = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = find_element(rest, name) {
+        let element = &rest[start..];
+        let Some(open_end) = element.find('>') else { break };
+        if element[..open_end].ends_with('/') {
+            rest = &element[open_end + 1..];
+            continue;
+        }
+        let body = &element[open_end + 1..];
+        let Some(end) = body.find(&close) else { break };
+        if let Some(text) = non_empty(Some(&body[..end])) {
+            texts.push(unescape(text));
+        }
+        rest = &body[end + close.len()..];
+    }
+    texts
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                sku TEXT NOT NULL,
+                name TEXT NOT NULL,
+                category TEXT NOT NULL,
+                unit_price REAL NOT NULL,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                is_active INTEGER NOT NULL DEFAULT 1
+            )",
+            "CREATE TABLE vehicle_fitment (
+                id TEXT PRIMARY KEY,
+                product_id TEXT NOT NULL,
+                make TEXT NOT NULL,
+                model TEXT NOT NULL,
+                year_start INTEGER NOT NULL,
+                year_end INTEGER NOT NULL,
+                engine TEXT,
+                trim TEXT,
+                notes TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                tenant_id VARCHAR(255) NOT NULL DEFAULT 'default'
+            )",
+            "CREATE TABLE maintenance_schedules (
+                id TEXT PRIMARY KEY,
+                make TEXT NOT NULL,
+                model TEXT,
+                year_start INTEGER,
+                year_end INTEGER,
+                service_type TEXT NOT NULL,
+                description TEXT NOT NULL,
+                mileage_interval INTEGER,
+                time_interval_months INTEGER,
+                priority TEXT NOT NULL DEFAULT 'medium',
+                tenant_id VARCHAR(255) NOT NULL DEFAULT 'default'
+            )",
+            "CREATE TABLE vehicles (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                customer_id TEXT NOT NULL,
+                make TEXT NOT NULL,
+                model TEXT NOT NULL,
+                year INTEGER NOT NULL
+            )",
+            "CREATE TABLE work_orders (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                vehicle_id TEXT,
+                created_at TEXT NOT NULL
+            )",
+            include_str!("../../../../migrations/072_vehicle_fitment_lookup.sql"),
+            "INSERT INTO products (id, tenant_id, sku, name, category, unit_price) VALUES
+                ('p1', 't1', 'OIL-5W30', 'Motor oil 5W-30', 'Fluids', 29.99),
+                ('p2', 't1', 'PAD-F', 'Front brake pads', 'Brakes', 49.99),
+                ('p3', 't1', 'FLT-15T', 'Oil filter 1.5T', 'Filters', 8.99),
+                ('p4', 't2', 'PAD-F', 'Front brake pads', 'Brakes', 45.00)",
+            "INSERT INTO maintenance_schedules (
+                id, tenant_id, make, model, year_start, year_end, service_type, description,
+                mileage_interval, time_interval_months, priority
+            ) VALUES
+                ('m1', 't1', 'Honda', 'Civic', 2016, 2023, 'Oil Change', 'Oil and filter', 8000, 6, 'high'),
+                ('m2', 't1', 'Honda', NULL, NULL, NULL, 'Tire Rotation', 'Rotate tires', 10000, NULL, 'medium'),
+                ('m3', 't1', 'Honda', 'Accord', NULL, NULL, 'Timing Belt', 'Replace belt', 100000, NULL, 'high')",
+            "INSERT INTO vehicles (id, tenant_id, customer_id, make, model, year) VALUES
+                ('v1', 't1', 'c1', 'Honda', 'Civic', 2019)",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    fn civic(engine: Option<&str>) -> VehicleQuery {
+        VehicleQuery {
+            year: 2019,
+            make: "honda".to_string(),
+            model: "CIVIC".to_string(),
+            engine: engine.map(str::to_string),
+            trim: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_and_lookup_by_vehicle() {
+        let pool = setup_test_db().await;
+        let service = FitmentService::new(pool.clone());
+
+        let aces = r#"<?xml version="1.0"?>
+            <ACES version="4.2">
+              <App action="A" id="1">
+                <Make>Honda</Make><Model>Civic</Model>
+                <Years from="2016" to="2023"/>
+                <Part>PAD-F</Part>
+                <Note>Front axle</Note><Note>Ceramic &amp; quiet</Note>
+              </App>
+              <App action="A" id="2">
+                <Make>Honda</Make><Model>Civic</Model><Years from="2016" to="2023"/>
+                <EngineBase>1.5L Turbo</EngineBase>
+                <Part>FLT-15T</Part>
+              </App>
+              <App action="A" id="3">
+                <Make>Honda</Make><Model>Civic</Model><Years from="2016" to="2023"/>
+                <Part>NOPE</Part>
+              </App>
+              <Approws/>
+            </ACES>"#;
+        let result = service.import("t1", FitmentImportFormat::Aces, aces).await.unwrap();
+        assert_eq!((result.imported, result.duplicates), (2, 0));
+        assert_eq!(result.errors, vec![FitmentImportError { row: 3, message: "Unknown SKU NOPE".to_string() }]);
+
+        let csv = "sku,make,model,year_start,year_end,engine\n\
+                   OIL-5W30,Honda,Civic,2016,2023,\n\
+                   PAD-F,Honda,Civic,2016,2023,\n\
+                   OIL-5W30,Honda,Civic,2024,2020,\n";
+        let result = service.import("t1", FitmentImportFormat::Csv, csv).await.unwrap();
+        assert_eq!((result.imported, result.duplicates), (1, 1));
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].row, 4);
+
+        // No engine given: everything for the vehicle
+        let skus = |products: Vec<CompatibleProduct>| products.into_iter().map(|p| p.sku).collect::<Vec<_>>();
+        assert_eq!(skus(service.lookup("t1", &civic(None)).await.unwrap()), vec!["PAD-F", "FLT-15T", "OIL-5W30"]);
+        // Another engine: only the parts that fit all engines
+        assert_eq!(skus(service.lookup("t1", &civic(Some("2.0L"))).await.unwrap()), vec!["PAD-F", "OIL-5W30"]);
+        assert!(service.lookup("t2", &civic(None)).await.unwrap().is_empty());
+
+        let fitment = service.for_product("t1", "p2").await.unwrap();
+        assert_eq!(fitment.len(), 1);
+        assert_eq!(fitment[0].notes.as_deref(), Some("Front axle; Ceramic & quiet"));
+        assert_eq!(fitment[0].source, "aces");
+
+        let options = |make: Option<&str>, model: Option<&str>| VehicleOptionsQuery {
+            year: Some(2019),
+            make: make.map(str::to_string),
+            model: model.map(str::to_string),
+        };
+        assert_eq!(service.vehicle_options("t1", &options(None, None)).await.unwrap(), vec!["Honda"]);
+        assert_eq!(service.vehicle_options("t1", &options(Some("Honda"), None)).await.unwrap(), vec!["Civic"]);
+        assert_eq!(
+            service.vehicle_options("t1", &options(Some("Honda"), Some("Civic"))).await.unwrap(),
+            vec!["1.5L Turbo"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_due_maintenance_from_service_history() {
+        let pool = setup_test_db().await;
+        let service = FitmentService::new(pool.clone());
+
+        // Nothing recorded: every schedule for a 2019 Civic is due
+        let due = service.due_maintenance("t1", "v1", Some(40_000)).await.unwrap();
+        assert_eq!(
+            due.iter().map(|d| (d.service_type.as_str(), d.reason)).collect::<Vec<_>>(),
+            vec![("Oil Change", DueReason::NotRecorded), ("Tire Rotation", DueReason::NotRecorded)]
+        );
+
+        sqlx::query(
+            "INSERT INTO work_orders (id, tenant_id, vehicle_id, created_at, odometer)
+             VALUES ('wo1', 't1', 'v1', '2026-01-01T00:00:00+00:00', 35000)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let recorded = service
+            .record_services("t1", "wo1", &["Oil Change".to_string(), "Tire Rotation".to_string()], "u1")
+            .await
+            .unwrap();
+        assert_eq!(recorded, 2);
+
+        // 5,000 on: neither interval has passed
+        assert!(service.due_maintenance("t1", "v1", Some(40_000)).await.unwrap().is_empty());
+
+        // 9,000 on: the oil change is due by mileage
+        let due = service.due_maintenance("t1", "v1", Some(44_000)).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].reason, due[0].distance_since), (DueReason::Mileage, Some(9_000)));
+
+        assert!(matches!(
+            service.due_maintenance("t1", "nope", None).await,
+            Err(FitmentError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_due_reason_by_time() {
+        let schedule = MaintenanceSchedule {
+            id: "m1".to_string(),
+            make: "Honda".to_string(),
+            model: None,
+            year_start: None,
+            year_end: None,
+            service_type: "Oil Change".to_string(),
+            description: "Oil and filter".to_string(),
+            mileage_interval: Some(8000),
+            time_interval_months: Some(6),
+            priority: "high".to_string(),
+        };
+        let now = DateTime::parse_from_rfc3339("2026-07-15T00:00:00+00:00").unwrap().with_timezone(&Utc);
+
+        assert_eq!(due_reason(&schedule, Some("2026-01-16T00:00:00+00:00"), None, None, now), None);
+        assert_eq!(
+            due_reason(&schedule, Some("2026-01-15T00:00:00+00:00"), None, None, now),
+            Some(DueReason::Time)
+        );
+    }
+}
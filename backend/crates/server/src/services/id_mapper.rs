@@ -20,6 +20,7 @@ impl IdMapper {
     }
 
     /// Store a mapping between source and target systems
+    #[allow(clippy::too_many_arguments)]
     pub async fn store_mapping(
         &self,
         tenant_id: &str,
@@ -113,11 +114,34 @@ impl IdMapper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
 
-    #[test]
-    fn test_id_mapper_creation() {
-        // Test will require actual database connection
-        // Placeholder for now
-        assert!(true);
+    #[tokio::test]
+    async fn test_store_replaces_and_delete_removes_mapping() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE products (id TEXT PRIMARY KEY)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(include_str!("../../../../migrations/078_square_import.sql"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mapper = IdMapper::new(pool);
+
+        mapper.store_mapping("t1", "square", "customer", "C1", "easysale", "customer", "c-1").await.unwrap();
+        mapper.store_mapping("t1", "square", "customer", "C1", "easysale", "customer", "c-2").await.unwrap();
+        assert_eq!(
+            mapper.get_mapping("t1", "square", "customer", "C1", "easysale").await.unwrap().as_deref(),
+            Some("c-2")
+        );
+        assert!(mapper.get_mapping("t2", "square", "customer", "C1", "easysale").await.unwrap().is_none());
+
+        mapper.delete_mapping("t1", "square", "customer", "C1", "easysale").await.unwrap();
+        assert!(mapper.get_mapping("t1", "square", "customer", "C1", "easysale").await.unwrap().is_none());
     }
 }
@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 use chrono::Utc;
 use uuid::Uuid;
 
+use crate::services::inventory_ledger_service::{
+    self, MovementType, StockMovement, REASON_VENDOR_RECEIPT, SOURCE_REVIEW_CASE,
+};
+
 #[derive(Debug, Clone)]
 pub struct InventoryIntegrationService {
     pool: SqlitePool,
@@ -54,7 +58,7 @@ impl InventoryIntegrationService {
     /// Process approved invoice and create/update inventory items
     pub async fn process_invoice(
         &self,
-        _case_id: &str,
+        case_id: &str,
         line_items: Vec<LineItemData>,
         tenant_id: &str,
     ) -> Result<IntegrationResult, IntegrationError> {
@@ -87,14 +91,14 @@ impl InventoryIntegrationService {
 
             if exists {
                 // Update existing product
-                if let Err(e) = self.update_inventory(&internal_sku, &item, tenant_id, &mut tx).await {
+                if let Err(e) = self.update_inventory(case_id, &internal_sku, &item, tenant_id, &mut tx).await {
                     errors.push(format!("SKU {}: {}", internal_sku, e));
                     continue;
                 }
                 items_updated += 1;
             } else {
                 // Create new product
-                if let Err(e) = self.create_inventory(&internal_sku, &item, tenant_id, &mut tx).await {
+                if let Err(e) = self.create_inventory(case_id, &internal_sku, &item, tenant_id, &mut tx).await {
                     errors.push(format!("SKU {}: {}", internal_sku, e));
                     continue;
                 }
@@ -180,6 +184,7 @@ impl InventoryIntegrationService {
     /// Create new inventory item
     async fn create_inventory(
         &self,
+        case_id: &str,
         sku: &str,
         item: &LineItemData,
         tenant_id: &str,
@@ -210,12 +215,27 @@ impl InventoryIntegrationService {
         .await
         .map_err(|e| format!("Failed to create product: {}", e))?;
 
+        // The invoice quantity is the product's opening receipt in the ledger
+        let receipt = StockMovement::new(
+            tenant_id,
+            &product_id,
+            MovementType::Receipt,
+            0.0,
+            REASON_VENDOR_RECEIPT,
+        )
+        .with_source(SOURCE_REVIEW_CASE, case_id)
+        .at_store("default-store");
+        inventory_ledger_service::set_on_hand(&mut **tx, receipt, item.quantity)
+            .await
+            .map_err(|e| format!("Failed to record stock movement: {}", e))?;
+
         Ok(())
     }
 
     /// Update existing inventory item
     async fn update_inventory(
         &self,
+        case_id: &str,
         sku: &str,
         item: &LineItemData,
         tenant_id: &str,
@@ -223,25 +243,36 @@ impl InventoryIntegrationService {
     ) -> Result<(), String> {
         let now = Utc::now().to_rfc3339();
 
-        // Update cost and add to quantity
-        sqlx::query(
+        // Update cost; the quantity is received through the ledger
+        let product_id: String = sqlx::query_scalar(
             r#"
             UPDATE products
             SET cost = ?,
-                quantity_on_hand = quantity_on_hand + ?,
                 updated_at = ?
             WHERE sku = ? AND tenant_id = ?
+            RETURNING id
             "#
         )
         .bind(item.unit_price)
-        .bind(item.quantity)
         .bind(&now)
         .bind(sku)
         .bind(tenant_id)
-        .execute(&mut **tx)
+        .fetch_one(&mut **tx)
         .await
         .map_err(|e| format!("Failed to update product: {}", e))?;
 
+        let receipt = StockMovement::new(
+            tenant_id,
+            product_id,
+            MovementType::Receipt,
+            item.quantity,
+            REASON_VENDOR_RECEIPT,
+        )
+        .with_source(SOURCE_REVIEW_CASE, case_id);
+        inventory_ledger_service::record_movement(&mut **tx, &receipt)
+            .await
+            .map_err(|e| format!("Failed to record stock movement: {}", e))?;
+
         Ok(())
     }
 
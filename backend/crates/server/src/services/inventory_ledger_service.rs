@@ -0,0 +1,1108 @@
+/**
+ * Inventory Ledger Service
+ *
+ * Append-only ledger of stock movements. Every change to a product's stock is
+ * written as a signed movement (sale, return, receipt, adjustment, transfer
+ * or count) with a reason code, the user who made it and the document it
+ * came from, on a caller-supplied connection so the movement commits or rolls
+ * back together with the sale, return or receipt that caused it.
+ *
+ * Every movement happens at a store: the one given, or else the product's
+ * own store. `product_locations.quantity_on_hand` caches the ledger per store
+ * and `products.quantity_on_hand` caches it over all stores; both are only
+ * written together with a movement, and `rebuild_on_hand` recomputes them
+ * from the ledger. Stock at any point in time is the sum of the movements up
+ * to it.
+ *
+ * Movements are never updated or deleted (the table has triggers refusing
+ * both); a wrong movement is corrected with a new adjustment.
+ *
+ * Every movement but a transfer is also costed (see `costing_service`):
+ * units coming in open a cost layer, units going out are charged by the
+ * tenant's costing method.
+ *
+ * A movement that takes a store's stock down to its reorder point publishes
+ * a `stock.low` webhook event on the same connection.
+ *
+ * For a tenant connected to WooCommerce, every movement but a transfer of a
+ * product with a SKU also queues a stock push in `sync_queue`; a trigger on
+ * `inventory_movements` (migration 080) does it, so it commits with the
+ * movement too.
+ */
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::costing_service;
+use crate::services::outbound_webhook_service::{self, WebhookEventType};
+
+/// Quantities closer than this are the same stock level
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+/// Carried-over stock of a product that predates the ledger
+pub const REASON_OPENING_BALANCE: &str = "opening_balance";
+/// Sold at the register
+pub const REASON_SALE: &str = "sale";
+/// Sale voided; goods back on the shelf
+pub const REASON_SALE_VOID: &str = "sale_void";
+/// Returned goods put back on the shelf
+pub const REASON_RETURN_RESTOCK: &str = "return_restock";
+/// Received against a vendor bill
+pub const REASON_VENDOR_RECEIPT: &str = "vendor_receipt";
+/// Quantity typed over on the product form
+pub const REASON_PRODUCT_EDIT: &str = "product_edit";
+/// Quantity set by a bulk import
+pub const REASON_IMPORT: &str = "import";
+/// Quantity set by a sync from another device or system
+pub const REASON_SYNC: &str = "sync";
+/// Shipped out on a transfer order
+pub const REASON_TRANSFER_OUT: &str = "transfer_out";
+/// Received in from a transfer order
+pub const REASON_TRANSFER_IN: &str = "transfer_in";
+/// Received against a purchase order
+pub const REASON_PO_RECEIPT: &str = "po_receipt";
+/// Variance posted from an approved stock count
+pub const REASON_CYCLE_COUNT: &str = "cycle_count";
+
+pub const SOURCE_SALE: &str = "sale";
+pub const SOURCE_RETURN: &str = "return";
+pub const SOURCE_VENDOR_BILL: &str = "vendor_bill";
+pub const SOURCE_REVIEW_CASE: &str = "review_case";
+pub const SOURCE_STOCK_ADJUSTMENT: &str = "stock_adjustment";
+pub const SOURCE_TRANSFER_ORDER: &str = "transfer_order";
+pub const SOURCE_PURCHASE_ORDER: &str = "purchase_order";
+pub const SOURCE_COUNT_SESSION: &str = "count_session";
+pub const SOURCE_PRODUCT: &str = "product";
+pub const SOURCE_DATA_BATCH: &str = "data_batch";
+pub const SOURCE_SYNC_QUEUE: &str = "sync_queue";
+pub const SOURCE_SQUARE_IMPORT: &str = "square_import";
+pub const SOURCE_CLOVER_SYNC: &str = "clover_sync";
+pub const SOURCE_SHOPIFY_SYNC: &str = "shopify_sync";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum LedgerError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Product not found: {0}")]
+    ProductNotFound(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<LedgerError> for ApiError {
+    fn from(err: LedgerError) -> Self {
+        match err {
+            LedgerError::Validation(msg) => Self::bad_request(msg),
+            LedgerError::ProductNotFound(id) => Self::not_found(format!("Product not found: {id}")),
+            LedgerError::Database(e) => Self::internal(format!("Failed to access inventory ledger: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Why stock moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementType {
+    Sale,
+    Return,
+    Receipt,
+    Adjustment,
+    Transfer,
+    Count,
+}
+
+impl MovementType {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Sale => "sale",
+            Self::Return => "return",
+            Self::Receipt => "receipt",
+            Self::Adjustment => "adjustment",
+            Self::Transfer => "transfer",
+            Self::Count => "count",
+        }
+    }
+}
+
+/// A stock movement to record
+#[derive(Debug, Clone)]
+pub struct StockMovement {
+    pub tenant_id: String,
+    pub product_id: String,
+    pub store_id: Option<String>,
+    pub movement_type: MovementType,
+    /// Signed change: negative when stock leaves
+    pub quantity: f64,
+    pub reason_code: String,
+    /// Kind of document the movement came from (`SOURCE_*`)
+    pub source_type: Option<String>,
+    pub source_id: Option<String>,
+    pub user_id: Option<String>,
+    pub notes: Option<String>,
+    /// What incoming units cost; `None` takes the product's current cost
+    pub unit_cost: Option<f64>,
+}
+
+impl StockMovement {
+    #[must_use]
+    pub fn new(
+        tenant_id: impl Into<String>,
+        product_id: impl Into<String>,
+        movement_type: MovementType,
+        quantity: f64,
+        reason_code: impl Into<String>,
+    ) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            product_id: product_id.into(),
+            store_id: None,
+            movement_type,
+            quantity,
+            reason_code: reason_code.into(),
+            source_type: None,
+            source_id: None,
+            user_id: None,
+            notes: None,
+            unit_cost: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_source(mut self, source_type: &str, source_id: impl Into<String>) -> Self {
+        self.source_type = Some(source_type.to_string());
+        self.source_id = Some(source_id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn by_user(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn at_store(mut self, store_id: impl Into<String>) -> Self {
+        self.store_id = Some(store_id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_notes(mut self, notes: Option<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    #[must_use]
+    pub fn with_unit_cost(mut self, unit_cost: Option<f64>) -> Self {
+        self.unit_cost = unit_cost;
+        self
+    }
+}
+
+/// A recorded stock movement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InventoryMovement {
+    pub id: String,
+    pub product_id: String,
+    pub store_id: Option<String>,
+    pub movement_type: String,
+    pub quantity: f64,
+    /// On-hand quantity right after the movement
+    pub quantity_after: f64,
+    pub reason_code: String,
+    pub source_type: Option<String>,
+    pub source_id: Option<String>,
+    pub user_id: Option<String>,
+    pub notes: Option<String>,
+    /// Unit cost the movement was valued at; `None` for transfers
+    pub unit_cost: Option<f64>,
+    pub created_at: String,
+}
+
+/// Filter for the movement history
+#[derive(Debug, Clone, Default)]
+pub struct MovementFilter {
+    pub product_id: Option<String>,
+    pub store_id: Option<String>,
+    pub movement_type: Option<MovementType>,
+    pub source_id: Option<String>,
+    /// Inclusive RFC 3339 bounds on `created_at`
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: i64,
+}
+
+/// A cached on-hand quantity that disagreed with the ledger
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OnHandCorrection {
+    pub product_id: String,
+    /// Store of a `product_locations` row; `None` for the product total
+    pub store_id: Option<String>,
+    pub cached: f64,
+    pub ledger: f64,
+}
+
+/// A product's stock at one store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LocationStock {
+    pub product_id: String,
+    pub store_id: String,
+    pub store_name: Option<String>,
+    pub quantity_on_hand: f64,
+    pub bin_location: Option<String>,
+    pub reorder_point: Option<f64>,
+    /// Shipped to this store on transfers not received yet
+    pub in_transit: f64,
+}
+
+// ============================================================================
+// Recording movements
+// ============================================================================
+
+/// Record a movement and apply it to the cached on-hand quantity
+///
+/// # Errors
+///
+/// Returns an error if the quantity is zero, the product does not exist for
+/// the tenant, or the database write fails.
+pub async fn record_movement(
+    conn: &mut SqliteConnection,
+    movement: &StockMovement,
+) -> Result<InventoryMovement, LedgerError> {
+    if movement.quantity.abs() < QUANTITY_EPSILON {
+        return Err(LedgerError::Validation("Stock movement quantity cannot be zero".to_string()));
+    }
+
+    let (quantity_after, home_store): (f64, Option<String>) = sqlx::query_as(
+        "UPDATE products SET quantity_on_hand = quantity_on_hand + ?
+         WHERE id = ? AND tenant_id = ?
+         RETURNING quantity_on_hand, store_id",
+    )
+    .bind(movement.quantity)
+    .bind(&movement.product_id)
+    .bind(&movement.tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| LedgerError::ProductNotFound(movement.product_id.clone()))?;
+
+    let store_id = movement.store_id.clone().or(home_store);
+    let now = Utc::now().to_rfc3339();
+
+    if let Some(store_id) = &store_id {
+        sqlx::query(
+            r"
+            INSERT INTO product_locations (
+                id, tenant_id, product_id, store_id, quantity_on_hand, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (product_id, store_id) DO UPDATE SET
+                quantity_on_hand = quantity_on_hand + excluded.quantity_on_hand,
+                updated_at = excluded.updated_at
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&movement.tenant_id)
+        .bind(&movement.product_id)
+        .bind(store_id)
+        .bind(movement.quantity)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let mut recorded = InventoryMovement {
+        id: Uuid::new_v4().to_string(),
+        product_id: movement.product_id.clone(),
+        store_id,
+        movement_type: movement.movement_type.as_str().to_string(),
+        quantity: movement.quantity,
+        quantity_after,
+        reason_code: movement.reason_code.clone(),
+        source_type: movement.source_type.clone(),
+        source_id: movement.source_id.clone(),
+        user_id: movement.user_id.clone(),
+        notes: movement.notes.clone(),
+        unit_cost: None,
+        created_at: now,
+    };
+
+    sqlx::query(
+        r"
+        INSERT INTO inventory_movements (
+            id, tenant_id, product_id, store_id, movement_type, quantity, quantity_after,
+            reason_code, source_type, source_id, user_id, notes, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&recorded.id)
+    .bind(&movement.tenant_id)
+    .bind(&recorded.product_id)
+    .bind(&recorded.store_id)
+    .bind(&recorded.movement_type)
+    .bind(recorded.quantity)
+    .bind(recorded.quantity_after)
+    .bind(&recorded.reason_code)
+    .bind(&recorded.source_type)
+    .bind(&recorded.source_id)
+    .bind(&recorded.user_id)
+    .bind(&recorded.notes)
+    .bind(&recorded.created_at)
+    .execute(&mut *conn)
+    .await?;
+
+    if movement.movement_type != MovementType::Transfer {
+        recorded.unit_cost = Some(
+            costing_service::cost_movement(
+                conn,
+                &movement.tenant_id,
+                &recorded.product_id,
+                &recorded.id,
+                recorded.quantity,
+                movement.unit_cost,
+                &recorded.created_at,
+            )
+            .await?,
+        );
+    }
+
+    if recorded.quantity < 0.0 {
+        if let Some(store_id) = &recorded.store_id {
+            publish_if_low(conn, &movement.tenant_id, &recorded.product_id, store_id, recorded.quantity).await?;
+        }
+    }
+
+    Ok(recorded)
+}
+
+/// Publish `stock.low` when a movement takes a product's stock at a store
+/// from above its reorder point to at or below it
+///
+/// The store's own reorder point is used, else the product's. Stock that
+/// stays low does not publish again until it has been restocked past the
+/// reorder point.
+async fn publish_if_low(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    product_id: &str,
+    store_id: &str,
+    quantity: f64,
+) -> Result<(), LedgerError> {
+    let level: Option<(f64, Option<f64>)> = sqlx::query_as(
+        "SELECT l.quantity_on_hand, COALESCE(l.reorder_point, p.reorder_point)
+         FROM product_locations l
+         JOIN products p ON p.id = l.product_id
+         WHERE l.product_id = ? AND l.store_id = ? AND p.tenant_id = ?",
+    )
+    .bind(product_id)
+    .bind(store_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some((on_hand, Some(reorder_point))) = level else {
+        return Ok(());
+    };
+    if on_hand > reorder_point || on_hand - quantity <= reorder_point {
+        return Ok(());
+    }
+
+    outbound_webhook_service::publish(
+        conn,
+        tenant_id,
+        WebhookEventType::StockLow,
+        json!({
+            "product_id": product_id,
+            "store_id": store_id,
+            "quantity_on_hand": on_hand,
+            "reorder_point": reorder_point,
+        }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Bring a product's stock at a store to `level` with one movement of the
+/// difference
+///
+/// The store is the movement's, or else the product's own. The difference is
+/// taken against the ledger rather than the caches, so this is also how stock
+/// written straight into `products` (a new product, an import) gets its
+/// opening movement. `movement.quantity` is replaced by the difference;
+/// nothing is recorded when there is none.
+///
+/// # Errors
+///
+/// Returns an error if the product does not exist for the tenant or the
+/// database write fails.
+pub async fn set_on_hand(
+    conn: &mut SqliteConnection,
+    mut movement: StockMovement,
+    level: f64,
+) -> Result<Option<InventoryMovement>, LedgerError> {
+    let home_store: Option<Option<String>> =
+        sqlx::query_scalar("SELECT store_id FROM products WHERE id = ? AND tenant_id = ?")
+            .bind(&movement.product_id)
+            .bind(&movement.tenant_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let Some(home_store) = home_store else {
+        return Err(LedgerError::ProductNotFound(movement.product_id));
+    };
+    if movement.store_id.is_none() {
+        movement.store_id = home_store;
+    }
+
+    // Re-align both caches with the ledger before moving the difference
+    let total = ledger_on_hand(conn, &movement.tenant_id, &movement.product_id, None, None).await?;
+    sqlx::query("UPDATE products SET quantity_on_hand = ? WHERE id = ? AND tenant_id = ?")
+        .bind(total)
+        .bind(&movement.product_id)
+        .bind(&movement.tenant_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let current = match movement.store_id.as_deref() {
+        Some(store_id) => {
+            let at_store =
+                ledger_on_hand(conn, &movement.tenant_id, &movement.product_id, Some(store_id), None).await?;
+            set_location_quantity(conn, &movement.tenant_id, &movement.product_id, store_id, at_store).await?;
+            at_store
+        }
+        None => total,
+    };
+
+    movement.quantity = level - current;
+    if movement.quantity.abs() < QUANTITY_EPSILON {
+        return Ok(None);
+    }
+
+    record_movement(conn, &movement).await.map(Some)
+}
+
+/// Overwrite the cached stock of a product at a store
+async fn set_location_quantity(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    product_id: &str,
+    store_id: &str,
+    quantity: f64,
+) -> Result<(), LedgerError> {
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r"
+        INSERT INTO product_locations (
+            id, tenant_id, product_id, store_id, quantity_on_hand, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (product_id, store_id) DO UPDATE SET
+            quantity_on_hand = excluded.quantity_on_hand,
+            updated_at = excluded.updated_at
+        ",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(tenant_id)
+    .bind(product_id)
+    .bind(store_id)
+    .bind(quantity)
+    .bind(&now)
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Sum of a product's movements, at one store and up to and including
+/// `as_of` when given
+///
+/// Movements recorded without a store count towards the product's own store.
+async fn ledger_on_hand(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    product_id: &str,
+    store_id: Option<&str>,
+    as_of: Option<&str>,
+) -> Result<f64, LedgerError> {
+    let quantity: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(m.quantity), 0.0)
+         FROM inventory_movements m
+         LEFT JOIN products p ON p.id = m.product_id
+         WHERE m.tenant_id = ? AND m.product_id = ?
+           AND (? IS NULL OR COALESCE(m.store_id, p.store_id) = ?)
+           AND (? IS NULL OR m.created_at <= ?)",
+    )
+    .bind(tenant_id)
+    .bind(product_id)
+    .bind(store_id)
+    .bind(store_id)
+    .bind(as_of)
+    .bind(as_of)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(quantity)
+}
+
+/// Stock per store (`LocationStock`), with what is in transit to it
+pub(crate) const SELECT_LOCATION_STOCK: &str = "SELECT
+        l.product_id,
+        l.store_id,
+        s.name AS store_name,
+        l.quantity_on_hand,
+        l.bin_location,
+        l.reorder_point,
+        COALESCE((
+            SELECT SUM(tl.quantity_shipped)
+            FROM transfer_order_lines tl
+            JOIN transfer_orders t ON t.id = tl.transfer_id
+            WHERE t.status = 'in_transit' AND t.to_store_id = l.store_id
+              AND tl.product_id = l.product_id
+        ), 0.0) AS in_transit
+     FROM product_locations l
+     LEFT JOIN stores s ON s.id = l.store_id";
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct InventoryLedgerService {
+    pool: SqlitePool,
+}
+
+impl InventoryLedgerService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a single movement in its own transaction
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the movement is invalid or the database write fails.
+    pub async fn record(&self, movement: &StockMovement) -> Result<InventoryMovement, LedgerError> {
+        let mut tx = self.pool.begin().await?;
+        let recorded = record_movement(&mut tx, movement).await?;
+        tx.commit().await?;
+        Ok(recorded)
+    }
+
+    /// Movement history, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn movements(
+        &self,
+        tenant_id: &str,
+        filter: &MovementFilter,
+    ) -> Result<Vec<InventoryMovement>, LedgerError> {
+        let movement_type = filter.movement_type.map(MovementType::as_str);
+
+        let movements = sqlx::query_as::<_, InventoryMovement>(
+            r"
+            SELECT m.id, m.product_id, m.store_id, m.movement_type, m.quantity, m.quantity_after,
+                   m.reason_code, m.source_type, m.source_id, m.user_id, m.notes,
+                   c.unit_cost, m.created_at
+            FROM inventory_movements m
+            LEFT JOIN inventory_cost_entries c ON c.movement_id = m.id
+            WHERE m.tenant_id = ?
+              AND (? IS NULL OR m.product_id = ?)
+              AND (? IS NULL OR m.store_id = ?)
+              AND (? IS NULL OR m.movement_type = ?)
+              AND (? IS NULL OR m.source_id = ?)
+              AND (? IS NULL OR m.created_at >= ?)
+              AND (? IS NULL OR m.created_at <= ?)
+            ORDER BY m.created_at DESC, m.rowid DESC
+            LIMIT ?
+            ",
+        )
+        .bind(tenant_id)
+        .bind(&filter.product_id)
+        .bind(&filter.product_id)
+        .bind(&filter.store_id)
+        .bind(&filter.store_id)
+        .bind(movement_type)
+        .bind(movement_type)
+        .bind(&filter.source_id)
+        .bind(&filter.source_id)
+        .bind(&filter.from)
+        .bind(&filter.from)
+        .bind(&filter.to)
+        .bind(&filter.to)
+        .bind(filter.limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(movements)
+    }
+
+    /// A product's stock as of an RFC 3339 timestamp, at one store or over
+    /// all stores
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the product does not exist or the database read
+    /// fails.
+    pub async fn on_hand_as_of(
+        &self,
+        tenant_id: &str,
+        product_id: &str,
+        store_id: Option<&str>,
+        as_of: &str,
+    ) -> Result<f64, LedgerError> {
+        let mut conn = self.pool.acquire().await?;
+
+        let exists: Option<String> = sqlx::query_scalar("SELECT id FROM products WHERE id = ? AND tenant_id = ?")
+            .bind(product_id)
+            .bind(tenant_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        if exists.is_none() {
+            return Err(LedgerError::ProductNotFound(product_id.to_string()));
+        }
+
+        ledger_on_hand(&mut conn, tenant_id, product_id, store_id, Some(as_of)).await
+    }
+
+    /// A product's stock at each store it has been stocked at, with what is
+    /// on its way there on transfer orders
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the product does not exist or the database read
+    /// fails.
+    pub async fn locations(&self, tenant_id: &str, product_id: &str) -> Result<Vec<LocationStock>, LedgerError> {
+        let exists: Option<String> = sqlx::query_scalar("SELECT id FROM products WHERE id = ? AND tenant_id = ?")
+            .bind(product_id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if exists.is_none() {
+            return Err(LedgerError::ProductNotFound(product_id.to_string()));
+        }
+
+        let locations = sqlx::query_as::<_, LocationStock>(&format!(
+            "{SELECT_LOCATION_STOCK} WHERE l.tenant_id = ? AND l.product_id = ? ORDER BY l.store_id"
+        ))
+        .bind(tenant_id)
+        .bind(product_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(locations)
+    }
+
+    /// Set where a product sits in a store and the store's reorder point
+    ///
+    /// Creates the location with no stock if the product has never been
+    /// stocked at the store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the product does not exist or the database write
+    /// fails.
+    pub async fn set_bin_location(
+        &self,
+        tenant_id: &str,
+        product_id: &str,
+        store_id: &str,
+        bin_location: Option<&str>,
+        reorder_point: Option<f64>,
+    ) -> Result<LocationStock, LedgerError> {
+        if reorder_point.is_some_and(|point| point < 0.0) {
+            return Err(LedgerError::Validation("Reorder point cannot be negative".to_string()));
+        }
+
+        let exists: Option<String> = sqlx::query_scalar("SELECT id FROM products WHERE id = ? AND tenant_id = ?")
+            .bind(product_id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if exists.is_none() {
+            return Err(LedgerError::ProductNotFound(product_id.to_string()));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r"
+            INSERT INTO product_locations (
+                id, tenant_id, product_id, store_id, quantity_on_hand, bin_location,
+                reorder_point, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, 0.0, ?, ?, ?, ?)
+            ON CONFLICT (product_id, store_id) DO UPDATE SET
+                bin_location = excluded.bin_location,
+                reorder_point = excluded.reorder_point,
+                updated_at = excluded.updated_at
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(product_id)
+        .bind(store_id)
+        .bind(bin_location)
+        .bind(reorder_point)
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        self.locations(tenant_id, product_id)
+            .await?
+            .into_iter()
+            .find(|location| location.store_id == store_id)
+            .ok_or_else(|| LedgerError::ProductNotFound(product_id.to_string()))
+    }
+
+    /// Recompute every cached on-hand quantity of a tenant from the ledger,
+    /// per product and per store
+    ///
+    /// Returns the caches that were off. The ledger itself is not touched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read or write fails.
+    pub async fn rebuild_on_hand(&self, tenant_id: &str) -> Result<Vec<OnHandCorrection>, LedgerError> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows: Vec<(String, f64, f64)> = sqlx::query_as(
+            r"
+            SELECT p.id, p.quantity_on_hand, COALESCE(SUM(m.quantity), 0.0)
+            FROM products p
+            LEFT JOIN inventory_movements m ON m.product_id = p.id AND m.tenant_id = p.tenant_id
+            WHERE p.tenant_id = ?
+            GROUP BY p.id, p.quantity_on_hand
+            ",
+        )
+        .bind(tenant_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut corrections = Vec::new();
+        for (product_id, cached, ledger) in rows {
+            if (cached - ledger).abs() < QUANTITY_EPSILON {
+                continue;
+            }
+
+            sqlx::query("UPDATE products SET quantity_on_hand = ? WHERE id = ? AND tenant_id = ?")
+                .bind(ledger)
+                .bind(&product_id)
+                .bind(tenant_id)
+                .execute(&mut *tx)
+                .await?;
+
+            corrections.push(OnHandCorrection { product_id, store_id: None, cached, ledger });
+        }
+
+        // Every store the ledger or the cache knows the product at
+        let locations: Vec<(String, String, f64, f64)> = sqlx::query_as(
+            r"
+            SELECT product_id, store_id, SUM(cached), SUM(ledger)
+            FROM (
+                SELECT product_id, store_id, quantity_on_hand AS cached, 0.0 AS ledger
+                FROM product_locations
+                WHERE tenant_id = ?
+                UNION ALL
+                SELECT m.product_id, COALESCE(m.store_id, p.store_id), 0.0, m.quantity
+                FROM inventory_movements m
+                JOIN products p ON p.id = m.product_id
+                WHERE m.tenant_id = ? AND COALESCE(m.store_id, p.store_id) IS NOT NULL
+            )
+            GROUP BY product_id, store_id
+            ",
+        )
+        .bind(tenant_id)
+        .bind(tenant_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for (product_id, store_id, cached, ledger) in locations {
+            if (cached - ledger).abs() < QUANTITY_EPSILON {
+                continue;
+            }
+
+            set_location_quantity(&mut tx, tenant_id, &product_id, &store_id, ledger).await?;
+            corrections.push(OnHandCorrection { product_id, store_id: Some(store_id), cached, ledger });
+        }
+
+        tx.commit().await?;
+
+        if !corrections.is_empty() {
+            tracing::warn!(
+                tenant_id = %tenant_id,
+                corrections = corrections.len(),
+                "Rebuilt cached stock levels from the inventory ledger"
+            );
+        }
+
+        Ok(corrections)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                cost REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                quantity_on_hand REAL NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE settings (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            "INSERT INTO stores (id, name) VALUES ('s1', 'Main Street'), ('s2', 'Warehouse')",
+            "INSERT INTO products (id, tenant_id, store_id, quantity_on_hand) VALUES
+                ('p1', 't1', 's1', 10),
+                ('p2', 't1', 's1', 0)",
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+            include_str!("../../../../migrations/077_outbound_webhooks.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    async fn on_hand(pool: &SqlitePool, product_id: &str) -> f64 {
+        sqlx::query_scalar("SELECT quantity_on_hand FROM products WHERE id = ?")
+            .bind(product_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn all_movements(product_id: &str) -> MovementFilter {
+        MovementFilter {
+            product_id: Some(product_id.to_string()),
+            limit: 100,
+            ..MovementFilter::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migration_carries_over_opening_balances() {
+        let pool = setup_test_db().await;
+        let service = InventoryLedgerService::new(pool.clone());
+
+        let opening = service.movements("t1", &all_movements("p1")).await.unwrap();
+        assert_eq!(opening.len(), 1);
+        assert_eq!(opening[0].reason_code, REASON_OPENING_BALANCE);
+        assert_eq!(opening[0].quantity, 10.0);
+
+        // Nothing to carry over for a product without stock
+        assert!(service.movements("t1", &all_movements("p2")).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_movements_update_cache_and_keep_history() {
+        let pool = setup_test_db().await;
+        let service = InventoryLedgerService::new(pool.clone());
+
+        let sale = StockMovement::new("t1", "p1", MovementType::Sale, -3.0, REASON_SALE)
+            .with_source(SOURCE_SALE, "sale-1")
+            .by_user("u1")
+            .at_store("s1");
+        let recorded = service.record(&sale).await.unwrap();
+        assert_eq!(recorded.quantity_after, 7.0);
+        assert_eq!(on_hand(&pool, "p1").await, 7.0);
+
+        let filter = MovementFilter {
+            source_id: Some("sale-1".to_string()),
+            limit: 10,
+            ..MovementFilter::default()
+        };
+        let history = service.movements("t1", &filter).await.unwrap();
+        assert_eq!(history, vec![recorded]);
+
+        // Unknown products and zero quantities are refused
+        let unknown = StockMovement::new("t1", "nope", MovementType::Receipt, 1.0, REASON_VENDOR_RECEIPT);
+        assert!(matches!(service.record(&unknown).await, Err(LedgerError::ProductNotFound(_))));
+        let other_tenant = StockMovement::new("t2", "p1", MovementType::Receipt, 1.0, REASON_VENDOR_RECEIPT);
+        assert!(matches!(service.record(&other_tenant).await, Err(LedgerError::ProductNotFound(_))));
+        let zero = StockMovement::new("t1", "p1", MovementType::Adjustment, 0.0, "manual");
+        assert!(matches!(service.record(&zero).await, Err(LedgerError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_falling_to_reorder_point_publishes_stock_low_once() {
+        let pool = setup_test_db().await;
+        let service = InventoryLedgerService::new(pool.clone());
+        sqlx::query("UPDATE products SET reorder_point = 5 WHERE id = 'p1'").execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO webhook_subscriptions (id, tenant_id, name, url, events, secret_encrypted, created_at, updated_at)
+             VALUES ('w1', 't1', 'Buyer', 'https://example.com', '[\"stock.low\"]', 'x', '', '')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let sell = |quantity: f64| StockMovement::new("t1", "p1", MovementType::Sale, -quantity, REASON_SALE).at_store("s1");
+        // 10 -> 7, still above
+        service.record(&sell(3.0)).await.unwrap();
+        // 7 -> 4, crosses
+        service.record(&sell(3.0)).await.unwrap();
+        // 4 -> 3, already low
+        service.record(&sell(1.0)).await.unwrap();
+
+        let payloads: Vec<String> = sqlx::query_scalar("SELECT payload FROM outbound_webhook_events WHERE event_type = 'stock.low'")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(payloads.len(), 1);
+        let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
+        assert_eq!(payload["data"]["store_id"], "s1");
+        assert_eq!(payload["data"]["quantity_on_hand"], 4.0);
+        assert_eq!(payload["data"]["reorder_point"], 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_set_on_hand_records_the_difference_against_the_ledger() {
+        let pool = setup_test_db().await;
+
+        // Stock written straight into products, e.g. by an import
+        sqlx::query("INSERT INTO products (id, tenant_id, store_id, quantity_on_hand) VALUES ('p3', 't1', 's1', 25)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let import = StockMovement::new("t1", "p3", MovementType::Adjustment, 0.0, REASON_IMPORT);
+        let opening = set_on_hand(&mut conn, import.clone(), 25.0).await.unwrap().unwrap();
+        assert_eq!(opening.quantity, 25.0);
+        assert_eq!(opening.quantity_after, 25.0);
+
+        assert!(set_on_hand(&mut conn, import.clone(), 25.0).await.unwrap().is_none());
+
+        let lowered = set_on_hand(&mut conn, import, 20.0).await.unwrap().unwrap();
+        assert_eq!(lowered.quantity, -5.0);
+        drop(conn);
+        assert_eq!(on_hand(&pool, "p3").await, 20.0);
+    }
+
+    #[tokio::test]
+    async fn test_on_hand_as_of_and_rebuild() {
+        let pool = setup_test_db().await;
+        let service = InventoryLedgerService::new(pool.clone());
+
+        let receipt = StockMovement::new("t1", "p2", MovementType::Receipt, 5.0, REASON_VENDOR_RECEIPT);
+        let recorded = service.record(&receipt).await.unwrap();
+
+        assert_eq!(service.on_hand_as_of("t1", "p2", None, "2000-01-01T00:00:00Z").await.unwrap(), 0.0);
+        assert_eq!(service.on_hand_as_of("t1", "p2", None, &recorded.created_at).await.unwrap(), 5.0);
+        assert!(matches!(
+            service.on_hand_as_of("t1", "nope", None, &recorded.created_at).await,
+            Err(LedgerError::ProductNotFound(_))
+        ));
+
+        // A cache written behind the ledger's back is put right
+        sqlx::query("UPDATE products SET quantity_on_hand = 99 WHERE id = 'p2'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let corrections = service.rebuild_on_hand("t1").await.unwrap();
+        assert_eq!(
+            corrections,
+            vec![OnHandCorrection { product_id: "p2".to_string(), store_id: None, cached: 99.0, ledger: 5.0 }]
+        );
+        assert_eq!(on_hand(&pool, "p2").await, 5.0);
+        assert!(service.rebuild_on_hand("t1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stock_is_kept_per_store() {
+        let pool = setup_test_db().await;
+        let service = InventoryLedgerService::new(pool.clone());
+
+        // Without a store a movement happens at the product's own store
+        let sale = StockMovement::new("t1", "p1", MovementType::Sale, -4.0, REASON_SALE);
+        assert_eq!(service.record(&sale).await.unwrap().store_id.as_deref(), Some("s1"));
+        let receipt = StockMovement::new("t1", "p1", MovementType::Receipt, 3.0, REASON_VENDOR_RECEIPT).at_store("s2");
+        let recorded = service.record(&receipt).await.unwrap();
+        assert_eq!(recorded.quantity_after, 9.0);
+
+        let locations = service.locations("t1", "p1").await.unwrap();
+        let levels: Vec<_> = locations.iter().map(|l| (l.store_id.as_str(), l.quantity_on_hand)).collect();
+        assert_eq!(levels, vec![("s1", 6.0), ("s2", 3.0)]);
+        assert_eq!(locations[1].store_name.as_deref(), Some("Warehouse"));
+        assert_eq!(
+            service.on_hand_as_of("t1", "p1", Some("s2"), &recorded.created_at).await.unwrap(),
+            3.0
+        );
+
+        // Counting one store leaves the other alone
+        let mut conn = pool.acquire().await.unwrap();
+        let count = StockMovement::new("t1", "p1", MovementType::Count, 0.0, "cycle_count").at_store("s2");
+        let counted = set_on_hand(&mut conn, count, 1.0).await.unwrap().unwrap();
+        assert_eq!(counted.quantity, -2.0);
+        drop(conn);
+        assert_eq!(on_hand(&pool, "p1").await, 7.0);
+
+        let bin = service.set_bin_location("t1", "p1", "s2", Some("A-03-2"), Some(2.0)).await.unwrap();
+        assert_eq!(bin.bin_location.as_deref(), Some("A-03-2"));
+        assert_eq!(bin.quantity_on_hand, 1.0);
+        assert!(matches!(
+            service.set_bin_location("t1", "p1", "s2", None, Some(-1.0)).await,
+            Err(LedgerError::Validation(_))
+        ));
+
+        // A location cache written behind the ledger's back is put right
+        sqlx::query("UPDATE product_locations SET quantity_on_hand = 50 WHERE product_id = 'p1' AND store_id = 's1'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let corrections = service.rebuild_on_hand("t1").await.unwrap();
+        assert_eq!(
+            corrections,
+            vec![OnHandCorrection {
+                product_id: "p1".to_string(),
+                store_id: Some("s1".to_string()),
+                cached: 50.0,
+                ledger: 6.0,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_movements_are_append_only() {
+        let pool = setup_test_db().await;
+
+        assert!(sqlx::query("UPDATE inventory_movements SET quantity = 1").execute(&pool).await.is_err());
+        assert!(sqlx::query("DELETE FROM inventory_movements").execute(&pool).await.is_err());
+    }
+}
@@ -19,24 +19,39 @@ pub mod backup_service;
 pub mod backup_service_pbt;
 pub mod barcode_service;
 pub mod bulk_operation_safety;
+pub mod checkout_service;
+pub mod commission_service;
 pub mod conflict_resolver;
+pub mod costing_service;
+pub mod count_service;
 pub mod credential_service;
 pub mod dry_run_executor;
 pub mod file_service;
+pub mod fitment_service;
 pub mod google_drive_service;
 pub mod health_check;
 pub mod id_mapper;
+pub mod inventory_ledger_service;
 pub mod offline_credit_checker;
+pub mod outbound_webhook_service;
+pub mod override_service;
 pub mod password_service;
 pub mod product_lookup_service;
 pub mod product_service;
+pub mod purchase_order_service;
 pub mod receiving_service;
 pub mod restore_service;
 pub mod retention_service;
+pub mod return_service;
+pub mod role_service;
 pub mod scheduler_service;
 pub mod search_service;
 pub mod settings_resolution;
 pub mod settings_scope_enforcement;
+pub mod shift_service;
+pub mod station_login_service;
+pub mod stored_value_service;
+pub mod suspended_sale_service;
 pub mod sync_direction_control;
 pub mod sync_orchestrator;
 pub mod sync_scheduler;
@@ -44,7 +59,10 @@ pub mod sync_logger;
 pub mod sync_queue_processor;
 #[cfg(feature = "notifications")]
 pub mod sync_notifier;
+pub mod tax_service;
 pub mod tenant_resolver;
+pub mod transfer_service;
+pub mod two_factor_service;
 pub mod unit_conversion_service;
 pub mod variant_service;
 pub mod branding_asset_service;
@@ -146,28 +164,43 @@ pub use artifact_storage::{ArtifactStorage, StorageConfig, StorageError};
 pub use audit_logger::AuditLogger;
 pub use backup_service::BackupService;
 pub use barcode_service::BarcodeService;
+pub use checkout_service::CheckoutService;
 pub use conflict_resolver::ConflictResolver;
+pub use costing_service::CostingService;
+pub use count_service::CountService;
 pub use credential_service::CredentialService;
 pub use file_service::FileService;
+pub use fitment_service::FitmentService;
 pub use google_drive_service::GoogleDriveService;
 pub use health_check::HealthCheckService;
+pub use inventory_ledger_service::InventoryLedgerService;
 pub use offline_credit_checker::OfflineCreditChecker;
+pub use outbound_webhook_service::OutboundWebhookService;
+pub use override_service::OverrideService;
 #[allow(unused_imports)]
 pub use password_service::{PasswordService, PasswordError};
 #[allow(unused_imports)]
 pub use product_lookup_service::{ProductLookupService, ProductLookupResult};
 pub use product_service::ProductService;
+pub use purchase_order_service::PurchaseOrderService;
 pub use receiving_service::ReceivingService;
 pub use restore_service::RestoreService;
 pub use retention_service::RetentionService;
+pub use return_service::ReturnService;
+pub use role_service::RoleService;
 pub use scheduler_service::SchedulerService;
 pub use search_service::SearchService;
 #[allow(unused_imports)]
 pub use settings_scope_enforcement::SettingsScopeEnforcement;
+pub use shift_service::ShiftService;
+pub use station_login_service::StationLoginService;
+pub use suspended_sale_service::SuspendedSaleService;
 pub use sync_direction_control::SyncDirectionControl;
 pub use sync_orchestrator::SyncOrchestrator;
 pub use sync_scheduler::SyncScheduler;
 pub use tenant_resolver::TenantResolver;
+pub use transfer_service::TransferService;
+pub use two_factor_service::TwoFactorService;
 pub use unit_conversion_service::UnitConversionService;
 pub use variant_service::VariantService;
 #[allow(unused_imports)]
@@ -0,0 +1,1436 @@
+/**
+ * Outbound Webhook Service
+ *
+ * Lets other systems subscribe to EasySale events over HTTP:
+ * - A tenant registers an endpoint URL and the event types it wants. Each
+ *   subscription has its own signing secret, shown when it is created or
+ *   rotated and kept encrypted with the integration credential key.
+ * - `publish` records an event on the caller's connection, so it commits or
+ *   rolls back with the sale, void or update that raised it, and queues one
+ *   delivery per active subscription that wants it. Nothing is written when
+ *   no subscription does.
+ * - The delivery worker POSTs due deliveries and retries failed ones with
+ *   exponential backoff (`BackoffPolicy`), giving up after the last retry.
+ *   Every request is logged, and any delivery can be sent again by hand.
+ *
+ * Endpoints must be public: URLs naming a loopback, private or link-local
+ * host (cloud metadata included) are refused, host names are checked again
+ * when they are resolved for each delivery, and redirects are not followed.
+ * Release builds only accept https endpoints.
+ *
+ * Each request carries `X-EasySale-Signature: t=<unix seconds>,v1=<hex>`,
+ * the HMAC-SHA256 of `<t>.<body>` keyed with the subscription's secret, so
+ * the receiver can check who sent it and turn away old replays. Delivery is
+ * at least once; the event and delivery ids are sent along for
+ * de-duplication.
+ *
+ * Inbound webhooks from WooCommerce and QuickBooks are in
+ * `handlers::webhooks`.
+ */
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration as StdDuration, Instant};
+
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::credential_service::CredentialService;
+use crate::services::sync_queue_processor::BackoffPolicy;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-EasySale-Signature";
+pub const EVENT_HEADER: &str = "X-EasySale-Event";
+pub const EVENT_ID_HEADER: &str = "X-EasySale-Event-Id";
+pub const DELIVERY_HEADER: &str = "X-EasySale-Delivery";
+
+const SECRET_PREFIX: &str = "whsec_";
+const SECRET_BYTES: usize = 32;
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_URL_LENGTH: usize = 2048;
+
+/// How long an endpoint has to answer
+const REQUEST_TIMEOUT_SECONDS: u64 = 10;
+/// How much of an endpoint's answer is kept in the delivery log
+const MAX_LOGGED_RESPONSE_CHARS: usize = 4096;
+/// How often the worker looks for due deliveries
+const WORKER_INTERVAL_SECONDS: u64 = 15;
+/// Deliveries sent per pass of the worker
+const WORKER_BATCH_SIZE: i64 = 50;
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 500;
+
+const STATUS_PENDING: &str = "pending";
+const STATUS_DELIVERED: &str = "delivered";
+const STATUS_FAILED: &str = "failed";
+
+/// Retries from 30 seconds doubling to at most 6 hours apart; a delivery is
+/// given up after 10 failed attempts, about 8.5 hours after the first
+#[must_use]
+pub fn delivery_backoff() -> BackoffPolicy {
+    BackoffPolicy {
+        base_delay_ms: 30_000,
+        max_delay_ms: 6 * 60 * 60 * 1000,
+        max_retries: 10,
+        jitter_factor: 0.1,
+        multiplier: 2.0,
+    }
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEventType {
+    SaleCompleted,
+    SaleVoided,
+    ProductUpdated,
+    StockLow,
+    LayawayOverdue,
+    CustomerCreated,
+}
+
+impl WebhookEventType {
+    pub const ALL: [Self; 6] = [
+        Self::SaleCompleted,
+        Self::SaleVoided,
+        Self::ProductUpdated,
+        Self::StockLow,
+        Self::LayawayOverdue,
+        Self::CustomerCreated,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::SaleCompleted => "sale.completed",
+            Self::SaleVoided => "sale.voided",
+            Self::ProductUpdated => "product.updated",
+            Self::StockLow => "stock.low",
+            Self::LayawayOverdue => "layaway.overdue",
+            Self::CustomerCreated => "customer.created",
+        }
+    }
+
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::SaleCompleted => "A sale was completed at the register",
+            Self::SaleVoided => "A completed sale was voided",
+            Self::ProductUpdated => "A product was edited",
+            Self::StockLow => "A product's stock at a store fell to its reorder point",
+            Self::LayawayOverdue => "A layaway passed its due date with a balance owing",
+            Self::CustomerCreated => "A customer was added",
+        }
+    }
+
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event_type| event_type.as_str() == value)
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<WebhookError> for ApiError {
+    fn from(err: WebhookError) -> Self {
+        match err {
+            WebhookError::Validation(msg) => Self::bad_request(msg),
+            WebhookError::NotFound(what) => Self::not_found(what),
+            WebhookError::Encryption(msg) => Self::internal(msg),
+            WebhookError::Database(e) => Self::internal(format!("Failed to access webhooks: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventTypeInfo {
+    #[serde(rename = "type")]
+    pub event_type: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub is_active: bool,
+    pub created_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Returned only when the secret is created or rotated
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionWithSecret {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    /// Key for checking the signature header
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSubscriptionRequest {
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateSubscriptionRequest {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub event_id: String,
+    pub event_type: String,
+    /// pending, delivered or failed (given up)
+    pub status: String,
+    pub attempts: i64,
+    /// When the worker sends it next; only while pending
+    pub next_attempt_at: Option<String>,
+    pub last_attempt_at: Option<String>,
+    pub delivered_at: Option<String>,
+    pub created_at: String,
+}
+
+/// One request made for a delivery
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DeliveryAttempt {
+    pub id: String,
+    pub attempted_at: String,
+    /// None when the endpoint could not be reached
+    pub response_status: Option<i64>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryDetail {
+    #[serde(flatten)]
+    pub delivery: WebhookDelivery,
+    /// The request body as sent
+    pub payload: Value,
+    pub attempts: Vec<DeliveryAttempt>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeliveryQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SubscriptionRow {
+    id: String,
+    name: String,
+    url: String,
+    events: String,
+    is_active: bool,
+    created_by: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+impl SubscriptionRow {
+    fn into_subscription(self) -> WebhookSubscription {
+        WebhookSubscription {
+            events: serde_json::from_str(&self.events).unwrap_or_default(),
+            id: self.id,
+            name: self.name,
+            url: self.url,
+            is_active: self.is_active,
+            created_by: self.created_by,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// A delivery with what is needed to send it
+#[derive(Debug, sqlx::FromRow)]
+struct Outgoing {
+    delivery_id: String,
+    status: String,
+    attempts: i64,
+    next_attempt_at: Option<String>,
+    event_id: String,
+    event_type: String,
+    payload: String,
+    url: String,
+    secret_encrypted: String,
+}
+
+#[derive(Debug)]
+struct AttemptOutcome {
+    response_status: Option<u16>,
+    response_body: Option<String>,
+    error: Option<String>,
+    duration_ms: i64,
+}
+
+impl AttemptOutcome {
+    fn succeeded(&self) -> bool {
+        self.response_status.is_some_and(|status| (200..300).contains(&status))
+    }
+}
+
+const SELECT_SUBSCRIPTION: &str = "SELECT id, name, url, events, is_active, created_by, created_at, updated_at
+     FROM webhook_subscriptions";
+
+const SELECT_DELIVERY: &str = "SELECT id, subscription_id, event_id, event_type, status, attempts,
+            next_attempt_at, last_attempt_at, delivered_at, created_at
+     FROM webhook_deliveries";
+
+const SELECT_OUTGOING: &str = "SELECT d.id AS delivery_id, d.status, d.attempts, d.next_attempt_at,
+            e.id AS event_id, e.event_type, e.payload, s.url, s.secret_encrypted
+     FROM webhook_deliveries d
+     JOIN outbound_webhook_events e ON e.id = d.event_id
+     JOIN webhook_subscriptions s ON s.id = d.subscription_id";
+
+// ============================================================================
+// Publishing
+// ============================================================================
+
+/// Record an event and queue it for every active subscription that wants it
+///
+/// Runs on the caller's connection, so the event commits or rolls back with
+/// the change that raised it. Returns the event id, or `None` when no
+/// subscription wants the event and nothing was written.
+///
+/// # Errors
+///
+/// Returns an error if the database write fails.
+pub async fn publish(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    event_type: WebhookEventType,
+    data: Value,
+) -> Result<Option<String>, sqlx::Error> {
+    let subscription_ids: Vec<String> = sqlx::query_scalar(
+        "SELECT s.id FROM webhook_subscriptions s
+         WHERE s.tenant_id = ? AND s.is_active = 1
+           AND EXISTS (SELECT 1 FROM json_each(s.events) WHERE json_each.value = ?)",
+    )
+    .bind(tenant_id)
+    .bind(event_type.as_str())
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if subscription_ids.is_empty() {
+        return Ok(None);
+    }
+
+    let event_id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let payload = json!({
+        "id": event_id,
+        "type": event_type.as_str(),
+        "created_at": now,
+        "tenant_id": tenant_id,
+        "data": data,
+    });
+
+    sqlx::query(
+        "INSERT INTO outbound_webhook_events (id, tenant_id, event_type, payload, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&event_id)
+    .bind(tenant_id)
+    .bind(event_type.as_str())
+    .bind(payload.to_string())
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+
+    for subscription_id in &subscription_ids {
+        sqlx::query(
+            "INSERT INTO webhook_deliveries (
+                id, tenant_id, subscription_id, event_id, event_type, status, attempts,
+                next_attempt_at, created_at
+             ) VALUES (?, ?, ?, ?, ?, ?, 0, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .bind(&event_id)
+        .bind(event_type.as_str())
+        .bind(STATUS_PENDING)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    tracing::debug!(
+        event_id = %event_id,
+        event_type = event_type.as_str(),
+        deliveries = subscription_ids.len(),
+        "Webhook event queued"
+    );
+
+    Ok(Some(event_id))
+}
+
+/// `t=<timestamp>,v1=<hex>` for the signature header: the HMAC-SHA256 of
+/// `<timestamp>.<body>` keyed with the subscription's secret
+#[must_use]
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!("t={timestamp},v1={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct OutboundWebhookService {
+    pool: SqlitePool,
+    /// Encrypts the signing secrets
+    credentials: CredentialService,
+    client: reqwest::Client,
+    backoff: BackoffPolicy,
+    /// Endpoints on this machine or the local network may be used (tests)
+    allow_internal_hosts: bool,
+}
+
+impl OutboundWebhookService {
+    #[must_use]
+    pub fn new(pool: SqlitePool, credentials: CredentialService) -> Self {
+        Self {
+            pool,
+            credentials,
+            client: delivery_client().build().unwrap_or_default(),
+            backoff: delivery_backoff(),
+            allow_internal_hosts: false,
+        }
+    }
+
+    #[must_use]
+    pub fn with_backoff_policy(mut self, backoff: BackoffPolicy) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Deliver to endpoints on the test machine
+    #[cfg(test)]
+    #[must_use]
+    pub(crate) fn allowing_internal_hosts(mut self) -> Self {
+        self.allow_internal_hosts = true;
+        self
+    }
+
+    /// Start the background delivery worker
+    pub fn start(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(StdDuration::from_secs(WORKER_INTERVAL_SECONDS));
+
+            tracing::info!("Webhook delivery worker started - checking every {WORKER_INTERVAL_SECONDS} seconds");
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = self.deliver_due().await {
+                    tracing::error!("Webhook delivery pass failed: {e}");
+                }
+            }
+        })
+    }
+
+    // ------------------------------------------------------------------------
+    // Subscriptions
+    // ------------------------------------------------------------------------
+
+    #[must_use]
+    pub fn event_types() -> Vec<EventTypeInfo> {
+        WebhookEventType::ALL
+            .into_iter()
+            .map(|event_type| EventTypeInfo {
+                event_type: event_type.as_str(),
+                description: event_type.description(),
+            })
+            .collect()
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn list_subscriptions(&self, tenant_id: &str) -> Result<Vec<WebhookSubscription>, WebhookError> {
+        let rows: Vec<SubscriptionRow> =
+            sqlx::query_as(&format!("{SELECT_SUBSCRIPTION} WHERE tenant_id = ? ORDER BY name"))
+                .bind(tenant_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.into_iter().map(SubscriptionRow::into_subscription).collect())
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the subscription does not exist for the tenant or
+    /// the database read fails.
+    pub async fn get_subscription(&self, tenant_id: &str, id: &str) -> Result<WebhookSubscription, WebhookError> {
+        let row: Option<SubscriptionRow> =
+            sqlx::query_as(&format!("{SELECT_SUBSCRIPTION} WHERE id = ? AND tenant_id = ?"))
+                .bind(id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.map(SubscriptionRow::into_subscription)
+            .ok_or_else(|| WebhookError::NotFound(format!("Webhook {id}")))
+    }
+
+    /// Register an endpoint; the response holds its new signing secret
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name, URL or event types are invalid, the
+    /// secret cannot be encrypted or the database write fails.
+    pub async fn create_subscription(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        request: CreateSubscriptionRequest,
+    ) -> Result<SubscriptionWithSecret, WebhookError> {
+        let name = validate_name(&request.name)?;
+        let url = validate_url(&request.url, self.allow_internal_hosts)?;
+        let events = validate_events(&request.events)?;
+        let secret = generate_secret();
+        let secret_encrypted = self.encrypt(&secret)?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "INSERT INTO webhook_subscriptions (
+                id, tenant_id, name, url, events, secret_encrypted, is_active,
+                created_by, created_at, updated_at
+             ) VALUES (?, ?, ?, ?, ?, ?, 1, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(&url)
+        .bind(&events)
+        .bind(&secret_encrypted)
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!(subscription_id = %id, url = %url, "Webhook subscription created");
+
+        Ok(SubscriptionWithSecret {
+            subscription: self.get_subscription(tenant_id, &id).await?,
+            secret,
+        })
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the subscription does not exist for the tenant, a
+    /// new value is invalid or the database write fails.
+    pub async fn update_subscription(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        request: UpdateSubscriptionRequest,
+    ) -> Result<WebhookSubscription, WebhookError> {
+        let current = self.get_subscription(tenant_id, id).await?;
+
+        let name = match &request.name {
+            Some(name) => validate_name(name)?,
+            None => current.name,
+        };
+        let url = match &request.url {
+            Some(url) => validate_url(url, self.allow_internal_hosts)?,
+            None => current.url,
+        };
+        let events = match &request.events {
+            Some(events) => validate_events(events)?,
+            None => serde_json::to_string(&current.events).unwrap_or_else(|_| "[]".to_string()),
+        };
+        let is_active = request.is_active.unwrap_or(current.is_active);
+
+        sqlx::query(
+            "UPDATE webhook_subscriptions
+             SET name = ?, url = ?, events = ?, is_active = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(&name)
+        .bind(&url)
+        .bind(&events)
+        .bind(is_active)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!(subscription_id = %id, "Webhook subscription updated");
+
+        self.get_subscription(tenant_id, id).await
+    }
+
+    /// Remove a subscription with its deliveries and their log
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subscription does not exist for the tenant or
+    /// the database write fails.
+    pub async fn delete_subscription(&self, tenant_id: &str, id: &str) -> Result<(), WebhookError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "DELETE FROM webhook_delivery_attempts
+             WHERE delivery_id IN (SELECT id FROM webhook_deliveries WHERE subscription_id = ? AND tenant_id = ?)",
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM webhook_deliveries WHERE subscription_id = ? AND tenant_id = ?")
+            .bind(id)
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await?;
+        let deleted = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = ? AND tenant_id = ?")
+            .bind(id)
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(WebhookError::NotFound(format!("Webhook {id}")));
+        }
+
+        tx.commit().await?;
+
+        tracing::info!(subscription_id = %id, "Webhook subscription deleted");
+
+        Ok(())
+    }
+
+    /// Replace the signing secret; requests are signed with the new one from
+    /// now on, including retries of earlier events
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subscription does not exist for the tenant,
+    /// the secret cannot be encrypted or the database write fails.
+    pub async fn rotate_secret(&self, tenant_id: &str, id: &str) -> Result<SubscriptionWithSecret, WebhookError> {
+        let secret = generate_secret();
+        let secret_encrypted = self.encrypt(&secret)?;
+
+        let updated = sqlx::query(
+            "UPDATE webhook_subscriptions SET secret_encrypted = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(&secret_encrypted)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(WebhookError::NotFound(format!("Webhook {id}")));
+        }
+
+        tracing::info!(subscription_id = %id, "Webhook secret rotated");
+
+        Ok(SubscriptionWithSecret {
+            subscription: self.get_subscription(tenant_id, id).await?,
+            secret,
+        })
+    }
+
+    // ------------------------------------------------------------------------
+    // Deliveries
+    // ------------------------------------------------------------------------
+
+    /// A subscription's deliveries, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the subscription does not exist for the tenant,
+    /// the status filter is unknown or the database read fails.
+    pub async fn list_deliveries(
+        &self,
+        tenant_id: &str,
+        subscription_id: &str,
+        query: &DeliveryQuery,
+    ) -> Result<Vec<WebhookDelivery>, WebhookError> {
+        self.get_subscription(tenant_id, subscription_id).await?;
+
+        if let Some(status) = &query.status {
+            if ![STATUS_PENDING, STATUS_DELIVERED, STATUS_FAILED].contains(&status.as_str()) {
+                return Err(WebhookError::Validation(format!("Unknown delivery status: {status}")));
+            }
+        }
+
+        let deliveries = sqlx::query_as(&format!(
+            "{SELECT_DELIVERY}
+             WHERE tenant_id = ? AND subscription_id = ? AND (? IS NULL OR status = ?)
+             ORDER BY created_at DESC, id
+             LIMIT ? OFFSET ?"
+        ))
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .bind(&query.status)
+        .bind(&query.status)
+        .bind(query.limit.unwrap_or(DEFAULT_DELIVERY_LIMIT).clamp(1, MAX_DELIVERY_LIMIT))
+        .bind(query.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    /// A delivery with the payload sent and every attempt made
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the delivery does not exist for the tenant or the
+    /// database read fails.
+    pub async fn get_delivery(&self, tenant_id: &str, delivery_id: &str) -> Result<DeliveryDetail, WebhookError> {
+        let delivery: WebhookDelivery =
+            sqlx::query_as(&format!("{SELECT_DELIVERY} WHERE id = ? AND tenant_id = ?"))
+                .bind(delivery_id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| WebhookError::NotFound(format!("Webhook delivery {delivery_id}")))?;
+
+        let payload: String = sqlx::query_scalar("SELECT payload FROM outbound_webhook_events WHERE id = ?")
+            .bind(&delivery.event_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let attempts = sqlx::query_as(
+            "SELECT id, attempted_at, response_status, response_body, error, duration_ms
+             FROM webhook_delivery_attempts
+             WHERE delivery_id = ?
+             ORDER BY attempted_at DESC",
+        )
+        .bind(delivery_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(DeliveryDetail {
+            delivery,
+            payload: serde_json::from_str(&payload).unwrap_or(Value::Null),
+            attempts,
+        })
+    }
+
+    /// Send a delivery again now, whatever its status
+    ///
+    /// The attempt is logged like any other. A delivery that goes through is
+    /// marked delivered; one that does not keeps its status and schedule.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the delivery does not exist for the tenant or the
+    /// database write fails. An endpoint that fails is not an error; it is in
+    /// the attempts returned.
+    pub async fn redeliver(&self, tenant_id: &str, delivery_id: &str) -> Result<DeliveryDetail, WebhookError> {
+        let outgoing: Outgoing =
+            sqlx::query_as(&format!("{SELECT_OUTGOING} WHERE d.id = ? AND d.tenant_id = ?"))
+                .bind(delivery_id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| WebhookError::NotFound(format!("Webhook delivery {delivery_id}")))?;
+
+        let outcome = self.send(&outgoing).await;
+        self.record_attempt(&outgoing, &outcome, false).await?;
+
+        tracing::info!(
+            delivery_id = %delivery_id,
+            succeeded = outcome.succeeded(),
+            "Webhook redelivered"
+        );
+
+        self.get_delivery(tenant_id, delivery_id).await
+    }
+
+    /// Send the deliveries that are due, for all tenants; returns how many
+    /// were sent
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read or write fails.
+    pub async fn deliver_due(&self) -> Result<usize, WebhookError> {
+        let due: Vec<Outgoing> = sqlx::query_as(&format!(
+            "{SELECT_OUTGOING}
+             WHERE d.status = ? AND d.next_attempt_at <= ? AND s.is_active = 1
+             ORDER BY d.next_attempt_at
+             LIMIT ?"
+        ))
+        .bind(STATUS_PENDING)
+        .bind(Utc::now().to_rfc3339())
+        .bind(WORKER_BATCH_SIZE)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for outgoing in &due {
+            let outcome = self.send(outgoing).await;
+            self.record_attempt(outgoing, &outcome, true).await?;
+        }
+
+        Ok(due.len())
+    }
+
+    /// The client to reach the endpoint with
+    ///
+    /// The URL is checked again, as it may have been saved before internal
+    /// hosts were refused. A host name is resolved here and the request is
+    /// pinned to those addresses, so it cannot resolve somewhere internal
+    /// between the check and the request.
+    async fn endpoint_client(&self, url: &str) -> Result<reqwest::Client, WebhookError> {
+        validate_url(url, self.allow_internal_hosts)?;
+        if self.allow_internal_hosts {
+            return Ok(self.client.clone());
+        }
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| WebhookError::Validation(format!("Webhook URL is not valid: {e}")))?;
+        let Some(url::Host::Domain(domain)) = parsed.host() else {
+            return Ok(self.client.clone());
+        };
+
+        let port = parsed.port_or_known_default().unwrap_or(443);
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain, port))
+            .await
+            .map_err(|e| WebhookError::Validation(format!("Could not resolve {domain}: {e}")))?
+            .collect();
+        if let Some(addr) = addrs.iter().find(|addr| is_internal_address(addr.ip())) {
+            return Err(WebhookError::Validation(format!(
+                "{domain} resolves to {}, which is not a public address",
+                addr.ip()
+            )));
+        }
+
+        delivery_client()
+            .resolve_to_addrs(domain, &addrs)
+            .build()
+            .map_err(|e| WebhookError::Validation(format!("Could not reach {domain}: {e}")))
+    }
+
+    /// POST the event to the endpoint
+    async fn send(&self, outgoing: &Outgoing) -> AttemptOutcome {
+        let started = Instant::now();
+        let elapsed_ms = || i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX);
+
+        let client = match self.endpoint_client(&outgoing.url).await {
+            Ok(client) => client,
+            Err(e) => {
+                return AttemptOutcome {
+                    response_status: None,
+                    response_body: None,
+                    error: Some(e.to_string()),
+                    duration_ms: elapsed_ms(),
+                };
+            }
+        };
+
+        let secret = match self.credentials.decrypt_data(&outgoing.secret_encrypted) {
+            Ok(secret) => secret,
+            Err(e) => {
+                return AttemptOutcome {
+                    response_status: None,
+                    response_body: None,
+                    error: Some(format!("Failed to decrypt the signing secret: {e}")),
+                    duration_ms: elapsed_ms(),
+                };
+            }
+        };
+
+        let result = client
+            .post(&outgoing.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &outgoing.event_type)
+            .header(EVENT_ID_HEADER, &outgoing.event_id)
+            .header(DELIVERY_HEADER, &outgoing.delivery_id)
+            .header(SIGNATURE_HEADER, sign(&secret, Utc::now().timestamp(), &outgoing.payload))
+            .body(outgoing.payload.clone())
+            .send()
+            .await;
+
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                let body = response
+                    .text()
+                    .await
+                    .ok()
+                    .filter(|body| !body.is_empty())
+                    .map(|body| body.chars().take(MAX_LOGGED_RESPONSE_CHARS).collect());
+
+                AttemptOutcome {
+                    response_status: Some(status.as_u16()),
+                    response_body: body,
+                    error: (!status.is_success()).then(|| format!("Endpoint answered {status}")),
+                    duration_ms: elapsed_ms(),
+                }
+            }
+            Err(e) => AttemptOutcome {
+                response_status: None,
+                response_body: None,
+                error: Some(e.to_string()),
+                duration_ms: elapsed_ms(),
+            },
+        }
+    }
+
+    /// Log an attempt and move the delivery on
+    ///
+    /// With `retry`, a failed delivery is scheduled again by the backoff
+    /// policy, or given up once it runs out of retries.
+    async fn record_attempt(
+        &self,
+        outgoing: &Outgoing,
+        outcome: &AttemptOutcome,
+        retry: bool,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        let attempts = outgoing.attempts + 1;
+
+        let (status, next_attempt_at, delivered_at) = if outcome.succeeded() {
+            (STATUS_DELIVERED.to_string(), None, Some(now.to_rfc3339()))
+        } else if retry {
+            let delay = u32::try_from(outgoing.attempts)
+                .ok()
+                .and_then(|failures| self.backoff.calculate_delay(failures))
+                .and_then(|delay| Duration::from_std(delay).ok());
+            match delay {
+                Some(delay) => (STATUS_PENDING.to_string(), Some((now + delay).to_rfc3339()), None),
+                None => {
+                    tracing::warn!(
+                        delivery_id = %outgoing.delivery_id,
+                        attempts,
+                        "Webhook delivery given up"
+                    );
+                    (STATUS_FAILED.to_string(), None, None)
+                }
+            }
+        } else {
+            (outgoing.status.clone(), outgoing.next_attempt_at.clone(), None)
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO webhook_delivery_attempts (
+                id, delivery_id, attempted_at, response_status, response_body, error, duration_ms
+             ) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&outgoing.delivery_id)
+        .bind(now.to_rfc3339())
+        .bind(outcome.response_status.map(i64::from))
+        .bind(&outcome.response_body)
+        .bind(&outcome.error)
+        .bind(outcome.duration_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE webhook_deliveries
+             SET status = ?, attempts = ?, next_attempt_at = ?, last_attempt_at = ?,
+                 delivered_at = COALESCE(?, delivered_at)
+             WHERE id = ?",
+        )
+        .bind(&status)
+        .bind(attempts)
+        .bind(&next_attempt_at)
+        .bind(now.to_rfc3339())
+        .bind(&delivered_at)
+        .bind(&outgoing.delivery_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    fn encrypt(&self, secret: &str) -> Result<String, WebhookError> {
+        self.credentials
+            .encrypt_data(secret)
+            .map_err(|e| WebhookError::Encryption(e.to_string()))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn generate_secret() -> String {
+    let bytes: [u8; SECRET_BYTES] = rand::thread_rng().gen();
+    format!("{SECRET_PREFIX}{}", hex::encode(bytes))
+}
+
+fn validate_name(name: &str) -> Result<String, WebhookError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(WebhookError::Validation("Webhook name is required".to_string()));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(WebhookError::Validation(format!(
+            "Webhook name must be at most {MAX_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_url(url: &str, allow_internal_hosts: bool) -> Result<String, WebhookError> {
+    let url = url.trim();
+    if url.len() > MAX_URL_LENGTH {
+        return Err(WebhookError::Validation(format!(
+            "Webhook URL must be at most {MAX_URL_LENGTH} characters"
+        )));
+    }
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| WebhookError::Validation(format!("Webhook URL is not valid: {e}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(WebhookError::Validation("Webhook URL must be an http or https address".to_string()));
+    }
+    if cfg!(not(debug_assertions)) && parsed.scheme() != "https" {
+        return Err(WebhookError::Validation("Webhook URL must be an https address".to_string()));
+    }
+    let internal = match parsed.host() {
+        Some(url::Host::Ipv4(ip)) => is_internal_address(IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => is_internal_address(IpAddr::V6(ip)),
+        Some(url::Host::Domain(domain)) => is_internal_domain(domain),
+        None => true,
+    };
+    if internal && !allow_internal_hosts {
+        return Err(WebhookError::Validation(
+            "Webhook URL must be a public address, not a local, private or link-local one".to_string(),
+        ));
+    }
+    Ok(url.to_string())
+}
+
+/// Names that only mean something on this machine or inside a network
+fn is_internal_domain(domain: &str) -> bool {
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    domain == "localhost"
+        || [".localhost", ".local", ".internal"].iter().any(|suffix| domain.ends_with(suffix))
+}
+
+/// Loopback, private, link-local (cloud metadata), carrier-grade NAT and
+/// unspecified addresses
+fn is_internal_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || (first == 100 && (second & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_internal_address(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                // Unique local fc00::/7, link-local fe80::/10
+                ip.is_loopback() || ip.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+            }
+        },
+    }
+}
+
+/// The HTTP client deliveries are sent with; redirects are not followed,
+/// so an endpoint cannot point it somewhere internal
+fn delivery_client() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(StdDuration::from_secs(REQUEST_TIMEOUT_SECONDS))
+        .redirect(reqwest::redirect::Policy::none())
+}
+
+/// The event types as the JSON array stored on the subscription
+fn validate_events(events: &[String]) -> Result<String, WebhookError> {
+    let mut valid: Vec<&str> = Vec::with_capacity(events.len());
+    for event in events {
+        let event_type = WebhookEventType::parse(event.trim())
+            .ok_or_else(|| WebhookError::Validation(format!("Unknown event type: {event}")))?;
+        if !valid.contains(&event_type.as_str()) {
+            valid.push(event_type.as_str());
+        }
+    }
+    if valid.is_empty() {
+        return Err(WebhookError::Validation("Subscribe to at least one event type".to_string()));
+    }
+    Ok(serde_json::to_string(&valid).unwrap_or_else(|_| "[]".to_string()))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    async fn setup_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::query(include_str!("../../../../migrations/077_outbound_webhooks.sql"))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        pool
+    }
+
+    /// Endpoints in these tests listen on 127.0.0.1
+    fn service(pool: &SqlitePool) -> OutboundWebhookService {
+        OutboundWebhookService::new(pool.clone(), CredentialService::new(pool.clone()).unwrap()).allowing_internal_hosts()
+    }
+
+    async fn subscribe(service: &OutboundWebhookService, url: &str, events: &[&str]) -> SubscriptionWithSecret {
+        service
+            .create_subscription(
+                "t1",
+                "u1",
+                CreateSubscriptionRequest {
+                    name: "ERP".to_string(),
+                    url: url.to_string(),
+                    events: events.iter().map(ToString::to_string).collect(),
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    /// Answers one request with `status` and hands back the request as received
+    async fn endpoint(status: u16) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0_u8; 4096];
+            loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+                if read == 0 {
+                    break;
+                }
+            }
+            let response = format!("HTTP/1.1 {status} Status\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok");
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_signature_is_hmac_of_timestamp_and_body() {
+        let body = r#"{"id":"e1","type":"sale.completed"}"#;
+        let signature = sign("whsec_test", 1_760_000_000, body);
+
+        let mut mac = HmacSha256::new_from_slice(b"whsec_test").unwrap();
+        mac.update(format!("1760000000.{body}").as_bytes());
+        let expected = hex::encode(mac.finalize().into_bytes());
+
+        assert_eq!(signature, format!("t=1760000000,v1={expected}"));
+        assert_ne!(signature, sign("whsec_other", 1_760_000_000, body));
+        assert_ne!(signature, sign("whsec_test", 1_760_000_001, body));
+    }
+
+    #[tokio::test]
+    async fn test_publish_queues_for_matching_active_subscriptions_only() {
+        let pool = setup_pool().await;
+        let service = service(&pool);
+
+        let sales = subscribe(&service, "https://erp.example.com/hooks", &["sale.completed", "sale.voided"]).await;
+        subscribe(&service, "https://crm.example.com/hooks", &["customer.created"]).await;
+        let paused = subscribe(&service, "https://old.example.com/hooks", &["sale.completed"]).await;
+        service
+            .update_subscription(
+                "t1",
+                &paused.subscription.id,
+                UpdateSubscriptionRequest { is_active: Some(false), ..Default::default() },
+            )
+            .await
+            .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let event_id = publish(&mut conn, "t1", WebhookEventType::SaleCompleted, json!({ "sale_id": "s1" }))
+            .await
+            .unwrap()
+            .unwrap();
+        // Nobody wants it, so nothing is recorded
+        assert!(publish(&mut conn, "t1", WebhookEventType::StockLow, json!({})).await.unwrap().is_none());
+        // Another tenant's event
+        assert!(publish(&mut conn, "t2", WebhookEventType::SaleCompleted, json!({})).await.unwrap().is_none());
+        drop(conn);
+
+        let deliveries: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT subscription_id, event_id, status FROM webhook_deliveries")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(deliveries, vec![(sales.subscription.id, event_id, STATUS_PENDING.to_string())]);
+
+        let events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM outbound_webhook_events").fetch_one(&pool).await.unwrap();
+        assert_eq!(events, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_retried_then_redelivered() {
+        let pool = setup_pool().await;
+        let service = service(&pool);
+        let (url, received) = endpoint(500).await;
+        let subscription = subscribe(&service, &url, &["sale.completed"]).await;
+
+        let mut conn = pool.acquire().await.unwrap();
+        let event_id = publish(&mut conn, "t1", WebhookEventType::SaleCompleted, json!({ "sale_id": "s1" }))
+            .await
+            .unwrap()
+            .unwrap();
+        drop(conn);
+
+        assert_eq!(service.deliver_due().await.unwrap(), 1);
+
+        // The request was signed with the subscription's secret
+        let request = received.await.unwrap();
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+        let header = |name: &str| {
+            head.lines()
+                .find_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    key.eq_ignore_ascii_case(name).then(|| value.trim().to_string())
+                })
+                .unwrap()
+        };
+        assert_eq!(header(EVENT_HEADER), "sale.completed");
+        assert_eq!(header(EVENT_ID_HEADER), event_id);
+        let signature = header(SIGNATURE_HEADER);
+        let timestamp: i64 = signature.strip_prefix("t=").unwrap().split(',').next().unwrap().parse().unwrap();
+        assert_eq!(signature, sign(&subscription.secret, timestamp, body));
+        let payload: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(payload["type"], "sale.completed");
+        assert_eq!(payload["data"]["sale_id"], "s1");
+
+        // Scheduled for a retry, not due yet
+        let deliveries = service
+            .list_deliveries("t1", &subscription.subscription.id, &DeliveryQuery::default())
+            .await
+            .unwrap();
+        let delivery = &deliveries[0];
+        assert_eq!(delivery.status, STATUS_PENDING);
+        assert_eq!(delivery.attempts, 1);
+        assert!(delivery.next_attempt_at.as_deref().unwrap() > delivery.last_attempt_at.as_deref().unwrap());
+        assert_eq!(service.deliver_due().await.unwrap(), 0);
+
+        // Sent again by hand, the endpoint takes it
+        let (url, received) = endpoint(200).await;
+        service
+            .update_subscription("t1", &subscription.subscription.id, UpdateSubscriptionRequest {
+                url: Some(url),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let detail = service.redeliver("t1", &delivery.id).await.unwrap();
+        received.await.unwrap();
+
+        assert_eq!(detail.delivery.status, STATUS_DELIVERED);
+        assert_eq!(detail.delivery.attempts, 2);
+        assert!(detail.delivery.delivered_at.is_some());
+        assert_eq!(detail.payload["id"], event_id);
+        let statuses: Vec<Option<i64>> = detail.attempts.iter().map(|a| a.response_status).collect();
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses.contains(&Some(500)) && statuses.contains(&Some(200)));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_given_up_after_last_retry() {
+        let pool = setup_pool().await;
+        let service = service(&pool).with_backoff_policy(BackoffPolicy { max_retries: 1, ..delivery_backoff() });
+
+        // Nothing listens here
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+        drop(listener);
+        subscribe(&service, &url, &["customer.created"]).await;
+
+        let mut conn = pool.acquire().await.unwrap();
+        publish(&mut conn, "t1", WebhookEventType::CustomerCreated, json!({ "customer_id": "c1" }))
+            .await
+            .unwrap();
+        drop(conn);
+
+        // First failure is retried
+        service.deliver_due().await.unwrap();
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = '2000-01-01T00:00:00+00:00'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        // Second failure runs out of retries
+        service.deliver_due().await.unwrap();
+
+        let (status, attempts, next_attempt_at): (String, i64, Option<String>) =
+            sqlx::query_as("SELECT status, attempts, next_attempt_at FROM webhook_deliveries")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(status, STATUS_FAILED);
+        assert_eq!(attempts, 2);
+        assert!(next_attempt_at.is_none());
+
+        let errors: Vec<(Option<i64>, Option<String>)> =
+            sqlx::query_as("SELECT response_status, error FROM webhook_delivery_attempts")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|(status, error)| status.is_none() && error.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_internal_endpoints_are_refused() {
+        for url in [
+            "http://localhost:8080/hooks",
+            "https://erp.localhost/hooks",
+            "http://metadata.google.internal/computeMetadata/v1/",
+            "http://127.0.0.1/hooks",
+            "http://2130706433/hooks",
+            "http://0.0.0.0/hooks",
+            "http://10.0.0.5/hooks",
+            "http://172.16.0.1/hooks",
+            "http://192.168.1.10/hooks",
+            "http://100.64.0.1/hooks",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]/hooks",
+            "http://[::ffff:127.0.0.1]/hooks",
+            "http://[fd00::1]/hooks",
+            "http://[fe80::1]/hooks",
+        ] {
+            assert!(matches!(validate_url(url, false), Err(WebhookError::Validation(_))), "{url}");
+        }
+        assert!(validate_url("https://erp.example.com/hooks", false).is_ok());
+        assert!(validate_url("https://203.0.113.7/hooks", false).is_ok());
+
+        // A subscription saved with an internal URL is not sent to
+        let pool = setup_pool().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+        drop(listener);
+        let subscription = subscribe(&service(&pool), &url, &["customer.created"]).await;
+        let mut conn = pool.acquire().await.unwrap();
+        publish(&mut conn, "t1", WebhookEventType::CustomerCreated, json!({ "customer_id": "c1" }))
+            .await
+            .unwrap();
+        drop(conn);
+
+        let strict = OutboundWebhookService::new(pool.clone(), CredentialService::new(pool.clone()).unwrap());
+        assert_eq!(strict.deliver_due().await.unwrap(), 1);
+        let deliveries = strict
+            .list_deliveries("t1", &subscription.subscription.id, &DeliveryQuery::default())
+            .await
+            .unwrap();
+        let detail = strict.get_delivery("t1", &deliveries[0].id).await.unwrap();
+        assert_eq!(detail.attempts[0].response_status, None);
+        assert!(detail.attempts[0].error.as_deref().unwrap().contains("public address"));
+    }
+
+    #[tokio::test]
+    async fn test_subscription_validation() {
+        let pool = setup_pool().await;
+        let service = service(&pool);
+
+        for (url, events) in [
+            ("ftp://example.com/hooks", vec!["sale.completed"]),
+            ("not a url", vec!["sale.completed"]),
+            ("https://example.com/hooks", vec![]),
+            ("https://example.com/hooks", vec!["sale.refunded"]),
+        ] {
+            let result = service
+                .create_subscription("t1", "u1", CreateSubscriptionRequest {
+                    name: "ERP".to_string(),
+                    url: url.to_string(),
+                    events: events.into_iter().map(ToString::to_string).collect(),
+                })
+                .await;
+            assert!(matches!(result, Err(WebhookError::Validation(_))), "{url}");
+        }
+
+        let created = subscribe(&service, "https://example.com/hooks", &["stock.low", "stock.low"]).await;
+        assert!(created.secret.starts_with(SECRET_PREFIX));
+        assert_eq!(created.subscription.events, vec!["stock.low"]);
+
+        let rotated = service.rotate_secret("t1", &created.subscription.id).await.unwrap();
+        assert_ne!(rotated.secret, created.secret);
+        assert!(matches!(
+            service.get_subscription("t2", &created.subscription.id).await,
+            Err(WebhookError::NotFound(_))
+        ));
+    }
+}
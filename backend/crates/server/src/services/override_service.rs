@@ -0,0 +1,1000 @@
+/**
+ * Override Service
+ *
+ * Manager approval, at the register, of actions the signed-in cashier is not
+ * permitted to do themselves:
+ * - Selling a line at another price than the catalog's (`override_price`)
+ * - A discount over the store's threshold (`apply_discount`)
+ * - A return without the receipt (`return_without_receipt`)
+ * - Voiding a sale (`void_sale`)
+ *
+ * A manager who has the permission enters their PIN or scans their badge on
+ * the same station. That gives a signed approval token for the one action
+ * and subject, valid for two minutes and used at most once. The cashier's
+ * request carries the token; `authorize` checks and uses it. Every approval
+ * is written to the audit log with both user IDs. An unrecognized PIN or
+ * badge counts towards the station's PIN pad lockout, the same one station
+ * sign-ins count towards.
+ */
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::{ApiError, ValidationError};
+use crate::models::UserContext;
+use crate::services::role_service;
+use crate::services::station_login_service::{self, StationLoginError};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Why a PIN or badge was refused; the same whether it is easy to guess or
+/// another user's, so setting one does not tell whose it is
+const PIN_REFUSED: &str = "That PIN cannot be used; choose another";
+const BADGE_REFUSED: &str = "That badge cannot be used; choose another";
+
+/// How long an approval can be used for
+const APPROVAL_TTL_SECONDS: i64 = 120;
+
+/// Total discount, as a percent of the sale before discounts, a cashier may
+/// give without approval
+pub const SETTING_DISCOUNT_THRESHOLD: &str = "pos.discount_approval_threshold_percent";
+const DEFAULT_DISCOUNT_THRESHOLD_PERCENT: f64 = 10.0;
+
+/// Amounts that differ by less than this are the same
+const MONEY_EPSILON: f64 = 0.005;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum OverrideError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Not permitted: {0}")]
+    Forbidden(String),
+
+    #[error("Manager approval required")]
+    ApprovalRequired(Vec<RequiredOverride>),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Too many failed attempts at this station; try again after {0}")]
+    LockedOut(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<StationLoginError> for OverrideError {
+    fn from(err: StationLoginError) -> Self {
+        match err {
+            StationLoginError::LockedOut(until) => Self::LockedOut(until),
+            StationLoginError::Database(e) => Self::Database(e),
+            StationLoginError::Validation(msg) => Self::Validation(msg),
+            StationLoginError::NotFound(what) => Self::NotFound(what),
+            StationLoginError::InvalidCredentials => Self::Forbidden(err.to_string()),
+            StationLoginError::Forbidden(msg) => Self::Forbidden(msg),
+        }
+    }
+}
+
+impl From<OverrideError> for ApiError {
+    /// A missing approval is a 403 with code `APPROVAL_REQUIRED`; each entry
+    /// of `errors` names an action in `field` and its subject in `message`,
+    /// which is what the approval has to be asked for.
+    fn from(err: OverrideError) -> Self {
+        match err {
+            OverrideError::Validation(msg) => Self::bad_request(msg),
+            OverrideError::NotFound(what) => Self::not_found(what),
+            OverrideError::Forbidden(msg) => Self::forbidden(msg),
+            OverrideError::ApprovalRequired(required) => {
+                Self::with_code(403, "Manager approval required", "APPROVAL_REQUIRED").with_errors(
+                    required
+                        .into_iter()
+                        .map(|r| ValidationError::new(r.action.as_str(), r.subject, "approval_required"))
+                        .collect(),
+                )
+            }
+            OverrideError::Conflict(msg) => Self::conflict(msg),
+            OverrideError::LockedOut(_) => Self::with_code(429, err.to_string(), "LOCKED_OUT"),
+            OverrideError::Database(e) => Self::internal(format!("Failed to access approvals: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverrideAction {
+    /// Subject: `{product_id}:{price}`, the price to two decimals
+    PriceOverride,
+    /// Subject: the sale's total discount to two decimals
+    Discount,
+    /// Subject: the ID of the sale returned against
+    NoReceiptReturn,
+    /// Subject: the ID of the sale
+    VoidSale,
+}
+
+impl OverrideAction {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PriceOverride => "price_override",
+            Self::Discount => "discount",
+            Self::NoReceiptReturn => "no_receipt_return",
+            Self::VoidSale => "void_sale",
+        }
+    }
+
+    /// The permission that does the action without approval, and that the
+    /// approver must have
+    #[must_use]
+    pub const fn permission(self) -> &'static str {
+        match self {
+            Self::PriceOverride => "override_price",
+            Self::Discount => "apply_discount",
+            Self::NoReceiptReturn => "return_without_receipt",
+            Self::VoidSale => "void_sale",
+        }
+    }
+}
+
+/// An action a request does and what it does it to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequiredOverride {
+    pub action: OverrideAction,
+    pub subject: String,
+}
+
+impl RequiredOverride {
+    #[must_use]
+    pub fn price_override(product_id: &str, price: f64) -> Self {
+        Self { action: OverrideAction::PriceOverride, subject: format!("{product_id}:{price:.2}") }
+    }
+
+    #[must_use]
+    pub fn discount(amount: f64) -> Self {
+        Self { action: OverrideAction::Discount, subject: format!("{amount:.2}") }
+    }
+
+    #[must_use]
+    pub fn no_receipt_return(sale_id: &str) -> Self {
+        Self { action: OverrideAction::NoReceiptReturn, subject: sale_id.to_string() }
+    }
+
+    #[must_use]
+    pub fn void_sale(sale_id: &str) -> Self {
+        Self { action: OverrideAction::VoidSale, subject: sale_id.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApproveRequest {
+    pub action: OverrideAction,
+    pub subject: String,
+    /// The approver's PIN; or `badge`
+    pub pin: Option<String>,
+    pub badge: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Approval {
+    pub id: String,
+    /// Send with the request the approval is for
+    pub token: String,
+    pub action: OverrideAction,
+    pub subject: String,
+    pub approved_by: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetCredentialsRequest {
+    /// 4 to 8 digits; an empty string removes it
+    pub pin: Option<String>,
+    /// The badge's scanned value; an empty string removes it
+    pub badge: Option<String>,
+}
+
+/// A sale line as the register sent it
+#[derive(Debug, Clone)]
+pub struct SaleLine {
+    pub product_id: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub discount: f64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ApprovalRecord {
+    id: String,
+    tenant_id: String,
+    station_id: String,
+    action: String,
+    subject: String,
+    requested_by: String,
+    expires_at: String,
+    used_at: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct Approver {
+    id: String,
+    role: String,
+    store_id: Option<String>,
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct OverrideService {
+    pool: SqlitePool,
+    /// Signs approval tokens and keys PIN and badge hashes
+    secret: String,
+}
+
+impl OverrideService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool, secret: String) -> Self {
+        Self { pool, secret }
+    }
+
+    /// A manager approves an action for the signed-in user at their station
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no station, the station's PIN pad is
+    /// locked, the PIN or badge is not an active user's, the approver is the
+    /// requester, works at another store or lacks the action's permission,
+    /// or the database write fails.
+    pub async fn approve(&self, context: &UserContext, request: &ApproveRequest) -> Result<Approval, OverrideError> {
+        let station_id = context
+            .station_id
+            .as_deref()
+            .ok_or_else(|| OverrideError::Validation("Approvals are given at a station".to_string()))?;
+        let subject = request.subject.trim();
+        if subject.is_empty() {
+            return Err(OverrideError::Validation("Subject is required".to_string()));
+        }
+
+        let Credential { method, column, hash } =
+            credential(&self.secret, &context.tenant_id, request.pin.as_deref(), request.badge.as_deref())
+                .ok_or_else(|| OverrideError::Validation("A PIN or badge is required".to_string()))?;
+
+        let mut conn = self.pool.acquire().await?;
+        let attempts = station_login_service::check_lockout(&mut conn, &context.tenant_id, station_id, Utc::now()).await?;
+        let approver = sqlx::query_as::<_, Approver>(&format!(
+            "SELECT id, role, store_id FROM users WHERE tenant_id = ? AND {column} = ? AND is_active = 1"
+        ))
+        .bind(&context.tenant_id)
+        .bind(&hash)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some(approver) = approver else {
+            tracing::warn!(
+                tenant_id = %context.tenant_id,
+                station_id = %station_id,
+                requested_by = %context.user_id,
+                "Override approval with an unknown {method}"
+            );
+            let failure =
+                station_login_service::record_failure(&mut conn, &context.tenant_id, station_id, attempts, Utc::now())
+                    .await?;
+            return Err(match failure {
+                StationLoginError::LockedOut(until) => OverrideError::LockedOut(until),
+                _ => OverrideError::Forbidden(format!("The {method} was not recognized")),
+            });
+        };
+        drop(conn);
+
+        if approver.id == context.user_id {
+            return Err(OverrideError::Forbidden("You cannot approve your own request".to_string()));
+        }
+        if approver.store_id.is_some() && approver.store_id != context.store_id {
+            return Err(OverrideError::Forbidden("The approver does not work at this store".to_string()));
+        }
+        let permissions = role_service::resolve_permissions(
+            &self.pool,
+            &context.tenant_id,
+            &approver.id,
+            &approver.role,
+            context.store_id.as_deref(),
+        )
+        .await?;
+        let permission = request.action.permission();
+        if !permissions.iter().any(|p| p == permission) {
+            return Err(OverrideError::Forbidden(format!("The approver does not have the {permission} permission")));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let expires_at = (now + Duration::seconds(APPROVAL_TTL_SECONDS)).to_rfc3339();
+        let reason = non_empty(request.reason.as_deref());
+
+        let mut tx = self.pool.begin().await?;
+        station_login_service::clear_failures(&mut tx, &context.tenant_id, station_id).await?;
+        sqlx::query(
+            "INSERT INTO override_approvals (
+                id, tenant_id, store_id, station_id, action, subject, requested_by, approved_by,
+                approval_method, reason, expires_at, created_at
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(&context.tenant_id)
+        .bind(&context.store_id)
+        .bind(station_id)
+        .bind(request.action.as_str())
+        .bind(subject)
+        .bind(&context.user_id)
+        .bind(&approver.id)
+        .bind(method)
+        .bind(reason)
+        .bind(&expires_at)
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO audit_log (
+                id, entity_type, entity_id, operation, user_id, employee_id, changes,
+                is_offline, created_at, store_id, tenant_id
+             ) VALUES (?, 'override_approval', ?, 'approve', ?, ?, ?, 0, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&id)
+        .bind(&context.user_id)
+        .bind(&approver.id)
+        .bind(
+            serde_json::json!({
+                "action": request.action.as_str(),
+                "subject": subject,
+                "requested_by": context.user_id,
+                "approved_by": approver.id,
+                "approval_method": method,
+                "station_id": station_id,
+                "reason": reason,
+            })
+            .to_string(),
+        )
+        .bind(now.to_rfc3339())
+        .bind(context.store_id.as_deref().unwrap_or_default())
+        .bind(&context.tenant_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            tenant_id = %context.tenant_id,
+            station_id = %station_id,
+            action = request.action.as_str(),
+            subject = %subject,
+            requested_by = %context.user_id,
+            approved_by = %approver.id,
+            "Override approved"
+        );
+
+        let token = self.sign(&ApprovalRecord {
+            id: id.clone(),
+            tenant_id: context.tenant_id.clone(),
+            station_id: station_id.to_string(),
+            action: request.action.as_str().to_string(),
+            subject: subject.to_string(),
+            requested_by: context.user_id.clone(),
+            expires_at: expires_at.clone(),
+            used_at: None,
+        });
+        Ok(Approval {
+            token: format!("{id}.{token}"),
+            id,
+            action: request.action,
+            subject: subject.to_string(),
+            approved_by: approver.id,
+            expires_at,
+        })
+    }
+
+    /// Check that every required action the user may not do themselves has
+    /// an approval among `tokens`, and use those approvals
+    ///
+    /// Returns the IDs of the approvals used; hand them to [`Self::release`]
+    /// if the action then fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApprovalRequired` with the actions still needing approval, a
+    /// validation error if a token is malformed or forged, or an error if
+    /// the database write fails.
+    pub async fn authorize(
+        &self,
+        context: &UserContext,
+        required: &[RequiredOverride],
+        tokens: &[String],
+    ) -> Result<Vec<String>, OverrideError> {
+        let pending: Vec<&RequiredOverride> = required
+            .iter()
+            .filter(|r| !context.has_permission(r.action.permission()))
+            .collect();
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut approvals = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            approvals.push(self.verify(&context.tenant_id, token).await?);
+        }
+
+        let now = Utc::now();
+        let mut matched: Vec<String> = Vec::new();
+        let mut missing = Vec::new();
+        for required in pending {
+            let approval = approvals.iter().find(|approval| {
+                !matched.contains(&approval.id)
+                    && approval.used_at.is_none()
+                    && approval.action == required.action.as_str()
+                    && approval.subject == required.subject
+                    && approval.requested_by == context.user_id
+                    && Some(approval.station_id.as_str()) == context.station_id.as_deref()
+                    && DateTime::parse_from_rfc3339(&approval.expires_at).is_ok_and(|expires| expires > now)
+            });
+            match approval {
+                Some(approval) => matched.push(approval.id.clone()),
+                None => missing.push(required.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(OverrideError::ApprovalRequired(missing));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for id in &matched {
+            let used = sqlx::query("UPDATE override_approvals SET used_at = ? WHERE id = ? AND used_at IS NULL")
+                .bind(now.to_rfc3339())
+                .bind(id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if used == 0 {
+                return Err(OverrideError::Conflict("The approval has already been used".to_string()));
+            }
+        }
+        tx.commit().await?;
+
+        tracing::info!(
+            tenant_id = %context.tenant_id,
+            user_id = %context.user_id,
+            approvals = ?matched,
+            "Override approvals used"
+        );
+        Ok(matched)
+    }
+
+    /// Make approvals used by an action that then failed usable again
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database write fails.
+    pub async fn release(&self, ids: &[String]) -> Result<(), OverrideError> {
+        for id in ids {
+            sqlx::query("UPDATE override_approvals SET used_at = NULL WHERE id = ?")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// The approvals a sale needs: lines priced differently than the catalog
+    /// or the customer's price level, and a total discount over the store's
+    /// threshold
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn sale_overrides(
+        &self,
+        tenant_id: &str,
+        store_id: Option<&str>,
+        customer_id: Option<&str>,
+        lines: &[SaleLine],
+        cart_discount: f64,
+    ) -> Result<Vec<RequiredOverride>, OverrideError> {
+        let mut conn = self.pool.acquire().await?;
+        let mut required = Vec::new();
+
+        for line in lines {
+            let prices: Vec<f64> = sqlx::query_scalar(
+                r"
+                SELECT unit_price FROM products WHERE id = ? AND tenant_id = ?
+                UNION ALL
+                SELECT pl.price FROM price_levels pl
+                JOIN customers c ON c.pricing_tier = pl.pricing_tier AND c.tenant_id = pl.tenant_id
+                WHERE pl.product_id = ? AND pl.tenant_id = ? AND c.id = ?
+                ",
+            )
+            .bind(&line.product_id)
+            .bind(tenant_id)
+            .bind(&line.product_id)
+            .bind(tenant_id)
+            .bind(customer_id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+            // Unknown products are turned away by checkout
+            if !prices.is_empty() && !prices.iter().any(|price| (price - line.unit_price).abs() < MONEY_EPSILON) {
+                let already = RequiredOverride::price_override(&line.product_id, line.unit_price);
+                if !required.contains(&already) {
+                    required.push(already);
+                }
+            }
+        }
+
+        let gross: f64 = lines.iter().map(|line| line.quantity * line.unit_price).sum();
+        let discount = lines.iter().map(|line| line.discount).sum::<f64>() + cart_discount;
+        let threshold = discount_threshold(&mut conn, tenant_id, store_id).await?;
+        if discount_exceeds_threshold(gross, discount, threshold) {
+            required.push(RequiredOverride::discount(discount));
+        }
+
+        Ok(required)
+    }
+
+    /// Set or remove a user's PIN and badge
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user does not exist, the PIN is not 4 to 8
+    /// digits or is easy to guess, the PIN or badge cannot be used, or the
+    /// database write fails. A PIN or badge another user has is refused
+    /// like an easy one, without saying that it is in use.
+    pub async fn set_credentials(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        request: &SetCredentialsRequest,
+    ) -> Result<(), OverrideError> {
+        let mut tx = self.pool.begin().await?;
+        let exists: Option<String> = sqlx::query_scalar("SELECT id FROM users WHERE id = ? AND tenant_id = ?")
+            .bind(user_id)
+            .bind(tenant_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if exists.is_none() {
+            return Err(OverrideError::NotFound(format!("User not found: {user_id}")));
+        }
+
+        if let Some(pin) = request.pin.as_deref().map(str::trim) {
+            let hash = if pin.is_empty() {
+                None
+            } else {
+                validate_pin(pin)?;
+                Some(credential_hash(&self.secret, "pin", tenant_id, pin))
+            };
+            set_credential(&mut tx, tenant_id, user_id, "pin_hash", hash.as_deref(), PIN_REFUSED).await?;
+        }
+        if let Some(badge) = request.badge.as_deref().map(str::trim) {
+            let hash = non_empty(Some(badge)).map(|badge| credential_hash(&self.secret, "badge", tenant_id, badge));
+            set_credential(&mut tx, tenant_id, user_id, "badge_hash", hash.as_deref(), BADGE_REFUSED).await?;
+        }
+        tx.commit().await?;
+
+        tracing::info!(tenant_id = %tenant_id, user_id = %user_id, "Override credentials changed");
+        Ok(())
+    }
+
+    fn sign(&self, approval: &ApprovalRecord) -> String {
+        let mut mac = self.mac();
+        mac.update(signed_fields(approval).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// The approval a token names, if the token's signature is good
+    async fn verify(&self, tenant_id: &str, token: &str) -> Result<ApprovalRecord, OverrideError> {
+        let invalid = || OverrideError::Validation("The approval token is not valid".to_string());
+        let (id, signature) = token.trim().split_once('.').ok_or_else(invalid)?;
+        let signature = hex::decode(signature).map_err(|_| invalid())?;
+
+        let approval = sqlx::query_as::<_, ApprovalRecord>(
+            "SELECT id, tenant_id, station_id, action, subject, requested_by, expires_at, used_at
+             FROM override_approvals WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(invalid)?;
+
+        let mut mac = self.mac();
+        mac.update(signed_fields(&approval).as_bytes());
+        mac.verify_slice(&signature).map_err(|_| invalid())?;
+        Ok(approval)
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(self.secret.as_bytes()).expect("HMAC accepts keys of any length")
+    }
+}
+
+// ============================================================================
+// Credentials
+// ============================================================================
+
+/// A PIN or badge a user entered at the register
+#[derive(Debug, Clone)]
+pub struct Credential {
+    /// pin or badge
+    pub method: &'static str,
+    /// The users column holding its hash
+    pub column: &'static str,
+    pub hash: String,
+}
+
+/// The PIN if one was entered, else the badge
+#[must_use]
+pub fn credential(secret: &str, tenant_id: &str, pin: Option<&str>, badge: Option<&str>) -> Option<Credential> {
+    match (non_empty(pin), non_empty(badge)) {
+        (Some(pin), _) => Some(Credential {
+            method: "pin",
+            column: "pin_hash",
+            hash: credential_hash(secret, "pin", tenant_id, pin),
+        }),
+        (None, Some(badge)) => Some(Credential {
+            method: "badge",
+            column: "badge_hash",
+            hash: credential_hash(secret, "badge", tenant_id, badge),
+        }),
+        (None, None) => None,
+    }
+}
+
+/// Keyed hash of a PIN or badge, so it can be looked up and kept unique
+#[must_use]
+pub fn credential_hash(secret: &str, kind: &str, tenant_id: &str, value: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{kind}:{tenant_id}:{value}").as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn signed_fields(approval: &ApprovalRecord) -> String {
+    [
+        approval.id.as_str(),
+        &approval.tenant_id,
+        &approval.station_id,
+        &approval.action,
+        &approval.subject,
+        &approval.requested_by,
+        &approval.expires_at,
+    ]
+    .join("|")
+}
+
+/// The store's threshold, else the tenant's, else the default
+async fn discount_threshold(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    store_id: Option<&str>,
+) -> Result<f64, sqlx::Error> {
+    let value: Option<String> = sqlx::query_scalar(
+        "SELECT value FROM settings
+         WHERE key = ? AND ((scope = 'store' AND scope_id = ?) OR (scope = 'tenant' AND scope_id = ?) OR scope = 'global')
+         ORDER BY CASE scope WHEN 'store' THEN 1 WHEN 'tenant' THEN 2 ELSE 3 END
+         LIMIT 1",
+    )
+    .bind(SETTING_DISCOUNT_THRESHOLD)
+    .bind(store_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(value
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|threshold| *threshold >= 0.0)
+        .unwrap_or(DEFAULT_DISCOUNT_THRESHOLD_PERCENT))
+}
+
+fn discount_exceeds_threshold(gross: f64, discount: f64, threshold_percent: f64) -> bool {
+    if discount < MONEY_EPSILON {
+        return false;
+    }
+    if gross < MONEY_EPSILON {
+        return true;
+    }
+    discount / gross * 100.0 > threshold_percent + 1e-9
+}
+
+async fn set_credential(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    user_id: &str,
+    column: &str,
+    hash: Option<&str>,
+    refused: &str,
+) -> Result<(), OverrideError> {
+    if let Some(hash) = hash {
+        let taken: Option<String> = sqlx::query_scalar(&format!(
+            "SELECT id FROM users WHERE tenant_id = ? AND {column} = ? AND id != ?"
+        ))
+        .bind(tenant_id)
+        .bind(hash)
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+        if taken.is_some() {
+            return Err(OverrideError::Validation(refused.to_string()));
+        }
+    }
+
+    sqlx::query(&format!("UPDATE users SET {column} = ? WHERE id = ? AND tenant_id = ?"))
+        .bind(hash)
+        .bind(user_id)
+        .bind(tenant_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+fn validate_pin(pin: &str) -> Result<(), OverrideError> {
+    if !(4..=8).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(OverrideError::Validation("A PIN is 4 to 8 digits".to_string()));
+    }
+    if pin_is_guessable(pin) {
+        return Err(OverrideError::Validation(PIN_REFUSED.to_string()));
+    }
+    Ok(())
+}
+
+/// One digit repeated, or a run up or down (`1111`, `1234`, `9876`)
+fn pin_is_guessable(pin: &str) -> bool {
+    let digits: Vec<i8> = pin.bytes().map(|b| (b - b'0') as i8).collect();
+    let steps: Vec<i8> = digits.windows(2).map(|pair| (pair[1] - pair[0] + 10) % 10).collect();
+    steps.iter().all(|step| *step == steps[0]) && matches!(steps[0], 0 | 1 | 9)
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE tenants (id TEXT PRIMARY KEY)",
+            "CREATE TABLE users (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                username TEXT NOT NULL,
+                role TEXT NOT NULL,
+                store_id TEXT,
+                is_active INTEGER NOT NULL DEFAULT 1
+            )",
+            "CREATE TABLE products (id TEXT PRIMARY KEY, tenant_id TEXT NOT NULL, unit_price REAL NOT NULL)",
+            "CREATE TABLE customers (id TEXT PRIMARY KEY, tenant_id TEXT NOT NULL, pricing_tier TEXT NOT NULL)",
+            "CREATE TABLE price_levels (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                pricing_tier TEXT NOT NULL,
+                price REAL NOT NULL
+            )",
+            "CREATE TABLE settings (key TEXT NOT NULL, value TEXT NOT NULL, scope TEXT NOT NULL, scope_id TEXT)",
+            "CREATE TABLE audit_log (
+                id TEXT PRIMARY KEY,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                user_id TEXT,
+                employee_id TEXT,
+                changes TEXT,
+                is_offline BOOLEAN NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                tenant_id TEXT NOT NULL
+            )",
+            "INSERT INTO tenants (id) VALUES ('t1')",
+            "INSERT INTO users (id, tenant_id, username, role, store_id) VALUES
+                ('cashier1', 't1', 'carol', 'cashier', 's1'),
+                ('manager1', 't1', 'mia', 'manager', 's1'),
+                ('manager2', 't1', 'max', 'manager', 's2')",
+            "INSERT INTO products (id, tenant_id, unit_price) VALUES ('p1', 't1', 20.0)",
+            "INSERT INTO customers (id, tenant_id, pricing_tier) VALUES ('c1', 't1', 'Wholesale')",
+            "INSERT INTO price_levels (id, tenant_id, product_id, pricing_tier, price) VALUES
+                ('pl1', 't1', 'p1', 'Wholesale', 16.0)",
+            "INSERT INTO settings (key, value, scope, scope_id) VALUES
+                ('pos.discount_approval_threshold_percent', '25', 'store', 's2')",
+            include_str!("../../../../migrations/073_roles_and_permissions.sql"),
+            include_str!("../../../../migrations/074_manager_overrides.sql"),
+            "CREATE TABLE sessions (id TEXT PRIMARY KEY)",
+            include_str!("../../../../migrations/075_station_pin_login.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    fn cashier() -> UserContext {
+        UserContext {
+            user_id: "cashier1".to_string(),
+            username: "carol".to_string(),
+            role: "cashier".to_string(),
+            tenant_id: "t1".to_string(),
+            store_id: Some("s1".to_string()),
+            station_id: Some("reg1".to_string()),
+            permissions: vec!["access_sell".to_string(), "process_return".to_string()],
+        }
+    }
+
+    fn line(product_id: &str, quantity: f64, unit_price: f64, discount: f64) -> SaleLine {
+        SaleLine { product_id: product_id.to_string(), quantity, unit_price, discount }
+    }
+
+    #[test]
+    fn test_discount_threshold() {
+        assert!(!discount_exceeds_threshold(100.0, 0.0, 10.0));
+        assert!(!discount_exceeds_threshold(100.0, 10.0, 10.0));
+        assert!(discount_exceeds_threshold(100.0, 10.01, 10.0));
+        assert!(discount_exceeds_threshold(0.0, 1.0, 10.0));
+    }
+
+    #[tokio::test]
+    async fn test_sale_overrides() {
+        let service = OverrideService::new(setup_test_db().await, "secret".to_string());
+
+        let catalog = [line("p1", 2.0, 20.0, 0.0), line("unknown", 1.0, 5.0, 0.0)];
+        assert!(service.sale_overrides("t1", Some("s1"), None, &catalog, 4.0).await.unwrap().is_empty());
+
+        // The customer's price level is not an override; 16 is 20% off at s1
+        let tiered = [line("p1", 1.0, 16.0, 0.0)];
+        assert!(service.sale_overrides("t1", Some("s1"), Some("c1"), &tiered, 0.0).await.unwrap().is_empty());
+        assert_eq!(
+            service.sale_overrides("t1", Some("s1"), None, &tiered, 0.0).await.unwrap(),
+            vec![RequiredOverride::price_override("p1", 16.0)]
+        );
+
+        let discounted = [line("p1", 1.0, 20.0, 3.0)];
+        assert_eq!(
+            service.sale_overrides("t1", Some("s1"), None, &discounted, 1.0).await.unwrap(),
+            vec![RequiredOverride::discount(4.0)]
+        );
+        // s2 allows up to 25%
+        assert!(service.sale_overrides("t1", Some("s2"), None, &discounted, 1.0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_manager_approves_at_the_station() {
+        let pool = setup_test_db().await;
+        let service = OverrideService::new(pool.clone(), "secret".to_string());
+        let pin = |pin: &str| SetCredentialsRequest { pin: Some(pin.to_string()), badge: None };
+        service.set_credentials("t1", "manager1", &pin("4815")).await.unwrap();
+        service.set_credentials("t1", "manager2", &pin("9162")).await.unwrap();
+        service.set_credentials("t1", "cashier1", &pin("1593")).await.unwrap();
+        // Another user's PIN is refused like an easy one
+        let taken = service.set_credentials("t1", "cashier1", &pin("4815")).await;
+        let easy = service.set_credentials("t1", "cashier1", &pin("3210")).await;
+        match (taken, easy) {
+            (Err(OverrideError::Validation(taken)), Err(OverrideError::Validation(easy))) => assert_eq!(taken, easy),
+            other => panic!("expected both PINs to be refused, got {other:?}"),
+        }
+        for refused in ["12a4", "7777", "8901"] {
+            assert!(matches!(
+                service.set_credentials("t1", "cashier1", &pin(refused)).await,
+                Err(OverrideError::Validation(_))
+            ));
+        }
+
+        let context = cashier();
+        let void = [RequiredOverride::void_sale("sale1")];
+        let request = |pin: &str| ApproveRequest {
+            action: OverrideAction::VoidSale,
+            subject: "sale1".to_string(),
+            pin: Some(pin.to_string()),
+            badge: None,
+            reason: Some("Rang up twice".to_string()),
+        };
+
+        match service.authorize(&context, &void, &[]).await {
+            Err(OverrideError::ApprovalRequired(missing)) => assert_eq!(missing, void),
+            other => panic!("expected approval to be required, got {other:?}"),
+        }
+        // Wrong PIN, the requester themselves, a manager of another store
+        for pin in ["0000", "1593", "9162"] {
+            assert!(matches!(service.approve(&context, &request(pin)).await, Err(OverrideError::Forbidden(_))));
+        }
+
+        let approval = service.approve(&context, &request("4815")).await.unwrap();
+        assert_eq!(approval.approved_by, "manager1");
+        let (requested_by, approved_by): (String, String) =
+            sqlx::query_as("SELECT user_id, employee_id FROM audit_log WHERE entity_id = ?")
+                .bind(&approval.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((requested_by.as_str(), approved_by.as_str()), ("cashier1", "manager1"));
+
+        // Tied to its subject and station, and not to be tampered with
+        let other_sale = [RequiredOverride::void_sale("sale2")];
+        let token = vec![approval.token.clone()];
+        assert!(matches!(
+            service.authorize(&context, &other_sale, &token).await,
+            Err(OverrideError::ApprovalRequired(_))
+        ));
+        let elsewhere = UserContext { station_id: Some("reg2".to_string()), ..cashier() };
+        assert!(matches!(
+            service.authorize(&elsewhere, &void, &token).await,
+            Err(OverrideError::ApprovalRequired(_))
+        ));
+        let forged = vec![format!("{}.{}", approval.id, "00".repeat(32))];
+        assert!(matches!(service.authorize(&context, &void, &forged).await, Err(OverrideError::Validation(_))));
+
+        // Used once; released when the void fails
+        let used = service.authorize(&context, &void, &token).await.unwrap();
+        assert_eq!(used, vec![approval.id.clone()]);
+        assert!(matches!(
+            service.authorize(&context, &void, &token).await,
+            Err(OverrideError::ApprovalRequired(_))
+        ));
+        service.release(&used).await.unwrap();
+        assert_eq!(service.authorize(&context, &void, &token).await.unwrap(), used);
+
+        // A manager needs no approval
+        let manager = UserContext {
+            user_id: "manager1".to_string(),
+            permissions: vec!["void_sale".to_string()],
+            ..cashier()
+        };
+        assert!(service.authorize(&manager, &other_sale, &[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_pins_lock_the_station() {
+        let service = OverrideService::new(setup_test_db().await, "secret".to_string());
+        let pin = SetCredentialsRequest { pin: Some("4815".to_string()), badge: None };
+        service.set_credentials("t1", "manager1", &pin).await.unwrap();
+        let request = |pin: &str| ApproveRequest {
+            action: OverrideAction::VoidSale,
+            subject: "sale1".to_string(),
+            pin: Some(pin.to_string()),
+            badge: None,
+            reason: None,
+        };
+
+        let context = cashier();
+        for _ in 1..station_login_service::MAX_FAILED_ATTEMPTS {
+            assert!(matches!(service.approve(&context, &request("0000")).await, Err(OverrideError::Forbidden(_))));
+        }
+        assert!(matches!(service.approve(&context, &request("0000")).await, Err(OverrideError::LockedOut(_))));
+        // Even the right PIN, until the lock expires; other stations are unaffected
+        assert!(matches!(service.approve(&context, &request("4815")).await, Err(OverrideError::LockedOut(_))));
+        let elsewhere = UserContext { station_id: Some("reg2".to_string()), ..cashier() };
+        assert_eq!(service.approve(&elsewhere, &request("4815")).await.unwrap().approved_by, "manager1");
+    }
+}
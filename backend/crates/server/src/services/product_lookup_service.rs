@@ -232,6 +232,7 @@ impl ProductRow {
             updated_at: self.updated_at,
             profit_margin,
             profit_amount,
+            fitment: Vec::new(),
         }
     }
 }
@@ -295,6 +296,7 @@ impl ProductRowWithAlt {
             updated_at: self.updated_at,
             profit_margin,
             profit_amount,
+            fitment: Vec::new(),
         }
     }
 }
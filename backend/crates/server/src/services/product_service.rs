@@ -4,6 +4,11 @@ use crate::models::{
     UpdateProductRequest, ValidationError,
 };
 use crate::services::attribute_validator::AttributeValidator;
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_OPENING_BALANCE, REASON_PRODUCT_EDIT,
+    SOURCE_PRODUCT,
+};
+use crate::services::outbound_webhook_service::{self, WebhookEventType};
 use chrono::Utc;
 use serde_json::json;
 use sqlx::SqlitePool;
@@ -127,6 +132,12 @@ impl ProductService {
             result.rows_affected()
         );
 
+        // Opening stock goes through the inventory ledger
+        if let Some(quantity) = req.quantity_on_hand {
+            self.set_stock_level(&product_id, tenant_id, user_id, quantity, REASON_OPENING_BALANCE)
+                .await?;
+        }
+
         // Update search index
         self.update_search_index(&product_id, &req.name, &req.category, tenant_id, &attributes_str)
             .await?;
@@ -280,10 +291,6 @@ impl ProductService {
             updates.push(format!("cost = ?{}", bind_count));
             bind_count += 1;
         }
-        if req.quantity_on_hand.is_some() {
-            updates.push(format!("quantity_on_hand = ?{}", bind_count));
-            bind_count += 1;
-        }
         if req.reorder_point.is_some() {
             updates.push(format!("reorder_point = ?{}", bind_count));
             bind_count += 1;
@@ -309,7 +316,8 @@ impl ProductService {
             bind_count += 1;
         }
 
-        if updates.is_empty() {
+        // Stock is moved through the inventory ledger below, not set here
+        if updates.is_empty() && req.quantity_on_hand.is_none() {
             // No changes, return existing product
             return Ok(ProductResponse::from(existing));
         }
@@ -349,9 +357,6 @@ impl ProductService {
         if let Some(cost) = req.cost {
             query = query.bind(cost);
         }
-        if let Some(quantity) = req.quantity_on_hand {
-            query = query.bind(quantity);
-        }
         if let Some(reorder) = req.reorder_point {
             query = query.bind(reorder);
         }
@@ -382,6 +387,11 @@ impl ProductService {
             }]
         })?;
 
+        if let Some(quantity) = req.quantity_on_hand {
+            self.set_stock_level(product_id, tenant_id, user_id, quantity, REASON_PRODUCT_EDIT)
+                .await?;
+        }
+
         tracing::info!("Product updated: {} by user {}", product_id, user_id);
 
         // Update search index if name or category changed
@@ -413,7 +423,10 @@ impl ProductService {
         self.queue_sync(tenant_id, product_id, "update").await?;
 
         // Fetch and return updated product
-        self.get_product(product_id, tenant_id).await
+        let product = self.get_product(product_id, tenant_id).await?;
+        self.publish_product_updated(tenant_id, &product).await?;
+
+        Ok(product)
     }
 
     /// Delete a product (soft delete)
@@ -722,6 +735,33 @@ impl ProductService {
         Ok(())
     }
 
+    /// Bring the product's stock to `quantity` with an inventory ledger movement
+    async fn set_stock_level(
+        &self,
+        product_id: &str,
+        tenant_id: &str,
+        user_id: &str,
+        quantity: f64,
+        reason_code: &str,
+    ) -> Result<(), Vec<ValidationError>> {
+        let movement = StockMovement::new(tenant_id, product_id, MovementType::Adjustment, 0.0, reason_code)
+            .with_source(SOURCE_PRODUCT, product_id)
+            .by_user(user_id);
+
+        let result: Result<_, LedgerError> = async {
+            let mut conn = self.pool.acquire().await?;
+            inventory_ledger_service::set_on_hand(&mut conn, movement, quantity).await
+        }
+        .await;
+
+        result.map(|_| ()).map_err(|e| {
+            vec![ValidationError {
+                field: "quantity_on_hand".to_string(),
+                message: format!("Failed to record stock movement: {}", e), code: None
+            }]
+        })
+    }
+
     /// Log to audit log
     async fn log_audit(
         &self,
@@ -790,4 +830,30 @@ impl ProductService {
 
         Ok(())
     }
+
+    /// Publish the `product.updated` webhook event with the product as saved
+    async fn publish_product_updated(
+        &self,
+        tenant_id: &str,
+        product: &ProductResponse,
+    ) -> Result<(), Vec<ValidationError>> {
+        let to_error = |e: sqlx::Error| {
+            vec![ValidationError {
+                field: "webhooks".to_string(),
+                message: format!("Failed to publish product update: {}", e), code: None
+            }]
+        };
+
+        let mut conn = self.pool.acquire().await.map_err(to_error)?;
+        outbound_webhook_service::publish(
+            &mut conn,
+            tenant_id,
+            WebhookEventType::ProductUpdated,
+            json!(product),
+        )
+        .await
+        .map_err(to_error)?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,1518 @@
+/**
+ * Purchase Order Service
+ *
+ * Orders stock from vendors and checks their bills against it.
+ *
+ * Lifecycle of a purchase order (one vendor, one store):
+ * - create: a draft, typed in or raised from the reorder suggestions
+ * - submit: sent to the vendor
+ * - receive: goods are received against PO lines in as many receipts as it
+ *   takes; each receipt goes into the store's stock through the inventory
+ *   ledger and the PO is partially received until every line is in full
+ * - cancel: before anything arrives; close: stop waiting for the rest
+ *
+ * Reorder suggestions list the products of a store at or below their reorder
+ * point, topped up to twice the reorder point less what is already on order,
+ * grouped by the product's preferred vendor (its highest-priority vendor SKU
+ * alias).
+ *
+ * Three-way match: a vendor bill for a PO is compared with the PO (price)
+ * and its receipts (quantity); billed items that were never ordered are
+ * flagged too. Variances open a review case in the review queue, and the
+ * bill cannot be posted until that case is approved. Goods on a matched bill
+ * were already received against the PO, so posting the bill books its cost
+ * without receiving the stock again.
+ */
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_PO_RECEIPT, SOURCE_PURCHASE_ORDER,
+};
+
+/// Setting holding how far a billed price may be off the PO price, in percent
+pub const SETTING_PRICE_VARIANCE_PERCENT: &str = "purchasing.price_variance_percent";
+
+/// Billed prices within 2% of the PO price match unless configured otherwise
+pub const DEFAULT_PRICE_VARIANCE_PERCENT: f64 = 2.0;
+
+/// `purchase_order_matches.status` of a bill that agrees with its PO
+pub const MATCH_STATUS_MATCHED: &str = "matched";
+/// `purchase_order_matches.status` of a bill with variances
+pub const MATCH_STATUS_VARIANCE: &str = "variance";
+
+/// Quantities closer than this are the same
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum PurchaseOrderError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Purchase order not found: {0}")]
+    NotFound(String),
+
+    #[error("Vendor bill not found: {0}")]
+    BillNotFound(String),
+
+    #[error("Invalid purchase order state: {0}")]
+    InvalidState(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<LedgerError> for PurchaseOrderError {
+    fn from(err: LedgerError) -> Self {
+        match err {
+            LedgerError::Database(e) => Self::Database(e),
+            other => Self::Validation(other.to_string()),
+        }
+    }
+}
+
+impl From<PurchaseOrderError> for ApiError {
+    fn from(err: PurchaseOrderError) -> Self {
+        match err {
+            PurchaseOrderError::Validation(msg) => Self::bad_request(msg),
+            PurchaseOrderError::NotFound(id) => Self::not_found(format!("Purchase order not found: {id}")),
+            PurchaseOrderError::BillNotFound(id) => Self::not_found(format!("Vendor bill not found: {id}")),
+            PurchaseOrderError::InvalidState(msg) => Self::conflict(msg),
+            PurchaseOrderError::Database(e) => {
+                Self::internal(format!("Failed to access purchase orders: {e}"))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurchaseOrderStatus {
+    Draft,
+    Ordered,
+    PartiallyReceived,
+    Received,
+    Cancelled,
+    Closed,
+}
+
+impl PurchaseOrderStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Ordered => "ordered",
+            Self::PartiallyReceived => "partially_received",
+            Self::Received => "received",
+            Self::Cancelled => "cancelled",
+            Self::Closed => "closed",
+        }
+    }
+}
+
+/// A product to order
+#[derive(Debug, Clone, Deserialize)]
+pub struct PurchaseOrderLineRequest {
+    pub product_id: String,
+    pub quantity: f64,
+    /// Defaults to the product's cost
+    pub unit_cost: Option<f64>,
+}
+
+/// A purchase order to create
+#[derive(Debug, Clone)]
+pub struct PurchaseOrderRequest {
+    pub tenant_id: String,
+    pub vendor_id: String,
+    pub store_id: String,
+    pub created_by: String,
+    pub expected_date: Option<String>,
+    pub notes: Option<String>,
+    pub lines: Vec<PurchaseOrderLineRequest>,
+}
+
+/// Quantity received on one PO line
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceiptLine {
+    pub line_id: String,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct PurchaseOrderLine {
+    pub id: String,
+    pub line_no: i64,
+    pub product_id: String,
+    pub sku: Option<String>,
+    pub product_name: Option<String>,
+    pub vendor_sku: Option<String>,
+    pub quantity_ordered: f64,
+    pub quantity_received: f64,
+    pub unit_cost: f64,
+}
+
+impl PurchaseOrderLine {
+    /// Still to come
+    #[must_use]
+    pub fn outstanding(&self) -> f64 {
+        (self.quantity_ordered - self.quantity_received).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PurchaseOrder {
+    pub id: String,
+    pub po_number: String,
+    pub vendor_id: String,
+    pub vendor_name: Option<String>,
+    pub store_id: String,
+    /// draft, ordered, partially_received, received, cancelled or closed
+    pub status: String,
+    /// Raised from the reorder suggestions
+    pub suggested: bool,
+    pub expected_date: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: String,
+    pub ordered_by: Option<String>,
+    pub ordered_at: Option<String>,
+    pub closed_by: Option<String>,
+    pub closed_at: Option<String>,
+    pub created_at: String,
+    #[sqlx(skip)]
+    pub lines: Vec<PurchaseOrderLine>,
+}
+
+impl PurchaseOrder {
+    /// Cost of the ordered quantities
+    #[must_use]
+    pub fn total_cost(&self) -> f64 {
+        self.lines.iter().map(|line| line.quantity_ordered * line.unit_cost).sum()
+    }
+}
+
+/// A product to reorder
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct SuggestedLine {
+    pub product_id: String,
+    pub sku: String,
+    pub name: String,
+    pub vendor_id: Option<String>,
+    pub vendor_sku: Option<String>,
+    pub on_hand: f64,
+    pub on_order: f64,
+    pub reorder_point: f64,
+    pub unit_cost: f64,
+    #[sqlx(skip)]
+    pub quantity: f64,
+}
+
+/// Products to reorder from one vendor; `vendor_id` is `None` for products
+/// without a vendor SKU alias
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReorderSuggestion {
+    pub vendor_id: Option<String>,
+    pub store_id: String,
+    pub lines: Vec<SuggestedLine>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VarianceKind {
+    /// Billed price off the PO price beyond the tolerance
+    Price,
+    /// Billed quantity differs from the quantity received
+    Quantity,
+    /// Billed item that is not on the PO
+    NotOrdered,
+}
+
+/// A disagreement between a bill, its PO and the receipts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Variance {
+    pub kind: VarianceKind,
+    pub bill_line_no: Option<i64>,
+    pub sku: String,
+    /// PO price or received quantity
+    pub expected: f64,
+    /// Billed price or quantity
+    pub billed: f64,
+    pub message: String,
+}
+
+/// Outcome of the three-way match of a vendor bill
+#[derive(Debug, Clone, Serialize)]
+pub struct BillMatch {
+    pub vendor_bill_id: String,
+    pub purchase_order_id: String,
+    pub po_number: String,
+    /// matched or variance
+    pub status: String,
+    pub variances: Vec<Variance>,
+    /// Review case holding the variances
+    pub review_case_id: Option<String>,
+    pub review_state: Option<String>,
+}
+
+impl BillMatch {
+    /// The bill agrees with its PO, or its variances were approved
+    #[must_use]
+    pub fn is_cleared(&self) -> bool {
+        self.status == MATCH_STATUS_MATCHED || self.review_state.as_deref() == Some("Approved")
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct BillHeader {
+    vendor_id: String,
+    po_number: Option<String>,
+    purchase_order_id: Option<String>,
+    file_path: String,
+    mime_type: String,
+    store_id: String,
+    invoice_no: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct BillLine {
+    line_no: i64,
+    matched_sku: String,
+    product_id: Option<String>,
+    normalized_qty: f64,
+    unit_price: f64,
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+const SELECT_PURCHASE_ORDER: &str = "SELECT po.id, po.po_number, po.vendor_id, v.name AS vendor_name,
+        po.store_id, po.status, po.suggested, po.expected_date, po.notes, po.created_by,
+        po.ordered_by, po.ordered_at, po.closed_by, po.closed_at, po.created_at
+     FROM purchase_orders po
+     LEFT JOIN vendors v ON v.id = po.vendor_id";
+
+pub struct PurchaseOrderService {
+    pool: SqlitePool,
+}
+
+impl PurchaseOrderService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Create a draft purchase order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the vendor, store or a product is unknown, a
+    /// product is listed twice, a quantity is not positive, or the database
+    /// write fails.
+    pub async fn create(&self, request: PurchaseOrderRequest) -> Result<PurchaseOrder, PurchaseOrderError> {
+        let mut tx = self.pool.begin().await?;
+        let id = insert_purchase_order(&mut tx, &request, false).await?;
+        tx.commit().await?;
+
+        self.get(&request.tenant_id, &id).await
+    }
+
+    /// Send a draft to the vendor
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is unknown or not a draft.
+    pub async fn submit(&self, tenant_id: &str, id: &str, user_id: &str) -> Result<PurchaseOrder, PurchaseOrderError> {
+        let now = Utc::now().to_rfc3339();
+
+        let submitted = sqlx::query(
+            "UPDATE purchase_orders SET status = ?, ordered_by = ?, ordered_at = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ? AND status = ?",
+        )
+        .bind(PurchaseOrderStatus::Ordered.as_str())
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(PurchaseOrderStatus::Draft.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        let order = self.get(tenant_id, id).await?;
+        if submitted.rows_affected() == 0 {
+            return Err(PurchaseOrderError::InvalidState(format!(
+                "Only a draft purchase order can be submitted; this one is {}",
+                order.status
+            )));
+        }
+
+        tracing::info!(purchase_order_id = %id, po_number = %order.po_number, "Purchase order submitted");
+
+        Ok(order)
+    }
+
+    /// Receive goods against PO lines into the PO's store
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is unknown or not awaiting goods, a line
+    /// is unknown or listed twice, a quantity is not positive, or the
+    /// database write fails.
+    pub async fn receive(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        user_id: &str,
+        lines: &[ReceiptLine],
+        notes: Option<String>,
+    ) -> Result<PurchaseOrder, PurchaseOrderError> {
+        let order = self.get(tenant_id, id).await?;
+        if order.status != PurchaseOrderStatus::Ordered.as_str()
+            && order.status != PurchaseOrderStatus::PartiallyReceived.as_str()
+        {
+            return Err(PurchaseOrderError::InvalidState(format!(
+                "Goods can only be received on an ordered purchase order; this one is {}",
+                order.status
+            )));
+        }
+        if lines.is_empty() {
+            return Err(PurchaseOrderError::Validation("Receipt must have at least one line".to_string()));
+        }
+        for (index, receipt) in lines.iter().enumerate() {
+            if receipt.quantity <= 0.0 {
+                return Err(PurchaseOrderError::Validation(format!(
+                    "Received quantity for line {} must be positive",
+                    receipt.line_id
+                )));
+            }
+            if lines[..index].iter().any(|other| other.line_id == receipt.line_id) {
+                return Err(PurchaseOrderError::Validation(format!(
+                    "Line {} is listed twice",
+                    receipt.line_id
+                )));
+            }
+            if !order.lines.iter().any(|line| line.id == receipt.line_id) {
+                return Err(PurchaseOrderError::Validation(format!(
+                    "Line {} is not on purchase order {}",
+                    receipt.line_id, order.po_number
+                )));
+            }
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        for receipt in lines {
+            let line = order
+                .lines
+                .iter()
+                .find(|line| line.id == receipt.line_id)
+                .ok_or_else(|| PurchaseOrderError::Validation(format!("Unknown line {}", receipt.line_id)))?;
+
+            sqlx::query(
+                r"
+                INSERT INTO purchase_order_receipts (
+                    id, tenant_id, purchase_order_id, purchase_order_line_id, quantity,
+                    received_by, received_at, notes
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                ",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(id)
+            .bind(&line.id)
+            .bind(receipt.quantity)
+            .bind(user_id)
+            .bind(&now)
+            .bind(&notes)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("UPDATE purchase_order_lines SET quantity_received = quantity_received + ? WHERE id = ?")
+                .bind(receipt.quantity)
+                .bind(&line.id)
+                .execute(&mut *tx)
+                .await?;
+
+            let movement = StockMovement::new(
+                tenant_id,
+                &line.product_id,
+                MovementType::Receipt,
+                receipt.quantity,
+                REASON_PO_RECEIPT,
+            )
+            .with_source(SOURCE_PURCHASE_ORDER, id)
+            .by_user(user_id)
+            .at_store(&order.store_id)
+            .with_notes(notes.clone())
+            .with_unit_cost(Some(line.unit_cost));
+            inventory_ledger_service::record_movement(&mut tx, &movement).await?;
+        }
+
+        let outstanding: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM purchase_order_lines
+             WHERE purchase_order_id = ? AND quantity_received < quantity_ordered",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let status = if outstanding == 0 {
+            PurchaseOrderStatus::Received
+        } else {
+            PurchaseOrderStatus::PartiallyReceived
+        };
+
+        sqlx::query("UPDATE purchase_orders SET status = ?, updated_at = ? WHERE id = ?")
+            .bind(status.as_str())
+            .bind(&now)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            purchase_order_id = %id,
+            po_number = %order.po_number,
+            lines = lines.len(),
+            status = status.as_str(),
+            "Goods received against purchase order"
+        );
+
+        self.get(tenant_id, id).await
+    }
+
+    /// Cancel an order nothing has been received on
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is unknown or goods have arrived.
+    pub async fn cancel(&self, tenant_id: &str, id: &str, user_id: &str) -> Result<PurchaseOrder, PurchaseOrderError> {
+        self.finish(tenant_id, id, user_id, PurchaseOrderStatus::Cancelled, &[
+            PurchaseOrderStatus::Draft,
+            PurchaseOrderStatus::Ordered,
+        ])
+        .await
+    }
+
+    /// Stop waiting for the rest of a partially received order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is unknown or not partially received.
+    pub async fn close(&self, tenant_id: &str, id: &str, user_id: &str) -> Result<PurchaseOrder, PurchaseOrderError> {
+        self.finish(tenant_id, id, user_id, PurchaseOrderStatus::Closed, &[
+            PurchaseOrderStatus::PartiallyReceived,
+        ])
+        .await
+    }
+
+    /// Purchase orders, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn list(
+        &self,
+        tenant_id: &str,
+        vendor_id: Option<&str>,
+        store_id: Option<&str>,
+        status: Option<PurchaseOrderStatus>,
+    ) -> Result<Vec<PurchaseOrder>, PurchaseOrderError> {
+        let status = status.map(PurchaseOrderStatus::as_str);
+
+        let mut orders = sqlx::query_as::<_, PurchaseOrder>(&format!(
+            "{SELECT_PURCHASE_ORDER}
+             WHERE po.tenant_id = ?
+               AND (? IS NULL OR po.vendor_id = ?)
+               AND (? IS NULL OR po.store_id = ?)
+               AND (? IS NULL OR po.status = ?)
+             ORDER BY po.created_at DESC"
+        ))
+        .bind(tenant_id)
+        .bind(vendor_id)
+        .bind(vendor_id)
+        .bind(store_id)
+        .bind(store_id)
+        .bind(status)
+        .bind(status)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for order in &mut orders {
+            order.lines = self.lines(&order.id).await?;
+        }
+
+        Ok(orders)
+    }
+
+    /// A purchase order with its lines
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotFound` if the tenant has no such purchase order.
+    pub async fn get(&self, tenant_id: &str, id: &str) -> Result<PurchaseOrder, PurchaseOrderError> {
+        let mut order = sqlx::query_as::<_, PurchaseOrder>(&format!(
+            "{SELECT_PURCHASE_ORDER} WHERE po.id = ? AND po.tenant_id = ?"
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| PurchaseOrderError::NotFound(id.to_string()))?;
+
+        order.lines = self.lines(id).await?;
+        Ok(order)
+    }
+
+    // ========================================================================
+    // Reorder suggestions
+    // ========================================================================
+
+    /// Products of a store at or below their reorder point, by vendor
+    ///
+    /// The store's own reorder point wins over the product's. Quantities are
+    /// topped up to twice the reorder point, less what is on open orders.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn suggestions(&self, tenant_id: &str, store_id: &str) -> Result<Vec<ReorderSuggestion>, PurchaseOrderError> {
+        let candidates = sqlx::query_as::<_, SuggestedLine>(
+            r"
+            SELECT
+                p.id AS product_id,
+                p.sku,
+                p.name,
+                (
+                    SELECT a.vendor_id FROM vendor_sku_aliases a
+                    WHERE a.internal_sku = p.sku AND a.tenant_id = p.tenant_id
+                    ORDER BY a.priority DESC, a.usage_count DESC LIMIT 1
+                ) AS vendor_id,
+                (
+                    SELECT a.vendor_sku_norm FROM vendor_sku_aliases a
+                    WHERE a.internal_sku = p.sku AND a.tenant_id = p.tenant_id
+                    ORDER BY a.priority DESC, a.usage_count DESC LIMIT 1
+                ) AS vendor_sku,
+                COALESCE(l.quantity_on_hand, CASE WHEN p.store_id = ? THEN p.quantity_on_hand ELSE 0.0 END) AS on_hand,
+                COALESCE((
+                    SELECT SUM(MAX(pl.quantity_ordered - pl.quantity_received, 0.0))
+                    FROM purchase_order_lines pl
+                    JOIN purchase_orders po ON po.id = pl.purchase_order_id
+                    WHERE pl.product_id = p.id AND po.store_id = ?
+                      AND po.status IN ('draft', 'ordered', 'partially_received')
+                ), 0.0) AS on_order,
+                COALESCE(l.reorder_point, p.reorder_point) AS reorder_point,
+                p.cost AS unit_cost
+            FROM products p
+            LEFT JOIN product_locations l ON l.product_id = p.id AND l.store_id = ?
+            WHERE p.tenant_id = ? AND p.is_active = 1
+              AND COALESCE(l.reorder_point, p.reorder_point) > 0
+            ORDER BY p.sku
+            ",
+        )
+        .bind(store_id)
+        .bind(store_id)
+        .bind(store_id)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut suggestions: Vec<ReorderSuggestion> = Vec::new();
+        for mut line in candidates {
+            if line.on_hand + line.on_order > line.reorder_point {
+                continue;
+            }
+            line.quantity = (line.reorder_point * 2.0 - line.on_hand - line.on_order).ceil().max(1.0);
+
+            match suggestions.iter_mut().find(|s| s.vendor_id == line.vendor_id) {
+                Some(suggestion) => suggestion.lines.push(line),
+                None => suggestions.push(ReorderSuggestion {
+                    vendor_id: line.vendor_id.clone(),
+                    store_id: store_id.to_string(),
+                    lines: vec![line],
+                }),
+            }
+        }
+
+        Ok(suggestions)
+    }
+
+    /// Raise a draft purchase order per vendor from the reorder suggestions
+    ///
+    /// Products without a vendor are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store is unknown or the database write fails.
+    pub async fn create_suggested(
+        &self,
+        tenant_id: &str,
+        store_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<PurchaseOrder>, PurchaseOrderError> {
+        let suggestions = self.suggestions(tenant_id, store_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut ids = Vec::new();
+        for suggestion in suggestions {
+            let Some(vendor_id) = suggestion.vendor_id else {
+                continue;
+            };
+
+            let request = PurchaseOrderRequest {
+                tenant_id: tenant_id.to_string(),
+                vendor_id,
+                store_id: store_id.to_string(),
+                created_by: user_id.to_string(),
+                expected_date: None,
+                notes: None,
+                lines: suggestion
+                    .lines
+                    .iter()
+                    .map(|line| PurchaseOrderLineRequest {
+                        product_id: line.product_id.clone(),
+                        quantity: line.quantity,
+                        unit_cost: Some(line.unit_cost),
+                    })
+                    .collect(),
+            };
+            ids.push(insert_purchase_order(&mut tx, &request, true).await?);
+        }
+        tx.commit().await?;
+
+        let mut orders = Vec::with_capacity(ids.len());
+        for id in ids {
+            orders.push(self.get(tenant_id, &id).await?);
+        }
+
+        if !orders.is_empty() {
+            tracing::info!(
+                tenant_id = %tenant_id,
+                store_id = %store_id,
+                purchase_orders = orders.len(),
+                "Suggested purchase orders raised"
+            );
+        }
+
+        Ok(orders)
+    }
+
+    // ========================================================================
+    // Three-way match
+    // ========================================================================
+
+    /// Tie a vendor bill to a purchase order of the same vendor and match it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bill or order is unknown, they are for
+    /// different vendors, or the bill is already posted.
+    pub async fn link_bill(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        bill_id: &str,
+    ) -> Result<BillMatch, PurchaseOrderError> {
+        let order = self.get(tenant_id, id).await?;
+        let bill: Option<(String, String)> =
+            sqlx::query_as("SELECT vendor_id, status FROM vendor_bills WHERE id = ? AND tenant_id = ?")
+                .bind(bill_id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some((vendor_id, status)) = bill else {
+            return Err(PurchaseOrderError::BillNotFound(bill_id.to_string()));
+        };
+        if vendor_id != order.vendor_id {
+            return Err(PurchaseOrderError::Validation(format!(
+                "Bill is from another vendor than purchase order {}",
+                order.po_number
+            )));
+        }
+        if status == "POSTED" {
+            return Err(PurchaseOrderError::InvalidState("Bill has already been posted".to_string()));
+        }
+
+        sqlx::query("UPDATE vendor_bills SET purchase_order_id = ? WHERE id = ? AND tenant_id = ?")
+            .bind(id)
+            .bind(bill_id)
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+
+        self.match_bill(tenant_id, bill_id)
+            .await?
+            .ok_or_else(|| PurchaseOrderError::NotFound(id.to_string()))
+    }
+
+    /// Match a vendor bill against its purchase order and receipts
+    ///
+    /// The PO is the one linked to the bill, or else the vendor's PO with the
+    /// bill's PO number. Returns `None` for a bill without a PO. Variances
+    /// are put in a review case; the same variances keep their case, so an
+    /// approval stands until the bill, the PO or the receipts change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bill is unknown or the database access fails.
+    pub async fn match_bill(&self, tenant_id: &str, bill_id: &str) -> Result<Option<BillMatch>, PurchaseOrderError> {
+        let bill = sqlx::query_as::<_, BillHeader>(
+            "SELECT vendor_id, po_number, purchase_order_id, file_path, mime_type, store_id, invoice_no
+             FROM vendor_bills WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(bill_id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| PurchaseOrderError::BillNotFound(bill_id.to_string()))?;
+
+        let purchase_order_id: Option<String> = match &bill.purchase_order_id {
+            Some(id) => Some(id.clone()),
+            None => {
+                sqlx::query_scalar(
+                    "SELECT id FROM purchase_orders
+                     WHERE tenant_id = ? AND vendor_id = ? AND po_number = ?
+                       AND status NOT IN ('draft', 'cancelled')",
+                )
+                .bind(tenant_id)
+                .bind(&bill.vendor_id)
+                .bind(&bill.po_number)
+                .fetch_optional(&self.pool)
+                .await?
+            }
+        };
+        let Some(purchase_order_id) = purchase_order_id else {
+            return Ok(None);
+        };
+        let order = self.get(tenant_id, &purchase_order_id).await?;
+
+        let bill_lines = sqlx::query_as::<_, BillLine>(
+            "SELECT l.line_no, l.matched_sku, p.id AS product_id, l.normalized_qty, l.unit_price
+             FROM vendor_bill_lines l
+             LEFT JOIN products p ON p.sku = l.matched_sku AND p.tenant_id = ?
+             WHERE l.vendor_bill_id = ? AND l.matched_sku IS NOT NULL AND l.matched_sku <> ''
+             ORDER BY l.line_no",
+        )
+        .bind(tenant_id)
+        .bind(bill_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let tolerance_percent = self.price_variance_percent(&bill.store_id).await?;
+        let variances = find_variances(&order, &bill_lines, tolerance_percent);
+
+        let existing: Option<(String, Option<String>)> = sqlx::query_as(
+            "SELECT variances, review_case_id FROM purchase_order_matches WHERE vendor_bill_id = ?",
+        )
+        .bind(bill_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let variances_json = serde_json::to_string(&variances).unwrap_or_else(|_| "[]".to_string());
+        let status = if variances.is_empty() { MATCH_STATUS_MATCHED } else { MATCH_STATUS_VARIANCE };
+        let review_case_id = match existing {
+            _ if variances.is_empty() => None,
+            Some((previous, Some(case_id))) if previous == variances_json => Some(case_id),
+            _ => Some(self.open_review_case(tenant_id, &bill, &order, &variances).await?),
+        };
+
+        sqlx::query(
+            r"
+            INSERT INTO purchase_order_matches (
+                id, tenant_id, vendor_bill_id, purchase_order_id, status, variances, review_case_id, matched_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (vendor_bill_id) DO UPDATE SET
+                purchase_order_id = excluded.purchase_order_id,
+                status = excluded.status,
+                variances = excluded.variances,
+                review_case_id = excluded.review_case_id,
+                matched_at = excluded.matched_at
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(bill_id)
+        .bind(&order.id)
+        .bind(status)
+        .bind(&variances_json)
+        .bind(&review_case_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        let review_state: Option<String> = match &review_case_id {
+            Some(case_id) => {
+                sqlx::query_scalar("SELECT state FROM review_cases WHERE id = ?")
+                    .bind(case_id)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            None => None,
+        };
+
+        if !variances.is_empty() {
+            tracing::warn!(
+                vendor_bill_id = %bill_id,
+                po_number = %order.po_number,
+                variances = variances.len(),
+                "Vendor bill does not match its purchase order"
+            );
+        }
+
+        Ok(Some(BillMatch {
+            vendor_bill_id: bill_id.to_string(),
+            purchase_order_id: order.id,
+            po_number: order.po_number,
+            status: status.to_string(),
+            variances,
+            review_case_id,
+            review_state,
+        }))
+    }
+
+    /// Put the variances of a bill in the review queue
+    async fn open_review_case(
+        &self,
+        tenant_id: &str,
+        bill: &BillHeader,
+        order: &PurchaseOrder,
+        variances: &[Variance],
+    ) -> Result<String, PurchaseOrderError> {
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+        let validation = serde_json::json!({
+            "hard_flags": variances.iter().map(|v| v.message.clone()).collect::<Vec<_>>(),
+            "soft_flags": [format!(
+                "Three-way match of invoice {} against purchase order {}",
+                bill.invoice_no, order.po_number
+            )],
+            // Approving the case accepts the variances
+            "can_approve": true,
+        });
+
+        sqlx::query(
+            r"
+            INSERT INTO review_cases (
+                id, tenant_id, state, vendor_id, vendor_name, confidence, source_file_path,
+                source_file_type, validation_result, created_at, updated_at, created_by
+            ) VALUES (?, ?, 'NeedsReview', ?, ?, 100, ?, ?, ?, ?, ?, 'three_way_match')
+            ",
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&bill.vendor_id)
+        .bind(&order.vendor_name)
+        .bind(&bill.file_path)
+        .bind(&bill.mime_type)
+        .bind(validation.to_string())
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn price_variance_percent(&self, store_id: &str) -> Result<f64, PurchaseOrderError> {
+        let value: Option<String> = sqlx::query_scalar(
+            "SELECT value FROM settings
+             WHERE key = ? AND ((scope = 'store' AND scope_id = ?) OR scope = 'global')
+             ORDER BY CASE scope WHEN 'store' THEN 1 ELSE 2 END
+             LIMIT 1",
+        )
+        .bind(SETTING_PRICE_VARIANCE_PERCENT)
+        .bind(store_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(value
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|percent| *percent >= 0.0)
+            .unwrap_or(DEFAULT_PRICE_VARIANCE_PERCENT))
+    }
+
+    async fn finish(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        user_id: &str,
+        status: PurchaseOrderStatus,
+        from: &[PurchaseOrderStatus],
+    ) -> Result<PurchaseOrder, PurchaseOrderError> {
+        let order = self.get(tenant_id, id).await?;
+        if !from.iter().any(|allowed| allowed.as_str() == order.status) {
+            return Err(PurchaseOrderError::InvalidState(format!(
+                "Purchase order {} is {} and cannot be {}",
+                order.po_number,
+                order.status,
+                status.as_str()
+            )));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "UPDATE purchase_orders SET status = ?, closed_by = ?, closed_at = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ? AND status = ?",
+        )
+        .bind(status.as_str())
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(&order.status)
+        .execute(&self.pool)
+        .await?;
+
+        self.get(tenant_id, id).await
+    }
+
+    async fn lines(&self, id: &str) -> Result<Vec<PurchaseOrderLine>, PurchaseOrderError> {
+        let lines = sqlx::query_as::<_, PurchaseOrderLine>(
+            "SELECT l.id, l.line_no, l.product_id, p.sku, p.name AS product_name, l.vendor_sku,
+                    l.quantity_ordered, l.quantity_received, l.unit_cost
+             FROM purchase_order_lines l
+             LEFT JOIN products p ON p.id = l.product_id
+             WHERE l.purchase_order_id = ?
+             ORDER BY l.line_no ASC",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lines)
+    }
+}
+
+/// Write a draft purchase order with its lines
+async fn insert_purchase_order(
+    tx: &mut Transaction<'_, Sqlite>,
+    request: &PurchaseOrderRequest,
+    suggested: bool,
+) -> Result<String, PurchaseOrderError> {
+    if request.lines.is_empty() {
+        return Err(PurchaseOrderError::Validation(
+            "Purchase order must have at least one product".to_string(),
+        ));
+    }
+    for (index, line) in request.lines.iter().enumerate() {
+        if line.quantity <= 0.0 {
+            return Err(PurchaseOrderError::Validation(format!(
+                "Quantity for product {} must be positive",
+                line.product_id
+            )));
+        }
+        if line.unit_cost.is_some_and(|cost| cost < 0.0) {
+            return Err(PurchaseOrderError::Validation(format!(
+                "Unit cost for product {} cannot be negative",
+                line.product_id
+            )));
+        }
+        if request.lines[..index].iter().any(|other| other.product_id == line.product_id) {
+            return Err(PurchaseOrderError::Validation(format!(
+                "Product {} is listed twice",
+                line.product_id
+            )));
+        }
+    }
+
+    let vendor: Option<String> = sqlx::query_scalar("SELECT id FROM vendors WHERE id = ? AND tenant_id = ?")
+        .bind(&request.vendor_id)
+        .bind(&request.tenant_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    if vendor.is_none() {
+        return Err(PurchaseOrderError::Validation(format!("Vendor not found: {}", request.vendor_id)));
+    }
+    let store: Option<String> = sqlx::query_scalar("SELECT id FROM stores WHERE id = ?")
+        .bind(&request.store_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    if store.is_none() {
+        return Err(PurchaseOrderError::Validation(format!("Store not found: {}", request.store_id)));
+    }
+
+    let today = Utc::now().format("%Y%m%d").to_string();
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM purchase_orders WHERE tenant_id = ? AND DATE(created_at) = DATE('now')",
+    )
+    .bind(&request.tenant_id)
+    .fetch_one(&mut **tx)
+    .await?;
+    let po_number = format!("PO-{}-{:04}", today, count + 1);
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r"
+        INSERT INTO purchase_orders (
+            id, tenant_id, po_number, vendor_id, store_id, status, suggested, expected_date,
+            notes, created_by, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&id)
+    .bind(&request.tenant_id)
+    .bind(&po_number)
+    .bind(&request.vendor_id)
+    .bind(&request.store_id)
+    .bind(PurchaseOrderStatus::Draft.as_str())
+    .bind(suggested)
+    .bind(&request.expected_date)
+    .bind(&request.notes)
+    .bind(&request.created_by)
+    .bind(&now)
+    .bind(&now)
+    .execute(&mut **tx)
+    .await?;
+
+    for (line_no, line) in (1_i64..).zip(&request.lines) {
+        let product: Option<(String, f64)> =
+            sqlx::query_as("SELECT sku, cost FROM products WHERE id = ? AND tenant_id = ?")
+                .bind(&line.product_id)
+                .bind(&request.tenant_id)
+                .fetch_optional(&mut **tx)
+                .await?;
+        let Some((sku, cost)) = product else {
+            return Err(PurchaseOrderError::Validation(format!("Product not found: {}", line.product_id)));
+        };
+
+        let vendor_sku: Option<String> = sqlx::query_scalar(
+            "SELECT vendor_sku_norm FROM vendor_sku_aliases
+             WHERE vendor_id = ? AND internal_sku = ? AND tenant_id = ?
+             ORDER BY priority DESC, usage_count DESC LIMIT 1",
+        )
+        .bind(&request.vendor_id)
+        .bind(&sku)
+        .bind(&request.tenant_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO purchase_order_lines (
+                id, purchase_order_id, line_no, product_id, vendor_sku, quantity_ordered, unit_cost
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&id)
+        .bind(line_no)
+        .bind(&line.product_id)
+        .bind(&vendor_sku)
+        .bind(line.quantity)
+        .bind(line.unit_cost.unwrap_or(cost))
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    tracing::info!(
+        purchase_order_id = %id,
+        po_number = %po_number,
+        vendor_id = %request.vendor_id,
+        suggested,
+        "Purchase order created"
+    );
+
+    Ok(id)
+}
+
+/// Compare bill lines with the PO prices and the received quantities
+fn find_variances(order: &PurchaseOrder, bill_lines: &[BillLine], tolerance_percent: f64) -> Vec<Variance> {
+    let mut variances = Vec::new();
+    let mut billed: Vec<(&PurchaseOrderLine, f64)> = Vec::new();
+
+    for bill_line in bill_lines {
+        let po_line = bill_line
+            .product_id
+            .as_ref()
+            .and_then(|product_id| order.lines.iter().find(|line| &line.product_id == product_id));
+        let Some(po_line) = po_line else {
+            variances.push(Variance {
+                kind: VarianceKind::NotOrdered,
+                bill_line_no: Some(bill_line.line_no),
+                sku: bill_line.matched_sku.clone(),
+                expected: 0.0,
+                billed: bill_line.normalized_qty,
+                message: format!(
+                    "Line {}: {} is billed but not on purchase order {}",
+                    bill_line.line_no, bill_line.matched_sku, order.po_number
+                ),
+            });
+            continue;
+        };
+
+        // Half a cent of slack for rounding on the bill
+        let tolerance = po_line.unit_cost * tolerance_percent / 100.0 + 0.005;
+        if (bill_line.unit_price - po_line.unit_cost).abs() > tolerance {
+            variances.push(Variance {
+                kind: VarianceKind::Price,
+                bill_line_no: Some(bill_line.line_no),
+                sku: bill_line.matched_sku.clone(),
+                expected: po_line.unit_cost,
+                billed: bill_line.unit_price,
+                message: format!(
+                    "Line {}: {} billed at {:.2}, ordered at {:.2}",
+                    bill_line.line_no, bill_line.matched_sku, bill_line.unit_price, po_line.unit_cost
+                ),
+            });
+        }
+
+        match billed.iter_mut().find(|(line, _)| line.id == po_line.id) {
+            Some((_, quantity)) => *quantity += bill_line.normalized_qty,
+            None => billed.push((po_line, bill_line.normalized_qty)),
+        }
+    }
+
+    for (po_line, quantity) in billed {
+        if (quantity - po_line.quantity_received).abs() > QUANTITY_EPSILON {
+            let sku = po_line.sku.clone().unwrap_or_else(|| po_line.product_id.clone());
+            variances.push(Variance {
+                kind: VarianceKind::Quantity,
+                bill_line_no: None,
+                message: format!(
+                    "{sku}: billed {quantity}, received {} on purchase order {}",
+                    po_line.quantity_received, order.po_number
+                ),
+                sku,
+                expected: po_line.quantity_received,
+                billed: quantity,
+            });
+        }
+    }
+
+    variances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE vendors (id TEXT PRIMARY KEY, tenant_id TEXT NOT NULL, name TEXT NOT NULL)",
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                sku TEXT NOT NULL,
+                name TEXT NOT NULL,
+                store_id TEXT,
+                cost REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                is_active INTEGER NOT NULL DEFAULT 1
+            )",
+            "CREATE TABLE vendor_sku_aliases (
+                id TEXT PRIMARY KEY,
+                vendor_id TEXT NOT NULL,
+                vendor_sku_norm TEXT NOT NULL,
+                internal_sku TEXT NOT NULL,
+                priority INTEGER NOT NULL DEFAULT 0,
+                usage_count INTEGER NOT NULL DEFAULT 0,
+                tenant_id TEXT NOT NULL
+            )",
+            "CREATE TABLE vendor_bills (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                vendor_id TEXT NOT NULL,
+                invoice_no TEXT NOT NULL,
+                po_number TEXT,
+                status TEXT NOT NULL DEFAULT 'REVIEW',
+                file_path TEXT NOT NULL,
+                mime_type TEXT NOT NULL,
+                store_id TEXT NOT NULL
+            )",
+            "CREATE TABLE vendor_bill_lines (
+                id TEXT PRIMARY KEY,
+                vendor_bill_id TEXT NOT NULL,
+                line_no INTEGER NOT NULL,
+                normalized_qty REAL NOT NULL,
+                unit_price REAL NOT NULL,
+                matched_sku TEXT
+            )",
+            "CREATE TABLE settings (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            include_str!("../../../../migrations/043_review_cases_tables.sql"),
+            "INSERT INTO stores (id, name) VALUES ('s1', 'Main Street')",
+            "INSERT INTO vendors (id, tenant_id, name) VALUES ('v1', 't1', 'AutoParts Direct'), ('v2', 't1', 'ACME')",
+            "INSERT INTO products (id, tenant_id, sku, name, store_id, cost, reorder_point, quantity_on_hand) VALUES
+                ('p1', 't1', 'PAD-1', 'Brake pads', 's1', 20.0, 4, 3),
+                ('p2', 't1', 'OIL-5', 'Oil 5L', 's1', 30.0, 2, 10),
+                ('p3', 't1', 'WIP-2', 'Wiper blade', 's1', 8.0, 5, 0)",
+            "INSERT INTO vendor_sku_aliases (id, vendor_id, vendor_sku_norm, internal_sku, priority, tenant_id) VALUES
+                ('a1', 'v1', 'APD-PAD', 'PAD-1', 1, 't1'),
+                ('a2', 'v1', 'APD-OIL', 'OIL-5', 1, 't1')",
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/069_purchase_orders.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    fn request(lines: &[(&str, f64, Option<f64>)]) -> PurchaseOrderRequest {
+        PurchaseOrderRequest {
+            tenant_id: "t1".to_string(),
+            vendor_id: "v1".to_string(),
+            store_id: "s1".to_string(),
+            created_by: "u1".to_string(),
+            expected_date: None,
+            notes: None,
+            lines: lines
+                .iter()
+                .map(|(product_id, quantity, unit_cost)| PurchaseOrderLineRequest {
+                    product_id: (*product_id).to_string(),
+                    quantity: *quantity,
+                    unit_cost: *unit_cost,
+                })
+                .collect(),
+        }
+    }
+
+    async fn bill(pool: &SqlitePool, po_number: &str, lines: &[(i64, &str, f64, f64)]) -> String {
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO vendor_bills (id, tenant_id, vendor_id, invoice_no, po_number, file_path, mime_type, store_id)
+             VALUES (?, 't1', 'v1', 'INV-1', ?, 'bills/inv-1.pdf', 'application/pdf', 's1')",
+        )
+        .bind(&id)
+        .bind(po_number)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        for (line_no, sku, quantity, unit_price) in lines {
+            sqlx::query(
+                "INSERT INTO vendor_bill_lines (id, vendor_bill_id, line_no, normalized_qty, unit_price, matched_sku)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&id)
+            .bind(line_no)
+            .bind(quantity)
+            .bind(unit_price)
+            .bind(sku)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+
+        id
+    }
+
+    #[tokio::test]
+    async fn test_partial_receipts_into_store_stock() {
+        let pool = setup_test_db().await;
+        let service = PurchaseOrderService::new(pool.clone());
+
+        let order = service.create(request(&[("p1", 10.0, None), ("p2", 4.0, Some(28.5))])).await.unwrap();
+        assert_eq!(order.status, "draft");
+        assert!(order.po_number.starts_with("PO-"));
+        assert_eq!(order.lines[0].unit_cost, 20.0);
+        assert_eq!(order.lines[0].vendor_sku.as_deref(), Some("APD-PAD"));
+        assert_eq!(order.total_cost(), 314.0);
+
+        let receipt = [ReceiptLine { line_id: order.lines[0].id.clone(), quantity: 6.0 }];
+        assert!(matches!(
+            service.receive("t1", &order.id, "u2", &receipt, None).await,
+            Err(PurchaseOrderError::InvalidState(_))
+        ));
+        service.submit("t1", &order.id, "u1").await.unwrap();
+
+        let partial = service.receive("t1", &order.id, "u2", &receipt, None).await.unwrap();
+        assert_eq!(partial.status, "partially_received");
+        assert_eq!(partial.lines[0].outstanding(), 4.0);
+
+        let rest = [
+            ReceiptLine { line_id: order.lines[0].id.clone(), quantity: 4.0 },
+            ReceiptLine { line_id: order.lines[1].id.clone(), quantity: 4.0 },
+        ];
+        let received = service.receive("t1", &order.id, "u2", &rest, Some("Second truck".to_string())).await.unwrap();
+        assert_eq!(received.status, "received");
+
+        let stock: f64 = sqlx::query_scalar(
+            "SELECT quantity_on_hand FROM product_locations WHERE product_id = 'p1' AND store_id = 's1'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(stock, 13.0);
+
+        let receipts: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM inventory_movements WHERE source_type = 'purchase_order' AND source_id = ?",
+        )
+        .bind(&order.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(receipts, 3);
+
+        assert!(matches!(service.cancel("t1", &order.id, "u1").await, Err(PurchaseOrderError::InvalidState(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_validation_cancel_and_close() {
+        let pool = setup_test_db().await;
+        let service = PurchaseOrderService::new(pool.clone());
+
+        assert!(matches!(service.create(request(&[])).await, Err(PurchaseOrderError::Validation(_))));
+        assert!(matches!(
+            service.create(request(&[("p1", 1.0, None), ("p1", 2.0, None)])).await,
+            Err(PurchaseOrderError::Validation(_))
+        ));
+        assert!(matches!(
+            service.create(request(&[("nope", 1.0, None)])).await,
+            Err(PurchaseOrderError::Validation(_))
+        ));
+        let mut other_vendor = request(&[("p1", 1.0, None)]);
+        other_vendor.vendor_id = "v9".to_string();
+        assert!(matches!(service.create(other_vendor).await, Err(PurchaseOrderError::Validation(_))));
+
+        let draft = service.create(request(&[("p1", 1.0, None)])).await.unwrap();
+        assert_eq!(service.cancel("t1", &draft.id, "u1").await.unwrap().status, "cancelled");
+
+        let order = service.create(request(&[("p1", 5.0, None)])).await.unwrap();
+        service.submit("t1", &order.id, "u1").await.unwrap();
+        assert!(matches!(service.close("t1", &order.id, "u1").await, Err(PurchaseOrderError::InvalidState(_))));
+        let receipt = [ReceiptLine { line_id: order.lines[0].id.clone(), quantity: 2.0 }];
+        service.receive("t1", &order.id, "u2", &receipt, None).await.unwrap();
+        let closed = service.close("t1", &order.id, "u1").await.unwrap();
+        assert_eq!(closed.status, "closed");
+        assert_eq!(closed.closed_by.as_deref(), Some("u1"));
+
+        assert_eq!(service.list("t1", Some("v1"), None, None).await.unwrap().len(), 2);
+        assert_eq!(
+            service.list("t1", None, None, Some(PurchaseOrderStatus::Closed)).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suggestions_top_up_below_reorder_point() {
+        let pool = setup_test_db().await;
+        let service = PurchaseOrderService::new(pool.clone());
+
+        let suggestions = service.suggestions("t1", "s1").await.unwrap();
+        // Brake pads (3 of 4) from their vendor; wipers (0 of 5) have none;
+        // oil is well stocked
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].vendor_id.as_deref(), Some("v1"));
+        assert_eq!(suggestions[0].lines.len(), 1);
+        assert_eq!(suggestions[0].lines[0].quantity, 5.0);
+        assert_eq!(suggestions[1].vendor_id, None);
+        assert_eq!(suggestions[1].lines[0].sku, "WIP-2");
+
+        let orders = service.create_suggested("t1", "s1", "u1").await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert!(orders[0].suggested);
+        assert_eq!(orders[0].lines[0].quantity_ordered, 5.0);
+
+        // What is on order counts towards the stock
+        let suggestions = service.suggestions("t1", "s1").await.unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].vendor_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_three_way_match_flags_variances_for_review() {
+        let pool = setup_test_db().await;
+        let service = PurchaseOrderService::new(pool.clone());
+
+        let order = service.create(request(&[("p1", 10.0, None), ("p2", 4.0, None)])).await.unwrap();
+        let order = service.submit("t1", &order.id, "u1").await.unwrap();
+        let receipt = [
+            ReceiptLine { line_id: order.lines[0].id.clone(), quantity: 8.0 },
+            ReceiptLine { line_id: order.lines[1].id.clone(), quantity: 4.0 },
+        ];
+        service.receive("t1", &order.id, "u2", &receipt, None).await.unwrap();
+
+        // A bill without a PO is not matched
+        let no_po = bill(&pool, "PO-UNKNOWN", &[(1, "PAD-1", 1.0, 20.0)]).await;
+        assert!(service.match_bill("t1", &no_po).await.unwrap().is_none());
+
+        // Billed for 10 pads though 8 arrived, oil 1% over (within 2%),
+        // wipers never ordered
+        let bill_id = bill(
+            &pool,
+            &order.po_number,
+            &[(1, "PAD-1", 10.0, 20.0), (2, "OIL-5", 4.0, 30.3), (3, "WIP-2", 2.0, 8.0)],
+        )
+        .await;
+        let matched = service.match_bill("t1", &bill_id).await.unwrap().unwrap();
+        assert_eq!(matched.status, MATCH_STATUS_VARIANCE);
+        let kinds: Vec<_> = matched.variances.iter().map(|v| v.kind).collect();
+        assert_eq!(kinds, vec![VarianceKind::NotOrdered, VarianceKind::Quantity]);
+        assert_eq!(matched.review_state.as_deref(), Some("NeedsReview"));
+        assert!(!matched.is_cleared());
+
+        // Matching again keeps the same case; approving it clears the bill
+        let case_id = matched.review_case_id.unwrap();
+        let again = service.match_bill("t1", &bill_id).await.unwrap().unwrap();
+        assert_eq!(again.review_case_id.as_deref(), Some(case_id.as_str()));
+        sqlx::query("UPDATE review_cases SET state = 'Approved' WHERE id = ?")
+            .bind(&case_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(service.match_bill("t1", &bill_id).await.unwrap().unwrap().is_cleared());
+
+        // A tighter tolerance turns up the oil price too, in a new case
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('purchasing.price_variance_percent', '0.5')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let tighter = service.match_bill("t1", &bill_id).await.unwrap().unwrap();
+        assert!(tighter.variances.iter().any(|v| v.kind == VarianceKind::Price && v.sku == "OIL-5"));
+        assert_ne!(tighter.review_case_id.as_deref(), Some(case_id.as_str()));
+        assert!(!tighter.is_cleared());
+    }
+
+    #[tokio::test]
+    async fn test_linked_bill_that_agrees_is_matched() {
+        let pool = setup_test_db().await;
+        let service = PurchaseOrderService::new(pool.clone());
+
+        let order = service.create(request(&[("p1", 2.0, None)])).await.unwrap();
+        service.submit("t1", &order.id, "u1").await.unwrap();
+        let receipt = [ReceiptLine { line_id: order.lines[0].id.clone(), quantity: 2.0 }];
+        service.receive("t1", &order.id, "u2", &receipt, None).await.unwrap();
+
+        // The bill has no PO number; it is tied to the PO by hand
+        let bill_id = bill(&pool, "", &[(1, "PAD-1", 2.0, 20.0)]).await;
+        let matched = service.link_bill("t1", &order.id, &bill_id).await.unwrap();
+        assert_eq!(matched.status, MATCH_STATUS_MATCHED);
+        assert!(matched.variances.is_empty());
+        assert!(matched.review_case_id.is_none());
+        assert!(matched.is_cleared());
+    }
+}
@@ -7,6 +7,10 @@
 
 use crate::models::vendor::{VendorBill, VendorBillLine};
 use crate::models::product::Product;
+use crate::services::inventory_ledger_service::{
+    self, MovementType, StockMovement, REASON_VENDOR_RECEIPT, SOURCE_VENDOR_BILL,
+};
+use crate::services::purchase_order_service::{PurchaseOrderError, PurchaseOrderService};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
@@ -162,6 +166,29 @@ impl ReceivingService {
             });
         }
 
+        // Three-way match against the purchase order, if the bill has one
+        match PurchaseOrderService::new(self.pool.clone()).match_bill(tenant_id, bill_id).await {
+            Ok(Some(matched)) if !matched.is_cleared() => {
+                errors.push(ValidationError {
+                    field: "three_way_match".to_string(),
+                    message: format!(
+                        "Bill does not match purchase order {} ({} variances); approve review case {} to post it",
+                        matched.po_number,
+                        matched.variances.len(),
+                        matched.review_case_id.unwrap_or_default()
+                    ),
+                });
+            }
+            Ok(_) => {}
+            Err(PurchaseOrderError::Database(e)) => return Err(e),
+            Err(e) => {
+                errors.push(ValidationError {
+                    field: "three_way_match".to_string(),
+                    message: e.to_string(),
+                });
+            }
+        }
+
         Ok(errors)
     }
 
@@ -200,6 +227,14 @@ impl ReceivingService {
         .fetch_all(&mut *tx)
         .await?;
 
+        // Goods on a bill matched to a purchase order were received against the PO
+        let received_on_po: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM purchase_order_matches WHERE vendor_bill_id = ?)"
+        )
+        .bind(bill_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
         let now = Utc::now().to_rfc3339();
         let mut total_items = 0.0;
         let mut total_cost = 0.0;
@@ -224,29 +259,51 @@ impl ReceivingService {
                 }
             };
 
+            // Stock received on the PO is already on hand at the old cost
+            let existing_quantity = if received_on_po {
+                (product.quantity_on_hand - line.normalized_qty).max(0.0)
+            } else {
+                product.quantity_on_hand
+            };
+
             // Calculate new cost based on policy
             let new_cost = self.calculate_new_cost(
                 &cost_policy,
                 product.cost,
                 line.unit_price,
-                product.quantity_on_hand,
+                existing_quantity,
                 line.normalized_qty,
             );
 
-            // Update product quantity and cost
-            let new_quantity = product.quantity_on_hand + line.normalized_qty;
-            
+            // Receive the stock through the ledger, then update the cost
+            let new_quantity = if received_on_po {
+                product.quantity_on_hand
+            } else {
+                let receipt = StockMovement::new(
+                    tenant_id,
+                    &product.id,
+                    MovementType::Receipt,
+                    line.normalized_qty,
+                    REASON_VENDOR_RECEIPT,
+                )
+                .with_source(SOURCE_VENDOR_BILL, bill_id)
+                .by_user(user_id)
+                .at_store(&bill.store_id)
+                .with_unit_cost(Some(line.unit_price));
+                inventory_ledger_service::record_movement(&mut tx, &receipt)
+                    .await?
+                    .quantity_after
+            };
+
             sqlx::query(
                 r#"
                 UPDATE products
-                SET quantity_on_hand = ?,
-                    cost = ?,
+                SET cost = ?,
                     updated_at = ?,
                     sync_version = sync_version + 1
                 WHERE sku = ? AND tenant_id = ?
                 "#
             )
-            .bind(new_quantity)
             .bind(new_cost)
             .bind(&now)
             .bind(matched_sku)
@@ -272,7 +329,8 @@ impl ReceivingService {
                 "vendor_bill_line_id": line.id,
                 "vendor_sku": line.vendor_sku_raw,
                 "internal_sku": matched_sku,
-                "quantity_change": line.normalized_qty,
+                "quantity_change": if received_on_po { 0.0 } else { line.normalized_qty },
+                "received_on_purchase_order": received_on_po,
                 "old_quantity": product.quantity_on_hand,
                 "new_quantity": new_quantity,
                 "old_cost": product.cost,
@@ -0,0 +1,1705 @@
+/**
+ * Return Service
+ *
+ * Returns goods against an original sale. Unlike voiding, a return may cover
+ * a subset of the lines and quantities, and a sale may be returned in several
+ * goes until every unit is back.
+ *
+ * A return is recorded as its own `sales_transactions` row (`transaction_type`
+ * 'return') pointing at the original sale. Amounts, quantities, line taxes and
+ * refund tenders are written negative so reports that sum the sales tables
+ * net returns out. Each return line is priced as its share of the original
+ * line (own discount, share of the cart discount and per-authority tax
+ * included); the last return of a line takes whatever is left so the line
+ * nets to exactly zero.
+ *
+ * In one database transaction the service:
+ * - restocks the returned units at the cost they sold at, or leaves them off
+ *   the shelf when damaged
+ * - refunds to the original tenders (gift cards and store credit are credited
+ *   back, cash/card are recorded as money out) or entirely to store credit
+ * - reverses the commission on the sale, or claws back its returned share
+ * - writes a credit-memo accounting snapshot for the exporters
+ *
+ * An exchange is a return refunded to store credit followed by a new sale
+ * tendered with that store credit.
+ */
+
+use accounting_snapshots::{
+    AccountingSnapshot, Payment, SnapshotError, SnapshotLine, SnapshotLineTax, SnapshotRepository,
+};
+use chrono::Utc;
+use pos_core_domain::allocate_proportionally;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::checkout_service::{
+    decimal_from_f64, money_to_f64, next_transaction_number, CheckoutError, PAYMENT_METHOD_SPLIT,
+    TENDER_GIFT_CARD, TENDER_STORE_CREDIT,
+};
+use crate::services::commission_service;
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_RETURN_RESTOCK, SOURCE_RETURN,
+};
+use crate::services::stored_value_service::{self, StoredValueError};
+
+/// `sales_transactions.transaction_type` of a sale
+pub const TRANSACTION_TYPE_SALE: &str = "sale";
+/// `sales_transactions.transaction_type` of a return
+pub const TRANSACTION_TYPE_RETURN: &str = "return";
+
+/// Decimal places kept for returned quantities (weighed goods)
+const QUANTITY_DP: u32 = 4;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum ReturnError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Sale not found: {0}")]
+    SaleNotFound(String),
+
+    #[error("Sale cannot be returned: {0}")]
+    NotReturnable(String),
+
+    #[error("Refund declined: {0}")]
+    Refund(#[from] StoredValueError),
+
+    #[error("Accounting snapshot error: {0}")]
+    Snapshot(#[from] SnapshotError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<CheckoutError> for ReturnError {
+    fn from(err: CheckoutError) -> Self {
+        match err {
+            CheckoutError::Database(e) => Self::Database(e),
+            other => Self::Validation(other.to_string()),
+        }
+    }
+}
+
+impl From<ReturnError> for ApiError {
+    fn from(err: ReturnError) -> Self {
+        match err {
+            ReturnError::Validation(msg) => Self::bad_request(msg),
+            ReturnError::SaleNotFound(id) => Self::not_found(format!("Sale not found: {id}")),
+            ReturnError::NotReturnable(msg) => Self::conflict(msg),
+            ReturnError::Refund(StoredValueError::Database(e)) => {
+                Self::internal(format!("Failed to apply refund: {e}"))
+            }
+            ReturnError::Refund(e) => Self::bad_request(format!("Refund declined: {e}")),
+            ReturnError::Snapshot(e @ SnapshotError::PeriodClosed(_)) => Self::conflict(e.to_string()),
+            ReturnError::Snapshot(e) => {
+                Self::internal(format!("Failed to record accounting snapshot: {e}"))
+            }
+            ReturnError::Database(e) => Self::internal(format!("Failed to record return: {e}")),
+        }
+    }
+}
+
+impl From<LedgerError> for ReturnError {
+    fn from(err: LedgerError) -> Self {
+        match err {
+            LedgerError::Database(e) => Self::Database(e),
+            other => Self::Validation(other.to_string()),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// What happens to returned goods
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReturnDisposition {
+    /// Back on the shelf
+    #[default]
+    Restock,
+    /// Written off; stock is not increased
+    Damaged,
+}
+
+impl ReturnDisposition {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Restock => "restock",
+            Self::Damaged => "damaged",
+        }
+    }
+}
+
+/// Where the refund goes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundDestination {
+    /// Back to the tenders the sale was paid with
+    #[default]
+    OriginalTenders,
+    /// Entirely to the customer's store credit
+    StoreCredit,
+}
+
+/// Money amounts of a sale line, or of the part of it returned so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LineAmounts {
+    pub quantity: Decimal,
+    pub subtotal: Decimal,
+    pub discount: Decimal,
+    pub tax: Decimal,
+    pub total: Decimal,
+}
+
+/// Tax charged by one authority on an original sale line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OriginalLineTax {
+    pub authority: String,
+    pub label: String,
+    pub rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub tax_amount: Decimal,
+}
+
+/// A line of the original sale with what has already been returned
+#[derive(Debug, Clone, Serialize)]
+pub struct ReturnableLine {
+    pub line_item_id: String,
+    pub product_id: String,
+    pub description: String,
+    pub unit_price: Decimal,
+    pub tax_class: Option<String>,
+    pub sold: LineAmounts,
+    pub returned: LineAmounts,
+    pub taxes: Vec<OriginalLineTax>,
+    /// Product category recorded on the sale's accounting snapshot
+    pub category: Option<String>,
+    /// Unit cost recorded on the sale's accounting snapshot
+    #[serde(skip)]
+    pub unit_cost: Option<Decimal>,
+}
+
+impl ReturnableLine {
+    /// Units that can still be returned
+    #[must_use]
+    pub fn returnable_quantity(&self) -> Decimal {
+        (self.sold.quantity - self.returned.quantity).max(Decimal::ZERO)
+    }
+}
+
+/// A tender of the original sale with what has already been refunded to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RefundableTender {
+    pub method: String,
+    pub reference: Option<String>,
+    /// Amount the tender paid towards the sale (change excluded)
+    pub paid: Decimal,
+    pub refunded: Decimal,
+}
+
+impl RefundableTender {
+    #[must_use]
+    pub fn remaining(&self) -> Decimal {
+        (self.paid - self.refunded).max(Decimal::ZERO)
+    }
+}
+
+/// Original sale as seen by the returns desk
+#[derive(Debug, Clone, Serialize)]
+pub struct OriginalSale {
+    pub id: String,
+    pub transaction_number: String,
+    pub customer_id: Option<String>,
+    pub status: String,
+    pub created_at: String,
+    pub total: Decimal,
+    pub prices_include_tax: bool,
+    pub lines: Vec<ReturnableLine>,
+    pub tenders: Vec<RefundableTender>,
+}
+
+impl OriginalSale {
+    /// Whether every unit of every line has been returned
+    #[must_use]
+    pub fn fully_returned(&self) -> bool {
+        self.lines.iter().all(|line| line.returnable_quantity().is_zero())
+    }
+}
+
+/// A line to return
+#[derive(Debug, Clone)]
+pub struct ReturnLine {
+    /// `sales_line_items.id` of the original line
+    pub line_item_id: String,
+    pub quantity: Decimal,
+    pub disposition: ReturnDisposition,
+}
+
+/// Everything needed to record a return
+#[derive(Debug, Clone)]
+pub struct ReturnRequest {
+    pub tenant_id: String,
+    pub store_id: String,
+    /// Register paying out the refund
+    pub station_id: Option<String>,
+    pub employee_id: String,
+    pub original_sale_id: String,
+    pub lines: Vec<ReturnLine>,
+    pub refund_to: RefundDestination,
+    /// Customer to credit when refunding to store credit a sale made without one
+    pub customer_id: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// A refund paid out on a return
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RefundedTender {
+    pub method: String,
+    pub reference: Option<String>,
+    pub amount: Decimal,
+}
+
+/// Result of a recorded return
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletedReturn {
+    pub return_id: String,
+    pub transaction_number: String,
+    pub original_sale_id: String,
+    pub snapshot_id: Uuid,
+    pub subtotal: Decimal,
+    pub discount: Decimal,
+    pub tax: Decimal,
+    pub total: Decimal,
+    pub refunds: Vec<RefundedTender>,
+    /// Whether the original sale has now been returned in full
+    pub fully_returned: bool,
+    pub created_at: String,
+}
+
+/// A priced return line, ready to persist
+#[derive(Debug, Clone)]
+struct PricedReturnLine<'a> {
+    original: &'a ReturnableLine,
+    disposition: ReturnDisposition,
+    amounts: LineAmounts,
+    taxes: Vec<SnapshotLineTax>,
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct ReturnService {
+    pool: SqlitePool,
+}
+
+impl ReturnService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Look up a sale by transaction number (as printed in the receipt barcode)
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReturnError::SaleNotFound` if no sale has that number.
+    pub async fn find_by_number(
+        &self,
+        tenant_id: &str,
+        transaction_number: &str,
+    ) -> Result<OriginalSale, ReturnError> {
+        let mut conn = self.pool.acquire().await?;
+
+        let sale_id: String = sqlx::query_scalar(
+            "SELECT id FROM sales_transactions
+             WHERE tenant_id = ? AND transaction_number = ? AND transaction_type = ?",
+        )
+        .bind(tenant_id)
+        .bind(transaction_number.trim())
+        .bind(TRANSACTION_TYPE_SALE)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| ReturnError::SaleNotFound(transaction_number.to_string()))?;
+
+        load_original_sale(&mut conn, tenant_id, &sale_id).await
+    }
+
+    /// Load a sale with its returnable quantities and refundable tenders
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReturnError::SaleNotFound` if the sale does not exist.
+    pub async fn find_by_id(&self, tenant_id: &str, sale_id: &str) -> Result<OriginalSale, ReturnError> {
+        let mut conn = self.pool.acquire().await?;
+        load_original_sale(&mut conn, tenant_id, sale_id).await
+    }
+
+    /// Record a return against an original sale
+    ///
+    /// The return transaction, stock changes, refunds, commission reversal and
+    /// credit-memo snapshot are committed together.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sale cannot be returned (voided, itself a
+    /// return), a line does not belong to it or exceeds the quantity still
+    /// returnable, a refund is declined, or the database write fails.
+    pub async fn process_return(&self, request: ReturnRequest) -> Result<CompletedReturn, ReturnError> {
+        if request.lines.is_empty() {
+            return Err(ReturnError::Validation("Return must have at least one item".to_string()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let original = load_original_sale(&mut tx, &request.tenant_id, &request.original_sale_id).await?;
+        if original.status == "voided" {
+            return Err(ReturnError::NotReturnable("Sale has been voided".to_string()));
+        }
+        let original_uuid = Uuid::parse_str(&original.id)
+            .map_err(|_| ReturnError::NotReturnable(format!("Sale {} predates returns", original.id)))?;
+        let first_return = original.lines.iter().all(|line| line.returned.quantity.is_zero());
+
+        let priced = price_return(&original, &request.lines)?;
+        let subtotal: Decimal = priced.iter().map(|line| line.amounts.subtotal).sum();
+        let discount: Decimal = priced.iter().map(|line| line.amounts.discount).sum();
+        let tax: Decimal = priced.iter().map(|line| line.amounts.tax).sum();
+        let total: Decimal = priced.iter().map(|line| line.amounts.total).sum();
+
+        let customer_id = original.customer_id.clone().or_else(|| request.customer_id.clone());
+        let refunds = match request.refund_to {
+            RefundDestination::OriginalTenders => allocate_refund(total, &original.tenders)?,
+            RefundDestination::StoreCredit => {
+                if customer_id.is_none() {
+                    return Err(ReturnError::Validation(
+                        "Refunding to store credit requires a customer".to_string(),
+                    ));
+                }
+                vec![RefundedTender {
+                    method: TENDER_STORE_CREDIT.to_string(),
+                    reference: None,
+                    amount: total,
+                }]
+            }
+        };
+
+        let return_uuid = Uuid::new_v4();
+        let return_id = return_uuid.to_string();
+        let transaction_number = next_transaction_number(&mut tx, &request.tenant_id, "RTN").await?;
+        let now = Utc::now();
+        let now_str = now.to_rfc3339();
+        let items_count = i32::try_from(priced.len())
+            .map_err(|_| ReturnError::Validation("Too many items in return".to_string()))?;
+        let payment_method = match refunds.as_slice() {
+            [single] => single.method.clone(),
+            _ => PAYMENT_METHOD_SPLIT.to_string(),
+        };
+
+        sqlx::query(
+            r"
+            INSERT INTO sales_transactions (
+                id, tenant_id, transaction_number, customer_id, employee_id, store_id, station_id,
+                total_amount, subtotal, tax_amount, discount_amount, items_count,
+                payment_method, payment_status, status, notes, prices_include_tax,
+                transaction_type, original_transaction_id, created_at, updated_at, completed_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'refunded', 'completed', ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&return_id)
+        .bind(&request.tenant_id)
+        .bind(&transaction_number)
+        .bind(&customer_id)
+        .bind(&request.employee_id)
+        .bind(&request.store_id)
+        .bind(&request.station_id)
+        .bind(-money_to_f64(total))
+        .bind(-money_to_f64(subtotal))
+        .bind(-money_to_f64(tax))
+        .bind(-money_to_f64(discount))
+        .bind(-items_count)
+        .bind(&payment_method)
+        .bind(&request.reason)
+        .bind(original.prices_include_tax)
+        .bind(TRANSACTION_TYPE_RETURN)
+        .bind(&original.id)
+        .bind(&now_str)
+        .bind(&now_str)
+        .bind(&now_str)
+        .execute(&mut *tx)
+        .await?;
+
+        for line in &priced {
+            insert_return_line(&mut tx, &request, &return_id, line, &now_str).await?;
+        }
+
+        for refund in &refunds {
+            pay_refund(&mut tx, &request, customer_id.as_deref(), &return_id, refund, &now_str).await?;
+        }
+
+        // Commission: the whole sale back in one go reverses it outright,
+        // anything less claws back the returned share
+        let fully_returned = original.lines.iter().all(|line| {
+            let returning: Decimal = priced
+                .iter()
+                .filter(|priced| priced.original.line_item_id == line.line_item_id)
+                .map(|priced| priced.amounts.quantity)
+                .sum();
+            line.returned.quantity + returning >= line.sold.quantity
+        });
+        if first_return && fully_returned {
+            commission_service::reverse_commission(&mut tx, &request.tenant_id, &original.id).await?;
+        } else {
+            let sold_net: Decimal = original.lines.iter().map(|line| line.sold.subtotal - line.sold.discount).sum();
+            if !sold_net.is_zero() {
+                let fraction = ((subtotal - discount) / sold_net).to_f64().unwrap_or_default();
+                commission_service::claw_back_commission(
+                    &mut tx,
+                    &request.tenant_id,
+                    &original.id,
+                    &return_id,
+                    fraction,
+                )
+                .await?;
+            }
+        }
+
+        let snapshot = build_credit_memo(
+            return_uuid,
+            original_uuid,
+            request.reason.clone(),
+            &priced,
+            &refunds,
+            now,
+        )?;
+        SnapshotRepository::save_in_transaction(&mut tx, &snapshot).await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            return_id = %return_id,
+            original_sale_id = %original.id,
+            total = %total,
+            "Return recorded"
+        );
+
+        Ok(CompletedReturn {
+            return_id,
+            transaction_number,
+            original_sale_id: original.id,
+            snapshot_id: snapshot.id,
+            subtotal,
+            discount,
+            tax,
+            total,
+            refunds,
+            fully_returned,
+            created_at: now_str,
+        })
+    }
+}
+
+// ============================================================================
+// Pricing
+// ============================================================================
+
+/// Share of `sold` for `quantity` units, given what was returned before
+///
+/// Each amount is prorated and rounded to the cent; the return that brings
+/// the line back to zero takes the exact remainder instead so rounding never
+/// leaves (or overshoots) a cent on the original line.
+#[must_use]
+pub fn prorate_line(sold: &LineAmounts, returned: &LineAmounts, quantity: Decimal) -> LineAmounts {
+    let (subtotal, discount, tax) = if returned.quantity + quantity >= sold.quantity {
+        (
+            sold.subtotal - returned.subtotal,
+            sold.discount - returned.discount,
+            sold.tax - returned.tax,
+        )
+    } else {
+        let share = |amount: Decimal| (amount * quantity / sold.quantity).round_dp(2);
+        (share(sold.subtotal), share(sold.discount), share(sold.tax))
+    };
+
+    LineAmounts {
+        quantity,
+        subtotal,
+        discount,
+        tax,
+        total: subtotal - discount + tax,
+    }
+}
+
+/// Price the requested return lines against the original sale
+fn price_return<'a>(
+    original: &'a OriginalSale,
+    lines: &[ReturnLine],
+) -> Result<Vec<PricedReturnLine<'a>>, ReturnError> {
+    let mut priced: Vec<PricedReturnLine<'a>> = Vec::with_capacity(lines.len());
+
+    for line in lines {
+        let quantity = line.quantity.round_dp(QUANTITY_DP);
+        if quantity <= Decimal::ZERO {
+            return Err(ReturnError::Validation("Return quantity must be positive".to_string()));
+        }
+        if priced.iter().any(|p| p.original.line_item_id == line.line_item_id) {
+            return Err(ReturnError::Validation(format!(
+                "Line {} is listed more than once",
+                line.line_item_id
+            )));
+        }
+
+        let sold = original
+            .lines
+            .iter()
+            .find(|sold| sold.line_item_id == line.line_item_id)
+            .ok_or_else(|| {
+                ReturnError::Validation(format!(
+                    "Line {} is not part of sale {}",
+                    line.line_item_id, original.transaction_number
+                ))
+            })?;
+
+        if quantity > sold.returnable_quantity() {
+            return Err(ReturnError::Validation(format!(
+                "Only {} of {} can still be returned",
+                sold.returnable_quantity().normalize(),
+                sold.description
+            )));
+        }
+
+        let amounts = prorate_line(&sold.sold, &sold.returned, quantity);
+        let taxes = split_line_tax(sold, &amounts);
+
+        priced.push(PricedReturnLine {
+            original: sold,
+            disposition: line.disposition,
+            amounts,
+            taxes,
+        });
+    }
+
+    Ok(priced)
+}
+
+/// Split a return line's tax over the authorities that taxed the original line
+fn split_line_tax(line: &ReturnableLine, amounts: &LineAmounts) -> Vec<SnapshotLineTax> {
+    let weights: Vec<Decimal> = line.taxes.iter().map(|tax| tax.tax_amount).collect();
+    let shares = allocate_proportionally(amounts.tax, &weights, 2);
+
+    line.taxes
+        .iter()
+        .zip(shares)
+        .map(|(tax, tax_amount)| SnapshotLineTax {
+            authority: tax.authority.clone(),
+            label: tax.label.clone(),
+            rate: tax.rate,
+            taxable_amount: (tax.taxable_amount * amounts.quantity / line.sold.quantity).round_dp(2),
+            tax_amount,
+        })
+        .collect()
+}
+
+/// Spread a refund over the tenders of the original sale, in the order they
+/// were taken, up to what each tender still has to give back
+///
+/// # Errors
+///
+/// Returns `ReturnError::Validation` if the tenders cannot cover the refund.
+pub fn allocate_refund(
+    total: Decimal,
+    tenders: &[RefundableTender],
+) -> Result<Vec<RefundedTender>, ReturnError> {
+    let mut outstanding = total;
+    let mut refunds = Vec::new();
+
+    for tender in tenders {
+        if outstanding <= Decimal::ZERO {
+            break;
+        }
+        let amount = tender.remaining().min(outstanding);
+        if amount > Decimal::ZERO {
+            refunds.push(RefundedTender {
+                method: tender.method.clone(),
+                reference: tender.reference.clone(),
+                amount,
+            });
+            outstanding -= amount;
+        }
+    }
+
+    if outstanding > Decimal::ZERO {
+        return Err(ReturnError::Validation(format!(
+            "Original tenders can refund only {}; refund the rest to store credit",
+            total - outstanding
+        )));
+    }
+
+    Ok(refunds)
+}
+
+/// Build the credit-memo snapshot of a return (amounts positive)
+///
+/// Only restocked lines carry the unit cost: their goods go back into
+/// inventory, reversing the cost of goods sold. The cost of damaged goods
+/// stays expensed.
+fn build_credit_memo(
+    return_id: Uuid,
+    original_sale_id: Uuid,
+    reason: Option<String>,
+    lines: &[PricedReturnLine<'_>],
+    refunds: &[RefundedTender],
+    finalized_at: chrono::DateTime<Utc>,
+) -> Result<AccountingSnapshot, ReturnError> {
+    let snapshot = AccountingSnapshot::new(
+        return_id,
+        finalized_at,
+        lines.iter().map(|line| line.amounts.subtotal).sum(),
+        lines.iter().map(|line| line.amounts.tax).sum(),
+        lines.iter().map(|line| line.amounts.discount).sum(),
+        lines.iter().map(|line| line.amounts.total).sum(),
+        refunds
+            .iter()
+            .map(|refund| Payment {
+                method: refund.method.clone(),
+                amount: refund.amount,
+            })
+            .collect(),
+        lines
+            .iter()
+            .map(|line| {
+                SnapshotLine::new(
+                    line.original.product_id.clone(),
+                    line.original.description.clone(),
+                    line.amounts.quantity,
+                    line.original.unit_price,
+                    line.amounts.subtotal,
+                    line.amounts.tax,
+                )
+                .with_taxes(line.taxes.clone())
+                .with_costing(
+                    line.original.category.clone(),
+                    line.original
+                        .unit_cost
+                        .filter(|_| line.disposition == ReturnDisposition::Restock),
+                )
+            })
+            .collect(),
+    )
+    .as_credit_memo(original_sale_id, reason);
+
+    if !snapshot.verify_consistency() {
+        return Err(SnapshotError::InconsistentData(format!(
+            "Credit memo for return {return_id} failed consistency verification"
+        ))
+        .into());
+    }
+
+    Ok(snapshot)
+}
+
+// ============================================================================
+// Persistence
+// ============================================================================
+
+#[derive(Debug, sqlx::FromRow)]
+struct SaleHeaderRow {
+    id: String,
+    transaction_number: String,
+    customer_id: Option<String>,
+    status: String,
+    created_at: String,
+    total_amount: f64,
+    payment_method: Option<String>,
+    prices_include_tax: bool,
+    transaction_type: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SaleLineRow {
+    id: String,
+    product_id: String,
+    description: String,
+    quantity: f64,
+    unit_price: f64,
+    subtotal: f64,
+    discount_amount: f64,
+    tax_amount: f64,
+    total: f64,
+    tax_class: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SnapshotCostingRow {
+    product_id: String,
+    category: Option<String>,
+    unit_cost: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct LineTaxRow {
+    line_item_id: String,
+    authority: String,
+    label: String,
+    rate: f64,
+    taxable_amount: f64,
+    tax_amount: f64,
+}
+
+fn money(value: f64, field: &str) -> Result<Decimal, ReturnError> {
+    Ok(decimal_from_f64(value, field)?.round_dp(2))
+}
+
+fn amounts_from_row(row: &SaleLineRow) -> Result<LineAmounts, ReturnError> {
+    Ok(LineAmounts {
+        quantity: decimal_from_f64(row.quantity, "quantity")?.round_dp(QUANTITY_DP),
+        subtotal: money(row.subtotal, "subtotal")?,
+        discount: money(row.discount_amount, "discount_amount")?,
+        tax: money(row.tax_amount, "tax_amount")?,
+        total: money(row.total, "total")?,
+    })
+}
+
+/// Load a sale with its lines, what each line has had returned, and its
+/// tenders with what each has refunded
+async fn load_original_sale(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    sale_id: &str,
+) -> Result<OriginalSale, ReturnError> {
+    let header = sqlx::query_as::<_, SaleHeaderRow>(
+        "SELECT id, transaction_number, customer_id, status, created_at, total_amount,
+                payment_method, prices_include_tax, transaction_type
+         FROM sales_transactions
+         WHERE id = ? AND tenant_id = ?",
+    )
+    .bind(sale_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| ReturnError::SaleNotFound(sale_id.to_string()))?;
+
+    if header.transaction_type != TRANSACTION_TYPE_SALE {
+        return Err(ReturnError::NotReturnable(format!(
+            "{} is a {}, not a sale",
+            header.transaction_number, header.transaction_type
+        )));
+    }
+
+    let line_rows = sqlx::query_as::<_, SaleLineRow>(
+        "SELECT sli.id, sli.product_id, COALESCE(p.name, sli.product_id) AS description,
+                sli.quantity, sli.unit_price, sli.subtotal, sli.discount_amount,
+                sli.tax_amount, sli.total, sli.tax_class
+         FROM sales_line_items sli
+         LEFT JOIN products p ON p.id = sli.product_id
+         WHERE sli.transaction_id = ?
+         ORDER BY sli.rowid",
+    )
+    .bind(&header.id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // Return lines are stored negative; flip them back to returned amounts
+    let returned_rows = sqlx::query_as::<_, SaleLineRow>(
+        "SELECT r.original_line_item_id AS id, r.product_id, r.product_id AS description,
+                -SUM(r.quantity) AS quantity, 0.0 AS unit_price, -SUM(r.subtotal) AS subtotal,
+                -SUM(r.discount_amount) AS discount_amount, -SUM(r.tax_amount) AS tax_amount,
+                -SUM(r.total) AS total, NULL AS tax_class
+         FROM sales_line_items r
+         JOIN sales_line_items o ON o.id = r.original_line_item_id
+         WHERE o.transaction_id = ?
+         GROUP BY r.original_line_item_id, r.product_id",
+    )
+    .bind(&header.id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let tax_rows = sqlx::query_as::<_, LineTaxRow>(
+        "SELECT line_item_id, authority, label, rate, taxable_amount, tax_amount
+         FROM sales_line_taxes
+         WHERE transaction_id = ? AND tenant_id = ?
+         ORDER BY rowid",
+    )
+    .bind(&header.id)
+    .bind(tenant_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // Category and cost as recorded when the sale was made; sales snapshotted
+    // before costing was recorded have neither
+    let costing_rows = sqlx::query_as::<_, SnapshotCostingRow>(
+        "SELECT sl.product_id, sl.category, sl.unit_cost
+         FROM snapshot_lines sl
+         JOIN accounting_snapshots s ON s.id = sl.snapshot_id
+         WHERE s.transaction_id = ?
+         ORDER BY sl.rowid",
+    )
+    .bind(&header.id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut lines = Vec::with_capacity(line_rows.len());
+    for row in &line_rows {
+        let returned = match returned_rows.iter().find(|r| r.id == row.id) {
+            Some(returned) => amounts_from_row(returned)?,
+            None => LineAmounts::default(),
+        };
+        let taxes = tax_rows
+            .iter()
+            .filter(|tax| tax.line_item_id == row.id)
+            .map(|tax| {
+                Ok(OriginalLineTax {
+                    authority: tax.authority.clone(),
+                    label: tax.label.clone(),
+                    rate: decimal_from_f64(tax.rate, "rate")?,
+                    taxable_amount: money(tax.taxable_amount, "taxable_amount")?,
+                    tax_amount: money(tax.tax_amount, "tax_amount")?,
+                })
+            })
+            .collect::<Result<Vec<_>, ReturnError>>()?;
+        let costing = costing_rows.iter().find(|costing| costing.product_id == row.product_id);
+        let unit_cost = costing
+            .and_then(|costing| costing.unit_cost.as_deref())
+            .map(|cost| {
+                cost.parse::<Decimal>()
+                    .map_err(|_| ReturnError::Validation(format!("Invalid unit cost on sale snapshot: {cost}")))
+            })
+            .transpose()?;
+
+        lines.push(ReturnableLine {
+            line_item_id: row.id.clone(),
+            product_id: row.product_id.clone(),
+            description: row.description.clone(),
+            unit_price: money(row.unit_price, "unit_price")?,
+            tax_class: row.tax_class.clone(),
+            sold: amounts_from_row(row)?,
+            returned,
+            taxes,
+            category: costing.and_then(|costing| costing.category.clone()),
+            unit_cost,
+        });
+    }
+
+    let tenders = load_refundable_tenders(conn, tenant_id, &header).await?;
+
+    Ok(OriginalSale {
+        id: header.id,
+        transaction_number: header.transaction_number,
+        customer_id: header.customer_id,
+        status: header.status,
+        created_at: header.created_at,
+        total: money(header.total_amount, "total_amount")?,
+        prices_include_tax: header.prices_include_tax,
+        lines,
+        tenders,
+    })
+}
+
+/// Tenders of a sale less what earlier returns refunded to each of them
+async fn load_refundable_tenders(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    header: &SaleHeaderRow,
+) -> Result<Vec<RefundableTender>, ReturnError> {
+    let paid: Vec<(String, Option<String>, f64)> = sqlx::query_as(
+        "SELECT method, reference, amount
+         FROM sales_payments
+         WHERE transaction_id = ? AND tenant_id = ? AND amount > 0
+         ORDER BY created_at, rowid",
+    )
+    .bind(&header.id)
+    .bind(tenant_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut tenders = paid
+        .into_iter()
+        .map(|(method, reference, amount)| {
+            Ok(RefundableTender {
+                method,
+                reference,
+                paid: money(amount, "amount")?,
+                refunded: Decimal::ZERO,
+            })
+        })
+        .collect::<Result<Vec<_>, ReturnError>>()?;
+
+    // Sales recorded before split tenders have no payment rows
+    if tenders.is_empty() {
+        tenders.push(RefundableTender {
+            method: header.payment_method.clone().unwrap_or_else(|| "cash".to_string()),
+            reference: None,
+            paid: money(header.total_amount, "total_amount")?,
+            refunded: Decimal::ZERO,
+        });
+    }
+
+    let refunded: Vec<(String, Option<String>, f64)> = sqlx::query_as(
+        "SELECT p.method, p.reference, -p.amount
+         FROM sales_payments p
+         JOIN sales_transactions t ON t.id = p.transaction_id
+         WHERE t.original_transaction_id = ? AND t.tenant_id = ?
+         ORDER BY p.created_at, p.rowid",
+    )
+    .bind(&header.id)
+    .bind(tenant_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (method, reference, amount) in refunded {
+        let mut left = money(amount, "amount")?;
+        for tender in tenders
+            .iter_mut()
+            .filter(|tender| tender.method == method && tender.reference == reference)
+        {
+            let applied = tender.remaining().min(left);
+            tender.refunded += applied;
+            left -= applied;
+        }
+    }
+
+    Ok(tenders)
+}
+
+/// Write a return line (negative amounts), its tax breakdown and the restock
+async fn insert_return_line(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    request: &ReturnRequest,
+    return_id: &str,
+    line: &PricedReturnLine<'_>,
+    now: &str,
+) -> Result<(), ReturnError> {
+    let line_item_id = Uuid::new_v4().to_string();
+    let quantity = line.amounts.quantity.to_f64().unwrap_or_default();
+
+    // Restocked goods take back their cost of goods; written-off goods keep
+    // it expensed
+    let cost = match (line.disposition, line.original.unit_cost) {
+        (ReturnDisposition::Restock, Some(unit_cost)) => (line.amounts.quantity * unit_cost).round_dp(2),
+        _ => Decimal::ZERO,
+    };
+    let profit = line.amounts.subtotal - line.amounts.discount - cost;
+
+    sqlx::query(
+        r"
+        INSERT INTO sales_line_items (
+            id, transaction_id, product_id, quantity, unit_price,
+            subtotal, discount_amount, tax_amount, total, tax_class,
+            cost, profit, original_line_item_id, return_disposition, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&line_item_id)
+    .bind(return_id)
+    .bind(&line.original.product_id)
+    .bind(-quantity)
+    .bind(money_to_f64(line.original.unit_price))
+    .bind(-money_to_f64(line.amounts.subtotal))
+    .bind(-money_to_f64(line.amounts.discount))
+    .bind(-money_to_f64(line.amounts.tax))
+    .bind(-money_to_f64(line.amounts.total))
+    .bind(&line.original.tax_class)
+    .bind(-money_to_f64(cost))
+    .bind(-money_to_f64(profit))
+    .bind(&line.original.line_item_id)
+    .bind(line.disposition.as_str())
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+
+    for tax in &line.taxes {
+        sqlx::query(
+            r"
+            INSERT INTO sales_line_taxes (
+                id, tenant_id, transaction_id, line_item_id, authority, label,
+                rate, taxable_amount, tax_amount, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&request.tenant_id)
+        .bind(return_id)
+        .bind(&line_item_id)
+        .bind(&tax.authority)
+        .bind(&tax.label)
+        .bind(tax.rate.to_f64().unwrap_or_default())
+        .bind(-money_to_f64(tax.taxable_amount))
+        .bind(-money_to_f64(tax.tax_amount))
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    if line.disposition == ReturnDisposition::Restock {
+        let restocked = StockMovement::new(
+            &request.tenant_id,
+            &line.original.product_id,
+            MovementType::Return,
+            quantity,
+            REASON_RETURN_RESTOCK,
+        )
+        .with_source(SOURCE_RETURN, return_id)
+        .by_user(&request.employee_id)
+        .at_store(&request.store_id)
+        .with_unit_cost(line.original.unit_cost.and_then(|cost| cost.to_f64()));
+        inventory_ledger_service::record_movement(&mut **tx, &restocked).await?;
+    }
+
+    Ok(())
+}
+
+/// Credit stored value for a refund and record it against the return
+async fn pay_refund(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    request: &ReturnRequest,
+    customer_id: Option<&str>,
+    return_id: &str,
+    refund: &RefundedTender,
+    now: &str,
+) -> Result<(), ReturnError> {
+    let amount = money_to_f64(refund.amount);
+
+    match refund.method.as_str() {
+        TENDER_GIFT_CARD => {
+            let card_number = refund.reference.as_deref().ok_or_else(|| {
+                ReturnError::Validation("Gift card tender has no card number to refund to".to_string())
+            })?;
+            stored_value_service::credit_gift_card(tx, &request.tenant_id, card_number, amount, return_id)
+                .await?;
+        }
+        TENDER_STORE_CREDIT => {
+            let customer_id = customer_id.ok_or_else(|| {
+                ReturnError::Validation("Refunding to store credit requires a customer".to_string())
+            })?;
+            stored_value_service::credit_store_credit(
+                tx,
+                &request.tenant_id,
+                customer_id,
+                amount,
+                return_id,
+                &request.employee_id,
+            )
+                .await?;
+        }
+        // Cash, card, ...: money handed back at the register
+        _ => {}
+    }
+
+    sqlx::query(
+        "INSERT INTO sales_payments (
+            id, tenant_id, transaction_id, method, tendered_amount, amount, change_amount, reference, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?, 0, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(&request.tenant_id)
+    .bind(return_id)
+    .bind(&refund.method)
+    .bind(-amount)
+    .bind(-amount)
+    .bind(&refund.reference)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::checkout_service::{CheckoutLine, CheckoutRequest, Tender, TENDER_CASH};
+    use crate::services::CheckoutService;
+    use accounting_snapshots::SnapshotKind;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn amounts(quantity: &str, subtotal: &str, discount: &str, tax: &str) -> LineAmounts {
+        let (subtotal, discount, tax) = (dec(subtotal), dec(discount), dec(tax));
+        LineAmounts {
+            quantity: dec(quantity),
+            subtotal,
+            discount,
+            tax,
+            total: subtotal - discount + tax,
+        }
+    }
+
+    fn tender(method: &str, paid: &str, refunded: &str) -> RefundableTender {
+        RefundableTender {
+            method: method.to_string(),
+            reference: None,
+            paid: dec(paid),
+            refunded: dec(refunded),
+        }
+    }
+
+    #[test]
+    fn test_prorate_line_takes_remainder_on_last_return() {
+        let sold = amounts("3", "10.00", "1.00", "1.17");
+
+        let first = prorate_line(&sold, &LineAmounts::default(), dec("1"));
+        assert_eq!(first, amounts("1", "3.33", "0.33", "0.39"));
+
+        let second = prorate_line(&sold, &first, dec("2"));
+        assert_eq!(second, amounts("2", "6.67", "0.67", "0.78"));
+        assert_eq!(first.total + second.total, sold.total);
+    }
+
+    #[test]
+    fn test_allocate_refund_follows_tender_order_and_limits() {
+        let tenders = vec![tender("gift_card", "10.00", "4.00"), tender("cash", "12.58", "0")];
+
+        let refunds = allocate_refund(dec("9.00"), &tenders).unwrap();
+        assert_eq!(refunds.len(), 2);
+        assert_eq!(refunds[0].amount, dec("6.00"));
+        assert_eq!(refunds[1].amount, dec("3.00"));
+
+        assert!(matches!(
+            allocate_refund(dec("20.00"), &tenders),
+            Err(ReturnError::Validation(_))
+        ));
+    }
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                store_id TEXT,
+                quantity_on_hand REAL NOT NULL DEFAULT 0,
+                tax_class TEXT NOT NULL DEFAULT 'standard',
+                category TEXT NOT NULL DEFAULT 'General',
+                cost REAL NOT NULL DEFAULT 0,
+                reorder_point REAL
+            )",
+            "CREATE TABLE tax_rules (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL DEFAULT 'default',
+                name TEXT NOT NULL,
+                rate REAL NOT NULL,
+                is_default INTEGER NOT NULL DEFAULT 0,
+                authority TEXT,
+                tax_classes TEXT,
+                is_compound INTEGER NOT NULL DEFAULT 0,
+                sort_order INTEGER NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE settings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT,
+                data_type TEXT NOT NULL DEFAULT 'string',
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(key, scope, scope_id)
+            )",
+            "CREATE TABLE sales_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_number TEXT NOT NULL,
+                customer_id TEXT,
+                employee_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                station_id TEXT,
+                total_amount REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                items_count INTEGER NOT NULL,
+                payment_method TEXT,
+                payment_status TEXT NOT NULL,
+                status TEXT NOT NULL,
+                notes TEXT,
+                tax_exempt_certificate TEXT,
+                prices_include_tax INTEGER NOT NULL DEFAULT 0,
+                transaction_type TEXT NOT NULL DEFAULT 'sale',
+                original_transaction_id TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                completed_at TEXT
+            )",
+            "CREATE TABLE sales_line_items (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                quantity REAL NOT NULL,
+                unit_price REAL NOT NULL,
+                subtotal REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                total REAL NOT NULL,
+                tax_class TEXT,
+                cost REAL,
+                profit REAL,
+                original_line_item_id TEXT,
+                return_disposition TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE sales_line_taxes (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_id TEXT NOT NULL,
+                line_item_id TEXT NOT NULL,
+                authority TEXT NOT NULL,
+                label TEXT NOT NULL,
+                rate REAL NOT NULL,
+                taxable_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE sales_payments (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                transaction_id TEXT NOT NULL,
+                method TEXT NOT NULL,
+                tendered_amount REAL NOT NULL,
+                amount REAL NOT NULL,
+                change_amount REAL NOT NULL DEFAULT 0.0,
+                reference TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE accounting_snapshots (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL,
+                finalized_at TEXT NOT NULL,
+                subtotal TEXT NOT NULL,
+                tax TEXT NOT NULL,
+                discount TEXT NOT NULL,
+                total TEXT NOT NULL,
+                kind TEXT NOT NULL DEFAULT 'sale',
+                original_transaction_id TEXT,
+                memo TEXT
+            )",
+            "CREATE TABLE snapshot_lines (
+                id TEXT PRIMARY KEY,
+                snapshot_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                description TEXT NOT NULL,
+                quantity TEXT NOT NULL,
+                unit_price TEXT NOT NULL,
+                line_total TEXT NOT NULL,
+                tax_amount TEXT NOT NULL,
+                category TEXT,
+                unit_cost TEXT
+            )",
+            "CREATE TABLE snapshot_line_taxes (
+                id TEXT PRIMARY KEY,
+                snapshot_line_id TEXT NOT NULL,
+                authority TEXT NOT NULL,
+                label TEXT NOT NULL,
+                rate TEXT NOT NULL,
+                taxable_amount TEXT NOT NULL,
+                tax_amount TEXT NOT NULL
+            )",
+            "CREATE TABLE snapshot_payments (
+                id TEXT PRIMARY KEY,
+                snapshot_id TEXT NOT NULL,
+                method TEXT NOT NULL,
+                amount TEXT NOT NULL
+            )",
+            "CREATE TABLE gift_cards (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                card_number TEXT NOT NULL UNIQUE,
+                current_balance REAL NOT NULL,
+                status TEXT NOT NULL,
+                expiry_date TEXT
+            )",
+            "CREATE TABLE gift_card_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                gift_card_id TEXT NOT NULL,
+                transaction_type TEXT NOT NULL,
+                amount REAL NOT NULL,
+                reference_id TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE customers (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL DEFAULT 'default',
+                store_credit REAL NOT NULL DEFAULT 0.0,
+                tax_exempt INTEGER NOT NULL DEFAULT 0,
+                tax_exempt_certificate TEXT,
+                tax_exempt_authorities TEXT,
+                updated_at TEXT,
+                sync_version INTEGER NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE loyalty_transactions (
+                id TEXT PRIMARY KEY,
+                customer_id TEXT NOT NULL,
+                transaction_type TEXT NOT NULL,
+                points INTEGER NOT NULL,
+                amount REAL,
+                reference_id TEXT,
+                created_at TEXT NOT NULL,
+                employee_id TEXT NOT NULL
+            )",
+            "CREATE TABLE commissions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                employee_id TEXT NOT NULL,
+                transaction_id TEXT NOT NULL,
+                rule_id TEXT NOT NULL,
+                sale_amount REAL NOT NULL,
+                profit_amount REAL NOT NULL,
+                commission_amount REAL NOT NULL,
+                created_at TEXT NOT NULL,
+                is_reversed INTEGER NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE inventory_movements (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                store_id TEXT,
+                movement_type TEXT NOT NULL,
+                quantity REAL NOT NULL,
+                quantity_after REAL NOT NULL,
+                reason_code TEXT NOT NULL,
+                source_type TEXT,
+                source_id TEXT,
+                user_id TEXT,
+                notes TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE product_locations (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                quantity_on_hand REAL NOT NULL DEFAULT 0.0,
+                bin_location TEXT,
+                reorder_point REAL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                UNIQUE (product_id, store_id)
+            )",
+            "INSERT INTO products (id, tenant_id, name, quantity_on_hand, cost) VALUES ('p1', 't1', 'Widget', 10, 4.25)",
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+            include_str!("../../../../migrations/077_outbound_webhooks.sql"),
+            "INSERT INTO gift_cards (id, tenant_id, card_number, current_balance, status)
+             VALUES ('gc1', 't1', '4000', 10.0, 'Active')",
+            "INSERT INTO customers (id, tenant_id, store_credit) VALUES ('c1', 't1', 0.0)",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    /// Sell two widgets at 9.99 (13% default tax: 22.58) and record a 2.00 commission
+    async fn sell(pool: &SqlitePool, customer_id: Option<&str>, tenders: Vec<Tender>) -> String {
+        let sale = CheckoutService::new(pool.clone())
+            .complete_sale(CheckoutRequest {
+                tenant_id: "t1".to_string(),
+                store_id: "s1".to_string(),
+                station_id: None,
+                employee_id: "u1".to_string(),
+                customer_id: customer_id.map(str::to_string),
+                lines: vec![CheckoutLine {
+                    product_id: "p1".to_string(),
+                    quantity: dec("2"),
+                    unit_price: dec("9.99"),
+                    discount_amount: Decimal::ZERO,
+                }],
+                cart_discount: Decimal::ZERO,
+                tenders,
+                notes: None,
+                suspended_sale_id: None,
+            })
+            .await
+            .unwrap();
+
+        sqlx::query(
+            "INSERT INTO commissions (id, tenant_id, employee_id, transaction_id, rule_id,
+             sale_amount, profit_amount, commission_amount, created_at)
+             VALUES ('cm1', 't1', 'u1', ?, 'r1', 19.98, 8.00, 2.00, '2026-01-01')",
+        )
+        .bind(&sale.sale_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        sale.sale_id
+    }
+
+    fn cash(amount: &str) -> Vec<Tender> {
+        vec![Tender {
+            method: TENDER_CASH.to_string(),
+            amount: Some(dec(amount)),
+            reference: None,
+        }]
+    }
+
+    async fn return_request(
+        service: &ReturnService,
+        sale_id: &str,
+        quantity: &str,
+        disposition: ReturnDisposition,
+        refund_to: RefundDestination,
+    ) -> ReturnRequest {
+        let original = service.find_by_id("t1", sale_id).await.unwrap();
+        ReturnRequest {
+            tenant_id: "t1".to_string(),
+            store_id: "s1".to_string(),
+            station_id: None,
+            employee_id: "u1".to_string(),
+            original_sale_id: sale_id.to_string(),
+            lines: vec![ReturnLine {
+                line_item_id: original.lines[0].line_item_id.clone(),
+                quantity: dec(quantity),
+                disposition,
+            }],
+            refund_to,
+            customer_id: None,
+            reason: Some("Changed mind".to_string()),
+        }
+    }
+
+    async fn scalar_f64(pool: &SqlitePool, sql: &str) -> f64 {
+        sqlx::query_scalar(sql).fetch_one(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_partial_returns_restock_refund_and_net_to_zero() {
+        let pool = setup_test_db().await;
+        let sale_id = sell(&pool, None, cash("30.00")).await;
+        let service = ReturnService::new(pool.clone());
+
+        let request = return_request(
+            &service,
+            &sale_id,
+            "1",
+            ReturnDisposition::Restock,
+            RefundDestination::OriginalTenders,
+        )
+        .await;
+        let first = service.process_return(request).await.unwrap();
+        assert_eq!(first.total, dec("11.29"));
+        assert_eq!(first.refunds, vec![RefundedTender {
+            method: TENDER_CASH.to_string(),
+            reference: None,
+            amount: dec("11.29"),
+        }]);
+        assert!(!first.fully_returned);
+        assert!(first.transaction_number.starts_with("RTN-"));
+        assert_eq!(scalar_f64(&pool, "SELECT quantity_on_hand FROM products WHERE id = 'p1'").await, 9.0);
+        let restocked: (f64, String) = sqlx::query_as(
+            "SELECT quantity, source_id FROM inventory_movements WHERE movement_type = 'return'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(restocked, (1.0, first.return_id.clone()));
+        // Back on the shelf at the cost it sold at
+        assert_eq!(scalar_f64(&pool, "SELECT cost FROM sales_line_items WHERE quantity < 0").await, -4.25);
+        assert_eq!(
+            scalar_f64(&pool, "SELECT unit_cost FROM inventory_cost_layers WHERE movement_id IS NOT NULL").await,
+            4.25
+        );
+
+        // Partial return claws back half the commission
+        let commission = scalar_f64(&pool, "SELECT SUM(commission_amount) FROM commissions").await;
+        assert!((commission - 1.0).abs() < 1e-9);
+
+        let snapshot = SnapshotRepository::new(pool.clone())
+            .find_by_transaction_id(Uuid::parse_str(&first.return_id).unwrap())
+            .await
+            .unwrap();
+        assert!(snapshot.is_credit_memo());
+        assert_eq!(snapshot.total, dec("11.29"));
+        assert_eq!(
+            snapshot.kind,
+            SnapshotKind::CreditMemo {
+                original_transaction_id: Uuid::parse_str(&sale_id).unwrap(),
+                reason: Some("Changed mind".to_string()),
+            }
+        );
+        // Restocked goods reverse their cost of goods at the cost they sold at
+        assert_eq!(snapshot.lines[0].category.as_deref(), Some("General"));
+        assert_eq!(snapshot.lines[0].cost_of_goods(), Some(dec("4.25")));
+
+        // Only one unit left to return
+        let request = return_request(
+            &service,
+            &sale_id,
+            "2",
+            ReturnDisposition::Restock,
+            RefundDestination::OriginalTenders,
+        )
+        .await;
+        assert!(matches!(service.process_return(request).await, Err(ReturnError::Validation(_))));
+
+        let request = return_request(
+            &service,
+            &sale_id,
+            "1",
+            ReturnDisposition::Restock,
+            RefundDestination::OriginalTenders,
+        )
+        .await;
+        let second = service.process_return(request).await.unwrap();
+        assert_eq!(second.total, dec("11.29"));
+        assert!(second.fully_returned);
+
+        // Sale and returns net out in the sales tables
+        let net = scalar_f64(&pool, "SELECT SUM(total_amount) FROM sales_transactions").await;
+        let net_tax = scalar_f64(&pool, "SELECT SUM(tax_amount) FROM sales_line_taxes").await;
+        let net_paid = scalar_f64(&pool, "SELECT SUM(amount) FROM sales_payments").await;
+        assert!(net.abs() < 1e-9);
+        assert!(net_tax.abs() < 1e-9);
+        assert!(net_paid.abs() < 1e-9);
+        assert_eq!(scalar_f64(&pool, "SELECT quantity_on_hand FROM products WHERE id = 'p1'").await, 10.0);
+
+        let original = service.find_by_id("t1", &sale_id).await.unwrap();
+        assert!(original.fully_returned());
+        assert_eq!(original.tenders[0].remaining(), Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_full_return_credits_gift_card_and_reverses_commission() {
+        let pool = setup_test_db().await;
+        let tenders = vec![
+            Tender {
+                method: TENDER_GIFT_CARD.to_string(),
+                amount: Some(dec("10.00")),
+                reference: Some("4000".to_string()),
+            },
+            Tender {
+                method: TENDER_CASH.to_string(),
+                amount: Some(dec("20.00")),
+                reference: None,
+            },
+        ];
+        let sale_id = sell(&pool, None, tenders).await;
+        let service = ReturnService::new(pool.clone());
+
+        let request = return_request(
+            &service,
+            &sale_id,
+            "2",
+            ReturnDisposition::Restock,
+            RefundDestination::OriginalTenders,
+        )
+        .await;
+        let completed = service.process_return(request).await.unwrap();
+
+        assert_eq!(completed.total, dec("22.58"));
+        assert_eq!(completed.refunds[0].amount, dec("10.00"));
+        assert_eq!(completed.refunds[1].amount, dec("12.58"));
+        assert_eq!(scalar_f64(&pool, "SELECT current_balance FROM gift_cards WHERE id = 'gc1'").await, 10.0);
+
+        let reversed: i64 = sqlx::query_scalar("SELECT is_reversed FROM commissions WHERE id = 'cm1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(reversed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_damaged_return_to_store_credit_does_not_restock() {
+        let pool = setup_test_db().await;
+        let sale_id = sell(&pool, Some("c1"), cash("30.00")).await;
+        let service = ReturnService::new(pool.clone());
+
+        let request = return_request(
+            &service,
+            &sale_id,
+            "1",
+            ReturnDisposition::Damaged,
+            RefundDestination::StoreCredit,
+        )
+        .await;
+        let completed = service.process_return(request).await.unwrap();
+
+        assert_eq!(completed.refunds[0].method, TENDER_STORE_CREDIT);
+        assert_eq!(scalar_f64(&pool, "SELECT store_credit FROM customers WHERE id = 'c1'").await, 11.29);
+        assert_eq!(scalar_f64(&pool, "SELECT quantity_on_hand FROM products WHERE id = 'p1'").await, 8.0);
+
+        let disposition: String = sqlx::query_scalar(
+            "SELECT return_disposition FROM sales_line_items WHERE transaction_id = ?",
+        )
+        .bind(&completed.return_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(disposition, "damaged");
+
+        // Written-off goods keep their cost expensed
+        let snapshot = SnapshotRepository::new(pool.clone())
+            .find_by_transaction_id(Uuid::parse_str(&completed.return_id).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(snapshot.lines[0].unit_cost, None);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_by_number_and_non_returnable_sales() {
+        let pool = setup_test_db().await;
+        let sale_id = sell(&pool, None, cash("30.00")).await;
+        let service = ReturnService::new(pool.clone());
+
+        let number: String = sqlx::query_scalar("SELECT transaction_number FROM sales_transactions WHERE id = ?")
+            .bind(&sale_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let original = service.find_by_number("t1", &number).await.unwrap();
+        assert_eq!(original.id, sale_id);
+        assert_eq!(original.lines[0].returnable_quantity(), dec("2"));
+        assert!(matches!(
+            service.find_by_number("t2", &number).await,
+            Err(ReturnError::SaleNotFound(_))
+        ));
+
+        // Store credit refunds need a customer
+        let request = return_request(
+            &service,
+            &sale_id,
+            "1",
+            ReturnDisposition::Restock,
+            RefundDestination::StoreCredit,
+        )
+        .await;
+        assert!(matches!(service.process_return(request).await, Err(ReturnError::Validation(_))));
+
+        sqlx::query("UPDATE sales_transactions SET status = 'voided' WHERE id = ?")
+            .bind(&sale_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let request = return_request(
+            &service,
+            &sale_id,
+            "1",
+            ReturnDisposition::Restock,
+            RefundDestination::OriginalTenders,
+        )
+        .await;
+        assert!(matches!(service.process_return(request).await, Err(ReturnError::NotReturnable(_))));
+    }
+}
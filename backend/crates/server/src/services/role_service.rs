@@ -0,0 +1,865 @@
+/**
+ * Role Service
+ *
+ * Roles and their permissions, stored per tenant.
+ *
+ * - Every tenant has the built-in roles from `models::user::SYSTEM_ROLES`,
+ *   seeded with their built-in permissions the first time the tenant's
+ *   roles are read. Admins create custom roles from the permission catalog
+ *   and change the permissions of any role but admin.
+ * - A user has their primary role (users.role) everywhere, and may be
+ *   assigned further roles at every store or at one store.
+ * - Permissions are resolved at sign-in for the store signed in to and
+ *   carried in the token. Changing a role, an assignment or a user moves
+ *   the user's tokens_valid_after forward and ends their sessions; the
+ *   context middleware rejects tokens issued before it.
+ */
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::models::user::{get_permissions_for_role, SYSTEM_ROLES};
+
+/// The role that always has every permission
+const ADMIN_ROLE: &str = "admin";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum RoleError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<RoleError> for ApiError {
+    fn from(err: RoleError) -> Self {
+        match err {
+            RoleError::Validation(msg) => Self::bad_request(msg),
+            RoleError::NotFound(what) => Self::not_found(what),
+            RoleError::Conflict(msg) => Self::conflict(msg),
+            RoleError::Database(e) => Self::internal(format!("Failed to access roles: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct Permission {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Role {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Built in; cannot be renamed or deleted
+    pub is_system: bool,
+    /// Holders must sign in with a second factor
+    pub requires_two_factor: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    #[sqlx(skip)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+    #[serde(default)]
+    pub requires_two_factor: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub description: Option<String>,
+    /// Replaces the role's permissions
+    pub permissions: Option<Vec<String>>,
+    /// The only change the admin role accepts
+    pub requires_two_factor: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RoleAssignment {
+    pub id: String,
+    pub user_id: String,
+    pub role_id: String,
+    pub role_name: String,
+    /// `None` for every store
+    pub store_id: Option<String>,
+    pub created_at: String,
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssignRoleRequest {
+    pub role_id: String,
+    /// Defaults to every store
+    pub store_id: Option<String>,
+}
+
+const SELECT_ROLE: &str =
+    "SELECT id, name, description, is_system, requires_two_factor, created_at, updated_at FROM roles";
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct RoleService {
+    pool: SqlitePool,
+}
+
+impl RoleService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// The permissions roles can be given
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn permissions(&self) -> Result<Vec<Permission>, RoleError> {
+        let permissions = sqlx::query_as::<_, Permission>("SELECT name, description FROM permissions ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(permissions)
+    }
+
+    /// The tenant's roles, built-in first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn list(&self, tenant_id: &str) -> Result<Vec<Role>, RoleError> {
+        let mut conn = self.pool.acquire().await?;
+        ensure_system_roles(&mut conn, tenant_id).await?;
+
+        let mut roles = sqlx::query_as::<_, Role>(&format!(
+            "{SELECT_ROLE} WHERE tenant_id = ? ORDER BY is_system DESC, name"
+        ))
+        .bind(tenant_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for role in &mut roles {
+            role.permissions = role_permissions(&mut conn, &role.id).await?;
+        }
+        Ok(roles)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the role does not exist or the database read
+    /// fails.
+    pub async fn get(&self, tenant_id: &str, role_id: &str) -> Result<Role, RoleError> {
+        let mut conn = self.pool.acquire().await?;
+        get_role(&mut conn, tenant_id, role_id).await
+    }
+
+    /// Whether the tenant has a role by this name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn role_exists(&self, tenant_id: &str, name: &str) -> Result<bool, RoleError> {
+        let mut conn = self.pool.acquire().await?;
+        ensure_system_roles(&mut conn, tenant_id).await?;
+
+        let exists: Option<String> = sqlx::query_scalar("SELECT id FROM roles WHERE tenant_id = ? AND name = ?")
+            .bind(tenant_id)
+            .bind(name)
+            .fetch_optional(&mut *conn)
+            .await?;
+        Ok(exists.is_some())
+    }
+
+    /// Create a custom role
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid or taken, a permission is
+    /// unknown, or the database write fails.
+    pub async fn create(&self, tenant_id: &str, request: &CreateRoleRequest) -> Result<Role, RoleError> {
+        let name = request.name.trim().to_lowercase();
+        validate_role_name(&name)?;
+
+        let mut tx = self.pool.begin().await?;
+        ensure_system_roles(&mut tx, tenant_id).await?;
+        let permissions = validate_permissions(&mut tx, &request.permissions).await?;
+
+        let existing: Option<String> = sqlx::query_scalar("SELECT id FROM roles WHERE tenant_id = ? AND name = ?")
+            .bind(tenant_id)
+            .bind(&name)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if existing.is_some() {
+            return Err(RoleError::Conflict(format!("Role {name} already exists")));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO roles (id, tenant_id, name, description, is_system, requires_two_factor, created_at, updated_at)
+             VALUES (?, ?, ?, ?, 0, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(request.description.as_deref().map(str::trim).filter(|d| !d.is_empty()))
+        .bind(request.requires_two_factor)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        set_role_permissions(&mut tx, &id, &permissions).await?;
+
+        let role = get_role(&mut tx, tenant_id, &id).await?;
+        tx.commit().await?;
+
+        tracing::info!(tenant_id = %tenant_id, role = %role.name, "Role created");
+        Ok(role)
+    }
+
+    /// Change a role's description, permissions or second-factor requirement
+    ///
+    /// Holders sign in again when the permissions change; a new
+    /// second-factor requirement applies from their next sign-in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the role does not exist, is admin and the change
+    /// is not to its second-factor requirement, a permission is unknown, or
+    /// the database write fails.
+    pub async fn update(
+        &self,
+        tenant_id: &str,
+        role_id: &str,
+        request: &UpdateRoleRequest,
+    ) -> Result<Role, RoleError> {
+        let mut tx = self.pool.begin().await?;
+        let role = get_role(&mut tx, tenant_id, role_id).await?;
+        if role.name == ADMIN_ROLE && (request.description.is_some() || request.permissions.is_some()) {
+            return Err(RoleError::Validation(
+                "Only the admin role's two-factor requirement can be changed".to_string(),
+            ));
+        }
+
+        if let Some(description) = &request.description {
+            sqlx::query("UPDATE roles SET description = ? WHERE id = ?")
+                .bind(Some(description.trim()).filter(|d| !d.is_empty()))
+                .bind(role_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        if let Some(permissions) = &request.permissions {
+            let permissions = validate_permissions(&mut tx, permissions).await?;
+            set_role_permissions(&mut tx, role_id, &permissions).await?;
+            invalidate_role_holders(&mut tx, tenant_id, &role).await?;
+        }
+
+        if let Some(required) = request.requires_two_factor {
+            sqlx::query("UPDATE roles SET requires_two_factor = ? WHERE id = ?")
+                .bind(required)
+                .bind(role_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("UPDATE roles SET updated_at = ? WHERE id = ?")
+            .bind(Utc::now().to_rfc3339())
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let role = get_role(&mut tx, tenant_id, role_id).await?;
+        tx.commit().await?;
+
+        tracing::info!(tenant_id = %tenant_id, role = %role.name, "Role updated");
+        Ok(role)
+    }
+
+    /// Delete a custom role and its assignments
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the role does not exist, is built in or is still
+    /// a user's primary role, or the database write fails.
+    pub async fn delete(&self, tenant_id: &str, role_id: &str) -> Result<(), RoleError> {
+        let mut tx = self.pool.begin().await?;
+        let role = get_role(&mut tx, tenant_id, role_id).await?;
+        if role.is_system {
+            return Err(RoleError::Validation(format!("Built-in role {} cannot be deleted", role.name)));
+        }
+
+        let primary: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE tenant_id = ? AND role = ?")
+            .bind(tenant_id)
+            .bind(&role.name)
+            .fetch_one(&mut *tx)
+            .await?;
+        if primary > 0 {
+            return Err(RoleError::Conflict(format!(
+                "Role {} is the primary role of {primary} user(s)",
+                role.name
+            )));
+        }
+
+        invalidate_role_holders(&mut tx, tenant_id, &role).await?;
+        sqlx::query("DELETE FROM user_role_assignments WHERE role_id = ?")
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM role_permissions WHERE role_id = ?")
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM roles WHERE id = ?")
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        tracing::info!(tenant_id = %tenant_id, role = %role.name, "Role deleted");
+        Ok(())
+    }
+
+    /// Roles assigned to a user on top of their primary role
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn assignments(&self, tenant_id: &str, user_id: &str) -> Result<Vec<RoleAssignment>, RoleError> {
+        let assignments = sqlx::query_as::<_, RoleAssignment>(
+            r"
+            SELECT a.id, a.user_id, a.role_id, r.name AS role_name, a.store_id, a.created_at, a.created_by
+            FROM user_role_assignments a
+            JOIN roles r ON r.id = a.role_id
+            WHERE a.tenant_id = ? AND a.user_id = ?
+            ORDER BY r.name, a.store_id
+            ",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(assignments)
+    }
+
+    /// Assign a role to a user at every store or at one store
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user or role does not exist, the role is
+    /// already assigned there, or the database write fails.
+    pub async fn assign(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        request: &AssignRoleRequest,
+        assigned_by: &str,
+    ) -> Result<RoleAssignment, RoleError> {
+        let store_id = request.store_id.as_deref().map(str::trim).filter(|s| !s.is_empty());
+
+        let mut tx = self.pool.begin().await?;
+        let user: Option<String> = sqlx::query_scalar("SELECT id FROM users WHERE id = ? AND tenant_id = ?")
+            .bind(user_id)
+            .bind(tenant_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if user.is_none() {
+            return Err(RoleError::NotFound(format!("User not found: {user_id}")));
+        }
+        get_role(&mut tx, tenant_id, &request.role_id).await?;
+
+        let existing: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM user_role_assignments
+             WHERE tenant_id = ? AND user_id = ? AND role_id = ? AND store_id IS ?",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(&request.role_id)
+        .bind(store_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if existing.is_some() {
+            return Err(RoleError::Conflict("The role is already assigned there".to_string()));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO user_role_assignments (id, tenant_id, user_id, role_id, store_id, created_at, created_by)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(&request.role_id)
+        .bind(store_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(assigned_by)
+        .execute(&mut *tx)
+        .await?;
+        invalidate_user_tokens(&mut tx, tenant_id, user_id).await?;
+        tx.commit().await?;
+
+        let assignment = self
+            .assignments(tenant_id, user_id)
+            .await?
+            .into_iter()
+            .find(|assignment| assignment.id == id)
+            .ok_or_else(|| RoleError::NotFound(format!("Assignment not found: {id}")))?;
+
+        tracing::info!(
+            tenant_id = %tenant_id,
+            user_id = %user_id,
+            role = %assignment.role_name,
+            store_id = ?assignment.store_id,
+            "Role assigned"
+        );
+        Ok(assignment)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the assignment does not exist or the database
+    /// write fails.
+    pub async fn unassign(&self, tenant_id: &str, user_id: &str, assignment_id: &str) -> Result<(), RoleError> {
+        let mut tx = self.pool.begin().await?;
+        let deleted = sqlx::query("DELETE FROM user_role_assignments WHERE id = ? AND tenant_id = ? AND user_id = ?")
+            .bind(assignment_id)
+            .bind(tenant_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(RoleError::NotFound(format!("Assignment not found: {assignment_id}")));
+        }
+        invalidate_user_tokens(&mut tx, tenant_id, user_id).await?;
+        tx.commit().await?;
+
+        tracing::info!(tenant_id = %tenant_id, user_id = %user_id, assignment_id = %assignment_id, "Role unassigned");
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Permission Resolution
+// ============================================================================
+
+/// Seed the tenant's built-in roles that are missing
+///
+/// # Errors
+///
+/// Returns an error if the database write fails.
+pub async fn ensure_system_roles(conn: &mut SqliteConnection, tenant_id: &str) -> Result<(), sqlx::Error> {
+    let existing: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM roles WHERE tenant_id = ? AND is_system = 1")
+        .bind(tenant_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if usize::try_from(existing).unwrap_or_default() >= SYSTEM_ROLES.len() {
+        return Ok(());
+    }
+
+    let now = Utc::now().to_rfc3339();
+    for name in SYSTEM_ROLES {
+        let id = Uuid::new_v4().to_string();
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO roles (id, tenant_id, name, is_system, created_at, updated_at)
+             VALUES (?, ?, ?, 1, ?, ?)",
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(name)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        if inserted > 0 {
+            set_role_permissions(&mut *conn, &id, &get_permissions_for_role(name)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// A user's permissions at a store: those of their primary role and of the
+/// roles assigned to them everywhere or at that store
+///
+/// # Errors
+///
+/// Returns an error if the database read fails.
+pub async fn resolve_permissions(
+    pool: &SqlitePool,
+    tenant_id: &str,
+    user_id: &str,
+    role: &str,
+    store_id: Option<&str>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    ensure_system_roles(&mut conn, tenant_id).await?;
+
+    sqlx::query_scalar(
+        r"
+        SELECT DISTINCT rp.permission
+        FROM role_permissions rp
+        JOIN roles r ON r.id = rp.role_id
+        WHERE r.tenant_id = ?
+          AND (r.name = ? OR r.id IN (
+              SELECT role_id FROM user_role_assignments
+              WHERE tenant_id = ? AND user_id = ? AND (store_id IS NULL OR store_id = ?)
+          ))
+        ORDER BY rp.permission
+        ",
+    )
+    .bind(tenant_id)
+    .bind(role)
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind(store_id)
+    .fetch_all(&mut *conn)
+    .await
+}
+
+/// Whether a token issued at `issued_at` was issued after the user's
+/// permissions last changed
+///
+/// A token issued in the same instant as the change is not current.
+///
+/// # Errors
+///
+/// Returns an error if the database read fails.
+pub async fn token_is_current(
+    pool: &SqlitePool,
+    tenant_id: &str,
+    user_id: &str,
+    issued_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let valid_after: Option<Option<String>> =
+        sqlx::query_scalar("SELECT tokens_valid_after FROM users WHERE id = ? AND tenant_id = ?")
+            .bind(user_id)
+            .bind(tenant_id)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(valid_after
+        .flatten()
+        .and_then(|valid_after| DateTime::parse_from_rfc3339(&valid_after).ok())
+        .is_none_or(|valid_after| issued_at > valid_after))
+}
+
+/// Reject the user's current tokens and end their sessions
+///
+/// # Errors
+///
+/// Returns an error if the database write fails.
+pub async fn invalidate_user_tokens(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE users SET tokens_valid_after = ? WHERE id = ? AND tenant_id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(user_id)
+        .bind(tenant_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM sessions WHERE user_id = ? AND tenant_id = ?")
+        .bind(user_id)
+        .bind(tenant_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+async fn get_role(conn: &mut SqliteConnection, tenant_id: &str, role_id: &str) -> Result<Role, RoleError> {
+    let mut role = sqlx::query_as::<_, Role>(&format!("{SELECT_ROLE} WHERE id = ? AND tenant_id = ?"))
+        .bind(role_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| RoleError::NotFound(format!("Role not found: {role_id}")))?;
+    role.permissions = role_permissions(conn, role_id).await?;
+    Ok(role)
+}
+
+async fn role_permissions(conn: &mut SqliteConnection, role_id: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT permission FROM role_permissions WHERE role_id = ? ORDER BY permission")
+        .bind(role_id)
+        .fetch_all(&mut *conn)
+        .await
+}
+
+async fn set_role_permissions(
+    conn: &mut SqliteConnection,
+    role_id: &str,
+    permissions: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM role_permissions WHERE role_id = ?")
+        .bind(role_id)
+        .execute(&mut *conn)
+        .await?;
+    for permission in permissions {
+        sqlx::query("INSERT INTO role_permissions (role_id, permission) VALUES (?, ?)")
+            .bind(role_id)
+            .bind(permission)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Users holding the role as their primary role or by assignment sign in again
+async fn invalidate_role_holders(conn: &mut SqliteConnection, tenant_id: &str, role: &Role) -> Result<(), sqlx::Error> {
+    let holders: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM users WHERE tenant_id = ? AND role = ?
+         UNION
+         SELECT user_id FROM user_role_assignments WHERE tenant_id = ? AND role_id = ?",
+    )
+    .bind(tenant_id)
+    .bind(&role.name)
+    .bind(tenant_id)
+    .bind(&role.id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for user_id in &holders {
+        invalidate_user_tokens(conn, tenant_id, user_id).await?;
+    }
+    Ok(())
+}
+
+/// Known, distinct permissions; a role needs at least one
+async fn validate_permissions(conn: &mut SqliteConnection, permissions: &[String]) -> Result<Vec<String>, RoleError> {
+    let permissions: BTreeSet<String> = permissions.iter().map(|p| p.trim().to_string()).collect();
+    if permissions.is_empty() || permissions.contains("") {
+        return Err(RoleError::Validation("A role needs at least one permission".to_string()));
+    }
+
+    let known: BTreeSet<String> = sqlx::query_scalar("SELECT name FROM permissions")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .collect();
+    let unknown: Vec<&str> = permissions.difference(&known).map(String::as_str).collect();
+    if !unknown.is_empty() {
+        return Err(RoleError::Validation(format!("Unknown permission(s): {}", unknown.join(", "))));
+    }
+
+    Ok(permissions.into_iter().collect())
+}
+
+fn validate_role_name(name: &str) -> Result<(), RoleError> {
+    let valid = (2..=50).contains(&name.len())
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(RoleError::Validation(
+            "Role names are 2 to 50 lowercase letters, digits and underscores, starting with a letter".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE tenants (id TEXT PRIMARY KEY)",
+            "CREATE TABLE users (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                username TEXT NOT NULL,
+                role TEXT NOT NULL,
+                store_id TEXT
+            )",
+            "CREATE TABLE sessions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                token TEXT NOT NULL
+            )",
+            "INSERT INTO tenants (id) VALUES ('t1')",
+            "INSERT INTO users (id, tenant_id, username, role, store_id) VALUES
+                ('u1', 't1', 'carol', 'cashier', 's1'),
+                ('u2', 't1', 'ann', 'admin', NULL)",
+            "INSERT INTO sessions (id, tenant_id, user_id, token) VALUES ('sess1', 't1', 'u1', 'tok')",
+            include_str!("../../../../migrations/073_roles_and_permissions.sql"),
+            include_str!("../../../../migrations/074_manager_overrides.sql"),
+            include_str!("../../../../migrations/076_two_factor_auth.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_custom_role_assigned_per_store() {
+        let pool = setup_test_db().await;
+        let service = RoleService::new(pool.clone());
+
+        // Seeded by the migration with the built-in permissions
+        assert_eq!(
+            resolve_permissions(&pool, "t1", "u1", "cashier", Some("s1")).await.unwrap(),
+            vec!["access_sell", "process_return"]
+        );
+        // A new tenant is seeded on first use
+        assert_eq!(
+            resolve_permissions(&pool, "t2", "x", "inventory_clerk", None).await.unwrap(),
+            vec!["access_warehouse", "adjust_inventory", "receive_stock"]
+        );
+
+        let lead = service
+            .create(
+                "t1",
+                &CreateRoleRequest {
+                    name: "Shift_Lead".to_string(),
+                    description: Some("Runs the floor".to_string()),
+                    permissions: vec!["apply_discount".to_string(), "override_price".to_string()],
+                    requires_two_factor: false,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(lead.name, "shift_lead");
+        assert!(matches!(
+            service
+                .create(
+                    "t1",
+                    &CreateRoleRequest {
+                        name: "bad".to_string(),
+                        description: None,
+                        permissions: vec!["fly".to_string()],
+                        requires_two_factor: false,
+                    },
+                )
+                .await,
+            Err(RoleError::Validation(_))
+        ));
+
+        service
+            .assign("t1", "u1", &AssignRoleRequest { role_id: lead.id.clone(), store_id: Some("s2".to_string()) }, "u2")
+            .await
+            .unwrap();
+        assert!(matches!(
+            service
+                .assign("t1", "u1", &AssignRoleRequest { role_id: lead.id.clone(), store_id: Some("s2".to_string()) }, "u2")
+                .await,
+            Err(RoleError::Conflict(_))
+        ));
+
+        // Only at the store it was assigned at
+        assert_eq!(
+            resolve_permissions(&pool, "t1", "u1", "cashier", Some("s1")).await.unwrap(),
+            vec!["access_sell", "process_return"]
+        );
+        assert_eq!(
+            resolve_permissions(&pool, "t1", "u1", "cashier", Some("s2")).await.unwrap(),
+            vec!["access_sell", "apply_discount", "override_price", "process_return"]
+        );
+
+        // Not anyone's primary role, so it can go; its assignments go with it
+        service.delete("t1", &lead.id).await.unwrap();
+        assert!(service.assignments("t1", "u1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_role_change_invalidates_tokens() {
+        let pool = setup_test_db().await;
+        let service = RoleService::new(pool.clone());
+
+        let issued_at = Utc::now() - chrono::Duration::seconds(60);
+        assert!(token_is_current(&pool, "t1", "u1", issued_at).await.unwrap());
+
+        let roles = service.list("t1").await.unwrap();
+        let cashier = roles.iter().find(|role| role.name == "cashier").unwrap();
+        let admin = roles.iter().find(|role| role.name == "admin").unwrap();
+        assert!(cashier.is_system);
+
+        let updated = service
+            .update(
+                "t1",
+                &cashier.id,
+                &UpdateRoleRequest {
+                    description: None,
+                    permissions: Some(vec!["access_sell".to_string()]),
+                    requires_two_factor: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.permissions, vec!["access_sell"]);
+
+        // The cashier signs in again; the admin's token is untouched
+        assert!(!token_is_current(&pool, "t1", "u1", issued_at).await.unwrap());
+        assert!(token_is_current(&pool, "t1", "u1", Utc::now()).await.unwrap());
+        // To the millisecond, not the second: a token from just before the change is stale
+        let changed_at: String = sqlx::query_scalar("SELECT tokens_valid_after FROM users WHERE id = 'u1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let changed_at = DateTime::parse_from_rfc3339(&changed_at).unwrap().with_timezone(&Utc);
+        let millisecond = chrono::Duration::milliseconds(1);
+        assert!(!token_is_current(&pool, "t1", "u1", changed_at - millisecond).await.unwrap());
+        assert!(!token_is_current(&pool, "t1", "u1", changed_at).await.unwrap());
+        assert!(token_is_current(&pool, "t1", "u1", changed_at + millisecond).await.unwrap());
+        assert!(token_is_current(&pool, "t1", "u2", issued_at).await.unwrap());
+        let sessions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions").fetch_one(&pool).await.unwrap();
+        assert_eq!(sessions, 0);
+
+        let request = UpdateRoleRequest {
+            description: None,
+            permissions: Some(vec!["access_sell".to_string()]),
+            requires_two_factor: None,
+        };
+        assert!(matches!(service.update("t1", &admin.id, &request).await, Err(RoleError::Validation(_))));
+
+        // Requiring a second factor takes effect at the next sign-in
+        let request = UpdateRoleRequest { description: None, permissions: None, requires_two_factor: Some(true) };
+        let admin = service.update("t1", &admin.id, &request).await.unwrap();
+        assert!(admin.requires_two_factor);
+        assert!(token_is_current(&pool, "t1", "u2", issued_at).await.unwrap());
+        assert!(matches!(service.delete("t1", &cashier.id).await, Err(RoleError::Validation(_))));
+    }
+}
@@ -0,0 +1,1086 @@
+/**
+ * Shift Service
+ *
+ * Cash drawer accountability per station. A cashier opens a shift on a
+ * station with a counted float, may pay cash in or out of the drawer during
+ * the shift, and closes it with a blind count of the drawer by denomination:
+ * the expected cash is only revealed on the Z-report, after the count has
+ * been committed.
+ *
+ * Reports are built from the sales and returns rung up on the station while
+ * the shift was open:
+ * - X-report: running totals of an open shift, as often as needed
+ * - Z-report: closing totals with the counted cash and over/short variance,
+ *   stored on the shift so a reprint always shows the same figures
+ *
+ * Voided sales are counted but left out of the totals: their tenders were
+ * handed back from the drawer. Returns are negative rows, so refunds paid in
+ * cash reduce the expected cash. Every close records the variance in the
+ * audit log.
+ */
+
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::audit_logger::AuditLogger;
+use crate::services::checkout_service::{money_to_f64, TENDER_CASH};
+use crate::services::return_service::TRANSACTION_TYPE_RETURN;
+
+pub const SHIFT_STATUS_OPEN: &str = "open";
+pub const SHIFT_STATUS_CLOSED: &str = "closed";
+
+const COUNT_TYPE_OPENING: &str = "opening";
+const COUNT_TYPE_CLOSING: &str = "closing";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum ShiftError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Shift not found: {0}")]
+    NotFound(String),
+
+    #[error("Station not found: {0}")]
+    StationNotFound(String),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("Failed to read stored report: {0}")]
+    Report(#[from] serde_json::Error),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<ShiftError> for ApiError {
+    fn from(err: ShiftError) -> Self {
+        match err {
+            ShiftError::Validation(msg) => Self::bad_request(msg),
+            ShiftError::NotFound(id) => Self::not_found(format!("Shift not found: {id}")),
+            ShiftError::StationNotFound(id) => Self::not_found(format!("Station not found: {id}")),
+            ShiftError::Conflict(msg) => Self::conflict(msg),
+            ShiftError::Report(e) => Self::internal(format!("Failed to read shift report: {e}")),
+            ShiftError::Database(e) => Self::internal(format!("Failed to access shifts: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Cash moved in or out of the drawer outside of a sale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CashMovementType {
+    /// Change brought in from the safe, petty cash returned, ...
+    PayIn,
+    /// Supplier paid from the till, cash drop to the safe, ...
+    PayOut,
+}
+
+impl CashMovementType {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PayIn => "pay_in",
+            Self::PayOut => "pay_out",
+        }
+    }
+}
+
+/// Number of notes or coins of one denomination in the drawer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DenominationCount {
+    pub denomination: Decimal,
+    pub quantity: i64,
+}
+
+/// A shift to open
+#[derive(Debug, Clone)]
+pub struct OpenShift {
+    pub tenant_id: String,
+    pub station_id: String,
+    pub employee_id: String,
+    pub opening_float: Decimal,
+    /// Optional count of the float; must add up to `opening_float`
+    pub counts: Vec<DenominationCount>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Shift {
+    pub id: String,
+    pub store_id: String,
+    pub station_id: String,
+    pub status: String,
+    pub opened_by: String,
+    pub opened_at: String,
+    pub opening_float: f64,
+    pub closed_by: Option<String>,
+    pub closed_at: Option<String>,
+    pub expected_cash: Option<f64>,
+    pub counted_cash: Option<f64>,
+    /// counted - expected; positive when the drawer is over
+    pub variance: Option<f64>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CashMovement {
+    pub id: String,
+    pub movement_type: String,
+    pub amount: f64,
+    pub reason: String,
+    pub employee_id: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TenderTotal {
+    pub method: String,
+    /// Payments and refunds taken with this tender
+    pub count: i64,
+    /// Net of refunds
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DenominationLine {
+    pub denomination: f64,
+    pub quantity: i64,
+    pub amount: f64,
+}
+
+/// X-report (`report_type` "X") or Z-report ("Z") of a shift
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShiftReport {
+    pub report_type: String,
+    pub shift_id: String,
+    pub store_id: String,
+    pub station_id: String,
+    pub opened_by: String,
+    pub opened_at: String,
+    pub closed_by: Option<String>,
+    pub closed_at: Option<String>,
+    pub generated_at: String,
+    pub sales_count: i64,
+    pub returns_count: i64,
+    pub voided_count: i64,
+    pub gross_sales: f64,
+    pub returns_total: f64,
+    pub net_sales: f64,
+    pub discount_total: f64,
+    pub tax_total: f64,
+    pub tenders: Vec<TenderTotal>,
+    pub opening_float: f64,
+    /// Cash taken less cash refunded
+    pub cash_sales: f64,
+    pub pay_ins: f64,
+    pub pay_outs: f64,
+    pub expected_cash: f64,
+    pub counted_cash: Option<f64>,
+    pub variance: Option<f64>,
+    pub denominations: Vec<DenominationLine>,
+}
+
+/// Shift totals in exact decimals, before they are written as a report
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ShiftTotals {
+    sales_count: i64,
+    returns_count: i64,
+    voided_count: i64,
+    gross_sales: Decimal,
+    returns_total: Decimal,
+    discount_total: Decimal,
+    tax_total: Decimal,
+    tenders: BTreeMap<String, (i64, Decimal)>,
+    pay_ins: Decimal,
+    pay_outs: Decimal,
+}
+
+impl ShiftTotals {
+    fn cash_sales(&self) -> Decimal {
+        self.tenders.get(TENDER_CASH).map_or(Decimal::ZERO, |(_, amount)| *amount)
+    }
+
+    fn expected_cash(&self, opening_float: Decimal) -> Decimal {
+        opening_float + self.cash_sales() + self.pay_ins - self.pay_outs
+    }
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+const SELECT_SHIFT: &str = "SELECT id, store_id, station_id, status, opened_by, opened_at, opening_float,
+        closed_by, closed_at, expected_cash, counted_cash, variance, notes
+     FROM shifts";
+
+pub struct ShiftService {
+    pool: SqlitePool,
+}
+
+impl ShiftService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Open a shift on a station
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the station is unknown or inactive, it already has
+    /// an open shift, the float is negative or does not match its count, or
+    /// the database write fails.
+    pub async fn open(&self, request: OpenShift) -> Result<Shift, ShiftError> {
+        if request.opening_float < Decimal::ZERO {
+            return Err(ShiftError::Validation("Opening float cannot be negative".to_string()));
+        }
+        if !request.counts.is_empty() {
+            let counted = count_total(&request.counts)?;
+            if counted != request.opening_float {
+                return Err(ShiftError::Validation(format!(
+                    "Float count {counted} does not match the opening float {}",
+                    request.opening_float
+                )));
+            }
+        }
+
+        let station: Option<(String, bool)> =
+            sqlx::query_as("SELECT store_id, is_active FROM stations WHERE id = ? AND tenant_id = ?")
+                .bind(&request.station_id)
+                .bind(&request.tenant_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let store_id = match station {
+            Some((store_id, true)) => store_id,
+            Some((_, false)) => {
+                return Err(ShiftError::Validation("Station is not active".to_string()));
+            }
+            None => return Err(ShiftError::StationNotFound(request.station_id)),
+        };
+
+        if let Some(open) = self.current(&request.tenant_id, &request.station_id).await? {
+            return Err(ShiftError::Conflict(format!(
+                "Station already has an open shift ({}) opened by {}",
+                open.id, open.opened_by
+            )));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO shifts (
+                id, tenant_id, store_id, station_id, status, opened_by, opened_at,
+                opening_float, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&id)
+        .bind(&request.tenant_id)
+        .bind(&store_id)
+        .bind(&request.station_id)
+        .bind(SHIFT_STATUS_OPEN)
+        .bind(&request.employee_id)
+        .bind(&now)
+        .bind(money_to_f64(request.opening_float))
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        insert_counts(&mut tx, &id, COUNT_TYPE_OPENING, &request.counts).await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            shift_id = %id,
+            station_id = %request.station_id,
+            opening_float = %request.opening_float,
+            "Shift opened"
+        );
+
+        self.get(&request.tenant_id, &id).await
+    }
+
+    /// Open shift of a station, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn current(&self, tenant_id: &str, station_id: &str) -> Result<Option<Shift>, ShiftError> {
+        let shift = sqlx::query_as::<_, Shift>(&format!(
+            "{SELECT_SHIFT} WHERE tenant_id = ? AND station_id = ? AND status = ?"
+        ))
+        .bind(tenant_id)
+        .bind(station_id)
+        .bind(SHIFT_STATUS_OPEN)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(shift)
+    }
+
+    /// # Errors
+    ///
+    /// Returns `NotFound` if the tenant has no such shift.
+    pub async fn get(&self, tenant_id: &str, id: &str) -> Result<Shift, ShiftError> {
+        sqlx::query_as::<_, Shift>(&format!("{SELECT_SHIFT} WHERE id = ? AND tenant_id = ?"))
+            .bind(id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ShiftError::NotFound(id.to_string()))
+    }
+
+    /// Shifts of a store, most recent first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn list(
+        &self,
+        tenant_id: &str,
+        store_id: Option<&str>,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Shift>, ShiftError> {
+        let shifts = sqlx::query_as::<_, Shift>(&format!(
+            "{SELECT_SHIFT}
+             WHERE tenant_id = ? AND (? IS NULL OR store_id = ?) AND (? IS NULL OR status = ?)
+             ORDER BY opened_at DESC
+             LIMIT ?"
+        ))
+        .bind(tenant_id)
+        .bind(store_id)
+        .bind(store_id)
+        .bind(status)
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(shifts)
+    }
+
+    /// Record a pay-in or pay-out on an open shift
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shift is not open, the amount is not positive,
+    /// no reason is given, or the database write fails.
+    pub async fn record_cash_movement(
+        &self,
+        tenant_id: &str,
+        shift_id: &str,
+        movement_type: CashMovementType,
+        amount: Decimal,
+        reason: &str,
+        employee_id: &str,
+    ) -> Result<CashMovement, ShiftError> {
+        if amount <= Decimal::ZERO {
+            return Err(ShiftError::Validation("Amount must be positive".to_string()));
+        }
+        if reason.trim().is_empty() {
+            return Err(ShiftError::Validation("A reason is required".to_string()));
+        }
+        ensure_open(&self.get(tenant_id, shift_id).await?)?;
+
+        let movement = CashMovement {
+            id: Uuid::new_v4().to_string(),
+            movement_type: movement_type.as_str().to_string(),
+            amount: money_to_f64(amount),
+            reason: reason.trim().to_string(),
+            employee_id: employee_id.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+        };
+
+        sqlx::query(
+            r"
+            INSERT INTO shift_cash_movements (
+                id, tenant_id, shift_id, movement_type, amount, reason, employee_id, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&movement.id)
+        .bind(tenant_id)
+        .bind(shift_id)
+        .bind(&movement.movement_type)
+        .bind(movement.amount)
+        .bind(&movement.reason)
+        .bind(&movement.employee_id)
+        .bind(&movement.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(movement)
+    }
+
+    /// Pay-ins and pay-outs of a shift, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn cash_movements(&self, tenant_id: &str, shift_id: &str) -> Result<Vec<CashMovement>, ShiftError> {
+        let movements = sqlx::query_as::<_, CashMovement>(
+            "SELECT id, movement_type, amount, reason, employee_id, created_at
+             FROM shift_cash_movements
+             WHERE tenant_id = ? AND shift_id = ?
+             ORDER BY created_at ASC",
+        )
+        .bind(tenant_id)
+        .bind(shift_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(movements)
+    }
+
+    /// Running totals of an open shift
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shift is unknown or already closed (its
+    /// Z-report is the final word), or a query fails.
+    pub async fn x_report(&self, tenant_id: &str, shift_id: &str) -> Result<ShiftReport, ShiftError> {
+        let shift = self.get(tenant_id, shift_id).await?;
+        ensure_open(&shift)?;
+
+        let now = Utc::now().to_rfc3339();
+        let mut conn = self.pool.acquire().await?;
+        let totals = shift_totals(&mut conn, tenant_id, &shift, &now).await?;
+
+        Ok(build_report("X", &shift, &totals, &now, None))
+    }
+
+    /// Close a shift with the blind count of its drawer
+    ///
+    /// The count, the expected cash and the variance are written together
+    /// with the Z-report and the over/short entry in the audit log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shift is not open, the count is empty or
+    /// invalid, or the database write fails.
+    pub async fn close(
+        &self,
+        tenant_id: &str,
+        shift_id: &str,
+        employee_id: &str,
+        counts: &[DenominationCount],
+        notes: Option<&str>,
+    ) -> Result<ShiftReport, ShiftError> {
+        if counts.is_empty() {
+            return Err(ShiftError::Validation("Count the drawer before closing the shift".to_string()));
+        }
+        let counted = count_total(counts)?;
+        let mut shift = self.get(tenant_id, shift_id).await?;
+        ensure_open(&shift)?;
+
+        let now = Utc::now().to_rfc3339();
+        shift.closed_by = Some(employee_id.to_string());
+        shift.closed_at = Some(now.clone());
+
+        let mut tx = self.pool.begin().await?;
+
+        let totals = shift_totals(&mut tx, tenant_id, &shift, &now).await?;
+        let report = build_report("Z", &shift, &totals, &now, Some(counts));
+        let expected = totals.expected_cash(to_decimal(shift.opening_float));
+        let variance = counted - expected;
+
+        let closed = sqlx::query(
+            r"
+            UPDATE shifts SET
+                status = ?, closed_by = ?, closed_at = ?, expected_cash = ?, counted_cash = ?,
+                variance = ?, notes = ?, z_report = ?, updated_at = ?
+            WHERE id = ? AND tenant_id = ? AND status = ?
+            ",
+        )
+        .bind(SHIFT_STATUS_CLOSED)
+        .bind(employee_id)
+        .bind(&now)
+        .bind(money_to_f64(expected))
+        .bind(money_to_f64(counted))
+        .bind(money_to_f64(variance))
+        .bind(notes)
+        .bind(serde_json::to_string(&report)?)
+        .bind(&now)
+        .bind(shift_id)
+        .bind(tenant_id)
+        .bind(SHIFT_STATUS_OPEN)
+        .execute(&mut *tx)
+        .await?;
+
+        if closed.rows_affected() == 0 {
+            return Err(ShiftError::Conflict("Shift was closed by another register".to_string()));
+        }
+
+        insert_counts(&mut tx, shift_id, COUNT_TYPE_CLOSING, counts).await?;
+
+        AuditLogger::log_cash_variance(
+            &mut tx,
+            shift_id,
+            &shift.station_id,
+            &expected.to_string(),
+            &counted.to_string(),
+            &variance.to_string(),
+            employee_id,
+            &shift.store_id,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            shift_id = %shift_id,
+            expected = %expected,
+            counted = %counted,
+            variance = %variance,
+            "Shift closed"
+        );
+
+        Ok(report)
+    }
+
+    /// Z-report stored when the shift was closed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shift is unknown or still open.
+    pub async fn z_report(&self, tenant_id: &str, shift_id: &str) -> Result<ShiftReport, ShiftError> {
+        let report: Option<Option<String>> =
+            sqlx::query_scalar("SELECT z_report FROM shifts WHERE id = ? AND tenant_id = ?")
+                .bind(shift_id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        match report {
+            None => Err(ShiftError::NotFound(shift_id.to_string())),
+            Some(None) => Err(ShiftError::Conflict(
+                "Shift is still open; print an X-report instead".to_string(),
+            )),
+            Some(Some(json)) => Ok(serde_json::from_str(&json)?),
+        }
+    }
+}
+
+fn ensure_open(shift: &Shift) -> Result<(), ShiftError> {
+    if shift.status == SHIFT_STATUS_OPEN {
+        Ok(())
+    } else {
+        Err(ShiftError::Conflict(format!("Shift {} is closed", shift.id)))
+    }
+}
+
+fn to_decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(2)
+}
+
+/// Value of a drawer count
+fn count_total(counts: &[DenominationCount]) -> Result<Decimal, ShiftError> {
+    counts.iter().try_fold(Decimal::ZERO, |total, count| {
+        if count.denomination <= Decimal::ZERO {
+            return Err(ShiftError::Validation("Denomination must be positive".to_string()));
+        }
+        if count.quantity < 0 {
+            return Err(ShiftError::Validation("Count cannot be negative".to_string()));
+        }
+        Ok(total + count.denomination * Decimal::from(count.quantity))
+    })
+}
+
+/// Sales, tenders and cash movements of a shift up to `until`
+async fn shift_totals(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    shift: &Shift,
+    until: &str,
+) -> Result<ShiftTotals, ShiftError> {
+    let mut totals = ShiftTotals::default();
+
+    let transactions: Vec<(Option<String>, String, f64, f64, f64)> = sqlx::query_as(
+        "SELECT transaction_type, status, total_amount, discount_amount, tax_amount
+         FROM sales_transactions
+         WHERE tenant_id = ? AND station_id = ? AND created_at >= ? AND created_at <= ?",
+    )
+    .bind(tenant_id)
+    .bind(&shift.station_id)
+    .bind(&shift.opened_at)
+    .bind(until)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (transaction_type, status, total, discount, tax) in transactions {
+        if status == "voided" {
+            totals.voided_count += 1;
+            continue;
+        }
+        if transaction_type.as_deref() == Some(TRANSACTION_TYPE_RETURN) {
+            totals.returns_count += 1;
+            totals.returns_total -= to_decimal(total);
+        } else {
+            totals.sales_count += 1;
+            totals.gross_sales += to_decimal(total);
+        }
+        totals.discount_total += to_decimal(discount);
+        totals.tax_total += to_decimal(tax);
+    }
+
+    // Tenders recorded per payment; sales from before split tenders only
+    // carry their payment method
+    let tenders: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT p.method, p.amount
+         FROM sales_payments p
+         JOIN sales_transactions t ON t.id = p.transaction_id
+         WHERE t.tenant_id = ? AND t.station_id = ? AND t.created_at >= ? AND t.created_at <= ?
+           AND t.status != 'voided'
+         UNION ALL
+         SELECT COALESCE(t.payment_method, 'unknown'), t.total_amount
+         FROM sales_transactions t
+         WHERE t.tenant_id = ? AND t.station_id = ? AND t.created_at >= ? AND t.created_at <= ?
+           AND t.status != 'voided'
+           AND NOT EXISTS (SELECT 1 FROM sales_payments p WHERE p.transaction_id = t.id)",
+    )
+    .bind(tenant_id)
+    .bind(&shift.station_id)
+    .bind(&shift.opened_at)
+    .bind(until)
+    .bind(tenant_id)
+    .bind(&shift.station_id)
+    .bind(&shift.opened_at)
+    .bind(until)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (method, amount) in tenders {
+        let entry = totals.tenders.entry(method).or_insert((0, Decimal::ZERO));
+        entry.0 += 1;
+        entry.1 += to_decimal(amount);
+    }
+
+    let movements: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT movement_type, amount FROM shift_cash_movements WHERE tenant_id = ? AND shift_id = ?",
+    )
+    .bind(tenant_id)
+    .bind(&shift.id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (movement_type, amount) in movements {
+        if movement_type == CashMovementType::PayIn.as_str() {
+            totals.pay_ins += to_decimal(amount);
+        } else {
+            totals.pay_outs += to_decimal(amount);
+        }
+    }
+
+    Ok(totals)
+}
+
+fn build_report(
+    report_type: &str,
+    shift: &Shift,
+    totals: &ShiftTotals,
+    generated_at: &str,
+    counts: Option<&[DenominationCount]>,
+) -> ShiftReport {
+    let opening_float = to_decimal(shift.opening_float);
+    let expected = totals.expected_cash(opening_float);
+    let counted = counts.map(|counts| {
+        counts
+            .iter()
+            .map(|count| count.denomination * Decimal::from(count.quantity))
+            .sum::<Decimal>()
+    });
+
+    ShiftReport {
+        report_type: report_type.to_string(),
+        shift_id: shift.id.clone(),
+        store_id: shift.store_id.clone(),
+        station_id: shift.station_id.clone(),
+        opened_by: shift.opened_by.clone(),
+        opened_at: shift.opened_at.clone(),
+        closed_by: shift.closed_by.clone(),
+        closed_at: shift.closed_at.clone(),
+        generated_at: generated_at.to_string(),
+        sales_count: totals.sales_count,
+        returns_count: totals.returns_count,
+        voided_count: totals.voided_count,
+        gross_sales: money_to_f64(totals.gross_sales),
+        returns_total: money_to_f64(totals.returns_total),
+        net_sales: money_to_f64(totals.gross_sales - totals.returns_total),
+        discount_total: money_to_f64(totals.discount_total),
+        tax_total: money_to_f64(totals.tax_total),
+        tenders: totals
+            .tenders
+            .iter()
+            .map(|(method, (count, amount))| TenderTotal {
+                method: method.clone(),
+                count: *count,
+                amount: money_to_f64(*amount),
+            })
+            .collect(),
+        opening_float: money_to_f64(opening_float),
+        cash_sales: money_to_f64(totals.cash_sales()),
+        pay_ins: money_to_f64(totals.pay_ins),
+        pay_outs: money_to_f64(totals.pay_outs),
+        expected_cash: money_to_f64(expected),
+        counted_cash: counted.map(money_to_f64),
+        variance: counted.map(|counted| money_to_f64(counted - expected)),
+        denominations: counts
+            .unwrap_or_default()
+            .iter()
+            .map(|count| DenominationLine {
+                denomination: count.denomination.to_f64().unwrap_or_default(),
+                quantity: count.quantity,
+                amount: money_to_f64(count.denomination * Decimal::from(count.quantity)),
+            })
+            .collect(),
+    }
+}
+
+async fn insert_counts(
+    conn: &mut SqliteConnection,
+    shift_id: &str,
+    count_type: &str,
+    counts: &[DenominationCount],
+) -> Result<(), ShiftError> {
+    let now = Utc::now().to_rfc3339();
+
+    for count in counts {
+        sqlx::query(
+            r"
+            INSERT INTO shift_cash_counts (id, shift_id, count_type, denomination, quantity, amount, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(shift_id)
+        .bind(count_type)
+        .bind(count.denomination.to_f64().unwrap_or_default())
+        .bind(count.quantity)
+        .bind(money_to_f64(count.denomination * Decimal::from(count.quantity)))
+        .bind(&now)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE stations (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                is_active BOOLEAN NOT NULL DEFAULT 1
+            )",
+            "CREATE TABLE shifts (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                station_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                opened_by TEXT NOT NULL,
+                opened_at TEXT NOT NULL,
+                opening_float REAL NOT NULL,
+                closed_by TEXT,
+                closed_at TEXT,
+                expected_cash REAL,
+                counted_cash REAL,
+                variance REAL,
+                notes TEXT,
+                z_report TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE TABLE shift_cash_movements (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                shift_id TEXT NOT NULL,
+                movement_type TEXT NOT NULL,
+                amount REAL NOT NULL,
+                reason TEXT NOT NULL,
+                employee_id TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE shift_cash_counts (
+                id TEXT PRIMARY KEY,
+                shift_id TEXT NOT NULL,
+                count_type TEXT NOT NULL,
+                denomination REAL NOT NULL,
+                quantity INTEGER NOT NULL,
+                amount REAL NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE sales_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                station_id TEXT,
+                total_amount REAL NOT NULL,
+                discount_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                payment_method TEXT,
+                status TEXT NOT NULL,
+                transaction_type TEXT NOT NULL DEFAULT 'sale',
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE sales_payments (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL,
+                method TEXT NOT NULL,
+                amount REAL NOT NULL
+            )",
+            "CREATE TABLE audit_log (
+                id TEXT PRIMARY KEY,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                user_id TEXT,
+                employee_id TEXT,
+                changes TEXT,
+                ip_address TEXT,
+                user_agent TEXT,
+                is_offline BOOLEAN NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                store_id TEXT NOT NULL
+            )",
+            "INSERT INTO stations (id, tenant_id, store_id) VALUES ('reg-1', 't1', 's1'), ('reg-2', 't1', 's1')",
+            "INSERT INTO stations (id, tenant_id, store_id, is_active) VALUES ('reg-3', 't1', 's1', 0)",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn count(denomination: &str, quantity: i64) -> DenominationCount {
+        DenominationCount { denomination: dec(denomination), quantity }
+    }
+
+    fn open_request(station_id: &str, opening_float: &str) -> OpenShift {
+        OpenShift {
+            tenant_id: "t1".to_string(),
+            station_id: station_id.to_string(),
+            employee_id: "u1".to_string(),
+            opening_float: dec(opening_float),
+            counts: Vec::new(),
+        }
+    }
+
+    /// Record a transaction on a station with its tenders
+    async fn ring_up(
+        pool: &SqlitePool,
+        station_id: &str,
+        transaction_type: &str,
+        status: &str,
+        total: f64,
+        tax: f64,
+        payments: &[(&str, f64)],
+    ) {
+        let id = Uuid::new_v4().to_string();
+        let method = if payments.len() == 1 { payments[0].0 } else { "split" };
+        sqlx::query(
+            "INSERT INTO sales_transactions (id, tenant_id, station_id, total_amount, discount_amount,
+             tax_amount, payment_method, status, transaction_type, created_at)
+             VALUES (?, 't1', ?, ?, 0, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(station_id)
+        .bind(total)
+        .bind(tax)
+        .bind(method)
+        .bind(status)
+        .bind(transaction_type)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .unwrap();
+
+        for (method, amount) in payments {
+            sqlx::query("INSERT INTO sales_payments (id, transaction_id, method, amount) VALUES (?, ?, ?, ?)")
+                .bind(Uuid::new_v4().to_string())
+                .bind(&id)
+                .bind(method)
+                .bind(amount)
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_shift_one_per_station() {
+        let pool = setup_test_db().await;
+        let service = ShiftService::new(pool);
+
+        let shift = service.open(open_request("reg-1", "150.00")).await.unwrap();
+        assert_eq!(shift.status, SHIFT_STATUS_OPEN);
+        assert_eq!(shift.store_id, "s1");
+        assert_eq!(shift.opening_float, 150.0);
+        assert_eq!(service.current("t1", "reg-1").await.unwrap().unwrap().id, shift.id);
+
+        assert!(matches!(
+            service.open(open_request("reg-1", "100.00")).await,
+            Err(ShiftError::Conflict(_))
+        ));
+        assert!(matches!(
+            service.open(open_request("reg-9", "100.00")).await,
+            Err(ShiftError::StationNotFound(_))
+        ));
+        assert!(matches!(
+            service.open(open_request("reg-3", "100.00")).await,
+            Err(ShiftError::Validation(_))
+        ));
+
+        // A counted float must match the float declared
+        let mut counted = open_request("reg-2", "100.00");
+        counted.counts = vec![count("20", 4), count("5", 3)];
+        assert!(matches!(service.open(counted.clone()).await, Err(ShiftError::Validation(_))));
+        counted.counts.push(count("1", 5));
+        assert!(service.open(counted).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_x_and_z_reports_group_by_tender_and_record_variance() {
+        let pool = setup_test_db().await;
+        let service = ShiftService::new(pool.clone());
+        let shift = service.open(open_request("reg-1", "150.00")).await.unwrap();
+
+        ring_up(&pool, "reg-1", "sale", "completed", 22.58, 2.60, &[("cash", 22.58)]).await;
+        ring_up(&pool, "reg-1", "sale", "completed", 10.00, 1.15, &[("cash", 5.0), ("gift_card", 5.0)]).await;
+        ring_up(&pool, "reg-1", "sale", "completed", 40.00, 4.60, &[("card", 40.0)]).await;
+        ring_up(&pool, "reg-1", "return", "completed", -11.29, -1.30, &[("cash", -11.29)]).await;
+        ring_up(&pool, "reg-1", "sale", "voided", 7.00, 0.81, &[("cash", 7.0)]).await;
+        ring_up(&pool, "reg-2", "sale", "completed", 99.00, 0.0, &[("cash", 99.0)]).await;
+
+        service
+            .record_cash_movement("t1", &shift.id, CashMovementType::PayIn, dec("20"), "Change from safe", "u1")
+            .await
+            .unwrap();
+        service
+            .record_cash_movement("t1", &shift.id, CashMovementType::PayOut, dec("15.50"), "Window cleaner", "u1")
+            .await
+            .unwrap();
+        assert!(matches!(
+            service
+                .record_cash_movement("t1", &shift.id, CashMovementType::PayOut, dec("5"), " ", "u1")
+                .await,
+            Err(ShiftError::Validation(_))
+        ));
+
+        let x = service.x_report("t1", &shift.id).await.unwrap();
+        assert_eq!(x.report_type, "X");
+        assert_eq!((x.sales_count, x.returns_count, x.voided_count), (3, 1, 1));
+        assert_eq!(x.gross_sales, 72.58);
+        assert_eq!(x.returns_total, 11.29);
+        assert_eq!(x.net_sales, 61.29);
+        assert_eq!(x.tax_total, 7.05);
+        assert_eq!(
+            x.tenders,
+            vec![
+                TenderTotal { method: "card".to_string(), count: 1, amount: 40.0 },
+                TenderTotal { method: "cash".to_string(), count: 3, amount: 16.29 },
+                TenderTotal { method: "gift_card".to_string(), count: 1, amount: 5.0 },
+            ]
+        );
+        // 150 float + 16.29 cash + 20 in - 15.50 out
+        assert_eq!(x.expected_cash, 170.79);
+        assert_eq!(x.counted_cash, None);
+
+        // Drawer holds 170.29: fifty cents short
+        let counts = [count("50", 2), count("20", 3), count("5", 2), count("0.25", 1), count("0.01", 4)];
+        let z = service
+            .close("t1", &shift.id, "u2", &counts, Some("Busy day"))
+            .await
+            .unwrap();
+        assert_eq!(z.report_type, "Z");
+        assert_eq!(z.expected_cash, 170.79);
+        assert_eq!(z.counted_cash, Some(170.29));
+        assert_eq!(z.variance, Some(-0.5));
+        assert_eq!(z.denominations.len(), 5);
+        assert_eq!(z.closed_by.as_deref(), Some("u2"));
+
+        let closed = service.get("t1", &shift.id).await.unwrap();
+        assert_eq!(closed.status, SHIFT_STATUS_CLOSED);
+        assert_eq!(closed.variance, Some(-0.5));
+        assert_eq!(service.z_report("t1", &shift.id).await.unwrap(), z);
+
+        let (operation, changes): (String, String) =
+            sqlx::query_as("SELECT operation, changes FROM audit_log WHERE entity_type = 'shift' AND entity_id = ?")
+                .bind(&shift.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(operation, "cash_variance");
+        let changes: serde_json::Value = serde_json::from_str(&changes).unwrap();
+        assert_eq!(changes["variance"], "-0.50");
+        assert_eq!(changes["station_id"], "reg-1");
+
+        // A closed shift only has its Z-report, and cannot be closed twice
+        assert!(matches!(service.x_report("t1", &shift.id).await, Err(ShiftError::Conflict(_))));
+        assert!(matches!(
+            service.close("t1", &shift.id, "u2", &counts, None).await,
+            Err(ShiftError::Conflict(_))
+        ));
+        assert!(service.current("t1", "reg-1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_close_requires_a_valid_count() {
+        let pool = setup_test_db().await;
+        let service = ShiftService::new(pool);
+        let shift = service.open(open_request("reg-1", "0")).await.unwrap();
+
+        assert!(matches!(
+            service.close("t1", &shift.id, "u1", &[], None).await,
+            Err(ShiftError::Validation(_))
+        ));
+        assert!(matches!(
+            service.close("t1", &shift.id, "u1", &[count("20", -1)], None).await,
+            Err(ShiftError::Validation(_))
+        ));
+        assert!(matches!(
+            service.z_report("t1", &shift.id).await,
+            Err(ShiftError::Conflict(_))
+        ));
+
+        let z = service.close("t1", &shift.id, "u1", &[count("20", 0)], None).await.unwrap();
+        assert_eq!(z.variance, Some(0.0));
+    }
+}
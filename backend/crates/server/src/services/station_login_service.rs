@@ -0,0 +1,543 @@
+/**
+ * Station Login Service
+ *
+ * Quick user switching on a shared register:
+ * - A user signs in at a station with their PIN or badge instead of their
+ *   username and password. The PIN and badge hashes are the ones manager
+ *   approvals use (see `override_service::credential`).
+ * - The sign-in is bound to the station and its store; the user must be
+ *   allowed at that station and have `access_sell` there.
+ * - Too many failed PINs or badges in a row lock the station's PIN pad.
+ *   Manager approvals entered at the station count towards the same lockout.
+ * - The session ends when it has been idle for the store's idle timeout;
+ *   the context middleware checks it on every request.
+ */
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::models::user::{StationLoginRequest, User};
+use crate::services::override_service::{credential, Credential};
+use crate::services::role_service;
+
+/// Failed attempts in a row, within `ATTEMPT_WINDOW_MINUTES`, that lock a station
+pub(crate) const MAX_FAILED_ATTEMPTS: i64 = 5;
+const ATTEMPT_WINDOW_MINUTES: i64 = 15;
+const LOCKOUT_MINUTES: i64 = 5;
+
+/// Minutes a station session may be idle, by store, tenant or globally
+pub const SETTING_IDLE_TIMEOUT: &str = "auth.station_idle_timeout_minutes";
+const DEFAULT_IDLE_TIMEOUT_MINUTES: i64 = 10;
+
+/// Activity is recorded at most this often per session
+const ACTIVITY_RESOLUTION_SECONDS: i64 = 30;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum StationLoginError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("The PIN or badge was not recognized")]
+    InvalidCredentials,
+
+    #[error("Too many failed attempts at this station; try again after {0}")]
+    LockedOut(String),
+
+    #[error("Not permitted: {0}")]
+    Forbidden(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<StationLoginError> for ApiError {
+    fn from(err: StationLoginError) -> Self {
+        match err {
+            StationLoginError::Validation(msg) => Self::bad_request(msg),
+            StationLoginError::NotFound(what) => Self::not_found(what),
+            StationLoginError::InvalidCredentials => Self::unauthorized(err.to_string()),
+            StationLoginError::LockedOut(_) => Self::with_code(429, err.to_string(), "LOCKED_OUT"),
+            StationLoginError::Forbidden(msg) => Self::forbidden(msg),
+            StationLoginError::Database(e) => Self::internal(format!("Failed to sign in: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A user signed in at a station, before their token is issued
+#[derive(Debug, Clone)]
+pub struct StationSignIn {
+    pub user: User,
+    pub station_id: String,
+    pub store_id: String,
+    /// pin or badge
+    pub method: &'static str,
+    pub permissions: Vec<String>,
+    pub idle_timeout_minutes: i64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct Station {
+    id: String,
+    store_id: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct Attempts {
+    failed_attempts: i64,
+    first_failed_at: Option<String>,
+    locked_until: Option<String>,
+}
+
+const SELECT_USER: &str = "SELECT id, tenant_id, username, email, password_hash, display_name, role, first_name, last_name,
+     store_id, station_policy, station_id, is_active, created_at, updated_at
+     FROM users";
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct StationLoginService {
+    pool: SqlitePool,
+    /// Keys the PIN and badge hashes
+    secret: String,
+}
+
+impl StationLoginService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool, secret: String) -> Self {
+        Self { pool, secret }
+    }
+
+    /// Identify the user by PIN or badge at a station
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the station does not exist or is locked, the PIN
+    /// or badge is not an active user's, the user may not sign in at the
+    /// station, or the database access fails.
+    pub async fn authenticate(
+        &self,
+        tenant_id: &str,
+        request: &StationLoginRequest,
+    ) -> Result<StationSignIn, StationLoginError> {
+        let Credential { method, column, hash } =
+            credential(&self.secret, tenant_id, request.pin.as_deref(), request.badge.as_deref())
+                .ok_or_else(|| StationLoginError::Validation("A PIN or badge is required".to_string()))?;
+
+        let mut conn = self.pool.acquire().await?;
+        let station = sqlx::query_as::<_, Station>(
+            "SELECT id, store_id FROM stations WHERE id = ? AND tenant_id = ? AND is_active = 1",
+        )
+        .bind(request.station_id.trim())
+        .bind(tenant_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| StationLoginError::NotFound(format!("Station not found: {}", request.station_id)))?;
+
+        let now = Utc::now();
+        let attempts = check_lockout(&mut conn, tenant_id, &station.id, now).await?;
+
+        let user = sqlx::query_as::<_, User>(&format!(
+            "{SELECT_USER} WHERE tenant_id = ? AND {column} = ? AND is_active = 1"
+        ))
+        .bind(tenant_id)
+        .bind(&hash)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some(user) = user else {
+            return Err(record_failure(&mut conn, tenant_id, &station.id, attempts, now).await?);
+        };
+
+        let allowed = match user.station_policy.as_str() {
+            "any" => true,
+            "specific" => user.station_id.as_deref() == Some(station.id.as_str()),
+            _ => false,
+        };
+        if !allowed || user.store_id.as_deref().is_some_and(|store| store != station.store_id) {
+            return Err(StationLoginError::Forbidden(format!(
+                "{} may not sign in at this station",
+                user.username
+            )));
+        }
+
+        drop(conn);
+        let permissions = role_service::resolve_permissions(
+            &self.pool,
+            tenant_id,
+            &user.id,
+            &user.role,
+            Some(&station.store_id),
+        )
+        .await?;
+        if !permissions.iter().any(|p| p == "access_sell") {
+            return Err(StationLoginError::Forbidden(format!("{} may not use the register", user.username)));
+        }
+
+        let mut conn = self.pool.acquire().await?;
+        clear_failures(&mut conn, tenant_id, &station.id).await?;
+        let idle_timeout_minutes = idle_timeout(&mut conn, tenant_id, &station.store_id).await?;
+
+        tracing::info!(
+            tenant_id = %tenant_id,
+            station_id = %station.id,
+            user_id = %user.id,
+            method = method,
+            "Station sign-in"
+        );
+        Ok(StationSignIn {
+            user,
+            station_id: station.id,
+            store_id: station.store_id,
+            method,
+            permissions,
+            idle_timeout_minutes,
+        })
+    }
+
+    /// Record the session of a station sign-in
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database write fails.
+    pub async fn start_session(
+        &self,
+        sign_in: &StationSignIn,
+        token: &str,
+        expires_at: &DateTime<Utc>,
+    ) -> Result<(), StationLoginError> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO sessions (
+                id, tenant_id, user_id, token, expires_at, station_id, login_method,
+                idle_timeout_minutes, last_activity_at
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&sign_in.user.tenant_id)
+        .bind(&sign_in.user.id)
+        .bind(token)
+        .bind(expires_at.to_rfc3339())
+        .bind(&sign_in.station_id)
+        .bind(sign_in.method)
+        .bind(sign_in.idle_timeout_minutes)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("UPDATE users SET last_login_at = ? WHERE id = ? AND tenant_id = ?")
+            .bind(&now)
+            .bind(&sign_in.user.id)
+            .bind(&sign_in.user.tenant_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Idle Sessions
+// ============================================================================
+
+/// Whether a station sign-in's session is still open, recording the activity
+///
+/// A session idle for longer than its timeout is ended. Signing out ends it
+/// too, so a station token without a session is not accepted.
+///
+/// # Errors
+///
+/// Returns an error if the database access fails.
+pub async fn session_is_active(pool: &SqlitePool, token: &str) -> Result<bool, sqlx::Error> {
+    let session: Option<(Option<i64>, Option<String>)> =
+        sqlx::query_as("SELECT idle_timeout_minutes, last_activity_at FROM sessions WHERE token = ?")
+            .bind(token)
+            .fetch_optional(pool)
+            .await?;
+    let Some((timeout, last_activity)) = session else {
+        return Ok(false);
+    };
+
+    let now = Utc::now();
+    let last_activity = last_activity.as_deref().and_then(parse_time).unwrap_or(now);
+    let idle = now - last_activity;
+    if timeout.is_some_and(|minutes| idle > Duration::minutes(minutes)) {
+        sqlx::query("DELETE FROM sessions WHERE token = ?")
+            .bind(token)
+            .execute(pool)
+            .await?;
+        tracing::info!("Station session ended after being idle");
+        return Ok(false);
+    }
+
+    if idle > Duration::seconds(ACTIVITY_RESOLUTION_SECONDS) {
+        sqlx::query("UPDATE sessions SET last_activity_at = ? WHERE token = ?")
+            .bind(now.to_rfc3339())
+            .bind(token)
+            .execute(pool)
+            .await?;
+    }
+    Ok(true)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+async fn station_attempts(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    station_id: &str,
+) -> Result<Option<Attempts>, sqlx::Error> {
+    sqlx::query_as::<_, Attempts>(
+        "SELECT failed_attempts, first_failed_at, locked_until
+         FROM station_login_attempts WHERE tenant_id = ? AND station_id = ?",
+    )
+    .bind(tenant_id)
+    .bind(station_id)
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+/// The station's failed attempts so far, or `LockedOut` while its PIN pad
+/// is locked
+///
+/// Station sign-ins and manager approvals share the lockout, as both look
+/// users up by PIN or badge.
+pub(crate) async fn check_lockout(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    station_id: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<Attempts>, StationLoginError> {
+    let attempts = station_attempts(conn, tenant_id, station_id).await?;
+    if let Some(until) = attempts.as_ref().and_then(|a| a.locked_until.as_deref()) {
+        if parse_time(until).is_some_and(|until| until > now) {
+            return Err(StationLoginError::LockedOut(until.to_string()));
+        }
+    }
+    Ok(attempts)
+}
+
+/// Reset the station's failed attempts after a PIN or badge was recognized
+pub(crate) async fn clear_failures(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    station_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM station_login_attempts WHERE tenant_id = ? AND station_id = ?")
+        .bind(tenant_id)
+        .bind(station_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Count a failed attempt, locking the station at the limit; the error to
+/// return for it
+pub(crate) async fn record_failure(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    station_id: &str,
+    attempts: Option<Attempts>,
+    now: DateTime<Utc>,
+) -> Result<StationLoginError, sqlx::Error> {
+    let window_start = now - Duration::minutes(ATTEMPT_WINDOW_MINUTES);
+    let (failed, first_failed_at) = match attempts {
+        Some(attempts)
+            if attempts.locked_until.is_none()
+                && attempts
+                    .first_failed_at
+                    .as_deref()
+                    .and_then(parse_time)
+                    .is_some_and(|first| first > window_start) =>
+        {
+            (attempts.failed_attempts + 1, attempts.first_failed_at.unwrap_or_default())
+        }
+        _ => (1, now.to_rfc3339()),
+    };
+    let locked_until = (failed >= MAX_FAILED_ATTEMPTS).then(|| (now + Duration::minutes(LOCKOUT_MINUTES)).to_rfc3339());
+
+    sqlx::query(
+        "INSERT INTO station_login_attempts (tenant_id, station_id, failed_attempts, first_failed_at, locked_until)
+         VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT (tenant_id, station_id) DO UPDATE SET
+             failed_attempts = excluded.failed_attempts,
+             first_failed_at = excluded.first_failed_at,
+             locked_until = excluded.locked_until",
+    )
+    .bind(tenant_id)
+    .bind(station_id)
+    .bind(failed)
+    .bind(&first_failed_at)
+    .bind(&locked_until)
+    .execute(&mut *conn)
+    .await?;
+
+    tracing::warn!(tenant_id = %tenant_id, station_id = %station_id, failed_attempts = failed, "Station sign-in failed");
+    Ok(match locked_until {
+        Some(until) => {
+            tracing::warn!(tenant_id = %tenant_id, station_id = %station_id, "Station PIN pad locked");
+            StationLoginError::LockedOut(until)
+        }
+        None => StationLoginError::InvalidCredentials,
+    })
+}
+
+/// The store's idle timeout, else the tenant's, else the default
+async fn idle_timeout(conn: &mut SqliteConnection, tenant_id: &str, store_id: &str) -> Result<i64, sqlx::Error> {
+    let value: Option<String> = sqlx::query_scalar(
+        "SELECT value FROM settings
+         WHERE key = ? AND ((scope = 'store' AND scope_id = ?) OR (scope = 'tenant' AND scope_id = ?) OR scope = 'global')
+         ORDER BY CASE scope WHEN 'store' THEN 1 WHEN 'tenant' THEN 2 ELSE 3 END
+         LIMIT 1",
+    )
+    .bind(SETTING_IDLE_TIMEOUT)
+    .bind(store_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(value
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|minutes| *minutes > 0)
+        .unwrap_or(DEFAULT_IDLE_TIMEOUT_MINUTES))
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::override_service::{OverrideService, SetCredentialsRequest};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE tenants (id TEXT PRIMARY KEY)",
+            "CREATE TABLE users (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                username TEXT NOT NULL,
+                email TEXT NOT NULL DEFAULT '',
+                password_hash TEXT NOT NULL DEFAULT '',
+                display_name TEXT,
+                role TEXT NOT NULL,
+                first_name TEXT,
+                last_name TEXT,
+                store_id TEXT,
+                station_policy TEXT NOT NULL DEFAULT 'any',
+                station_id TEXT,
+                is_active INTEGER NOT NULL DEFAULT 1,
+                last_login_at TEXT,
+                created_at TEXT NOT NULL DEFAULT '',
+                updated_at TEXT NOT NULL DEFAULT ''
+            )",
+            "CREATE TABLE sessions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                token TEXT NOT NULL,
+                expires_at TEXT NOT NULL
+            )",
+            "CREATE TABLE stations (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                is_active INTEGER NOT NULL DEFAULT 1
+            )",
+            "CREATE TABLE settings (key TEXT NOT NULL, value TEXT NOT NULL, scope TEXT NOT NULL, scope_id TEXT)",
+            "INSERT INTO tenants (id) VALUES ('t1')",
+            "INSERT INTO users (id, tenant_id, username, role, store_id, station_policy, station_id) VALUES
+                ('u1', 't1', 'carol', 'cashier', 's1', 'any', NULL),
+                ('u2', 't1', 'dave', 'cashier', 's1', 'specific', 'reg2'),
+                ('u3', 't1', 'ivy', 'inventory_clerk', 's1', 'any', NULL)",
+            "INSERT INTO stations (id, tenant_id, store_id) VALUES ('reg1', 't1', 's1'), ('reg2', 't1', 's1')",
+            "INSERT INTO settings (key, value, scope, scope_id) VALUES
+                ('auth.station_idle_timeout_minutes', '3', 'store', 's1')",
+            include_str!("../../../../migrations/073_roles_and_permissions.sql"),
+            include_str!("../../../../migrations/074_manager_overrides.sql"),
+            include_str!("../../../../migrations/075_station_pin_login.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        let overrides = OverrideService::new(pool.clone(), "secret".to_string());
+        for (user_id, pin) in [("u1", "1357"), ("u2", "2468"), ("u3", "3579")] {
+            overrides
+                .set_credentials("t1", user_id, &SetCredentialsRequest { pin: Some(pin.to_string()), badge: None })
+                .await
+                .unwrap();
+        }
+        pool
+    }
+
+    fn pin(station_id: &str, pin: &str) -> StationLoginRequest {
+        StationLoginRequest { station_id: station_id.to_string(), pin: Some(pin.to_string()), badge: None }
+    }
+
+    #[tokio::test]
+    async fn test_pin_sign_in_bound_to_station() {
+        let pool = setup_test_db().await;
+        let service = StationLoginService::new(pool.clone(), "secret".to_string());
+
+        let sign_in = service.authenticate("t1", &pin("reg1", "1357")).await.unwrap();
+        assert_eq!(sign_in.user.id, "u1");
+        assert_eq!((sign_in.station_id.as_str(), sign_in.store_id.as_str()), ("reg1", "s1"));
+        assert_eq!(sign_in.idle_timeout_minutes, 3);
+        assert!(sign_in.permissions.contains(&"access_sell".to_string()));
+
+        // dave is bound to reg2; ivy does not sell
+        assert!(matches!(service.authenticate("t1", &pin("reg1", "2468")).await, Err(StationLoginError::Forbidden(_))));
+        assert_eq!(service.authenticate("t1", &pin("reg2", "2468")).await.unwrap().user.id, "u2");
+        assert!(matches!(service.authenticate("t1", &pin("reg1", "3579")).await, Err(StationLoginError::Forbidden(_))));
+        assert!(matches!(service.authenticate("t1", &pin("reg9", "1357")).await, Err(StationLoginError::NotFound(_))));
+
+        // Idle sessions end
+        service.start_session(&sign_in, "tok", &(Utc::now() + Duration::hours(8))).await.unwrap();
+        assert!(session_is_active(&pool, "tok").await.unwrap());
+        sqlx::query("UPDATE sessions SET last_activity_at = ?")
+            .bind((Utc::now() - Duration::minutes(4)).to_rfc3339())
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(!session_is_active(&pool, "tok").await.unwrap());
+        assert!(!session_is_active(&pool, "tok").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_station_locks_after_failed_attempts() {
+        let service = StationLoginService::new(setup_test_db().await, "secret".to_string());
+
+        for _ in 1..MAX_FAILED_ATTEMPTS {
+            assert!(matches!(
+                service.authenticate("t1", &pin("reg1", "0000")).await,
+                Err(StationLoginError::InvalidCredentials)
+            ));
+        }
+        assert!(matches!(service.authenticate("t1", &pin("reg1", "0000")).await, Err(StationLoginError::LockedOut(_))));
+        // Even the right PIN, until the lock expires; other stations are unaffected
+        assert!(matches!(service.authenticate("t1", &pin("reg1", "1357")).await, Err(StationLoginError::LockedOut(_))));
+        assert_eq!(service.authenticate("t1", &pin("reg2", "2468")).await.unwrap().user.id, "u2");
+    }
+}
@@ -0,0 +1,494 @@
+/**
+ * Stored Value Service
+ *
+ * Debits and credits gift cards and customer store credit on a caller-supplied
+ * connection so that the balance change commits or rolls back together with
+ * the surrounding operation. Used by the gift card / store credit redeem
+ * endpoints, by checkout when a sale is paid (partly) with stored value, by
+ * voids crediting those tenders back, and by returns refunding to a gift card
+ * or to store credit.
+ *
+ * Balances are decremented with a guarded UPDATE (`balance >= amount`) so two
+ * registers cannot overdraw the same card concurrently.
+ */
+
+use chrono::Utc;
+use sqlx::SqliteConnection;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{GiftCardStatus, GiftCardTransactionType};
+
+/// Balance at or below which a gift card is marked depleted
+const DEPLETED_THRESHOLD: f64 = 0.01;
+
+#[derive(Debug, Error)]
+pub enum StoredValueError {
+    #[error("Gift card not found: {0}")]
+    GiftCardNotFound(String),
+
+    #[error("Gift card is {0}")]
+    GiftCardInactive(String),
+
+    #[error("Gift card has expired")]
+    GiftCardExpired,
+
+    #[error("Customer not found: {0}")]
+    CustomerNotFound(String),
+
+    #[error("Insufficient balance: available {available:.2}, requested {requested:.2}")]
+    InsufficientBalance { available: f64, requested: f64 },
+
+    #[error("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Outcome of a successful gift card debit
+#[derive(Debug, Clone)]
+pub struct GiftCardDebit {
+    pub gift_card_id: String,
+    pub amount: f64,
+    pub new_balance: f64,
+    pub status: String,
+}
+
+/// Outcome of a successful store credit debit
+#[derive(Debug, Clone)]
+pub struct StoreCreditDebit {
+    pub customer_id: String,
+    pub amount: f64,
+    pub new_balance: f64,
+}
+
+/// Outcome of a successful stored value credit
+#[derive(Debug, Clone)]
+pub struct StoredValueCredit {
+    /// Gift card or customer id
+    pub account_id: String,
+    pub amount: f64,
+    pub new_balance: f64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct GiftCardRow {
+    id: String,
+    tenant_id: String,
+    current_balance: f64,
+    status: String,
+    expiry_date: Option<String>,
+}
+
+/// Debit a gift card by card number
+///
+/// When `tenant_id` is given the card must belong to that tenant.
+pub async fn debit_gift_card(
+    conn: &mut SqliteConnection,
+    tenant_id: Option<&str>,
+    card_number: &str,
+    amount: f64,
+    reference_id: Option<&str>,
+) -> Result<GiftCardDebit, StoredValueError> {
+    if amount <= 0.0 {
+        return Err(StoredValueError::InvalidAmount);
+    }
+
+    let card = sqlx::query_as::<_, GiftCardRow>(
+        "SELECT id, tenant_id, current_balance, status, expiry_date
+         FROM gift_cards
+         WHERE card_number = ? AND (? IS NULL OR tenant_id = ?)",
+    )
+    .bind(card_number)
+    .bind(tenant_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| StoredValueError::GiftCardNotFound(card_number.to_string()))?;
+
+    // Check card status
+    if card.status != GiftCardStatus::Active.as_str() {
+        return Err(StoredValueError::GiftCardInactive(card.status));
+    }
+
+    // Check expiry
+    if let Some(expiry_date) = &card.expiry_date {
+        if let Ok(expiry) = chrono::DateTime::parse_from_rfc3339(expiry_date) {
+            if Utc::now() > expiry {
+                return Err(StoredValueError::GiftCardExpired);
+            }
+        }
+    }
+
+    let updated = sqlx::query(
+        "UPDATE gift_cards
+         SET current_balance = current_balance - ?,
+             status = CASE WHEN current_balance - ? <= ? THEN ? ELSE status END
+         WHERE id = ? AND current_balance >= ?",
+    )
+    .bind(amount)
+    .bind(amount)
+    .bind(DEPLETED_THRESHOLD)
+    .bind(GiftCardStatus::Depleted.as_str())
+    .bind(&card.id)
+    .bind(amount)
+    .execute(&mut *conn)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(StoredValueError::InsufficientBalance {
+            available: card.current_balance,
+            requested: amount,
+        });
+    }
+
+    // Record transaction
+    sqlx::query(
+        "INSERT INTO gift_card_transactions (id, tenant_id, gift_card_id, transaction_type, amount,
+         reference_id, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(&card.tenant_id)
+    .bind(&card.id)
+    .bind(GiftCardTransactionType::Redeemed.as_str())
+    .bind(amount)
+    .bind(reference_id)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut *conn)
+    .await?;
+
+    let (new_balance, status): (f64, String) =
+        sqlx::query_as("SELECT current_balance, status FROM gift_cards WHERE id = ?")
+            .bind(&card.id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+    Ok(GiftCardDebit {
+        gift_card_id: card.id,
+        amount,
+        new_balance,
+        status,
+    })
+}
+
+/// Debit a customer's store credit balance
+///
+/// The customer must belong to `tenant_id`.
+pub async fn debit_store_credit(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    customer_id: &str,
+    amount: f64,
+    reference_id: &str,
+    employee_id: &str,
+) -> Result<StoreCreditDebit, StoredValueError> {
+    if amount <= 0.0 {
+        return Err(StoredValueError::InvalidAmount);
+    }
+
+    let current_balance: f64 = sqlx::query_scalar("SELECT store_credit FROM customers WHERE id = ? AND tenant_id = ?")
+        .bind(customer_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| StoredValueError::CustomerNotFound(customer_id.to_string()))?;
+
+    let now = Utc::now().to_rfc3339();
+
+    // Update customer store credit balance
+    let updated = sqlx::query(
+        "UPDATE customers
+         SET store_credit = store_credit - ?, updated_at = ?, sync_version = sync_version + 1
+         WHERE id = ? AND tenant_id = ? AND store_credit >= ?",
+    )
+    .bind(amount)
+    .bind(&now)
+    .bind(customer_id)
+    .bind(tenant_id)
+    .bind(amount)
+    .execute(&mut *conn)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(StoredValueError::InsufficientBalance {
+            available: current_balance,
+            requested: amount,
+        });
+    }
+
+    // Record loyalty transaction for audit trail
+    sqlx::query(
+        "INSERT INTO loyalty_transactions (id, customer_id, transaction_type, points, amount,
+         reference_id, created_at, employee_id)
+         VALUES (?, ?, ?, 0, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(customer_id)
+    .bind("StoreCreditRedeemed")
+    .bind(-amount) // Negative for redemption
+    .bind(reference_id)
+    .bind(&now)
+    .bind(employee_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(StoreCreditDebit {
+        customer_id: customer_id.to_string(),
+        amount,
+        new_balance: current_balance - amount,
+    })
+}
+
+/// Credit a gift card by card number (refund of a gift card tender)
+///
+/// A depleted card is reactivated; cancelled or expired cards cannot be
+/// credited.
+pub async fn credit_gift_card(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    card_number: &str,
+    amount: f64,
+    reference_id: &str,
+) -> Result<StoredValueCredit, StoredValueError> {
+    if amount <= 0.0 {
+        return Err(StoredValueError::InvalidAmount);
+    }
+
+    let card = sqlx::query_as::<_, GiftCardRow>(
+        "SELECT id, tenant_id, current_balance, status, expiry_date
+         FROM gift_cards
+         WHERE card_number = ? AND tenant_id = ?",
+    )
+    .bind(card_number)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| StoredValueError::GiftCardNotFound(card_number.to_string()))?;
+
+    if card.status != GiftCardStatus::Active.as_str() && card.status != GiftCardStatus::Depleted.as_str() {
+        return Err(StoredValueError::GiftCardInactive(card.status));
+    }
+
+    sqlx::query(
+        "UPDATE gift_cards SET current_balance = current_balance + ?, status = ? WHERE id = ?",
+    )
+    .bind(amount)
+    .bind(GiftCardStatus::Active.as_str())
+    .bind(&card.id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO gift_card_transactions (id, tenant_id, gift_card_id, transaction_type, amount,
+         reference_id, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(&card.tenant_id)
+    .bind(&card.id)
+    .bind(GiftCardTransactionType::Refunded.as_str())
+    .bind(amount)
+    .bind(reference_id)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(StoredValueCredit {
+        account_id: card.id,
+        amount,
+        new_balance: card.current_balance + amount,
+    })
+}
+
+/// Credit a customer's store credit balance
+///
+/// The customer must belong to `tenant_id`.
+pub async fn credit_store_credit(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    customer_id: &str,
+    amount: f64,
+    reference_id: &str,
+    employee_id: &str,
+) -> Result<StoredValueCredit, StoredValueError> {
+    if amount <= 0.0 {
+        return Err(StoredValueError::InvalidAmount);
+    }
+
+    let current_balance: f64 = sqlx::query_scalar("SELECT store_credit FROM customers WHERE id = ? AND tenant_id = ?")
+        .bind(customer_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| StoredValueError::CustomerNotFound(customer_id.to_string()))?;
+
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        "UPDATE customers
+         SET store_credit = store_credit + ?, updated_at = ?, sync_version = sync_version + 1
+         WHERE id = ? AND tenant_id = ?",
+    )
+    .bind(amount)
+    .bind(&now)
+    .bind(customer_id)
+    .bind(tenant_id)
+    .execute(&mut *conn)
+    .await?;
+
+    // Record loyalty transaction for audit trail
+    sqlx::query(
+        "INSERT INTO loyalty_transactions (id, customer_id, transaction_type, points, amount,
+         reference_id, created_at, employee_id)
+         VALUES (?, ?, ?, 0, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(customer_id)
+    .bind("StoreCredit")
+    .bind(amount)
+    .bind(reference_id)
+    .bind(&now)
+    .bind(employee_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(StoredValueCredit {
+        account_id: customer_id.to_string(),
+        amount,
+        new_balance: current_balance + amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE gift_cards (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL DEFAULT 'default',
+                card_number TEXT NOT NULL UNIQUE,
+                initial_balance REAL NOT NULL,
+                current_balance REAL NOT NULL,
+                status TEXT NOT NULL,
+                issued_date TEXT NOT NULL DEFAULT (datetime('now')),
+                expiry_date TEXT,
+                customer_id TEXT
+            )",
+            "CREATE TABLE gift_card_transactions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL DEFAULT 'default',
+                gift_card_id TEXT NOT NULL,
+                transaction_type TEXT NOT NULL,
+                amount REAL NOT NULL,
+                reference_id TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE customers (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL DEFAULT 'default',
+                store_credit REAL NOT NULL DEFAULT 0.0,
+                updated_at TEXT,
+                sync_version INTEGER NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE loyalty_transactions (
+                id TEXT PRIMARY KEY,
+                customer_id TEXT NOT NULL,
+                transaction_type TEXT NOT NULL,
+                points INTEGER NOT NULL,
+                amount REAL,
+                reference_id TEXT,
+                created_at TEXT NOT NULL,
+                employee_id TEXT NOT NULL
+            )",
+            "INSERT INTO gift_cards (id, tenant_id, card_number, initial_balance, current_balance, status)
+             VALUES ('gc1', 't1', '1111', 50.0, 50.0, 'Active')",
+            "INSERT INTO customers (id, tenant_id, store_credit) VALUES ('c1', 't1', 20.0)",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_debit_gift_card_depletes_card() {
+        let pool = setup_test_db().await;
+        let mut conn = pool.acquire().await.unwrap();
+
+        let debit = debit_gift_card(&mut conn, Some("t1"), "1111", 50.0, Some("sale-1"))
+            .await
+            .unwrap();
+
+        assert_eq!(debit.new_balance, 0.0);
+        assert_eq!(debit.status, GiftCardStatus::Depleted.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_debit_gift_card_rejects_other_tenant_and_overdraw() {
+        let pool = setup_test_db().await;
+        let mut conn = pool.acquire().await.unwrap();
+
+        assert!(matches!(
+            debit_gift_card(&mut conn, Some("t2"), "1111", 10.0, None).await,
+            Err(StoredValueError::GiftCardNotFound(_))
+        ));
+        assert!(matches!(
+            debit_gift_card(&mut conn, None, "1111", 60.0, None).await,
+            Err(StoredValueError::InsufficientBalance { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_debit_store_credit() {
+        let pool = setup_test_db().await;
+        let mut conn = pool.acquire().await.unwrap();
+
+        let debit = debit_store_credit(&mut conn, "t1", "c1", 15.0, "sale-1", "u1").await.unwrap();
+        assert_eq!(debit.new_balance, 5.0);
+
+        assert!(matches!(
+            debit_store_credit(&mut conn, "t1", "c1", 10.0, "sale-2", "u1").await,
+            Err(StoredValueError::InsufficientBalance { .. })
+        ));
+        // Another tenant's customer is not found
+        assert!(matches!(
+            debit_store_credit(&mut conn, "t2", "c1", 1.0, "sale-3", "u1").await,
+            Err(StoredValueError::CustomerNotFound(_))
+        ));
+        assert!(matches!(
+            credit_store_credit(&mut conn, "t2", "c1", 1.0, "return-1", "u1").await,
+            Err(StoredValueError::CustomerNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_credit_reactivates_depleted_gift_card() {
+        let pool = setup_test_db().await;
+        let mut conn = pool.acquire().await.unwrap();
+
+        debit_gift_card(&mut conn, Some("t1"), "1111", 50.0, Some("sale-1")).await.unwrap();
+        let credit = credit_gift_card(&mut conn, "t1", "1111", 20.0, "return-1").await.unwrap();
+        assert_eq!(credit.new_balance, 20.0);
+
+        let status: String = sqlx::query_scalar("SELECT status FROM gift_cards WHERE id = 'gc1'")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(status, GiftCardStatus::Active.as_str());
+
+        let credit = credit_store_credit(&mut conn, "t1", "c1", 5.0, "return-1", "u1").await.unwrap();
+        assert_eq!(credit.new_balance, 25.0);
+    }
+}
@@ -0,0 +1,840 @@
+/**
+ * Suspended Sale Service
+ *
+ * Parks in-progress sales so a register can serve the next customer while
+ * someone fetches their wallet. A suspended sale is a draft cart
+ * (`TransactionStatus::Draft`) kept in `suspended_sales`, outside the sales
+ * tables, so reports never see it. A station may hold several parked carts
+ * at once, and any register of the store can resume one.
+ *
+ * Lifecycle:
+ * - suspend: the cart is priced (pre-tax) and parked with an expiry time
+ * - resume: a register takes the parked cart; only one register can hold it
+ * - suspend again: the open cart's contents replace the parked ones
+ * - checkout: `CheckoutService` finalizes the draft in the sale's own
+ *   database transaction, so the cart can never be sold twice
+ * - cancel / expire: the draft is voided; parked carts past their expiry are
+ *   voided lazily when the store's carts are listed or resumed
+ *
+ * Tax is worked out at checkout, since the customer or the store's tax rules
+ * may change while the cart is parked.
+ */
+
+use chrono::{DateTime, Duration, Utc};
+use pos_core_domain::TransactionStatus;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::checkout_service::{
+    build_transaction, finalize_transaction, money_to_f64, CheckoutError, CheckoutLine,
+};
+
+/// Setting holding how long a parked cart is kept, in minutes
+pub const SETTING_SUSPENDED_SALE_TTL_MINUTES: &str = "sales.suspended_sale_ttl_minutes";
+
+/// Parked carts are kept for a shift unless configured otherwise
+pub const DEFAULT_SUSPENDED_SALE_TTL_MINUTES: i64 = 8 * 60;
+
+/// `suspended_sales.void_reason` of a cart voided by the cashier
+pub const VOID_REASON_CANCELLED: &str = "cancelled";
+/// `suspended_sales.void_reason` of a parked cart that was never resumed
+pub const VOID_REASON_EXPIRED: &str = "expired";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum SuspendedSaleError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Suspended sale not found: {0}")]
+    NotFound(String),
+
+    #[error("Suspended sale unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<CheckoutError> for SuspendedSaleError {
+    fn from(err: CheckoutError) -> Self {
+        match err {
+            CheckoutError::Database(e) => Self::Database(e),
+            CheckoutError::Validation(msg) => Self::Validation(msg),
+            other => Self::Validation(other.to_string()),
+        }
+    }
+}
+
+impl From<SuspendedSaleError> for ApiError {
+    fn from(err: SuspendedSaleError) -> Self {
+        match err {
+            SuspendedSaleError::Validation(msg) => Self::bad_request(msg),
+            SuspendedSaleError::NotFound(id) => Self::not_found(format!("Suspended sale not found: {id}")),
+            SuspendedSaleError::Unavailable(msg) => Self::conflict(msg),
+            SuspendedSaleError::Database(e) => {
+                Self::internal(format!("Failed to access suspended sales: {e}"))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A cart to park
+#[derive(Debug, Clone)]
+pub struct SuspendRequest {
+    pub tenant_id: String,
+    pub store_id: String,
+    /// Register parking the cart
+    pub station_id: Option<String>,
+    pub employee_id: String,
+    pub customer_id: Option<String>,
+    /// Short name shown in the parked list
+    pub label: Option<String>,
+    pub notes: Option<String>,
+    pub lines: Vec<CheckoutLine>,
+    pub cart_discount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SuspendedSaleLine {
+    pub product_id: String,
+    pub product_name: Option<String>,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub discount_amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SuspendedSale {
+    pub id: String,
+    pub store_id: String,
+    pub station_id: Option<String>,
+    pub employee_id: String,
+    pub customer_id: Option<String>,
+    pub label: Option<String>,
+    pub notes: Option<String>,
+    pub cart_discount: f64,
+    /// Pre-tax amounts when the cart was parked
+    pub subtotal: f64,
+    pub discount_amount: f64,
+    pub items_count: i64,
+    /// draft, finalized or voided
+    pub status: String,
+    /// Waiting to be resumed (false while open at a register)
+    pub parked: bool,
+    pub suspended_at: String,
+    pub resumed_at: Option<String>,
+    pub resumed_by: Option<String>,
+    pub expires_at: String,
+    /// Sale the cart was checked out as
+    pub sale_id: Option<String>,
+    pub void_reason: Option<String>,
+    #[sqlx(skip)]
+    pub lines: Vec<SuspendedSaleLine>,
+}
+
+/// `suspended_sales.status` value of a transaction status
+#[must_use]
+pub const fn status_str(status: &TransactionStatus) -> &'static str {
+    match status {
+        TransactionStatus::Draft => "draft",
+        TransactionStatus::Finalized => "finalized",
+        TransactionStatus::Voided => "voided",
+    }
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+const SELECT_SUSPENDED_SALE: &str = "SELECT id, store_id, station_id, employee_id, customer_id, label,
+        notes, cart_discount, subtotal, discount_amount, items_count, status, parked,
+        suspended_at, resumed_at, resumed_by, expires_at, sale_id, void_reason
+     FROM suspended_sales";
+
+pub struct SuspendedSaleService {
+    pool: SqlitePool,
+}
+
+impl SuspendedSaleService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Park a cart
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cart is empty or cannot be priced, a product
+    /// is unknown, or the database write fails.
+    pub async fn suspend(&self, request: SuspendRequest) -> Result<SuspendedSale, SuspendedSaleError> {
+        let (subtotal, discount) = price_cart(&request.lines, request.cart_discount)?;
+        let ttl = self.ttl(&request.store_id).await?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO suspended_sales (
+                id, tenant_id, store_id, station_id, employee_id, customer_id, label, notes,
+                cart_discount, subtotal, discount_amount, items_count, status, parked,
+                suspended_at, expires_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&id)
+        .bind(&request.tenant_id)
+        .bind(&request.store_id)
+        .bind(&request.station_id)
+        .bind(&request.employee_id)
+        .bind(&request.customer_id)
+        .bind(&request.label)
+        .bind(&request.notes)
+        .bind(money_to_f64(request.cart_discount))
+        .bind(money_to_f64(subtotal))
+        .bind(money_to_f64(discount))
+        .bind(i64::try_from(request.lines.len()).unwrap_or(i64::MAX))
+        .bind(status_str(&TransactionStatus::Draft))
+        .bind(now.to_rfc3339())
+        .bind((now + ttl).to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        insert_lines(&mut tx, &request.tenant_id, &id, &request.lines).await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            suspended_sale_id = %id,
+            store_id = %request.store_id,
+            station_id = ?request.station_id,
+            "Sale suspended"
+        );
+
+        self.get(&request.tenant_id, &id).await
+    }
+
+    /// Park a resumed cart again, replacing its contents
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cart is unknown, no longer a draft, still
+    /// parked elsewhere, or the new contents are invalid.
+    pub async fn suspend_again(
+        &self,
+        id: &str,
+        request: SuspendRequest,
+    ) -> Result<SuspendedSale, SuspendedSaleError> {
+        let current = self.get(&request.tenant_id, id).await?;
+        ensure_draft(&current)?;
+        if current.parked {
+            return Err(SuspendedSaleError::Unavailable(
+                "Suspended sale must be resumed before it can be changed".to_string(),
+            ));
+        }
+
+        let (subtotal, discount) = price_cart(&request.lines, request.cart_discount)?;
+        let ttl = self.ttl(&request.store_id).await?;
+        let now = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query(
+            r"
+            UPDATE suspended_sales SET
+                station_id = COALESCE(?, station_id), employee_id = ?, customer_id = ?,
+                label = ?, notes = ?, cart_discount = ?, subtotal = ?, discount_amount = ?,
+                items_count = ?, parked = 1, suspended_at = ?, expires_at = ?, updated_at = ?
+            WHERE id = ? AND tenant_id = ? AND status = ? AND parked = 0
+            ",
+        )
+        .bind(&request.station_id)
+        .bind(&request.employee_id)
+        .bind(&request.customer_id)
+        .bind(&request.label)
+        .bind(&request.notes)
+        .bind(money_to_f64(request.cart_discount))
+        .bind(money_to_f64(subtotal))
+        .bind(money_to_f64(discount))
+        .bind(i64::try_from(request.lines.len()).unwrap_or(i64::MAX))
+        .bind(now.to_rfc3339())
+        .bind((now + ttl).to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(id)
+        .bind(&request.tenant_id)
+        .bind(status_str(&TransactionStatus::Draft))
+        .execute(&mut *tx)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(SuspendedSaleError::Unavailable(
+                "Suspended sale was changed by another register".to_string(),
+            ));
+        }
+
+        sqlx::query("DELETE FROM suspended_sale_lines WHERE suspended_sale_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        insert_lines(&mut tx, &request.tenant_id, id, &request.lines).await?;
+
+        tx.commit().await?;
+
+        self.get(&request.tenant_id, id).await
+    }
+
+    /// Take a parked cart to a register
+    ///
+    /// Only one register can resume a cart; a second attempt gets
+    /// `Unavailable`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cart is unknown, already open at a register,
+    /// checked out, voided or expired.
+    pub async fn resume(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        station_id: Option<&str>,
+        employee_id: &str,
+    ) -> Result<SuspendedSale, SuspendedSaleError> {
+        let now = Utc::now();
+        self.expire_stale(tenant_id, now).await?;
+
+        let resumed = sqlx::query(
+            r"
+            UPDATE suspended_sales SET
+                parked = 0, station_id = COALESCE(?, station_id),
+                resumed_at = ?, resumed_by = ?, updated_at = ?
+            WHERE id = ? AND tenant_id = ? AND status = ? AND parked = 1
+            ",
+        )
+        .bind(station_id)
+        .bind(now.to_rfc3339())
+        .bind(employee_id)
+        .bind(now.to_rfc3339())
+        .bind(id)
+        .bind(tenant_id)
+        .bind(status_str(&TransactionStatus::Draft))
+        .execute(&self.pool)
+        .await?;
+
+        let sale = self.get(tenant_id, id).await?;
+        if resumed.rows_affected() == 0 {
+            ensure_draft(&sale)?;
+            return Err(SuspendedSaleError::Unavailable(format!(
+                "Suspended sale is already open at register {}",
+                sale.station_id.as_deref().unwrap_or("unknown")
+            )));
+        }
+
+        tracing::info!(
+            suspended_sale_id = %id,
+            station_id = ?station_id,
+            "Suspended sale resumed"
+        );
+
+        Ok(sale)
+    }
+
+    /// Void a draft cart
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cart is unknown or no longer a draft.
+    pub async fn cancel(&self, tenant_id: &str, id: &str) -> Result<SuspendedSale, SuspendedSaleError> {
+        let now = Utc::now().to_rfc3339();
+
+        let voided = sqlx::query(
+            "UPDATE suspended_sales SET status = ?, void_reason = ?, voided_at = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ? AND status = ?",
+        )
+        .bind(status_str(&TransactionStatus::Voided))
+        .bind(VOID_REASON_CANCELLED)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(status_str(&TransactionStatus::Draft))
+        .execute(&self.pool)
+        .await?;
+
+        let sale = self.get(tenant_id, id).await?;
+        if voided.rows_affected() == 0 {
+            ensure_draft(&sale)?;
+        }
+
+        Ok(sale)
+    }
+
+    /// Draft carts of a store, oldest first
+    ///
+    /// Expired carts are voided first. Only parked carts are listed unless
+    /// `include_open` is set; `station_id` limits the list to one register.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn list(
+        &self,
+        tenant_id: &str,
+        store_id: &str,
+        station_id: Option<&str>,
+        include_open: bool,
+    ) -> Result<Vec<SuspendedSale>, SuspendedSaleError> {
+        self.expire_stale(tenant_id, Utc::now()).await?;
+
+        let mut sales = sqlx::query_as::<_, SuspendedSale>(&format!(
+            "{SELECT_SUSPENDED_SALE}
+             WHERE tenant_id = ? AND store_id = ? AND status = ?
+               AND (? IS NULL OR station_id = ?)
+               AND (? OR parked = 1)
+             ORDER BY suspended_at ASC"
+        ))
+        .bind(tenant_id)
+        .bind(store_id)
+        .bind(status_str(&TransactionStatus::Draft))
+        .bind(station_id)
+        .bind(station_id)
+        .bind(include_open)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for sale in &mut sales {
+            sale.lines = self.lines(&sale.id).await?;
+        }
+
+        Ok(sales)
+    }
+
+    /// A suspended sale with its lines, whatever its status
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotFound` if the tenant has no such suspended sale.
+    pub async fn get(&self, tenant_id: &str, id: &str) -> Result<SuspendedSale, SuspendedSaleError> {
+        let mut sale = sqlx::query_as::<_, SuspendedSale>(&format!(
+            "{SELECT_SUSPENDED_SALE} WHERE id = ? AND tenant_id = ?"
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SuspendedSaleError::NotFound(id.to_string()))?;
+
+        sale.lines = self.lines(id).await?;
+        Ok(sale)
+    }
+
+    /// Void the tenant's parked carts whose expiry has passed
+    ///
+    /// Carts open at a register are left alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails.
+    pub async fn expire_stale(&self, tenant_id: &str, now: DateTime<Utc>) -> Result<u64, SuspendedSaleError> {
+        let now = now.to_rfc3339();
+
+        let expired = sqlx::query(
+            "UPDATE suspended_sales SET status = ?, void_reason = ?, voided_at = ?, updated_at = ?
+             WHERE tenant_id = ? AND status = ? AND parked = 1 AND expires_at <= ?",
+        )
+        .bind(status_str(&TransactionStatus::Voided))
+        .bind(VOID_REASON_EXPIRED)
+        .bind(&now)
+        .bind(&now)
+        .bind(tenant_id)
+        .bind(status_str(&TransactionStatus::Draft))
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        if expired.rows_affected() > 0 {
+            tracing::info!(
+                tenant_id = %tenant_id,
+                expired = expired.rows_affected(),
+                "Expired suspended sales voided"
+            );
+        }
+
+        Ok(expired.rows_affected())
+    }
+
+    async fn lines(&self, id: &str) -> Result<Vec<SuspendedSaleLine>, SuspendedSaleError> {
+        let lines = sqlx::query_as::<_, SuspendedSaleLine>(
+            "SELECT l.product_id, p.name AS product_name, l.quantity, l.unit_price, l.discount_amount
+             FROM suspended_sale_lines l
+             LEFT JOIN products p ON p.id = l.product_id
+             WHERE l.suspended_sale_id = ?
+             ORDER BY l.line_number ASC",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lines)
+    }
+
+    async fn ttl(&self, store_id: &str) -> Result<Duration, SuspendedSaleError> {
+        let value: Option<String> = sqlx::query_scalar(
+            "SELECT value FROM settings
+             WHERE key = ? AND ((scope = 'store' AND scope_id = ?) OR scope = 'global')
+             ORDER BY CASE scope WHEN 'store' THEN 1 ELSE 2 END
+             LIMIT 1",
+        )
+        .bind(SETTING_SUSPENDED_SALE_TTL_MINUTES)
+        .bind(store_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let minutes = value
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_SUSPENDED_SALE_TTL_MINUTES);
+
+        Ok(Duration::minutes(minutes))
+    }
+}
+
+/// Mark a draft cart as checked out as `sale_id`
+///
+/// Runs on the checkout's own connection so the cart is finalized if and only
+/// if the sale commits. Returns `false` if the cart is not a draft of the
+/// tenant, or is still parked past its expiry.
+///
+/// # Errors
+///
+/// Returns an error if the update fails.
+pub async fn finalize_suspended_sale(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    id: &str,
+    sale_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+
+    let finalized = sqlx::query(
+        "UPDATE suspended_sales SET status = ?, parked = 0, sale_id = ?, finalized_at = ?, updated_at = ?
+         WHERE id = ? AND tenant_id = ? AND status = ? AND (parked = 0 OR expires_at > ?)",
+    )
+    .bind(status_str(&TransactionStatus::Finalized))
+    .bind(sale_id)
+    .bind(&now)
+    .bind(&now)
+    .bind(id)
+    .bind(tenant_id)
+    .bind(status_str(&TransactionStatus::Draft))
+    .bind(&now)
+    .execute(conn)
+    .await?;
+
+    Ok(finalized.rows_affected() > 0)
+}
+
+/// Pre-tax subtotal and discount of a cart, as the checkout would price it
+fn price_cart(lines: &[CheckoutLine], cart_discount: Decimal) -> Result<(Decimal, Decimal), SuspendedSaleError> {
+    if lines.is_empty() {
+        return Err(SuspendedSaleError::Validation(
+            "Suspended sale must have at least one item".to_string(),
+        ));
+    }
+
+    let mut transaction = build_transaction(Uuid::new_v4(), lines, &[], cart_discount)?;
+    finalize_transaction(&mut transaction)?;
+
+    Ok((transaction.subtotal, transaction.discount_total))
+}
+
+fn ensure_draft(sale: &SuspendedSale) -> Result<(), SuspendedSaleError> {
+    if sale.status == status_str(&TransactionStatus::Draft) {
+        return Ok(());
+    }
+
+    let reason = match (sale.status.as_str(), sale.void_reason.as_deref()) {
+        (_, Some(VOID_REASON_EXPIRED)) => "Suspended sale has expired".to_string(),
+        ("finalized", _) => "Suspended sale has already been checked out".to_string(),
+        _ => "Suspended sale has been cancelled".to_string(),
+    };
+    Err(SuspendedSaleError::Unavailable(reason))
+}
+
+async fn insert_lines(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    id: &str,
+    lines: &[CheckoutLine],
+) -> Result<(), SuspendedSaleError> {
+    for (line_number, line) in lines.iter().enumerate() {
+        let known: Option<String> =
+            sqlx::query_scalar("SELECT id FROM products WHERE id = ? AND tenant_id = ?")
+                .bind(&line.product_id)
+                .bind(tenant_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+        if known.is_none() {
+            return Err(SuspendedSaleError::Validation(format!(
+                "Product not found: {}",
+                line.product_id
+            )));
+        }
+
+        sqlx::query(
+            r"
+            INSERT INTO suspended_sale_lines (
+                id, suspended_sale_id, line_number, product_id, quantity, unit_price, discount_amount
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(id)
+        .bind(i64::try_from(line_number).unwrap_or(i64::MAX))
+        .bind(&line.product_id)
+        .bind(line.quantity.to_f64().unwrap_or_default())
+        .bind(money_to_f64(line.unit_price))
+        .bind(money_to_f64(line.discount_amount))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                name TEXT NOT NULL
+            )",
+            "CREATE TABLE settings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            "CREATE TABLE suspended_sales (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                station_id TEXT,
+                employee_id TEXT NOT NULL,
+                customer_id TEXT,
+                label TEXT,
+                notes TEXT,
+                cart_discount REAL NOT NULL DEFAULT 0.0,
+                subtotal REAL NOT NULL DEFAULT 0.0,
+                discount_amount REAL NOT NULL DEFAULT 0.0,
+                items_count INTEGER NOT NULL DEFAULT 0,
+                status TEXT NOT NULL DEFAULT 'draft',
+                parked INTEGER NOT NULL DEFAULT 1,
+                suspended_at TEXT NOT NULL,
+                resumed_at TEXT,
+                resumed_by TEXT,
+                expires_at TEXT NOT NULL,
+                sale_id TEXT,
+                finalized_at TEXT,
+                voided_at TEXT,
+                void_reason TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE TABLE suspended_sale_lines (
+                id TEXT PRIMARY KEY,
+                suspended_sale_id TEXT NOT NULL,
+                line_number INTEGER NOT NULL,
+                product_id TEXT NOT NULL,
+                quantity REAL NOT NULL,
+                unit_price REAL NOT NULL,
+                discount_amount REAL NOT NULL DEFAULT 0.0
+            )",
+            "INSERT INTO products (id, tenant_id, name) VALUES ('p1', 't1', 'Widget'), ('p2', 't1', 'Gadget')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    fn line(product_id: &str, quantity: i64, unit_price_cents: i64) -> CheckoutLine {
+        CheckoutLine {
+            product_id: product_id.to_string(),
+            quantity: Decimal::from(quantity),
+            unit_price: Decimal::new(unit_price_cents, 2),
+            discount_amount: Decimal::ZERO,
+        }
+    }
+
+    fn request(station_id: &str, lines: Vec<CheckoutLine>) -> SuspendRequest {
+        SuspendRequest {
+            tenant_id: "t1".to_string(),
+            store_id: "s1".to_string(),
+            station_id: Some(station_id.to_string()),
+            employee_id: "u1".to_string(),
+            customer_id: None,
+            label: Some("Blue jacket".to_string()),
+            notes: None,
+            lines,
+            cart_discount: Decimal::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_suspend_prices_cart_and_lists_per_station() {
+        let pool = setup_test_db().await;
+        let service = SuspendedSaleService::new(pool);
+
+        let mut discounted = request("reg-1", vec![line("p1", 2, 999), line("p2", 1, 500)]);
+        discounted.cart_discount = Decimal::new(198, 2);
+        let first = service.suspend(discounted).await.unwrap();
+        service.suspend(request("reg-1", vec![line("p2", 1, 500)])).await.unwrap();
+        service.suspend(request("reg-2", vec![line("p1", 1, 999)])).await.unwrap();
+
+        assert_eq!(first.status, "draft");
+        assert!(first.parked);
+        assert!((first.subtotal - 24.98).abs() < 1e-9);
+        assert!((first.discount_amount - 1.98).abs() < 1e-9);
+        assert_eq!(first.items_count, 2);
+        assert_eq!(first.lines[0].product_name.as_deref(), Some("Widget"));
+        assert!(first.expires_at > first.suspended_at);
+
+        assert_eq!(service.list("t1", "s1", None, false).await.unwrap().len(), 3);
+        assert_eq!(service.list("t1", "s1", Some("reg-1"), false).await.unwrap().len(), 2);
+        assert!(service.list("t2", "s1", None, false).await.unwrap().is_empty());
+
+        // Unknown products and empty carts are rejected
+        assert!(matches!(
+            service.suspend(request("reg-1", vec![line("nope", 1, 100)])).await,
+            Err(SuspendedSaleError::Validation(_))
+        ));
+        assert!(matches!(
+            service.suspend(request("reg-1", Vec::new())).await,
+            Err(SuspendedSaleError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resume_on_another_register_is_exclusive() {
+        let pool = setup_test_db().await;
+        let service = SuspendedSaleService::new(pool);
+        let parked = service.suspend(request("reg-1", vec![line("p1", 1, 999)])).await.unwrap();
+
+        let resumed = service.resume("t1", &parked.id, Some("reg-2"), "u2").await.unwrap();
+        assert!(!resumed.parked);
+        assert_eq!(resumed.station_id.as_deref(), Some("reg-2"));
+        assert_eq!(resumed.resumed_by.as_deref(), Some("u2"));
+
+        // Open carts are hidden from the parked list and cannot be taken twice
+        assert!(service.list("t1", "s1", None, false).await.unwrap().is_empty());
+        assert_eq!(service.list("t1", "s1", None, true).await.unwrap().len(), 1);
+        assert!(matches!(
+            service.resume("t1", &parked.id, Some("reg-1"), "u1").await,
+            Err(SuspendedSaleError::Unavailable(_))
+        ));
+
+        // Park it again with more items, then cancel it
+        let reparked = service
+            .suspend_again(&parked.id, request("reg-2", vec![line("p1", 1, 999), line("p2", 3, 500)]))
+            .await
+            .unwrap();
+        assert!(reparked.parked);
+        assert_eq!(reparked.lines.len(), 2);
+        assert!((reparked.subtotal - 24.99).abs() < 1e-9);
+
+        let cancelled = service.cancel("t1", &parked.id).await.unwrap();
+        assert_eq!(cancelled.status, "voided");
+        assert_eq!(cancelled.void_reason.as_deref(), Some(VOID_REASON_CANCELLED));
+        assert!(matches!(
+            service.resume("t1", &parked.id, None, "u1").await,
+            Err(SuspendedSaleError::Unavailable(_))
+        ));
+        assert!(matches!(
+            service.resume("t1", "missing", None, "u1").await,
+            Err(SuspendedSaleError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_parked_carts_expire_but_open_carts_do_not() {
+        let pool = setup_test_db().await;
+        sqlx::query("INSERT INTO settings (key, value, scope, scope_id) VALUES (?, '30', 'store', 's1')")
+            .bind(SETTING_SUSPENDED_SALE_TTL_MINUTES)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let service = SuspendedSaleService::new(pool);
+
+        let parked = service.suspend(request("reg-1", vec![line("p1", 1, 999)])).await.unwrap();
+        let open = service.suspend(request("reg-1", vec![line("p2", 1, 500)])).await.unwrap();
+        service.resume("t1", &open.id, None, "u1").await.unwrap();
+
+        let suspended_at = DateTime::parse_from_rfc3339(&parked.suspended_at).unwrap();
+        let expires_at = DateTime::parse_from_rfc3339(&parked.expires_at).unwrap();
+        assert_eq!(expires_at - suspended_at, Duration::minutes(30));
+
+        assert_eq!(service.expire_stale("t1", Utc::now()).await.unwrap(), 0);
+        assert_eq!(
+            service.expire_stale("t1", Utc::now() + Duration::minutes(31)).await.unwrap(),
+            1
+        );
+
+        let expired = service.get("t1", &parked.id).await.unwrap();
+        assert_eq!(expired.status, "voided");
+        assert_eq!(expired.void_reason.as_deref(), Some(VOID_REASON_EXPIRED));
+        assert!(matches!(
+            service.resume("t1", &parked.id, None, "u1").await,
+            Err(SuspendedSaleError::Unavailable(msg)) if msg.contains("expired")
+        ));
+        assert_eq!(service.get("t1", &open.id).await.unwrap().status, "draft");
+    }
+
+    #[tokio::test]
+    async fn test_finalize_checks_out_a_draft_once() {
+        let pool = setup_test_db().await;
+        let service = SuspendedSaleService::new(pool.clone());
+        let parked = service.suspend(request("reg-1", vec![line("p1", 1, 999)])).await.unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        assert!(finalize_suspended_sale(&mut conn, "t1", &parked.id, "sale-1").await.unwrap());
+        assert!(!finalize_suspended_sale(&mut conn, "t1", &parked.id, "sale-2").await.unwrap());
+        drop(conn);
+
+        let finalized = service.get("t1", &parked.id).await.unwrap();
+        assert_eq!(finalized.status, status_str(&TransactionStatus::Finalized));
+        assert_eq!(finalized.sale_id.as_deref(), Some("sale-1"));
+        assert!(matches!(
+            service.cancel("t1", &parked.id).await,
+            Err(SuspendedSaleError::Unavailable(msg)) if msg.contains("checked out")
+        ));
+    }
+}
@@ -17,6 +17,10 @@ use crate::connectors::quickbooks::client::QuickBooksClient;
 use crate::connectors::supabase::client::SupabaseClient;
 use crate::flows::woo_to_qbo::WooToQboFlow;
 use crate::flows::woo_to_supabase::WooToSupabaseFlow;
+use crate::connectors::clover::{CloverClient, CloverTokens};
+use crate::flows::clover_sync::{CloverSyncFlow, CloverSyncRequest};
+use crate::connectors::shopify::{ShopifyClient, ShopifyTokens};
+use crate::flows::shopify_sync::{Direction, ShopifySyncFlow, ShopifySyncRequest};
 use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -525,7 +529,7 @@ impl SyncOrchestrator {
         };
         
         // Parse connector_id to determine source and target
-        // Format: "source-to-target" (e.g., "woocommerce-to-quickbooks", "woocommerce-to-supabase")
+        // Format: "source-to-target" (e.g., "woocommerce-to-quickbooks", "woocommerce-to-supabase", "clover-to-pos", "pos-to-shopify")
         let parts: Vec<&str> = connector_id.split("-to-").collect();
         if parts.len() != 2 {
             return Err(format!("Invalid connector_id format: {}", connector_id));
@@ -547,6 +551,15 @@ impl SyncOrchestrator {
             ("woocommerce", "supabase", entity) => {
                 self.sync_woo_to_supabase(tenant_id, sync_id, entity, options, &mut result).await?;
             }
+            ("clover", "pos", entity) => {
+                self.sync_clover_to_pos(tenant_id, sync_id, entity, options, &mut result).await?;
+            }
+            ("shopify", "pos", entity) => {
+                self.sync_shopify(tenant_id, sync_id, entity, Direction::Pull, options, &mut result).await?;
+            }
+            ("pos", "shopify", entity) => {
+                self.sync_shopify(tenant_id, sync_id, entity, Direction::Push, options, &mut result).await?;
+            }
             _ => {
                 return Err(format!(
                     "Unsupported sync route: {} → {} for entity type {}",
@@ -1239,6 +1252,205 @@ impl SyncOrchestrator {
         Ok(())
     }
 
+    /// Sync Clover to the POS
+    ///
+    /// Records go to the store named by the `store_id` filter, or the
+    /// tenant's first active store. An incremental sync only looks at
+    /// records changed since the start of its date range.
+    async fn sync_clover_to_pos(
+        &self,
+        tenant_id: &str,
+        sync_id: &str,
+        entity_type: &str,
+        options: &SyncOptions,
+        result: &mut SyncResult,
+    ) -> Result<(), String> {
+        // Load Clover credentials
+        let clover_creds = self.credential_service
+            .get_credentials(tenant_id, "clover")
+            .await
+            .map_err(|e| format!("Failed to load Clover credentials: {}", e))?
+            .ok_or_else(|| "Clover credentials not found".to_string())?;
+
+        let clover_config = match clover_creds {
+            PlatformCredentials::Clover(config) => config,
+            _ => return Err("Invalid Clover credentials type".to_string()),
+        };
+
+        // Conflicts and sync operations are recorded against the credential
+        let credential_id: String = sqlx::query_scalar(
+            "SELECT id FROM integration_credentials WHERE tenant_id = ? AND platform = 'clover' AND is_active = 1"
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to load Clover credential record: {}", e))?
+        .ok_or_else(|| "Clover credentials not found".to_string())?;
+
+        let store_id = match options.filters.get("store_id") {
+            Some(store_id) => store_id.clone(),
+            None => sqlx::query_scalar(
+                "SELECT id FROM stores WHERE tenant_id = ? AND is_active = 1 ORDER BY created_at LIMIT 1"
+            )
+            .bind(tenant_id)
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| format!("Failed to load store: {}", e))?
+            .ok_or_else(|| "No active store to sync Clover records to".to_string())?,
+        };
+
+        let modified_since = match (&options.mode, &options.date_range) {
+            (SyncMode::Incremental, Some(range)) => Some(
+                chrono::DateTime::parse_from_rfc3339(&range.start)
+                    .map_err(|e| format!("Invalid date range start {}: {}", range.start, e))?
+                    .timestamp_millis(),
+            ),
+            _ => None,
+        };
+
+        // Create client and flow
+        let clover_client = CloverClient::new(CloverTokens {
+            access_token: clover_config.access_token,
+            merchant_id: clover_config.merchant_id,
+        })
+        .map_err(|e| format!("Failed to create Clover client: {}", e))?;
+
+        let flow = CloverSyncFlow::new(
+            self.db.clone(),
+            clover_client,
+            credential_id,
+            self.direction_control.clone(),
+        );
+
+        let request = CloverSyncRequest {
+            tenant_id: tenant_id.to_string(),
+            store_id,
+            sync_id: sync_id.to_string(),
+            modified_since,
+            dry_run: options.dry_run,
+        };
+        let counts = flow.sync(entity_type, &request).await?;
+
+        result.records_processed += counts.processed();
+        result.records_created += counts.created;
+        result.records_updated += counts.updated;
+        result.records_failed += counts.failed;
+        result.errors.extend(counts.errors.into_iter().map(|(clover_id, error_message)| SyncError {
+            entity_type: entity_type.to_string(),
+            entity_id: clover_id,
+            error_message,
+        }));
+
+        tracing::info!(
+            "Clover {} sync: {} created, {} updated, {} skipped, {} conflicts, {} failed",
+            entity_type, counts.created, counts.updated, counts.skipped, counts.conflicts, counts.failed
+        );
+
+        Ok(())
+    }
+
+    /// Sync between Shopify and the POS, one way
+    ///
+    /// Runs only when the credential's sync direction control allows the
+    /// direction for the entity type. Stock and sales are taken at the store
+    /// named by the `store_id` filter, or the tenant's first active store.
+    /// An incremental sync only looks at records changed since the start of
+    /// its date range.
+    async fn sync_shopify(
+        &self,
+        tenant_id: &str,
+        sync_id: &str,
+        entity_type: &str,
+        direction: Direction,
+        options: &SyncOptions,
+        result: &mut SyncResult,
+    ) -> Result<(), String> {
+        // Load Shopify credentials
+        let shopify_creds = self.credential_service
+            .get_credentials(tenant_id, "shopify")
+            .await
+            .map_err(|e| format!("Failed to load Shopify credentials: {}", e))?
+            .ok_or_else(|| "Shopify credentials not found".to_string())?;
+
+        let shopify_config = match shopify_creds {
+            PlatformCredentials::Shopify(config) => config,
+            _ => return Err("Invalid Shopify credentials type".to_string()),
+        };
+
+        // Conflicts and sync operations are recorded against the credential
+        let credential_id: String = sqlx::query_scalar(
+            "SELECT id FROM integration_credentials WHERE tenant_id = ? AND platform = 'shopify' AND is_active = 1"
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to load Shopify credential record: {}", e))?
+        .ok_or_else(|| "Shopify credentials not found".to_string())?;
+
+        let store_id = match options.filters.get("store_id") {
+            Some(store_id) => store_id.clone(),
+            None => sqlx::query_scalar(
+                "SELECT id FROM stores WHERE tenant_id = ? AND is_active = 1 ORDER BY created_at LIMIT 1"
+            )
+            .bind(tenant_id)
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| format!("Failed to load store: {}", e))?
+            .ok_or_else(|| "No active store to sync Shopify records with".to_string())?,
+        };
+
+        let updated_since = match (&options.mode, &options.date_range) {
+            (SyncMode::Incremental, Some(range)) => Some(
+                chrono::DateTime::parse_from_rfc3339(&range.start)
+                    .map_err(|e| format!("Invalid date range start {}: {}", range.start, e))?
+                    .to_rfc3339(),
+            ),
+            _ => None,
+        };
+
+        // Create client and flow
+        let shopify_client = ShopifyClient::new(ShopifyTokens {
+            access_token: shopify_config.access_token,
+            shop_domain: shopify_config.shop_domain,
+            scope: None,
+        })
+        .map_err(|e| format!("Failed to create Shopify client: {}", e))?;
+
+        let flow = ShopifySyncFlow::new(
+            self.db.clone(),
+            shopify_client,
+            credential_id,
+            shopify_config.location_id,
+            self.direction_control.clone(),
+        );
+
+        let request = ShopifySyncRequest {
+            tenant_id: tenant_id.to_string(),
+            store_id,
+            sync_id: sync_id.to_string(),
+            updated_since,
+            dry_run: options.dry_run,
+        };
+        let counts = flow.sync(direction, entity_type, &request).await?;
+
+        result.records_processed += counts.processed();
+        result.records_created += counts.created;
+        result.records_updated += counts.updated;
+        result.records_failed += counts.failed;
+        result.errors.extend(counts.errors.into_iter().map(|(id, error_message)| SyncError {
+            entity_type: entity_type.to_string(),
+            entity_id: id,
+            error_message,
+        }));
+
+        tracing::info!(
+            "Shopify {} sync ({}): {} created, {} updated, {} skipped, {} conflicts, {} failed",
+            entity_type, direction.as_str(), counts.created, counts.updated, counts.skipped, counts.conflicts, counts.failed
+        );
+
+        Ok(())
+    }
+
     /// Create sync state record
     async fn create_sync_state(
         &self,
@@ -8,13 +8,22 @@
 //! - Queue bounds enforcement (TASK-007)
 //! - Entity priority ordering (TASK-008)
 //! - Idempotency key deduplication (TASK-006)
+//! - Debounced WooCommerce stock pushes, sent in batches
 //!
 //! Requirements: 9.1, 9.4
 
+use crate::connectors::woocommerce::client::WooCommerceClient;
+use crate::flows::woo_stock_push::{self, PushOutcome, WooStockPushFlow};
 use crate::models::sync::SyncQueueItem;
+use crate::services::credential_service::{CredentialService, PlatformCredentials};
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_SYNC, SOURCE_SYNC_QUEUE,
+};
+use crate::services::sync_direction_control::SyncDirectionControl;
 use crate::services::sync_orchestrator::SyncError;
 use sqlx::SqlitePool;
 use serde_json::Value;
+use std::sync::Arc;
 use std::time::Duration;
 use sha2::{Sha256, Digest};
 
@@ -139,6 +148,31 @@ pub fn generate_idempotency_key(
     format!("{:x}", hash)
 }
 
+// ============================================================================
+// Stock Pushes
+// ============================================================================
+
+/// Queued by stock movements (see migration 080); one pending per product
+const STOCK_PUSH_ENTITY: &str = "inventory";
+const STOCK_PUSH_OPERATION: &str = "push";
+
+/// Products pushed per pass; one WooCommerce lookup covers them all
+const STOCK_PUSH_BATCH_SIZE: i64 = 100;
+
+/// How often the stock push worker looks for pushes whose debounce has run out
+const STOCK_PUSH_INTERVAL_SECONDS: u64 = 5;
+
+/// What one stock push pass did with a tenant's due pushes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StockPushCounts {
+    pub pushed: usize,
+    pub unchanged: usize,
+    pub oversold: usize,
+    pub skipped: usize,
+    /// Left pending for a retry after their backoff, or failed for good
+    pub failed: usize,
+}
+
 // ============================================================================
 // Sync Queue Processor
 // ============================================================================
@@ -366,6 +400,7 @@ impl SyncQueueProcessor {
         let delay = self.backoff_policy.calculate_delay(new_retry_count as u32);
 
         let new_status = if delay.is_some() { "pending" } else { "failed" };
+        let retry_after = format!("+{} seconds", delay.map_or(0, |d| d.as_secs()));
 
         sqlx::query(
             r"
@@ -373,6 +408,7 @@ impl SyncQueueProcessor {
             SET sync_status = ?,
                 retry_count = ?,
                 last_retry_at = datetime('now'),
+                not_before = datetime('now', ?),
                 error_message = ?,
                 updated_at = datetime('now')
             WHERE id = ?
@@ -380,6 +416,7 @@ impl SyncQueueProcessor {
         )
         .bind(new_status)
         .bind(new_retry_count)
+        .bind(retry_after)
         .bind(error_message)
         .bind(item_id)
         .execute(&self.db)
@@ -432,6 +469,241 @@ impl SyncQueueProcessor {
         &self.backoff_policy
     }
 
+    // ========================================================================
+    // WooCommerce Stock Pushes
+    // ========================================================================
+
+    /// Start the background worker that sends due stock pushes to WooCommerce
+    pub fn start_stock_push_worker(self, credentials: CredentialService) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(STOCK_PUSH_INTERVAL_SECONDS));
+
+            tracing::info!(
+                "WooCommerce stock push worker started - checking every {} seconds",
+                STOCK_PUSH_INTERVAL_SECONDS
+            );
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = self.push_due_stock(&credentials).await {
+                    tracing::error!("Stock push pass failed: {}", e.error_message);
+                }
+            }
+        })
+    }
+
+    /// Send every tenant's due stock pushes
+    ///
+    /// Pushes for a tenant no longer connected to WooCommerce are failed;
+    /// the next movement after reconnecting queues a fresh one.
+    pub async fn push_due_stock(&self, credentials: &CredentialService) -> Result<(), SyncError> {
+        let tenant_ids: Vec<String> = sqlx::query_scalar(
+            r"
+            SELECT DISTINCT tenant_id FROM sync_queue
+            WHERE entity_type = ? AND operation = ? AND sync_status = 'pending'
+              AND (not_before IS NULL OR not_before <= datetime('now'))
+            "
+        )
+        .bind(STOCK_PUSH_ENTITY)
+        .bind(STOCK_PUSH_OPERATION)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| SyncError {
+            entity_type: "queue".to_string(),
+            entity_id: STOCK_PUSH_ENTITY.to_string(),
+            error_message: format!("Failed to fetch due stock pushes: {}", e),
+        })?;
+
+        for tenant_id in tenant_ids {
+            let flow = match self.stock_push_flow(&tenant_id, credentials).await {
+                Ok(Some(flow)) => flow,
+                Ok(None) => {
+                    self.fail_stock_pushes(&tenant_id, "WooCommerce is not connected").await?;
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Stock pushes for tenant {} not sent: {}", tenant_id, e);
+                    continue;
+                }
+            };
+
+            let counts = self.push_stock(&tenant_id, &flow).await?;
+            tracing::info!("Stock pushes for tenant {}: {:?}", tenant_id, counts);
+        }
+
+        Ok(())
+    }
+
+    /// Fetch a tenant's stock pushes whose debounce (or retry backoff) has run out
+    pub async fn fetch_due_stock_pushes(
+        &self,
+        tenant_id: &str,
+        limit: i64,
+    ) -> Result<Vec<SyncQueueItem>, SyncError> {
+        sqlx::query_as::<_, SyncQueueItem>(
+            r"
+            SELECT * FROM sync_queue
+            WHERE tenant_id = ? AND entity_type = ? AND operation = ? AND sync_status = 'pending'
+              AND (not_before IS NULL OR not_before <= datetime('now'))
+            ORDER BY not_before ASC, created_at ASC
+            LIMIT ?
+            "
+        )
+        .bind(tenant_id)
+        .bind(STOCK_PUSH_ENTITY)
+        .bind(STOCK_PUSH_OPERATION)
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| SyncError {
+            entity_type: "queue".to_string(),
+            entity_id: tenant_id.to_string(),
+            error_message: format!("Failed to fetch due stock pushes: {}", e),
+        })
+    }
+
+    /// Send a tenant's due stock pushes to WooCommerce in one pass
+    ///
+    /// A push the flow could not deliver is retried after the backoff
+    /// delay. One that went out is completed unless another movement was
+    /// coalesced into it meanwhile, in which case it stays pending and goes
+    /// out again once its new debounce runs out.
+    pub async fn push_stock(&self, tenant_id: &str, flow: &WooStockPushFlow) -> Result<StockPushCounts, SyncError> {
+        let items = self.fetch_due_stock_pushes(tenant_id, STOCK_PUSH_BATCH_SIZE).await?;
+        let mut counts = StockPushCounts::default();
+        if items.is_empty() {
+            return Ok(counts);
+        }
+
+        let product_ids: Vec<String> = items.iter().map(|item| item.entity_id.clone()).collect();
+        let outcomes = match flow.push(tenant_id, &product_ids).await {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                for item in &items {
+                    self.mark_failed_with_backoff(&item.id, &e).await?;
+                }
+                counts.failed = items.len();
+                return Ok(counts);
+            }
+        };
+
+        for item in &items {
+            match outcomes.get(&item.entity_id) {
+                Some(PushOutcome::Failed(e)) => {
+                    self.mark_failed_with_backoff(&item.id, e).await?;
+                    counts.failed += 1;
+                    continue;
+                }
+                None => {
+                    self.mark_failed_with_backoff(&item.id, "Product was not pushed").await?;
+                    counts.failed += 1;
+                    continue;
+                }
+                Some(PushOutcome::Pushed(_)) => counts.pushed += 1,
+                Some(PushOutcome::Unchanged) => counts.unchanged += 1,
+                Some(PushOutcome::Oversold(_)) => counts.oversold += 1,
+                Some(PushOutcome::Skipped(reason)) => {
+                    tracing::debug!("Stock push of product {} skipped: {}", item.entity_id, reason);
+                    counts.skipped += 1;
+                }
+            }
+            self.complete_stock_push(item).await?;
+        }
+
+        Ok(counts)
+    }
+
+    /// Complete a push, unless a movement was coalesced into it since it was fetched
+    async fn complete_stock_push(&self, item: &SyncQueueItem) -> Result<(), SyncError> {
+        sqlx::query(
+            r"
+            UPDATE sync_queue
+            SET sync_status = 'completed',
+                error_message = NULL,
+                updated_at = datetime('now')
+            WHERE id = ? AND payload = ?
+            "
+        )
+        .bind(&item.id)
+        .bind(&item.payload)
+        .execute(&self.db)
+        .await
+        .map_err(|e| SyncError {
+            entity_type: item.entity_type.clone(),
+            entity_id: item.entity_id.clone(),
+            error_message: format!("Failed to complete stock push: {}", e),
+        })?;
+
+        Ok(())
+    }
+
+    /// Fail a tenant's pending stock pushes without retrying them
+    async fn fail_stock_pushes(&self, tenant_id: &str, error_message: &str) -> Result<(), SyncError> {
+        sqlx::query(
+            r"
+            UPDATE sync_queue
+            SET sync_status = 'failed',
+                error_message = ?,
+                updated_at = datetime('now')
+            WHERE tenant_id = ? AND entity_type = ? AND operation = ? AND sync_status = 'pending'
+            "
+        )
+        .bind(error_message)
+        .bind(tenant_id)
+        .bind(STOCK_PUSH_ENTITY)
+        .bind(STOCK_PUSH_OPERATION)
+        .execute(&self.db)
+        .await
+        .map_err(|e| SyncError {
+            entity_type: "queue".to_string(),
+            entity_id: tenant_id.to_string(),
+            error_message: format!("Failed to fail stock pushes: {}", e),
+        })?;
+
+        Ok(())
+    }
+
+    /// The push flow for a tenant's active WooCommerce connection, if it has one
+    async fn stock_push_flow(
+        &self,
+        tenant_id: &str,
+        credentials: &CredentialService,
+    ) -> Result<Option<WooStockPushFlow>, String> {
+        let woo_config = match credentials
+            .get_credentials(tenant_id, woo_stock_push::PLATFORM)
+            .await
+            .map_err(|e| format!("Failed to load WooCommerce credentials: {}", e))?
+        {
+            Some(PlatformCredentials::WooCommerce(config)) => config,
+            Some(_) => return Err("Invalid WooCommerce credentials type".to_string()),
+            None => return Ok(None),
+        };
+
+        // Operations and conflicts are recorded against the credential
+        let credential_id: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM integration_credentials WHERE tenant_id = ? AND platform = ? AND is_active = 1"
+        )
+        .bind(tenant_id)
+        .bind(woo_stock_push::PLATFORM)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| format!("Failed to load WooCommerce credential record: {}", e))?;
+        let Some(credential_id) = credential_id else {
+            return Ok(None);
+        };
+
+        let client = WooCommerceClient::new(woo_config)
+            .map_err(|e| format!("Failed to create WooCommerce client: {}", e))?;
+
+        Ok(Some(WooStockPushFlow::new(
+            self.db.clone(),
+            client,
+            credential_id,
+            Arc::new(SyncDirectionControl::new(self.db.clone())),
+        )))
+    }
+
     /// Process a sync queue item by routing based on operation field
     pub async fn process_item(&self, item: &SyncQueueItem) -> Result<(), SyncError> {
         // Parse payload
@@ -671,6 +943,8 @@ impl SyncQueueProcessor {
             error_message: format!("Database error creating product: {}", e),
         })?;
         
+        self.sync_stock_level(item, tenant_id, store_id, quantity).await?;
+        
         tracing::info!("Created product {} (SKU: {})", item.entity_id, sku);
         Ok(())
     }
@@ -686,7 +960,7 @@ impl SyncQueueProcessor {
         let barcode = payload.get("barcode").and_then(|v| v.as_str()).unwrap_or("");
         
         sqlx::query(
-            r"UPDATE products SET sku = ?, name = ?, description = ?, category = ?, unit_price = ?, cost = ?, quantity_on_hand = ?, barcode = ?, updated_at = datetime('now'), sync_version = sync_version + 1
+            r"UPDATE products SET sku = ?, name = ?, description = ?, category = ?, unit_price = ?, cost = ?, barcode = ?, updated_at = datetime('now'), sync_version = sync_version + 1
               WHERE id = ?"
         )
         .bind(sku)
@@ -695,7 +969,6 @@ impl SyncQueueProcessor {
         .bind(category)
         .bind(unit_price)
         .bind(cost)
-        .bind(quantity)
         .bind(barcode)
         .bind(&item.entity_id)
         .execute(&self.db)
@@ -706,10 +979,37 @@ impl SyncQueueProcessor {
             error_message: format!("Database error updating product: {}", e),
         })?;
         
+        self.sync_stock_level(item, &item.tenant_id, &item.store_id, quantity).await?;
+        
         tracing::info!("Updated product {} (SKU: {})", item.entity_id, sku);
         Ok(())
     }
 
+    /// Bring a synced product's stock to the synced level through the inventory ledger
+    async fn sync_stock_level(
+        &self,
+        item: &SyncQueueItem,
+        tenant_id: &str,
+        store_id: &str,
+        quantity: f64,
+    ) -> Result<(), SyncError> {
+        let movement = StockMovement::new(tenant_id, &item.entity_id, MovementType::Adjustment, 0.0, REASON_SYNC)
+            .with_source(SOURCE_SYNC_QUEUE, &item.id)
+            .at_store(store_id);
+        
+        let result: Result<_, LedgerError> = async {
+            let mut conn = self.db.acquire().await?;
+            inventory_ledger_service::set_on_hand(&mut conn, movement, quantity).await
+        }
+        .await;
+        
+        result.map(|_| ()).map_err(|e| SyncError {
+            entity_type: item.entity_type.clone(),
+            entity_id: item.entity_id.clone(),
+            error_message: format!("Failed to record stock movement: {}", e),
+        })
+    }
+
     async fn delete_product(&self, item: &SyncQueueItem) -> Result<(), SyncError> {
         // Soft delete - set deleted_at timestamp
         sqlx::query(
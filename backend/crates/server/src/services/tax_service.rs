@@ -0,0 +1,703 @@
+/**
+ * Tax Service
+ *
+ * Loads the sales tax configuration used at checkout and maintains it:
+ * - Store jurisdictions: the `tax_rules` rows of a store, one per authority
+ *   (GST + PST, HST, US state + county, ...), applied in `sort_order`
+ * - Product tax classes (`products.tax_class`)
+ * - Customer exemptions (`customers.tax_exempt*`)
+ *
+ * - Store tax policy: whether shelf prices include tax and where tax is
+ *   rounded (`store.prices_include_tax` / `store.tax_rounding` settings)
+ *
+ * A store without rules of its own falls back to the tenant's default tax
+ * rule, then to the localization tax rate, then to 13%, taxed as a single
+ * authority on every non-exempt class. A store without policy settings uses
+ * the tenant's settings, then the global settings, then tax-exclusive prices with per-invoice
+ * rounding.
+ */
+
+use pos_core_domain::tax::{TaxExemption, TaxJurisdiction};
+use pos_core_domain::{DomainError, PriceMode, TaxRate, TaxRounding};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::utils::csv_validation::validate_tax_class;
+
+/// Tax rate used when a tenant has neither a default tax rule nor localization settings
+const DEFAULT_TAX_RATE_PERCENT: i64 = 13;
+
+/// Rate code of the single-rate fallback jurisdiction
+const FALLBACK_RATE_CODE: &str = "TAX";
+
+/// Setting key: whether shelf prices include tax (boolean)
+pub const SETTING_PRICES_INCLUDE_TAX: &str = "store.prices_include_tax";
+/// Setting key: `per_line` or `per_invoice` tax rounding
+pub const SETTING_TAX_ROUNDING: &str = "store.tax_rounding";
+
+#[derive(Debug, Error)]
+pub enum TaxError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error(transparent)]
+    Domain(#[from] DomainError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<TaxError> for ApiError {
+    fn from(err: TaxError) -> Self {
+        match err {
+            TaxError::Validation(msg) => Self::bad_request(msg),
+            TaxError::Domain(e) => Self::bad_request(e.to_string()),
+            TaxError::NotFound(msg) => Self::not_found(msg),
+            TaxError::Database(e) => Self::internal(format!("Database error: {e}")),
+        }
+    }
+}
+
+/// A `tax_rules` row as configured for a store
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct StoreTaxRule {
+    pub id: String,
+    pub store_id: String,
+    pub name: String,
+    pub authority: Option<String>,
+    pub rate: f64,
+    /// Comma-separated tax classes; `None` applies to every non-exempt class
+    pub tax_classes: Option<String>,
+    pub is_compound: bool,
+    pub sort_order: i64,
+}
+
+/// One rule when replacing a store's jurisdiction
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct StoreTaxRuleInput {
+    pub name: String,
+    pub authority: String,
+    pub rate: f64,
+    #[serde(default)]
+    pub tax_classes: Vec<String>,
+    #[serde(default)]
+    pub is_compound: bool,
+}
+
+/// How a store's prices are taxed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StoreTaxPolicy {
+    pub price_mode: PriceMode,
+    pub rounding: TaxRounding,
+}
+
+pub struct TaxService {
+    pool: SqlitePool,
+}
+
+impl TaxService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Tax jurisdiction that applies to sales made at `store_id`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured rate is invalid or the query fails.
+    pub async fn jurisdiction_for_store(
+        &self,
+        tenant_id: &str,
+        store_id: &str,
+    ) -> Result<TaxJurisdiction, TaxError> {
+        let rules = self.store_rules(tenant_id, store_id).await?;
+
+        if rules.is_empty() {
+            let rate = self.default_rate_percent(tenant_id).await;
+            let rates = if rate > Decimal::ZERO {
+                vec![TaxRate::new(
+                    FALLBACK_RATE_CODE.to_string(),
+                    rate,
+                    "Sales Tax".to_string(),
+                )?]
+            } else {
+                Vec::new()
+            };
+            return Ok(TaxJurisdiction::new("default".to_string(), "Default".to_string(), rates));
+        }
+
+        let mut rates = Vec::with_capacity(rules.len());
+        for rule in rules {
+            let percent = Decimal::from_f64(rule.rate)
+                .ok_or_else(|| TaxError::Validation(format!("Invalid tax rate: {}", rule.rate)))?;
+            let mut rate = TaxRate::new(rule.authority.unwrap_or_else(|| rule.name.clone()), percent, rule.name)?
+                .with_tax_classes(rule.tax_classes.as_deref().map(split_list).unwrap_or_default());
+            if rule.is_compound {
+                rate = rate.compound();
+            }
+            rates.push(rate);
+        }
+
+        Ok(TaxJurisdiction::new(store_id.to_string(), store_id.to_string(), rates))
+    }
+
+    /// Tax policy of a store: its own settings, then the tenant's, then the global settings
+    ///
+    /// # Errors
+    ///
+    /// Returns `TaxError::Validation` if a stored rounding value is unknown,
+    /// or a database error.
+    pub async fn policy_for_store(&self, tenant_id: &str, store_id: &str) -> Result<StoreTaxPolicy, TaxError> {
+        let mut policy = StoreTaxPolicy::default();
+
+        if let Some(value) = self.setting(SETTING_PRICES_INCLUDE_TAX, tenant_id, store_id).await? {
+            policy.price_mode = if value == "true" || value == "1" {
+                PriceMode::TaxInclusive
+            } else {
+                PriceMode::TaxExclusive
+            };
+        }
+
+        if let Some(value) = self.setting(SETTING_TAX_ROUNDING, tenant_id, store_id).await? {
+            policy.rounding = parse_rounding(&value)?;
+        }
+
+        Ok(policy)
+    }
+
+    /// Fail with `TaxError::NotFound` unless `store_id` is a store of the tenant
+    ///
+    /// # Errors
+    ///
+    /// Returns `TaxError::NotFound` for another tenant's or an unknown store,
+    /// or a database error.
+    pub async fn require_store(&self, tenant_id: &str, store_id: &str) -> Result<(), TaxError> {
+        let store: Option<String> = sqlx::query_scalar("SELECT id FROM stores WHERE id = ? AND tenant_id = ?")
+            .bind(store_id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        store
+            .map(|_| ())
+            .ok_or_else(|| TaxError::NotFound(format!("Store {store_id} not found")))
+    }
+
+    /// Store the tax policy of a store of the tenant
+    ///
+    /// # Errors
+    ///
+    /// Returns `TaxError::NotFound` if the store is not the tenant's, or an
+    /// error if the settings cannot be written.
+    pub async fn set_store_policy(
+        &self,
+        tenant_id: &str,
+        store_id: &str,
+        policy: StoreTaxPolicy,
+    ) -> Result<(), TaxError> {
+        self.require_store(tenant_id, store_id).await?;
+
+        let include_tax = if policy.price_mode == PriceMode::TaxInclusive { "true" } else { "false" };
+        let rounding = match policy.rounding {
+            TaxRounding::PerLine => "per_line",
+            TaxRounding::PerInvoice => "per_invoice",
+        };
+
+        let mut tx = self.pool.begin().await?;
+        for (key, value, data_type) in [
+            (SETTING_PRICES_INCLUDE_TAX, include_tax, "boolean"),
+            (SETTING_TAX_ROUNDING, rounding, "string"),
+        ] {
+            sqlx::query(
+                "INSERT INTO settings (key, value, scope, scope_id, data_type)
+                 VALUES (?, ?, 'store', ?, ?)
+                 ON CONFLICT(key, scope, scope_id) DO UPDATE SET
+                    value = excluded.value, data_type = excluded.data_type, updated_at = datetime('now')",
+            )
+            .bind(key)
+            .bind(value)
+            .bind(store_id)
+            .bind(data_type)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Value of a setting at store scope, falling back to tenant then global scope
+    async fn setting(&self, key: &str, tenant_id: &str, store_id: &str) -> Result<Option<String>, TaxError> {
+        let value = sqlx::query_scalar(
+            "SELECT value FROM settings
+             WHERE key = ? AND ((scope = 'store' AND scope_id = ?) OR (scope = 'tenant' AND scope_id = ?) OR scope = 'global')
+             ORDER BY CASE scope WHEN 'store' THEN 1 WHEN 'tenant' THEN 2 ELSE 3 END
+             LIMIT 1",
+        )
+        .bind(key)
+        .bind(store_id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(value)
+    }
+
+    /// Tax rules configured for a store, in the order they are applied
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn store_rules(&self, tenant_id: &str, store_id: &str) -> Result<Vec<StoreTaxRule>, TaxError> {
+        let rules = sqlx::query_as::<_, StoreTaxRule>(
+            "SELECT id, store_id, name, authority, rate, tax_classes, is_compound, sort_order
+             FROM tax_rules
+             WHERE tenant_id = ? AND store_id = ? AND store_id != 'default'
+             ORDER BY sort_order ASC, name ASC",
+        )
+        .bind(tenant_id)
+        .bind(store_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// Replace the tax rules of a store; an empty list reverts the store to the tenant default
+    ///
+    /// # Errors
+    ///
+    /// Returns `TaxError::Validation` for an invalid rate or tax class, or a
+    /// database error.
+    pub async fn replace_store_rules(
+        &self,
+        tenant_id: &str,
+        store_id: &str,
+        rules: &[StoreTaxRuleInput],
+    ) -> Result<Vec<StoreTaxRule>, TaxError> {
+        if store_id == "default" {
+            return Err(TaxError::Validation(
+                "Use the default tax rule for tenant-wide tax".to_string(),
+            ));
+        }
+
+        for rule in rules {
+            if rule.authority.trim().is_empty() {
+                return Err(TaxError::Validation("Tax authority is required".to_string()));
+            }
+            let percent = Decimal::from_f64(rule.rate)
+                .ok_or_else(|| TaxError::Validation(format!("Invalid tax rate: {}", rule.rate)))?;
+            TaxRate::new(rule.authority.clone(), percent, rule.name.clone())?;
+            if let Some(class) = rule.tax_classes.iter().find(|c| !validate_tax_class(c)) {
+                return Err(TaxError::Validation(format!("Unknown tax class: {class}")));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM tax_rules WHERE tenant_id = ? AND store_id = ?")
+            .bind(tenant_id)
+            .bind(store_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (sort_order, rule) in (0_i64..).zip(rules) {
+            let tax_classes = (!rule.tax_classes.is_empty()).then(|| rule.tax_classes.join(","));
+            sqlx::query(
+                "INSERT INTO tax_rules (
+                    id, tenant_id, store_id, name, rate, is_default,
+                    authority, tax_classes, is_compound, sort_order
+                 ) VALUES (?, ?, ?, ?, ?, 0, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(store_id)
+            .bind(&rule.name)
+            .bind(rule.rate)
+            .bind(rule.authority.trim())
+            .bind(tax_classes)
+            .bind(rule.is_compound)
+            .bind(sort_order)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        self.store_rules(tenant_id, store_id).await
+    }
+
+    /// Set the tax class of a product
+    ///
+    /// # Errors
+    ///
+    /// Returns `TaxError::Validation` for an unknown class or
+    /// `TaxError::NotFound` if the product does not exist.
+    pub async fn set_product_tax_class(
+        &self,
+        tenant_id: &str,
+        product_id: &str,
+        tax_class: &str,
+    ) -> Result<(), TaxError> {
+        if tax_class.is_empty() || !validate_tax_class(tax_class) {
+            return Err(TaxError::Validation(format!("Unknown tax class: {tax_class}")));
+        }
+
+        let updated = sqlx::query(
+            "UPDATE products SET tax_class = ?, updated_at = datetime('now'), sync_version = sync_version + 1
+             WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(tax_class.to_lowercase())
+        .bind(product_id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(TaxError::NotFound(format!("Product {product_id}")));
+        }
+        Ok(())
+    }
+
+    /// Record or clear a customer's tax exemption
+    ///
+    /// # Errors
+    ///
+    /// Returns `TaxError::Validation` if the certificate number is missing or
+    /// `TaxError::NotFound` if the customer does not exist.
+    pub async fn set_customer_exemption(
+        &self,
+        tenant_id: &str,
+        customer_id: &str,
+        exemption: Option<&TaxExemption>,
+    ) -> Result<(), TaxError> {
+        if exemption.is_some_and(|e| e.certificate_number.trim().is_empty()) {
+            return Err(TaxError::Validation(
+                "A certificate number is required for tax exemption".to_string(),
+            ));
+        }
+
+        let authorities = exemption
+            .filter(|e| !e.rate_codes.is_empty())
+            .map(|e| e.rate_codes.join(","));
+
+        let updated = sqlx::query(
+            "UPDATE customers
+             SET tax_exempt = ?, tax_exempt_certificate = ?, tax_exempt_authorities = ?,
+                 updated_at = datetime('now'), sync_version = sync_version + 1
+             WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(exemption.is_some())
+        .bind(exemption.map(|e| e.certificate_number.trim().to_string()))
+        .bind(authorities)
+        .bind(customer_id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(TaxError::NotFound(format!("Customer {customer_id}")));
+        }
+        Ok(())
+    }
+
+    /// Tenant-wide tax rate (percent) from the default tax rule or localization settings
+    async fn default_rate_percent(&self, tenant_id: &str) -> Decimal {
+        // First try to get default tax rule
+        let tax_rule: Option<(f64,)> = sqlx::query_as(
+            "SELECT rate FROM tax_rules WHERE tenant_id = ? AND is_default = 1 LIMIT 1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten();
+
+        if let Some(rate) = tax_rule.and_then(|(rate,)| Decimal::from_f64(rate)) {
+            return rate;
+        }
+
+        // Fall back to localization settings
+        let localization: Option<(f64,)> = sqlx::query_as(
+            "SELECT tax_rate FROM localization_settings WHERE tenant_id = ?",
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten();
+
+        if let Some(rate) = localization.and_then(|(rate,)| Decimal::from_f64(rate)) {
+            return rate;
+        }
+
+        Decimal::from(DEFAULT_TAX_RATE_PERCENT)
+    }
+}
+
+/// Tax exemption of a customer of the tenant, if any
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub async fn customer_exemption(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    customer_id: &str,
+) -> Result<Option<TaxExemption>, sqlx::Error> {
+    let row: Option<(bool, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT tax_exempt, tax_exempt_certificate, tax_exempt_authorities
+         FROM customers WHERE id = ? AND tenant_id = ?",
+    )
+    .bind(customer_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.and_then(|(exempt, certificate, authorities)| {
+        exempt.then(|| TaxExemption {
+            certificate_number: certificate.unwrap_or_default(),
+            rate_codes: authorities.as_deref().map(split_list).unwrap_or_default(),
+        })
+    }))
+}
+
+/// Parse a `store.tax_rounding` setting value
+fn parse_rounding(value: &str) -> Result<TaxRounding, TaxError> {
+    match value.trim().trim_matches('"') {
+        "per_line" | "line" => Ok(TaxRounding::PerLine),
+        "per_invoice" | "invoice" => Ok(TaxRounding::PerInvoice),
+        other => Err(TaxError::Validation(format!("Unknown tax rounding: {other}"))),
+    }
+}
+
+/// Split a comma-separated column into trimmed, non-empty values
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE tax_rules (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                store_id TEXT NOT NULL DEFAULT 'default',
+                name TEXT NOT NULL,
+                rate REAL NOT NULL,
+                category TEXT,
+                is_default INTEGER NOT NULL DEFAULT 0,
+                authority TEXT,
+                tax_classes TEXT,
+                is_compound INTEGER NOT NULL DEFAULT 0,
+                sort_order INTEGER NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE localization_settings (tenant_id TEXT PRIMARY KEY, tax_rate REAL NOT NULL)",
+            "CREATE TABLE settings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT,
+                data_type TEXT NOT NULL DEFAULT 'string',
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(key, scope, scope_id)
+            )",
+            "CREATE TABLE customers (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                tax_exempt INTEGER NOT NULL DEFAULT 0,
+                tax_exempt_certificate TEXT,
+                tax_exempt_authorities TEXT,
+                updated_at TEXT,
+                sync_version INTEGER NOT NULL DEFAULT 0
+            )",
+            "INSERT INTO tax_rules (id, tenant_id, name, rate, is_default) VALUES ('d1', 't1', 'Default', 5.0, 1)",
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, tenant_id TEXT NOT NULL)",
+            "INSERT INTO customers (id, tenant_id) VALUES ('c1', 't1')",
+            "INSERT INTO stores (id, tenant_id) VALUES ('s1', 't1'), ('s2', 't1'), ('s3', 't2')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_store_without_rules_uses_tenant_default() {
+        let pool = setup_test_db().await;
+        let jurisdiction = TaxService::new(pool).jurisdiction_for_store("t1", "s1").await.unwrap();
+
+        assert_eq!(jurisdiction.rates.len(), 1);
+        assert_eq!(jurisdiction.rates[0].rate_code, FALLBACK_RATE_CODE);
+        assert_eq!(jurisdiction.rates[0].rate_percent, Decimal::from(5));
+    }
+
+    #[tokio::test]
+    async fn test_replace_store_rules_builds_jurisdiction() {
+        let pool = setup_test_db().await;
+        let service = TaxService::new(pool);
+
+        let rules = service
+            .replace_store_rules(
+                "t1",
+                "s1",
+                &[
+                    StoreTaxRuleInput {
+                        name: "GST".to_string(),
+                        authority: "GST".to_string(),
+                        rate: 5.0,
+                        tax_classes: Vec::new(),
+                        is_compound: false,
+                    },
+                    StoreTaxRuleInput {
+                        name: "PST".to_string(),
+                        authority: "PST".to_string(),
+                        rate: 7.0,
+                        tax_classes: vec!["standard".to_string()],
+                        is_compound: false,
+                    },
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(rules.len(), 2);
+
+        let jurisdiction = service.jurisdiction_for_store("t1", "s1").await.unwrap();
+        let codes: Vec<&str> = jurisdiction.rates.iter().map(|r| r.rate_code.as_str()).collect();
+        assert_eq!(codes, vec!["GST", "PST"]);
+        assert_eq!(jurisdiction.rates[1].tax_classes, vec!["standard".to_string()]);
+
+        assert!(matches!(
+            service
+                .replace_store_rules(
+                    "t1",
+                    "s1",
+                    &[StoreTaxRuleInput {
+                        name: "Bad".to_string(),
+                        authority: "BAD".to_string(),
+                        rate: 5.0,
+                        tax_classes: vec!["luxury".to_string()],
+                        is_compound: false,
+                    }],
+                )
+                .await,
+            Err(TaxError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_customer_exemption_round_trip() {
+        let pool = setup_test_db().await;
+        let service = TaxService::new(pool.clone());
+
+        service
+            .set_customer_exemption(
+                "t1",
+                "c1",
+                Some(&TaxExemption {
+                    certificate_number: "EX-123".to_string(),
+                    rate_codes: vec!["PST".to_string()],
+                }),
+            )
+            .await
+            .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let exemption = customer_exemption(&mut conn, "t1", "c1").await.unwrap().unwrap();
+        assert_eq!(exemption.certificate_number, "EX-123");
+        assert_eq!(exemption.rate_codes, vec!["PST".to_string()]);
+        // Not another tenant's to claim
+        assert!(customer_exemption(&mut conn, "t2", "c1").await.unwrap().is_none());
+        drop(conn);
+
+        service.set_customer_exemption("t1", "c1", None).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        assert!(customer_exemption(&mut conn, "t1", "c1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_store_policy_overrides_tenant_and_global_settings() {
+        let pool = setup_test_db().await;
+        let service = TaxService::new(pool.clone());
+
+        assert_eq!(service.policy_for_store("t1", "s1").await.unwrap(), StoreTaxPolicy::default());
+
+        sqlx::query(
+            "INSERT INTO settings (key, value, scope, data_type) VALUES ('store.prices_include_tax', 'true', 'global', 'boolean')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let policy = service.policy_for_store("t1", "s1").await.unwrap();
+        assert_eq!(policy.price_mode, PriceMode::TaxInclusive);
+        assert_eq!(policy.rounding, TaxRounding::PerInvoice);
+
+        // A tenant-wide default wins over the global setting for that tenant only
+        sqlx::query(
+            "INSERT INTO settings (key, value, scope, scope_id, data_type)
+             VALUES ('store.tax_rounding', 'per_line', 'tenant', 't1', 'string')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(service.policy_for_store("t1", "s1").await.unwrap().rounding, TaxRounding::PerLine);
+        assert_eq!(service.policy_for_store("t2", "s3").await.unwrap().rounding, TaxRounding::PerInvoice);
+
+        service
+            .set_store_policy(
+                "t1",
+                "s2",
+                StoreTaxPolicy {
+                    price_mode: PriceMode::TaxExclusive,
+                    rounding: TaxRounding::PerLine,
+                },
+            )
+            .await
+            .unwrap();
+        let policy = service.policy_for_store("t1", "s2").await.unwrap();
+        assert_eq!(policy.price_mode, PriceMode::TaxExclusive);
+        assert_eq!(policy.rounding, TaxRounding::PerLine);
+    }
+
+    #[tokio::test]
+    async fn test_store_policy_of_another_tenant_is_refused() {
+        let pool = setup_test_db().await;
+        let service = TaxService::new(pool.clone());
+
+        assert!(matches!(service.require_store("t1", "s3").await, Err(TaxError::NotFound(_))));
+        assert!(matches!(
+            service.set_store_policy("t1", "s3", StoreTaxPolicy::default()).await,
+            Err(TaxError::NotFound(_))
+        ));
+        let written: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM settings WHERE scope_id = 's3'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(written, 0);
+    }
+}
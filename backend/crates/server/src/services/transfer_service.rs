@@ -0,0 +1,769 @@
+/**
+ * Transfer Service
+ *
+ * Moves stock between stores (shops and warehouses) with transfer orders.
+ *
+ * Lifecycle:
+ * - request: a store asks for products from another store
+ * - ship: the sending store ships what it can; the shipped quantities leave
+ *   its stock and the order is in transit
+ * - receive: the receiving store counts what arrived; the received
+ *   quantities enter its stock and any difference from the shipped quantity
+ *   is kept on the line as a discrepancy with its reason
+ * - cancel: a request can be cancelled until it ships
+ *
+ * Stock in transit is in neither store. Every stock change is a `transfer`
+ * movement in the inventory ledger pointing at the transfer order.
+ */
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_TRANSFER_IN, REASON_TRANSFER_OUT,
+    SOURCE_TRANSFER_ORDER,
+};
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum TransferError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Transfer order not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid transfer state: {0}")]
+    InvalidState(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<LedgerError> for TransferError {
+    fn from(err: LedgerError) -> Self {
+        match err {
+            LedgerError::Database(e) => Self::Database(e),
+            other => Self::Validation(other.to_string()),
+        }
+    }
+}
+
+impl From<TransferError> for ApiError {
+    fn from(err: TransferError) -> Self {
+        match err {
+            TransferError::Validation(msg) => Self::bad_request(msg),
+            TransferError::NotFound(id) => Self::not_found(format!("Transfer order not found: {id}")),
+            TransferError::InvalidState(msg) => Self::conflict(msg),
+            TransferError::Database(e) => Self::internal(format!("Failed to access transfer orders: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    Requested,
+    InTransit,
+    Received,
+    Cancelled,
+}
+
+impl TransferStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Requested => "requested",
+            Self::InTransit => "in_transit",
+            Self::Received => "received",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A product and quantity to transfer
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferLineRequest {
+    pub product_id: String,
+    pub quantity: f64,
+}
+
+/// A transfer to request
+#[derive(Debug, Clone)]
+pub struct TransferRequest {
+    pub tenant_id: String,
+    pub from_store_id: String,
+    pub to_store_id: String,
+    pub requested_by: String,
+    pub notes: Option<String>,
+    pub lines: Vec<TransferLineRequest>,
+}
+
+/// Quantity shipped or received on one line, overriding the default
+#[derive(Debug, Clone, Deserialize)]
+pub struct LineQuantity {
+    pub line_id: String,
+    pub quantity: f64,
+    /// Why the received quantity differs from the shipped one
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct TransferOrderLine {
+    pub id: String,
+    pub product_id: String,
+    pub product_name: Option<String>,
+    pub quantity_requested: f64,
+    pub quantity_shipped: Option<f64>,
+    pub quantity_received: Option<f64>,
+    /// Received minus shipped, when they differ
+    pub discrepancy: Option<f64>,
+    pub discrepancy_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TransferOrder {
+    pub id: String,
+    pub transfer_number: String,
+    pub from_store_id: String,
+    pub to_store_id: String,
+    /// requested, in_transit, received or cancelled
+    pub status: String,
+    pub notes: Option<String>,
+    pub requested_by: String,
+    pub requested_at: String,
+    pub shipped_by: Option<String>,
+    pub shipped_at: Option<String>,
+    pub received_by: Option<String>,
+    pub received_at: Option<String>,
+    pub cancelled_by: Option<String>,
+    pub cancelled_at: Option<String>,
+    #[sqlx(skip)]
+    pub lines: Vec<TransferOrderLine>,
+}
+
+impl TransferOrder {
+    /// Whether any line arrived short or over
+    #[must_use]
+    pub fn has_discrepancies(&self) -> bool {
+        self.lines.iter().any(|line| line.discrepancy.is_some())
+    }
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+const SELECT_TRANSFER_ORDER: &str = "SELECT id, transfer_number, from_store_id, to_store_id, status,
+        notes, requested_by, requested_at, shipped_by, shipped_at, received_by, received_at,
+        cancelled_by, cancelled_at
+     FROM transfer_orders";
+
+/// Received and shipped quantities closer than this match
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+pub struct TransferService {
+    pool: SqlitePool,
+}
+
+impl TransferService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Request products from another store
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stores are the same or unknown, a product is
+    /// unknown, a quantity is not positive, or the database write fails.
+    pub async fn request(&self, request: TransferRequest) -> Result<TransferOrder, TransferError> {
+        if request.from_store_id == request.to_store_id {
+            return Err(TransferError::Validation(
+                "A transfer must go between two different stores".to_string(),
+            ));
+        }
+        if request.lines.is_empty() {
+            return Err(TransferError::Validation(
+                "Transfer order must have at least one product".to_string(),
+            ));
+        }
+        if let Some(line) = request.lines.iter().find(|line| line.quantity <= 0.0) {
+            return Err(TransferError::Validation(format!(
+                "Quantity for product {} must be positive",
+                line.product_id
+            )));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        for store_id in [&request.from_store_id, &request.to_store_id] {
+            let exists: Option<String> = sqlx::query_scalar("SELECT id FROM stores WHERE id = ?")
+                .bind(store_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if exists.is_none() {
+                return Err(TransferError::Validation(format!("Store not found: {store_id}")));
+            }
+        }
+
+        let today = Utc::now().format("%Y%m%d").to_string();
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM transfer_orders WHERE tenant_id = ? AND DATE(created_at) = DATE('now')",
+        )
+        .bind(&request.tenant_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let transfer_number = format!("TRF-{}-{:04}", today, count + 1);
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r"
+            INSERT INTO transfer_orders (
+                id, tenant_id, transfer_number, from_store_id, to_store_id, status, notes,
+                requested_by, requested_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&id)
+        .bind(&request.tenant_id)
+        .bind(&transfer_number)
+        .bind(&request.from_store_id)
+        .bind(&request.to_store_id)
+        .bind(TransferStatus::Requested.as_str())
+        .bind(&request.notes)
+        .bind(&request.requested_by)
+        .bind(&now)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        for line in &request.lines {
+            let product: Option<String> =
+                sqlx::query_scalar("SELECT id FROM products WHERE id = ? AND tenant_id = ?")
+                    .bind(&line.product_id)
+                    .bind(&request.tenant_id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            if product.is_none() {
+                return Err(TransferError::Validation(format!("Product not found: {}", line.product_id)));
+            }
+
+            sqlx::query(
+                "INSERT INTO transfer_order_lines (id, transfer_id, product_id, quantity_requested)
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&id)
+            .bind(&line.product_id)
+            .bind(line.quantity)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        tracing::info!(
+            transfer_id = %id,
+            transfer_number = %transfer_number,
+            from_store_id = %request.from_store_id,
+            to_store_id = %request.to_store_id,
+            "Transfer requested"
+        );
+
+        self.get(&request.tenant_id, &id).await
+    }
+
+    /// Ship a requested transfer, taking the shipped quantities out of the
+    /// sending store
+    ///
+    /// Lines not in `shipped` ship as requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is unknown or not requested, a line is
+    /// unknown or has a negative quantity, or the database write fails.
+    pub async fn ship(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        user_id: &str,
+        shipped: &[LineQuantity],
+    ) -> Result<TransferOrder, TransferError> {
+        let order = self.get(tenant_id, id).await?;
+        let quantities = line_quantities(&order, shipped, |line| Some(line.quantity_requested))?;
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query(
+            "UPDATE transfer_orders SET status = ?, shipped_by = ?, shipped_at = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ? AND status = ?",
+        )
+        .bind(TransferStatus::InTransit.as_str())
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(TransferStatus::Requested.as_str())
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(TransferError::InvalidState(format!(
+                "Only a requested transfer can be shipped; this one is {}",
+                order.status
+            )));
+        }
+
+        for LineUpdate { line, quantity, .. } in &quantities {
+            sqlx::query("UPDATE transfer_order_lines SET quantity_shipped = ? WHERE id = ?")
+                .bind(quantity)
+                .bind(&line.id)
+                .execute(&mut *tx)
+                .await?;
+
+            if *quantity > 0.0 {
+                let movement = StockMovement::new(
+                    tenant_id,
+                    &line.product_id,
+                    MovementType::Transfer,
+                    -*quantity,
+                    REASON_TRANSFER_OUT,
+                )
+                .with_source(SOURCE_TRANSFER_ORDER, id)
+                .by_user(user_id)
+                .at_store(&order.from_store_id);
+                inventory_ledger_service::record_movement(&mut tx, &movement).await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        tracing::info!(transfer_id = %id, from_store_id = %order.from_store_id, "Transfer shipped");
+
+        self.get(tenant_id, id).await
+    }
+
+    /// Receive a shipped transfer, putting the received quantities into the
+    /// receiving store
+    ///
+    /// Lines not in `received` arrive as shipped. A received quantity that
+    /// differs from the shipped one is recorded as a discrepancy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is unknown or not in transit, a line is
+    /// unknown or has a negative quantity, or the database write fails.
+    pub async fn receive(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        user_id: &str,
+        received: &[LineQuantity],
+    ) -> Result<TransferOrder, TransferError> {
+        let order = self.get(tenant_id, id).await?;
+        let quantities = line_quantities(&order, received, |line| line.quantity_shipped)?;
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query(
+            "UPDATE transfer_orders SET status = ?, received_by = ?, received_at = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ? AND status = ?",
+        )
+        .bind(TransferStatus::Received.as_str())
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(TransferStatus::InTransit.as_str())
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(TransferError::InvalidState(format!(
+                "Only a transfer in transit can be received; this one is {}",
+                order.status
+            )));
+        }
+
+        for LineUpdate { line, quantity, reason } in &quantities {
+            let shipped = line.quantity_shipped.unwrap_or(0.0);
+            let discrepancy = quantity - shipped;
+            let (discrepancy, reason) = if discrepancy.abs() < QUANTITY_EPSILON {
+                (None, None)
+            } else {
+                (Some(discrepancy), reason.clone())
+            };
+
+            sqlx::query(
+                "UPDATE transfer_order_lines SET quantity_received = ?, discrepancy = ?, discrepancy_reason = ?
+                 WHERE id = ?",
+            )
+            .bind(quantity)
+            .bind(discrepancy)
+            .bind(&reason)
+            .bind(&line.id)
+            .execute(&mut *tx)
+            .await?;
+
+            if *quantity > 0.0 {
+                let movement = StockMovement::new(
+                    tenant_id,
+                    &line.product_id,
+                    MovementType::Transfer,
+                    *quantity,
+                    REASON_TRANSFER_IN,
+                )
+                .with_source(SOURCE_TRANSFER_ORDER, id)
+                .by_user(user_id)
+                .at_store(&order.to_store_id)
+                .with_notes(reason);
+                inventory_ledger_service::record_movement(&mut tx, &movement).await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        let order = self.get(tenant_id, id).await?;
+        if order.has_discrepancies() {
+            tracing::warn!(
+                transfer_id = %id,
+                transfer_number = %order.transfer_number,
+                "Transfer received with discrepancies"
+            );
+        } else {
+            tracing::info!(transfer_id = %id, to_store_id = %order.to_store_id, "Transfer received");
+        }
+
+        Ok(order)
+    }
+
+    /// Cancel a transfer that has not shipped yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is unknown or already shipped.
+    pub async fn cancel(&self, tenant_id: &str, id: &str, user_id: &str) -> Result<TransferOrder, TransferError> {
+        let now = Utc::now().to_rfc3339();
+
+        let cancelled = sqlx::query(
+            "UPDATE transfer_orders SET status = ?, cancelled_by = ?, cancelled_at = ?, updated_at = ?
+             WHERE id = ? AND tenant_id = ? AND status = ?",
+        )
+        .bind(TransferStatus::Cancelled.as_str())
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(tenant_id)
+        .bind(TransferStatus::Requested.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        let order = self.get(tenant_id, id).await?;
+        if cancelled.rows_affected() == 0 {
+            return Err(TransferError::InvalidState(format!(
+                "Only a requested transfer can be cancelled; this one is {}",
+                order.status
+            )));
+        }
+
+        Ok(order)
+    }
+
+    /// Transfer orders from or to a store, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn list(
+        &self,
+        tenant_id: &str,
+        store_id: Option<&str>,
+        status: Option<TransferStatus>,
+    ) -> Result<Vec<TransferOrder>, TransferError> {
+        let status = status.map(TransferStatus::as_str);
+
+        let mut orders = sqlx::query_as::<_, TransferOrder>(&format!(
+            "{SELECT_TRANSFER_ORDER}
+             WHERE tenant_id = ?
+               AND (? IS NULL OR from_store_id = ? OR to_store_id = ?)
+               AND (? IS NULL OR status = ?)
+             ORDER BY requested_at DESC"
+        ))
+        .bind(tenant_id)
+        .bind(store_id)
+        .bind(store_id)
+        .bind(store_id)
+        .bind(status)
+        .bind(status)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for order in &mut orders {
+            order.lines = self.lines(&order.id).await?;
+        }
+
+        Ok(orders)
+    }
+
+    /// A transfer order with its lines
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotFound` if the tenant has no such transfer order.
+    pub async fn get(&self, tenant_id: &str, id: &str) -> Result<TransferOrder, TransferError> {
+        let mut order = sqlx::query_as::<_, TransferOrder>(&format!(
+            "{SELECT_TRANSFER_ORDER} WHERE id = ? AND tenant_id = ?"
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| TransferError::NotFound(id.to_string()))?;
+
+        order.lines = self.lines(id).await?;
+        Ok(order)
+    }
+
+    async fn lines(&self, id: &str) -> Result<Vec<TransferOrderLine>, TransferError> {
+        let lines = sqlx::query_as::<_, TransferOrderLine>(
+            "SELECT l.id, l.product_id, p.name AS product_name, l.quantity_requested, l.quantity_shipped,
+                    l.quantity_received, l.discrepancy, l.discrepancy_reason
+             FROM transfer_order_lines l
+             LEFT JOIN products p ON p.id = l.product_id
+             WHERE l.transfer_id = ?
+             ORDER BY l.rowid ASC",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lines)
+    }
+}
+
+/// Quantity to ship or receive on a line
+struct LineUpdate<'a> {
+    line: &'a TransferOrderLine,
+    quantity: f64,
+    reason: Option<String>,
+}
+
+/// Each line of an order with its quantity: the override when given, else
+/// `default`, with the override's reason
+fn line_quantities<'a>(
+    order: &'a TransferOrder,
+    overrides: &[LineQuantity],
+    default: impl Fn(&TransferOrderLine) -> Option<f64>,
+) -> Result<Vec<LineUpdate<'a>>, TransferError> {
+    if let Some(unknown) = overrides
+        .iter()
+        .find(|o| !order.lines.iter().any(|line| line.id == o.line_id))
+    {
+        return Err(TransferError::Validation(format!(
+            "Line {} is not on transfer {}",
+            unknown.line_id, order.transfer_number
+        )));
+    }
+    if let Some(negative) = overrides.iter().find(|o| o.quantity < 0.0) {
+        return Err(TransferError::Validation(format!(
+            "Quantity for line {} cannot be negative",
+            negative.line_id
+        )));
+    }
+
+    Ok(order
+        .lines
+        .iter()
+        .map(|line| match overrides.iter().find(|o| o.line_id == line.id) {
+            Some(o) => LineUpdate { line, quantity: o.quantity, reason: o.reason.clone() },
+            None => LineUpdate { line, quantity: default(line).unwrap_or(0.0), reason: None },
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::InventoryLedgerService;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE stores (id TEXT PRIMARY KEY, name TEXT NOT NULL)",
+            "CREATE TABLE products (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                store_id TEXT,
+                cost REAL NOT NULL DEFAULT 0,
+                reorder_point REAL,
+                quantity_on_hand REAL NOT NULL DEFAULT 0
+            )",
+            "CREATE TABLE settings (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                scope TEXT NOT NULL DEFAULT 'global',
+                scope_id TEXT
+            )",
+            "INSERT INTO stores (id, name) VALUES ('wh', 'Warehouse'), ('shop', 'Main Street')",
+            "INSERT INTO products (id, tenant_id, name, store_id, quantity_on_hand) VALUES
+                ('p1', 't1', 'Brake pads', 'wh', 20),
+                ('p2', 't1', 'Wiper blade', 'wh', 5)",
+            include_str!("../../../../migrations/067_inventory_movements.sql"),
+            include_str!("../../../../migrations/068_multi_location_inventory.sql"),
+            include_str!("../../../../migrations/071_inventory_costing.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    async fn at_store(pool: &SqlitePool, product_id: &str, store_id: &str) -> f64 {
+        sqlx::query_scalar(
+            "SELECT COALESCE(SUM(quantity_on_hand), 0.0) FROM product_locations WHERE product_id = ? AND store_id = ?",
+        )
+        .bind(product_id)
+        .bind(store_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    fn request(lines: &[(&str, f64)]) -> TransferRequest {
+        TransferRequest {
+            tenant_id: "t1".to_string(),
+            from_store_id: "wh".to_string(),
+            to_store_id: "shop".to_string(),
+            requested_by: "u1".to_string(),
+            notes: None,
+            lines: lines
+                .iter()
+                .map(|(product_id, quantity)| TransferLineRequest {
+                    product_id: (*product_id).to_string(),
+                    quantity: *quantity,
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transfer_moves_stock_between_stores() {
+        let pool = setup_test_db().await;
+        let service = TransferService::new(pool.clone());
+
+        let order = service.request(request(&[("p1", 8.0), ("p2", 2.0)])).await.unwrap();
+        assert_eq!(order.status, "requested");
+        assert!(order.transfer_number.starts_with("TRF-"));
+        assert_eq!(order.lines.len(), 2);
+        assert_eq!(order.lines[0].product_name.as_deref(), Some("Brake pads"));
+
+        let ledger = InventoryLedgerService::new(pool.clone());
+        ledger.set_bin_location("t1", "p1", "shop", Some("B-01"), None).await.unwrap();
+
+        // Only 6 brake pads could be found
+        let pads = order.lines[0].id.clone();
+        let shipped = service
+            .ship("t1", &order.id, "u2", &[LineQuantity { line_id: pads.clone(), quantity: 6.0, reason: None }])
+            .await
+            .unwrap();
+        assert_eq!(shipped.status, "in_transit");
+        assert_eq!(shipped.lines[0].quantity_shipped, Some(6.0));
+        assert_eq!(shipped.lines[1].quantity_shipped, Some(2.0));
+        assert_eq!(at_store(&pool, "p1", "wh").await, 14.0);
+        assert_eq!(at_store(&pool, "p1", "shop").await, 0.0);
+
+        // Stock in transit shows against the receiving store
+        let shop = ledger.locations("t1", "p1").await.unwrap().into_iter().find(|l| l.store_id == "shop").unwrap();
+        assert_eq!(shop.in_transit, 6.0);
+        assert_eq!(shop.bin_location.as_deref(), Some("B-01"));
+
+        // One arrived damaged
+        let received = service
+            .receive(
+                "t1",
+                &order.id,
+                "u3",
+                &[LineQuantity { line_id: pads, quantity: 5.0, reason: Some("Box crushed".to_string()) }],
+            )
+            .await
+            .unwrap();
+        assert_eq!(received.status, "received");
+        assert!(received.has_discrepancies());
+        assert_eq!(received.lines[0].discrepancy, Some(-1.0));
+        assert_eq!(received.lines[0].discrepancy_reason.as_deref(), Some("Box crushed"));
+        assert_eq!(received.lines[1].discrepancy, None);
+        assert_eq!(at_store(&pool, "p1", "shop").await, 5.0);
+        assert_eq!(at_store(&pool, "p2", "shop").await, 2.0);
+
+        let total: f64 = sqlx::query_scalar("SELECT quantity_on_hand FROM products WHERE id = 'p1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(total, 19.0);
+
+        // A received transfer is final
+        assert!(matches!(
+            service.receive("t1", &order.id, "u3", &[]).await,
+            Err(TransferError::InvalidState(_))
+        ));
+        assert!(matches!(service.cancel("t1", &order.id, "u1").await, Err(TransferError::InvalidState(_))));
+    }
+
+    #[tokio::test]
+    async fn test_request_validation_and_cancel() {
+        let pool = setup_test_db().await;
+        let service = TransferService::new(pool.clone());
+
+        let mut same_store = request(&[("p1", 1.0)]);
+        same_store.to_store_id = "wh".to_string();
+        assert!(matches!(service.request(same_store).await, Err(TransferError::Validation(_))));
+        assert!(matches!(service.request(request(&[("p1", 0.0)])).await, Err(TransferError::Validation(_))));
+        assert!(matches!(service.request(request(&[("nope", 1.0)])).await, Err(TransferError::Validation(_))));
+        let mut unknown_store = request(&[("p1", 1.0)]);
+        unknown_store.to_store_id = "moon".to_string();
+        assert!(matches!(service.request(unknown_store).await, Err(TransferError::Validation(_))));
+
+        let order = service.request(request(&[("p1", 3.0)])).await.unwrap();
+        assert!(matches!(
+            service
+                .ship("t1", &order.id, "u2", &[LineQuantity { line_id: "x".to_string(), quantity: 1.0, reason: None }])
+                .await,
+            Err(TransferError::Validation(_))
+        ));
+
+        let cancelled = service.cancel("t1", &order.id, "u1").await.unwrap();
+        assert_eq!(cancelled.status, "cancelled");
+        assert!(matches!(service.ship("t1", &order.id, "u2", &[]).await, Err(TransferError::InvalidState(_))));
+        assert_eq!(at_store(&pool, "p1", "wh").await, 20.0);
+
+        assert_eq!(service.list("t1", Some("shop"), None).await.unwrap().len(), 1);
+        assert!(service.list("t1", None, Some(TransferStatus::InTransit)).await.unwrap().is_empty());
+        assert!(matches!(service.get("t2", &order.id).await, Err(TransferError::NotFound(_))));
+    }
+}
@@ -0,0 +1,1074 @@
+/**
+ * Two-Factor Service
+ *
+ * Time-based one-time codes (TOTP, RFC 6238) as a second factor for
+ * username and password sign-in:
+ * - A user enrolls by adding a secret to their authenticator app, usually
+ *   by scanning the provisioning URI as a QR code, and entering a code from
+ *   it. Enrolling hands out single-use recovery codes for a lost device.
+ * - Secrets are encrypted with the integration credential key; recovery
+ *   codes are kept only as hashes. A code is accepted once.
+ * - A role can require a second factor. Holders who have not enrolled do
+ *   so during their next sign-in.
+ * - Sign-in is two steps: the password starts a short login challenge and
+ *   the token is issued when the challenge is completed with a code. Failed
+ *   codes count against the challenge and against the user.
+ * - Admins reset the second factor of a user who has lost their device and
+ *   their recovery codes; the user's tokens stop working.
+ *
+ * Station sign-in by PIN or badge is bound to a registered station and
+ * does not ask for a second factor.
+ */
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::errors::ApiError;
+use crate::models::UserContext;
+use crate::services::credential_service::CredentialService;
+use crate::services::role_service;
+
+type HmacSha1 = Hmac<Sha1>;
+
+const TOTP_DIGITS: u32 = 6;
+const TOTP_PERIOD_SECONDS: i64 = 30;
+/// Codes from this many periods either side of now are accepted, for clock drift
+const TOTP_SKEW_STEPS: i64 = 1;
+const SECRET_BYTES: usize = 20;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Without the easily confused 0, 1, i, l and o
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// How long the second step of a sign-in may take
+const CHALLENGE_MINUTES: i64 = 5;
+/// Failed codes before a sign-in has to start again with the password
+const MAX_CHALLENGE_ATTEMPTS: i64 = 5;
+/// Failed codes across a user's sign-ins, within `ATTEMPT_WINDOW_MINUTES`,
+/// before they have to wait
+const MAX_USER_ATTEMPTS: i64 = 10;
+const ATTEMPT_WINDOW_MINUTES: i64 = 15;
+
+const PURPOSE_VERIFY: &str = "verify";
+const PURPOSE_ENROLL: &str = "enroll";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Error)]
+pub enum TwoFactorError {
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("The code was not accepted")]
+    InvalidCode,
+
+    #[error("The sign-in has expired; sign in again")]
+    ChallengeExpired,
+
+    #[error("Too many failed codes; try again later")]
+    TooManyAttempts,
+
+    #[error("Not permitted: {0}")]
+    Forbidden(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<TwoFactorError> for ApiError {
+    fn from(err: TwoFactorError) -> Self {
+        match err {
+            TwoFactorError::Validation(msg) => Self::bad_request(msg),
+            TwoFactorError::NotFound(what) => Self::not_found(what),
+            TwoFactorError::InvalidCode => Self::with_code(400, err.to_string(), "INVALID_CODE"),
+            TwoFactorError::ChallengeExpired => Self::unauthorized(err.to_string()),
+            TwoFactorError::TooManyAttempts => Self::with_code(429, err.to_string(), "TOO_MANY_ATTEMPTS"),
+            TwoFactorError::Forbidden(msg) => Self::forbidden(msg),
+            TwoFactorError::Conflict(msg) => Self::conflict(msg),
+            TwoFactorError::Encryption(msg) => Self::internal(msg),
+            TwoFactorError::Database(e) => Self::internal(format!("Failed to access two-factor settings: {e}")),
+        }
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    /// One of the user's roles requires a second factor
+    pub required: bool,
+    pub enabled_at: Option<String>,
+    pub recovery_codes_remaining: i64,
+}
+
+/// A secret to add to an authenticator app; enrollment completes when a
+/// code from the app is confirmed
+#[derive(Debug, Clone, Serialize)]
+pub struct Enrollment {
+    /// Base32, for typing into the app by hand
+    pub secret: String,
+    /// otpauth:// URI, for showing as a QR code
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CodeRequest {
+    /// A code from the authenticator app, or a recovery code where accepted
+    pub code: String,
+}
+
+/// Shown once; only their hashes are kept
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+/// The second step of a sign-in, after the password
+#[derive(Debug, Clone)]
+pub struct LoginChallenge {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    /// The user must enroll before they can complete the sign-in
+    pub setup_required: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompletedLogin {
+    pub user_id: String,
+    /// When completing the sign-in also completed an enrollment
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct Factor {
+    secret_encrypted: String,
+    enabled_at: Option<String>,
+    last_used_step: Option<i64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct Challenge {
+    id: String,
+    user_id: String,
+    purpose: String,
+    failed_attempts: i64,
+    expires_at: String,
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+pub struct TwoFactorService {
+    pool: SqlitePool,
+    /// Encrypts the TOTP secrets
+    credentials: CredentialService,
+}
+
+impl TwoFactorService {
+    #[must_use]
+    pub const fn new(pool: SqlitePool, credentials: CredentialService) -> Self {
+        Self { pool, credentials }
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn status(&self, tenant_id: &str, user_id: &str) -> Result<TwoFactorStatus, TwoFactorError> {
+        let mut conn = self.pool.acquire().await?;
+        let enabled_at = load_factor(&mut conn, tenant_id, user_id)
+            .await?
+            .and_then(|factor| factor.enabled_at);
+        let recovery_codes_remaining: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM user_recovery_codes WHERE tenant_id = ? AND user_id = ? AND used_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(TwoFactorStatus {
+            enabled: enabled_at.is_some(),
+            required: is_required(&mut conn, tenant_id, user_id).await?,
+            enabled_at,
+            recovery_codes_remaining,
+        })
+    }
+
+    /// Start enrolling with a new secret, replacing an unconfirmed one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user does not exist or already has a second
+    /// factor, or the encryption or database write fails.
+    pub async fn begin_enrollment(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        issuer: &str,
+    ) -> Result<Enrollment, TwoFactorError> {
+        let mut conn = self.pool.acquire().await?;
+        let username: Option<String> = sqlx::query_scalar("SELECT username FROM users WHERE id = ? AND tenant_id = ?")
+            .bind(user_id)
+            .bind(tenant_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        let username = username.ok_or_else(|| TwoFactorError::NotFound(format!("User not found: {user_id}")))?;
+
+        if load_factor(&mut conn, tenant_id, user_id)
+            .await?
+            .is_some_and(|factor| factor.enabled_at.is_some())
+        {
+            return Err(TwoFactorError::Conflict("Two-factor authentication is already enabled".to_string()));
+        }
+
+        let secret = base32_encode(&rand::thread_rng().gen::<[u8; SECRET_BYTES]>());
+        let secret_encrypted = self
+            .credentials
+            .encrypt_data(&secret)
+            .map_err(|e| TwoFactorError::Encryption(e.to_string()))?;
+        sqlx::query(
+            "INSERT INTO user_two_factor (user_id, tenant_id, secret_encrypted, enabled_at, last_used_step, created_at)
+             VALUES (?, ?, ?, NULL, NULL, ?)
+             ON CONFLICT(user_id) DO UPDATE SET
+                secret_encrypted = excluded.secret_encrypted,
+                created_at = excluded.created_at
+             WHERE user_two_factor.enabled_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(tenant_id)
+        .bind(&secret_encrypted)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(Enrollment {
+            provisioning_uri: provisioning_uri(issuer, &username, &secret),
+            secret,
+        })
+    }
+
+    /// Complete enrollment with a code from the authenticator app
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if enrollment was not started or is complete, the
+    /// code is wrong, or the database write fails.
+    pub async fn confirm_enrollment(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        code: &str,
+    ) -> Result<RecoveryCodes, TwoFactorError> {
+        let mut tx = self.pool.begin().await?;
+        let factor = load_factor(&mut tx, tenant_id, user_id)
+            .await?
+            .ok_or_else(|| TwoFactorError::Validation("Start two-factor enrollment first".to_string()))?;
+        if factor.enabled_at.is_some() {
+            return Err(TwoFactorError::Conflict("Two-factor authentication is already enabled".to_string()));
+        }
+
+        let step = self.match_totp(&factor, code)?.ok_or(TwoFactorError::InvalidCode)?;
+        let recovery_codes = enable_factor(&mut tx, tenant_id, user_id, step).await?;
+        tx.commit().await?;
+
+        tracing::info!(tenant_id = %tenant_id, user_id = %user_id, "Two-factor authentication enabled");
+        Ok(RecoveryCodes { recovery_codes })
+    }
+
+    /// Replace the user's recovery codes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user has no second factor, the code is
+    /// wrong, or the database write fails.
+    pub async fn regenerate_recovery_codes(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        code: &str,
+    ) -> Result<RecoveryCodes, TwoFactorError> {
+        let mut tx = self.pool.begin().await?;
+        let factor = enabled_factor(&mut tx, tenant_id, user_id).await?;
+        if !self.accept_code(&mut tx, tenant_id, user_id, &factor, code).await? {
+            return Err(TwoFactorError::InvalidCode);
+        }
+
+        let recovery_codes = replace_recovery_codes(&mut tx, tenant_id, user_id).await?;
+        tx.commit().await?;
+
+        tracing::info!(tenant_id = %tenant_id, user_id = %user_id, "Recovery codes regenerated");
+        Ok(RecoveryCodes { recovery_codes })
+    }
+
+    /// Turn off the user's second factor
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user has no second factor or their role
+    /// requires one, the code is wrong, or the database write fails.
+    pub async fn disable(&self, tenant_id: &str, user_id: &str, code: &str) -> Result<(), TwoFactorError> {
+        let mut tx = self.pool.begin().await?;
+        let factor = enabled_factor(&mut tx, tenant_id, user_id).await?;
+        if is_required(&mut tx, tenant_id, user_id).await? {
+            return Err(TwoFactorError::Forbidden(
+                "Your role requires two-factor authentication".to_string(),
+            ));
+        }
+        if !self.accept_code(&mut tx, tenant_id, user_id, &factor, code).await? {
+            return Err(TwoFactorError::InvalidCode);
+        }
+
+        delete_factor(&mut tx, tenant_id, user_id).await?;
+        tx.commit().await?;
+
+        tracing::info!(tenant_id = %tenant_id, user_id = %user_id, "Two-factor authentication disabled");
+        Ok(())
+    }
+
+    /// Remove a user's second factor for them; their tokens stop working and
+    /// they enroll again at their next sign-in if their role requires it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user does not exist or has no second factor,
+    /// or the database write fails.
+    pub async fn reset(&self, context: &UserContext, user_id: &str) -> Result<(), TwoFactorError> {
+        let tenant_id = &context.tenant_id;
+        let mut tx = self.pool.begin().await?;
+        let user: Option<String> = sqlx::query_scalar("SELECT id FROM users WHERE id = ? AND tenant_id = ?")
+            .bind(user_id)
+            .bind(tenant_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if user.is_none() {
+            return Err(TwoFactorError::NotFound(format!("User not found: {user_id}")));
+        }
+
+        if !delete_factor(&mut tx, tenant_id, user_id).await? {
+            return Err(TwoFactorError::NotFound(
+                "The user has no two-factor authentication".to_string(),
+            ));
+        }
+        role_service::invalidate_user_tokens(&mut tx, tenant_id, user_id).await?;
+
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO audit_log (
+                id, entity_type, entity_id, operation, user_id, employee_id, changes,
+                is_offline, created_at, store_id, tenant_id
+             ) VALUES (?, 'user_two_factor', ?, 'reset', ?, NULL, ?, 0, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(user_id)
+        .bind(&context.user_id)
+        .bind(serde_json::json!({ "reset_by": context.user_id }).to_string())
+        .bind(&now)
+        .bind(context.store_id.as_deref().unwrap_or("system"))
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            tenant_id = %tenant_id,
+            user_id = %user_id,
+            reset_by = %context.user_id,
+            "Two-factor authentication reset"
+        );
+        Ok(())
+    }
+
+    // ========================================================================
+    // Sign-in
+    // ========================================================================
+
+    /// The second step for a user whose password was accepted, if they have
+    /// or need a second factor
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database access fails.
+    pub async fn start_login(&self, tenant_id: &str, user_id: &str) -> Result<Option<LoginChallenge>, TwoFactorError> {
+        let mut conn = self.pool.acquire().await?;
+        let enabled = load_factor(&mut conn, tenant_id, user_id)
+            .await?
+            .is_some_and(|factor| factor.enabled_at.is_some());
+        let purpose = if enabled {
+            PURPOSE_VERIFY
+        } else if is_required(&mut conn, tenant_id, user_id).await? {
+            PURPOSE_ENROLL
+        } else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        // Kept for the window failed attempts are counted over
+        sqlx::query("DELETE FROM login_challenges WHERE created_at < ?")
+            .bind((now - Duration::minutes(ATTEMPT_WINDOW_MINUTES.max(CHALLENGE_MINUTES))).to_rfc3339())
+            .execute(&mut *conn)
+            .await?;
+
+        let token = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+        let expires_at = now + Duration::minutes(CHALLENGE_MINUTES);
+        sqlx::query(
+            "INSERT INTO login_challenges (id, tenant_id, user_id, token_hash, purpose, failed_attempts, expires_at, created_at)
+             VALUES (?, ?, ?, ?, ?, 0, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(sha256_hex(&token))
+        .bind(purpose)
+        .bind(expires_at.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(Some(LoginChallenge {
+            token,
+            expires_at,
+            setup_required: purpose == PURPOSE_ENROLL,
+        }))
+    }
+
+    /// Start enrolling during a sign-in that requires it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sign-in has expired or does not need
+    /// enrollment, or the enrollment cannot be started.
+    pub async fn challenge_enrollment(
+        &self,
+        tenant_id: &str,
+        token: &str,
+        issuer: &str,
+    ) -> Result<Enrollment, TwoFactorError> {
+        let challenge = {
+            let mut conn = self.pool.acquire().await?;
+            open_challenge(&mut conn, tenant_id, token).await?
+        };
+        if challenge.purpose != PURPOSE_ENROLL {
+            return Err(TwoFactorError::Validation(
+                "Two-factor authentication is already set up".to_string(),
+            ));
+        }
+
+        self.begin_enrollment(tenant_id, &challenge.user_id, issuer).await
+    }
+
+    /// Complete a sign-in with a code; a sign-in that required enrollment
+    /// confirms it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sign-in has expired, the code is wrong, too
+    /// many codes have failed, or the database access fails.
+    pub async fn complete_login(
+        &self,
+        tenant_id: &str,
+        token: &str,
+        code: &str,
+    ) -> Result<CompletedLogin, TwoFactorError> {
+        let mut tx = self.pool.begin().await?;
+        let challenge = open_challenge(&mut tx, tenant_id, token).await?;
+        let user_id = challenge.user_id.clone();
+        if challenge.failed_attempts >= MAX_CHALLENGE_ATTEMPTS
+            || recent_failed_attempts(&mut tx, tenant_id, &user_id).await? >= MAX_USER_ATTEMPTS
+        {
+            return Err(TwoFactorError::TooManyAttempts);
+        }
+
+        let factor = load_factor(&mut tx, tenant_id, &user_id)
+            .await?
+            .ok_or_else(|| TwoFactorError::Validation("Set up two-factor authentication first".to_string()))?;
+
+        let accepted = if factor.enabled_at.is_some() {
+            self.accept_code(&mut tx, tenant_id, &user_id, &factor, code)
+                .await?
+                .then_some(None)
+        } else if challenge.purpose == PURPOSE_ENROLL {
+            match self.match_totp(&factor, code)? {
+                Some(step) => Some(Some(enable_factor(&mut tx, tenant_id, &user_id, step).await?)),
+                None => None,
+            }
+        } else {
+            return Err(TwoFactorError::Validation("Set up two-factor authentication first".to_string()));
+        };
+
+        let Some(recovery_codes) = accepted else {
+            sqlx::query("UPDATE login_challenges SET failed_attempts = failed_attempts + 1 WHERE id = ?")
+                .bind(&challenge.id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            tracing::warn!(tenant_id = %tenant_id, user_id = %user_id, "Second factor rejected");
+            return Err(TwoFactorError::InvalidCode);
+        };
+
+        sqlx::query("UPDATE login_challenges SET completed_at = ? WHERE id = ?")
+            .bind(Utc::now().to_rfc3339())
+            .bind(&challenge.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        if recovery_codes.is_some() {
+            tracing::info!(tenant_id = %tenant_id, user_id = %user_id, "Two-factor authentication enabled");
+        }
+        Ok(CompletedLogin { user_id, recovery_codes })
+    }
+
+    // ========================================================================
+    // Codes
+    // ========================================================================
+
+    /// The time step an authenticator code matches, not counting steps
+    /// already used
+    fn match_totp(&self, factor: &Factor, code: &str) -> Result<Option<i64>, TwoFactorError> {
+        let secret = self
+            .credentials
+            .decrypt_data(&factor.secret_encrypted)
+            .map_err(|e| TwoFactorError::Encryption(e.to_string()))?;
+        let secret = base32_decode(&secret)
+            .ok_or_else(|| TwoFactorError::Encryption("The stored secret is not valid".to_string()))?;
+
+        Ok(verify_totp(
+            &secret,
+            &normalize_code(code),
+            Utc::now().timestamp(),
+            factor.last_used_step,
+        ))
+    }
+
+    /// Use an authenticator code or an unused recovery code
+    async fn accept_code(
+        &self,
+        conn: &mut SqliteConnection,
+        tenant_id: &str,
+        user_id: &str,
+        factor: &Factor,
+        code: &str,
+    ) -> Result<bool, TwoFactorError> {
+        let code = normalize_code(code);
+        if code.len() == TOTP_DIGITS as usize && code.chars().all(|c| c.is_ascii_digit()) {
+            let Some(step) = self.match_totp(factor, &code)? else {
+                return Ok(false);
+            };
+            // A concurrent sign-in may have used the same code
+            let updated = sqlx::query(
+                "UPDATE user_two_factor SET last_used_step = ?
+                 WHERE user_id = ? AND tenant_id = ? AND (last_used_step IS NULL OR last_used_step < ?)",
+            )
+            .bind(step)
+            .bind(user_id)
+            .bind(tenant_id)
+            .bind(step)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+            return Ok(updated > 0);
+        }
+
+        let used = sqlx::query(
+            "UPDATE user_recovery_codes SET used_at = ?
+             WHERE tenant_id = ? AND user_id = ? AND code_hash = ? AND used_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(sha256_hex(&code))
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+        if used > 0 {
+            tracing::info!(tenant_id = %tenant_id, user_id = %user_id, "Recovery code used");
+        }
+        Ok(used > 0)
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+async fn load_factor(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    user_id: &str,
+) -> Result<Option<Factor>, sqlx::Error> {
+    sqlx::query_as::<_, Factor>(
+        "SELECT secret_encrypted, enabled_at, last_used_step FROM user_two_factor WHERE user_id = ? AND tenant_id = ?",
+    )
+    .bind(user_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+async fn enabled_factor(conn: &mut SqliteConnection, tenant_id: &str, user_id: &str) -> Result<Factor, TwoFactorError> {
+    load_factor(conn, tenant_id, user_id)
+        .await?
+        .filter(|factor| factor.enabled_at.is_some())
+        .ok_or_else(|| TwoFactorError::Validation("Two-factor authentication is not enabled".to_string()))
+}
+
+/// Whether the user's primary role or a role assigned to them requires a
+/// second factor
+async fn is_required(conn: &mut SqliteConnection, tenant_id: &str, user_id: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r"
+        SELECT EXISTS (
+            SELECT 1 FROM roles r
+            WHERE r.tenant_id = ? AND r.requires_two_factor = 1
+              AND (r.name = (SELECT role FROM users WHERE id = ? AND tenant_id = ?)
+                   OR r.id IN (SELECT role_id FROM user_role_assignments WHERE tenant_id = ? AND user_id = ?))
+        )
+        ",
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind(tenant_id)
+    .bind(tenant_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+async fn enable_factor(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    user_id: &str,
+    step: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query("UPDATE user_two_factor SET enabled_at = ?, last_used_step = ? WHERE user_id = ? AND tenant_id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(step)
+        .bind(user_id)
+        .bind(tenant_id)
+        .execute(&mut *conn)
+        .await?;
+    replace_recovery_codes(conn, tenant_id, user_id).await
+}
+
+async fn replace_recovery_codes(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    user_id: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query("DELETE FROM user_recovery_codes WHERE tenant_id = ? AND user_id = ?")
+        .bind(tenant_id)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let codes = generate_recovery_codes();
+    let now = Utc::now().to_rfc3339();
+    for code in &codes {
+        sqlx::query(
+            "INSERT INTO user_recovery_codes (id, tenant_id, user_id, code_hash, used_at, created_at)
+             VALUES (?, ?, ?, ?, NULL, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(sha256_hex(&normalize_code(code)))
+        .bind(&now)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(codes)
+}
+
+/// Whether the user had a second factor to delete
+async fn delete_factor(conn: &mut SqliteConnection, tenant_id: &str, user_id: &str) -> Result<bool, sqlx::Error> {
+    let deleted = sqlx::query("DELETE FROM user_two_factor WHERE user_id = ? AND tenant_id = ?")
+        .bind(user_id)
+        .bind(tenant_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    sqlx::query("DELETE FROM user_recovery_codes WHERE tenant_id = ? AND user_id = ?")
+        .bind(tenant_id)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM login_challenges WHERE tenant_id = ? AND user_id = ? AND completed_at IS NULL")
+        .bind(tenant_id)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(deleted > 0)
+}
+
+/// A sign-in awaiting its second step
+async fn open_challenge(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    token: &str,
+) -> Result<Challenge, TwoFactorError> {
+    let challenge = sqlx::query_as::<_, Challenge>(
+        "SELECT id, user_id, purpose, failed_attempts, expires_at FROM login_challenges
+         WHERE tenant_id = ? AND token_hash = ? AND completed_at IS NULL",
+    )
+    .bind(tenant_id)
+    .bind(sha256_hex(token.trim()))
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(TwoFactorError::ChallengeExpired)?;
+
+    let expired = DateTime::parse_from_rfc3339(&challenge.expires_at)
+        .map_or(true, |expires_at| expires_at <= Utc::now());
+    if expired {
+        return Err(TwoFactorError::ChallengeExpired);
+    }
+    Ok(challenge)
+}
+
+async fn recent_failed_attempts(
+    conn: &mut SqliteConnection,
+    tenant_id: &str,
+    user_id: &str,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(failed_attempts), 0) FROM login_challenges
+         WHERE tenant_id = ? AND user_id = ? AND created_at > ?",
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind((Utc::now() - Duration::minutes(ATTEMPT_WINDOW_MINUTES)).to_rfc3339())
+    .fetch_one(&mut *conn)
+    .await
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let code: String = (0..8)
+                .map(|_| char::from(RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())]))
+                .collect();
+            format!("{}-{}", &code[..4], &code[4..])
+        })
+        .collect()
+}
+
+/// Codes as typed: spaces, dashes and case do not matter
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn sha256_hex(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
+
+fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let issuer = urlencoding::encode(issuer);
+    format!(
+        "otpauth://totp/{issuer}:{}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={TOTP_DIGITS}&period={TOTP_PERIOD_SECONDS}",
+        urlencoding::encode(account)
+    )
+}
+
+/// The code for a 30-second time step (RFC 6238 with HMAC-SHA1)
+fn totp_code(secret: &[u8], step: i64) -> String {
+    let mut mac = HmacSha1::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226 section 5.3)
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!("{:0width$}", binary % 10_u32.pow(TOTP_DIGITS), width = TOTP_DIGITS as usize)
+}
+
+/// The step within the allowed skew of `now` that `code` matches, if it is
+/// later than the last step used
+fn verify_totp(secret: &[u8], code: &str, now: i64, last_used_step: Option<i64>) -> Option<i64> {
+    let current = now.div_euclid(TOTP_PERIOD_SECONDS);
+    (current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| constant_time_eq(totp_code(secret, *step).as_bytes(), code.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// RFC 4648 base32 without padding, as authenticator apps expect
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = ((buffer << 8) | u32::from(byte)) & 0xffff;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize]));
+        }
+    }
+    if bits > 0 {
+        encoded.push(char::from(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize]));
+    }
+    encoded
+}
+
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in encoded.chars().filter(|c| *c != '=' && !c.is_whitespace()) {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&symbol| char::from(symbol) == c.to_ascii_uppercase())?;
+        buffer = ((buffer << 5) | value as u32) & 0xffff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push(((buffer >> bits) & 0xff) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// RFC 6238 appendix B, SHA1, truncated to six digits
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE tenants (id TEXT PRIMARY KEY)",
+            "CREATE TABLE users (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                username TEXT NOT NULL,
+                role TEXT NOT NULL,
+                store_id TEXT
+            )",
+            "CREATE TABLE sessions (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                token TEXT NOT NULL
+            )",
+            "CREATE TABLE audit_log (
+                id TEXT PRIMARY KEY,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                user_id TEXT,
+                employee_id TEXT,
+                changes TEXT,
+                is_offline BOOLEAN NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                store_id TEXT NOT NULL,
+                tenant_id TEXT NOT NULL
+            )",
+            "INSERT INTO tenants (id) VALUES ('t1')",
+            "INSERT INTO users (id, tenant_id, username, role, store_id) VALUES
+                ('u1', 't1', 'carol', 'cashier', 's1'),
+                ('u2', 't1', 'ann', 'admin', NULL)",
+            include_str!("../../../../migrations/073_roles_and_permissions.sql"),
+            include_str!("../../../../migrations/074_manager_overrides.sql"),
+            include_str!("../../../../migrations/076_two_factor_auth.sql"),
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    fn current_code(secret: &str) -> String {
+        let secret = base32_decode(secret).unwrap();
+        totp_code(&secret, Utc::now().timestamp().div_euclid(TOTP_PERIOD_SECONDS))
+    }
+
+    #[test]
+    fn test_totp_matches_rfc_6238() {
+        assert_eq!(totp_code(RFC_SECRET, 59 / 30), "287082");
+        assert_eq!(totp_code(RFC_SECRET, 1_111_111_109 / 30), "081804");
+        assert_eq!(totp_code(RFC_SECRET, 1_234_567_890 / 30), "005924");
+        assert_eq!(totp_code(RFC_SECRET, 2_000_000_000 / 30), "279037");
+
+        // One step of drift either way, and each step only once
+        assert_eq!(verify_totp(RFC_SECRET, "287082", 59, None), Some(1));
+        assert_eq!(verify_totp(RFC_SECRET, "287082", 89, None), Some(1));
+        assert_eq!(verify_totp(RFC_SECRET, "287082", 119, None), None);
+        assert_eq!(verify_totp(RFC_SECRET, "287082", 59, Some(1)), None);
+
+        let encoded = base32_encode(RFC_SECRET);
+        assert_eq!(encoded, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(base32_decode(&encoded.to_lowercase()).unwrap(), RFC_SECRET);
+        assert_eq!(base32_decode("MZXW6==="), Some(b"foo".to_vec()));
+        assert!(base32_decode("not base32!").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_required_enrollment_at_sign_in() {
+        let pool = setup_test_db().await;
+        let service = TwoFactorService::new(pool.clone(), CredentialService::new(pool.clone()).unwrap());
+
+        // Nobody needs a second factor until their role requires it
+        assert!(service.start_login("t1", "u2").await.unwrap().is_none());
+        sqlx::query("UPDATE roles SET requires_two_factor = 1 WHERE tenant_id = 't1' AND name = 'admin'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(service.start_login("t1", "u1").await.unwrap().is_none());
+
+        let challenge = service.start_login("t1", "u2").await.unwrap().unwrap();
+        assert!(challenge.setup_required);
+        let enrollment = service.challenge_enrollment("t1", &challenge.token, "Main Store").await.unwrap();
+        assert!(enrollment
+            .provisioning_uri
+            .starts_with(&format!("otpauth://totp/Main%20Store:ann?secret={}", enrollment.secret)));
+
+        assert!(matches!(
+            service.complete_login("t1", &challenge.token, "000000").await,
+            Err(TwoFactorError::InvalidCode)
+        ));
+        let code = current_code(&enrollment.secret);
+        let completed = service.complete_login("t1", &challenge.token, &code).await.unwrap();
+        assert_eq!(completed.user_id, "u2");
+        let recovery_codes = completed.recovery_codes.unwrap();
+        assert_eq!(recovery_codes.len(), RECOVERY_CODE_COUNT);
+
+        // Completed sign-ins cannot be reused
+        assert!(matches!(
+            service.complete_login("t1", &challenge.token, &code).await,
+            Err(TwoFactorError::ChallengeExpired)
+        ));
+
+        // The next sign-in verifies; the code just used is spent, a recovery
+        // code works once
+        let challenge = service.start_login("t1", "u2").await.unwrap().unwrap();
+        assert!(!challenge.setup_required);
+        assert!(matches!(
+            service.complete_login("t1", &challenge.token, &code).await,
+            Err(TwoFactorError::InvalidCode)
+        ));
+        let completed = service
+            .complete_login("t1", &challenge.token, &recovery_codes[0].to_uppercase())
+            .await
+            .unwrap();
+        assert!(completed.recovery_codes.is_none());
+
+        let challenge = service.start_login("t1", "u2").await.unwrap().unwrap();
+        assert!(matches!(
+            service.complete_login("t1", &challenge.token, &recovery_codes[0]).await,
+            Err(TwoFactorError::InvalidCode)
+        ));
+
+        let status = service.status("t1", "u2").await.unwrap();
+        assert!(status.enabled && status.required);
+        assert_eq!(status.recovery_codes_remaining, 9);
+
+        // Required, so only an admin can take it away
+        assert!(matches!(
+            service.disable("t1", "u2", &recovery_codes[1]).await,
+            Err(TwoFactorError::Forbidden(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_codes_and_admin_reset() {
+        let pool = setup_test_db().await;
+        let service = TwoFactorService::new(pool.clone(), CredentialService::new(pool.clone()).unwrap());
+
+        let enrollment = service.begin_enrollment("t1", "u1", "Main Store").await.unwrap();
+        assert!(matches!(
+            service.confirm_enrollment("t1", "u1", "123 456").await,
+            Err(TwoFactorError::InvalidCode)
+        ));
+        service
+            .confirm_enrollment("t1", "u1", &current_code(&enrollment.secret))
+            .await
+            .unwrap();
+        assert!(matches!(
+            service.begin_enrollment("t1", "u1", "Main Store").await,
+            Err(TwoFactorError::Conflict(_))
+        ));
+
+        // Each sign-in allows a few guesses, each user a few more overall
+        for _ in 0..2 {
+            let challenge = service.start_login("t1", "u1").await.unwrap().unwrap();
+            for _ in 0..MAX_CHALLENGE_ATTEMPTS {
+                assert!(matches!(
+                    service.complete_login("t1", &challenge.token, "nope-nope").await,
+                    Err(TwoFactorError::InvalidCode)
+                ));
+            }
+            assert!(matches!(
+                service.complete_login("t1", &challenge.token, "nope-nope").await,
+                Err(TwoFactorError::TooManyAttempts)
+            ));
+        }
+        let challenge = service.start_login("t1", "u1").await.unwrap().unwrap();
+        assert!(matches!(
+            service.complete_login("t1", &challenge.token, "nope-nope").await,
+            Err(TwoFactorError::TooManyAttempts)
+        ));
+
+        let admin = UserContext {
+            user_id: "u2".to_string(),
+            username: "ann".to_string(),
+            role: "admin".to_string(),
+            tenant_id: "t1".to_string(),
+            store_id: None,
+            station_id: None,
+            permissions: vec!["manage_users".to_string()],
+        };
+        service.reset(&admin, "u1").await.unwrap();
+        assert!(!service.status("t1", "u1").await.unwrap().enabled);
+        assert!(service.start_login("t1", "u1").await.unwrap().is_none());
+        assert!(matches!(service.reset(&admin, "u1").await, Err(TwoFactorError::NotFound(_))));
+
+        let audited: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE entity_type = 'user_two_factor' AND entity_id = 'u1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(audited, 1);
+    }
+}
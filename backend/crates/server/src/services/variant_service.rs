@@ -2,6 +2,9 @@
     CreateProductVariantRequest, Product, ProductResponse, ProductVariant, ProductVariantResponse,
     ValidationError,
 };
+use crate::services::inventory_ledger_service::{
+    self, LedgerError, MovementType, StockMovement, REASON_OPENING_BALANCE, SOURCE_PRODUCT,
+};
 use chrono::Utc;
 use serde_json::json;
 use sqlx::SqlitePool;
@@ -93,6 +96,30 @@ impl VariantService {
             }]
         })?;
 
+        // Opening stock goes through the inventory ledger
+        if let Some(quantity) = req.variant_product.quantity_on_hand {
+            let opening = StockMovement::new(
+                tenant_id,
+                &variant_id,
+                MovementType::Adjustment,
+                0.0,
+                REASON_OPENING_BALANCE,
+            )
+            .with_source(SOURCE_PRODUCT, &variant_id)
+            .by_user(user_id);
+            let result: Result<_, LedgerError> = async {
+                let mut conn = self.pool.acquire().await?;
+                inventory_ledger_service::set_on_hand(&mut conn, opening, quantity).await
+            }
+            .await;
+            result.map_err(|e| {
+                vec![ValidationError {
+                    field: "quantity_on_hand".to_string(),
+                    message: format!("Failed to record stock movement: {}", e), code: None
+                }]
+            })?;
+        }
+
         // Create variant relationship
         let relationship_id = Uuid::new_v4().to_string();
         let variant_attrs_str = serde_json::to_string(&req.variant_attributes.unwrap_or(json!({}))).unwrap();
@@ -227,6 +227,7 @@ pub fn clean_tax_class(value: &str) -> String {
         "standard" | "taxable" | "default" => "standard".to_string(),
         "clothing" | "apparel" | "clothes" => "clothing".to_string(),
         "grocery" | "food" | "groceries" => "grocery".to_string(),
+        "reduced" | "reduced-rate" | "reduced rate" => "reduced".to_string(),
         "exempt" | "tax-exempt" | "non-taxable" | "nontaxable" => "exempt".to_string(),
         "" => String::new(),
         other => other.to_lowercase(),
@@ -310,7 +311,7 @@ pub fn validate_tax_class(value: &str) -> bool {
     }
     matches!(
         value.to_lowercase().as_str(),
-        "standard" | "clothing" | "grocery" | "exempt"
+        "standard" | "reduced" | "clothing" | "grocery" | "exempt"
     )
 }
 
@@ -72,7 +72,9 @@ proptest! {
             station_id: Some("station-1".to_string()),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
@@ -112,7 +114,9 @@ proptest! {
             station_id: Some("station-1".to_string()),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
@@ -145,7 +149,9 @@ proptest! {
             station_id: Some("station-1".to_string()),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
@@ -177,7 +183,9 @@ proptest! {
             station_id: Some("station-1".to_string()),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
@@ -215,7 +223,9 @@ mod unit_tests {
             station_id: Some("station-1".to_string()),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         let context = UserContext::from_claims(claims);
@@ -233,7 +243,9 @@ mod unit_tests {
             station_id: Some("station-1".to_string()),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         let context = UserContext::from_claims(claims);
@@ -251,7 +263,9 @@ mod unit_tests {
             station_id: Some("station-1".to_string()),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         let context = UserContext::from_claims(claims);
@@ -269,7 +283,9 @@ mod unit_tests {
             station_id: Some("station-1".to_string()),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         let context = UserContext::from_claims(claims);
@@ -28,8 +28,11 @@ const PERMISSIONS: &[&str] = &[
     "apply_discount",
     "override_price",
     "process_return",
+    "return_without_receipt",
+    "void_sale",
     "receive_stock",
     "adjust_inventory",
+    "approve_inventory_counts",
     "manage_users",
     "manage_settings",
     "view_audit_logs",
@@ -86,8 +89,11 @@ fn get_role_permissions(role: &str) -> Vec<&str> {
             "apply_discount",
             "override_price",
             "process_return",
+            "return_without_receipt",
+            "void_sale",
             "receive_stock",
             "adjust_inventory",
+            "approve_inventory_counts",
             "manage_users",
             "manage_settings",
             "view_audit_logs",
@@ -98,8 +104,11 @@ fn get_role_permissions(role: &str) -> Vec<&str> {
             "apply_discount",
             "override_price",
             "process_return",
+            "return_without_receipt",
+            "void_sale",
             "receive_stock",
             "adjust_inventory",
+            "approve_inventory_counts",
             "view_audit_logs",
         ],
         "cashier" => vec!["access_sell", "process_return"],
@@ -140,7 +149,9 @@ proptest! {
             station_id: station_id.clone(),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
@@ -186,7 +197,9 @@ proptest! {
             station_id,
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
@@ -220,7 +233,9 @@ proptest! {
             station_id,
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
@@ -261,7 +276,9 @@ proptest! {
             station_id: station_id.clone(),
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
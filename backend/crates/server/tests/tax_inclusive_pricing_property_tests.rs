@@ -0,0 +1,223 @@
+//! Property-Based Tests for Tax-Inclusive Pricing
+//! Feature: jurisdiction-tax, Property: Tax-inclusive totals never drift
+//!
+//! These tests validate that when shelf prices include tax, back-calculating
+//! net and tax keeps every line at its shelf price, and that the rounded tax
+//! stays within the calculator's tolerance of the exact tax, both when tax is
+//! rounded per line and when it is rounded once per invoice.
+
+use easysale_server::services::checkout_service::{
+    apply_tax, build_transaction, finalize_transaction, CheckoutLine,
+};
+use pos_core_domain::tax::{TaxableLine, TaxJurisdiction};
+use pos_core_domain::{JurisdictionTaxCalculator, PriceMode, TaxRate, TaxRounding};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+/// Rates in use in VAT markets, in basis points
+const VAT_RATES_BP: [i64; 8] = [500, 700, 1000, 1300, 1900, 2000, 2100, 2500];
+
+fn rate(code: &str, basis_points: i64) -> TaxRate {
+    TaxRate::new(code.to_string(), Decimal::new(basis_points, 2), code.to_string()).unwrap()
+}
+
+fn calculator(rates: Vec<TaxRate>, rounding: TaxRounding) -> JurisdictionTaxCalculator {
+    JurisdictionTaxCalculator::new(TaxJurisdiction::new("VAT".to_string(), "VAT".to_string(), rates))
+        .with_price_mode(PriceMode::TaxInclusive)
+        .with_rounding(rounding)
+}
+
+fn gross_lines(cents: &[i64]) -> Vec<TaxableLine> {
+    cents
+        .iter()
+        .enumerate()
+        .map(|(index, &amount)| TaxableLine {
+            product_id: format!("P{index}"),
+            tax_class: "standard".to_string(),
+            amount: Decimal::new(amount, 2),
+        })
+        .collect()
+}
+
+/// Unrounded tax contained in `gross` for additive rates
+fn exact_tax(gross: Decimal, rates: &[TaxRate]) -> Decimal {
+    let factor: Decimal = Decimal::ONE
+        + rates
+            .iter()
+            .map(|rate| rate.rate_percent / Decimal::ONE_HUNDRED)
+            .sum::<Decimal>();
+    gross - gross / factor
+}
+
+fn rates_strategy() -> impl Strategy<Value = Vec<TaxRate>> {
+    prop_oneof![
+        prop::sample::select(VAT_RATES_BP.to_vec()).prop_map(|bp| vec![rate("VAT", bp)]),
+        (0..=1000i64, 0..=1000i64).prop_map(|(state, local)| vec![rate("STATE", state), rate("LOCAL", local)]),
+    ]
+}
+
+fn rounding_strategy() -> impl Strategy<Value = TaxRounding> {
+    prop_oneof![Just(TaxRounding::PerLine), Just(TaxRounding::PerInvoice)]
+}
+
+// ============================================================================
+// Property: Gross prices are preserved
+// ============================================================================
+// For any set of tax-inclusive lines, net + tax of every line equals the
+// shelf price exactly, whatever the rounding.
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    #[test]
+    fn net_plus_tax_equals_gross(
+        cents in prop::collection::vec(1..100_000i64, 1..12),
+        rates in rates_strategy(),
+        rounding in rounding_strategy(),
+    ) {
+        let lines = gross_lines(&cents);
+        let breakdown = calculator(rates, rounding).calculate_breakdown(&lines).unwrap();
+
+        for (line, taxed) in lines.iter().zip(&breakdown.lines) {
+            prop_assert_eq!(taxed.net_amount + taxed.total(), line.amount);
+            prop_assert!(taxed.net_amount >= Decimal::ZERO);
+            prop_assert!(taxed.taxes.iter().all(|tax| tax.tax_amount.scale() <= 2));
+        }
+
+        let line_sum: Decimal = breakdown.lines.iter().map(|line| line.total()).sum();
+        prop_assert_eq!(line_sum, breakdown.total_tax);
+    }
+}
+
+// ============================================================================
+// Property: Totals stay within tolerance
+// ============================================================================
+// Rounded once per invoice, the invoice tax never drifts from the exact tax
+// by more than the configured tolerance. Rounded per line, no line drifts by
+// more than the tolerance, so the invoice drifts by at most one tolerance per
+// line.
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    #[test]
+    fn per_invoice_tax_within_tolerance(
+        cents in prop::collection::vec(1..100_000i64, 1..12),
+        rates in rates_strategy(),
+    ) {
+        let calculator = calculator(rates.clone(), TaxRounding::PerInvoice);
+        let lines = gross_lines(&cents);
+        let breakdown = calculator.calculate_breakdown(&lines).unwrap();
+
+        let exact: Decimal = lines.iter().map(|line| exact_tax(line.amount, &rates)).sum();
+        let drift = (breakdown.total_tax - exact).abs();
+        prop_assert!(
+            drift <= calculator.tolerance(),
+            "Invoice tax {} drifted {} from exact tax {}",
+            breakdown.total_tax,
+            drift,
+            exact
+        );
+    }
+
+    #[test]
+    fn per_line_tax_within_tolerance(
+        cents in prop::collection::vec(1..100_000i64, 1..12),
+        rates in rates_strategy(),
+    ) {
+        let calculator = calculator(rates.clone(), TaxRounding::PerLine);
+        let lines = gross_lines(&cents);
+        let breakdown = calculator.calculate_breakdown(&lines).unwrap();
+
+        for (line, taxed) in lines.iter().zip(&breakdown.lines) {
+            let drift = (taxed.total() - exact_tax(line.amount, &rates)).abs();
+            prop_assert!(
+                drift <= calculator.tolerance(),
+                "Line tax {} on {} drifted {}",
+                taxed.total(),
+                line.amount,
+                drift
+            );
+        }
+
+        let exact: Decimal = lines.iter().map(|line| exact_tax(line.amount, &rates)).sum();
+        let max_drift = calculator.tolerance() * Decimal::from(lines.len());
+        prop_assert!((breakdown.total_tax - exact).abs() <= max_drift);
+    }
+
+    #[test]
+    fn net_reprices_to_gross_within_tolerance(
+        cents in 1..100_000i64,
+        basis_points in prop::sample::select(VAT_RATES_BP.to_vec()),
+    ) {
+        let inclusive = calculator(vec![rate("VAT", basis_points)], TaxRounding::PerLine);
+        let exclusive = inclusive.clone().with_price_mode(PriceMode::TaxExclusive);
+        let gross = gross_lines(&[cents]);
+
+        let net = inclusive.calculate_breakdown(&gross).unwrap().lines[0].net_amount;
+        let repriced = exclusive
+            .calculate_breakdown(&[TaxableLine { amount: net, ..gross[0].clone() }])
+            .unwrap();
+
+        let drift = (net + repriced.total_tax - gross[0].amount).abs();
+        prop_assert!(drift <= inclusive.tolerance(), "Net {} repriced with drift {}", net, drift);
+    }
+}
+
+// ============================================================================
+// Property: Checkout header balances
+// ============================================================================
+// For any tax-inclusive cart with line and cart discounts, the sale total is
+// what the customer sees on the shelf less discounts, and
+// subtotal - discount + tax = total holds on the header and on every line.
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    #[test]
+    fn inclusive_checkout_balances(
+        items in prop::collection::vec((1..50_000i64, 1..5i64, 0..20i64), 1..8),
+        cart_discount_percent in 0..30i64,
+        basis_points in prop::sample::select(VAT_RATES_BP.to_vec()),
+        rounding in rounding_strategy(),
+    ) {
+        let lines: Vec<CheckoutLine> = items
+            .iter()
+            .enumerate()
+            .map(|(index, &(cents, quantity, discount_percent))| {
+                let subtotal = Decimal::new(cents * quantity, 2);
+                CheckoutLine {
+                    product_id: format!("P{index}"),
+                    quantity: Decimal::from(quantity),
+                    unit_price: Decimal::new(cents, 2),
+                    discount_amount: (subtotal * Decimal::new(discount_percent, 2)).round_dp(2),
+                }
+            })
+            .collect();
+        let gross: Decimal = lines.iter().map(|l| l.quantity * l.unit_price - l.discount_amount).sum();
+        let cart_discount = (gross * Decimal::new(cart_discount_percent, 2)).round_dp(2);
+
+        let mut transaction = build_transaction(Uuid::new_v4(), &lines, &[], cart_discount).unwrap();
+        finalize_transaction(&mut transaction).unwrap();
+        let calculator = calculator(vec![rate("VAT", basis_points)], rounding);
+        let (priced, breakdown) = apply_tax(&mut transaction, &lines, &calculator).unwrap();
+
+        prop_assert_eq!(transaction.total, gross - cart_discount);
+        prop_assert_eq!(transaction.tax, breakdown.total_tax);
+        prop_assert_eq!(
+            transaction.subtotal - transaction.discount_total + transaction.tax,
+            transaction.total
+        );
+
+        for line in &priced {
+            prop_assert_eq!(line.subtotal - line.discount + line.tax, line.total);
+        }
+        let total: Decimal = priced.iter().map(|line| line.total).sum();
+        prop_assert_eq!(total, transaction.total);
+    }
+}
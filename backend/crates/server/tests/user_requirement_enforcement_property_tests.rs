@@ -532,7 +532,9 @@ proptest! {
             station_id,
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
@@ -577,7 +579,9 @@ proptest! {
             station_id,
             exp: 9999999999,
             iat: 1000000000,
-            permissions: vec![],
+            iat_ms: None,
+            permissions: None,
+            login_method: None,
         };
 
         // Create UserContext from claims
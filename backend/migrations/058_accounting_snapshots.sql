@@ -0,0 +1,63 @@
+-- Migration 058: Accounting Snapshots
+-- Created: 2026-02-02
+-- Purpose: Immutable accounting snapshots written at sale finalization.
+-- Snapshots capture the exact subtotal/tax/discount/total, per-line values and
+-- tenders computed by pos_core_domain so exports never recompute totals.
+-- Decimal values are stored as TEXT to preserve exact precision.
+
+CREATE TABLE IF NOT EXISTS accounting_snapshots (
+    id TEXT PRIMARY KEY,
+    transaction_id TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL,
+    finalized_at TEXT NOT NULL,
+    subtotal TEXT NOT NULL,
+    tax TEXT NOT NULL,
+    discount TEXT NOT NULL,
+    total TEXT NOT NULL,
+    FOREIGN KEY (transaction_id) REFERENCES sales_transactions(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_accounting_snapshots_finalized_at ON accounting_snapshots(finalized_at);
+
+CREATE TABLE IF NOT EXISTS snapshot_lines (
+    id TEXT PRIMARY KEY,
+    snapshot_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    description TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    unit_price TEXT NOT NULL,
+    line_total TEXT NOT NULL,
+    tax_amount TEXT NOT NULL,
+    FOREIGN KEY (snapshot_id) REFERENCES accounting_snapshots(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshot_lines_snapshot_id ON snapshot_lines(snapshot_id);
+
+CREATE TABLE IF NOT EXISTS snapshot_payments (
+    id TEXT PRIMARY KEY,
+    snapshot_id TEXT NOT NULL,
+    method TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    FOREIGN KEY (snapshot_id) REFERENCES accounting_snapshots(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshot_payments_snapshot_id ON snapshot_payments(snapshot_id);
+
+-- Snapshots are immutable once written
+CREATE TRIGGER IF NOT EXISTS accounting_snapshots_no_update
+BEFORE UPDATE ON accounting_snapshots
+BEGIN
+    SELECT RAISE(ABORT, 'accounting snapshots are immutable');
+END;
+
+CREATE TRIGGER IF NOT EXISTS snapshot_lines_no_update
+BEFORE UPDATE ON snapshot_lines
+BEGIN
+    SELECT RAISE(ABORT, 'accounting snapshots are immutable');
+END;
+
+CREATE TRIGGER IF NOT EXISTS snapshot_payments_no_update
+BEFORE UPDATE ON snapshot_payments
+BEGIN
+    SELECT RAISE(ABORT, 'accounting snapshots are immutable');
+END;
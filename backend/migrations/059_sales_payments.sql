@@ -0,0 +1,24 @@
+-- Migration 059: Sales Payments (split tenders)
+-- Created: 2026-02-03
+-- Purpose: Record every tender applied to a sale (cash, card, gift card,
+-- store credit, ...). sales_transactions.payment_method keeps the single
+-- method, or 'split' when more than one tender was used.
+
+CREATE TABLE IF NOT EXISTS sales_payments (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    method TEXT NOT NULL,
+    -- Amount handed over by the customer
+    tendered_amount REAL NOT NULL,
+    -- Amount applied to the sale (tendered_amount - change_amount)
+    amount REAL NOT NULL,
+    change_amount REAL NOT NULL DEFAULT 0.0,
+    -- Gift card number, card authorization code, etc.
+    reference TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (transaction_id) REFERENCES sales_transactions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_sales_payments_transaction_id ON sales_payments(transaction_id);
+CREATE INDEX IF NOT EXISTS idx_sales_payments_tenant_method ON sales_payments(tenant_id, method);
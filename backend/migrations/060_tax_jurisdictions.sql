@@ -0,0 +1,71 @@
+-- Migration 060: Tax Jurisdictions and Tax Classes
+-- Created: 2026-02-04
+-- Purpose: Jurisdiction-aware sales tax.
+-- - tax_rules rows with a store_id form that store's jurisdiction (GST+PST,
+--   HST, US state+county, ...). Each row is one authority; tax_classes limits
+--   the rule to some product tax classes and is_compound charges it on the
+--   amount plus the preceding rules (applied in sort_order).
+-- - products carry a tax class (standard, reduced, clothing, grocery, exempt).
+-- - customers may be tax exempt with a certificate number.
+-- - The per-line, per-authority tax of each sale is kept in sales_line_taxes
+--   and in snapshot_line_taxes for the accounting snapshot.
+
+-- Tax rules: authority and class scoping
+ALTER TABLE tax_rules ADD COLUMN authority TEXT;
+ALTER TABLE tax_rules ADD COLUMN tax_classes TEXT;
+ALTER TABLE tax_rules ADD COLUMN is_compound INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tax_rules ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_tax_rules_tenant_store ON tax_rules(tenant_id, store_id, sort_order);
+
+-- Product tax class
+ALTER TABLE products ADD COLUMN tax_class TEXT NOT NULL DEFAULT 'standard';
+
+-- Customer tax exemption
+ALTER TABLE customers ADD COLUMN tax_exempt INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE customers ADD COLUMN tax_exempt_certificate TEXT;
+-- Comma-separated authorities covered by the certificate; NULL means all
+ALTER TABLE customers ADD COLUMN tax_exempt_authorities TEXT;
+
+-- Tax details on sales
+ALTER TABLE sales_transactions ADD COLUMN tax_exempt_certificate TEXT;
+ALTER TABLE sales_line_items ADD COLUMN tax_class TEXT;
+
+CREATE TABLE IF NOT EXISTS sales_line_taxes (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    line_item_id TEXT NOT NULL,
+    authority TEXT NOT NULL,
+    label TEXT NOT NULL,
+    -- Rate in percent
+    rate REAL NOT NULL,
+    taxable_amount REAL NOT NULL,
+    tax_amount REAL NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (transaction_id) REFERENCES sales_transactions(id) ON DELETE CASCADE,
+    FOREIGN KEY (line_item_id) REFERENCES sales_line_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_sales_line_taxes_transaction_id ON sales_line_taxes(transaction_id);
+CREATE INDEX IF NOT EXISTS idx_sales_line_taxes_tenant_authority ON sales_line_taxes(tenant_id, authority);
+
+-- Per-authority breakdown of snapshot_lines.tax_amount (decimals as TEXT)
+CREATE TABLE IF NOT EXISTS snapshot_line_taxes (
+    id TEXT PRIMARY KEY,
+    snapshot_line_id TEXT NOT NULL,
+    authority TEXT NOT NULL,
+    label TEXT NOT NULL,
+    rate TEXT NOT NULL,
+    taxable_amount TEXT NOT NULL,
+    tax_amount TEXT NOT NULL,
+    FOREIGN KEY (snapshot_line_id) REFERENCES snapshot_lines(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshot_line_taxes_line_id ON snapshot_line_taxes(snapshot_line_id);
+
+CREATE TRIGGER IF NOT EXISTS snapshot_line_taxes_no_update
+BEFORE UPDATE ON snapshot_line_taxes
+BEGIN
+    SELECT RAISE(ABORT, 'accounting snapshots are immutable');
+END;
@@ -0,0 +1,11 @@
+-- Migration 061: Tax-Inclusive Pricing
+-- Created: 2026-02-05
+-- Purpose: Support stores whose shelf prices include tax (VAT markets).
+-- The pricing mode and rounding are settings, resolved store scope first,
+-- then global scope:
+-- - store.prices_include_tax: 'true' when prices include tax
+-- - store.tax_rounding: 'per_line' or 'per_invoice' (default)
+-- Sales record the mode they were priced in; for tax-inclusive sales the
+-- subtotal columns are net of the included tax.
+
+ALTER TABLE sales_transactions ADD COLUMN prices_include_tax INTEGER NOT NULL DEFAULT 0;
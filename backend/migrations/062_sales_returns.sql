@@ -0,0 +1,28 @@
+-- Migration 062: Sales Returns
+-- Created: 2026-02-05
+-- Purpose: Returns and exchanges against an original sale.
+-- - A return is its own sales_transactions row (transaction_type 'return')
+--   pointing at the sale it was returned from. Its amounts, line quantities,
+--   line taxes and refund tenders are stored negative so that reports summing
+--   sales_transactions / sales_line_items / sales_payments net returns out.
+-- - Each return line points at the original line item so the quantity still
+--   returnable can be computed, and records whether the goods were put back
+--   on the shelf ('restock') or written off ('damaged').
+-- - The accounting snapshot of a return is a credit memo (kind 'credit_memo')
+--   with positive amounts and a reference to the original sale.
+
+ALTER TABLE sales_transactions ADD COLUMN transaction_type TEXT NOT NULL DEFAULT 'sale';
+ALTER TABLE sales_transactions ADD COLUMN original_transaction_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_sales_transactions_original ON sales_transactions(original_transaction_id);
+
+ALTER TABLE sales_line_items ADD COLUMN original_line_item_id TEXT;
+ALTER TABLE sales_line_items ADD COLUMN return_disposition TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_sales_line_items_original ON sales_line_items(original_line_item_id);
+
+ALTER TABLE accounting_snapshots ADD COLUMN kind TEXT NOT NULL DEFAULT 'sale';
+ALTER TABLE accounting_snapshots ADD COLUMN original_transaction_id TEXT;
+ALTER TABLE accounting_snapshots ADD COLUMN memo TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_accounting_snapshots_kind ON accounting_snapshots(kind);
@@ -0,0 +1,61 @@
+-- Migration 063: Suspended Sales
+-- Created: 2026-02-06
+-- Purpose: Park an in-progress sale so the register can serve the next
+-- customer, then resume it later on the same or another register.
+-- - A suspended sale is a draft cart (status 'draft', the pos_core_models
+--   TransactionStatus::Draft). It becomes 'finalized' when it is checked out
+--   as a sale and 'voided' when cancelled or expired.
+-- - parked is 1 while the cart is waiting to be resumed and 0 while it is
+--   open at a register; station_id is the register holding it.
+-- - Parked carts expire at expires_at and are then voided with void_reason
+--   'expired'. Drafts are kept out of sales_transactions so reports never
+--   see them.
+
+CREATE TABLE IF NOT EXISTS suspended_sales (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    store_id TEXT NOT NULL,
+    station_id TEXT,
+    employee_id TEXT NOT NULL,
+    customer_id TEXT,
+    -- Short name shown in the parked list (customer name, "blue jacket", ...)
+    label TEXT,
+    notes TEXT,
+    cart_discount REAL NOT NULL DEFAULT 0.0,
+    -- Pre-tax totals at the time the cart was parked; tax is worked out at checkout
+    subtotal REAL NOT NULL DEFAULT 0.0,
+    discount_amount REAL NOT NULL DEFAULT 0.0,
+    items_count INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'draft',
+    parked INTEGER NOT NULL DEFAULT 1,
+    suspended_at TEXT NOT NULL,
+    resumed_at TEXT,
+    resumed_by TEXT,
+    expires_at TEXT NOT NULL,
+    sale_id TEXT,
+    finalized_at TEXT,
+    voided_at TEXT,
+    void_reason TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (customer_id) REFERENCES customers(id),
+    FOREIGN KEY (sale_id) REFERENCES sales_transactions(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_suspended_sales_tenant_store ON suspended_sales(tenant_id, store_id, status);
+CREATE INDEX IF NOT EXISTS idx_suspended_sales_station ON suspended_sales(station_id, status);
+CREATE INDEX IF NOT EXISTS idx_suspended_sales_expires_at ON suspended_sales(status, parked, expires_at);
+
+CREATE TABLE IF NOT EXISTS suspended_sale_lines (
+    id TEXT PRIMARY KEY,
+    suspended_sale_id TEXT NOT NULL,
+    line_number INTEGER NOT NULL,
+    product_id TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    unit_price REAL NOT NULL,
+    discount_amount REAL NOT NULL DEFAULT 0.0,
+    FOREIGN KEY (suspended_sale_id) REFERENCES suspended_sales(id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_suspended_sale_lines_sale_id ON suspended_sale_lines(suspended_sale_id, line_number);
@@ -0,0 +1,68 @@
+-- Migration 064: Shifts and Cash Drawer Reconciliation
+-- Created: 2026-02-07
+-- Purpose: Cash drawer accountability per station.
+-- - A shift is opened on a station with a cash float and closed with a blind
+--   count of the drawer by denomination. Only one shift may be open per
+--   station at a time.
+-- - Pay-ins and pay-outs move cash in or out of the drawer outside of sales.
+-- - Sales and returns rung up on the station while the shift is open make up
+--   its X-report (mid-shift) and Z-report (closing). The Z-report is stored
+--   on the shift when it closes so it can be reprinted unchanged.
+-- - variance = counted_cash - expected_cash (positive over, negative short).
+
+CREATE TABLE IF NOT EXISTS shifts (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    store_id TEXT NOT NULL,
+    station_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open',
+    opened_by TEXT NOT NULL,
+    opened_at TEXT NOT NULL,
+    opening_float REAL NOT NULL DEFAULT 0.0,
+    closed_by TEXT,
+    closed_at TEXT,
+    expected_cash REAL,
+    counted_cash REAL,
+    variance REAL,
+    notes TEXT,
+    -- JSON Z-report generated at close
+    z_report TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (station_id) REFERENCES stations(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_shifts_tenant_store ON shifts(tenant_id, store_id, opened_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_shifts_open_station ON shifts(station_id) WHERE status = 'open';
+
+CREATE TABLE IF NOT EXISTS shift_cash_movements (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    shift_id TEXT NOT NULL,
+    -- pay_in or pay_out
+    movement_type TEXT NOT NULL,
+    amount REAL NOT NULL,
+    reason TEXT NOT NULL,
+    employee_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (shift_id) REFERENCES shifts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_shift_cash_movements_shift_id ON shift_cash_movements(shift_id);
+
+CREATE TABLE IF NOT EXISTS shift_cash_counts (
+    id TEXT PRIMARY KEY,
+    shift_id TEXT NOT NULL,
+    -- opening or closing
+    count_type TEXT NOT NULL,
+    denomination REAL NOT NULL,
+    quantity INTEGER NOT NULL,
+    amount REAL NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (shift_id) REFERENCES shifts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_shift_cash_counts_shift_id ON shift_cash_counts(shift_id, count_type);
+
+-- Sales are reported per station
+CREATE INDEX IF NOT EXISTS idx_sales_transactions_station_created ON sales_transactions(station_id, created_at);
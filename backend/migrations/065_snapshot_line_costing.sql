@@ -0,0 +1,11 @@
+-- Migration 065: Snapshot Line Costing
+-- Created: 2026-02-08
+-- Purpose: Journal-entry exports post revenue per product category and cost
+-- of goods sold against inventory, so snapshot lines record the product's
+-- category and unit cost at time of sale. Both are NULL on snapshots written
+-- before this migration; those lines post to the default revenue account and
+-- carry no cost of goods.
+-- unit_cost is a decimal stored as TEXT like the other snapshot amounts.
+
+ALTER TABLE snapshot_lines ADD COLUMN category TEXT;
+ALTER TABLE snapshot_lines ADD COLUMN unit_cost TEXT;
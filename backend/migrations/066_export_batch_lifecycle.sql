@@ -0,0 +1,55 @@
+-- Migration 066: Export Batch Lifecycle
+-- Created: 2026-02-09
+-- Purpose: Export batches group the accounting snapshots of a date range for
+-- one export to the accounting package.
+-- - A completed batch can be superseded by a re-export of the same range when
+--   snapshots were added or exporter settings changed; the two batches point
+--   at each other (supersedes / superseded_by).
+-- - Once the accountant confirms the import, the batch is locked. A locked
+--   batch closes its date range: no snapshot finalized inside it may be
+--   written any more, and the batch can no longer be superseded.
+-- - config_hash is a SHA-256 of the date range and the exporter settings the
+--   batch was exported with, so a settings change shows up as a new hash.
+
+CREATE TABLE IF NOT EXISTS export_batches (
+    id TEXT PRIMARY KEY,
+    created_at TEXT NOT NULL,
+    created_by TEXT NOT NULL,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    snapshot_count INTEGER NOT NULL DEFAULT 0,
+    config_hash TEXT NOT NULL,
+    error_message TEXT,
+    supersedes TEXT,
+    superseded_by TEXT,
+    locked_at TEXT,
+    locked_by TEXT,
+    FOREIGN KEY (supersedes) REFERENCES export_batches(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_export_batches_range ON export_batches(start_date, end_date);
+CREATE INDEX IF NOT EXISTS idx_export_batches_status ON export_batches(status);
+
+CREATE TABLE IF NOT EXISTS batch_snapshots (
+    batch_id TEXT NOT NULL,
+    snapshot_id TEXT NOT NULL,
+    included_at TEXT NOT NULL,
+    PRIMARY KEY (batch_id, snapshot_id),
+    FOREIGN KEY (batch_id) REFERENCES export_batches(id),
+    FOREIGN KEY (snapshot_id) REFERENCES accounting_snapshots(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_batch_snapshots_snapshot_id ON batch_snapshots(snapshot_id);
+
+-- Period close: snapshots cannot be written into a locked batch's range
+CREATE TRIGGER IF NOT EXISTS accounting_snapshots_period_closed
+BEFORE INSERT ON accounting_snapshots
+WHEN EXISTS (
+    SELECT 1 FROM export_batches
+    WHERE status = 'locked'
+    AND NEW.finalized_at >= start_date AND NEW.finalized_at <= end_date
+)
+BEGIN
+    SELECT RAISE(ABORT, 'accounting period is closed');
+END;
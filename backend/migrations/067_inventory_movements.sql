@@ -0,0 +1,66 @@
+-- Migration 067: Inventory Movement Ledger
+-- Created: 2026-02-10
+-- Purpose: Append-only ledger of every unit that enters or leaves stock.
+-- - Every change to a product's stock is a signed movement: sale, return,
+--   receipt, adjustment, transfer or count, with a reason code, the user who
+--   made it and the source document (sale, return, vendor bill, ...).
+-- - products.quantity_on_hand stays as a cache of the ledger sum; it is only
+--   written together with a movement and can be rebuilt from the ledger.
+-- - Stock at any point in time is the sum of the movements up to it.
+-- - Movements are never updated or deleted; mistakes are corrected with a
+--   new adjustment.
+-- - Existing stock is carried over as one opening balance per product.
+
+CREATE TABLE IF NOT EXISTS inventory_movements (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    store_id TEXT,
+    -- sale, return, receipt, adjustment, transfer or count
+    movement_type TEXT NOT NULL
+        CHECK (movement_type IN ('sale', 'return', 'receipt', 'adjustment', 'transfer', 'count')),
+    -- Signed change: negative when stock leaves
+    quantity REAL NOT NULL,
+    -- Cached on-hand quantity right after the movement
+    quantity_after REAL NOT NULL,
+    reason_code TEXT NOT NULL,
+    -- sale, return, vendor_bill, stock_adjustment, ...
+    source_type TEXT,
+    source_id TEXT,
+    user_id TEXT,
+    notes TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_product ON inventory_movements(tenant_id, product_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_type ON inventory_movements(tenant_id, movement_type, created_at);
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_source ON inventory_movements(source_type, source_id);
+
+CREATE TRIGGER IF NOT EXISTS inventory_movements_no_update
+BEFORE UPDATE ON inventory_movements
+BEGIN
+    SELECT RAISE(ABORT, 'inventory movements are append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS inventory_movements_no_delete
+BEFORE DELETE ON inventory_movements
+BEGIN
+    SELECT RAISE(ABORT, 'inventory movements are append-only');
+END;
+
+INSERT INTO inventory_movements (
+    id, tenant_id, product_id, store_id, movement_type, quantity, quantity_after,
+    reason_code, created_at
+)
+SELECT
+    lower(hex(randomblob(16))),
+    tenant_id,
+    id,
+    store_id,
+    'adjustment',
+    quantity_on_hand,
+    quantity_on_hand,
+    'opening_balance',
+    strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+FROM products
+WHERE quantity_on_hand <> 0;
@@ -0,0 +1,88 @@
+-- Migration 068: Multi-Location Inventory and Transfer Orders
+-- Created: 2026-02-11
+-- Purpose: Stock per store (shop or warehouse) and transfers between them.
+-- - product_locations holds a product's stock at each store and where it
+--   sits there (bin/shelf). It is a cache of the inventory ledger per
+--   store_id, just as products.quantity_on_hand caches the total over all
+--   stores; both are only written together with a ledger movement.
+-- - A transfer order moves stock from one store to another:
+--   requested -> in_transit (shipped) -> received, or cancelled before it
+--   ships. Shipping takes the shipped quantities out of the sending store,
+--   receiving puts the received quantities into the receiving store; while
+--   in transit the goods are in neither. A received quantity that differs
+--   from the shipped one is kept on the line as a discrepancy.
+-- - Existing stock starts out at the product's own store.
+
+CREATE TABLE IF NOT EXISTS product_locations (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    store_id TEXT NOT NULL,
+    quantity_on_hand REAL NOT NULL DEFAULT 0.0,
+    -- Bin, aisle or shelf within the store, e.g. "A-03-2"
+    bin_location TEXT,
+    -- Store-level reorder point; falls back to products.reorder_point
+    reorder_point REAL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (product_id, store_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_locations_store ON product_locations(tenant_id, store_id);
+
+INSERT OR IGNORE INTO product_locations (
+    id, tenant_id, product_id, store_id, quantity_on_hand, created_at, updated_at
+)
+SELECT
+    lower(hex(randomblob(16))),
+    tenant_id,
+    id,
+    store_id,
+    quantity_on_hand,
+    strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now'),
+    strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+FROM products
+WHERE store_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS transfer_orders (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    transfer_number TEXT NOT NULL,
+    from_store_id TEXT NOT NULL,
+    to_store_id TEXT NOT NULL,
+    -- requested, in_transit, received or cancelled
+    status TEXT NOT NULL DEFAULT 'requested',
+    notes TEXT,
+    requested_by TEXT NOT NULL,
+    requested_at TEXT NOT NULL,
+    shipped_by TEXT,
+    shipped_at TEXT,
+    received_by TEXT,
+    received_at TEXT,
+    cancelled_by TEXT,
+    cancelled_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (from_store_id) REFERENCES stores(id),
+    FOREIGN KEY (to_store_id) REFERENCES stores(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_transfer_orders_tenant_status ON transfer_orders(tenant_id, status, requested_at);
+CREATE INDEX IF NOT EXISTS idx_transfer_orders_from_store ON transfer_orders(from_store_id);
+CREATE INDEX IF NOT EXISTS idx_transfer_orders_to_store ON transfer_orders(to_store_id);
+
+CREATE TABLE IF NOT EXISTS transfer_order_lines (
+    id TEXT PRIMARY KEY,
+    transfer_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    quantity_requested REAL NOT NULL,
+    quantity_shipped REAL,
+    quantity_received REAL,
+    -- quantity_received - quantity_shipped when they differ
+    discrepancy REAL,
+    discrepancy_reason TEXT,
+    FOREIGN KEY (transfer_id) REFERENCES transfer_orders(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_transfer_order_lines_transfer ON transfer_order_lines(transfer_id);
+CREATE INDEX IF NOT EXISTS idx_transfer_order_lines_product ON transfer_order_lines(product_id);
@@ -0,0 +1,92 @@
+-- Migration 069: Purchase Orders and Three-Way Match
+-- Created: 2026-02-12
+-- Purpose: Order stock from vendors and check vendor bills against what was
+-- ordered and received before they are posted.
+-- - A purchase order is raised per vendor for one store:
+--   draft -> ordered -> partially_received -> received, or cancelled before
+--   anything arrives, or closed short when the rest will never come.
+-- - Goods are received against PO lines, in as many receipts as it takes;
+--   each receipt puts its quantity into the store's stock through the
+--   inventory ledger.
+-- - A vendor bill for a PO (by vendor_bills.po_number or an explicit link)
+--   is matched three ways: PO price vs billed price, received vs billed
+--   quantity, and billed items that were never ordered. Variances open a
+--   review case; the bill cannot be posted until the case is approved.
+
+CREATE TABLE IF NOT EXISTS purchase_orders (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    po_number TEXT NOT NULL,
+    vendor_id TEXT NOT NULL,
+    store_id TEXT NOT NULL,
+    -- draft, ordered, partially_received, received, cancelled or closed
+    status TEXT NOT NULL DEFAULT 'draft',
+    -- Raised from the reorder suggestions
+    suggested INTEGER NOT NULL DEFAULT 0,
+    expected_date TEXT,
+    notes TEXT,
+    created_by TEXT NOT NULL,
+    ordered_by TEXT,
+    ordered_at TEXT,
+    closed_by TEXT,
+    closed_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (tenant_id, po_number),
+    FOREIGN KEY (vendor_id) REFERENCES vendors(id) ON DELETE RESTRICT
+);
+
+CREATE INDEX IF NOT EXISTS idx_purchase_orders_vendor ON purchase_orders(tenant_id, vendor_id, status);
+CREATE INDEX IF NOT EXISTS idx_purchase_orders_store ON purchase_orders(tenant_id, store_id, status);
+
+CREATE TABLE IF NOT EXISTS purchase_order_lines (
+    id TEXT PRIMARY KEY,
+    purchase_order_id TEXT NOT NULL,
+    line_no INTEGER NOT NULL,
+    product_id TEXT NOT NULL,
+    -- Vendor's SKU from vendor_sku_aliases, when known
+    vendor_sku TEXT,
+    quantity_ordered REAL NOT NULL,
+    quantity_received REAL NOT NULL DEFAULT 0.0,
+    unit_cost REAL NOT NULL,
+    FOREIGN KEY (purchase_order_id) REFERENCES purchase_orders(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_purchase_order_lines_po ON purchase_order_lines(purchase_order_id, line_no);
+CREATE INDEX IF NOT EXISTS idx_purchase_order_lines_product ON purchase_order_lines(product_id);
+
+CREATE TABLE IF NOT EXISTS purchase_order_receipts (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    purchase_order_id TEXT NOT NULL,
+    purchase_order_line_id TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    received_by TEXT NOT NULL,
+    received_at TEXT NOT NULL,
+    notes TEXT,
+    FOREIGN KEY (purchase_order_id) REFERENCES purchase_orders(id) ON DELETE CASCADE,
+    FOREIGN KEY (purchase_order_line_id) REFERENCES purchase_order_lines(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_purchase_order_receipts_po ON purchase_order_receipts(purchase_order_id);
+
+-- Latest three-way match of a vendor bill
+CREATE TABLE IF NOT EXISTS purchase_order_matches (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    vendor_bill_id TEXT NOT NULL UNIQUE,
+    purchase_order_id TEXT NOT NULL,
+    -- matched or variance
+    status TEXT NOT NULL,
+    -- JSON array of variances
+    variances TEXT NOT NULL DEFAULT '[]',
+    -- Review case holding the variances for approval
+    review_case_id TEXT,
+    matched_at TEXT NOT NULL,
+    FOREIGN KEY (purchase_order_id) REFERENCES purchase_orders(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_purchase_order_matches_po ON purchase_order_matches(purchase_order_id);
+
+-- Explicit link from a bill to its PO; otherwise found by po_number
+ALTER TABLE vendor_bills ADD COLUMN purchase_order_id TEXT;
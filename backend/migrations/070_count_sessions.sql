@@ -0,0 +1,80 @@
+-- Migration 070: Stock Count Sessions
+-- Created: 2026-02-13
+-- Purpose: Cycle counts and full physical inventories.
+-- - A count session covers one store: every product (full), one category,
+--   or one bin. Starting it freezes the expected quantity and unit cost of
+--   each product in scope.
+-- - Counters scan or key in products from any number of devices; every
+--   entry is kept and the line holds the running total.
+-- - Finishing the count flags lines whose variance is above the session's
+--   threshold for a recount; finishing the recount sends the session for
+--   approval.
+-- - A manager approves the session, which posts each variance to the
+--   inventory ledger as a count movement.
+
+CREATE TABLE IF NOT EXISTS count_sessions (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    session_number TEXT NOT NULL,
+    store_id TEXT NOT NULL,
+    -- full, category or bin
+    scope TEXT NOT NULL DEFAULT 'full',
+    -- Category or bin counted; NULL for a full count
+    scope_value TEXT,
+    -- counting, recount, pending_approval, posted or cancelled
+    status TEXT NOT NULL DEFAULT 'counting',
+    -- Variance, in percent of the expected quantity, that calls for a recount
+    recount_threshold_percent REAL NOT NULL,
+    notes TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    submitted_at TEXT,
+    approved_by TEXT,
+    approved_at TEXT,
+    cancelled_by TEXT,
+    cancelled_at TEXT,
+    updated_at TEXT NOT NULL,
+    UNIQUE (tenant_id, session_number),
+    FOREIGN KEY (store_id) REFERENCES stores(id) ON DELETE RESTRICT
+);
+
+CREATE INDEX IF NOT EXISTS idx_count_sessions_store ON count_sessions(tenant_id, store_id, status);
+
+CREATE TABLE IF NOT EXISTS count_session_lines (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    bin_location TEXT,
+    -- Frozen when the session starts
+    expected_quantity REAL NOT NULL,
+    unit_cost REAL NOT NULL,
+    -- Running totals of the entries; NULL until the product is counted
+    counted_quantity REAL,
+    recount_required INTEGER NOT NULL DEFAULT 0,
+    recount_quantity REAL,
+    UNIQUE (session_id, product_id),
+    FOREIGN KEY (session_id) REFERENCES count_sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_count_session_lines_session ON count_session_lines(session_id);
+
+-- Every scan or keyed quantity, from whichever device
+CREATE TABLE IF NOT EXISTS count_entries (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    line_id TEXT NOT NULL,
+    -- Barcode, SKU or alternate SKU as entered
+    code TEXT NOT NULL,
+    -- Negative to take back a mis-scan
+    quantity REAL NOT NULL,
+    -- 1 for the count, 2 for the recount
+    round INTEGER NOT NULL DEFAULT 1,
+    device_id TEXT,
+    counted_by TEXT NOT NULL,
+    counted_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES count_sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (line_id) REFERENCES count_session_lines(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_count_entries_line ON count_entries(line_id, round);
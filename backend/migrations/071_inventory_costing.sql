@@ -0,0 +1,83 @@
+-- Migration 071: Inventory Costing
+-- Created: 2026-02-14
+-- Purpose: Value stock at what it cost rather than at products.cost.
+-- - Every unit that comes into stock opens a cost layer at its unit cost
+--   (the vendor price on a receipt, the sale's cost on a restocked return,
+--   the current cost otherwise).
+-- - Units leaving stock are costed by the tenant's costing method, setting
+--   inventory.costing_method: fifo draws the oldest layers first;
+--   weighted_average (the default) charges the moving average cost. Both
+--   draw the layers down oldest first so the method can be changed.
+-- - Each costed movement writes a cost entry: signed quantity and value.
+--   Stock value as of any date is the sum of the entries up to it, and the
+--   moving average cost is value over quantity.
+-- - Transfers between stores move no value; layers are per product.
+-- - Cost of goods sold is stored on each sale line (sales_line_items.cost,
+--   profit) and on the accounting snapshot line.
+-- - Stock on hand when this migration runs opens one layer per product at
+--   products.cost; valuations as of earlier dates are not meaningful.
+
+CREATE TABLE IF NOT EXISTS inventory_cost_layers (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    -- Movement that brought the units in; NULL for the opening layer
+    movement_id TEXT,
+    quantity REAL NOT NULL,
+    remaining_quantity REAL NOT NULL,
+    unit_cost REAL NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_cost_layers_open
+    ON inventory_cost_layers(tenant_id, product_id, remaining_quantity, created_at);
+
+CREATE TABLE IF NOT EXISTS inventory_cost_entries (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    -- NULL for the opening entry
+    movement_id TEXT UNIQUE,
+    -- Signed like the movement
+    quantity REAL NOT NULL,
+    unit_cost REAL NOT NULL,
+    -- quantity x unit_cost
+    value REAL NOT NULL,
+    -- fifo or weighted_average, as it was when the movement was costed
+    costing_method TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_cost_entries_product
+    ON inventory_cost_entries(tenant_id, product_id, created_at);
+
+INSERT INTO inventory_cost_layers (
+    id, tenant_id, product_id, quantity, remaining_quantity, unit_cost, created_at
+)
+SELECT
+    lower(hex(randomblob(16))),
+    tenant_id,
+    id,
+    quantity_on_hand,
+    quantity_on_hand,
+    cost,
+    strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+FROM products
+WHERE quantity_on_hand > 0;
+
+INSERT INTO inventory_cost_entries (
+    id, tenant_id, product_id, quantity, unit_cost, value, costing_method, created_at
+)
+SELECT
+    lower(hex(randomblob(16))),
+    tenant_id,
+    id,
+    quantity_on_hand,
+    cost,
+    quantity_on_hand * cost,
+    'weighted_average',
+    strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+FROM products
+WHERE quantity_on_hand <> 0;
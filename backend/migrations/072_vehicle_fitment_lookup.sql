@@ -0,0 +1,38 @@
+-- Migration 072: Vehicle Fitment Lookup
+-- Created: 2026-02-15
+-- Purpose: Put the vehicle_fitment and maintenance_schedules tables from
+-- migration 004 to use at the parts counter.
+-- - Products are looked up by year, make, model and engine; a fitment row
+--   without an engine fits every engine of the vehicle.
+-- - Fitment is bulk imported from ACES-style XML or CSV, keyed by SKU;
+--   source records where a row came from.
+-- - A work order records the odometer reading it was opened at. Maintenance
+--   schedules for the vehicle are due when they were never done on it, or
+--   their mileage or time interval has passed since they last were.
+-- - vehicle_service_history records which scheduled services a work order
+--   carried out.
+
+ALTER TABLE vehicle_fitment ADD COLUMN source TEXT NOT NULL DEFAULT 'manual';
+
+CREATE INDEX IF NOT EXISTS idx_vehicle_fitment_lookup
+    ON vehicle_fitment(tenant_id, make, model, year_start, year_end);
+
+ALTER TABLE work_orders ADD COLUMN odometer INTEGER;
+
+CREATE TABLE IF NOT EXISTS vehicle_service_history (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    vehicle_id TEXT NOT NULL,
+    -- maintenance_schedules.service_type
+    service_type TEXT NOT NULL,
+    work_order_id TEXT,
+    -- Odometer reading when the service was done
+    odometer INTEGER,
+    performed_at TEXT NOT NULL,
+    performed_by TEXT,
+    FOREIGN KEY (vehicle_id) REFERENCES vehicles(id) ON DELETE CASCADE,
+    FOREIGN KEY (work_order_id) REFERENCES work_orders(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_vehicle_service_history_vehicle
+    ON vehicle_service_history(tenant_id, vehicle_id, service_type, performed_at);
@@ -0,0 +1,109 @@
+-- Migration 073: Roles and Permissions
+-- Created: 2026-02-16
+-- Purpose: Define roles and their permissions per tenant in the database
+-- instead of in code.
+-- - permissions is the catalog of permissions the server checks.
+-- - Each tenant has the six built-in roles (admin, manager, cashier,
+--   specialist, inventory_clerk, technician), seeded with the permissions
+--   they had in code, plus any custom roles an admin creates. Built-in roles
+--   cannot be deleted or renamed; admin cannot be changed.
+-- - users.role stays the user's primary role. user_role_assignments grant
+--   further roles, at every store (store_id NULL) or at one store.
+-- - A user's permissions are resolved at sign-in and carried in the token.
+--   When a role, an assignment or the user changes, users.tokens_valid_after
+--   is moved forward so tokens issued before it are rejected and the user
+--   signs in again with the new permissions.
+
+CREATE TABLE IF NOT EXISTS permissions (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL
+);
+
+INSERT OR IGNORE INTO permissions (name, description) VALUES
+    ('access_sell', 'Use the register'),
+    ('access_warehouse', 'Use warehouse and inventory screens'),
+    ('access_admin', 'Use the admin screens'),
+    ('apply_discount', 'Apply discounts to sales'),
+    ('override_price', 'Change the price of a sale line'),
+    ('process_return', 'Process returns and refunds'),
+    ('receive_stock', 'Receive stock from vendors and transfers'),
+    ('adjust_inventory', 'Adjust stock and count inventory'),
+    ('approve_inventory_counts', 'Approve count variances'),
+    ('manage_users', 'Manage users, roles and role assignments'),
+    ('manage_settings', 'Change settings'),
+    ('view_audit_logs', 'View audit logs');
+
+CREATE TABLE IF NOT EXISTS roles (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    is_system INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (tenant_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role_id TEXT NOT NULL,
+    permission TEXT NOT NULL,
+    PRIMARY KEY (role_id, permission),
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE,
+    FOREIGN KEY (permission) REFERENCES permissions(name) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS user_role_assignments (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    -- NULL grants the role at every store
+    store_id TEXT,
+    created_at TEXT NOT NULL,
+    created_by TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_role_assignments_user
+    ON user_role_assignments(tenant_id, user_id, store_id);
+
+ALTER TABLE users ADD COLUMN tokens_valid_after TEXT;
+
+INSERT OR IGNORE INTO roles (id, tenant_id, name, description, is_system, created_at, updated_at)
+SELECT lower(hex(randomblob(16))), t.tenant_id, r.name, r.description, 1, datetime('now'), datetime('now')
+FROM (SELECT id AS tenant_id FROM tenants UNION SELECT tenant_id FROM users) t
+CROSS JOIN (
+    SELECT 'admin' AS name, 'Full access' AS description
+    UNION ALL SELECT 'manager', 'Runs a store'
+    UNION ALL SELECT 'cashier', 'Sells at the register'
+    UNION ALL SELECT 'specialist', 'Sells and receives stock'
+    UNION ALL SELECT 'inventory_clerk', 'Receives and adjusts stock'
+    UNION ALL SELECT 'technician', 'Works on service orders'
+) r;
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission)
+SELECT roles.id, defaults.permission
+FROM roles
+JOIN (
+    SELECT 'admin' AS role, name AS permission FROM permissions
+    UNION ALL SELECT 'manager', 'access_sell'
+    UNION ALL SELECT 'manager', 'access_warehouse'
+    UNION ALL SELECT 'manager', 'apply_discount'
+    UNION ALL SELECT 'manager', 'override_price'
+    UNION ALL SELECT 'manager', 'process_return'
+    UNION ALL SELECT 'manager', 'receive_stock'
+    UNION ALL SELECT 'manager', 'adjust_inventory'
+    UNION ALL SELECT 'manager', 'approve_inventory_counts'
+    UNION ALL SELECT 'manager', 'view_audit_logs'
+    UNION ALL SELECT 'cashier', 'access_sell'
+    UNION ALL SELECT 'cashier', 'process_return'
+    UNION ALL SELECT 'specialist', 'access_sell'
+    UNION ALL SELECT 'specialist', 'access_warehouse'
+    UNION ALL SELECT 'specialist', 'receive_stock'
+    UNION ALL SELECT 'inventory_clerk', 'access_warehouse'
+    UNION ALL SELECT 'inventory_clerk', 'receive_stock'
+    UNION ALL SELECT 'inventory_clerk', 'adjust_inventory'
+    UNION ALL SELECT 'technician', 'access_sell'
+) defaults ON defaults.role = roles.name
+WHERE roles.is_system = 1;
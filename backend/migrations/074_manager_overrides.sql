@@ -0,0 +1,59 @@
+-- Migration 074: Manager Overrides
+-- Created: 2026-02-17
+-- Purpose: Let a manager approve, at the register, an action the signed-in
+-- cashier is not permitted to do.
+-- - users.pin_hash and users.badge_hash identify an approver by PIN or badge
+--   scan. Both are keyed hashes, so they are looked up directly and must be
+--   unique within a tenant.
+-- - void_sale and return_without_receipt join the permission catalog and are
+--   given to the built-in admin and manager roles.
+-- - override_approvals records each approval: the action and the subject it
+--   covers (a product and price, a discount amount, a sale), the station, the
+--   cashier who asked and the manager who approved. An approval expires
+--   shortly after it is given and is used at most once.
+
+ALTER TABLE users ADD COLUMN pin_hash TEXT;
+ALTER TABLE users ADD COLUMN badge_hash TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_pin_hash
+    ON users(tenant_id, pin_hash) WHERE pin_hash IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_badge_hash
+    ON users(tenant_id, badge_hash) WHERE badge_hash IS NOT NULL;
+
+INSERT OR IGNORE INTO permissions (name, description) VALUES
+    ('void_sale', 'Void a completed sale'),
+    ('return_without_receipt', 'Process a return without the receipt');
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission)
+SELECT roles.id, granted.permission
+FROM roles
+CROSS JOIN (
+    SELECT 'void_sale' AS permission
+    UNION ALL SELECT 'return_without_receipt'
+) granted
+WHERE roles.is_system = 1 AND roles.name IN ('admin', 'manager');
+
+CREATE TABLE IF NOT EXISTS override_approvals (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    store_id TEXT,
+    station_id TEXT NOT NULL,
+    -- price_override, discount, no_receipt_return or void_sale
+    action TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    requested_by TEXT NOT NULL,
+    approved_by TEXT NOT NULL,
+    -- pin or badge
+    approval_method TEXT NOT NULL,
+    reason TEXT,
+    expires_at TEXT NOT NULL,
+    used_at TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (requested_by) REFERENCES users(id),
+    FOREIGN KEY (approved_by) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_override_approvals_tenant
+    ON override_approvals(tenant_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_override_approvals_approver
+    ON override_approvals(tenant_id, approved_by);
@@ -0,0 +1,24 @@
+-- Migration 075: Station PIN Login
+-- Created: 2026-02-18
+-- Purpose: Quick user switching on a shared register by PIN or badge scan.
+-- - The PIN and badge are the ones users.pin_hash and users.badge_hash hold
+--   (migration 074), kept apart from the password hash.
+-- - A station sign-in is bound to the station and its store. Its session
+--   records the station, how the user signed in and the idle timeout; a
+--   session idle for longer than that is ended.
+-- - station_login_attempts counts failed PINs and badges per station; too
+--   many in a row lock the station's PIN pad for a while.
+
+ALTER TABLE sessions ADD COLUMN station_id TEXT;
+ALTER TABLE sessions ADD COLUMN login_method TEXT;
+ALTER TABLE sessions ADD COLUMN idle_timeout_minutes INTEGER;
+ALTER TABLE sessions ADD COLUMN last_activity_at TEXT;
+
+CREATE TABLE IF NOT EXISTS station_login_attempts (
+    tenant_id TEXT NOT NULL,
+    station_id TEXT NOT NULL,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    first_failed_at TEXT,
+    locked_until TEXT,
+    PRIMARY KEY (tenant_id, station_id)
+);
@@ -0,0 +1,53 @@
+-- Migration 076: Two-Factor Authentication
+-- Created: 2026-02-19
+-- Purpose: TOTP second factor for sign-in with a username and password.
+-- - user_two_factor holds a user's TOTP secret, encrypted like integration
+--   credentials. enabled_at is set once the user has entered a code from
+--   their authenticator app; last_used_step stops a code being used twice.
+-- - user_recovery_codes holds hashes of single-use recovery codes.
+-- - roles.requires_two_factor makes a second factor mandatory for the
+--   role's holders; they enroll during their next sign-in.
+-- - login_challenges is the step between the password and the code: the
+--   token is only issued once the challenge is completed.
+
+ALTER TABLE roles ADD COLUMN requires_two_factor INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS user_two_factor (
+    user_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    secret_encrypted TEXT NOT NULL,
+    enabled_at TEXT,
+    last_used_step INTEGER,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS user_recovery_codes (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    used_at TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_recovery_codes_user
+    ON user_recovery_codes(tenant_id, user_id);
+
+CREATE TABLE IF NOT EXISTS login_challenges (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    -- verify, or enroll for a user who must set up a second factor first
+    purpose TEXT NOT NULL,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TEXT NOT NULL,
+    completed_at TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_challenges_user
+    ON login_challenges(tenant_id, user_id, created_at);
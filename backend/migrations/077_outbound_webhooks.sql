@@ -0,0 +1,78 @@
+-- Migration 077: Outbound Webhooks
+-- Created: 2026-02-20
+-- Purpose: Let other systems subscribe to EasySale events over HTTP.
+-- - webhook_subscriptions is a tenant's endpoint and the event types it
+--   wants. Its signing secret is encrypted like integration credentials.
+--   (webhook_configs holds the secrets of inbound platform webhooks and is
+--   keyed by platform, so it is not reused here.)
+-- - outbound_webhook_events records each event once, written in the same
+--   database transaction as the change that raised it, and only when an
+--   active subscription wants it. (webhook_events is the log of inbound
+--   payment provider events.)
+-- - webhook_deliveries is the queue: one row per event and subscription,
+--   retried with backoff until delivered or given up as failed.
+-- - webhook_delivery_attempts logs every request made for a delivery,
+--   including manual redeliveries.
+
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    -- JSON array of event types, e.g. ["sale.completed", "stock.low"]
+    events TEXT NOT NULL,
+    secret_encrypted TEXT NOT NULL,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    created_by TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_tenant
+    ON webhook_subscriptions(tenant_id, is_active);
+
+CREATE TABLE IF NOT EXISTS outbound_webhook_events (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    subscription_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TEXT,
+    last_attempt_at TEXT,
+    delivered_at TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (subscription_id) REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+    FOREIGN KEY (event_id) REFERENCES outbound_webhook_events(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON webhook_deliveries(status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription
+    ON webhook_deliveries(subscription_id, created_at);
+
+CREATE TABLE IF NOT EXISTS webhook_delivery_attempts (
+    id TEXT PRIMARY KEY,
+    delivery_id TEXT NOT NULL,
+    attempted_at TEXT NOT NULL,
+    -- NULL when no response was received
+    response_status INTEGER,
+    -- First few KB of the response body
+    response_body TEXT,
+    error TEXT,
+    duration_ms INTEGER NOT NULL,
+    FOREIGN KEY (delivery_id) REFERENCES webhook_deliveries(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_delivery
+    ON webhook_delivery_attempts(delivery_id, attempted_at);
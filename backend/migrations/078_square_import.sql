@@ -0,0 +1,62 @@
+-- Migration 078: Square Import
+-- Created: 2026-02-21
+-- Purpose: Bring a shop's catalog, customers, stock and sales history over
+-- from Square.
+-- - id_mappings links a record in another system to the EasySale record made
+--   from it. IdMapper has always written here but no migration created the
+--   table; re-running an import updates the mapped records instead of
+--   creating them again.
+-- - product_modifiers holds the modifier options (toppings, sizes, add-ons)
+--   a product can be sold with. An import replaces a product's options.
+-- - square_import_runs records each import: what was asked for, how far it
+--   got, and the counts and errors per kind of record.
+
+CREATE TABLE IF NOT EXISTS id_mappings (
+    tenant_id TEXT NOT NULL,
+    source_system TEXT NOT NULL,
+    source_entity TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    target_system TEXT NOT NULL,
+    target_entity TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (tenant_id, source_system, source_entity, source_id, target_system)
+);
+
+CREATE INDEX IF NOT EXISTS idx_id_mappings_target
+    ON id_mappings(tenant_id, target_system, target_entity, target_id);
+
+CREATE TABLE IF NOT EXISTS product_modifiers (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    -- Name of the option group, e.g. "Milk"
+    group_name TEXT NOT NULL,
+    name TEXT NOT NULL,
+    -- Added to the product's price when chosen
+    price REAL NOT NULL DEFAULT 0,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_modifiers_product
+    ON product_modifiers(product_id, group_name, sort_order);
+
+CREATE TABLE IF NOT EXISTS square_import_runs (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'completed', 'failed')),
+    -- JSON of the options the import was started with
+    options TEXT NOT NULL,
+    -- JSON counts per kind of record, set when the run ends
+    summary TEXT,
+    error TEXT,
+    started_by TEXT,
+    started_at TEXT NOT NULL,
+    completed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_square_import_runs_tenant
+    ON square_import_runs(tenant_id, started_at);
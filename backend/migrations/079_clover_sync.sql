@@ -0,0 +1,79 @@
+-- Migration 079: Clover Sync
+-- Created: 2026-02-22
+-- Purpose: Let Clover (and the other connectors added since 025) keep
+-- credentials, sync operations and conflicts like WooCommerce does.
+-- - integration_credentials.platform was limited to woocommerce, quickbooks
+--   and supabase, so connecting Square, Clover or Stripe failed the CHECK.
+--   The table is rebuilt without it; CredentialService decides which
+--   platforms exist.
+-- - Dropping the old table cascades to the tables that reference it, so
+--   their rows are copied aside first and put back afterwards.
+
+CREATE TABLE integration_credentials_new (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    credentials_encrypted TEXT NOT NULL,
+    oauth_tokens_encrypted TEXT,
+    realm_id TEXT,
+    store_url TEXT,
+    project_url TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    last_verified_at TIMESTAMP,
+    verification_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_by TEXT,
+    updated_by TEXT,
+    sync_direction TEXT NOT NULL DEFAULT 'one_way' CHECK (sync_direction IN ('one_way', 'two_way')),
+    sync_config TEXT,
+    UNIQUE(tenant_id, platform)
+);
+
+INSERT INTO integration_credentials_new (
+    id, tenant_id, platform, credentials_encrypted, oauth_tokens_encrypted,
+    realm_id, store_url, project_url, is_active, last_verified_at, verification_error,
+    created_at, updated_at, created_by, updated_by, sync_direction, sync_config
+)
+SELECT
+    id, tenant_id, platform, credentials_encrypted, oauth_tokens_encrypted,
+    realm_id, store_url, project_url, is_active, last_verified_at, verification_error,
+    created_at, updated_at, created_by, updated_by, sync_direction, sync_config
+FROM integration_credentials;
+
+CREATE TABLE integration_status_079 AS SELECT * FROM integration_status;
+CREATE TABLE integration_sync_operations_079 AS SELECT * FROM integration_sync_operations;
+CREATE TABLE integration_sync_conflicts_079 AS SELECT * FROM integration_sync_conflicts;
+CREATE TABLE sync_schedules_079 AS SELECT * FROM sync_schedules;
+
+DROP TABLE integration_credentials;
+ALTER TABLE integration_credentials_new RENAME TO integration_credentials;
+
+DELETE FROM integration_status;
+DELETE FROM integration_sync_operations;
+DELETE FROM integration_sync_conflicts;
+DELETE FROM sync_schedules;
+INSERT INTO integration_status SELECT * FROM integration_status_079;
+INSERT INTO integration_sync_operations SELECT * FROM integration_sync_operations_079;
+INSERT INTO integration_sync_conflicts SELECT * FROM integration_sync_conflicts_079;
+INSERT INTO sync_schedules SELECT * FROM sync_schedules_079;
+
+DROP TABLE integration_status_079;
+DROP TABLE integration_sync_operations_079;
+DROP TABLE integration_sync_conflicts_079;
+DROP TABLE sync_schedules_079;
+
+CREATE INDEX IF NOT EXISTS idx_integration_credentials_tenant ON integration_credentials(tenant_id);
+CREATE INDEX IF NOT EXISTS idx_integration_credentials_platform ON integration_credentials(platform);
+CREATE INDEX IF NOT EXISTS idx_integration_credentials_active ON integration_credentials(is_active);
+
+CREATE TRIGGER IF NOT EXISTS update_integration_credentials_timestamp
+AFTER UPDATE ON integration_credentials
+FOR EACH ROW
+BEGIN
+    UPDATE integration_credentials SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- Looking up what a platform record was last synced as
+CREATE INDEX IF NOT EXISTS idx_integration_ops_platform_entity
+    ON integration_sync_operations(credential_id, entity_type, platform_entity_id);
@@ -0,0 +1,66 @@
+-- Migration 080: WooCommerce Stock Push
+-- Created: 2026-02-23
+-- Purpose: Send POS stock changes to WooCommerce as they happen instead of
+-- waiting for the next scheduled sync.
+-- - Every stock movement of a product with a SKU, for a tenant with an
+--   active WooCommerce connection, queues a stock push in sync_queue
+--   (entity_type 'inventory', operation 'push'). Transfers leave the total
+--   unchanged and queue nothing.
+-- - Pushes are coalesced: a product has at most one pending push, and
+--   further movements only bump its change count in the payload.
+-- - Pushes are debounced: each movement moves not_before 5 seconds out, but
+--   never past 60 seconds after the push was queued, so a busy SKU still
+--   goes out.
+-- - not_before is also when a failed item may be retried.
+
+ALTER TABLE sync_queue ADD COLUMN not_before TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_queue_pending_stock_push
+    ON sync_queue(tenant_id, entity_id)
+    WHERE entity_type = 'inventory' AND operation = 'push' AND sync_status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_sync_queue_not_before
+    ON sync_queue(entity_type, operation, sync_status, not_before);
+
+CREATE TRIGGER IF NOT EXISTS inventory_movements_queue_stock_push
+AFTER INSERT ON inventory_movements
+FOR EACH ROW
+WHEN NEW.movement_type <> 'transfer'
+BEGIN
+    INSERT INTO sync_queue (
+        id, tenant_id, entity_type, entity_id, operation, payload, sync_status,
+        retry_count, store_id, priority, not_before, created_at, updated_at
+    )
+    SELECT
+        lower(hex(randomblob(16))),
+        p.tenant_id,
+        'inventory',
+        p.id,
+        'push',
+        json_object('platform', 'woocommerce', 'sku', p.sku, 'changes', 1),
+        'pending',
+        0,
+        COALESCE(NEW.store_id, p.store_id, ''),
+        2,
+        datetime('now', '+5 seconds'),
+        datetime('now'),
+        datetime('now')
+    FROM products p
+    WHERE p.id = NEW.product_id
+      AND p.tenant_id = NEW.tenant_id
+      AND p.sku IS NOT NULL AND p.sku <> ''
+      AND EXISTS (
+          SELECT 1 FROM integration_credentials c
+          WHERE c.tenant_id = p.tenant_id AND c.platform = 'woocommerce' AND c.is_active = 1
+      )
+    ON CONFLICT (tenant_id, entity_id)
+        WHERE entity_type = 'inventory' AND operation = 'push' AND sync_status = 'pending'
+    DO UPDATE SET
+        payload = json_set(
+            sync_queue.payload,
+            '$.sku', json_extract(excluded.payload, '$.sku'),
+            '$.changes', json_extract(sync_queue.payload, '$.changes') + 1
+        ),
+        not_before = MIN(excluded.not_before, datetime(sync_queue.created_at, '+60 seconds')),
+        updated_at = excluded.updated_at;
+END;
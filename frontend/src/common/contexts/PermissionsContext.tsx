@@ -11,6 +11,7 @@ export type Permission =
   | 'process_return'
   | 'receive_stock'
   | 'adjust_inventory'
+  | 'approve_inventory_counts'
   | 'manage_users'
   | 'manage_settings'
   | 'view_audit_logs'
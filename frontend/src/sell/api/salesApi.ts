@@ -17,10 +17,36 @@ export interface SaleLineItem {
   discount_amount?: number;
 }
 
+export type TenderMethod = 'cash' | 'card' | 'gift_card' | 'store_credit' | 'other';
+
+export interface SaleTender {
+  method: TenderMethod;
+  /** Omit to pay the remaining balance */
+  amount?: number;
+  /** Gift card number, card authorization code, etc. */
+  reference?: string;
+}
+
+export interface SaleTenderResult {
+  method: string;
+  tendered_amount: number;
+  amount: number;
+  change_amount: number;
+  reference?: string;
+}
+
+export interface SaleTax {
+  authority: string;
+  label: string;
+  tax_amount: number;
+}
+
 export interface CreateSaleRequest {
   customer_id?: string;
   items: SaleLineItem[];
-  payment_method: 'cash' | 'card' | 'other';
+  /** Single tender for the full amount; ignored when `tenders` is given */
+  payment_method?: 'cash' | 'card' | 'other';
+  tenders?: SaleTender[];
   discount_amount?: number;
   notes?: string;
 }
@@ -37,6 +63,13 @@ export interface Sale {
   payment_method: string;
   status: string;
   created_at: string;
+  change_due?: number;
+  tenders?: SaleTenderResult[];
+  /** Tax per authority (e.g. GST, PST) */
+  taxes?: SaleTax[];
+  tax_exempt_certificate?: string;
+  /** When true, subtotal is net of the tax included in the prices */
+  prices_include_tax?: boolean;
 }
 
 export interface SaleListResponse {
@@ -67,6 +67,27 @@ export const storesTaxSettings: SettingDefinition[] = [
     allowedScopes: ['store', 'default'],
     schemaVersion: 1,
   },
+  {
+    key: 'store.prices_include_tax',
+    label: 'Prices Include Tax',
+    description: 'Shelf prices include tax (VAT); tax is calculated back from the price',
+    type: 'policy',
+    group: 'stores-tax',
+    defaultValue: false,
+    allowedScopes: ['store', 'default'],
+    schemaVersion: 1,
+  },
+  {
+    key: 'store.tax_rounding',
+    label: 'Tax Rounding',
+    description: 'Round tax on each line ("per_line") or once per invoice ("per_invoice")',
+    type: 'policy',
+    group: 'stores-tax',
+    defaultValue: 'per_invoice',
+    allowedScopes: ['store', 'default'],
+    validator: (value: string) => value === 'per_line' || value === 'per_invoice',
+    schemaVersion: 1,
+  },
   {
     key: 'store.theme_lock_mode',
     label: 'Lock Theme Mode',